@@ -35,8 +35,38 @@ void test_object_open(void) {
     printf("  PASS\n\n");
 }
 
+void test_object_functions(void) {
+    printf("[TEST] iterate functions and files:\n");
+
+    SymbolicArchive *archive =
+        symbolic_archive_open("../symbolic-testutils/fixtures/linux/crash.debug");
+    assert(archive != 0);
+
+    SymbolicObject *object = symbolic_archive_get_object(archive, 0);
+    assert(object != 0);
+
+    SymbolicFunctions functions = symbolic_object_get_functions(object);
+    printf("  functions: %d\n", (int)functions.len);
+    assert(functions.len > 0);
+    assert(functions.items[0].inline_depth == 0);
+    assert(functions.items[0].name.len > 0);
+
+    SymbolicFileEntries files = symbolic_object_get_files(object);
+    printf("  files: %d\n", (int)files.len);
+    assert(files.len > 0);
+
+    symbolic_functions_free(&functions);
+    symbolic_file_entries_free(&files);
+    symbolic_object_free(object);
+    symbolic_archive_free(archive);
+    symbolic_err_clear();
+
+    printf("  PASS\n\n");
+}
+
 int main() {
     test_object_open();
+    test_object_functions();
 
     return 0;
 }
@@ -48,6 +48,7 @@ enum SymbolicErrorCode {
   SYMBOLIC_ERROR_CODE_CFI_ERROR_WRITE_ERROR = 3005,
   SYMBOLIC_ERROR_CODE_CFI_ERROR_BAD_FILE_MAGIC = 3006,
   SYMBOLIC_ERROR_CODE_CFI_ERROR_INVALID_ADDRESS = 3007,
+  SYMBOLIC_ERROR_CODE_CFI_ERROR_BAD_FORMAT = 3008,
   SYMBOLIC_ERROR_CODE_PROCESS_MINIDUMP_ERROR_MINIDUMP_NOT_FOUND = 4001,
   SYMBOLIC_ERROR_CODE_PROCESS_MINIDUMP_ERROR_NO_MINIDUMP_HEADER = 4002,
   SYMBOLIC_ERROR_CODE_PROCESS_MINIDUMP_ERROR_NO_THREAD_LIST = 4003,
@@ -202,6 +203,98 @@ typedef struct SymbolicObjectFeatures {
   bool sources;
 } SymbolicObjectFeatures;
 
+/**
+ * A line record of a function.
+ */
+typedef struct SymbolicLineRecord {
+  /**
+   * The instruction address relative to the image base.
+   */
+  uint64_t address;
+  /**
+   * The code size covered by this line record, or 0 if unknown.
+   */
+  uint64_t size;
+  /**
+   * The absolute line number starting at 1, or 0 if unknown.
+   */
+  uint64_t line;
+  /**
+   * The absolute path of the source file.
+   */
+  struct SymbolicStr abs_path;
+} SymbolicLineRecord;
+
+/**
+ * A function from the debug information of an object.
+ */
+typedef struct SymbolicFunction {
+  /**
+   * The relative instruction address of the start of the function.
+   */
+  uint64_t address;
+  /**
+   * The code size covered by the function body, including inlined functions.
+   */
+  uint64_t size;
+  /**
+   * The name of the function, which may be mangled.
+   */
+  struct SymbolicStr name;
+  /**
+   * The source language of the function.
+   */
+  struct SymbolicStr lang;
+  /**
+   * The compilation directory of the function.
+   */
+  struct SymbolicStr comp_dir;
+  /**
+   * The number of functions this function is inlined into, or 0 for top-level functions.
+   */
+  uint32_t inline_depth;
+  /**
+   * The line records covered by this function, including inlined children.
+   */
+  struct SymbolicLineRecord *lines;
+  /**
+   * The number of line records.
+   */
+  uintptr_t lines_len;
+} SymbolicFunction;
+
+/**
+ * A list of functions.
+ *
+ * Inlined functions directly follow the function they are inlined into.
+ */
+typedef struct SymbolicFunctions {
+  struct SymbolicFunction *items;
+  uintptr_t len;
+} SymbolicFunctions;
+
+/**
+ * A source file referenced by the debug information of an object.
+ */
+typedef struct SymbolicFileEntry {
+  /**
+   * The compilation directory of the file.
+   */
+  struct SymbolicStr comp_dir;
+  /**
+   * The absolute path of the file, including the compilation directory.
+   */
+  struct SymbolicStr abs_path;
+} SymbolicFileEntry;
+
+/**
+ * A list of source files.
+ */
+typedef struct SymbolicFileEntries {
+  struct SymbolicFileEntry *items;
+  uintptr_t len;
+} SymbolicFileEntries;
+
 /**
  * OS and CPU information in a minidump.
  */
@@ -327,6 +420,48 @@ typedef struct SymbolicLookupResult {
   uintptr_t len;
 } SymbolicLookupResult;
 
+/**
+ * Represents a single source location after lookup.
+ *
+ * Unlike [`SymbolicLineInfo`], this contains only the information stored in SymCaches of the
+ * latest format.
+ */
+typedef struct SymbolicSourceLocation {
+  /**
+   * The entry address of the function, or `u64::MAX` if unknown.
+   */
+  uint64_t function_address;
+  /**
+   * The line number within the file, or 0 if unknown.
+   */
+  uint32_t line;
+  /**
+   * The source language of the function.
+   */
+  struct SymbolicStr lang;
+  /**
+   * The mangled name of the function, or `?` if unknown.
+   */
+  struct SymbolicStr symbol;
+  /**
+   * The absolute path of the source file, including the compilation directory.
+   */
+  struct SymbolicStr full_path;
+  /**
+   * The compilation directory of the source file.
+   */
+  struct SymbolicStr comp_dir;
+} SymbolicSourceLocation;
+
+/**
+ * Represents the source locations of an address, ordered from the innermost inlined frame to
+ * the outermost function.
+ */
+typedef struct SymbolicSourceLocations {
+  struct SymbolicSourceLocation *items;
+  uintptr_t len;
+} SymbolicSourceLocations;
+
 /**
  * Represents an instruction info.
  */
@@ -483,6 +618,31 @@ struct SymbolicObjectFeatures symbolic_object_get_features(const struct Symbolic
  */
 void symbolic_object_free(struct SymbolicObject *object);
 
+/**
+ * Returns all functions from the debug information of the object.
+ *
+ * Inlined functions are listed directly after the function they are inlined into, with an
+ * increased `inline_depth`. The result must be freed with `symbolic_functions_free`.
+ */
+struct SymbolicFunctions symbolic_object_get_functions(const struct SymbolicObject *object);
+
+/**
+ * Frees the functions returned by `symbolic_object_get_functions`.
+ */
+void symbolic_functions_free(struct SymbolicFunctions *functions);
+
+/**
+ * Returns all source files referenced by the debug information of the object.
+ *
+ * The result must be freed with `symbolic_file_entries_free`.
+ */
+struct SymbolicFileEntries symbolic_object_get_files(const struct SymbolicObject *object);
+
+/**
+ * Frees the files returned by `symbolic_object_get_files`.
+ */
+void symbolic_file_entries_free(struct SymbolicFileEntries *files);
+
 /**
  * Loads a BCSymbolmap from a given path
  */
@@ -591,12 +751,16 @@ struct SymbolicCfiCache *symbolic_cficache_open(const char *path);
 uint32_t symbolic_cficache_get_version(const struct SymbolicCfiCache *cache);
 
 /**
- * Returns a pointer to the raw buffer of the CFI cache.
+ * Returns a pointer to the contents of the CFI cache in Breakpad ASCII format.
+ *
+ * Binary caches are converted on the first call.
  */
 const uint8_t *symbolic_cficache_get_bytes(const struct SymbolicCfiCache *cache);
 
 /**
- * Returns the size of the raw buffer of the CFI cache.
+ * Returns the size of the contents of the CFI cache in Breakpad ASCII format.
+ *
+ * Binary caches are converted on the first call.
  */
 uintptr_t symbolic_cficache_get_size(const struct SymbolicCfiCache *cache);
 
@@ -808,6 +972,25 @@ struct SymbolicLookupResult symbolic_symcache_lookup(const struct SymbolicSymCac
  */
 void symbolic_lookup_result_free(struct SymbolicLookupResult *lookup_result);
 
+/**
+ * Returns true if the symcache uses the latest file format.
+ */
+bool symbolic_symcache_is_latest(const struct SymbolicSymCache *symcache);
+
+/**
+ * Looks up the source locations of an address, including all inlined frames.
+ *
+ * The returned locations are ordered from the innermost inlined frame to the outermost
+ * function. The result must be freed with `symbolic_source_locations_free`.
+ */
+struct SymbolicSourceLocations symbolic_symcache_lookup_source_locations(const struct SymbolicSymCache *symcache,
+                                                                         uint64_t addr);
+
+/**
+ * Frees the source locations returned by `symbolic_symcache_lookup_source_locations`.
+ */
+void symbolic_source_locations_free(struct SymbolicSourceLocations *source_locations);
+
 /**
  * Return the best instruction for an isntruction info.
  */
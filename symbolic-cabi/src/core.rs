@@ -176,6 +176,7 @@ pub enum SymbolicErrorCode {
     CfiErrorWriteError = 3005,
     CfiErrorBadFileMagic = 3006,
     CfiErrorInvalidAddress = 3007,
+    CfiErrorBadFormat = 3008,
 
     // symbolic::minidump::processor
     ProcessMinidumpErrorMinidumpNotFound = 4001,
@@ -268,6 +269,7 @@ impl SymbolicErrorCode {
                     CfiErrorKind::InvalidAddress => SymbolicErrorCode::CfiErrorInvalidAddress,
                     CfiErrorKind::WriteFailed => SymbolicErrorCode::CfiErrorWriteError,
                     CfiErrorKind::BadFileMagic => SymbolicErrorCode::CfiErrorBadFileMagic,
+                    CfiErrorKind::BadFormat => SymbolicErrorCode::CfiErrorBadFormat,
                     _ => SymbolicErrorCode::CfiErrorUnknown,
                 };
             }
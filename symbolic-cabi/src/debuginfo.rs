@@ -1,12 +1,13 @@
 use std::ffi::CStr;
+use std::mem;
 use std::os::raw::c_char;
 use std::ptr;
 use std::slice;
 use std::str::FromStr;
 
-use symbolic::common::{ByteView, CodeId, DebugId, SelfCell};
+use symbolic::common::{clean_path, join_path, ByteView, CodeId, DebugId, SelfCell};
 use symbolic::debuginfo::macho::{BcSymbolMap, UuidMapping};
-use symbolic::debuginfo::{Archive, Object};
+use symbolic::debuginfo::{Archive, Function, Object, ObjectFeatures};
 
 use crate::core::SymbolicStr;
 use crate::utils::ForeignObject;
@@ -34,6 +35,106 @@ pub struct SymbolicObjectFeatures {
     sources: bool,
 }
 
+/// A line record of a function.
+#[repr(C)]
+pub struct SymbolicLineRecord {
+    /// The instruction address relative to the image base.
+    pub address: u64,
+    /// The code size covered by this line record, or 0 if unknown.
+    pub size: u64,
+    /// The absolute line number starting at 1, or 0 if unknown.
+    pub line: u64,
+    /// The absolute path of the source file.
+    pub abs_path: SymbolicStr,
+}
+
+/// A function from the debug information of an object.
+#[repr(C)]
+pub struct SymbolicFunction {
+    /// The relative instruction address of the start of the function.
+    pub address: u64,
+    /// The code size covered by the function body, including inlined functions.
+    pub size: u64,
+    /// The name of the function, which may be mangled.
+    pub name: SymbolicStr,
+    /// The source language of the function.
+    pub lang: SymbolicStr,
+    /// The compilation directory of the function.
+    pub comp_dir: SymbolicStr,
+    /// The number of functions this function is inlined into, or 0 for top-level functions.
+    pub inline_depth: u32,
+    /// The line records covered by this function, including inlined children.
+    pub lines: *mut SymbolicLineRecord,
+    /// The number of line records.
+    pub lines_len: usize,
+}
+
+/// A list of functions.
+///
+/// Inlined functions directly follow the function they are inlined into.
+#[repr(C)]
+pub struct SymbolicFunctions {
+    pub items: *mut SymbolicFunction,
+    pub len: usize,
+}
+
+/// A source file referenced by the debug information of an object.
+#[repr(C)]
+pub struct SymbolicFileEntry {
+    /// The compilation directory of the file.
+    pub comp_dir: SymbolicStr,
+    /// The absolute path of the file, including the compilation directory.
+    pub abs_path: SymbolicStr,
+}
+
+/// A list of source files.
+#[repr(C)]
+pub struct SymbolicFileEntries {
+    pub items: *mut SymbolicFileEntry,
+    pub len: usize,
+}
+
+/// Converts a vector into a pointer and length, which must be freed with `Vec::from_raw_parts`.
+fn into_raw_parts<T>(mut vec: Vec<T>) -> (*mut T, usize) {
+    vec.shrink_to_fit();
+    let rv = (vec.as_mut_ptr(), vec.len());
+    mem::forget(vec);
+    rv
+}
+
+/// Appends the function and all of its inlinees to `items` in pre-order.
+fn push_function(items: &mut Vec<SymbolicFunction>, function: Function<'_>, inline_depth: u32) {
+    let comp_dir = String::from_utf8_lossy(&function.compilation_dir);
+    let lines = function
+        .lines
+        .iter()
+        .map(|line| SymbolicLineRecord {
+            address: line.address,
+            size: line.size.unwrap_or(0),
+            line: line.line,
+            abs_path: SymbolicStr::from_string(
+                clean_path(&join_path(&comp_dir, &line.file.path_str())).into_owned(),
+            ),
+        })
+        .collect();
+
+    let (lines, lines_len) = into_raw_parts(lines);
+    items.push(SymbolicFunction {
+        address: function.address,
+        size: function.size,
+        name: SymbolicStr::from_string(function.name.as_str().to_owned()),
+        lang: SymbolicStr::new(function.name.language().name()),
+        comp_dir: SymbolicStr::from_string(comp_dir.into_owned()),
+        inline_depth,
+        lines,
+        lines_len,
+    });
+
+    for inlinee in function.inlinees {
+        push_function(items, inlinee, inline_depth + 1);
+    }
+}
+
 /// A `BCSymbolMap`.
 pub struct SymbolicBcSymbolMap;
 
@@ -141,12 +242,12 @@ ffi_fn! {
     unsafe fn symbolic_object_get_features(
         object: *const SymbolicObject,
     ) -> Result<SymbolicObjectFeatures> {
-        let object = SymbolicObject::as_rust(object).get();
+        let features = SymbolicObject::as_rust(object).get().features();
         Ok(SymbolicObjectFeatures {
-            symtab: object.has_symbols(),
-            debug: object.has_debug_info(),
-            unwind: object.has_unwind_info(),
-            sources: object.has_sources(),
+            symtab: features.contains(ObjectFeatures::SYMBOLS),
+            debug: features.contains(ObjectFeatures::DEBUG_INFO),
+            unwind: features.contains(ObjectFeatures::UNWIND_INFO),
+            sources: features.contains(ObjectFeatures::SOURCES),
         })
     }
 }
@@ -158,6 +259,72 @@ ffi_fn! {
     }
 }
 
+ffi_fn! {
+    /// Returns all functions from the debug information of the object.
+    ///
+    /// Inlined functions are listed directly after the function they are inlined into, with an
+    /// increased `inline_depth`. The result must be freed with `symbolic_functions_free`.
+    unsafe fn symbolic_object_get_functions(
+        object: *const SymbolicObject,
+    ) -> Result<SymbolicFunctions> {
+        let session = SymbolicObject::as_rust(object).get().debug_session()?;
+
+        let mut items = Vec::new();
+        for function in session.functions() {
+            push_function(&mut items, function?, 0);
+        }
+
+        let (items, len) = into_raw_parts(items);
+        Ok(SymbolicFunctions { items, len })
+    }
+}
+
+ffi_fn! {
+    /// Frees the functions returned by `symbolic_object_get_functions`.
+    unsafe fn symbolic_functions_free(functions: *mut SymbolicFunctions) {
+        if !functions.is_null() {
+            let functions = &*functions;
+            let items = Vec::from_raw_parts(functions.items, functions.len, functions.len);
+            for function in items {
+                Vec::from_raw_parts(function.lines, function.lines_len, function.lines_len);
+            }
+        }
+    }
+}
+
+ffi_fn! {
+    /// Returns all source files referenced by the debug information of the object.
+    ///
+    /// The result must be freed with `symbolic_file_entries_free`.
+    unsafe fn symbolic_object_get_files(
+        object: *const SymbolicObject,
+    ) -> Result<SymbolicFileEntries> {
+        let session = SymbolicObject::as_rust(object).get().debug_session()?;
+
+        let mut items = Vec::new();
+        for file in session.files() {
+            let file = file?;
+            items.push(SymbolicFileEntry {
+                comp_dir: SymbolicStr::from_string(file.compilation_dir_str().into_owned()),
+                abs_path: SymbolicStr::from_string(file.abs_path_str()),
+            });
+        }
+
+        let (items, len) = into_raw_parts(items);
+        Ok(SymbolicFileEntries { items, len })
+    }
+}
+
+ffi_fn! {
+    /// Frees the files returned by `symbolic_object_get_files`.
+    unsafe fn symbolic_file_entries_free(files: *mut SymbolicFileEntries) {
+        if !files.is_null() {
+            let files = &*files;
+            Vec::from_raw_parts(files.items, files.len, files.len);
+        }
+    }
+}
+
 ffi_fn! {
     /// Loads a BCSymbolmap from a given path
     unsafe fn symbolic_bcsymbolmap_open(path: *const c_char,) -> Result<*mut SymbolicBcSymbolMap> {
@@ -1,3 +1,4 @@
+use std::cell::RefCell;
 use std::ffi::CStr;
 use std::mem;
 use std::os::raw::c_char;
@@ -18,7 +19,38 @@ use crate::utils::ForeignObject;
 pub struct SymbolicCfiCache;
 
 impl ForeignObject for SymbolicCfiCache {
-    type RustObject = CfiCache<'static>;
+    type RustObject = CfiCacheData;
+}
+
+/// A CFI cache along with its contents in Breakpad ASCII format.
+///
+/// Binary caches are converted to ASCII when their bytes are first requested, since consumers of
+/// the C interface expect Breakpad `STACK` records.
+pub struct CfiCacheData {
+    cache: CfiCache<'static>,
+    converted: RefCell<Option<Vec<u8>>>,
+}
+
+impl CfiCacheData {
+    fn new(cache: CfiCache<'static>) -> Self {
+        CfiCacheData {
+            cache,
+            converted: RefCell::new(None),
+        }
+    }
+
+    /// Returns a pointer to the contents of the cache in Breakpad ASCII format and their size.
+    fn breakpad_cfi(&self) -> (*const u8, usize) {
+        // Text-based caches already contain Breakpad ASCII.
+        if !self.cache.is_latest() {
+            let bytes = self.cache.as_slice();
+            return (bytes.as_ptr(), bytes.len());
+        }
+
+        let mut converted = self.converted.borrow_mut();
+        let bytes = converted.get_or_insert_with(|| self.cache.to_breakpad_cfi().into_owned());
+        (bytes.as_ptr(), bytes.len())
+    }
 }
 
 /// A map of stack frame infos for images.
@@ -277,7 +309,7 @@ ffi_fn! {
     ) -> Result<()> {
         let map = SymbolicFrameInfoMap::as_rust_mut(frame_info_map);
         let id = CodeModuleId::from_str((*debug_id).as_str())?;
-        let cache = *SymbolicCfiCache::into_rust(cfi_cache);
+        let cache = SymbolicCfiCache::into_rust(cfi_cache).cache;
 
         map.insert(id, cache);
         Ok(())
@@ -349,7 +381,7 @@ ffi_fn! {
     ) -> Result<*mut SymbolicCfiCache> {
         let object = SymbolicObject::as_rust(object).get();
         let cache = CfiCache::from_object(object)?;
-        Ok(SymbolicCfiCache::from_rust(cache))
+        Ok(SymbolicCfiCache::from_rust(CfiCacheData::new(cache)))
     }
 }
 
@@ -358,28 +390,32 @@ ffi_fn! {
     unsafe fn symbolic_cficache_open(path: *const c_char) -> Result<*mut SymbolicCfiCache> {
         let byteview = ByteView::open(CStr::from_ptr(path).to_str()?)?;
         let cache = CfiCache::from_bytes(byteview)?;
-        Ok(SymbolicCfiCache::from_rust(cache))
+        Ok(SymbolicCfiCache::from_rust(CfiCacheData::new(cache)))
     }
 }
 
 ffi_fn! {
     /// Returns the file format version of the CFI cache.
     unsafe fn symbolic_cficache_get_version(cache: *const SymbolicCfiCache) -> Result<u32> {
-        Ok(SymbolicCfiCache::as_rust(cache).version())
+        Ok(SymbolicCfiCache::as_rust(cache).cache.version())
     }
 }
 
 ffi_fn! {
-    /// Returns a pointer to the raw buffer of the CFI cache.
+    /// Returns a pointer to the contents of the CFI cache in Breakpad ASCII format.
+    ///
+    /// Binary caches are converted on the first call.
     unsafe fn symbolic_cficache_get_bytes(cache: *const SymbolicCfiCache) -> Result<*const u8> {
-        Ok(SymbolicCfiCache::as_rust(cache).as_slice().as_ptr())
+        Ok(SymbolicCfiCache::as_rust(cache).breakpad_cfi().0)
     }
 }
 
 ffi_fn! {
-    /// Returns the size of the raw buffer of the CFI cache.
+    /// Returns the size of the contents of the CFI cache in Breakpad ASCII format.
+    ///
+    /// Binary caches are converted on the first call.
     unsafe fn symbolic_cficache_get_size(cache: *const SymbolicCfiCache) -> Result<usize> {
-        Ok(SymbolicCfiCache::as_rust(cache).as_slice().len())
+        Ok(SymbolicCfiCache::as_rust(cache).breakpad_cfi().1)
     }
 }
 
@@ -39,6 +39,34 @@ pub struct SymbolicLookupResult {
     pub len: usize,
 }
 
+/// Represents a single source location after lookup.
+///
+/// Unlike [`SymbolicLineInfo`], this contains only the information stored in SymCaches of the
+/// latest format.
+#[repr(C)]
+pub struct SymbolicSourceLocation {
+    /// The entry address of the function, or `u64::MAX` if unknown.
+    pub function_address: u64,
+    /// The line number within the file, or 0 if unknown.
+    pub line: u32,
+    /// The source language of the function.
+    pub lang: SymbolicStr,
+    /// The mangled name of the function, or `?` if unknown.
+    pub symbol: SymbolicStr,
+    /// The absolute path of the source file, including the compilation directory.
+    pub full_path: SymbolicStr,
+    /// The compilation directory of the source file.
+    pub comp_dir: SymbolicStr,
+}
+
+/// Represents the source locations of an address, ordered from the innermost inlined frame to
+/// the outermost function.
+#[repr(C)]
+pub struct SymbolicSourceLocations {
+    pub items: *mut SymbolicSourceLocation,
+    pub len: usize,
+}
+
 /// Represents an instruction info.
 #[repr(C)]
 pub struct SymbolicInstructionInfo {
@@ -196,6 +224,57 @@ ffi_fn! {
     }
 }
 
+ffi_fn! {
+    /// Returns true if the symcache uses the latest file format.
+    unsafe fn symbolic_symcache_is_latest(symcache: *const SymbolicSymCache) -> Result<bool> {
+        Ok(SymbolicSymCache::as_rust(symcache).get().is_latest())
+    }
+}
+
+ffi_fn! {
+    /// Looks up the source locations of an address, including all inlined frames.
+    ///
+    /// The returned locations are ordered from the innermost inlined frame to the outermost
+    /// function. The result must be freed with `symbolic_source_locations_free`.
+    unsafe fn symbolic_symcache_lookup_source_locations(
+        symcache: *const SymbolicSymCache,
+        addr: u64,
+    ) -> Result<SymbolicSourceLocations> {
+        let cache = SymbolicSymCache::as_rust(symcache).get();
+
+        let mut items = vec![];
+        for line_info in cache.lookup(addr)? {
+            let line_info = line_info?;
+            items.push(SymbolicSourceLocation {
+                function_address: line_info.function_address(),
+                line: line_info.line(),
+                lang: SymbolicStr::new(line_info.language().name()),
+                symbol: SymbolicStr::new(line_info.symbol()),
+                full_path: SymbolicStr::from_string(line_info.abs_path()),
+                comp_dir: SymbolicStr::new(line_info.compilation_dir()),
+            });
+        }
+
+        items.shrink_to_fit();
+        let rv = SymbolicSourceLocations {
+            items: items.as_mut_ptr(),
+            len: items.len(),
+        };
+        mem::forget(items);
+        Ok(rv)
+    }
+}
+
+ffi_fn! {
+    /// Frees the source locations returned by `symbolic_symcache_lookup_source_locations`.
+    unsafe fn symbolic_source_locations_free(source_locations: *mut SymbolicSourceLocations) {
+        if !source_locations.is_null() {
+            let result = &*source_locations;
+            Vec::from_raw_parts(result.items, result.len, result.len);
+        }
+    }
+}
+
 ffi_fn! {
     /// Return the best instruction for an isntruction info.
     unsafe fn symbolic_find_best_instruction(ii: *const SymbolicInstructionInfo) -> Result<u64> {
@@ -7,14 +7,58 @@
 use std::borrow::Cow;
 use std::fs::File;
 use std::io;
+#[cfg(feature = "compression")]
+use std::io::{Read, Seek};
 use std::ops::Deref;
 use std::path::Path;
 use std::sync::Arc;
 
+#[cfg(feature = "compression")]
+use flate2::read::MultiGzDecoder;
 use memmap2::Mmap;
 
 use crate::cell::StableDeref;
 
+/// The default limit for the decompressed size of files opened with [`ByteView::open_auto`].
+#[cfg(feature = "compression")]
+pub const DEFAULT_DECOMPRESSION_LIMIT: u64 = 4 * 1024 * 1024 * 1024;
+
+/// Magic bytes of a gzip stream.
+#[cfg(feature = "compression")]
+const GZIP_MAGIC: &[u8] = b"\x1f\x8b";
+/// Magic bytes of a Zstandard frame.
+#[cfg(feature = "compression")]
+const ZSTD_MAGIC: &[u8] = b"\x28\xb5\x2f\xfd";
+/// Magic bytes of an XZ stream.
+#[cfg(feature = "compression")]
+const XZ_MAGIC: &[u8] = b"\xfd7zXZ\x00";
+
+/// The compression format of a file, detected by its magic bytes.
+#[cfg(feature = "compression")]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Compression {
+    None,
+    Gzip,
+    Zstd,
+    Xz,
+}
+
+#[cfg(feature = "compression")]
+impl Compression {
+    /// Detects the compression format from the first bytes of a file.
+    fn detect(header: &[u8]) -> Self {
+        if header.starts_with(GZIP_MAGIC) {
+            Compression::Gzip
+        } else if header.starts_with(ZSTD_MAGIC) {
+            Compression::Zstd
+        } else if header.starts_with(XZ_MAGIC) {
+            Compression::Xz
+        } else {
+            Compression::None
+        }
+    }
+}
+
 /// The owner of data behind a ByteView.
 ///
 /// This can either be an mmapped file, an owned buffer or a borrowed binary slice.
@@ -218,6 +262,75 @@ impl<'a> ByteView<'a> {
         Self::map_file(file)
     }
 
+    /// Constructs a `ByteView` from a file path, transparently decompressing gzip-compressed files.
+    ///
+    /// Gzip is the only supported compression format. It is detected from the magic bytes at the
+    /// start of the file, regardless of the file extension. Compressed files are decompressed into
+    /// an anonymous temporary file, which is then memory mapped and removed once the `ByteView` is
+    /// dropped. Uncompressed files are memory mapped directly, just like [`open`].
+    ///
+    /// Files compressed with Zstandard or XZ are not decompressed, since this crate does not depend
+    /// on decoders for these formats. Instead, they are detected and rejected with an error of kind
+    /// [`io::ErrorKind::InvalidData`], so that they are not mistaken for uncompressed files. Callers
+    /// have to decompress such files themselves before opening them.
+    ///
+    /// The decompressed size is limited to [`DEFAULT_DECOMPRESSION_LIMIT`]. Use
+    /// [`open_auto_with_limit`] to choose a different limit.
+    ///
+    /// This requires the `compression` feature.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use symbolic_common::ByteView;
+    ///
+    /// fn main() -> Result<(), std::io::Error> {
+    ///     let view = ByteView::open_auto("crash.debug.gz")?;
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// [`open`]: struct.ByteView.html#method.open
+    /// [`open_auto_with_limit`]: struct.ByteView.html#method.open_auto_with_limit
+    #[cfg(feature = "compression")]
+    pub fn open_auto<P: AsRef<Path>>(path: P) -> Result<Self, io::Error> {
+        Self::open_auto_with_limit(path, DEFAULT_DECOMPRESSION_LIMIT)
+    }
+
+    /// Constructs a `ByteView` from a file path, decompressing gzip-compressed files up to `limit`
+    /// bytes.
+    ///
+    /// This behaves like [`open_auto`], but fails with an error of kind
+    /// [`io::ErrorKind::InvalidData`] as soon as the decompressed contents exceed `limit` bytes.
+    /// The limit does not apply to uncompressed files.
+    ///
+    /// This requires the `compression` feature.
+    ///
+    /// [`open_auto`]: struct.ByteView.html#method.open_auto
+    #[cfg(feature = "compression")]
+    pub fn open_auto_with_limit<P: AsRef<Path>>(path: P, limit: u64) -> Result<Self, io::Error> {
+        let mut file = File::open(path)?;
+
+        let mut header = [0; 6];
+        let header_len = read_header(&mut file, &mut header)?;
+        file.rewind()?;
+
+        match Compression::detect(&header[..header_len]) {
+            Compression::None => Self::map_file(file),
+            Compression::Gzip => {
+                let mut decoder = MultiGzDecoder::new(file).take(limit.saturating_add(1));
+                let mut decompressed = tempfile::tempfile()?;
+                if io::copy(&mut decoder, &mut decompressed)? > limit {
+                    let message = format!("decompressed size exceeds {} bytes", limit);
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+                }
+                Self::map_file(decompressed)
+            }
+            Compression::Zstd => Err(unsupported_compression("zstd")),
+            Compression::Xz => Err(unsupported_compression("xz")),
+        }
+    }
+
     /// Returns a slice of the underlying data.
     ///
     ///
@@ -253,6 +366,30 @@ impl Deref for ByteView<'_> {
 
 unsafe impl StableDeref for ByteView<'_> {}
 
+/// Reads as many bytes into `buf` as available, returning the number of bytes read.
+#[cfg(feature = "compression")]
+fn read_header<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<usize, io::Error> {
+    let mut len = 0;
+    while len < buf.len() {
+        match reader.read(&mut buf[len..]) {
+            Ok(0) => break,
+            Ok(n) => len += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(len)
+}
+
+#[cfg(feature = "compression")]
+fn unsupported_compression(format: &str) -> io::Error {
+    let message = format!(
+        "{} compression is not supported, decompress the file before opening it",
+        format
+    );
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -311,4 +448,86 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    #[cfg(feature = "compression")]
+    fn test_open_auto_uncompressed() -> Result<(), std::io::Error> {
+        let mut tmp = NamedTempFile::new()?;
+        tmp.write_all(b"1234")?;
+
+        let view = ByteView::open_auto(tmp.path())?;
+        assert_eq!(&*view, b"1234");
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "compression")]
+    fn test_open_auto_empty() -> Result<(), std::io::Error> {
+        let tmp = NamedTempFile::new()?;
+
+        let view = ByteView::open_auto(tmp.path())?;
+        assert_eq!(&*view, b"");
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "compression")]
+    fn test_open_auto_gzip() -> Result<(), std::io::Error> {
+        let tmp = NamedTempFile::new()?;
+
+        let mut encoder =
+            flate2::write::GzEncoder::new(tmp.as_file(), flate2::Compression::default());
+        encoder.write_all(b"1234")?;
+        encoder.finish()?;
+
+        let view = ByteView::open_auto(tmp.path())?;
+        assert_eq!(&*view, b"1234");
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "compression")]
+    fn test_open_auto_limit() -> Result<(), std::io::Error> {
+        let tmp = NamedTempFile::new()?;
+
+        let mut encoder =
+            flate2::write::GzEncoder::new(tmp.as_file(), flate2::Compression::default());
+        encoder.write_all(&[0; 1024])?;
+        encoder.finish()?;
+
+        let view = ByteView::open_auto_with_limit(tmp.path(), 1024)?;
+        assert_eq!(view.len(), 1024);
+
+        let error = ByteView::open_auto_with_limit(tmp.path(), 1023).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "compression")]
+    fn test_open_auto_unsupported() -> Result<(), std::io::Error> {
+        let mut tmp = NamedTempFile::new()?;
+        tmp.write_all(b"\x28\xb5\x2f\xfd1234")?;
+
+        let error = ByteView::open_auto(tmp.path()).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        assert!(error
+            .to_string()
+            .starts_with("zstd compression is not supported"));
+
+        let mut tmp = NamedTempFile::new()?;
+        tmp.write_all(b"\xfd7zXZ\x001234")?;
+
+        let error = ByteView::open_auto(tmp.path()).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        assert!(error
+            .to_string()
+            .starts_with("xz compression is not supported"));
+
+        Ok(())
+    }
 }
@@ -0,0 +1,335 @@
+//! Typed code identifiers with format-specific parsing and formatting.
+
+use std::fmt;
+
+use debugid::{CodeId, DebugId, ParseCodeIdError};
+use uuid::Uuid;
+
+/// The object file format that determines the layout of a [`TypedCodeId`].
+///
+/// [`TypedCodeId`]: enum.TypedCodeId.html
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum CodeIdFormat {
+    /// A GNU build id or the hash of the text section of an ELF file.
+    Elf,
+    /// The timestamp and image size of a Portable Executable.
+    Pe,
+    /// The `LC_UUID` load command of a MachO file.
+    MachO,
+}
+
+/// A code identifier with a known object file format.
+///
+/// The untyped [`CodeId`] only stores a normalized hex string, which makes it impossible to tell
+/// how it was derived. Since every platform defines its own layout for code identifiers, converting
+/// between them and debug identifiers or symbol server paths requires knowledge of the format.
+///
+/// The canonical string representation of every variant is lowercase hex without separators, which
+/// matches the code identifiers emitted by `symbolic-debuginfo`:
+///
+///  - **ELF**: The hex-encoded build id, e.g. `f1c3bcc0279865fe3058404b2831d9e64135386c`.
+///  - **PE**: The timestamp as 8 hex digits, followed by the image size without leading zeros,
+///    e.g. `5ab380779000`.
+///  - **MachO**: The UUID without hyphens, e.g. `dfb8e43af2423d73a453aeb6a777ef75`.
+///
+/// # Examples
+///
+/// ```
+/// use symbolic_common::{CodeIdFormat, TypedCodeId};
+///
+/// let code_id = TypedCodeId::parse(CodeIdFormat::Pe, "5AB380779000").unwrap();
+/// assert_eq!(
+///     code_id,
+///     TypedCodeId::Pe {
+///         timestamp: 0x5ab3_8077,
+///         size_of_image: 0x9000,
+///     }
+/// );
+/// assert_eq!(code_id.to_string(), "5ab380779000");
+/// ```
+///
+/// [`CodeId`]: struct.CodeId.html
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum TypedCodeId {
+    /// The build id of an ELF file.
+    Elf(Vec<u8>),
+    /// The timestamp and image size from the COFF and optional headers of a PE file.
+    Pe {
+        /// The `TimeDateStamp` field of the COFF file header.
+        timestamp: u32,
+        /// The `SizeOfImage` field of the optional header.
+        size_of_image: u32,
+    },
+    /// The UUID of a MachO file.
+    MachO(Uuid),
+}
+
+impl TypedCodeId {
+    /// Parses a code identifier string in the given format.
+    ///
+    /// Parsing is case insensitive. MachO code identifiers may optionally contain hyphens. Returns
+    /// an error if the string does not match the layout of the format.
+    pub fn parse(format: CodeIdFormat, string: &str) -> Result<Self, ParseCodeIdError> {
+        match format {
+            CodeIdFormat::Elf => {
+                if string.is_empty() || !string.is_ascii() {
+                    return Err(ParseCodeIdError);
+                }
+
+                let bytes = string
+                    .as_bytes()
+                    .chunks(2)
+                    .map(|digits| match digits {
+                        [high, low] => Ok((hex_digit(*high)? << 4) | hex_digit(*low)?),
+                        _ => Err(ParseCodeIdError),
+                    })
+                    .collect::<Result<_, _>>()?;
+
+                Ok(TypedCodeId::Elf(bytes))
+            }
+            CodeIdFormat::Pe => {
+                if string.len() < 9 || string.len() > 16 {
+                    return Err(ParseCodeIdError);
+                }
+
+                let timestamp = string.get(..8).ok_or(ParseCodeIdError)?;
+                let size_of_image = string.get(8..).ok_or(ParseCodeIdError)?;
+
+                Ok(TypedCodeId::Pe {
+                    timestamp: parse_hex(timestamp)?,
+                    size_of_image: parse_hex(size_of_image)?,
+                })
+            }
+            CodeIdFormat::MachO => Uuid::parse_str(string)
+                .map(TypedCodeId::MachO)
+                .map_err(|_| ParseCodeIdError),
+        }
+    }
+
+    /// Interprets an untyped [`CodeId`] in the given format.
+    ///
+    /// [`CodeId`]: struct.CodeId.html
+    pub fn from_code_id(format: CodeIdFormat, code_id: &CodeId) -> Result<Self, ParseCodeIdError> {
+        Self::parse(format, code_id.as_str())
+    }
+
+    /// Derives a code identifier from a debug identifier.
+    ///
+    /// This is the inverse of [`to_debug_id`]. PE files store their debug identifier independently
+    /// of the code identifier, so this returns `None` for [`CodeIdFormat::Pe`]. Since debug
+    /// identifiers are truncated to 16 bytes, ELF build ids can only be restored if they were not
+    /// longer than that.
+    ///
+    /// [`to_debug_id`]: enum.TypedCodeId.html#method.to_debug_id
+    /// [`CodeIdFormat::Pe`]: enum.CodeIdFormat.html#variant.Pe
+    pub fn from_debug_id(format: CodeIdFormat, debug_id: DebugId) -> Option<Self> {
+        if debug_id.appendix() != 0 {
+            return None;
+        }
+
+        match format {
+            CodeIdFormat::Elf => {
+                let mut data = *debug_id.uuid().as_bytes();
+                flip_uuid_fields(&mut data);
+                Some(TypedCodeId::Elf(data.to_vec()))
+            }
+            CodeIdFormat::Pe => None,
+            CodeIdFormat::MachO => Some(TypedCodeId::MachO(debug_id.uuid())),
+        }
+    }
+
+    /// Returns the object file format of this code identifier.
+    pub fn format(&self) -> CodeIdFormat {
+        match self {
+            TypedCodeId::Elf(_) => CodeIdFormat::Elf,
+            TypedCodeId::Pe { .. } => CodeIdFormat::Pe,
+            TypedCodeId::MachO(_) => CodeIdFormat::MachO,
+        }
+    }
+
+    /// Converts this into an untyped [`CodeId`] in canonical representation.
+    ///
+    /// [`CodeId`]: struct.CodeId.html
+    pub fn to_code_id(&self) -> CodeId {
+        CodeId::new(self.to_string())
+    }
+
+    /// Derives the debug identifier of the object file from this code identifier.
+    ///
+    /// For ELF files, the first 16 bytes of the build id are interpreted as UUID in little endian
+    /// byte order, padded with zeros if the build id is shorter. This matches the debug identifiers
+    /// of little-endian ELF files, which make up virtually all ELF files in practice.
+    ///
+    /// Returns `None` for PE files, since their debug identifier is stored separately in the
+    /// CodeView record and cannot be derived from the code identifier.
+    pub fn to_debug_id(&self) -> Option<DebugId> {
+        match self {
+            TypedCodeId::Elf(build_id) => {
+                let mut data = [0; 16];
+                let len = build_id.len().min(data.len());
+                data[..len].copy_from_slice(&build_id[..len]);
+                flip_uuid_fields(&mut data);
+                Some(DebugId::from_uuid(Uuid::from_bytes(data)))
+            }
+            TypedCodeId::Pe { .. } => None,
+            TypedCodeId::MachO(uuid) => Some(DebugId::from_uuid(*uuid)),
+        }
+    }
+
+    /// Returns the signature used to locate the executable on a Microsoft symbol server.
+    ///
+    /// For PE files, this is the timestamp as 8 uppercase hex digits, followed by the image size in
+    /// lowercase hex, e.g. `5AB380779000`. All other formats use the canonical representation.
+    pub fn symstore_signature(&self) -> String {
+        match *self {
+            TypedCodeId::Pe {
+                timestamp,
+                size_of_image,
+            } => format!("{:08X}{:x}", timestamp, size_of_image),
+            _ => self.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for TypedCodeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TypedCodeId::Elf(build_id) => {
+                for byte in build_id {
+                    write!(f, "{:02x}", byte)?;
+                }
+                Ok(())
+            }
+            TypedCodeId::Pe {
+                timestamp,
+                size_of_image,
+            } => write!(f, "{:08x}{:x}", timestamp, size_of_image),
+            TypedCodeId::MachO(uuid) => write!(f, "{:x}", uuid.to_simple_ref()),
+        }
+    }
+}
+
+impl From<TypedCodeId> for CodeId {
+    fn from(code_id: TypedCodeId) -> Self {
+        code_id.to_code_id()
+    }
+}
+
+/// Parses a hex string without prefix or sign into an integer.
+fn parse_hex(string: &str) -> Result<u32, ParseCodeIdError> {
+    if !string.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(ParseCodeIdError);
+    }
+
+    u32::from_str_radix(string, 16).map_err(|_| ParseCodeIdError)
+}
+
+/// Parses a single ASCII hex digit.
+fn hex_digit(digit: u8) -> Result<u8, ParseCodeIdError> {
+    (digit as char)
+        .to_digit(16)
+        .map(|value| value as u8)
+        .ok_or(ParseCodeIdError)
+}
+
+/// Swaps the byte order of the first three UUID fields.
+fn flip_uuid_fields(data: &mut [u8; 16]) {
+    data[0..4].reverse(); // uuid field 1
+    data[4..6].reverse(); // uuid field 2
+    data[6..8].reverse(); // uuid field 3
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_elf() {
+        let code_id = TypedCodeId::parse(
+            CodeIdFormat::Elf,
+            "F1C3BCC0279865FE3058404B2831D9E64135386C",
+        )
+        .unwrap();
+
+        assert_eq!(code_id.format(), CodeIdFormat::Elf);
+        assert_eq!(
+            code_id.to_string(),
+            "f1c3bcc0279865fe3058404b2831d9e64135386c"
+        );
+        assert!(TypedCodeId::parse(CodeIdFormat::Elf, "").is_err());
+        assert!(TypedCodeId::parse(CodeIdFormat::Elf, "abc").is_err());
+        assert!(TypedCodeId::parse(CodeIdFormat::Elf, "+f").is_err());
+    }
+
+    #[test]
+    fn test_parse_pe() {
+        let code_id = TypedCodeId::parse(CodeIdFormat::Pe, "5ab380771A000").unwrap();
+
+        assert_eq!(
+            code_id,
+            TypedCodeId::Pe {
+                timestamp: 0x5ab3_8077,
+                size_of_image: 0x1a000,
+            }
+        );
+        assert_eq!(code_id.to_string(), "5ab380771a000");
+        assert_eq!(code_id.symstore_signature(), "5AB380771a000");
+        assert!(TypedCodeId::parse(CodeIdFormat::Pe, "5ab38077").is_err());
+        assert!(TypedCodeId::parse(CodeIdFormat::Pe, "5ab38077123456789").is_err());
+        assert!(TypedCodeId::parse(CodeIdFormat::Pe, "5ab3807x9000").is_err());
+    }
+
+    #[test]
+    fn test_parse_macho() {
+        let code_id =
+            TypedCodeId::parse(CodeIdFormat::MachO, "DFB8E43A-F242-3D73-A453-AEB6A777EF75")
+                .unwrap();
+
+        assert_eq!(code_id.to_string(), "dfb8e43af2423d73a453aeb6a777ef75");
+        assert_eq!(
+            code_id.to_debug_id(),
+            Some("dfb8e43a-f242-3d73-a453-aeb6a777ef75".parse().unwrap())
+        );
+        assert!(TypedCodeId::parse(CodeIdFormat::MachO, "dfb8e43a").is_err());
+    }
+
+    #[test]
+    fn test_elf_debug_id() {
+        let code_id = TypedCodeId::parse(
+            CodeIdFormat::Elf,
+            "f1c3bcc0279865fe3058404b2831d9e64135386c",
+        )
+        .unwrap();
+        let debug_id = code_id.to_debug_id().unwrap();
+
+        assert_eq!(debug_id.to_string(), "c0bcc3f1-9827-fe65-3058-404b2831d9e6");
+        assert_eq!(
+            TypedCodeId::from_debug_id(CodeIdFormat::Elf, debug_id),
+            TypedCodeId::parse(CodeIdFormat::Elf, "f1c3bcc0279865fe3058404b2831d9e6").ok()
+        );
+    }
+
+    #[test]
+    fn test_pe_debug_id() {
+        let code_id = TypedCodeId::Pe {
+            timestamp: 0x5ab3_8077,
+            size_of_image: 0x9000,
+        };
+
+        assert_eq!(code_id.to_debug_id(), None);
+        assert_eq!(
+            TypedCodeId::from_debug_id(CodeIdFormat::Pe, DebugId::default()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_to_code_id() {
+        let code_id = TypedCodeId::Pe {
+            timestamp: 0x0000_8077,
+            size_of_image: 0x9000,
+        };
+
+        assert_eq!(CodeId::from(code_id).as_str(), "000080779000");
+    }
+}
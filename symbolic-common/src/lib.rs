@@ -10,6 +10,9 @@
 //!
 //! # Features
 //!
+//! - `compression` (optional): Enables `ByteView::open_auto`, which transparently decompresses
+//!   gzip-compressed files. In the `symbolic` crate, this feature is exposed via
+//!   `common-compression`.
 //! - `serde` (optional): Implements `serde::Deserialize` and `serde::Serialize` for all data types.
 //!   In the `symbolic` crate, this feature is exposed via `common-serde`.
 //!
@@ -24,14 +27,20 @@
 
 mod byteview;
 mod cell;
+mod codeid;
 mod heuristics;
+mod normalize;
 mod path;
+mod sourcecontext;
 mod types;
 
 pub use crate::byteview::*;
 pub use crate::cell::*;
+pub use crate::codeid::*;
 pub use crate::heuristics::*;
+pub use crate::normalize::*;
 pub use crate::path::*;
+pub use crate::sourcecontext::*;
 pub use crate::types::*;
 
 pub use debugid::*;
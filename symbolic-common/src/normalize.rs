@@ -0,0 +1,341 @@
+//! Normalization of function names across compiler generated variants.
+
+use std::borrow::Cow;
+
+use crate::types::Name;
+
+/// Suffixes that GCC and LLVM append to the symbols of specialized or outlined function clones.
+const CLONE_SUFFIXES: &[&str] = &[
+    "clone",
+    "cold",
+    "constprop",
+    "isra",
+    "llvm",
+    "localalias",
+    "lto_priv",
+    "part",
+];
+
+/// Prefixes of demangled thunk names.
+const THUNK_PREFIXES: &[&str] = &[
+    "non-virtual thunk to ",
+    "virtual thunk to ",
+    "covariant return thunk to ",
+];
+
+/// Strips trailing clone suffixes, such as `.isra.0` or `.cold`, from a symbol name.
+fn strip_clone_suffixes(name: &str) -> &str {
+    let mut end = name.len();
+    let mut rest = name;
+
+    while let Some(pos) = rest.rfind('.') {
+        let component = &rest[pos + 1..];
+        if CLONE_SUFFIXES.contains(&component) {
+            end = pos;
+        } else if component.is_empty() || !component.bytes().all(|b| b.is_ascii_digit()) {
+            break;
+        }
+
+        rest = &rest[..pos];
+    }
+
+    // Never strip the entire name, for instance in the case of a symbol called `.cold`.
+    if end == 0 {
+        return name;
+    }
+
+    &name[..end]
+}
+
+/// Strips trailing ` [clone .cold]` annotations from a demangled function name.
+fn strip_demangled_clone_suffixes(mut name: &str) -> &str {
+    while name.ends_with(']') {
+        match name.rfind(" [clone .") {
+            Some(pos) if pos > 0 => name = &name[..pos],
+            _ => break,
+        }
+    }
+
+    name
+}
+
+/// Parses an Itanium call offset number, such as `8` or `n16`, followed by an underscore.
+fn strip_call_offset(name: &str) -> Option<&str> {
+    let name = name.strip_prefix('n').unwrap_or(name);
+    let digits = name.bytes().take_while(u8::is_ascii_digit).count();
+    if digits == 0 {
+        return None;
+    }
+
+    name[digits..].strip_prefix('_')
+}
+
+/// Resolves an Itanium mangled thunk symbol to the mangled name of its target function.
+///
+/// Returns `None` if the name is not a non-virtual (`_ZTh`) or virtual (`_ZTv`) thunk.
+fn strip_mangled_thunk(name: &str) -> Option<String> {
+    // MachO symbols carry an additional leading underscore.
+    let (prefix, rest) = match name.strip_prefix("__ZT") {
+        Some(rest) => ("__Z", rest),
+        None => ("_Z", name.strip_prefix("_ZT")?),
+    };
+
+    let target = if let Some(rest) = rest.strip_prefix('h') {
+        strip_call_offset(rest)?
+    } else if let Some(rest) = rest.strip_prefix('v') {
+        strip_call_offset(strip_call_offset(rest)?)?
+    } else {
+        return None;
+    };
+
+    if target.is_empty() {
+        return None;
+    }
+
+    Some(format!("{}{}", prefix, target))
+}
+
+/// Strips a thunk prefix, such as `non-virtual thunk to `, from a demangled function name.
+fn strip_demangled_thunk(name: &str) -> &str {
+    THUNK_PREFIXES
+        .iter()
+        .find_map(|prefix| name.strip_prefix(prefix))
+        .filter(|target| !target.is_empty())
+        .unwrap_or(name)
+}
+
+/// Maps compiler generated variants of a function name to a canonical name.
+///
+/// Compilers emit specialized copies of functions under distinct symbol names. For instance, GCC
+/// moves unlikely code into a `foo.cold` function, and creates `foo.isra.0` or `foo.constprop.0`
+/// when optimizing a call signature. Likewise, C++ compilers generate thunks that adjust the `this`
+/// pointer before calling into a virtual method. All of these variants share the source code of
+/// the original function, so that it is often desirable to report them under one name.
+///
+/// The normalizer works on both mangled and demangled names. By default, all rules are enabled:
+///
+///  - **Clone suffixes**: Strips trailing `.cold`, `.isra.N`, `.constprop.N`, `.part.N`,
+///    `.clone.N`, `.lto_priv.N`, `.llvm.N` and `.localalias` suffixes from symbols, as well as
+///    ` [clone .cold]` annotations from demangled names.
+///  - **Thunks**: Resolves mangled `_ZTh` and `_ZTv` thunks to the mangled name of their target
+///    function, and strips `non-virtual thunk to ` and similar prefixes from demangled names.
+///
+/// # Examples
+///
+/// ```
+/// use symbolic_common::{Name, NameNormalizer};
+///
+/// let normalizer = NameNormalizer::new();
+///
+/// assert_eq!(normalizer.normalize_str("_ZN3foo3barEv.isra.0"), "_ZN3foo3barEv");
+/// assert_eq!(normalizer.normalize_str("_ZThn8_N3foo3barEv"), "_ZN3foo3barEv");
+/// assert_eq!(normalizer.normalize_str("foo::bar() [clone .cold]"), "foo::bar()");
+///
+/// let name = normalizer.normalize(Name::from("main.cold"));
+/// assert_eq!(name.as_str(), "main");
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NameNormalizer {
+    clone_suffixes: bool,
+    thunks: bool,
+}
+
+impl NameNormalizer {
+    /// Creates a new normalizer with all rules enabled.
+    pub fn new() -> Self {
+        Self {
+            clone_suffixes: true,
+            thunks: true,
+        }
+    }
+
+    /// Enables or disables stripping of clone suffixes, such as `.cold` or `.isra.0`.
+    pub fn clone_suffixes(&mut self, flag: bool) -> &mut Self {
+        self.clone_suffixes = flag;
+        self
+    }
+
+    /// Enables or disables resolving thunks to their target function.
+    pub fn thunks(&mut self, flag: bool) -> &mut Self {
+        self.thunks = flag;
+        self
+    }
+
+    /// Normalizes a mangled or demangled function name.
+    ///
+    /// The name is returned unchanged if none of the enabled rules apply.
+    pub fn normalize_str<'a, S>(&self, name: S) -> Cow<'a, str>
+    where
+        S: Into<Cow<'a, str>>,
+    {
+        let mut name = name.into();
+
+        if self.thunks {
+            if let Some(target) = strip_mangled_thunk(&name) {
+                name = Cow::Owned(target);
+            } else {
+                name = strip_cow(name, strip_demangled_thunk);
+            }
+        }
+
+        if self.clone_suffixes {
+            name = strip_cow(name, strip_demangled_clone_suffixes);
+            name = strip_cow(name, strip_clone_suffixes);
+        }
+
+        name
+    }
+
+    /// Normalizes a [`Name`], retaining its language and mangling state.
+    pub fn normalize<'a>(&self, name: Name<'a>) -> Name<'a> {
+        let mangling = name.mangling();
+        let language = name.language();
+        Name::new(self.normalize_str(name.into_cow()), mangling, language)
+    }
+}
+
+impl Default for NameNormalizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Applies a function returning a substring to a `Cow`, only allocating if the string is owned.
+fn strip_cow<'a, F>(name: Cow<'a, str>, f: F) -> Cow<'a, str>
+where
+    F: for<'s> Fn(&'s str) -> &'s str,
+{
+    match name {
+        Cow::Borrowed(s) => Cow::Borrowed(f(s)),
+        Cow::Owned(s) => {
+            let stripped = f(&s);
+            if stripped.len() == s.len() {
+                Cow::Owned(s)
+            } else {
+                Cow::Owned(stripped.to_owned())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use similar_asserts::assert_eq;
+
+    use crate::{Language, NameMangling};
+
+    #[test]
+    fn test_clone_suffixes() {
+        let normalizer = NameNormalizer::new();
+
+        assert_eq!(normalizer.normalize_str("foo.cold"), "foo");
+        assert_eq!(normalizer.normalize_str("foo.cold.12"), "foo");
+        assert_eq!(normalizer.normalize_str("foo.isra.0"), "foo");
+        assert_eq!(normalizer.normalize_str("foo.constprop.0.isra.0"), "foo");
+        assert_eq!(normalizer.normalize_str("foo.part.3.cold"), "foo");
+        assert_eq!(
+            normalizer.normalize_str("foo.llvm.8517243942871813418"),
+            "foo"
+        );
+        assert_eq!(normalizer.normalize_str("foo.lto_priv.0"), "foo");
+        assert_eq!(
+            normalizer.normalize_str("_ZN3foo3barEv.localalias"),
+            "_ZN3foo3barEv"
+        );
+    }
+
+    #[test]
+    fn test_clone_suffixes_unchanged() {
+        let normalizer = NameNormalizer::new();
+
+        assert_eq!(normalizer.normalize_str("foo"), "foo");
+        assert_eq!(normalizer.normalize_str("foo.1"), "foo.1");
+        assert_eq!(normalizer.normalize_str("foo.bar"), "foo.bar");
+        assert_eq!(normalizer.normalize_str("foo.cold.bar"), "foo.cold.bar");
+        assert_eq!(normalizer.normalize_str(".cold"), ".cold");
+        assert_eq!(normalizer.normalize_str("foo."), "foo.");
+    }
+
+    #[test]
+    fn test_demangled_clone_suffixes() {
+        let normalizer = NameNormalizer::new();
+
+        assert_eq!(
+            normalizer.normalize_str("foo::bar(int) [clone .isra.0] [clone .cold]"),
+            "foo::bar(int)"
+        );
+        assert_eq!(normalizer.normalize_str("operator[]"), "operator[]");
+    }
+
+    #[test]
+    fn test_thunks() {
+        let normalizer = NameNormalizer::new();
+
+        assert_eq!(
+            normalizer.normalize_str("_ZThn8_N3foo3barEv"),
+            "_ZN3foo3barEv"
+        );
+        assert_eq!(
+            normalizer.normalize_str("__ZThn16_N3foo3barEv"),
+            "__ZN3foo3barEv"
+        );
+        assert_eq!(
+            normalizer.normalize_str("_ZTv0_n24_N3foo3barEv"),
+            "_ZN3foo3barEv"
+        );
+        assert_eq!(
+            normalizer.normalize_str("_ZThn8_N3foo3barEv.cold"),
+            "_ZN3foo3barEv"
+        );
+        assert_eq!(
+            normalizer.normalize_str("non-virtual thunk to foo::bar()"),
+            "foo::bar()"
+        );
+        assert_eq!(
+            normalizer.normalize_str("virtual thunk to foo::bar()"),
+            "foo::bar()"
+        );
+
+        // Other special names must remain intact.
+        assert_eq!(normalizer.normalize_str("_ZTV3foo"), "_ZTV3foo");
+        assert_eq!(normalizer.normalize_str("_ZThn8_"), "_ZThn8_");
+    }
+
+    #[test]
+    fn test_disabled_rules() {
+        let mut normalizer = NameNormalizer::new();
+        normalizer.clone_suffixes(false);
+        assert_eq!(normalizer.normalize_str("foo.cold"), "foo.cold");
+        assert_eq!(
+            normalizer.normalize_str("_ZThn8_N3foo3barEv"),
+            "_ZN3foo3barEv"
+        );
+
+        normalizer.clone_suffixes(true).thunks(false);
+        assert_eq!(normalizer.normalize_str("foo.cold"), "foo");
+        assert_eq!(
+            normalizer.normalize_str("_ZThn8_N3foo3barEv"),
+            "_ZThn8_N3foo3barEv"
+        );
+    }
+
+    #[test]
+    fn test_normalize_name() {
+        let normalizer = NameNormalizer::new();
+        let name = Name::new("_ZN3foo3barEv.cold", NameMangling::Mangled, Language::Cpp);
+
+        assert_eq!(
+            normalizer.normalize(name),
+            Name::new("_ZN3foo3barEv", NameMangling::Mangled, Language::Cpp)
+        );
+    }
+
+    #[test]
+    fn test_normalize_borrowed() {
+        let normalizer = NameNormalizer::new();
+        let normalized = normalizer.normalize_str("foo.isra.0");
+        assert!(matches!(normalized, Cow::Borrowed("foo")));
+    }
+}
@@ -401,6 +401,127 @@ pub fn shorten_path(path: &str, length: usize) -> Cow<'_, str> {
     Cow::Owned(rv)
 }
 
+/// Rewrites the prefixes of paths according to a list of rules.
+///
+/// Build systems often compile code in temporary or machine-specific locations, such as
+/// `/build/worker/src`, which then end up in debug information. A remapper replaces such prefixes
+/// with a stable path, for instance `/src`, so that file paths match the layout of a source
+/// repository or bundle.
+///
+/// Rules only match at path component boundaries: a rule for `/build/src` applies to
+/// `/build/src/main.c`, but not to `/build/srcfoo/main.c`. Forward slashes and backslashes are
+/// treated as equivalent when matching. If multiple rules match, the one with the longest prefix
+/// wins.
+///
+/// # Examples
+///
+/// ```
+/// use symbolic_common::PathRemapper;
+///
+/// let mut remapper = PathRemapper::new();
+/// remapper.add("/build/worker/src", "/src");
+///
+/// assert_eq!(remapper.remap("/build/worker/src/main.c"), "/src/main.c");
+/// assert_eq!(remapper.remap("/build/worker/srcfoo/main.c"), "/build/worker/srcfoo/main.c");
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PathRemapper {
+    rules: Vec<(String, String)>,
+}
+
+impl PathRemapper {
+    /// Creates a new remapper without any rules.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a rule replacing the prefix `from` with `to`.
+    ///
+    /// Trailing separators are ignored on both sides. Rules with an empty `from` prefix are
+    /// ignored. If `to` is empty, matching paths become relative.
+    pub fn add(&mut self, from: &str, to: &str) -> &mut Self {
+        let from = from.trim_end_matches(is_path_separator);
+        let to = to.trim_end_matches(is_path_separator);
+
+        if !from.is_empty() {
+            // Keep rules sorted by descending prefix length so that the longest match wins. Rules
+            // of equal length retain the order in which they were added.
+            let index = self.rules.partition_point(|(f, _)| f.len() >= from.len());
+            self.rules.insert(index, (from.to_owned(), to.to_owned()));
+        }
+
+        self
+    }
+
+    /// Returns `true` if this remapper does not contain any rules.
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Finds the first rule matching the given path and returns it along with the remainder.
+    fn find<'p>(&self, path: &'p [u8]) -> Option<(&str, &'p [u8])> {
+        self.rules.iter().find_map(|(from, to)| {
+            let from = from.as_bytes();
+            let prefix = path.get(..from.len())?;
+            let rest = &path[from.len()..];
+
+            let matches = prefix
+                .iter()
+                .zip(from)
+                .all(|(a, b)| a == b || is_path_separator(a) && is_path_separator(b));
+
+            let at_boundary = rest.is_empty() || is_path_separator(rest[0]);
+            if matches && at_boundary {
+                Some((to.as_str(), rest))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Remaps a path given as bytes.
+    ///
+    /// The path is returned unchanged if none of the rules match.
+    pub fn remap_bytes<'a, B>(&self, path: B) -> Cow<'a, [u8]>
+    where
+        B: Into<Cow<'a, [u8]>>,
+    {
+        let path = path.into();
+
+        let remapped = match self.find(&path) {
+            Some(("", rest)) => rest.get(1..).unwrap_or_default().to_vec(),
+            Some((to, rest)) => [to.as_bytes(), rest].concat(),
+            None => return path,
+        };
+
+        Cow::Owned(remapped)
+    }
+
+    /// Remaps a path.
+    ///
+    /// The path is returned unchanged if none of the rules match.
+    pub fn remap<'a, S>(&self, path: S) -> Cow<'a, str>
+    where
+        S: Into<Cow<'a, str>>,
+    {
+        let path = path.into();
+
+        // Rules match at separators, which are ASCII, so the remainder is always valid UTF-8.
+        let remapped = match self.find(path.as_bytes()) {
+            Some((to, rest)) => {
+                let rest = &path[path.len() - rest.len()..];
+                match to {
+                    "" => rest.get(1..).unwrap_or_default().to_owned(),
+                    _ => format!("{}{}", to, rest),
+                }
+            }
+            None => return path,
+        };
+
+        Cow::Owned(remapped)
+    }
+}
+
 /// Extensions to `Path` for handling `dSYM` directories.
 ///
 /// # dSYM Files
@@ -544,6 +665,40 @@ mod tests {
     use similar_asserts::assert_eq;
     use symbolic_testutils::fixture;
 
+    #[test]
+    fn test_path_remapper() {
+        let mut remapper = PathRemapper::new();
+        remapper
+            .add("/build/worker/", "/src")
+            .add("/build/worker/vendor", "/deps/")
+            .add("C:\\build", "");
+
+        assert_eq!(remapper.remap("/build/worker/main.c"), "/src/main.c");
+        assert_eq!(remapper.remap("/build/worker"), "/src");
+        assert_eq!(remapper.remap("/build/worker/vendor/lib.c"), "/deps/lib.c");
+        assert_eq!(
+            remapper.remap("/build/workers/main.c"),
+            "/build/workers/main.c"
+        );
+        assert_eq!(remapper.remap("C:/build\\foo\\main.c"), "foo\\main.c");
+        assert_eq!(remapper.remap("main.c"), "main.c");
+    }
+
+    #[test]
+    fn test_path_remapper_bytes() {
+        let mut remapper = PathRemapper::new();
+        remapper.add("/build", "/src");
+
+        assert_eq!(
+            remapper.remap_bytes(&b"/build/\xffmain.c"[..]),
+            &b"/src/\xffmain.c"[..]
+        );
+        assert!(matches!(
+            remapper.remap_bytes(&b"/other/main.c"[..]),
+            Cow::Borrowed(_)
+        ));
+    }
+
     #[test]
     fn test_join_path() {
         assert_eq!(join_path("foo", "C:"), "C:");
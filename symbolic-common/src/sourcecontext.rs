@@ -0,0 +1,233 @@
+//! Extraction of source lines surrounding a line of interest.
+
+use std::borrow::Cow;
+
+/// The byte order mark of UTF-8 encoded files.
+const UTF8_BOM: &[u8] = b"\xef\xbb\xbf";
+
+/// Options for [`source_context`].
+///
+/// # Examples
+///
+/// ```
+/// use symbolic_common::SourceContextOptions;
+///
+/// let options = SourceContextOptions::new()
+///     .context_lines(3)
+///     .max_line_length(None);
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SourceContextOptions {
+    context_lines: usize,
+    max_line_length: Option<usize>,
+}
+
+impl SourceContextOptions {
+    /// Options with five lines of context and lines truncated to 150 characters.
+    pub const fn new() -> Self {
+        Self {
+            context_lines: 5,
+            max_line_length: Some(150),
+        }
+    }
+
+    /// Sets the number of lines returned before and after the line of interest.
+    pub const fn context_lines(mut self, context_lines: usize) -> Self {
+        self.context_lines = context_lines;
+        self
+    }
+
+    /// Sets the maximum number of characters returned for every line.
+    ///
+    /// Longer lines, such as in minified or generated code, are truncated. `None` disables
+    /// truncation.
+    pub const fn max_line_length(mut self, max_line_length: Option<usize>) -> Self {
+        self.max_line_length = max_line_length;
+        self
+    }
+}
+
+impl Default for SourceContextOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Source lines surrounding a line of interest, returned by [`source_context`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SourceContext<'a> {
+    /// The lines preceding the line of interest, in source order.
+    pub pre_context: Vec<Cow<'a, str>>,
+    /// The line of interest.
+    pub context_line: Cow<'a, str>,
+    /// The lines following the line of interest, in source order.
+    pub post_context: Vec<Cow<'a, str>>,
+}
+
+impl SourceContext<'_> {
+    /// Converts all lines into owned strings, detaching the context from the source.
+    pub fn into_owned(self) -> SourceContext<'static> {
+        let into_owned = |lines: Vec<Cow<'_, str>>| {
+            lines
+                .into_iter()
+                .map(|line| Cow::Owned(line.into_owned()))
+                .collect()
+        };
+
+        SourceContext {
+            pre_context: into_owned(self.pre_context),
+            context_line: Cow::Owned(self.context_line.into_owned()),
+            post_context: into_owned(self.post_context),
+        }
+    }
+}
+
+/// Extracts the given line and its surrounding lines from a source file.
+///
+/// The `line` is 1-based. Returns `None` if the line is zero or exceeds the number of lines in the
+/// source. Near the beginning or end of the file, fewer context lines are returned.
+///
+/// Lines are separated by `\n` or `\r\n`, and a leading UTF-8 byte order mark is skipped. Invalid
+/// UTF-8 sequences are replaced with `U+FFFD REPLACEMENT CHARACTER` on a per-line basis, so that
+/// binary garbage in one line does not affect the others.
+///
+/// # Examples
+///
+/// ```
+/// use symbolic_common::{source_context, SourceContextOptions};
+///
+/// let source = b"fn main() {\n    let x = 1;\n    panic!();\n}\n";
+/// let options = SourceContextOptions::new().context_lines(1);
+/// let context = source_context(source, 3, options).unwrap();
+///
+/// assert_eq!(context.pre_context, ["    let x = 1;"]);
+/// assert_eq!(context.context_line, "    panic!();");
+/// assert_eq!(context.post_context, ["}"]);
+/// ```
+pub fn source_context(
+    source: &[u8],
+    line: u32,
+    options: SourceContextOptions,
+) -> Option<SourceContext<'_>> {
+    let index = (line as usize).checked_sub(1)?;
+    let start = index.saturating_sub(options.context_lines);
+    let end = index.saturating_add(options.context_lines);
+
+    let source = source.strip_prefix(UTF8_BOM).unwrap_or(source);
+    let source = source.strip_suffix(b"\n").unwrap_or(source);
+    if source.is_empty() {
+        return None;
+    }
+
+    let mut context = SourceContext::default();
+    let mut found = false;
+
+    let lines = source.split(|&byte| byte == b'\n').enumerate();
+    for (current, bytes) in lines.skip(start).take(end - start + 1) {
+        let bytes = bytes.strip_suffix(b"\r").unwrap_or(bytes);
+        let text = truncate(String::from_utf8_lossy(bytes), options.max_line_length);
+
+        if current < index {
+            context.pre_context.push(text);
+        } else if current == index {
+            context.context_line = text;
+            found = true;
+        } else {
+            context.post_context.push(text);
+        }
+    }
+
+    if found {
+        Some(context)
+    } else {
+        None
+    }
+}
+
+/// Truncates a line to at most `max_length` characters.
+fn truncate(text: Cow<'_, str>, max_length: Option<usize>) -> Cow<'_, str> {
+    let len = match max_length.and_then(|max| text.char_indices().nth(max)) {
+        Some((len, _)) => len,
+        None => return text,
+    };
+
+    match text {
+        Cow::Borrowed(text) => Cow::Borrowed(&text[..len]),
+        Cow::Owned(mut text) => {
+            text.truncate(len);
+            Cow::Owned(text)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use similar_asserts::assert_eq;
+
+    const SOURCE: &[u8] = b"one\ntwo\r\nthree\nfour\nfive\n";
+
+    fn context(source: &[u8], line: u32, lines: usize) -> Option<SourceContext<'_>> {
+        source_context(
+            source,
+            line,
+            SourceContextOptions::new().context_lines(lines),
+        )
+    }
+
+    #[test]
+    fn test_context_middle() {
+        let context = context(SOURCE, 3, 1).unwrap();
+        assert_eq!(context.pre_context, ["two"]);
+        assert_eq!(context.context_line, "three");
+        assert_eq!(context.post_context, ["four"]);
+    }
+
+    #[test]
+    fn test_context_bounds() {
+        let first = context(SOURCE, 1, 2).unwrap();
+        assert!(first.pre_context.is_empty());
+        assert_eq!(first.context_line, "one");
+        assert_eq!(first.post_context, ["two", "three"]);
+
+        let last = context(SOURCE, 5, 2).unwrap();
+        assert_eq!(last.pre_context, ["three", "four"]);
+        assert_eq!(last.context_line, "five");
+        assert!(last.post_context.is_empty());
+    }
+
+    #[test]
+    fn test_context_out_of_range() {
+        assert_eq!(context(SOURCE, 0, 1), None);
+        assert_eq!(context(SOURCE, 6, 1), None);
+        assert_eq!(context(b"", 1, 1), None);
+    }
+
+    #[test]
+    fn test_context_bom() {
+        let context = context(b"\xef\xbb\xbfone\ntwo", 1, 1).unwrap();
+        assert_eq!(context.context_line, "one");
+        assert_eq!(context.post_context, ["two"]);
+    }
+
+    #[test]
+    fn test_context_invalid_utf8() {
+        let context = context(b"one\nt\xffo\nthree", 2, 1).unwrap();
+        assert_eq!(context.pre_context, ["one"]);
+        assert_eq!(context.context_line, "t\u{fffd}o");
+        assert_eq!(context.post_context, ["three"]);
+    }
+
+    #[test]
+    fn test_context_long_lines() {
+        let source = "äöü".repeat(100);
+        let options = SourceContextOptions::new().max_line_length(Some(4));
+        let context = source_context(source.as_bytes(), 1, options).unwrap();
+        assert_eq!(context.context_line, "äöüä");
+
+        let options = SourceContextOptions::new().max_line_length(None);
+        let context = source_context(source.as_bytes(), 1, options).unwrap();
+        assert_eq!(context.context_line, source);
+    }
+}
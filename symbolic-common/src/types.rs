@@ -57,6 +57,11 @@ static MIPS: &[&str] = &[
     "$f17", "$f18", "$f19", "$f20", "$f21", "$f22", "$f23", "$f24", "$f25", "$f26", "$f27", "$f28",
     "$f29", "$f30", "$f31", "$fcsr", "$fir",
 ];
+static RISCV: &[&str] = &[
+    "pc", "ra", "sp", "gp", "tp", "t0", "t1", "t2", "s0", "s1", "a0", "a1", "a2", "a3", "a4", "a5",
+    "a6", "a7", "s2", "s3", "s4", "s5", "s6", "s7", "s8", "s9", "s10", "s11", "t3", "t4", "t5",
+    "t6",
+];
 
 /// Represents a family of CPUs.
 ///
@@ -92,6 +97,10 @@ pub enum CpuFamily {
     Arm64_32 = 9,
     /// Virtual WASM 32-bit architecture.
     Wasm32 = 10,
+    /// 32-bit RISC-V.
+    Riscv32 = 11,
+    /// 64-bit RISC-V.
+    Riscv64 = 12,
 }
 
 impl CpuFamily {
@@ -118,8 +127,13 @@ impl CpuFamily {
             | CpuFamily::Arm64
             | CpuFamily::Ppc64
             | CpuFamily::Mips64
+            | CpuFamily::Riscv64
             | CpuFamily::Arm64_32 => Some(8),
-            CpuFamily::Intel32 | CpuFamily::Arm32 | CpuFamily::Ppc32 | CpuFamily::Mips32 => Some(4),
+            CpuFamily::Intel32
+            | CpuFamily::Arm32
+            | CpuFamily::Ppc32
+            | CpuFamily::Mips32
+            | CpuFamily::Riscv32 => Some(4),
         }
     }
 
@@ -147,6 +161,8 @@ impl CpuFamily {
             CpuFamily::Arm64 | CpuFamily::Arm64_32 => Some(4),
             CpuFamily::Ppc32 | CpuFamily::Mips32 | CpuFamily::Mips64 => Some(4),
             CpuFamily::Ppc64 => Some(8),
+            // Compressed instructions are 2 bytes wide.
+            CpuFamily::Riscv32 | CpuFamily::Riscv64 => Some(2),
             CpuFamily::Intel32 | CpuFamily::Amd64 => None,
             CpuFamily::Unknown => None,
         }
@@ -178,6 +194,7 @@ impl CpuFamily {
             CpuFamily::Arm32 | CpuFamily::Arm64 | CpuFamily::Arm64_32 => Some("pc"),
             CpuFamily::Ppc32 | CpuFamily::Ppc64 => Some("srr0"),
             CpuFamily::Mips32 | CpuFamily::Mips64 => Some("pc"),
+            CpuFamily::Riscv32 | CpuFamily::Riscv64 => Some("pc"),
             CpuFamily::Wasm32 => None,
             CpuFamily::Unknown => None,
         }
@@ -212,6 +229,7 @@ impl CpuFamily {
             CpuFamily::Arm64 | CpuFamily::Arm64_32 => ARM64.get(index),
             CpuFamily::Arm32 => ARM.get(index),
             CpuFamily::Mips32 | CpuFamily::Mips64 => MIPS.get(index),
+            CpuFamily::Riscv32 | CpuFamily::Riscv64 => RISCV.get(index),
             _ => None,
         };
 
@@ -286,6 +304,8 @@ pub enum Arch {
     Arm64_32V8 = 902,
     Arm64_32Unknown = 999,
     Wasm32 = 1001,
+    Riscv32 = 1101,
+    Riscv64 = 1201,
 }
 
 impl Arch {
@@ -332,6 +352,8 @@ impl Arch {
             902 => Arch::Arm64_32V8,
             999 => Arch::Arm64_32Unknown,
             1001 => Arch::Wasm32,
+            1101 => Arch::Riscv32,
+            1201 => Arch::Riscv64,
             _ => Arch::Unknown,
         }
     }
@@ -369,6 +391,8 @@ impl Arch {
             Arch::Mips64 => CpuFamily::Mips64,
             Arch::Arm64_32 | Arch::Arm64_32V8 | Arch::Arm64_32Unknown => CpuFamily::Arm64_32,
             Arch::Wasm32 => CpuFamily::Wasm32,
+            Arch::Riscv32 => CpuFamily::Riscv32,
+            Arch::Riscv64 => CpuFamily::Riscv64,
         }
     }
 
@@ -420,6 +444,8 @@ impl Arch {
             Arch::Arm64_32 => "arm64_32",
             Arch::Arm64_32V8 => "arm64_32_v8",
             Arch::Arm64_32Unknown => "arm64_32_unknown",
+            Arch::Riscv32 => "riscv32",
+            Arch::Riscv64 => "riscv64",
         }
     }
 
@@ -503,6 +529,10 @@ impl str::FromStr for Arch {
             // wasm extensions
             "wasm32" => Arch::Wasm32,
 
+            // RISC-V, where breakpad names the 32-bit variant "riscv"
+            "riscv32" | "riscv" => Arch::Riscv32,
+            "riscv64" => Arch::Riscv64,
+
             _ => return Err(UnknownArchError),
         })
     }
@@ -544,6 +574,8 @@ pub enum Language {
     Rust = 7,
     Swift = 8,
     CSharp = 9,
+    Dart = 10,
+    Kotlin = 11,
 }
 
 impl Language {
@@ -571,6 +603,8 @@ impl Language {
             7 => Self::Rust,
             8 => Self::Swift,
             9 => Self::CSharp,
+            10 => Self::Dart,
+            11 => Self::Kotlin,
             _ => Self::Unknown,
         }
     }
@@ -604,8 +638,135 @@ impl Language {
             Language::Rust => "rust",
             Language::Swift => "swift",
             Language::CSharp => "csharp",
+            Language::Dart => "dart",
+            Language::Kotlin => "kotlin",
         }
     }
+
+    /// Guesses the language of a source file from its extension.
+    ///
+    /// Both forward and backward slashes are accepted as path separators. Returns
+    /// `Language::Unknown` for unknown extensions and for C headers (`.h`), which are shared between
+    /// C, C++ and Objective-C.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use symbolic_common::Language;
+    ///
+    /// assert_eq!(Language::from_path("src/main.rs"), Language::Rust);
+    /// assert_eq!(Language::from_path(r"C:\code\app.cpp"), Language::Cpp);
+    /// assert_eq!(Language::from_path("include/foo.h"), Language::Unknown);
+    /// ```
+    pub fn from_path(path: &str) -> Language {
+        let file_name = match path.rfind(&['/', '\\'][..]) {
+            Some(index) => &path[index + 1..],
+            None => path,
+        };
+
+        let extension = match file_name.rfind('.') {
+            Some(index) => &file_name[index + 1..],
+            None => return Language::Unknown,
+        };
+
+        // `.C` and `.H` are conventional extensions for C++, so match case-sensitively first.
+        match extension {
+            "C" | "H" => return Language::Cpp,
+            "M" => return Language::ObjCpp,
+            _ => (),
+        }
+
+        match extension.to_ascii_lowercase().as_str() {
+            "c" => Language::C,
+            "cc" | "cpp" | "cxx" | "c++" | "cp" | "hh" | "hpp" | "hxx" | "h++" | "inl" | "ipp"
+            | "tcc" => Language::Cpp,
+            "d" | "di" => Language::D,
+            "go" => Language::Go,
+            "m" => Language::ObjC,
+            "mm" => Language::ObjCpp,
+            "rs" => Language::Rust,
+            "swift" => Language::Swift,
+            "cs" => Language::CSharp,
+            "dart" => Language::Dart,
+            "kt" | "kts" => Language::Kotlin,
+            _ => Language::Unknown,
+        }
+    }
+
+    /// Guesses the language of a symbol from its mangling scheme.
+    ///
+    /// This recognizes Itanium and MSVC mangled C++ names, legacy and v0 Rust names, Swift names,
+    /// D names, Kotlin/Native names and Objective-C method names. The checks are purely syntactic, so a positive result
+    /// does not guarantee that the name can be demangled. Returns `Language::Unknown` if the scheme
+    /// is not recognized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use symbolic_common::Language;
+    ///
+    /// assert_eq!(Language::from_mangled("_ZN3foo3barEv"), Language::Cpp);
+    /// assert_eq!(Language::from_mangled("_RNvCs1234_3foo3bar"), Language::Rust);
+    /// assert_eq!(Language::from_mangled("-[NSObject init]"), Language::ObjC);
+    /// assert_eq!(Language::from_mangled("main"), Language::Unknown);
+    /// ```
+    pub fn from_mangled(symbol: &str) -> Language {
+        if (symbol.starts_with("-[") || symbol.starts_with("+[")) && symbol.ends_with(']') {
+            return Language::ObjC;
+        }
+
+        // Apple platforms prepend an additional underscore to all symbols.
+        let trimmed = symbol.strip_prefix('_').unwrap_or(symbol);
+
+        if trimmed.starts_with("_R") || symbol.starts_with("_R") {
+            return Language::Rust;
+        }
+
+        for prefix in &["_Z", "__Z", "___Z"] {
+            if let Some(rest) = symbol.strip_prefix(prefix) {
+                // Legacy Rust symbols use Itanium mangling with a trailing hash `17h<16 hex>E`.
+                if rest.starts_with('N') && is_legacy_rust_hash(rest) {
+                    return Language::Rust;
+                }
+                return Language::Cpp;
+            }
+        }
+
+        if symbol.starts_with('?') || symbol.starts_with("@?") {
+            return Language::Cpp;
+        }
+
+        for prefix in &["$s", "$S", "$e", "_T0"] {
+            if symbol.starts_with(prefix) || trimmed.starts_with(prefix) {
+                return Language::Swift;
+            }
+        }
+
+        if symbol.starts_with("kfun:") {
+            return Language::Kotlin;
+        }
+
+        if let Some(rest) = symbol.strip_prefix("_D") {
+            if rest.starts_with(|c: char| c.is_ascii_digit()) {
+                return Language::D;
+            }
+        }
+
+        Language::Unknown
+    }
+}
+
+/// Checks whether an Itanium mangled name ends in the hash appended by the legacy Rust mangling.
+fn is_legacy_rust_hash(mangled: &str) -> bool {
+    // The hash is an additional path segment of the form `17h` followed by 16 hex digits.
+    let mangled = mangled.strip_suffix('E').unwrap_or(mangled);
+    let len = mangled.len();
+    if len < 19 || !mangled.is_char_boundary(len - 19) {
+        return false;
+    }
+
+    let (prefix, hash) = mangled[len - 19..].split_at(3);
+    prefix == "17h" && hash.chars().all(|c| c.is_ascii_hexdigit())
 }
 
 impl Default for Language {
@@ -627,6 +788,8 @@ impl fmt::Display for Language {
             Language::Rust => "Rust",
             Language::Swift => "Swift",
             Language::CSharp => "C#",
+            Language::Dart => "Dart",
+            Language::Kotlin => "Kotlin",
         };
 
         write!(f, "{}", formatted)
@@ -647,6 +810,8 @@ impl str::FromStr for Language {
             "objcpp" => Language::ObjCpp,
             "rust" => Language::Rust,
             "swift" => Language::Swift,
+            "dart" => Language::Dart,
+            "kotlin" => Language::Kotlin,
             _ => return Err(UnknownLanguageError),
         })
     }
@@ -947,4 +1112,60 @@ mod tests {
     fn test_cfi_register_name_none() {
         assert_eq!(CpuFamily::Arm64.cfi_register_name(33), None);
     }
+
+    #[test]
+    fn test_riscv_arch() {
+        assert_eq!("riscv".parse::<Arch>().unwrap(), Arch::Riscv32);
+        assert_eq!("riscv64".parse::<Arch>().unwrap(), Arch::Riscv64);
+        assert_eq!(Arch::from_u32(1201), Arch::Riscv64);
+        assert_eq!(Arch::Riscv64.cpu_family().pointer_size(), Some(8));
+        assert_eq!(CpuFamily::Riscv64.cfi_register_name(2), Some("sp"));
+        assert_eq!(CpuFamily::Riscv64.cfi_register_name(32), None);
+    }
+
+    #[test]
+    fn test_language_from_path() {
+        assert_eq!(Language::from_path("main.c"), Language::C);
+        assert_eq!(Language::from_path("/src/lib.CPP"), Language::Cpp);
+        assert_eq!(Language::from_path("foo/Bar.C"), Language::Cpp);
+        assert_eq!(Language::from_path("foo/bar.hpp"), Language::Cpp);
+        assert_eq!(Language::from_path(r"C:\\src\\app.mm"), Language::ObjCpp);
+        assert_eq!(Language::from_path("View.m"), Language::ObjC);
+        assert_eq!(Language::from_path("main.swift"), Language::Swift);
+        assert_eq!(Language::from_path("main.go"), Language::Go);
+        assert_eq!(Language::from_path("Program.cs"), Language::CSharp);
+        assert_eq!(Language::from_path("lib/main.dart"), Language::Dart);
+        assert_eq!(Language::from_path("MainActivity.kt"), Language::Kotlin);
+        assert_eq!(Language::from_path("foo.h"), Language::Unknown);
+        assert_eq!(Language::from_path("foo.d/bar"), Language::Unknown);
+        assert_eq!(Language::from_path("Makefile"), Language::Unknown);
+        assert_eq!(Language::from_path(""), Language::Unknown);
+    }
+
+    #[test]
+    fn test_language_from_mangled() {
+        assert_eq!(Language::from_mangled("_ZN3foo3barEv"), Language::Cpp);
+        assert_eq!(Language::from_mangled("__ZN3foo3barEv"), Language::Cpp);
+        assert_eq!(Language::from_mangled("?foo@@YAXXZ"), Language::Cpp);
+        assert_eq!(
+            Language::from_mangled("_ZN4core3fmt5write17h0123456789abcdefE"),
+            Language::Rust
+        );
+        assert_eq!(
+            Language::from_mangled("_RNvCs1234_3foo3bar"),
+            Language::Rust
+        );
+        assert_eq!(Language::from_mangled("$s4main3fooyyF"), Language::Swift);
+        assert_eq!(Language::from_mangled("_$s4main3fooyyF"), Language::Swift);
+        assert_eq!(Language::from_mangled("_T0s4main3fooyyF"), Language::Swift);
+        assert_eq!(Language::from_mangled("_D3foo3barFZv"), Language::D);
+        assert_eq!(Language::from_mangled("+[Foo bar:]"), Language::ObjC);
+        assert_eq!(
+            Language::from_mangled("kfun:com.example#main(){}"),
+            Language::Kotlin
+        );
+        assert_eq!(Language::from_mangled("main"), Language::Unknown);
+        assert_eq!(Language::from_mangled("_Data"), Language::Unknown);
+        assert_eq!(Language::from_mangled(""), Language::Unknown);
+    }
 }
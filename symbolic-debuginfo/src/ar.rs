@@ -0,0 +1,393 @@
+//! Support for static libraries in the Unix `ar` archive format.
+//!
+//! Static libraries (`.a` on Unix and `.lib` on Windows) are `ar` archives containing relocatable
+//! object files. Both the GNU and BSD variants of the format are supported, as well as GNU thin
+//! archives, which only reference their members by path instead of embedding them.
+
+use std::error::Error;
+use std::fmt;
+use std::str;
+
+use thiserror::Error;
+
+use symbolic_common::{DebugId, Uuid};
+
+use crate::object::Object;
+
+/// The magic at the start of a regular archive.
+const AR_MAGIC: &[u8] = b"!<arch>\n";
+/// The magic at the start of a GNU thin archive.
+const THIN_MAGIC: &[u8] = b"!<thin>\n";
+/// The size of the header preceding each member.
+const HEADER_SIZE: usize = 60;
+/// The terminator of every member header.
+const HEADER_END: &[u8] = b"`\n";
+/// The prefix of BSD member names that are stored after the header.
+const BSD_NAME_PREFIX: &str = "#1/";
+
+/// The error type for [`StaticLibraryError`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StaticLibraryErrorKind {
+    /// The file does not start with the archive magic.
+    BadMagic,
+
+    /// A member header is truncated or contains invalid fields.
+    BadHeader,
+
+    /// A member name refers outside of the long names table.
+    BadName,
+
+    /// The contents of a thin archive member are not embedded in the archive.
+    ThinMember,
+
+    /// The member does not contain a supported object file.
+    BadObject,
+}
+
+impl fmt::Display for StaticLibraryErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BadMagic => write!(f, "invalid archive magic"),
+            Self::BadHeader => write!(f, "invalid archive member header"),
+            Self::BadName => write!(f, "invalid archive member name"),
+            Self::ThinMember => write!(f, "thin archive member is not embedded"),
+            Self::BadObject => write!(f, "invalid object in archive member"),
+        }
+    }
+}
+
+/// An error when dealing with [`StaticLibrary`](struct.StaticLibrary.html).
+#[derive(Debug, Error)]
+#[error("{kind}")]
+pub struct StaticLibraryError {
+    kind: StaticLibraryErrorKind,
+    #[source]
+    source: Option<Box<dyn Error + Send + Sync + 'static>>,
+}
+
+impl StaticLibraryError {
+    /// Creates a new static library error from a known kind of error as well as an arbitrary error
+    /// payload.
+    fn new<E>(kind: StaticLibraryErrorKind, source: E) -> Self
+    where
+        E: Into<Box<dyn Error + Send + Sync>>,
+    {
+        let source = Some(source.into());
+        Self { kind, source }
+    }
+
+    /// Returns the corresponding [`StaticLibraryErrorKind`] for this error.
+    pub fn kind(&self) -> StaticLibraryErrorKind {
+        self.kind
+    }
+}
+
+impl From<StaticLibraryErrorKind> for StaticLibraryError {
+    fn from(kind: StaticLibraryErrorKind) -> Self {
+        Self { kind, source: None }
+    }
+}
+
+/// A static library in the `ar` archive format.
+///
+/// The members of a static library are usually relocatable object files, which can be inspected
+/// with [`StaticLibraryMember::object`]. Symbol tables and the long names table of the archive are
+/// skipped during iteration.
+///
+/// # Example
+///
+/// ```no_run
+/// use symbolic_debuginfo::ar::StaticLibrary;
+///
+/// let data = std::fs::read("libfoo.a").unwrap();
+/// let library = StaticLibrary::parse(&data).unwrap();
+///
+/// for member in library.members() {
+///     let member = member.unwrap();
+///     println!("{} {}", member.name(), member.debug_id());
+/// }
+/// ```
+#[derive(Clone, Copy)]
+pub struct StaticLibrary<'d> {
+    data: &'d [u8],
+    thin: bool,
+}
+
+impl<'d> StaticLibrary<'d> {
+    /// Tests whether the buffer contains a static library.
+    pub fn test(data: &[u8]) -> bool {
+        data.starts_with(AR_MAGIC) || data.starts_with(THIN_MAGIC)
+    }
+
+    /// Tries to parse a static library from the given slice.
+    pub fn parse(data: &'d [u8]) -> Result<Self, StaticLibraryError> {
+        let thin = if data.starts_with(AR_MAGIC) {
+            false
+        } else if data.starts_with(THIN_MAGIC) {
+            true
+        } else {
+            return Err(StaticLibraryErrorKind::BadMagic.into());
+        };
+
+        Ok(StaticLibrary { data, thin })
+    }
+
+    /// Returns `true` if this is a thin archive.
+    ///
+    /// Thin archives only store the paths of their members relative to the archive. The contents
+    /// of the members have to be loaded from the file system.
+    pub fn is_thin(&self) -> bool {
+        self.thin
+    }
+
+    /// Returns an iterator over the members of this library.
+    pub fn members(&self) -> StaticLibraryMembers<'d> {
+        StaticLibraryMembers {
+            data: self.data,
+            offset: AR_MAGIC.len(),
+            long_names: &[],
+            thin: self.thin,
+            finished: false,
+        }
+    }
+
+    /// Returns the raw data of the library.
+    pub fn data(&self) -> &'d [u8] {
+        self.data
+    }
+}
+
+impl fmt::Debug for StaticLibrary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StaticLibrary")
+            .field("thin", &self.thin)
+            .field("members", &self.members().count())
+            .finish()
+    }
+}
+
+/// A member of a [`StaticLibrary`].
+#[derive(Clone)]
+pub struct StaticLibraryMember<'d> {
+    name: &'d str,
+    offset: usize,
+    size: usize,
+    data: Option<&'d [u8]>,
+}
+
+impl<'d> StaticLibraryMember<'d> {
+    /// The name of the member.
+    ///
+    /// For thin archives, this is the path of the member relative to the archive.
+    pub fn name(&self) -> &'d str {
+        self.name
+    }
+
+    /// The offset of the member's header within the archive.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The size of the member's contents in bytes.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The contents of the member, or `None` if this member of a thin archive is not embedded.
+    pub fn data(&self) -> Option<&'d [u8]> {
+        self.data
+    }
+
+    /// Parses the object file contained in this member.
+    pub fn object(&self) -> Result<Object<'d>, StaticLibraryError> {
+        let data = self.data.ok_or(StaticLibraryErrorKind::ThinMember)?;
+        Object::parse(data)
+            .map_err(|e| StaticLibraryError::new(StaticLibraryErrorKind::BadObject, e))
+    }
+
+    /// The debug identifier of this member.
+    ///
+    /// Relocatable objects rarely declare identifiers, so this falls back to an identifier
+    /// synthesized from a SHA-1 hash of the member name and contents. The synthesized identifier is
+    /// stable across reads of the same archive and distinguishes equal objects stored under different
+    /// names.
+    /// Members of thin archives are identified by their name only.
+    pub fn debug_id(&self) -> DebugId {
+        if let Ok(object) = self.object() {
+            if let Ok(identifiers) = object.identifiers() {
+                return identifiers.debug_id;
+            }
+        }
+
+        self.synthesized_id()
+    }
+
+    /// Computes an identifier from the name and contents of this member.
+    fn synthesized_id(&self) -> DebugId {
+        let mut hash = sha1::Sha1::new();
+        hash.update(self.name.as_bytes());
+        hash.update(&[0]);
+        hash.update(self.data.unwrap_or_default());
+
+        let digest = hash.digest().bytes();
+        match Uuid::from_slice(&digest[..16]) {
+            Ok(uuid) => DebugId::from_uuid(uuid),
+            Err(_) => DebugId::nil(),
+        }
+    }
+}
+
+impl fmt::Debug for StaticLibraryMember<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StaticLibraryMember")
+            .field("name", &self.name)
+            .field("offset", &self.offset)
+            .field("size", &self.size)
+            .field("thin", &self.data.is_none())
+            .finish()
+    }
+}
+
+/// An iterator over the members of a [`StaticLibrary`].
+pub struct StaticLibraryMembers<'d> {
+    data: &'d [u8],
+    offset: usize,
+    long_names: &'d [u8],
+    thin: bool,
+    finished: bool,
+}
+
+impl<'d> StaticLibraryMembers<'d> {
+    /// Reads the member at the current offset, or `None` if it is an internal table.
+    fn read_member(&mut self) -> Result<Option<StaticLibraryMember<'d>>, StaticLibraryError> {
+        let offset = self.offset;
+        let header = self
+            .data
+            .get(offset..offset + HEADER_SIZE)
+            .ok_or(StaticLibraryErrorKind::BadHeader)?;
+
+        if &header[58..] != HEADER_END {
+            return Err(StaticLibraryErrorKind::BadHeader.into());
+        }
+
+        let raw_name = header_field(&header[..16])?;
+        let size = header_field(&header[48..58])?
+            .parse::<usize>()
+            .map_err(|e| StaticLibraryError::new(StaticLibraryErrorKind::BadHeader, e))?;
+
+        let start = offset + HEADER_SIZE;
+        let is_table = is_table_name(raw_name);
+
+        // Members of thin archives are stored externally, except for the internal tables.
+        let embedded = !self.thin || is_table;
+        let data = if embedded {
+            let end = start.checked_add(size);
+            let data = end
+                .and_then(|end| self.data.get(start..end))
+                .ok_or(StaticLibraryErrorKind::BadHeader)?;
+            self.offset = start + size + (size & 1);
+            Some(data)
+        } else {
+            self.offset = start;
+            None
+        };
+
+        if raw_name == "//" {
+            self.long_names = data.unwrap_or_default();
+            return Ok(None);
+        } else if is_table {
+            return Ok(None);
+        }
+
+        let (name, data) = if let Some(len) = raw_name.strip_prefix(BSD_NAME_PREFIX) {
+            // BSD archives store long names in front of the contents, which includes the name.
+            let len = len
+                .parse::<usize>()
+                .map_err(|e| StaticLibraryError::new(StaticLibraryErrorKind::BadName, e))?;
+            let data = data.ok_or(StaticLibraryErrorKind::BadName)?;
+            if len > data.len() {
+                return Err(StaticLibraryErrorKind::BadName.into());
+            }
+            let (name, data) = data.split_at(len);
+            let name = name.split(|&b| b == 0).next().unwrap_or_default();
+            (to_str(name)?, Some(data))
+        } else if let Some(index) = raw_name.strip_prefix('/') {
+            // GNU and MSVC archives reference long names by their offset in the long names table.
+            let index = index
+                .parse::<usize>()
+                .map_err(|e| StaticLibraryError::new(StaticLibraryErrorKind::BadName, e))?;
+            let names = self
+                .long_names
+                .get(index..)
+                .ok_or(StaticLibraryErrorKind::BadName)?;
+            let end = names
+                .iter()
+                .position(|&b| b == b'\n' || b == 0)
+                .unwrap_or(names.len());
+            let name = &names[..end];
+            (to_str(name.strip_suffix(b"/").unwrap_or(name))?, data)
+        } else {
+            (raw_name.strip_suffix('/').unwrap_or(raw_name), data)
+        };
+
+        // BSD symbol tables can only be identified once their long name has been resolved.
+        if name.starts_with("__.SYMDEF") {
+            return Ok(None);
+        }
+
+        let size = match data {
+            Some(data) => data.len(),
+            None => size,
+        };
+
+        Ok(Some(StaticLibraryMember {
+            name,
+            offset,
+            size,
+            data,
+        }))
+    }
+}
+
+impl<'d> Iterator for StaticLibraryMembers<'d> {
+    type Item = Result<StaticLibraryMember<'d>, StaticLibraryError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.finished && self.offset < self.data.len() {
+            match self.read_member() {
+                Ok(Some(member)) => return Some(Ok(member)),
+                Ok(None) => continue,
+                Err(error) => {
+                    self.finished = true;
+                    return Some(Err(error));
+                }
+            }
+        }
+
+        None
+    }
+}
+
+impl std::iter::FusedIterator for StaticLibraryMembers<'_> {}
+
+/// Reads a space-padded ASCII field from a member header.
+fn header_field(field: &[u8]) -> Result<&str, StaticLibraryError> {
+    match str::from_utf8(field) {
+        Ok(field) => Ok(field.trim_end_matches(' ')),
+        Err(e) => Err(StaticLibraryError::new(
+            StaticLibraryErrorKind::BadHeader,
+            e,
+        )),
+    }
+}
+
+/// Converts a name or header field to a string.
+fn to_str(bytes: &[u8]) -> Result<&str, StaticLibraryError> {
+    str::from_utf8(bytes).map_err(|e| StaticLibraryError::new(StaticLibraryErrorKind::BadName, e))
+}
+
+/// Checks whether a raw member name refers to a symbol table or the long names table.
+fn is_table_name(name: &str) -> bool {
+    matches!(name, "/" | "//" | "/SYM64/" | "/<ECSYMBOLS>/") || name.starts_with("__.SYMDEF")
+}
@@ -1,10 +1,14 @@
 use std::borrow::Cow;
+use std::collections::HashSet;
 use std::fmt;
 use std::iter::FromIterator;
-use std::ops::{Bound, Deref, RangeBounds};
+use std::ops::{Bound, Deref, Range, RangeBounds};
 use std::str::FromStr;
+use std::sync::Mutex;
 
-use symbolic_common::{clean_path, join_path, Arch, CodeId, DebugId, Name};
+use symbolic_common::{
+    clean_path, join_path, Arch, CodeId, DebugId, Language, Name, NameMangling, PathRemapper,
+};
 
 /// An error returned for unknown or invalid `ObjectKinds`.
 #[derive(Debug)]
@@ -199,6 +203,77 @@ impl FromStr for FileFormat {
     }
 }
 
+/// Options to control how much of an object file is parsed up front.
+///
+/// By default, objects are fully parsed, including their symbol tables. When only identifiers
+/// such as the debug id or code id are needed, parsing can be restricted to reduce latency on very
+/// large files. Options that do not apply to a file format are ignored. Currently, only ELF objects
+/// honor these options.
+///
+/// # Example
+///
+/// ```
+/// use symbolic_debuginfo::ObjectOptions;
+///
+/// let options = ObjectOptions::new()
+///     .symbols(false)
+///     .max_section_size(Some(64 * 1024 * 1024));
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ObjectOptions {
+    pub(crate) symbols: bool,
+    pub(crate) notes: bool,
+    pub(crate) max_section_size: Option<u64>,
+}
+
+impl ObjectOptions {
+    /// Options that fully parse objects.
+    pub const fn new() -> Self {
+        Self {
+            symbols: true,
+            notes: true,
+            max_section_size: None,
+        }
+    }
+
+    /// Determines whether symbol tables, dynamic symbols and relocations are parsed.
+    ///
+    /// If disabled, the object does not list any symbols.
+    pub const fn symbols(mut self, symbols: bool) -> Self {
+        self.symbols = symbols;
+        self
+    }
+
+    /// Determines whether note segments are scanned for identifiers.
+    ///
+    /// If disabled, the build id of ELF objects is only read from the `.note.gnu.build-id`
+    /// section instead of all `PT_NOTE` program headers, which can be large in core files.
+    pub const fn notes(mut self, notes: bool) -> Self {
+        self.notes = notes;
+        self
+    }
+
+    /// Sets the maximum size in bytes of sections that are loaded.
+    ///
+    /// Larger sections, such as huge symbol tables or debug information sections, are treated as
+    /// if they were missing. For compressed sections, this applies to the decompressed size.
+    pub const fn max_section_size(mut self, max_section_size: Option<u64>) -> Self {
+        self.max_section_size = max_section_size;
+        self
+    }
+
+    /// Returns `true` if a section of the given size exceeds the configured maximum.
+    pub(crate) fn exceeds_section_size(&self, size: u64) -> bool {
+        matches!(self.max_section_size, Some(max) if size > max)
+    }
+}
+
+impl Default for ObjectOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// A symbol from a symbol table.
 #[derive(Clone, Default, Eq, PartialEq)]
 pub struct Symbol<'data> {
@@ -234,6 +309,15 @@ impl<'data> Symbol<'data> {
     pub fn contains(&self, address: u64) -> bool {
         address >= self.address && (self.size == 0 || address < self.address + self.size)
     }
+
+    /// Converts this symbol into an owned version that no longer borrows from the object.
+    pub fn into_owned(self) -> Symbol<'static> {
+        Symbol {
+            name: self.name.map(|name| Cow::Owned(name.into_owned())),
+            address: self.address,
+            size: self.size,
+        }
+    }
 }
 
 impl<'d> fmt::Debug for Symbol<'d> {
@@ -246,6 +330,36 @@ impl<'d> fmt::Debug for Symbol<'d> {
     }
 }
 
+/// A symbol exported by an object file for dynamic linking.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Export<'data> {
+    /// The name of the exported symbol.
+    pub name: Cow<'data, str>,
+
+    /// The address of the exported symbol relative to the load address of the object.
+    pub address: u64,
+}
+
+/// A symbol imported by an object file from a shared library.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Import<'data> {
+    /// The name of the imported symbol.
+    pub name: Cow<'data, str>,
+
+    /// The name of the library providing the symbol, if declared by the object file.
+    ///
+    /// ELF files do not associate imported symbols with libraries, so this is always `None` for
+    /// them.
+    pub library: Option<Cow<'data, str>>,
+
+    /// The address of the slot receiving the resolved symbol address, relative to the load address
+    /// of the object.
+    ///
+    /// This is the import address table entry on Windows, the bound pointer on macOS, and the
+    /// relocated GOT entry on Linux, if known.
+    pub address: Option<u64>,
+}
+
 /// IntoIterator type for [`SymbolMap`](struct.SymbolMap.html).
 pub type SymbolMapIter<'data> = std::vec::IntoIter<Symbol<'data>>;
 
@@ -259,6 +373,8 @@ pub type SymbolMapIter<'data> = std::vec::IntoIter<Symbol<'data>>;
 /// `SymbolMap` also exposes a read-only view on the sorted slice of symbols. It can be converted to
 /// and from lists of symbols.
 ///
+/// To also infer the size of the last symbol in every section, use [`SymbolMap::iter_sized`].
+///
 /// ## Example
 ///
 /// ```rust
@@ -280,6 +396,7 @@ pub type SymbolMapIter<'data> = std::vec::IntoIter<Symbol<'data>>;
 #[derive(Clone, Debug, Default)]
 pub struct SymbolMap<'data> {
     symbols: Vec<Symbol<'data>>,
+    sections: Vec<Range<u64>>,
 }
 
 impl<'data> SymbolMap<'data> {
@@ -287,6 +404,91 @@ impl<'data> SymbolMap<'data> {
     pub fn new() -> Self {
         SymbolMap {
             symbols: Vec::new(),
+            sections: Vec::new(),
+        }
+    }
+
+    /// Returns the address ranges of code sections, sorted by start address.
+    ///
+    /// These are used by [`iter_sized`](Self::iter_sized) to bound the sizes of symbols. Symbol
+    /// maps created by objects contain the executable sections of the object, if available.
+    pub fn sections(&self) -> &[Range<u64>] {
+        &self.sections
+    }
+
+    /// Sets the address ranges of code sections containing the symbols.
+    ///
+    /// Ranges are relative to the load address of the object, just like symbol addresses.
+    pub fn set_sections<I>(&mut self, sections: I)
+    where
+        I: IntoIterator<Item = Range<u64>>,
+    {
+        self.sections = sections
+            .into_iter()
+            .filter(|section| section.start < section.end)
+            .collect();
+        self.sections.sort_by_key(|section| section.start);
+    }
+
+    /// Returns an iterator over all symbols with inferred sizes.
+    ///
+    /// Symbols without a size extend up to the next symbol. The last symbol extends to the end of
+    /// its section. Additionally, sizes are clipped to the end of the section containing the symbol,
+    /// so that symbols do not reach into padding or unrelated data. If no section is known for a
+    /// symbol without a size, its size remains `0`.
+    pub fn iter_sized(&self) -> SymbolMapSizedIter<'data, '_> {
+        SymbolMapSizedIter {
+            map: self,
+            index: 0,
+        }
+    }
+
+    /// Returns an iterator over all named symbols with inferred sizes and names for demangling.
+    ///
+    /// This is equivalent to [`iter_sized`](Self::iter_sized), except that symbols without a name
+    /// are skipped. Names are not demangled, yet. Instead, the mangling scheme and language are
+    /// detected once the name is demangled, for instance with `symbolic-demangle`. This avoids the
+    /// cost of demangling names that are never printed.
+    pub fn iter_named(&self) -> SymbolMapNamedIter<'data, '_> {
+        SymbolMapNamedIter {
+            inner: self.iter_sized(),
+        }
+    }
+
+    /// Returns the section containing the given address.
+    fn section(&self, address: u64) -> Option<&Range<u64>> {
+        let index = self
+            .sections
+            .partition_point(|section| section.start <= address);
+        let section = self.sections.get(index.checked_sub(1)?)?;
+        if section.contains(&address) {
+            Some(section)
+        } else {
+            None
+        }
+    }
+
+    /// Computes the inferred size of the symbol at the given index.
+    fn sized_symbol(&self, index: usize) -> Symbol<'data> {
+        let symbol = &self.symbols[index];
+
+        let mut end = if symbol.size > 0 {
+            symbol.address.checked_add(symbol.size)
+        } else {
+            self.symbols.get(index + 1).map(|next| next.address)
+        };
+
+        if let Some(section) = self.section(symbol.address) {
+            end = Some(match end {
+                Some(end) => end.min(section.end),
+                None => section.end,
+            });
+        }
+
+        Symbol {
+            name: symbol.name.clone(),
+            address: symbol.address,
+            size: end.map_or(0, |end| end.saturating_sub(symbol.address)),
         }
     }
 
@@ -353,6 +555,69 @@ impl<'data> SymbolMap<'data> {
     }
 }
 
+/// An iterator over symbols with inferred sizes, returned by [`SymbolMap::iter_sized`].
+#[derive(Clone, Debug)]
+pub struct SymbolMapSizedIter<'data, 'map> {
+    map: &'map SymbolMap<'data>,
+    index: usize,
+}
+
+impl<'data> Iterator for SymbolMapSizedIter<'data, '_> {
+    type Item = Symbol<'data>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.map.symbols.len() {
+            return None;
+        }
+
+        let symbol = self.map.sized_symbol(self.index);
+        self.index += 1;
+        Some(symbol)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.map.symbols.len().saturating_sub(self.index);
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for SymbolMapSizedIter<'_, '_> {}
+
+/// A named symbol with an inferred size, returned by [`SymbolMap::iter_named`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NamedSymbol<'data> {
+    /// The name of the symbol, which may still be mangled.
+    pub name: Name<'data>,
+    /// The relative address of this symbol.
+    pub address: u64,
+    /// The inferred size of this symbol, or `0` if unknown.
+    pub size: u64,
+}
+
+/// An iterator over named symbols, returned by [`SymbolMap::iter_named`].
+#[derive(Clone, Debug)]
+pub struct SymbolMapNamedIter<'data, 'map> {
+    inner: SymbolMapSizedIter<'data, 'map>,
+}
+
+impl<'data> Iterator for SymbolMapNamedIter<'data, '_> {
+    type Item = NamedSymbol<'data>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for symbol in &mut self.inner {
+            if let Some(name) = symbol.name {
+                return Some(NamedSymbol {
+                    name: Name::from(name),
+                    address: symbol.address,
+                    size: symbol.size,
+                });
+            }
+        }
+
+        None
+    }
+}
+
 impl<'d> Deref for SymbolMap<'d> {
     type Target = [Symbol<'d>];
 
@@ -412,7 +677,10 @@ impl<'d> From<Vec<Symbol<'d>>> for SymbolMap<'d> {
             })
         }
 
-        SymbolMap { symbols }
+        SymbolMap {
+            symbols,
+            sections: Vec::new(),
+        }
     }
 }
 
@@ -425,38 +693,266 @@ impl<'d> FromIterator<Symbol<'d>> for SymbolMap<'d> {
     }
 }
 
+/// Attributes of a symbol used to rank overlapping symbols in a [`SymbolIntervalMap`].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct SymbolFlags {
+    /// The symbol points to Thumb code on ARM.
+    pub thumb: bool,
+    /// The symbol has local binding and is not visible outside of its object file.
+    pub local: bool,
+}
+
+#[derive(Clone, Debug)]
+struct SymbolInterval<'data> {
+    symbol: Symbol<'data>,
+    flags: SymbolFlags,
+    end: u64,
+}
+
+impl SymbolInterval<'_> {
+    fn contains(&self, address: u64) -> bool {
+        address >= self.symbol.address && address < self.end
+    }
+
+    /// Returns the key by which overlapping symbols are ranked, lowest first.
+    fn rank(&self) -> impl Ord + '_ {
+        (
+            self.flags,
+            self.end - self.symbol.address,
+            self.symbol.address,
+            self.symbol.name(),
+        )
+    }
+}
+
+/// An interval map of symbols, supporting overlapping symbols and aliases.
+///
+/// Contrary to [`SymbolMap`], which only retains a single symbol at every address, this map keeps
+/// all symbols and returns every symbol covering an address. This includes aliases sharing the same
+/// address, as well as symbols nested inside other symbols.
+///
+/// Symbols with a size of zero extend up to the next symbol with a higher address, or to the end of
+/// the address space if there is no such symbol.
+///
+/// When multiple symbols cover an address, [`lookup`](Self::lookup) returns a deterministic best
+/// match: ARM symbols are preferred over Thumb symbols, global over local symbols, and smaller over
+/// larger symbols. Remaining ties are broken by address and name.
+///
+/// ## Example
+///
+/// ```rust
+/// # use symbolic_debuginfo::{Symbol, SymbolIntervalMap};
+/// let map: SymbolIntervalMap = vec![
+///     Symbol { name: Some("outer".into()), address: 0x1000, size: 0x100 },
+///     Symbol { name: Some("inner".into()), address: 0x1010, size: 0x10 },
+///     Symbol { name: Some("alias".into()), address: 0x1000, size: 0x100 },
+/// ]
+/// .into_iter()
+/// .collect();
+///
+/// assert_eq!(map.lookup(0x1018).unwrap().name(), Some("inner"));
+/// assert_eq!(map.lookup_range(0x1018).len(), 3);
+/// assert_eq!(map.lookup_range(0x1020).len(), 2);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct SymbolIntervalMap<'data> {
+    /// Intervals sorted by start address.
+    intervals: Vec<SymbolInterval<'data>>,
+    /// The maximum end address of all intervals up to and including the same index.
+    max_ends: Vec<u64>,
+}
+
+impl<'data> SymbolIntervalMap<'data> {
+    /// Creates a new, empty symbol interval map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a symbol interval map from symbols and their flags.
+    pub fn from_symbols<I>(symbols: I) -> Self
+    where
+        I: IntoIterator<Item = (Symbol<'data>, SymbolFlags)>,
+    {
+        let mut intervals: Vec<_> = symbols
+            .into_iter()
+            .map(|(symbol, flags)| SymbolInterval {
+                end: symbol.address.saturating_add(symbol.size),
+                symbol,
+                flags,
+            })
+            .collect();
+
+        intervals.sort_by_key(|interval| interval.symbol.address);
+
+        // Extend zero-sized symbols up to the next higher symbol address.
+        let mut next_address = u64::MAX;
+        for index in (0..intervals.len()).rev() {
+            let interval = &mut intervals[index];
+            if interval.symbol.size == 0 {
+                interval.end = next_address;
+            }
+
+            if index > 0 && intervals[index - 1].symbol.address < intervals[index].symbol.address {
+                next_address = intervals[index].symbol.address;
+            }
+        }
+
+        let max_ends = intervals
+            .iter()
+            .scan(0, |max_end, interval| {
+                *max_end = interval.end.max(*max_end);
+                Some(*max_end)
+            })
+            .collect();
+
+        SymbolIntervalMap {
+            intervals,
+            max_ends,
+        }
+    }
+
+    /// Returns the number of symbols in this map.
+    pub fn len(&self) -> usize {
+        self.intervals.len()
+    }
+
+    /// Returns `true` if this map contains no symbols.
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// Returns all symbols covering the given address, ordered from the best to the worst match.
+    pub fn lookup_range(&self, address: u64) -> Vec<&Symbol<'data>> {
+        let mut matches: Vec<_> = self.intervals_at(address).collect();
+        matches.sort_by(|a, b| a.rank().cmp(&b.rank()));
+        matches
+            .into_iter()
+            .map(|interval| &interval.symbol)
+            .collect()
+    }
+
+    /// Looks up the best symbol covering the given address.
+    pub fn lookup(&self, address: u64) -> Option<&Symbol<'data>> {
+        self.intervals_at(address)
+            .min_by(|a, b| a.rank().cmp(&b.rank()))
+            .map(|interval| &interval.symbol)
+    }
+
+    /// Iterates all intervals containing the given address.
+    fn intervals_at(&self, address: u64) -> impl Iterator<Item = &SymbolInterval<'data>> {
+        // All candidates start at or before the address. Walking backwards, we can stop as soon as
+        // no preceding interval reaches beyond the address.
+        let count = self
+            .intervals
+            .partition_point(|interval| interval.symbol.address <= address);
+
+        self.intervals[..count]
+            .iter()
+            .zip(&self.max_ends)
+            .rev()
+            .take_while(move |(_, max_end)| **max_end > address)
+            .map(|(interval, _)| interval)
+            .filter(move |interval| interval.contains(address))
+    }
+}
+
+impl<'d> FromIterator<Symbol<'d>> for SymbolIntervalMap<'d> {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = Symbol<'d>>,
+    {
+        Self::from_symbols(iter.into_iter().map(|s| (s, SymbolFlags::default())))
+    }
+}
+
+/// Converts a possibly borrowed byte path to a string, retaining the borrow where possible.
+fn from_utf8_cow_lossy<'data>(input: &Cow<'data, [u8]>) -> Cow<'data, str> {
+    match input {
+        Cow::Borrowed(bytes) => String::from_utf8_lossy(bytes),
+        Cow::Owned(bytes) => Cow::Owned(String::from_utf8_lossy(bytes).into_owned()),
+    }
+}
+
+/// Describes how the path of a [`FileInfo`] was recorded in the debug information.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum FilePathKind {
+    /// The debug information does not specify how the path was recorded.
+    ///
+    /// This is the case for all formats other than DWARF.
+    #[default]
+    Unknown,
+    /// The file name is an absolute path, and the directory is not needed to locate the file.
+    Absolute,
+    /// The path is relative to the compilation directory.
+    ///
+    /// This applies to files in the compilation directory itself, as well as files in a relative
+    /// directory that has to be joined with the compilation directory.
+    CompDirRelative,
+    /// The file name is relative to an absolute directory from the directory table.
+    DirectoryRelative,
+}
+
 /// File information referred by [`LineInfo`](struct.LineInfo.html) comprising a directory and name.
 ///
 /// The file path is usually relative to a compilation directory. It might contain parent directory
 /// segments (`../`).
 #[derive(Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct FileInfo<'data> {
     /// The file's basename.
-    pub name: &'data [u8],
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "derive_serde::serialize_lossy")
+    )]
+    pub name: Cow<'data, [u8]>,
     /// Path to the file.
-    pub dir: &'data [u8],
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "derive_serde::serialize_lossy")
+    )]
+    pub dir: Cow<'data, [u8]>,
+    /// How the path of the file was recorded in the debug information.
+    pub path_kind: FilePathKind,
 }
 
 impl<'data> FileInfo<'data> {
     /// Creates a `FileInfo` from a joined path by trying to split it.
-    #[cfg(any(feature = "breakpad", feature = "ms", feature = "sourcebundle"))]
+    #[cfg(any(
+        feature = "breakpad",
+        feature = "macho",
+        feature = "ms",
+        feature = "sourcebundle"
+    ))]
     pub(crate) fn from_path(path: &'data [u8]) -> Self {
         let (dir, name) = symbolic_common::split_path_bytes(path);
 
         FileInfo {
-            name,
-            dir: dir.unwrap_or_default(),
+            name: Cow::Borrowed(name),
+            dir: Cow::Borrowed(dir.unwrap_or_default()),
+            path_kind: FilePathKind::Unknown,
+        }
+    }
+
+    /// Applies a [`PathRemapper`] to the directory and name of this file.
+    ///
+    /// The name is usually a plain file name, but some formats store full paths in it.
+    pub(crate) fn remap(mut self, remapper: Option<&PathRemapper>) -> Self {
+        if let Some(remapper) = remapper {
+            self.dir = remapper.remap_bytes(self.dir);
+            self.name = remapper.remap_bytes(self.name);
         }
+        self
     }
 
     /// The file name as UTF-8 string.
     pub fn name_str(&self) -> Cow<'data, str> {
-        String::from_utf8_lossy(self.name)
+        from_utf8_cow_lossy(&self.name)
     }
 
     /// Path to the file relative to the compilation directory.
     pub fn dir_str(&self) -> Cow<'data, str> {
-        String::from_utf8_lossy(self.dir)
+        from_utf8_cow_lossy(&self.dir)
     }
 
     /// The full path to the file, relative to the compilation directory.
@@ -464,29 +960,59 @@ impl<'data> FileInfo<'data> {
         let joined = join_path(&self.dir_str(), &self.name_str());
         clean_path(&joined).into_owned()
     }
+
+    /// Converts this file into an owned version that no longer borrows from the object.
+    pub fn into_owned(self) -> FileInfo<'static> {
+        FileInfo {
+            name: Cow::Owned(self.name.into_owned()),
+            dir: Cow::Owned(self.dir.into_owned()),
+            path_kind: self.path_kind,
+        }
+    }
+
+    /// Returns the number of bytes allocated for the owned parts of this file's path.
+    pub(crate) fn heap_size(&self) -> usize {
+        owned_len(&self.name) + owned_len(&self.dir)
+    }
+}
+
+/// Returns the length of owned data, or zero if the data is borrowed.
+#[allow(clippy::ptr_arg)] // needs to inspect the `Cow` variant
+fn owned_len(data: &Cow<'_, [u8]>) -> usize {
+    match data {
+        Cow::Borrowed(_) => 0,
+        Cow::Owned(owned) => owned.len(),
+    }
 }
 
 impl fmt::Debug for FileInfo<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("FileInfo")
-            .field("name", &String::from_utf8_lossy(self.name))
-            .field("dir", &String::from_utf8_lossy(self.dir))
+            .field("name", &String::from_utf8_lossy(&self.name))
+            .field("dir", &String::from_utf8_lossy(&self.dir))
+            .field("path_kind", &self.path_kind)
             .finish()
     }
 }
 
 /// File information comprising a compilation directory, relative path and name.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct FileEntry<'data> {
     /// Path to the compilation directory. File paths are relative to this.
-    pub compilation_dir: &'data [u8],
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "derive_serde::serialize_lossy")
+    )]
+    pub compilation_dir: Cow<'data, [u8]>,
     /// File name and path.
+    #[cfg_attr(feature = "serde", serde(flatten))]
     pub info: FileInfo<'data>,
 }
 
 impl<'data> FileEntry<'data> {
     /// Path to the compilation directory.
     pub fn compilation_dir_str(&self) -> Cow<'data, str> {
-        String::from_utf8_lossy(self.compilation_dir)
+        from_utf8_cow_lossy(&self.compilation_dir)
     }
 
     /// Absolute path to the file, including the compilation directory.
@@ -503,6 +1029,7 @@ impl fmt::Debug for FileEntry<'_> {
             .field("compilation_dir", &self.compilation_dir_str())
             .field("name", &self.name_str())
             .field("dir", &self.dir_str())
+            .field("path_kind", &self.path_kind)
             .finish()
     }
 }
@@ -517,6 +1044,7 @@ impl<'data> Deref for FileEntry<'data> {
 
 /// File and line number mapping for an instruction address.
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct LineInfo<'data> {
     /// The instruction address relative to the image base (load address).
     pub address: u64,
@@ -544,91 +1072,1075 @@ impl fmt::Debug for LineInfo<'_> {
     }
 }
 
-/// Debug information for a function.
-#[derive(Clone)]
-pub struct Function<'data> {
-    /// Relative instruction address of the start of the function.
-    pub address: u64,
-    /// Total code size covered by the function body, including inlined functions.
-    pub size: u64,
-    /// The name and language of the function symbol.
-    pub name: Name<'data>,
-    /// Path to the compilation directory. File paths are relative to this.
-    pub compilation_dir: &'data [u8],
-    /// Lines covered by this function, including inlined children.
-    pub lines: Vec<LineInfo<'data>>,
-    /// Functions that have been inlined into this function's body.
-    pub inlinees: Vec<Function<'data>>,
-    /// Specifies whether this function is inlined.
-    pub inline: bool,
+/// Encodes a signed delta so that small magnitudes result in small unsigned values.
+fn zigzag_encode(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
 }
 
-impl Function<'_> {
-    /// End address of the entire function body, including inlined functions.
-    ///
-    /// This address points at the first instruction after the function body.
-    pub fn end_address(&self) -> u64 {
-        self.address + self.size
-    }
+/// Decodes a value encoded with [`zigzag_encode`].
+fn zigzag_decode(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
 }
 
-impl fmt::Debug for Function<'_> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("Function")
-            .field("address", &format_args!("{:#x}", self.address))
-            .field("size", &format_args!("{:#x}", self.size))
-            .field("name", &self.name)
-            .field(
-                "compilation_dir",
-                &String::from_utf8_lossy(self.compilation_dir),
-            )
-            .field("lines", &self.lines)
-            .field("inlinees", &self.inlinees)
-            .field("inline", &self.inline)
-            .finish()
+/// Appends an unsigned LEB128 encoded value to the buffer.
+fn write_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push((value as u8) | 0x80);
+        value >>= 7;
     }
+    buf.push(value as u8);
 }
 
-/// A dynamically dispatched iterator over items with the given lifetime.
-pub type DynIterator<'a, T> = Box<dyn Iterator<Item = T> + 'a>;
+/// Reads an unsigned LEB128 encoded value from the buffer and advances the offset.
+fn read_varint(buf: &[u8], offset: &mut usize) -> u64 {
+    let mut value = 0;
+    let mut shift = 0;
 
-/// A stateful session for interfacing with debug information.
-///
-/// Debug sessions can be obtained via [`ObjectLike::debug_session`]. Since computing a session may
-/// be a costly operation, try to reuse the session as much as possible.
-///
-/// ## Implementing DebugSession
-///
-/// Reading debug information from object files usually requires loading multiple sections into
-/// memory and computing maps for quick random access to certain information. Since this can be a
-/// quite costly process, this is encapsulated into a `DebugSession`. The session may hold whatever
-/// data and caches may be necessary for efficiently interfacing with the debug info.
-///
-/// All trait methods on a `DebugSession` receive `&mut self`, to allow mutation of internal cache
-/// structures. Lifetimes of returned types are tied to this session's lifetime, which allows to
-/// borrow data from the session.
-///
-/// Examples for things to compute when building a debug session are:
-///
-///  - Decompress debug information if it is stored with compression.
-///  - Build a symbol map for random access to public symbols.
-///  - Map string tables and other lookup tables.
-///  - Read headers of compilation units (compilands) to resolve cross-unit references.
+    while let Some(&byte) = buf.get(*offset) {
+        *offset += 1;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            break;
+        }
+        shift += 7;
+    }
+
+    value
+}
+
+/// A compact list of [`LineInfo`] records.
 ///
-/// [`ObjectLike::debug_session`]: trait.ObjectLike.html#tymethod.debug_session
-pub trait DebugSession<'session> {
-    /// The error returned when reading debug information fails.
-    type Error;
+/// Records are stored as deltas to their predecessor, and their files are deduplicated into a
+/// table. This takes a fraction of the memory of a `Vec<LineInfo>`. Records are decoded on the fly
+/// by [`iter`](Self::iter), in the order in which they were pushed.
+#[derive(Clone, Default, Eq, PartialEq)]
+pub struct LineRecords<'data> {
+    files: Vec<FileInfo<'data>>,
+    data: Vec<u8>,
+    len: usize,
+    last_address: u64,
+    last_line: u64,
+}
 
-    /// An iterator over all functions in this debug file.
-    type FunctionIterator: Iterator<Item = Result<Function<'session>, Self::Error>>;
+impl<'data> LineRecords<'data> {
+    /// Creates an empty list of line records.
+    pub fn new() -> Self {
+        Self::default()
+    }
 
-    /// An iterator over all source files referenced by this debug file.
-    type FileIterator: Iterator<Item = Result<FileEntry<'session>, Self::Error>>;
+    /// Returns the number of line records.
+    pub fn len(&self) -> usize {
+        self.len
+    }
 
-    /// Returns an iterator over all functions in this debug file.
-    ///
-    /// Functions are iterated in the order they are declared in their compilation units. The
+    /// Returns `true` if there are no line records.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Appends a line record.
+    pub fn push(&mut self, line: LineInfo<'data>) {
+        let file_index = match self.files.iter().rposition(|file| *file == line.file) {
+            Some(index) => index,
+            None => {
+                self.files.push(line.file);
+                self.files.len() - 1
+            }
+        };
+
+        let address_delta = line.address.wrapping_sub(self.last_address) as i64;
+        write_varint(&mut self.data, zigzag_encode(address_delta));
+        // The lowest bit of the file index flags whether a size follows.
+        write_varint(
+            &mut self.data,
+            (file_index as u64) << 1 | line.size.is_some() as u64,
+        );
+        if let Some(size) = line.size {
+            write_varint(&mut self.data, size);
+        }
+        let line_delta = line.line.wrapping_sub(self.last_line) as i64;
+        write_varint(&mut self.data, zigzag_encode(line_delta));
+
+        self.last_address = line.address;
+        self.last_line = line.line;
+        self.len += 1;
+    }
+
+    /// Returns an iterator over all line records.
+    pub fn iter(&self) -> LineRecordsIter<'_, 'data> {
+        LineRecordsIter {
+            records: self,
+            offset: 0,
+            remaining: self.len,
+            address: 0,
+            line: 0,
+        }
+    }
+
+    /// Returns the first line record, if any.
+    pub fn first(&self) -> Option<LineInfo<'data>> {
+        self.iter().next()
+    }
+
+    /// Applies a [`PathRemapper`] to the files of all line records.
+    pub(crate) fn remap(mut self, remapper: Option<&PathRemapper>) -> Self {
+        if remapper.is_some() {
+            self.files = self
+                .files
+                .into_iter()
+                .map(|file| file.remap(remapper))
+                .collect();
+        }
+        self
+    }
+
+    /// Adds `offset` to the addresses of all line records, wrapping around on overflow.
+    pub(crate) fn shift_addresses(self, offset: u64) -> Self {
+        if offset == 0 {
+            return self;
+        }
+
+        self.iter()
+            .map(|mut line| {
+                line.address = line.address.wrapping_add(offset);
+                line
+            })
+            .collect()
+    }
+
+    /// Stores the paths of all files in a [`StringInterner`].
+    pub(crate) fn intern(mut self, interner: Option<&'data StringInterner>) -> Self {
+        if let Some(interner) = interner {
+            self.files = self
+                .files
+                .into_iter()
+                .map(|file| interner.intern_file(file))
+                .collect();
+        }
+        self
+    }
+
+    /// Returns an estimate of the heap memory allocated by these records in bytes.
+    pub(crate) fn heap_size(&self) -> usize {
+        let files: usize = self.files.iter().map(FileInfo::heap_size).sum();
+        self.files.len() * std::mem::size_of::<FileInfo<'_>>() + files + self.data.len()
+    }
+
+    /// Shrinks the allocated storage to the size of the records.
+    pub fn shrink_to_fit(&mut self) {
+        self.files.shrink_to_fit();
+        self.data.shrink_to_fit();
+    }
+
+    /// Converts these records into an owned version that no longer borrows from the object.
+    pub fn into_owned(self) -> LineRecords<'static> {
+        LineRecords {
+            files: self.files.into_iter().map(FileInfo::into_owned).collect(),
+            data: self.data,
+            len: self.len,
+            last_address: self.last_address,
+            last_line: self.last_line,
+        }
+    }
+}
+
+impl fmt::Debug for LineRecords<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'data> Extend<LineInfo<'data>> for LineRecords<'data> {
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = LineInfo<'data>>,
+    {
+        for line in iter {
+            self.push(line);
+        }
+    }
+}
+
+impl<'data> FromIterator<LineInfo<'data>> for LineRecords<'data> {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = LineInfo<'data>>,
+    {
+        let mut records = Self::new();
+        records.extend(iter);
+        records.shrink_to_fit();
+        records
+    }
+}
+
+impl<'data> From<Vec<LineInfo<'data>>> for LineRecords<'data> {
+    fn from(lines: Vec<LineInfo<'data>>) -> Self {
+        lines.into_iter().collect()
+    }
+}
+
+/// Serializes line records as a sequence of [`LineInfo`].
+#[cfg(feature = "serde")]
+impl serde::Serialize for LineRecords<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl<'a, 'data> IntoIterator for &'a LineRecords<'data> {
+    type Item = LineInfo<'data>;
+    type IntoIter = LineRecordsIter<'a, 'data>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over [`LineRecords`], decoding one [`LineInfo`] at a time.
+#[derive(Clone)]
+pub struct LineRecordsIter<'a, 'data> {
+    records: &'a LineRecords<'data>,
+    offset: usize,
+    remaining: usize,
+    address: u64,
+    line: u64,
+}
+
+impl<'a, 'data> Iterator for LineRecordsIter<'a, 'data> {
+    type Item = LineInfo<'data>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let data = &self.records.data;
+        let address_delta = zigzag_decode(read_varint(data, &mut self.offset));
+        let file = read_varint(data, &mut self.offset);
+        let size = match file & 1 {
+            0 => None,
+            _ => Some(read_varint(data, &mut self.offset)),
+        };
+        let line_delta = zigzag_decode(read_varint(data, &mut self.offset));
+
+        self.address = self.address.wrapping_add(address_delta as u64);
+        self.line = self.line.wrapping_add(line_delta as u64);
+        self.remaining -= 1;
+
+        Some(LineInfo {
+            address: self.address,
+            size,
+            file: self.records.files[(file >> 1) as usize].clone(),
+            line: self.line,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for LineRecordsIter<'_, '_> {}
+
+impl std::iter::FusedIterator for LineRecordsIter<'_, '_> {}
+
+impl fmt::Debug for LineRecordsIter<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LineRecordsIter")
+            .field("remaining", &self.remaining)
+            .finish()
+    }
+}
+
+/// A reference to the entity in the debug information that a [`Function`] was created from.
+///
+/// This allows to trace symbolication results back to the exact record in the original debug file,
+/// for instance to investigate bad symbol quality.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum DebugEntity {
+    /// The offset of a DIE in the `.debug_info` section.
+    DwarfDie(u64),
+    /// A symbol record in the symbol stream of a PDB module.
+    PdbSymbol {
+        /// The index of the module.
+        module: u32,
+        /// The offset of the symbol record within the module's symbol stream.
+        symbol: u32,
+    },
+    /// The byte offset of a `FUNC` record in a Breakpad symbol file.
+    BreakpadFunc(u64),
+}
+
+/// The calling convention of a [`Function`].
+///
+/// This corresponds to the `DW_AT_calling_convention` attribute in DWARF.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum CallingConvention {
+    /// The function obeys the normal calling conventions of the target architecture.
+    Normal,
+    /// The function is the main program of a compilation unit.
+    Program,
+    /// The function does not obey standard calling conventions and cannot be called directly.
+    NoCall,
+    /// A vendor-specific calling convention, given as raw DWARF value.
+    Other(u8),
+}
+
+/// A call from the body of a function to another function.
+///
+/// This corresponds to `DW_TAG_call_site` entries in DWARF, which compilers emit for optimized
+/// code.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CallSite {
+    /// Relative address of the instruction following the call, which is the return address of
+    /// the called function.
+    pub return_address: u64,
+    /// Relative address of the called function, if it is known statically.
+    pub target: Option<u64>,
+    /// Specifies whether the call is a tail call, which never returns to the caller.
+    ///
+    /// Stack traces do not contain a frame of the caller for tail calls.
+    pub tail_call: bool,
+}
+
+/// Debug information for a function.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Function<'data> {
+    /// Relative instruction address of the start of the function.
+    pub address: u64,
+    /// Total code size covered by the function body, including inlined functions.
+    pub size: u64,
+    /// Address ranges of the function body if it is not contiguous, sorted by address.
+    ///
+    /// Compilers may split a function into a hot and a cold part, which are placed in different
+    /// sections. In this case, `address` points to the start of the function's entry and `size` is
+    /// the sum of all range sizes. This list is empty if the function is covered entirely by
+    /// `address` and `size`. Use [`address_ranges`](Self::address_ranges) to iterate the body of
+    /// any function.
+    pub ranges: Vec<Range<u64>>,
+    /// The name and language of the function symbol.
+    pub name: Name<'data>,
+    /// Path to the compilation directory. File paths are relative to this.
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "derive_serde::serialize_lossy")
+    )]
+    pub compilation_dir: Cow<'data, [u8]>,
+    /// Lines covered by this function, including inlined children.
+    pub lines: LineRecords<'data>,
+    /// Functions that have been inlined into this function's body.
+    pub inlinees: Vec<Function<'data>>,
+    /// Specifies whether this function is inlined.
+    pub inline: bool,
+    /// Specifies whether this function was generated by the compiler, such as a thunk or a
+    /// trampoline.
+    ///
+    /// Such functions do not correspond to source code written by the user and can usually be
+    /// skipped when grouping or displaying stack traces.
+    pub artificial: bool,
+    /// Specifies whether this function never returns to its caller, such as `abort` or `exit`.
+    ///
+    /// Compilers may place a call to such a function at the very end of the caller's code. The
+    /// return address then points past the calling function, so that stack walkers should look up
+    /// the call instruction instead of the return address when resolving its source location.
+    pub noreturn: bool,
+    /// The calling convention of this function, if declared explicitly.
+    pub calling_convention: Option<CallingConvention>,
+    /// Calls made directly from the body of this function, excluding calls of its inlinees.
+    ///
+    /// This is only available for DWARF and requires debug information of optimized code.
+    pub call_sites: Vec<CallSite>,
+    /// The debug information entity this function was created from, if known.
+    pub entity: Option<DebugEntity>,
+}
+
+impl<'data> Function<'data> {
+    /// End address of the entire function body, including inlined functions.
+    ///
+    /// This address points at the first instruction after the function body. For functions that
+    /// are split into multiple [`ranges`](Self::ranges), this is only the sum of `address` and
+    /// `size`, which does not correspond to the end of any part.
+    pub fn end_address(&self) -> u64 {
+        self.address + self.size
+    }
+
+    /// Returns the address ranges covered by the function body, sorted by address.
+    ///
+    /// This yields a single range from `address` to [`end_address`](Self::end_address) if the
+    /// function is contiguous, and the individual [`ranges`](Self::ranges) otherwise. Hot and cold
+    /// parts of a split function appear as separate ranges.
+    pub fn address_ranges(&self) -> impl Iterator<Item = Range<u64>> + '_ {
+        let body = if self.ranges.is_empty() {
+            Some(self.address..self.end_address())
+        } else {
+            None
+        };
+
+        body.into_iter().chain(self.ranges.iter().cloned())
+    }
+
+    /// Returns the end address of the last range of the function body.
+    ///
+    /// This is the same as [`end_address`](Self::end_address) for contiguous functions.
+    pub fn ranges_end(&self) -> u64 {
+        match self.ranges.last() {
+            Some(range) => range.end,
+            None => self.end_address(),
+        }
+    }
+
+    /// Checks whether the address is within one of the ranges of the function body.
+    pub fn contains(&self, address: u64) -> bool {
+        self.address_ranges().any(|range| range.contains(&address))
+    }
+
+    /// Checks whether this function was created by the machine outliner.
+    ///
+    /// With `-moutline`, which is the default for Apple platforms at `-Oz`, LLVM moves instruction
+    /// sequences repeated across functions into shared functions named `OUTLINED_FUNCTION_N`.
+    /// These show up as separate frames in stack traces. Their
+    /// [`call_sites`](Self::call_sites) in the callers can be used to attribute them back.
+    pub fn is_outlined(&self) -> bool {
+        is_outlined_name(self.name.as_str())
+    }
+
+    /// Returns the maximum nesting depth of inlined functions below this function.
+    ///
+    /// The depth is zero if there are no inlinees, one if none of the inlinees have inlinees of
+    /// their own, and so on.
+    pub fn inline_depth(&self) -> usize {
+        self.inlinees
+            .iter()
+            .map(|inlinee| inlinee.inline_depth() + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Limits the nesting depth of inlined functions below this function to `max_depth`.
+    ///
+    /// Every inlinee that would exceed the limit is replaced, along with all of its own inlinees,
+    /// by a single synthetic inlinee named `… N frames inlined`, where `N` is the depth of the
+    /// deepest chain of frames it replaces. The synthetic inlinee covers the same code ranges and
+    /// lines as the inlinee it replaces, so that lookups still resolve to the outermost truncated
+    /// call site. Synthetic inlinees are placed one level below `max_depth`, so a `max_depth` of
+    /// zero replaces every inlinee of this function.
+    pub fn limit_inline_depth(&mut self, max_depth: usize) {
+        for inlinee in &mut self.inlinees {
+            if max_depth == 0 {
+                inlinee.truncate_inlinees();
+            } else {
+                inlinee.limit_inline_depth(max_depth - 1);
+            }
+        }
+    }
+
+    /// Applies [`limit_inline_depth`](Self::limit_inline_depth) if a limit is given.
+    pub(crate) fn with_inline_depth_limit(mut self, max_depth: Option<usize>) -> Self {
+        if let Some(max_depth) = max_depth {
+            self.limit_inline_depth(max_depth);
+        }
+        self
+    }
+
+    /// Adds `offset` to the addresses of this function, its lines and all of its inlinees.
+    ///
+    /// This converts addresses relative to the image into absolute addresses of an image loaded at
+    /// `offset`. Addresses wrap around on overflow.
+    pub fn shift_addresses(&mut self, offset: u64) {
+        self.address = self.address.wrapping_add(offset);
+        for range in &mut self.ranges {
+            range.start = range.start.wrapping_add(offset);
+            range.end = range.end.wrapping_add(offset);
+        }
+        for call_site in &mut self.call_sites {
+            call_site.return_address = call_site.return_address.wrapping_add(offset);
+            call_site.target = call_site.target.map(|t| t.wrapping_add(offset));
+        }
+
+        self.lines = std::mem::take(&mut self.lines).shift_addresses(offset);
+        for inlinee in &mut self.inlinees {
+            inlinee.shift_addresses(offset);
+        }
+    }
+
+    /// Applies [`shift_addresses`](Self::shift_addresses) if the offset is not zero.
+    pub(crate) fn with_address_offset(mut self, offset: u64) -> Self {
+        if offset != 0 {
+            self.shift_addresses(offset);
+        }
+        self
+    }
+
+    /// Replaces this inlinee with a synthetic record for itself and all of its inlinees.
+    fn truncate_inlinees(&mut self) {
+        let frames = self.inline_depth() + 1;
+        let label = if frames == 1 { "frame" } else { "frames" };
+        let name = format!("… {} {} inlined", frames, label);
+
+        // Calls of the replaced inlinees are now made by the synthetic record.
+        for mut inlinee in std::mem::take(&mut self.inlinees) {
+            inlinee.truncate_inlinees();
+            self.call_sites.append(&mut inlinee.call_sites);
+        }
+        self.call_sites
+            .sort_by_key(|call_site| call_site.return_address);
+
+        self.name = Name::new(name, NameMangling::Unmangled, Language::Unknown);
+        self.inline = true;
+        self.artificial = false;
+        self.noreturn = false;
+        self.calling_convention = None;
+        self.entity = None;
+    }
+
+    /// Applies a [`PathRemapper`] to the compilation directory and all files of this function and
+    /// its inlinees.
+    pub(crate) fn remap(mut self, remapper: Option<&PathRemapper>) -> Self {
+        if let Some(remapper) = remapper {
+            self.compilation_dir = remapper.remap_bytes(self.compilation_dir);
+            self.lines = self.lines.remap(Some(remapper));
+            self.inlinees = self
+                .inlinees
+                .into_iter()
+                .map(|inlinee| inlinee.remap(Some(remapper)))
+                .collect();
+        }
+        self
+    }
+
+    /// Stores the name, compilation directory and file paths of this function and its inlinees in
+    /// a [`StringInterner`].
+    pub(crate) fn intern(mut self, interner: Option<&'data StringInterner>) -> Self {
+        if let Some(interner) = interner {
+            self.name = interner.intern_name(self.name);
+            self.compilation_dir = interner.intern_path(self.compilation_dir);
+            self.lines = self.lines.intern(Some(interner));
+            self.inlinees = self
+                .inlinees
+                .into_iter()
+                .map(|inlinee| inlinee.intern(Some(interner)))
+                .collect();
+        }
+        self
+    }
+
+    /// Returns an estimate of the memory used by this function and its inlinees in bytes.
+    ///
+    /// The name is always counted, since it is often computed by the debug session. Other data
+    /// borrowed from the object is not included.
+    pub(crate) fn heap_size(&self) -> usize {
+        let inlinees: usize = self.inlinees.iter().map(Function::heap_size).sum();
+        std::mem::size_of::<Self>()
+            + self.ranges.len() * std::mem::size_of::<Range<u64>>()
+            + self.call_sites.len() * std::mem::size_of::<CallSite>()
+            + self.name.as_str().len()
+            + owned_len(&self.compilation_dir)
+            + self.lines.heap_size()
+            + inlinees
+    }
+
+    /// Converts this function into an owned version that no longer borrows from the object.
+    ///
+    /// This allows functions to outlive the debug session they were read from, for instance to
+    /// hand them to another thread.
+    pub fn into_owned(self) -> Function<'static> {
+        let name = Name::new(
+            self.name.as_str().to_owned(),
+            self.name.mangling(),
+            self.name.language(),
+        );
+
+        Function {
+            address: self.address,
+            size: self.size,
+            ranges: self.ranges,
+            name,
+            compilation_dir: Cow::Owned(self.compilation_dir.into_owned()),
+            lines: self.lines.into_owned(),
+            inlinees: self
+                .inlinees
+                .into_iter()
+                .map(Function::into_owned)
+                .collect(),
+            inline: self.inline,
+            artificial: self.artificial,
+            noreturn: self.noreturn,
+            calling_convention: self.calling_convention,
+            call_sites: self.call_sites,
+            entity: self.entity,
+        }
+    }
+}
+
+/// Checks whether a function name was generated by the LLVM machine outliner.
+///
+/// Outlined functions are named `OUTLINED_FUNCTION_N`, with a leading underscore in MachO symbol
+/// tables.
+pub(crate) fn is_outlined_name(name: &str) -> bool {
+    let name = name.strip_prefix('_').unwrap_or(name);
+    match name.strip_prefix("OUTLINED_FUNCTION_") {
+        Some(suffix) => suffix.starts_with(|c: char| c.is_ascii_digit()),
+        None => false,
+    }
+}
+
+impl fmt::Debug for Function<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Function")
+            .field("address", &format_args!("{:#x}", self.address))
+            .field("size", &format_args!("{:#x}", self.size))
+            .field("name", &self.name)
+            .field(
+                "compilation_dir",
+                &String::from_utf8_lossy(&self.compilation_dir),
+            )
+            .field("lines", &self.lines)
+            .field("inlinees", &self.inlinees)
+            .field("inline", &self.inline)
+            .field("artificial", &self.artificial)
+            .field("noreturn", &self.noreturn)
+            .field("calling_convention", &self.calling_convention)
+            .field("entity", &self.entity)
+            .finish()
+    }
+}
+
+/// A dynamically dispatched iterator over items with the given lifetime.
+pub type DynIterator<'a, T> = Box<dyn Iterator<Item = T> + 'a>;
+
+/// The location of an error or diagnostic within a debug file.
+///
+/// The section names the part of the file that failed to parse, such as `.debug_info` for DWARF,
+/// the record type for Breakpad, or the module name for PDB. If a section is given, the offset is
+/// relative to its start, otherwise it is relative to the start of the file.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct ErrorLocation {
+    /// The name of the section, record type or module.
+    pub section: Option<Cow<'static, str>>,
+    /// The byte offset within the section or file.
+    pub offset: Option<u64>,
+}
+
+impl ErrorLocation {
+    /// Creates a location at the given offset within a section.
+    pub(crate) fn new<S>(section: S, offset: u64) -> Self
+    where
+        S: Into<Cow<'static, str>>,
+    {
+        Self {
+            section: Some(section.into()),
+            offset: Some(offset),
+        }
+    }
+}
+
+impl fmt::Display for ErrorLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.section, self.offset) {
+            (Some(section), Some(offset)) => write!(f, "{} at offset {:#x}", section, offset),
+            (Some(section), None) => write!(f, "{}", section),
+            (None, Some(offset)) => write!(f, "offset {:#x}", offset),
+            (None, None) => write!(f, "unknown location"),
+        }
+    }
+}
+
+/// Formats an optional [`ErrorLocation`] as a suffix of an error message.
+pub(crate) struct LocationSuffix<'a>(pub &'a Option<ErrorLocation>);
+
+impl fmt::Display for LocationSuffix<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(location) => write!(f, " (in {})", location),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A non-fatal issue encountered while reading debug information.
+///
+/// Diagnostics are only recorded if a debug session is put into collection mode, for instance via
+/// [`ObjectDebugSession::set_collect_diagnostics`](crate::ObjectDebugSession::set_collect_diagnostics).
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Diagnostic {
+    /// The location of the issue.
+    pub location: ErrorLocation,
+    /// A description of the issue.
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// Creates a diagnostic from an error, including the chain of its sources.
+    ///
+    /// The location is passed explicitly since it would otherwise be part of the message.
+    pub(crate) fn from_error<K>(
+        kind: K,
+        source: Option<&(dyn std::error::Error + Send + Sync + 'static)>,
+        location: Option<&ErrorLocation>,
+    ) -> Self
+    where
+        K: fmt::Display,
+    {
+        let mut message = kind.to_string();
+        let mut source = source.map(|e| e as &dyn std::error::Error);
+        while let Some(error) = source {
+            message.push_str(": ");
+            message.push_str(&error.to_string());
+            source = error.source();
+        }
+
+        Self {
+            location: location.cloned().unwrap_or_default(),
+            message,
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (in {})", self.message, self.location)
+    }
+}
+
+/// Accumulates [`Diagnostic`]s of a debug session while collection mode is enabled.
+#[derive(Debug, Default)]
+pub(crate) struct Diagnostics {
+    enabled: bool,
+    entries: Mutex<Vec<Diagnostic>>,
+}
+
+impl Diagnostics {
+    /// Enables or disables collection mode.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Returns `true` if errors should be recorded and skipped instead of returned.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Records a diagnostic if collection mode is enabled.
+    ///
+    /// Identical diagnostics are only recorded once, since iterating a session multiple times
+    /// would otherwise report the same issues repeatedly.
+    pub fn push(&self, diagnostic: Diagnostic) {
+        if !self.enabled {
+            return;
+        }
+
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if !entries.contains(&diagnostic) {
+            entries.push(diagnostic);
+        }
+    }
+
+    /// Returns all recorded diagnostics and clears the collection.
+    pub fn take(&self) -> Vec<Diagnostic> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        std::mem::take(&mut *entries)
+    }
+}
+
+/// Limits on the amount of debug information yielded by a debug session.
+///
+/// Hostile or degenerate debug files can describe an enormous number of functions or line records.
+/// Limits protect shared workers from exhausting their memory while processing such files. Once a
+/// limit is exceeded, the function iterator of the session yields an error that carries a
+/// [`LimitExceeded`] as its source and stops. All limits are disabled by default.
+///
+/// Limits are set on debug sessions, for instance via
+/// [`ObjectDebugSession::set_limits`](crate::ObjectDebugSession::set_limits), and apply to every
+/// iteration over functions separately.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct SessionLimits {
+    /// The maximum number of top-level functions yielded by a function iterator.
+    pub max_functions: Option<usize>,
+    /// The maximum number of line records of a single function or inlinee.
+    pub max_lines_per_function: Option<usize>,
+    /// The maximum estimated memory in bytes of all functions yielded by a function iterator.
+    ///
+    /// This includes inlinees, line records and owned strings, but not data borrowed from the
+    /// object file.
+    pub max_total_bytes: Option<usize>,
+}
+
+/// The kind of limit that was exceeded, see [`LimitExceeded`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum LimitKind {
+    /// [`SessionLimits::max_functions`] was exceeded.
+    Functions,
+    /// [`SessionLimits::max_lines_per_function`] was exceeded.
+    LinesPerFunction,
+    /// [`SessionLimits::max_total_bytes`] was exceeded.
+    TotalBytes,
+}
+
+impl fmt::Display for LimitKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Functions => write!(f, "function count"),
+            Self::LinesPerFunction => write!(f, "lines per function"),
+            Self::TotalBytes => write!(f, "total memory"),
+        }
+    }
+}
+
+/// An error returned when a debug session exceeds one of its [`SessionLimits`].
+///
+/// The errors of all debug sessions carry this error as their source. Use
+/// [`find`](Self::find) to detect it in an arbitrary error chain.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct LimitExceeded {
+    kind: LimitKind,
+    limit: usize,
+}
+
+impl LimitExceeded {
+    /// Returns the kind of limit that was exceeded.
+    pub fn kind(&self) -> LimitKind {
+        self.kind
+    }
+
+    /// Returns the configured value of the exceeded limit.
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Searches an error and its chain of sources for a `LimitExceeded` error.
+    pub fn find<'a>(error: &'a (dyn std::error::Error + 'static)) -> Option<&'a Self> {
+        let mut next = Some(error);
+        while let Some(error) = next {
+            if let Some(exceeded) = error.downcast_ref::<Self>() {
+                return Some(exceeded);
+            }
+            next = error.source();
+        }
+        None
+    }
+}
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} limit of {} exceeded", self.kind, self.limit)
+    }
+}
+
+impl std::error::Error for LimitExceeded {}
+
+/// Enforces [`SessionLimits`] on the functions yielded by a function iterator.
+#[derive(Debug, Default)]
+pub(crate) struct LimitTracker {
+    limits: SessionLimits,
+    functions: usize,
+    bytes: usize,
+    exceeded: bool,
+}
+
+impl LimitTracker {
+    /// Creates a tracker for a new function iterator.
+    pub fn new(limits: SessionLimits) -> Self {
+        Self {
+            limits,
+            ..Self::default()
+        }
+    }
+
+    /// Returns `true` if any limit is configured, in which case iteration may stop early.
+    pub fn has_limits(&self) -> bool {
+        self.limits != SessionLimits::default()
+    }
+
+    /// Returns `true` if a limit has been exceeded, after which iterators must stop.
+    pub fn is_exceeded(&self) -> bool {
+        self.exceeded
+    }
+
+    /// Accounts for a function that is about to be yielded.
+    pub fn track(&mut self, function: &Function<'_>) -> Result<(), LimitExceeded> {
+        let result = self.check(function);
+        self.exceeded |= result.is_err();
+        result
+    }
+
+    fn check(&mut self, function: &Function<'_>) -> Result<(), LimitExceeded> {
+        self.functions += 1;
+        if let Some(limit) = self.limits.max_functions {
+            if self.functions > limit {
+                return Err(LimitExceeded {
+                    kind: LimitKind::Functions,
+                    limit,
+                });
+            }
+        }
+
+        if let Some(limit) = self.limits.max_lines_per_function {
+            if max_lines(function) > limit {
+                return Err(LimitExceeded {
+                    kind: LimitKind::LinesPerFunction,
+                    limit,
+                });
+            }
+        }
+
+        if let Some(limit) = self.limits.max_total_bytes {
+            self.bytes = self.bytes.saturating_add(function.heap_size());
+            if self.bytes > limit {
+                return Err(LimitExceeded {
+                    kind: LimitKind::TotalBytes,
+                    limit,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the largest number of line records of the function or any of its inlinees.
+fn max_lines(function: &Function<'_>) -> usize {
+    function
+        .inlinees
+        .iter()
+        .map(max_lines)
+        .fold(function.lines.len(), usize::max)
+}
+
+/// An arena that stores every distinct string only once.
+///
+/// Debug sessions compute many strings that are not contained verbatim in the debug file, such as
+/// qualified or demangled function names and remapped file paths. Large debug files repeat these
+/// strings many times, for instance names of template functions inlined into every compilation
+/// unit or paths of common headers. Interning stores a single copy and hands out borrows with the
+/// lifetime of the interner, so that [`Name`]s and [`FileInfo`]s can borrow from it like they
+/// borrow from the object.
+///
+/// Interned strings are only released when the interner is dropped.
+#[derive(Debug, Default)]
+pub struct StringInterner {
+    strings: Mutex<HashSet<Box<[u8]>>>,
+}
+
+impl StringInterner {
+    /// Creates an empty interner.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a copy of the given bytes that is stored in this interner.
+    pub fn intern_bytes<'a>(&'a self, bytes: &[u8]) -> &'a [u8] {
+        let mut strings = self.strings.lock().unwrap_or_else(|e| e.into_inner());
+        let stored = match strings.get(bytes) {
+            Some(stored) => stored,
+            None => {
+                strings.insert(bytes.into());
+                strings.get(bytes).unwrap()
+            }
+        };
+
+        // SAFETY: The bytes live in a heap allocation owned by the set, which does not move when
+        // the set reallocates. Entries are never removed, so the allocation lives as long as
+        // `self`.
+        unsafe { std::slice::from_raw_parts(stored.as_ptr(), stored.len()) }
+    }
+
+    /// Returns a copy of the given string that is stored in this interner.
+    pub fn intern<'a>(&'a self, string: &str) -> &'a str {
+        let bytes = self.intern_bytes(string.as_bytes());
+        // SAFETY: The interned bytes are an exact copy of a valid UTF-8 string.
+        unsafe { std::str::from_utf8_unchecked(bytes) }
+    }
+
+    /// Interns a name unless it already borrows its string.
+    pub fn intern_name<'a>(&'a self, name: Name<'a>) -> Name<'a> {
+        let (mangling, language) = (name.mangling(), name.language());
+        match name.into_cow() {
+            Cow::Owned(string) => Name::new(self.intern(&string), mangling, language),
+            Cow::Borrowed(string) => Name::new(string, mangling, language),
+        }
+    }
+
+    /// Interns the directory and name of a file unless they already borrow their paths.
+    pub fn intern_file<'a>(&'a self, file: FileInfo<'a>) -> FileInfo<'a> {
+        FileInfo {
+            name: self.intern_path(file.name),
+            dir: self.intern_path(file.dir),
+            path_kind: file.path_kind,
+        }
+    }
+
+    /// Interns a path unless it is already borrowed.
+    pub fn intern_path<'a>(&'a self, path: Cow<'a, [u8]>) -> Cow<'a, [u8]> {
+        match path {
+            Cow::Owned(bytes) => Cow::Borrowed(self.intern_bytes(&bytes)),
+            borrowed => borrowed,
+        }
+    }
+
+    /// Returns the number of distinct strings stored in this interner.
+    pub fn len(&self) -> usize {
+        self.strings.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Returns `true` if no strings have been interned.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the total size of all interned strings in bytes.
+    pub fn size(&self) -> usize {
+        let strings = self.strings.lock().unwrap_or_else(|e| e.into_inner());
+        strings.iter().map(|s| s.len()).sum()
+    }
+}
+
+/// A stateful session for interfacing with debug information.
+///
+/// Debug sessions can be obtained via [`ObjectLike::debug_session`]. Since computing a session may
+/// be a costly operation, try to reuse the session as much as possible.
+///
+/// ## Implementing DebugSession
+///
+/// Reading debug information from object files usually requires loading multiple sections into
+/// memory and computing maps for quick random access to certain information. Since this can be a
+/// quite costly process, this is encapsulated into a `DebugSession`. The session may hold whatever
+/// data and caches may be necessary for efficiently interfacing with the debug info.
+///
+/// All trait methods on a `DebugSession` receive `&mut self`, to allow mutation of internal cache
+/// structures. Lifetimes of returned types are tied to this session's lifetime, which allows to
+/// borrow data from the session.
+///
+/// Examples for things to compute when building a debug session are:
+///
+///  - Decompress debug information if it is stored with compression.
+///  - Build a symbol map for random access to public symbols.
+///  - Map string tables and other lookup tables.
+///  - Read headers of compilation units (compilands) to resolve cross-unit references.
+///
+/// [`ObjectLike::debug_session`]: trait.ObjectLike.html#tymethod.debug_session
+pub trait DebugSession<'session> {
+    /// The error returned when reading debug information fails.
+    type Error;
+
+    /// An iterator over all functions in this debug file.
+    type FunctionIterator: Iterator<Item = Result<Function<'session>, Self::Error>>;
+
+    /// An iterator over all source files referenced by this debug file.
+    type FileIterator: Iterator<Item = Result<FileEntry<'session>, Self::Error>>;
+
+    /// Returns an iterator over all functions in this debug file.
+    ///
+    /// Functions are iterated in the order they are declared in their compilation units. The
     /// functions yielded by this iterator include all inlinees and line records resolved.
     ///
     /// Note that the iterator holds a mutable borrow on the debug session, which allows it to use
@@ -644,6 +2156,65 @@ pub trait DebugSession<'session> {
     fn source_by_path(&self, path: &str) -> Result<Option<Cow<'_, str>>, Self::Error>;
 }
 
+/// The error type returned by [`DynDebugSession`].
+pub type DynDebugSessionError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// An object-safe version of [`DebugSession`].
+///
+/// `DebugSession` declares its iterators as associated types, which prevents it from being used as
+/// a trait object. This trait is implemented for all debug sessions in this crate, including
+/// [`ObjectDebugSession`], and returns boxed iterators and errors instead. This allows to store
+/// debug sessions of different formats in the same container as `Box<dyn DynDebugSession>`.
+///
+/// [`ObjectDebugSession`]: crate::ObjectDebugSession
+///
+/// # Example
+///
+/// ```
+/// use symbolic_debuginfo::DynDebugSession;
+///
+/// fn count_functions(sessions: &[Box<dyn DynDebugSession + '_>]) -> usize {
+///     sessions
+///         .iter()
+///         .flat_map(|session| session.functions())
+///         .filter(Result::is_ok)
+///         .count()
+/// }
+/// ```
+pub trait DynDebugSession {
+    /// Returns an iterator over all functions in this debug file.
+    ///
+    /// See [`DebugSession::functions`] for more information.
+    fn functions(&self) -> DynIterator<'_, Result<Function<'_>, DynDebugSessionError>>;
+
+    /// Returns an iterator over all source files referenced by this debug file.
+    ///
+    /// See [`DebugSession::files`] for more information.
+    fn files(&self) -> DynIterator<'_, Result<FileEntry<'_>, DynDebugSessionError>>;
+
+    /// Looks up a file's source contents by its full canonicalized path.
+    ///
+    /// The given path must be canonicalized.
+    fn source_by_path(&self, path: &str) -> Result<Option<Cow<'_, str>>, DynDebugSessionError>;
+}
+
+bitflags::bitflags! {
+    /// The kinds of information contained in an object file.
+    ///
+    /// Returned by [`ObjectLike::features`] to classify objects with a single call.
+    #[derive(Default)]
+    pub struct ObjectFeatures: u32 {
+        /// The object exposes a public symbol table.
+        const SYMBOLS = 1 << 0;
+        /// The object contains debug information.
+        const DEBUG_INFO = 1 << 1;
+        /// The object contains stack unwinding information.
+        const UNWIND_INFO = 1 << 2;
+        /// The object contains embedded sources.
+        const SOURCES = 1 << 3;
+    }
+}
+
 /// An object containing debug information.
 pub trait ObjectLike<'data, 'object> {
     /// Errors thrown when reading information from this object.
@@ -707,6 +2278,19 @@ pub trait ObjectLike<'data, 'object> {
 
     /// Determines whether this object is malformed and was only partially parsed
     fn is_malformed(&self) -> bool;
+
+    /// Returns the kinds of information contained in this object.
+    ///
+    /// This combines [`has_symbols`](Self::has_symbols), [`has_debug_info`](Self::has_debug_info),
+    /// [`has_unwind_info`](Self::has_unwind_info) and [`has_sources`](Self::has_sources).
+    fn features(&self) -> ObjectFeatures {
+        let mut features = ObjectFeatures::empty();
+        features.set(ObjectFeatures::SYMBOLS, self.has_symbols());
+        features.set(ObjectFeatures::DEBUG_INFO, self.has_debug_info());
+        features.set(ObjectFeatures::UNWIND_INFO, self.has_unwind_info());
+        features.set(ObjectFeatures::SOURCES, self.has_sources());
+        features
+    }
 }
 
 mod derive_serde {
@@ -741,6 +2325,15 @@ mod derive_serde {
 
     impl_str_serde!(super::ObjectKind);
     impl_str_serde!(super::FileFormat);
+
+    /// Serializes a byte path as string, replacing invalid UTF-8 sequences.
+    #[cfg(feature = "serde")]
+    pub fn serialize_lossy<S>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ::serde::ser::Serializer,
+    {
+        serializer.serialize_str(&String::from_utf8_lossy(bytes))
+    }
 }
 
 #[cfg(test)]
@@ -750,14 +2343,15 @@ mod tests {
 
     fn file_info<'a>(dir: &'a str, name: &'a str) -> FileInfo<'a> {
         FileInfo {
-            dir: dir.as_bytes(),
-            name: name.as_bytes(),
+            dir: Cow::Borrowed(dir.as_bytes()),
+            name: Cow::Borrowed(name.as_bytes()),
+            path_kind: FilePathKind::Unknown,
         }
     }
 
     fn file_entry<'a>(compilation_dir: &'a str, dir: &'a str, name: &'a str) -> FileEntry<'a> {
         FileEntry {
-            compilation_dir: compilation_dir.as_bytes(),
+            compilation_dir: Cow::Borrowed(compilation_dir.as_bytes()),
             info: file_info(dir, name),
         }
     }
@@ -797,4 +2391,412 @@ mod tests {
             "/src/foo.h"
         );
     }
+
+    fn symbol(name: &'static str, address: u64, size: u64) -> Symbol<'static> {
+        Symbol {
+            name: Some(name.into()),
+            address,
+            size,
+        }
+    }
+
+    fn names<'a>(symbols: Vec<&'a Symbol<'_>>) -> Vec<&'a str> {
+        symbols.into_iter().filter_map(Symbol::name).collect()
+    }
+
+    #[test]
+    fn test_symbol_interval_map_overlapping() {
+        let map: SymbolIntervalMap = vec![
+            symbol("outer", 0x1000, 0x100),
+            symbol("inner", 0x1010, 0x10),
+            symbol("after", 0x1100, 0x10),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(names(map.lookup_range(0x0fff)), Vec::<&str>::new());
+        assert_eq!(names(map.lookup_range(0x1018)), ["inner", "outer"]);
+        assert_eq!(names(map.lookup_range(0x1020)), ["outer"]);
+        assert_eq!(names(map.lookup_range(0x1100)), ["after"]);
+        assert_eq!(names(map.lookup_range(0x1110)), Vec::<&str>::new());
+        assert_eq!(map.lookup(0x1018).and_then(Symbol::name), Some("inner"));
+    }
+
+    #[test]
+    fn test_symbol_interval_map_zero_sized() {
+        let map: SymbolIntervalMap = vec![
+            symbol("b", 0x2000, 0),
+            symbol("a", 0x1000, 0),
+            symbol("a_alias", 0x1000, 0),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(names(map.lookup_range(0x1fff)), ["a", "a_alias"]);
+        assert_eq!(names(map.lookup_range(0x2000)), ["b"]);
+        assert_eq!(names(map.lookup_range(u64::MAX - 1)), ["b"]);
+    }
+
+    #[test]
+    fn test_symbol_interval_map_tie_breaking() {
+        let thumb = SymbolFlags {
+            thumb: true,
+            local: false,
+        };
+        let local = SymbolFlags {
+            thumb: false,
+            local: true,
+        };
+
+        let map = SymbolIntervalMap::from_symbols(vec![
+            (symbol("thumb", 0x1000, 0x10), thumb),
+            (symbol("local", 0x1000, 0x10), local),
+            (symbol("global", 0x1000, 0x10), SymbolFlags::default()),
+            (symbol("b_global", 0x1000, 0x10), SymbolFlags::default()),
+        ]);
+
+        assert_eq!(
+            names(map.lookup_range(0x1008)),
+            ["b_global", "global", "local", "thumb"]
+        );
+        assert_eq!(map.lookup(0x1008).and_then(Symbol::name), Some("b_global"));
+    }
+
+    #[test]
+    fn test_symbol_map_iter_sized() {
+        let mut map: SymbolMap = vec![
+            symbol("a", 0x1000, 0),
+            symbol("b", 0x1100, 0x400),
+            symbol("c", 0x1800, 0),
+            symbol("d", 0x3000, 0),
+        ]
+        .into_iter()
+        .collect();
+
+        // Without sections, the last symbol has no size.
+        let sizes: Vec<_> = map.iter_sized().map(|s| (s.address, s.size)).collect();
+        assert_eq!(
+            sizes,
+            [
+                (0x1000, 0x100),
+                (0x1100, 0x400),
+                (0x1800, 0x1800),
+                (0x3000, 0)
+            ]
+        );
+
+        map.set_sections(vec![0x3000..0x3080, 0x1000..0x2000]);
+        let sizes: Vec<_> = map.iter_sized().map(|s| (s.address, s.size)).collect();
+        assert_eq!(
+            sizes,
+            [
+                (0x1000, 0x100),
+                (0x1100, 0x400),
+                (0x1800, 0x800),
+                (0x3000, 0x80)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_symbol_map_iter_named() {
+        let mut map: SymbolMap = vec![
+            symbol("_ZN3foo3barEv", 0x1000, 0),
+            Symbol {
+                name: None,
+                address: 0x1010,
+                size: 0,
+            },
+        ]
+        .into_iter()
+        .collect();
+        map.set_sections(std::iter::once(0x1000..0x1020));
+
+        let named: Vec<_> = map.iter_named().collect();
+        assert_eq!(
+            named,
+            [NamedSymbol {
+                name: Name::from("_ZN3foo3barEv"),
+                address: 0x1000,
+                size: 0x10,
+            }]
+        );
+        assert_eq!(named[0].name.mangling(), NameMangling::Unknown);
+    }
+
+    #[test]
+    fn test_line_records_roundtrip() {
+        let main = FileInfo {
+            name: Cow::Borrowed(b"main.c"),
+            dir: Cow::Borrowed(b"src"),
+            path_kind: FilePathKind::Unknown,
+        };
+        let header = FileInfo {
+            name: Cow::Borrowed(b"util.h"),
+            dir: Cow::Borrowed(b"include"),
+            path_kind: FilePathKind::Unknown,
+        };
+
+        let lines = vec![
+            LineInfo {
+                address: 0x1000,
+                size: Some(0x10),
+                file: main.clone(),
+                line: 42,
+            },
+            LineInfo {
+                address: 0x1010,
+                size: None,
+                file: header.clone(),
+                line: 7,
+            },
+            LineInfo {
+                address: 0x0800,
+                size: Some(u64::MAX),
+                file: main,
+                line: u64::MAX,
+            },
+            LineInfo {
+                address: u64::MAX,
+                size: Some(0),
+                file: header,
+                line: 0,
+            },
+        ];
+
+        let records = LineRecords::from(lines.clone());
+        assert_eq!(records.len(), lines.len());
+        assert_eq!(records.files.len(), 2);
+        assert_eq!(records.iter().len(), lines.len());
+
+        let decoded = records.iter().collect::<Vec<_>>();
+        assert_eq!(decoded.len(), lines.len());
+        for (decoded, expected) in decoded.iter().zip(&lines) {
+            assert_eq!(decoded.address, expected.address);
+            assert_eq!(decoded.size, expected.size);
+            assert_eq!(decoded.file, expected.file);
+            assert_eq!(decoded.line, expected.line);
+        }
+    }
+
+    #[test]
+    fn test_error_location_display() {
+        let location = ErrorLocation::new(".debug_info", 0x2a);
+        assert_eq!(location.to_string(), ".debug_info at offset 0x2a");
+        assert_eq!(
+            LocationSuffix(&Some(location)).to_string(),
+            " (in .debug_info at offset 0x2a)"
+        );
+        assert_eq!(LocationSuffix(&None).to_string(), "");
+        assert_eq!(ErrorLocation::default().to_string(), "unknown location");
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let diagnostic = Diagnostic {
+            location: ErrorLocation::new("FUNC", 0x10),
+            message: "parse error".into(),
+        };
+
+        let mut diagnostics = Diagnostics::default();
+        diagnostics.push(diagnostic.clone());
+        assert!(diagnostics.take().is_empty());
+
+        diagnostics.set_enabled(true);
+        diagnostics.push(diagnostic.clone());
+        diagnostics.push(diagnostic.clone());
+        assert_eq!(diagnostics.take(), vec![diagnostic]);
+        assert!(diagnostics.take().is_empty());
+    }
+
+    #[test]
+    fn test_line_records_empty() {
+        let records = LineRecords::new();
+        assert!(records.is_empty());
+        assert!(records.first().is_none());
+        assert_eq!(records.iter().count(), 0);
+    }
+
+    #[test]
+    fn test_string_interner() {
+        let interner = StringInterner::new();
+        assert!(interner.is_empty());
+
+        let a = interner.intern(&String::from("std::vector<int>::push_back"));
+        let b = interner.intern(&String::from("std::vector<int>::push_back"));
+        assert_eq!(a, "std::vector<int>::push_back");
+        assert_eq!(a.as_ptr(), b.as_ptr());
+
+        let c = interner.intern("main");
+        assert_ne!(a.as_ptr(), c.as_ptr());
+        assert_eq!(interner.len(), 2);
+        assert_eq!(interner.size(), a.len() + c.len());
+
+        let name = Name::new(String::from("main"), NameMangling::Unmangled, Language::Cpp);
+        let name = interner.intern_name(name);
+        assert_eq!(name.as_str().as_ptr(), c.as_ptr());
+        assert_eq!(name.language(), Language::Cpp);
+
+        let file = FileInfo {
+            dir: Cow::Owned(b"/usr/include".to_vec()),
+            name: Cow::Borrowed(b"stdio.h"),
+            path_kind: FilePathKind::Unknown,
+        };
+        let file = interner.intern_file(file);
+        assert!(matches!(file.dir, Cow::Borrowed(b"/usr/include")));
+        assert_eq!(interner.len(), 3);
+    }
+
+    fn inline_function<'a>(
+        name: &'a str,
+        address: u64,
+        inlinees: Vec<Function<'a>>,
+    ) -> Function<'a> {
+        Function {
+            address,
+            size: 0x10,
+            ranges: Vec::new(),
+            name: Name::new(name, NameMangling::Unmangled, Language::C),
+            compilation_dir: Cow::Borrowed(b"/src"),
+            lines: LineRecords::new(),
+            inlinees,
+            inline: true,
+            artificial: false,
+            noreturn: false,
+            calling_convention: None,
+            call_sites: Vec::new(),
+            entity: None,
+        }
+    }
+
+    #[test]
+    fn test_address_ranges() {
+        let mut function = inline_function("main", 0x1000, vec![]);
+        assert!(function
+            .address_ranges()
+            .eq(std::iter::once(0x1000..0x1010)));
+        assert_eq!(function.ranges_end(), 0x1010);
+        assert!(function.contains(0x100f));
+        assert!(!function.contains(0x1010));
+
+        function.size = 0x18;
+        function.ranges = vec![0x1000..0x1010, 0x3000..0x3008];
+        assert_eq!(
+            function.address_ranges().collect::<Vec<_>>(),
+            [0x1000..0x1010, 0x3000..0x3008]
+        );
+        assert_eq!(function.ranges_end(), 0x3008);
+        assert!(function.contains(0x3000));
+        assert!(!function.contains(0x1010));
+    }
+
+    #[test]
+    fn test_is_outlined() {
+        let mut function = inline_function("OUTLINED_FUNCTION_12", 0x1000, vec![]);
+        assert!(function.is_outlined());
+
+        function.name = Name::from("_OUTLINED_FUNCTION_0");
+        assert!(function.is_outlined());
+
+        function.name = Name::from("OUTLINED_FUNCTION_");
+        assert!(!function.is_outlined());
+
+        function.name = Name::from("main");
+        assert!(!function.is_outlined());
+    }
+
+    #[test]
+    fn test_limit_inline_depth() {
+        let mut function = inline_function(
+            "main",
+            0x1000,
+            vec![
+                inline_function(
+                    "a",
+                    0x1000,
+                    vec![inline_function(
+                        "b",
+                        0x1000,
+                        vec![inline_function("c", 0x1000, vec![])],
+                    )],
+                ),
+                inline_function("d", 0x1010, vec![inline_function("e", 0x1010, vec![])]),
+            ],
+        );
+        function.inline = false;
+        assert_eq!(function.inline_depth(), 3);
+
+        function.limit_inline_depth(1);
+        assert_eq!(function.inline_depth(), 2);
+
+        let a = &function.inlinees[0];
+        assert_eq!(a.name.as_str(), "a");
+        assert_eq!(a.inlinees[0].name.as_str(), "… 2 frames inlined");
+        assert_eq!(a.inlinees[0].address, 0x1000);
+        assert!(a.inlinees[0].inlinees.is_empty());
+
+        let d = &function.inlinees[1];
+        assert_eq!(d.name.as_str(), "d");
+        assert_eq!(d.inlinees[0].name.as_str(), "… 1 frame inlined");
+        assert_eq!(d.inlinees[0].compilation_dir.as_ref(), b"/src");
+
+        function.limit_inline_depth(0);
+        assert_eq!(function.inline_depth(), 1);
+        assert_eq!(function.inlinees[0].name.as_str(), "… 2 frames inlined");
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_serialize_function() {
+        let mut function = inline_function("main", 0x1000, vec![]);
+        function.inline = false;
+        function.entity = Some(DebugEntity::DwarfDie(0x2a));
+        function.lines.push(LineInfo {
+            address: 0x1000,
+            size: Some(0x10),
+            file: file_info("src", "main.c"),
+            line: 42,
+        });
+
+        let value = serde_json::to_value(&function).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "address": 0x1000,
+                "size": 0x10,
+                "ranges": [],
+                "name": {"string": "main", "lang": "c", "mangling": "Unmangled"},
+                "compilation_dir": "/src",
+                "lines": [{
+                    "address": 0x1000,
+                    "size": 0x10,
+                    "file": {"name": "main.c", "dir": "src", "path_kind": "Unknown"},
+                    "line": 42,
+                }],
+                "inlinees": [],
+                "inline": false,
+                "artificial": false,
+                "noreturn": false,
+                "calling_convention": null,
+                "call_sites": [],
+                "entity": {"DwarfDie": 0x2a},
+            })
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_serialize_file_entry() {
+        let entry = file_entry("/home/user", "src", "main.c");
+        let value = serde_json::to_value(&entry).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "compilation_dir": "/home/user",
+                "name": "main.c",
+                "dir": "src",
+                "path_kind": "Unknown",
+            })
+        );
+    }
 }
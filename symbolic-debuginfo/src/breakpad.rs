@@ -1,15 +1,19 @@
 //! Support for Breakpad ASCII symbols, used by the Breakpad and Crashpad libraries.
 
 use std::borrow::Cow;
+use std::collections::btree_map::Entry;
 use std::collections::BTreeMap;
 use std::error::Error;
 use std::fmt;
+use std::io::{self, Write};
 use std::ops::Range;
 use std::str;
 
 use thiserror::Error;
 
-use symbolic_common::{Arch, AsSelf, CodeId, DebugId, Language, Name, NameMangling};
+use symbolic_common::{
+    Arch, AsSelf, CodeId, CpuFamily, DebugId, Language, Name, NameMangling, PathRemapper,
+};
 
 use crate::base::*;
 use crate::shared::Parse;
@@ -146,6 +150,18 @@ pub enum BreakpadErrorKind {
 
     /// The architecture is invalid.
     InvalidArchitecture,
+
+    /// The debug identifier of the module does not match the requested module.
+    DebugIdMismatch,
+
+    /// The code identifier of the module does not match the requested module.
+    CodeIdMismatch,
+
+    /// The architecture of the module does not match the requested module.
+    ArchMismatch,
+
+    /// A limit of the debug session was exceeded, see [`SessionLimits`].
+    LimitExceeded,
 }
 
 impl fmt::Display for BreakpadErrorKind {
@@ -156,6 +172,10 @@ impl fmt::Display for BreakpadErrorKind {
             Self::Parse(_) => write!(f, "parsing error"),
             Self::InvalidModuleId => write!(f, "invalid module id"),
             Self::InvalidArchitecture => write!(f, "invalid architecture"),
+            Self::DebugIdMismatch => write!(f, "debug id does not match module"),
+            Self::CodeIdMismatch => write!(f, "code id does not match module"),
+            Self::ArchMismatch => write!(f, "architecture does not match module"),
+            Self::LimitExceeded => write!(f, "debug session limit exceeded"),
             _ => Ok(()),
         }
     }
@@ -163,11 +183,12 @@ impl fmt::Display for BreakpadErrorKind {
 
 /// An error when dealing with [`BreakpadObject`](struct.BreakpadObject.html).
 #[derive(Debug, Error)]
-#[error("{kind}")]
+#[error("{kind}{}", LocationSuffix(.location))]
 pub struct BreakpadError {
     kind: BreakpadErrorKind,
     #[source]
     source: Option<Box<dyn Error + Send + Sync + 'static>>,
+    location: Option<ErrorLocation>,
 }
 
 impl BreakpadError {
@@ -178,18 +199,53 @@ impl BreakpadError {
         E: Into<Box<dyn Error + Send + Sync>>,
     {
         let source = Some(source.into());
-        Self { kind, source }
+        Self {
+            kind,
+            source,
+            location: None,
+        }
     }
 
     /// Returns the corresponding [`BreakpadErrorKind`] for this error.
     pub fn kind(&self) -> BreakpadErrorKind {
         self.kind
     }
+
+    /// Returns the location in the file at which this error occurred, if known.
+    pub fn location(&self) -> Option<&ErrorLocation> {
+        self.location.as_ref()
+    }
+
+    /// Attaches a location to this error, unless a more specific location is already known.
+    fn at<S>(mut self, section: S, offset: u64) -> Self
+    where
+        S: Into<Cow<'static, str>>,
+    {
+        if self.location.is_none() {
+            self.location = Some(ErrorLocation::new(section, offset));
+        }
+        self
+    }
+
+    /// Converts this error into a [`Diagnostic`].
+    pub(crate) fn to_diagnostic(&self) -> Diagnostic {
+        Diagnostic::from_error(self.kind, self.source.as_deref(), self.location.as_ref())
+    }
 }
 
 impl From<BreakpadErrorKind> for BreakpadError {
     fn from(kind: BreakpadErrorKind) -> Self {
-        Self { kind, source: None }
+        Self {
+            kind,
+            source: None,
+            location: None,
+        }
+    }
+}
+
+impl From<LimitExceeded> for BreakpadError {
+    fn from(e: LimitExceeded) -> Self {
+        Self::new(BreakpadErrorKind::LimitExceeded, e)
     }
 }
 
@@ -282,7 +338,7 @@ impl<'d> Iterator for BreakpadInfoRecords<'d> {
             return None;
         }
 
-        for line in &mut self.lines {
+        for (offset, line) in &mut self.lines.0 {
             if line.starts_with(b"MODULE ") {
                 continue;
             }
@@ -292,7 +348,7 @@ impl<'d> Iterator for BreakpadInfoRecords<'d> {
                 break;
             }
 
-            return Some(BreakpadInfoRecord::parse(line));
+            return Some(BreakpadInfoRecord::parse(line).map_err(|e| e.at("INFO", offset as u64)));
         }
 
         self.finished = true;
@@ -341,7 +397,7 @@ impl<'d> Iterator for BreakpadFileRecords<'d> {
             return None;
         }
 
-        for line in &mut self.lines {
+        for (offset, line) in &mut self.lines.0 {
             if line.starts_with(b"MODULE ") || line.starts_with(b"INFO ") {
                 continue;
             }
@@ -351,7 +407,7 @@ impl<'d> Iterator for BreakpadFileRecords<'d> {
                 break;
             }
 
-            return Some(BreakpadFileRecord::parse(line));
+            return Some(BreakpadFileRecord::parse(line).map_err(|e| e.at("FILE", offset as u64)));
         }
 
         self.finished = true;
@@ -402,7 +458,7 @@ impl<'d> Iterator for BreakpadPublicRecords<'d> {
             return None;
         }
 
-        for line in &mut self.lines {
+        for (offset, line) in &mut self.lines.0 {
             // Fast path: PUBLIC records are always before stack records. Once we encounter the
             // first stack record, we can therefore exit.
             if line.starts_with(b"STACK ") {
@@ -413,7 +469,8 @@ impl<'d> Iterator for BreakpadPublicRecords<'d> {
                 continue;
             }
 
-            return Some(BreakpadPublicRecord::parse(line));
+            let record = BreakpadPublicRecord::parse(line);
+            return Some(record.map_err(|e| e.at("PUBLIC", offset as u64)));
         }
 
         self.finished = true;
@@ -439,6 +496,7 @@ pub struct BreakpadFuncRecord<'d> {
     /// The demangled function name.
     pub name: &'d str,
     lines: Lines<'d>,
+    offset: usize,
 }
 
 impl<'d> BreakpadFuncRecord<'d> {
@@ -508,7 +566,7 @@ impl<'d> Iterator for BreakpadFuncRecords<'d> {
             return None;
         }
 
-        for line in &mut self.lines {
+        while let Some((offset, line)) = self.lines.0.next() {
             // Fast path: FUNC records are always before stack records. Once we encounter the
             // first stack record, we can therefore exit.
             if line.starts_with(b"STACK ") {
@@ -519,7 +577,11 @@ impl<'d> Iterator for BreakpadFuncRecords<'d> {
                 continue;
             }
 
-            return Some(BreakpadFuncRecord::parse(line, self.lines.clone()));
+            let record = BreakpadFuncRecord::parse(line, self.lines.clone());
+            return Some(match record {
+                Ok(record) => Ok(BreakpadFuncRecord { offset, ..record }),
+                Err(error) => Err(error.at("FUNC", offset as u64)),
+            });
         }
 
         self.finished = true;
@@ -582,7 +644,7 @@ impl<'d> Iterator for BreakpadLineRecords<'d> {
             return None;
         }
 
-        for line in &mut self.lines {
+        for (offset, line) in &mut self.lines.0 {
             // Stop parsing LINE records once other expected records are encountered.
             if line.starts_with(b"FUNC ")
                 || line.starts_with(b"PUBLIC ")
@@ -600,7 +662,7 @@ impl<'d> Iterator for BreakpadLineRecords<'d> {
 
             let record = match BreakpadLineRecord::parse(line) {
                 Ok(record) => record,
-                Err(error) => return Some(Err(error)),
+                Err(error) => return Some(Err(error.at("LINE", offset as u64))),
             };
 
             // Skip line records for empty ranges. These do not carry any information.
@@ -826,16 +888,20 @@ impl<'d> Iterator for BreakpadStackRecords<'d> {
             return None;
         }
 
-        while let Some(line) = self.lines.next() {
+        while let Some((offset, line)) = self.lines.0.next() {
             if line.starts_with(b"STACK WIN") {
-                return Some(BreakpadStackRecord::parse(line));
+                let record = BreakpadStackRecord::parse(line);
+                return Some(record.map_err(|e| e.at("STACK WIN", offset as u64)));
             }
 
             if line.starts_with(b"STACK CFI INIT") {
-                return Some(BreakpadStackCfiRecord::parse(line).map(|mut r| {
-                    r.deltas = self.lines.clone();
-                    BreakpadStackRecord::Cfi(r)
-                }));
+                return Some(match BreakpadStackCfiRecord::parse(line) {
+                    Ok(mut record) => {
+                        record.deltas = self.lines.clone();
+                        Ok(BreakpadStackRecord::Cfi(record))
+                    }
+                    Err(error) => Err(error.at("STACK CFI INIT", offset as u64)),
+                });
             }
         }
 
@@ -844,6 +910,230 @@ impl<'d> Iterator for BreakpadStackRecords<'d> {
     }
 }
 
+/// A single rule of a `STACK CFI` record, recovering the value of one register.
+///
+/// Example: `.cfa: $rsp 8 +`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct BreakpadCfiRule<'d> {
+    /// The register recovered by this rule, such as `.cfa`, `.ra` or `$rbp`.
+    pub register: &'d str,
+
+    /// The postfix expression computing the caller's value of the register.
+    pub expression: &'d str,
+}
+
+impl fmt::Display for BreakpadCfiRule<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.register, self.expression)
+    }
+}
+
+/// Splits a string of CFI rules and merges them into `rules`.
+///
+/// Rules for registers that already have a rule replace the previous rule, so that applying the
+/// rules of a `STACK CFI INIT` record followed by its delta records yields the effective rules.
+fn merge_cfi_rules<'d>(
+    rules: &mut Vec<BreakpadCfiRule<'d>>,
+    string: &'d str,
+) -> Result<(), BreakpadError> {
+    fn push<'d>(
+        rules: &mut Vec<BreakpadCfiRule<'d>>,
+        register: &'d str,
+        expression: &'d str,
+    ) -> Result<(), BreakpadError> {
+        let expression = expression.trim();
+        if register.is_empty() || expression.is_empty() {
+            let message = format!("missing expression for register `{}`", register);
+            return Err(BreakpadError::new(BreakpadErrorKind::Parse(""), message));
+        }
+
+        let rule = BreakpadCfiRule {
+            register,
+            expression,
+        };
+
+        match rules.iter_mut().find(|r| r.register == register) {
+            Some(existing) => *existing = rule,
+            None => rules.push(rule),
+        }
+
+        Ok(())
+    }
+
+    let mut register = None;
+    let mut start = 0;
+
+    for token in string.split_whitespace() {
+        let offset = token.as_ptr() as usize - string.as_ptr() as usize;
+
+        if let Some(name) = token.strip_suffix(':') {
+            if let Some(register) = register {
+                push(rules, register, &string[start..offset])?;
+            }
+
+            register = Some(name);
+            start = offset + token.len();
+        } else if register.is_none() {
+            let message = format!("expected register name, found `{}`", token);
+            return Err(BreakpadError::new(BreakpadErrorKind::Parse(""), message));
+        }
+    }
+
+    match register {
+        Some(register) => push(rules, register, &string[start..]),
+        None => Ok(()),
+    }
+}
+
+/// The unwind rules in effect for a range of addresses.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BreakpadUnwindRules<'d> {
+    /// The effective CFI rules, combining a `STACK CFI INIT` record with all preceding `STACK CFI`
+    /// delta records.
+    Cfi(Vec<BreakpadCfiRule<'d>>),
+    /// A Windows stack record, used for x86 binaries.
+    Win(BreakpadStackWinRecord<'d>),
+}
+
+/// An entry of a [`BreakpadCfiTable`], containing unwind rules for a range of addresses.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BreakpadCfiEntry<'d> {
+    /// The range of addresses covered by this entry, relative to the module's load address.
+    pub range: Range<u64>,
+
+    /// The unwind rules in effect for all addresses in the range.
+    pub rules: BreakpadUnwindRules<'d>,
+}
+
+impl BreakpadCfiEntry<'_> {
+    /// Returns the CFI rules of this entry as a single string.
+    ///
+    /// The string can be passed directly to an evaluator of Breakpad CFI rules, such as
+    /// `.cfa: $rsp 16 + .ra: .cfa -8 + ^ $rbp: .cfa -16 + ^`. Returns `None` for Windows stack
+    /// records.
+    pub fn cfi_rules_string(&self) -> Option<String> {
+        match self.rules {
+            BreakpadUnwindRules::Cfi(ref rules) => {
+                let rules: Vec<_> = rules.iter().map(ToString::to_string).collect();
+                Some(rules.join(" "))
+            }
+            BreakpadUnwindRules::Win(_) => None,
+        }
+    }
+}
+
+/// Unwind information of a Breakpad object, sorted by address for efficient lookups.
+///
+/// In contrast to [`BreakpadStackRecords`], the `STACK CFI` delta records are already applied to
+/// their `STACK CFI INIT` record. Each entry therefore contains the complete set of rules needed
+/// to unwind a frame at any address within its range.
+///
+/// This is returned by [`BreakpadObject::cfi_records`].
+#[derive(Clone, Debug, Default)]
+pub struct BreakpadCfiTable<'d> {
+    entries: Vec<BreakpadCfiEntry<'d>>,
+}
+
+impl<'d> BreakpadCfiTable<'d> {
+    /// Builds a table from an iterator of stack records.
+    pub fn from_records<I>(records: I) -> Result<Self, BreakpadError>
+    where
+        I: IntoIterator<Item = Result<BreakpadStackRecord<'d>, BreakpadError>>,
+    {
+        let mut entries = Vec::new();
+
+        for record in records {
+            match record? {
+                BreakpadStackRecord::Cfi(record) => {
+                    let range = record.range();
+
+                    let mut deltas = Vec::new();
+                    for delta in record.deltas() {
+                        let delta = delta?;
+                        if range.contains(&delta.address) {
+                            deltas.push(delta);
+                        }
+                    }
+                    deltas.sort_by_key(|delta| delta.address);
+
+                    let mut rules = Vec::new();
+                    merge_cfi_rules(&mut rules, record.init_rules)?;
+
+                    let mut start = range.start;
+                    for delta in deltas {
+                        if delta.address > start {
+                            entries.push(BreakpadCfiEntry {
+                                range: start..delta.address,
+                                rules: BreakpadUnwindRules::Cfi(rules.clone()),
+                            });
+                            start = delta.address;
+                        }
+
+                        merge_cfi_rules(&mut rules, delta.rules)?;
+                    }
+
+                    if range.end > start {
+                        entries.push(BreakpadCfiEntry {
+                            range: start..range.end,
+                            rules: BreakpadUnwindRules::Cfi(rules),
+                        });
+                    }
+                }
+                BreakpadStackRecord::Win(record) => {
+                    let range = record.code_range();
+                    if !range.is_empty() {
+                        entries.push(BreakpadCfiEntry {
+                            range: range.start.into()..range.end.into(),
+                            rules: BreakpadUnwindRules::Win(record),
+                        });
+                    }
+                }
+            }
+        }
+
+        entries.sort_by_key(|entry| entry.range.start);
+        Ok(Self { entries })
+    }
+
+    /// Looks up the unwind rules in effect at the given module-relative address.
+    ///
+    /// If multiple entries cover the address, the one with the highest start address is returned.
+    pub fn lookup(&self, address: u64) -> Option<&BreakpadCfiEntry<'d>> {
+        let index = self
+            .entries
+            .partition_point(|entry| entry.range.start <= address);
+
+        self.entries[..index]
+            .iter()
+            .rev()
+            .find(|entry| entry.range.contains(&address))
+    }
+
+    /// Returns an iterator over all entries, ordered by their start address.
+    pub fn iter(&self) -> std::slice::Iter<'_, BreakpadCfiEntry<'d>> {
+        self.entries.iter()
+    }
+
+    /// Returns the number of entries in this table.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if this table contains no unwind information.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl<'a, 'd> IntoIterator for &'a BreakpadCfiTable<'d> {
+    type Item = &'a BreakpadCfiEntry<'d>;
+    type IntoIter = std::slice::Iter<'a, BreakpadCfiEntry<'d>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
 /// A Breakpad object file.
 ///
 /// To process minidump crash reports without having to understand all sorts of native symbol
@@ -909,6 +1199,10 @@ impl<'data> BreakpadObject<'data> {
     }
 
     /// The code identifier of this object.
+    ///
+    /// This is read from the `INFO CODE_ID` record, which contains the build ID of ELF files, the
+    /// UUID of MachO files, or the timestamp and size of PE files. If the record is missing from
+    /// symbols of a MachO file, the code identifier is derived from the module's UUID.
     pub fn code_id(&self) -> Option<CodeId> {
         for result in self.info_records().flatten() {
             if let BreakpadInfoRecord::CodeId { code_id, .. } = result {
@@ -918,9 +1212,78 @@ impl<'data> BreakpadObject<'data> {
             }
         }
 
+        if self.module.os.eq_ignore_ascii_case("mac") && self.id.appendix() == 0 {
+            return Some(CodeId::from_binary(self.id.uuid().as_bytes()));
+        }
+
         None
     }
 
+    /// The name of the code file, as declared in the `INFO CODE_ID` record.
+    ///
+    /// Symbols dumped from PDBs declare the name of the executable or library here, such as
+    /// `crash.exe`. Other platforms usually omit it, since the code file has the same name as the
+    /// module.
+    pub fn code_file(&self) -> Option<&'data str> {
+        self.info_records()
+            .flatten()
+            .find_map(|record| match record {
+                BreakpadInfoRecord::CodeId { code_file, .. }
+                    if !code_file.is_empty() && code_file != UNKNOWN_NAME =>
+                {
+                    Some(code_file)
+                }
+                _ => None,
+            })
+    }
+
+    /// Checks whether these symbols belong to the given module.
+    ///
+    /// Symbol files are usually associated with modules by their debug identifier alone. Using
+    /// symbols of a different build or architecture silently produces wrong symbolication, so this
+    /// additionally verifies:
+    ///
+    ///  - The debug identifiers are equal, or [`DebugIdMismatch`] is returned.
+    ///  - The code identifiers are equal if both are known, or [`CodeIdMismatch`] is returned.
+    ///  - The architectures belong to the same CPU family if both are known, or [`ArchMismatch`]
+    ///    is returned.
+    ///
+    /// [`DebugIdMismatch`]: BreakpadErrorKind::DebugIdMismatch
+    /// [`CodeIdMismatch`]: BreakpadErrorKind::CodeIdMismatch
+    /// [`ArchMismatch`]: BreakpadErrorKind::ArchMismatch
+    pub fn check_module(
+        &self,
+        debug_id: DebugId,
+        code_id: Option<&CodeId>,
+        arch: Arch,
+    ) -> Result<(), BreakpadError> {
+        if self.id != debug_id {
+            let message = format!("expected {debug_id}, found {}", self.id);
+            return Err(BreakpadError::new(
+                BreakpadErrorKind::DebugIdMismatch,
+                message,
+            ));
+        }
+
+        if let (Some(expected), Some(actual)) = (code_id, self.code_id()) {
+            if !expected.is_nil() && *expected != actual {
+                let message = format!("expected {expected}, found {actual}");
+                return Err(BreakpadError::new(
+                    BreakpadErrorKind::CodeIdMismatch,
+                    message,
+                ));
+            }
+        }
+
+        let known = |arch: Arch| arch.cpu_family() != CpuFamily::Unknown;
+        if known(arch) && known(self.arch) && arch.cpu_family() != self.arch.cpu_family() {
+            let message = format!("expected {arch}, found {}", self.arch);
+            return Err(BreakpadError::new(BreakpadErrorKind::ArchMismatch, message));
+        }
+
+        Ok(())
+    }
+
     /// The debug information identifier of this object.
     pub fn debug_id(&self) -> DebugId {
         self.id
@@ -988,6 +1351,9 @@ impl<'data> BreakpadObject<'data> {
         Ok(BreakpadDebugSession {
             file_map: self.file_map(),
             func_records: self.func_records(),
+            path_remapper: None,
+            limits: SessionLimits::default(),
+            diagnostics: Diagnostics::default(),
         })
     }
 
@@ -1054,10 +1420,254 @@ impl<'data> BreakpadObject<'data> {
         }
     }
 
+    /// Parses all stack frame records into a table of unwind rules keyed by address range.
+    ///
+    /// See [`BreakpadCfiTable`] for more information.
+    pub fn cfi_records(&self) -> Result<BreakpadCfiTable<'data>, BreakpadError> {
+        BreakpadCfiTable::from_records(self.stack_records())
+    }
+
     /// Returns the raw data of the Breakpad file.
     pub fn data(&self) -> &'data [u8] {
         self.data
     }
+
+    /// Splits this file into symbol information and stack unwinding information.
+    ///
+    /// Both parts start with the `MODULE` and `INFO` records of this file, so that they can be
+    /// parsed as Breakpad objects on their own. Duplicate records are removed in the same way as
+    /// by [`merge`](Self::merge), and file identifiers are renumbered.
+    pub fn split(&self) -> Result<BreakpadSplit, BreakpadError> {
+        let mut records = BreakpadRecordSet::default();
+        records.add(self)?;
+
+        let mut split = BreakpadSplit::default();
+        // Writing into a vector cannot fail.
+        records.write_symbols(&mut split.symbols).ok();
+        records.write_stack(&mut split.stack).ok();
+        Ok(split)
+    }
+
+    /// Merges multiple partial Breakpad files of the same module into a single file.
+    ///
+    /// This combines, for example, a file containing only `PUBLIC` records with a file containing
+    /// only `STACK` records. All files must have the same debug identifier and compatible code
+    /// identifiers and architectures, see [`check_module`](Self::check_module). The `MODULE`
+    /// record is taken from the first file.
+    ///
+    /// Records are de-duplicated: If multiple files contain a function, public symbol or stack
+    /// record at the same address, the record of the first file is kept. `FILE` records are merged
+    /// by path and renumbered. All records are written in the order of their addresses.
+    pub fn merge<'o, I>(objects: I) -> Result<Vec<u8>, BreakpadError>
+    where
+        'data: 'o,
+        I: IntoIterator<Item = &'o BreakpadObject<'data>>,
+    {
+        let mut objects = objects.into_iter();
+        let first = objects.next().ok_or(BreakpadErrorKind::InvalidMagic)?;
+
+        let mut records = BreakpadRecordSet::default();
+        records.add(first)?;
+
+        let code_id = first.code_id();
+        for object in objects {
+            object.check_module(first.debug_id(), code_id.as_ref(), first.arch())?;
+            records.add(object)?;
+        }
+
+        let mut buffer = Vec::new();
+        // Writing into a vector cannot fail.
+        records.write_symbols(&mut buffer).ok();
+        records.write_stack_records(&mut buffer).ok();
+        Ok(buffer)
+    }
+}
+
+/// The parts of a Breakpad file, as returned by [`BreakpadObject::split`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BreakpadSplit {
+    /// A Breakpad file with the `FILE`, `FUNC` and `PUBLIC` records, including line records.
+    pub symbols: Vec<u8>,
+    /// A Breakpad file with the `STACK CFI` and `STACK WIN` records.
+    pub stack: Vec<u8>,
+}
+
+/// A de-duplicated set of records from one or more Breakpad files of the same module.
+#[derive(Default)]
+struct BreakpadRecordSet<'d> {
+    module: &'d [u8],
+    info: Vec<&'d [u8]>,
+    files: BTreeMap<&'d str, u64>,
+    next_file_id: u64,
+    funcs: BTreeMap<u64, (BreakpadFuncRecord<'d>, Vec<BreakpadLineRecord>)>,
+    publics: BTreeMap<u64, BreakpadPublicRecord<'d>>,
+    stack_cfi: BTreeMap<u64, Vec<&'d [u8]>>,
+    stack_win: BTreeMap<(u32, i32), &'d [u8]>,
+}
+
+impl<'d> BreakpadRecordSet<'d> {
+    /// Adds all records of the given object that are not yet part of this set.
+    fn add(&mut self, object: &BreakpadObject<'d>) -> Result<(), BreakpadError> {
+        let mut cfi_start = None;
+
+        for (offset, line) in Lines::new(object.data).0 {
+            if line.starts_with(b"MODULE ") {
+                if self.module.is_empty() {
+                    self.module = line;
+                }
+            } else if line.starts_with(b"INFO ") {
+                if !self.info.contains(&line) {
+                    self.info.push(line);
+                }
+            } else if line.starts_with(b"STACK CFI INIT") {
+                let record = BreakpadStackCfiRecord::parse(line)
+                    .map_err(|e| e.at("STACK CFI INIT", offset as u64))?;
+
+                // Delta records of a duplicate `STACK CFI INIT` record are skipped as well.
+                cfi_start = match self.stack_cfi.entry(record.start) {
+                    Entry::Vacant(entry) => {
+                        entry.insert(vec![line]);
+                        Some(record.start)
+                    }
+                    Entry::Occupied(_) => None,
+                };
+                continue;
+            } else if line.starts_with(b"STACK CFI") {
+                if let Some(lines) = cfi_start.and_then(|start| self.stack_cfi.get_mut(&start)) {
+                    lines.push(line);
+                }
+                continue;
+            } else if line.starts_with(b"STACK WIN") {
+                let record = BreakpadStackWinRecord::parse(line)
+                    .map_err(|e| e.at("STACK WIN", offset as u64))?;
+                let key = (record.code_start, record.ty as i32);
+                self.stack_win.entry(key).or_insert(line);
+            }
+
+            cfi_start = None;
+        }
+
+        let mut file_ids = BTreeMap::new();
+        for file in object.file_records() {
+            let file = file?;
+            let id = match self.files.get(file.name) {
+                Some(id) => *id,
+                None => {
+                    let id = self.allocate_file_id();
+                    self.files.insert(file.name, id);
+                    id
+                }
+            };
+            file_ids.insert(file.id, id);
+        }
+
+        for func in object.func_records() {
+            let func = func?;
+            if self.funcs.contains_key(&func.address) {
+                continue;
+            }
+
+            let mut lines = Vec::new();
+            for line in func.lines() {
+                let mut line = line?;
+
+                // Line records referencing a missing file record retain a file id of their own.
+                line.file_id = match file_ids.get(&line.file_id) {
+                    Some(id) => *id,
+                    None => {
+                        let id = self.allocate_file_id();
+                        file_ids.insert(line.file_id, id);
+                        id
+                    }
+                };
+
+                lines.push(line);
+            }
+
+            self.funcs.insert(func.address, (func, lines));
+        }
+
+        for public in object.public_records() {
+            let public = public?;
+            self.publics.entry(public.address).or_insert(public);
+        }
+
+        Ok(())
+    }
+
+    fn allocate_file_id(&mut self) -> u64 {
+        let id = self.next_file_id;
+        self.next_file_id += 1;
+        id
+    }
+
+    /// Writes the `MODULE` and `INFO` records.
+    fn write_header<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(self.module)?;
+        writer.write_all(b"\n")?;
+
+        for info in &self.info {
+            writer.write_all(info)?;
+            writer.write_all(b"\n")?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the header followed by all `FILE`, `FUNC`, line and `PUBLIC` records.
+    fn write_symbols<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.write_header(writer)?;
+
+        let files: BTreeMap<_, _> = self.files.iter().map(|(name, id)| (id, name)).collect();
+        for (id, name) in files {
+            writeln!(writer, "FILE {} {}", id, name)?;
+        }
+
+        for (func, lines) in self.funcs.values() {
+            let multiple = if func.multiple { "m " } else { "" };
+            writeln!(
+                writer,
+                "FUNC {}{:x} {:x} {:x} {}",
+                multiple, func.address, func.size, func.parameter_size, func.name
+            )?;
+
+            for line in lines {
+                writeln!(
+                    writer,
+                    "{:x} {:x} {} {}",
+                    line.address, line.size, line.line, line.file_id
+                )?;
+            }
+        }
+
+        for public in self.publics.values() {
+            let multiple = if public.multiple { "m " } else { "" };
+            writeln!(
+                writer,
+                "PUBLIC {}{:x} {:x} {}",
+                multiple, public.address, public.parameter_size, public.name
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the header followed by all `STACK` records.
+    fn write_stack<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.write_header(writer)?;
+        self.write_stack_records(writer)
+    }
+
+    /// Writes all `STACK CFI` and `STACK WIN` records.
+    fn write_stack_records<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let lines = self.stack_cfi.values().flatten();
+        for line in lines.chain(self.stack_win.values()) {
+            writer.write_all(line)?;
+            writer.write_all(b"\n")?;
+        }
+
+        Ok(())
+    }
 }
 
 impl fmt::Debug for BreakpadObject<'_> {
@@ -1180,14 +1790,48 @@ impl<'data> Iterator for BreakpadSymbolIterator<'data> {
 pub struct BreakpadDebugSession<'data> {
     file_map: BreakpadFileMap<'data>,
     func_records: BreakpadFuncRecords<'data>,
+    path_remapper: Option<PathRemapper>,
+    limits: SessionLimits,
+    diagnostics: Diagnostics,
 }
 
 impl<'data> BreakpadDebugSession<'data> {
+    /// Sets a [`PathRemapper`] applied to all file paths in this debug session.
+    ///
+    /// Remapping is disabled by default.
+    pub fn set_path_remapper(&mut self, remapper: Option<PathRemapper>) {
+        self.path_remapper = remapper;
+    }
+
+    /// Enables collection of [`Diagnostic`]s instead of failing on malformed records.
+    ///
+    /// When enabled, `FUNC` records that cannot be parsed, including their `LINE` records, are
+    /// skipped and recorded. Use [`take_diagnostics`](Self::take_diagnostics) to retrieve them
+    /// after iterating. This is disabled by default.
+    pub fn set_collect_diagnostics(&mut self, collect: bool) {
+        self.diagnostics.set_enabled(collect);
+    }
+
+    /// Sets the [`SessionLimits`] enforced by the function iterators of this session.
+    ///
+    /// There are no limits by default.
+    pub fn set_limits(&mut self, limits: SessionLimits) {
+        self.limits = limits;
+    }
+
+    /// Returns all [`Diagnostic`]s collected so far and clears them.
+    pub fn take_diagnostics(&self) -> Vec<Diagnostic> {
+        self.diagnostics.take()
+    }
+
     /// Returns an iterator over all functions in this debug file.
     pub fn functions(&self) -> BreakpadFunctionIterator<'_> {
         BreakpadFunctionIterator {
             file_map: &self.file_map,
             func_records: self.func_records.clone(),
+            remapper: self.path_remapper.as_ref(),
+            limits: LimitTracker::new(self.limits),
+            diagnostics: &self.diagnostics,
         }
     }
 
@@ -1195,6 +1839,7 @@ impl<'data> BreakpadDebugSession<'data> {
     pub fn files(&self) -> BreakpadFileIterator<'_> {
         BreakpadFileIterator {
             files: self.file_map.values(),
+            remapper: self.path_remapper.as_ref(),
         }
     }
 
@@ -1227,6 +1872,7 @@ impl<'data, 'session> DebugSession<'session> for BreakpadDebugSession<'data> {
 /// An iterator over source files in a Breakpad object.
 pub struct BreakpadFileIterator<'s> {
     files: std::collections::btree_map::Values<'s, u64, &'s str>,
+    remapper: Option<&'s PathRemapper>,
 }
 
 impl<'s> Iterator for BreakpadFileIterator<'s> {
@@ -1235,8 +1881,8 @@ impl<'s> Iterator for BreakpadFileIterator<'s> {
     fn next(&mut self) -> Option<Self::Item> {
         let path = self.files.next()?;
         Some(Ok(FileEntry {
-            compilation_dir: &[],
-            info: FileInfo::from_path(path.as_bytes()),
+            compilation_dir: Cow::Borrowed(&[]),
+            info: FileInfo::from_path(path.as_bytes()).remap(self.remapper),
         }))
     }
 }
@@ -1245,19 +1891,28 @@ impl<'s> Iterator for BreakpadFileIterator<'s> {
 pub struct BreakpadFunctionIterator<'s> {
     file_map: &'s BreakpadFileMap<'s>,
     func_records: BreakpadFuncRecords<'s>,
+    remapper: Option<&'s PathRemapper>,
+    limits: LimitTracker,
+    diagnostics: &'s Diagnostics,
 }
 
 impl<'s> BreakpadFunctionIterator<'s> {
     fn convert(&self, record: BreakpadFuncRecord<'s>) -> Result<Function<'s>, BreakpadError> {
-        let mut lines = Vec::new();
+        let mut lines = LineRecords::new();
+        let mut language = Language::from_mangled(record.name);
         for line in record.lines() {
             let line = line?;
             let filename = line.filename(self.file_map).unwrap_or_default();
 
+            // Breakpad records carry no language information. Guess it from the source files.
+            if language == Language::Unknown {
+                language = Language::from_path(filename);
+            }
+
             lines.push(LineInfo {
                 address: line.address,
                 size: Some(line.size),
-                file: FileInfo::from_path(filename.as_bytes()),
+                file: FileInfo::from_path(filename.as_bytes()).remap(self.remapper),
                 line: line.line,
             });
         }
@@ -1265,11 +1920,19 @@ impl<'s> BreakpadFunctionIterator<'s> {
         Ok(Function {
             address: record.address,
             size: record.size,
-            name: Name::new(record.name, NameMangling::Unmangled, Language::Unknown),
-            compilation_dir: &[],
+            ranges: Vec::new(),
+            name: Name::new(record.name, NameMangling::Unmangled, language),
+            compilation_dir: Cow::Borrowed(&[]),
             lines,
             inlinees: Vec::new(),
             inline: false,
+            // Breakpad does not record artificial functions, but outlined functions can be
+            // recognized by their name.
+            artificial: is_outlined_name(record.name),
+            noreturn: false,
+            calling_convention: None,
+            call_sites: Vec::new(),
+            entity: Some(DebugEntity::BreakpadFunc(record.offset as u64)),
         })
     }
 }
@@ -1278,11 +1941,27 @@ impl<'s> Iterator for BreakpadFunctionIterator<'s> {
     type Item = Result<Function<'s>, BreakpadError>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        match self.func_records.next() {
-            Some(Ok(record)) => Some(self.convert(record)),
-            Some(Err(error)) => Some(Err(error)),
-            None => None,
+        if self.limits.is_exceeded() {
+            return None;
+        }
+
+        while let Some(result) = self.func_records.next() {
+            let error = match result.and_then(|record| self.convert(record)) {
+                Ok(function) => match self.limits.track(&function) {
+                    Ok(()) => return Some(Ok(function)),
+                    Err(error) => return Some(Err(error.into())),
+                },
+                Err(error) => error,
+            };
+
+            if !self.diagnostics.is_enabled() {
+                return Some(Err(error));
+            }
+
+            self.diagnostics.push(error.to_diagnostic());
         }
+
+        None
     }
 }
 
@@ -1290,7 +1969,7 @@ impl std::iter::FusedIterator for BreakpadFunctionIterator<'_> {}
 
 mod parsing {
     use nom::branch::alt;
-    use nom::bytes::complete::take_while;
+    use nom::bytes::complete::{take_while, take_while1};
     use nom::character::complete::{char, hex_digit1, multispace1};
     use nom::combinator::{cond, eof, rest};
     use nom::sequence::{pair, tuple};
@@ -1455,7 +2134,8 @@ mod parsing {
 
     /// Parse the `CodeId` variant of a [`BreakpadInfoRecord`].
     ///
-    /// A `CodeId` record has the form `CODE_ID <code_id>( <code_file>)?`.
+    /// A `CodeId` record has the form `CODE_ID <code_id>( <code_file>)?`. The code id may be
+    /// written as a UUID with dashes, which some tools emit for MachO files.
     fn info_code_id_record(input: &str) -> ParseResult<BreakpadInfoRecord> {
         let (input, _) = tag("CODE_ID")
             .terminated(multispace1)
@@ -1463,7 +2143,7 @@ mod parsing {
             .parse(input)?;
 
         let (input, (code_id, code_file)) = pair(
-            hex_digit1
+            take_while1(|c: char| c.is_ascii_hexdigit() || c == '-')
                 .terminated(multispace1.or(eof))
                 .context("code id"),
             name.context("file name"),
@@ -1617,6 +2297,7 @@ mod parsing {
                 parameter_size,
                 name,
                 lines: Lines::default(),
+                offset: 0,
             },
         ))
     }
@@ -2161,6 +2842,157 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_cfi_records() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 D3096ED481217FD4C16B29CD9BC208BA0 firefox-bin
+STACK CFI INIT 1880 2d .cfa: $rsp 8 + .ra: .cfa -8 + ^
+STACK CFI 1881 .cfa: $rsp 16 +
+STACK CFI 1884 $rbp: .cfa -16 + ^ .cfa: $rbp 16 +
+STACK WIN 4 2170 14 1 0 0 0 0 0 1 $eip 4 + ^ = $esp $ebp 8 + = $ebp $ebp ^ =
+";
+        let object = BreakpadObject::parse(data)?;
+        let table = object.cfi_records()?;
+        assert_eq!(table.len(), 4);
+
+        let entry = table.lookup(0x1880).unwrap();
+        assert_eq!(entry.range, 0x1880..0x1881);
+        assert_eq!(
+            entry.cfi_rules_string().unwrap(),
+            ".cfa: $rsp 8 + .ra: .cfa -8 + ^"
+        );
+
+        let entry = table.lookup(0x1883).unwrap();
+        assert_eq!(entry.range, 0x1881..0x1884);
+        assert_eq!(
+            entry.cfi_rules_string().unwrap(),
+            ".cfa: $rsp 16 + .ra: .cfa -8 + ^"
+        );
+
+        let entry = table.lookup(0x18ac).unwrap();
+        assert_eq!(entry.range, 0x1884..0x18ad);
+        assert_eq!(
+            entry.cfi_rules_string().unwrap(),
+            ".cfa: $rbp 16 + .ra: .cfa -8 + ^ $rbp: .cfa -16 + ^"
+        );
+
+        let entry = table.lookup(0x2170).unwrap();
+        assert!(matches!(entry.rules, BreakpadUnwindRules::Win(_)));
+        assert_eq!(entry.cfi_rules_string(), None);
+
+        assert_eq!(table.lookup(0x18ad), None);
+        assert_eq!(table.lookup(0x187f), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cfi_records_invalid_rules() {
+        let data = b"MODULE Linux x86_64 D3096ED481217FD4C16B29CD9BC208BA0 firefox-bin
+STACK CFI INIT 1880 2d .cfa: .ra: .cfa -8 + ^
+";
+        let object = BreakpadObject::parse(data).unwrap();
+        let error = object.cfi_records().unwrap_err();
+        assert_eq!(error.kind(), BreakpadErrorKind::Parse(""));
+    }
+
+    #[test]
+    fn test_function_diagnostics() -> Result<(), BreakpadError> {
+        let data = b"MODULE Linux x86_64 D3096ED481217FD4C16B29CD9BC208BA0 firefox-bin
+FILE 0 main.c
+FUNC 1000 10 0 good
+1000 10 1 0
+FUNC zz
+FUNC 2000 10 0 other
+";
+        let bad_offset = data.windows(7).position(|w| w == b"FUNC zz").unwrap() as u64;
+
+        let object = BreakpadObject::parse(data)?;
+        let mut session = object.debug_session()?;
+
+        let error = session.functions().nth(1).unwrap().unwrap_err();
+        let location = error.location().unwrap();
+        assert_eq!(location.section.as_deref(), Some("FUNC"));
+        assert_eq!(location.offset, Some(bad_offset));
+        assert!(error
+            .to_string()
+            .ends_with(&format!("(in FUNC at offset {:#x})", bad_offset)));
+        assert!(session.take_diagnostics().is_empty());
+
+        session.set_collect_diagnostics(true);
+        let names = session
+            .functions()
+            .map(|f| f.map(|f| f.name.as_str().to_owned()))
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(names, ["good", "other"]);
+
+        let diagnostics = session.take_diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].location, *location);
+        assert!(diagnostics[0].message.starts_with("parsing error: "));
+        assert!(session.take_diagnostics().is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_code_id_dashed_uuid() -> Result<(), BreakpadError> {
+        let data = b"MODULE mac arm64 67E9247C814E392BA027DBDE6748FCBF0 crash
+INFO CODE_ID 67E9247C-814E-392B-A027-DBDE6748FCBF
+";
+        let object = BreakpadObject::parse(data)?;
+        let code_id = object.code_id().unwrap();
+        assert_eq!(code_id.as_str(), "67e9247c814e392ba027dbde6748fcbf");
+        assert_eq!(object.code_file(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_code_id_mac_fallback() -> Result<(), BreakpadError> {
+        let data = b"MODULE mac x86_64 67E9247C814E392BA027DBDE6748FCBF0 crash\n";
+        let object = BreakpadObject::parse(data)?;
+        let code_id = object.code_id().unwrap();
+        assert_eq!(code_id.as_str(), "67e9247c814e392ba027dbde6748fcbf");
+
+        let data = b"MODULE Linux x86_64 C0BCC3F19827FE653058404B2831D9E60 crash\n";
+        let object = BreakpadObject::parse(data)?;
+        assert_eq!(object.code_id(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_module() -> Result<(), BreakpadError> {
+        let data = b"MODULE windows x86 3249D99D0C4049318610F4E4FB0B69361 crash.pdb
+INFO CODE_ID 5AB380779000 crash.exe
+";
+        let object = BreakpadObject::parse(data)?;
+        assert_eq!(object.code_file(), Some("crash.exe"));
+
+        let debug_id = object.debug_id();
+        let code_id = CodeId::new("5AB380779000".into());
+        object.check_module(debug_id, Some(&code_id), Arch::X86)?;
+        object.check_module(debug_id, None, Arch::Unknown)?;
+
+        let other_id = "3249D99D0C4049318610F4E4FB0B69362".parse().unwrap();
+        let error = object.check_module(other_id, None, Arch::X86).unwrap_err();
+        assert_eq!(error.kind(), BreakpadErrorKind::DebugIdMismatch);
+
+        let other_code = CodeId::new("5AB380779001".into());
+        let error = object
+            .check_module(debug_id, Some(&other_code), Arch::X86)
+            .unwrap_err();
+        assert_eq!(error.kind(), BreakpadErrorKind::CodeIdMismatch);
+
+        let error = object
+            .check_module(debug_id, None, Arch::Arm64)
+            .unwrap_err();
+        assert_eq!(error.kind(), BreakpadErrorKind::ArchMismatch);
+        assert_eq!(error.to_string(), "architecture does not match module");
+
+        Ok(())
+    }
+
     use similar_asserts::assert_eq;
 
     #[test]
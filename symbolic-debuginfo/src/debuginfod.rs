@@ -0,0 +1,328 @@
+//! Helpers to look up ELF files on [debuginfod] servers by their GNU build id.
+//!
+//! debuginfod is an HTTP file server that serves executables, debug information and sources of
+//! ELF files indexed by their GNU build id. Many Linux distributions operate public servers, so
+//! that debug information does not need to be uploaded separately.
+//!
+//! This module does not perform any network requests. Instead, it builds the lookup URLs for an
+//! artifact via [`DebuginfodServers`], and verifies the downloaded files with [`verify_object`] or
+//! [`load_object`] before they are used:
+//!
+//! ```
+//! use symbolic_common::CodeId;
+//! use symbolic_debuginfo::debuginfod::{DebuginfodArtifact, DebuginfodServers};
+//!
+//! let servers = DebuginfodServers::parse("https://debuginfod.example.org/");
+//! let build_id = CodeId::new("f1c3bcc0279865fe3058404b2831d9e64135386c".into());
+//!
+//! assert_eq!(
+//!     servers.lookup_urls(&build_id, DebuginfodArtifact::DebugInfo),
+//!     vec![
+//!         "https://debuginfod.example.org/buildid/f1c3bcc0279865fe3058404b2831d9e64135386c/debuginfo"
+//!     ]
+//! );
+//! ```
+//!
+//! [debuginfod]: https://sourceware.org/elfutils/Debuginfod.html
+
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+
+use thiserror::Error;
+
+use symbolic_common::{ByteView, CodeId, SelfCell};
+
+use crate::base::*;
+use crate::Object;
+
+/// The environment variable listing debuginfod servers, separated by whitespace.
+pub const DEBUGINFOD_URLS: &str = "DEBUGINFOD_URLS";
+
+/// The error type for [`DebuginfodError`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DebuginfodErrorKind {
+    /// The downloaded file is not a valid object file.
+    InvalidObject,
+
+    /// The downloaded file is not an ELF file.
+    UnexpectedFormat(FileFormat),
+
+    /// The downloaded file does not declare a build id.
+    MissingBuildId,
+
+    /// The build id of the downloaded file does not match the requested build id.
+    BuildIdMismatch,
+
+    /// The downloaded file does not contain debug information.
+    MissingDebugInfo,
+}
+
+impl fmt::Display for DebuginfodErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidObject => write!(f, "invalid object file"),
+            Self::UnexpectedFormat(format) => write!(f, "unexpected {} file", format),
+            Self::MissingBuildId => write!(f, "missing build id"),
+            Self::BuildIdMismatch => write!(f, "build id mismatch"),
+            Self::MissingDebugInfo => write!(f, "missing debug information"),
+        }
+    }
+}
+
+/// An error when verifying files downloaded from a debuginfod server.
+#[derive(Debug, Error)]
+#[error("{kind}")]
+pub struct DebuginfodError {
+    kind: DebuginfodErrorKind,
+    #[source]
+    source: Option<Box<dyn Error + Send + Sync + 'static>>,
+}
+
+impl DebuginfodError {
+    /// Creates a new debuginfod error from a known kind of error as well as an arbitrary error
+    /// payload.
+    fn new<E>(kind: DebuginfodErrorKind, source: E) -> Self
+    where
+        E: Into<Box<dyn Error + Send + Sync>>,
+    {
+        let source = Some(source.into());
+        Self { kind, source }
+    }
+
+    /// Returns the corresponding [`DebuginfodErrorKind`] for this error.
+    pub fn kind(&self) -> DebuginfodErrorKind {
+        self.kind
+    }
+}
+
+impl From<DebuginfodErrorKind> for DebuginfodError {
+    fn from(kind: DebuginfodErrorKind) -> Self {
+        Self { kind, source: None }
+    }
+}
+
+/// An artifact that can be requested from a debuginfod server.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DebuginfodArtifact<'a> {
+    /// The executable or shared library, which may be stripped.
+    Executable,
+    /// The separate debug information file.
+    DebugInfo,
+    /// A source file referenced by the debug information, by its absolute path.
+    Source(&'a str),
+}
+
+impl DebuginfodArtifact<'_> {
+    /// Returns the URL path component of this artifact following the build id.
+    pub(crate) fn path(&self) -> Cow<'_, str> {
+        match *self {
+            Self::Executable => Cow::Borrowed("executable"),
+            Self::DebugInfo => Cow::Borrowed("debuginfo"),
+            Self::Source(path) => {
+                let mut encoded = String::from("source");
+                if !path.starts_with('/') {
+                    encoded.push('/');
+                }
+                percent_encode_path(path, &mut encoded);
+                Cow::Owned(encoded)
+            }
+        }
+    }
+}
+
+/// Percent-encodes a path, retaining path separators and unreserved characters.
+fn percent_encode_path(path: &str, out: &mut String) {
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+}
+
+/// Builds the URL of an artifact on a single debuginfod server.
+///
+/// The build id is formatted as lowercase hexadecimal string. Trailing slashes of the server URL
+/// are ignored.
+pub fn lookup_url(server: &str, build_id: &CodeId, artifact: DebuginfodArtifact<'_>) -> String {
+    format!(
+        "{}/buildid/{}/{}",
+        server.trim_end_matches('/'),
+        build_id.as_str().to_ascii_lowercase(),
+        artifact.path()
+    )
+}
+
+/// A list of debuginfod servers, queried in order.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DebuginfodServers {
+    urls: Vec<String>,
+}
+
+impl DebuginfodServers {
+    /// Creates a list of servers from the given base URLs.
+    pub fn new<I, S>(urls: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let urls = urls
+            .into_iter()
+            .map(Into::into)
+            .filter(|url| !url.is_empty())
+            .collect();
+
+        Self { urls }
+    }
+
+    /// Parses a whitespace separated list of server URLs, as used by `DEBUGINFOD_URLS`.
+    pub fn parse(urls: &str) -> Self {
+        Self::new(urls.split_whitespace())
+    }
+
+    /// Reads the list of servers from the `DEBUGINFOD_URLS` environment variable.
+    ///
+    /// Returns an empty list if the variable is not set.
+    pub fn from_env() -> Self {
+        match std::env::var(DEBUGINFOD_URLS) {
+            Ok(urls) => Self::parse(&urls),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Returns `true` if no servers are configured.
+    pub fn is_empty(&self) -> bool {
+        self.urls.is_empty()
+    }
+
+    /// Returns the base URLs of all servers.
+    pub fn urls(&self) -> &[String] {
+        &self.urls
+    }
+
+    /// Builds the URLs of an artifact on all servers, in order.
+    ///
+    /// Returns an empty list if the build id is empty.
+    pub fn lookup_urls(&self, build_id: &CodeId, artifact: DebuginfodArtifact<'_>) -> Vec<String> {
+        if build_id.is_nil() {
+            return Vec::new();
+        }
+
+        self.urls
+            .iter()
+            .map(|server| lookup_url(server, build_id, artifact))
+            .collect()
+    }
+}
+
+/// Parses a file downloaded from a debuginfod server and verifies that it matches the request.
+///
+/// The file must be an ELF object with the requested build id. For
+/// [`DebuginfodArtifact::DebugInfo`], the object must also contain debug information. Source
+/// artifacts are not objects and cannot be verified.
+pub fn verify_object<'data>(
+    data: &'data [u8],
+    build_id: &CodeId,
+    artifact: DebuginfodArtifact<'_>,
+) -> Result<Object<'data>, DebuginfodError> {
+    let object = Object::parse(data)
+        .map_err(|e| DebuginfodError::new(DebuginfodErrorKind::InvalidObject, e))?;
+
+    if object.file_format() != FileFormat::Elf {
+        return Err(DebuginfodErrorKind::UnexpectedFormat(object.file_format()).into());
+    }
+
+    let actual = object
+        .code_id()
+        .ok_or(DebuginfodErrorKind::MissingBuildId)?;
+    if !actual.as_str().eq_ignore_ascii_case(build_id.as_str()) {
+        return Err(DebuginfodErrorKind::BuildIdMismatch.into());
+    }
+
+    if artifact == DebuginfodArtifact::DebugInfo && !object.has_debug_info() {
+        return Err(DebuginfodErrorKind::MissingDebugInfo.into());
+    }
+
+    Ok(object)
+}
+
+/// An [`Object`] that owns its underlying data.
+pub type OwnedObject = SelfCell<ByteView<'static>, Object<'static>>;
+
+/// Verifies a downloaded file like [`verify_object`] and returns an object that owns the data.
+///
+/// This is useful to keep a downloaded file, for instance from [`ByteView::open`] or
+/// [`ByteView::read`], in a cache without tracking its lifetime separately.
+pub fn load_object(
+    byteview: ByteView<'static>,
+    build_id: &CodeId,
+    artifact: DebuginfodArtifact<'_>,
+) -> Result<OwnedObject, DebuginfodError> {
+    SelfCell::try_new(byteview, |data| {
+        // SAFETY: the data is only borrowed by the object, which is stored in the same cell.
+        verify_object(unsafe { &*data }, build_id, artifact)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use similar_asserts::assert_eq;
+
+    fn build_id() -> CodeId {
+        CodeId::new("F1C3BCC0279865FE".into())
+    }
+
+    #[test]
+    fn test_lookup_url() {
+        assert_eq!(
+            lookup_url(
+                "https://debuginfod.example.org//",
+                &build_id(),
+                DebuginfodArtifact::Executable
+            ),
+            "https://debuginfod.example.org/buildid/f1c3bcc0279865fe/executable"
+        );
+    }
+
+    #[test]
+    fn test_source_url() {
+        assert_eq!(
+            lookup_url(
+                "http://localhost:8002",
+                &build_id(),
+                DebuginfodArtifact::Source("/usr/src/my lib/a#b.c")
+            ),
+            "http://localhost:8002/buildid/f1c3bcc0279865fe/source/usr/src/my%20lib/a%23b.c"
+        );
+    }
+
+    #[test]
+    fn test_servers() {
+        let servers = DebuginfodServers::parse("  https://a.example.org\n\thttps://b.example.org ");
+        assert_eq!(
+            servers.urls(),
+            &["https://a.example.org", "https://b.example.org"]
+        );
+
+        let urls = servers.lookup_urls(&build_id(), DebuginfodArtifact::DebugInfo);
+        assert_eq!(
+            urls,
+            vec![
+                "https://a.example.org/buildid/f1c3bcc0279865fe/debuginfo",
+                "https://b.example.org/buildid/f1c3bcc0279865fe/debuginfo",
+            ]
+        );
+
+        let nil = CodeId::new(String::new());
+        assert!(servers
+            .lookup_urls(&nil, DebuginfodArtifact::DebugInfo)
+            .is_empty());
+        assert!(DebuginfodServers::parse(" ").is_empty());
+    }
+}
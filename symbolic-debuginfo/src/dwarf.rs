@@ -8,7 +8,8 @@
 //! [`MachObject`]: ../macho/struct.MachObject.html
 
 use std::borrow::Cow;
-use std::collections::BTreeSet;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap};
 use std::error::Error;
 use std::fmt;
 use std::marker::PhantomData;
@@ -16,12 +17,14 @@ use std::ops::Deref;
 use std::sync::Arc;
 
 use fallible_iterator::FallibleIterator;
-use gimli::read::{AttributeValue, Error as GimliError, Range};
+use gimli::read::{AttributeValue, Error as GimliError, Range, Reader};
 use gimli::{constants, DwarfFileType, UnitSectionOffset};
 use lazycell::LazyCell;
 use thiserror::Error;
 
-use symbolic_common::{AsSelf, Language, Name, NameMangling, SelfCell};
+use symbolic_common::{
+    AsSelf, Language, Name, NameMangling, NameNormalizer, PathRemapper, SelfCell,
+};
 
 use crate::base::*;
 #[cfg(feature = "macho")]
@@ -88,6 +91,12 @@ pub enum DwarfErrorKind {
 
     /// The DWARF file is corrupted. See the cause for more information.
     CorruptedData,
+
+    /// A DWARF expression requires a value that was not provided.
+    IncompleteExpression,
+
+    /// A limit of the debug session was exceeded, see [`SessionLimits`].
+    LimitExceeded,
 }
 
 impl fmt::Display for DwarfErrorKind {
@@ -100,17 +109,20 @@ impl fmt::Display for DwarfErrorKind {
             Self::UnexpectedInline => write!(f, "unexpected inline function without parent"),
             Self::InvertedFunctionRange => write!(f, "function with inverted address range"),
             Self::CorruptedData => write!(f, "corrupted dwarf debug data"),
+            Self::IncompleteExpression => write!(f, "dwarf expression requires missing value"),
+            Self::LimitExceeded => write!(f, "debug session limit exceeded"),
         }
     }
 }
 
 /// An error handling [`DWARF`](trait.Dwarf.html) debugging information.
 #[derive(Debug, Error)]
-#[error("{kind}")]
+#[error("{kind}{}", LocationSuffix(.location))]
 pub struct DwarfError {
     kind: DwarfErrorKind,
     #[source]
     source: Option<Box<dyn Error + Send + Sync + 'static>>,
+    location: Option<ErrorLocation>,
 }
 
 impl DwarfError {
@@ -121,18 +133,53 @@ impl DwarfError {
         E: Into<Box<dyn Error + Send + Sync>>,
     {
         let source = Some(source.into());
-        Self { kind, source }
+        Self {
+            kind,
+            source,
+            location: None,
+        }
     }
 
     /// Returns the corresponding [`DwarfErrorKind`] for this error.
     pub fn kind(&self) -> DwarfErrorKind {
         self.kind
     }
+
+    /// Returns the location in the file at which this error occurred, if known.
+    pub fn location(&self) -> Option<&ErrorLocation> {
+        self.location.as_ref()
+    }
+
+    /// Attaches a location to this error, unless a more specific location is already known.
+    fn at<S>(mut self, section: S, offset: u64) -> Self
+    where
+        S: Into<Cow<'static, str>>,
+    {
+        if self.location.is_none() {
+            self.location = Some(ErrorLocation::new(section, offset));
+        }
+        self
+    }
+
+    /// Converts this error into a [`Diagnostic`].
+    pub(crate) fn to_diagnostic(&self) -> Diagnostic {
+        Diagnostic::from_error(self.kind, self.source.as_deref(), self.location.as_ref())
+    }
 }
 
 impl From<DwarfErrorKind> for DwarfError {
     fn from(kind: DwarfErrorKind) -> Self {
-        Self { kind, source: None }
+        Self {
+            kind,
+            source: None,
+            location: None,
+        }
+    }
+}
+
+impl From<LimitExceeded> for DwarfError {
+    fn from(e: LimitExceeded) -> Self {
+        Self::new(DwarfErrorKind::LimitExceeded, e)
     }
 }
 
@@ -217,6 +264,91 @@ pub trait Dwarf<'data> {
     }
 }
 
+/// The maximum number of rows read from a single line program.
+///
+/// Malformed line programs can encode an excessive number of rows in very little space. Rows beyond
+/// this limit are dropped with a [`DwarfLineWarning::TooManyRows`] warning.
+const MAX_LINE_PROGRAM_ROWS: usize = 1 << 24;
+
+/// The maximum number of warnings recorded for a single line program.
+const MAX_LINE_PROGRAM_WARNINGS: usize = 100;
+
+/// A recoverable issue encountered while reading a DWARF line program.
+///
+/// Line programs with such issues are still used, but the affected rows are skipped or clamped.
+/// Use [`DwarfDebugSession::line_warnings`] to list the warnings of all compilation units.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DwarfLineWarning {
+    /// The line program is corrupted and could not be read to the end.
+    ///
+    /// All rows before the corrupted instruction are retained.
+    InvalidProgram,
+
+    /// A row's address is lower than the address of the previous row in the same sequence.
+    ///
+    /// The row is skipped.
+    DecreasingAddress {
+        /// The address of the skipped row.
+        address: u64,
+        /// The address of the previous row.
+        previous: u64,
+    },
+
+    /// The end of a sequence exceeds the address space.
+    ///
+    /// The end address of the sequence is clamped to the maximum address.
+    AddressOverflow {
+        /// The address of the last row in the sequence.
+        address: u64,
+    },
+
+    /// The line program contains more rows than supported.
+    ///
+    /// All rows after the limit are dropped.
+    TooManyRows {
+        /// The maximum number of rows read from a line program.
+        limit: usize,
+    },
+}
+
+impl fmt::Display for DwarfLineWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::InvalidProgram => write!(f, "corrupted line program"),
+            Self::DecreasingAddress { address, previous } => write!(
+                f,
+                "line row at {:#x} precedes previous row at {:#x}",
+                address, previous
+            ),
+            Self::AddressOverflow { address } => {
+                write!(f, "line sequence ending after {:#x} overflows", address)
+            }
+            Self::TooManyRows { limit } => {
+                write!(f, "line program exceeds the limit of {} rows", limit)
+            }
+        }
+    }
+}
+
+/// Line program warnings of a single compilation unit.
+#[derive(Clone, Debug)]
+pub struct DwarfUnitLineWarnings<'data> {
+    /// The name of the compilation unit, usually the path to its primary source file.
+    pub name: &'data [u8],
+    /// The path of the compilation directory.
+    pub compilation_dir: Cow<'data, [u8]>,
+    /// Warnings in the order they were encountered, capped to a reasonable number.
+    pub warnings: Vec<DwarfLineWarning>,
+}
+
+impl<'data> DwarfUnitLineWarnings<'data> {
+    /// The name of the compilation unit as UTF-8 string.
+    pub fn name_str(&self) -> Cow<'data, str> {
+        String::from_utf8_lossy(self.name)
+    }
+}
+
 /// A row in the DWARF line program.
 #[derive(Debug)]
 struct DwarfRow {
@@ -239,16 +371,51 @@ struct DwarfSequence {
 struct DwarfLineProgram<'d> {
     header: LineNumberProgramHeader<'d>,
     sequences: Vec<DwarfSequence>,
+    warnings: Vec<DwarfLineWarning>,
 }
 
 impl<'d, 'a> DwarfLineProgram<'d> {
     fn prepare(program: IncompleteLineNumberProgram<'d>) -> Self {
         let mut sequences = Vec::new();
         let mut sequence_rows = Vec::<DwarfRow>::new();
+        let mut warnings = Vec::new();
         let mut prev_address = 0;
+        let mut row_count = 0;
         let mut state_machine = program.rows();
 
-        while let Ok(Some((_, &program_row))) = state_machine.next_row() {
+        let mut warn = |warning| {
+            if warnings.len() < MAX_LINE_PROGRAM_WARNINGS {
+                warnings.push(warning);
+            }
+        };
+
+        // Computes the end of a sequence after its last row, saturating at the end of the address
+        // space.
+        let sequence_end = |address: u64, warn: &mut dyn FnMut(DwarfLineWarning)| {
+            address.checked_add(1).unwrap_or_else(|| {
+                warn(DwarfLineWarning::AddressOverflow { address });
+                address
+            })
+        };
+
+        loop {
+            let program_row = match state_machine.next_row() {
+                Ok(Some((_, &program_row))) => program_row,
+                Ok(None) => break,
+                Err(_) => {
+                    warn(DwarfLineWarning::InvalidProgram);
+                    break;
+                }
+            };
+
+            row_count += 1;
+            if row_count > MAX_LINE_PROGRAM_ROWS {
+                warn(DwarfLineWarning::TooManyRows {
+                    limit: MAX_LINE_PROGRAM_ROWS,
+                });
+                break;
+            }
+
             let address = program_row.address();
 
             // we have seen rustc emit for WASM targets a bad sequence that spans from 0 to
@@ -273,7 +440,7 @@ impl<'d, 'a> DwarfLineProgram<'d> {
                         // Take a defensive approach and ensure that `high_address` always covers
                         // the last encountered row, assuming a 1 byte instruction.
                         end: if address < prev_address {
-                            prev_address + 1
+                            sequence_end(prev_address, &mut warn)
                         } else {
                             address
                         },
@@ -288,6 +455,10 @@ impl<'d, 'a> DwarfLineProgram<'d> {
                 //
                 // If we wanted to handle this, we could start a new sequence
                 // here, but let's wait until that is needed.
+                warn(DwarfLineWarning::DecreasingAddress {
+                    address,
+                    previous: prev_address,
+                });
             } else {
                 let file_index = program_row.file_index();
                 let line = program_row.line().map(|v| v.get());
@@ -315,7 +486,7 @@ impl<'d, 'a> DwarfLineProgram<'d> {
             // A sequence without an end_sequence row.
             // Let's assume the last row covered 1 byte.
             let start = sequence_rows[0].address;
-            let end = prev_address + 1;
+            let end = sequence_end(prev_address, &mut warn);
             sequences.push(DwarfSequence {
                 start,
                 end,
@@ -329,6 +500,7 @@ impl<'d, 'a> DwarfLineProgram<'d> {
         DwarfLineProgram {
             header: state_machine.header().clone(),
             sequences,
+            warnings,
         }
     }
 
@@ -353,6 +525,10 @@ impl<'d, 'a> DwarfLineProgram<'d> {
     }
 }
 
+/// Function names resolved from `DW_AT_abstract_origin` and `DW_AT_specification` references, keyed
+/// by the `.debug_info` offset of the referenced entry.
+type NameCache<'d> = HashMap<u64, Option<Name<'d>>>;
+
 /// A slim wrapper around a DWARF unit.
 #[derive(Clone, Copy, Debug)]
 struct UnitRef<'d, 'a> {
@@ -407,12 +583,30 @@ impl<'d, 'a> UnitRef<'d, 'a> {
         self.unit.header.offset()
     }
 
+    /// Returns the `.debug_info` offset of the entry referenced by an attribute.
+    fn reference_offset(&self, value: AttributeValue<Slice<'d>>) -> Option<u64> {
+        let offset = match value {
+            AttributeValue::UnitRef(offset) => offset.to_unit_section_offset(self.unit),
+            AttributeValue::DebugInfoRef(offset) => offset.into(),
+            _ => return None,
+        };
+
+        match offset {
+            UnitSectionOffset::DebugInfoOffset(offset) => Some(offset.0 as u64),
+            UnitSectionOffset::DebugTypesOffset(_) => None,
+        }
+    }
+
     /// Resolves the function name of a debug entry.
+    ///
+    /// If a `cache` is given, names resolved through references are memoized, so that the chain of
+    /// references is only followed once for all inline instances of the same abstract origin.
     fn resolve_function_name(
         &self,
         entry: &Die<'d, '_>,
         language: Language,
         bcsymbolmap: Option<&'d BcSymbolMap<'d>>,
+        mut cache: Option<&mut NameCache<'d>>,
     ) -> Result<Option<Name<'d>>, DwarfError> {
         let mut attrs = entry.attrs();
         let mut fallback_name = None;
@@ -445,17 +639,135 @@ impl<'d, 'a> UnitRef<'d, 'a> {
         }
 
         if let Some(attr) = reference_target {
-            return self.resolve_reference(attr, |ref_unit, ref_entry| {
+            let key = self.reference_offset(attr.value());
+            if let (Some(cache), Some(key)) = (cache.as_deref(), key) {
+                if let Some(name) = cache.get(&key) {
+                    return Ok(name.clone());
+                }
+            }
+
+            let name = self.resolve_reference(attr, |ref_unit, ref_entry| {
                 if self.offset() != ref_unit.offset() || entry.offset() != ref_entry.offset() {
-                    ref_unit.resolve_function_name(ref_entry, language, bcsymbolmap)
+                    ref_unit.resolve_function_name(
+                        ref_entry,
+                        language,
+                        bcsymbolmap,
+                        cache.as_deref_mut(),
+                    )
                 } else {
                     Ok(None)
                 }
-            });
+            })?;
+
+            if let (Some(cache), Some(key)) = (cache, key) {
+                cache.insert(key, name.clone());
+            }
+
+            return Ok(name);
         }
 
         Ok(None)
     }
+
+    /// Returns the value of an attribute of a debug entry.
+    ///
+    /// If the entry does not declare the attribute, it is looked up in the entry's abstract origin
+    /// or specification instead.
+    fn inherited_attr_value(
+        &self,
+        entry: &Die<'d, '_>,
+        name: constants::DwAt,
+    ) -> Result<Option<AttributeValue<Slice<'d>>>, DwarfError> {
+        let mut attrs = entry.attrs();
+        let mut reference_target = None;
+
+        while let Some(attr) = attrs.next()? {
+            match attr.name() {
+                attr_name if attr_name == name => return Ok(Some(attr.value())),
+                constants::DW_AT_abstract_origin | constants::DW_AT_specification => {
+                    reference_target = Some(attr);
+                }
+                _ => {}
+            }
+        }
+
+        match reference_target {
+            Some(attr) => self.resolve_reference(attr, |ref_unit, ref_entry| {
+                if self.offset() != ref_unit.offset() || entry.offset() != ref_entry.offset() {
+                    ref_unit.inherited_attr_value(ref_entry, name)
+                } else {
+                    Ok(None)
+                }
+            }),
+            None => Ok(None),
+        }
+    }
+
+    /// Determines whether a debug entry was generated by the compiler.
+    ///
+    /// This is the case if the entry or its abstract origin is marked with `DW_AT_artificial` or
+    /// `DW_AT_trampoline`.
+    fn is_artificial(&self, entry: &Die<'d, '_>) -> Result<bool, DwarfError> {
+        let mut attrs = entry.attrs();
+        let mut reference_target = None;
+
+        while let Some(attr) = attrs.next()? {
+            match attr.name() {
+                constants::DW_AT_artificial if attr.value() == AttributeValue::Flag(true) => {
+                    return Ok(true);
+                }
+                // The trampoline attribute either is a flag or refers to the target function.
+                constants::DW_AT_trampoline if attr.value() != AttributeValue::Flag(false) => {
+                    return Ok(true);
+                }
+                constants::DW_AT_abstract_origin | constants::DW_AT_specification => {
+                    reference_target = Some(attr);
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(attr) = reference_target {
+            let artificial = self.resolve_reference(attr, |ref_unit, ref_entry| {
+                if self.offset() != ref_unit.offset() || entry.offset() != ref_entry.offset() {
+                    ref_unit.is_artificial(ref_entry).map(Some)
+                } else {
+                    Ok(None)
+                }
+            })?;
+
+            return Ok(artificial.unwrap_or(false));
+        }
+
+        Ok(false)
+    }
+
+    /// Resolves and relocates an address attribute value.
+    ///
+    /// Returns `None` for a zero address, which indicates code eliminated by the linker.
+    fn address_value(&self, value: AttributeValue<Slice<'d>>) -> Result<Option<u64>, DwarfError> {
+        let address = match value {
+            AttributeValue::Addr(address) => address,
+            AttributeValue::DebugAddrIndex(index) => self.info.address(self.unit, index)?,
+            _ => return Err(GimliError::UnsupportedAttributeForm.into()),
+        };
+
+        if address == 0 && self.info.kind != ObjectKind::Relocatable {
+            return Ok(None);
+        }
+
+        Ok(Some(offset(
+            address,
+            self.info.relocation.offset_at(address),
+        )))
+    }
+
+    /// Returns the `.debug_info` offset of the abstract origin of a debug entry, if declared.
+    fn abstract_origin(&self, entry: &Die<'d, '_>) -> Result<Option<u64>, DwarfError> {
+        Ok(entry
+            .attr_value(constants::DW_AT_abstract_origin)?
+            .and_then(|value| self.reference_offset(value)))
+    }
 }
 
 /// Wrapper around a DWARF Unit.
@@ -463,6 +775,9 @@ impl<'d, 'a> UnitRef<'d, 'a> {
 struct DwarfUnit<'d, 'a> {
     inner: UnitRef<'d, 'a>,
     bcsymbolmap: Option<&'d BcSymbolMap<'d>>,
+    normalizer: Option<&'d NameNormalizer>,
+    remapper: Option<&'d PathRemapper>,
+    interner: Option<&'d StringInterner>,
     language: Language,
     line_program: Option<DwarfLineProgram<'d>>,
     prefer_dwarf_names: bool,
@@ -474,6 +789,9 @@ impl<'d, 'a> DwarfUnit<'d, 'a> {
         unit: &'a Unit<'d>,
         info: &'a DwarfInfo<'d>,
         bcsymbolmap: Option<&'d BcSymbolMap<'d>>,
+        normalizer: Option<&'d NameNormalizer>,
+        remapper: Option<&'d PathRemapper>,
+        interner: Option<&'d StringInterner>,
     ) -> Result<Option<Self>, DwarfError> {
         let mut entries = unit.entries();
         let entry = match entries.next_dfs()? {
@@ -494,7 +812,10 @@ impl<'d, 'a> DwarfUnit<'d, 'a> {
 
         let language = match entry.attr_value(constants::DW_AT_language)? {
             Some(AttributeValue::Language(lang)) => language_from_dwarf(lang),
-            _ => Language::Unknown,
+            _ => match unit.name {
+                Some(ref name) => Language::from_path(&name.to_string_lossy()),
+                None => Language::Unknown,
+            },
         };
 
         let line_program = unit
@@ -511,9 +832,20 @@ impl<'d, 'a> DwarfUnit<'d, 'a> {
         // mangling is lossy, we need to load the demangled name instead.
         let prefer_dwarf_names = producer.as_deref() == Some(b"Dart VM");
 
+        // The Dart VM does not declare a source language, but its function names carry VM-internal
+        // decorations that are only removed when demangling them as Dart.
+        let language = if prefer_dwarf_names {
+            Language::Dart
+        } else {
+            language
+        };
+
         Ok(Some(DwarfUnit {
             inner: UnitRef { info, unit },
             bcsymbolmap,
+            normalizer,
+            remapper,
+            interner,
             language,
             line_program,
             prefer_dwarf_names,
@@ -521,11 +853,70 @@ impl<'d, 'a> DwarfUnit<'d, 'a> {
     }
 
     /// The path of the compilation directory. File names are usually relative to this path.
-    fn compilation_dir(&self) -> &'d [u8] {
-        match self.inner.unit.comp_dir {
+    fn compilation_dir(&self) -> Cow<'d, [u8]> {
+        let dir = match self.inner.unit.comp_dir {
             Some(ref dir) => resolve_byte_name(self.bcsymbolmap, dir.slice()),
             None => &[],
+        };
+
+        let dir = match self.remapper {
+            Some(remapper) => remapper.remap_bytes(dir),
+            None => Cow::Borrowed(dir),
+        };
+
+        match self.interner {
+            Some(interner) => interner.intern_path(dir),
+            None => dir,
+        }
+    }
+
+    /// Parses a call site entry, returning `None` if it does not declare a return address.
+    ///
+    /// This supports both `DW_TAG_call_site` from DWARF 5 and the `DW_TAG_GNU_call_site` extension
+    /// for DWARF 4. The target of the call is only resolved for direct calls to functions with an
+    /// address.
+    fn parse_call_site(&self, entry: &Die<'d, '_>) -> Result<Option<CallSite>, DwarfError> {
+        let mut return_address = None;
+        let mut origin = None;
+        let mut tail_call = false;
+
+        let mut attrs = entry.attrs();
+        while let Some(attr) = attrs.next()? {
+            match attr.name() {
+                constants::DW_AT_call_return_pc | constants::DW_AT_low_pc => {
+                    return_address = self.inner.address_value(attr.value())?;
+                }
+                constants::DW_AT_call_origin | constants::DW_AT_abstract_origin => {
+                    origin = Some(attr);
+                }
+                constants::DW_AT_call_tail_call | constants::DW_AT_GNU_tail_call => {
+                    tail_call = attr.value() == AttributeValue::Flag(true);
+                }
+                _ => {}
+            }
         }
+
+        let return_address = match return_address {
+            Some(address) => address,
+            None => return Ok(None),
+        };
+
+        let target =
+            match origin {
+                Some(attr) => self.inner.resolve_reference(attr, |ref_unit, ref_entry| {
+                    match ref_entry.attr_value(constants::DW_AT_low_pc)? {
+                        Some(value) => ref_unit.address_value(value),
+                        None => Ok(None),
+                    }
+                })?,
+                None => None,
+            };
+
+        Ok(Some(CallSite {
+            return_address,
+            target,
+            tail_call,
+        }))
     }
 
     /// Parses the call site and range lists of this Debugging Information Entry.
@@ -533,6 +924,7 @@ impl<'d, 'a> DwarfUnit<'d, 'a> {
         &self,
         entry: &Die<'d, '_>,
         range_buf: &mut Vec<Range>,
+        keep_zero_size: bool,
     ) -> Result<(Option<u64>, Option<u64>), DwarfError> {
         let mut tuple = (None, None);
         let mut low_pc = None;
@@ -618,7 +1010,7 @@ impl<'d, 'a> DwarfUnit<'d, 'a> {
             _ => return Ok(tuple),
         };
 
-        if low_pc == high_pc {
+        if low_pc == high_pc && !keep_zero_size {
             // most likely low_pc == high_pc means the DIE should be ignored.
             // https://sourceware.org/ml/gdb-patches/2011-03/msg00739.html
             return Ok(tuple);
@@ -665,10 +1057,15 @@ impl<'d, 'a> DwarfUnit<'d, 'a> {
             // At the end we exactly splited the initial range into 3 contiguous ranges
             // and each of them maps a different line.
             if let Some((first, rows)) = rows.split_first() {
+                // All rows of a range are located in the same segment.
+                let address_offset = self.inner.info.relocation.offset_at(range.begin);
                 let mut last_file = first.file_index;
+                let mut last_address = range.begin;
                 let mut last_info = LineInfo {
-                    address: offset(range.begin, self.inner.info.address_offset),
-                    size: first.size.map(|s| s + first.address - range.begin),
+                    address: offset(range.begin, address_offset),
+                    size: first
+                        .size
+                        .map(|s| first.address.saturating_add(s).saturating_sub(range.begin)),
                     file: self.resolve_file(first.file_index).unwrap_or_default(),
                     line: first.line.unwrap_or(0),
                 };
@@ -680,7 +1077,7 @@ impl<'d, 'a> DwarfUnit<'d, 'a> {
                     if (last_file, last_info.line) == (row.file_index, line) {
                         // We collapse the lines but need to fix the last line size
                         if let Some(size) = last_info.size.as_mut() {
-                            *size += row.size.unwrap_or(0);
+                            *size = size.saturating_add(row.size.unwrap_or(0));
                         }
 
                         continue;
@@ -690,17 +1087,19 @@ impl<'d, 'a> DwarfUnit<'d, 'a> {
                     lines.push(last_info);
 
                     last_file = row.file_index;
+                    last_address = row.address;
                     last_info = LineInfo {
-                        address: offset(row.address, self.inner.info.address_offset),
+                        address: offset(row.address, address_offset),
                         size: row.size,
                         file: self.resolve_file(row.file_index).unwrap_or_default(),
                         line,
                     };
                 }
 
-                // Fix the size of the last line
+                // Fix the size of the last line. Compute this on unrelocated addresses, since the
+                // relocation offset may wrap around.
                 if let Some(size) = last_info.size.as_mut() {
-                    *size = offset(range.end, self.inner.info.address_offset) - last_info.address;
+                    *size = range.end.saturating_sub(last_address);
                 }
 
                 lines.push(last_info);
@@ -716,17 +1115,37 @@ impl<'d, 'a> DwarfUnit<'d, 'a> {
         line_program: &LineNumberProgramHeader<'d>,
         file: &LineProgramFileEntry<'d>,
     ) -> FileInfo<'d> {
-        FileInfo {
-            dir: resolve_byte_name(
-                self.bcsymbolmap,
-                file.directory(line_program)
-                    .and_then(|attr| self.inner.slice_value(attr))
-                    .unwrap_or_default(),
-            ),
-            name: resolve_byte_name(
-                self.bcsymbolmap,
-                self.inner.slice_value(file.path_name()).unwrap_or_default(),
-            ),
+        let dir = resolve_byte_name(
+            self.bcsymbolmap,
+            file.directory(line_program)
+                .and_then(|attr| self.inner.slice_value(attr))
+                .unwrap_or_default(),
+        );
+        let name = resolve_byte_name(
+            self.bcsymbolmap,
+            self.inner.slice_value(file.path_name()).unwrap_or_default(),
+        );
+
+        // Classify the path before remapping, which may turn relative paths into absolute ones.
+        // Directory index 0 refers to the compilation directory in all DWARF versions.
+        let path_kind = if is_absolute_path(name) {
+            FilePathKind::Absolute
+        } else if file.directory_index() != 0 && is_absolute_path(dir) {
+            FilePathKind::DirectoryRelative
+        } else {
+            FilePathKind::CompDirRelative
+        };
+
+        let info = FileInfo {
+            dir: Cow::Borrowed(dir),
+            name: Cow::Borrowed(name),
+            path_kind,
+        };
+
+        let info = info.remap(self.remapper);
+        match self.interner {
+            Some(interner) => interner.intern_file(info),
+            None => info,
         }
     }
 
@@ -742,35 +1161,152 @@ impl<'d, 'a> DwarfUnit<'d, 'a> {
             .map(|file| self.file_info(line_program, file))
     }
 
+    /// Collects the macro information of this unit.
+    ///
+    /// Returns an empty list if the unit does not reference macro information.
+    fn macros(&self) -> Result<Vec<DwarfMacro<'d>>, DwarfError> {
+        let unit = self.inner.unit;
+        let mut entries = unit.entries();
+        let entry = match entries.next_dfs()? {
+            Some((_, entry)) => entry,
+            None => return Ok(Vec::new()),
+        };
+
+        let info = self.inner.info;
+        let mut parser = DwarfMacroParser {
+            inner: &info.inner,
+            debug_macro: info.debug_macro,
+            debug_macinfo: info.debug_macinfo,
+            str_offsets_base: unit.str_offsets_base,
+            resolve_file: |file_id| self.resolve_file(file_id),
+            macros: Vec::new(),
+        };
+
+        if let Some(AttributeValue::DebugMacroRef(offset)) =
+            entry.attr_value(constants::DW_AT_macros)?
+        {
+            parser.parse_macro(offset.0, 0)?;
+        } else if let Some(offset) = entry
+            .attr(constants::DW_AT_GNU_macros)?
+            .and_then(|attr| attr.offset_value())
+        {
+            parser.parse_macro(offset, 0)?;
+        } else if let Some(AttributeValue::DebugMacinfoRef(offset)) =
+            entry.attr_value(constants::DW_AT_macro_info)?
+        {
+            parser.parse_macinfo(offset.0)?;
+        }
+
+        Ok(parser.macros)
+    }
+
     /// Resolves the name of a function from the symbol table.
     fn resolve_symbol_name(&self, address: u64) -> Option<Name<'d>> {
         let symbol = self.inner.info.symbol_map.lookup_exact(address)?;
         let name = resolve_cow_name(self.bcsymbolmap, symbol.name.clone()?);
-        Some(Name::new(name, NameMangling::Mangled, self.language))
+        let language = match self.language {
+            Language::Unknown => Language::from_mangled(&name),
+            language => language,
+        };
+        let name = Name::new(name, NameMangling::Mangled, language);
+        Some(self.normalize_name(name))
     }
 
     /// Resolves the name of a function from DWARF debug information.
-    fn resolve_dwarf_name(&self, entry: &Die<'d, '_>) -> Option<Name<'d>> {
+    fn resolve_dwarf_name(
+        &self,
+        entry: &Die<'d, '_>,
+        cache: Option<&mut NameCache<'d>>,
+    ) -> Option<Name<'d>> {
         self.inner
-            .resolve_function_name(entry, self.language, self.bcsymbolmap)
+            .resolve_function_name(entry, self.language, self.bcsymbolmap, cache)
             .ok()
             .flatten()
+            .map(|name| self.normalize_name(name))
+    }
+
+    /// Applies the session's [`NameNormalizer`], if any, to a function name and interns it.
+    fn normalize_name(&self, name: Name<'d>) -> Name<'d> {
+        let name = match self.normalizer {
+            Some(normalizer) => normalizer.normalize(name),
+            None => name,
+        };
+
+        match self.interner {
+            Some(interner) => interner.intern_name(name),
+            None => name,
+        }
     }
 
     /// Collects all functions within this compilation unit.
+    ///
+    /// Errors are located at the last DIE read, or at the unit header if no DIE has been read.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "trace",
+            skip_all,
+            fields(unit = ?self.inner.unit.header.offset())
+        )
+    )]
     fn functions(
         &self,
         range_buf: &mut Vec<Range>,
         seen_ranges: &mut BTreeSet<(u64, u64)>,
+        options: FunctionsOptions,
+        merge_split_functions: bool,
+        cache_function_names: bool,
+    ) -> Result<Vec<Function<'d>>, DwarfError> {
+        let mut die_offset = None;
+        let result = self
+            .collect_functions(
+                range_buf,
+                seen_ranges,
+                options,
+                merge_split_functions,
+                cache_function_names,
+                &mut die_offset,
+            )
+            .map_err(|error| {
+                let offset = match die_offset {
+                    Some(offset) => offset.to_unit_section_offset(self.inner.unit),
+                    None => self.inner.unit.header.offset(),
+                };
+                locate_error(error, offset)
+            });
+
+        #[cfg(feature = "tracing")]
+        if let Ok(ref functions) = result {
+            tracing::trace!(functions = functions.len(), "collected unit functions");
+        }
+
+        result
+    }
+
+    fn collect_functions(
+        &self,
+        range_buf: &mut Vec<Range>,
+        seen_ranges: &mut BTreeSet<(u64, u64)>,
+        options: FunctionsOptions,
+        merge_split_functions: bool,
+        cache_function_names: bool,
+        die_offset: &mut Option<UnitOffset>,
     ) -> Result<Vec<Function<'d>>, DwarfError> {
         let mut depth = 0;
         let mut skipped_depth = None;
         let mut functions = Vec::new();
+        let mut origins = BTreeMap::new();
+        let mut name_cache = if cache_function_names {
+            Some(NameCache::new())
+        } else {
+            None
+        };
 
         let mut stack = FunctionStack::new();
         let mut entries = self.inner.unit.entries();
         while let Some((movement, entry)) = entries.next_dfs()? {
             depth += movement;
+            *die_offset = Some(entry.offset());
 
             // If we're navigating within a skipped function (see below), we can ignore this
             // entry completely. Otherwise, we've moved out of any skipped function and can
@@ -785,15 +1321,25 @@ impl<'d, 'a> DwarfUnit<'d, 'a> {
             // previous function at the same level or any of it's children.
             stack.flush(depth, &mut functions);
 
-            // Skip anything that is not a function.
+            // Skip anything that is not a function. Call sites are attached to the innermost
+            // function containing them, which is on top of the stack.
             let inline = match entry.tag() {
                 constants::DW_TAG_subprogram => false,
                 constants::DW_TAG_inlined_subroutine => true,
+                constants::DW_TAG_call_site | constants::DW_TAG_GNU_call_site => {
+                    if let (Some(call_site), Some(function)) =
+                        (self.parse_call_site(entry)?, stack.peek_mut())
+                    {
+                        function.call_sites.push(call_site);
+                    }
+                    continue;
+                }
                 _ => continue,
             };
 
             range_buf.clear();
-            let (call_line, call_file) = self.parse_ranges(entry, range_buf)?;
+            let (call_line, call_file) =
+                self.parse_ranges(entry, range_buf, options.keep_zero_size)?;
 
             // Ranges can be empty for two reasons: (1) the function is a no-op and does not
             // contain any code, or (2) the function did contain eliminated dead code. In the
@@ -811,10 +1357,23 @@ impl<'d, 'a> DwarfUnit<'d, 'a> {
             // should avoid this problem.
             range_buf.sort_by_key(|r| r.begin);
 
-            let function_address = offset(range_buf[0].begin, self.inner.info.address_offset);
+            let function_begin = range_buf[0].begin;
+            let function_address = offset(
+                function_begin,
+                self.inner.info.relocation.offset_at(function_begin),
+            );
+
+            // Functions in type units are not supported, so the DIE must be in `.debug_info`.
+            let entity = match entry.offset().to_unit_section_offset(self.inner.unit) {
+                UnitSectionOffset::DebugInfoOffset(offset) => {
+                    Some(DebugEntity::DwarfDie(offset.0 as u64))
+                }
+                UnitSectionOffset::DebugTypesOffset(_) => None,
+            };
 
             // For multi-range functions, calculate the function_size by summing all range sizes.
             let function_size = range_buf.iter().map(|r| r.end - r.begin).sum();
+            let function_ranges = self.function_ranges(range_buf);
 
             // We have seen duplicate top-level function entries being yielded from the
             // [`DwarfFunctionIterator`], which combined with recursively walking its inlinees can
@@ -824,11 +1383,22 @@ impl<'d, 'a> DwarfUnit<'d, 'a> {
             // which merges templated code that is being generated multiple times in each
             // compilation unit. We make sure to detect this here, so we can avoid creating these
             // duplicates as early as possible.
-            if !inline && !seen_ranges.insert((function_address, function_size)) {
+            let dedupe = options.dedupe_overlaps == OverlapPolicy::SkipDuplicates;
+            if dedupe && !inline && !seen_ranges.insert((function_address, function_size)) {
                 skipped_depth = Some(depth);
                 continue;
             }
 
+            // Remember the abstract origin of top-level functions, so that the parts of a function
+            // split by the compiler can be identified below.
+            if merge_split_functions && !inline {
+                if let (Some(DebugEntity::DwarfDie(die)), Some(origin)) =
+                    (entity, self.inner.abstract_origin(entry)?)
+                {
+                    origins.insert(die, origin);
+                }
+            }
+
             // Resolve functions in the symbol table first. Only if there is no entry, fall back
             // to debug information only if there is no match. Sometimes, debug info contains a
             // lesser quality of symbol names.
@@ -842,9 +1412,31 @@ impl<'d, 'a> DwarfUnit<'d, 'a> {
             };
 
             let name = symbol_name
-                .or_else(|| self.resolve_dwarf_name(entry))
+                .or_else(|| self.resolve_dwarf_name(entry, name_cache.as_mut()))
                 .unwrap_or_else(|| Name::new("", NameMangling::Unmangled, self.language));
 
+            // Compiler-generated thunks, such as Swift and Objective-C interop trampolines, are
+            // flagged so that consumers can skip them.
+            let artificial = self.inner.is_artificial(entry).unwrap_or(false);
+
+            // Calls to noreturn functions can be the last instruction of the caller, which stack
+            // walkers need to know when computing source locations from return addresses.
+            let noreturn = matches!(
+                self.inner
+                    .inherited_attr_value(entry, constants::DW_AT_noreturn),
+                Ok(Some(AttributeValue::Flag(true)))
+            );
+
+            let calling_convention = match self
+                .inner
+                .inherited_attr_value(entry, constants::DW_AT_calling_convention)
+            {
+                Ok(Some(AttributeValue::CallingConvention(cc))) => {
+                    Some(calling_convention_from_dwarf(cc))
+                }
+                _ => None,
+            };
+
             // Avoid constant allocations by collecting repeatedly into the same buffer and
             // draining the results out of it. This keeps the original buffer allocated and
             // allows for a single allocation per call to `resolve_lines`.
@@ -853,8 +1445,8 @@ impl<'d, 'a> DwarfUnit<'d, 'a> {
             if inline {
                 // An inlined function must always have a parent. An empty list of funcs
                 // indicates invalid debug information.
-                let parent = match stack.peek_mut() {
-                    Some(parent) => parent,
+                let parent_lines = match stack.peek_lines_mut() {
+                    Some(parent_lines) => parent_lines,
                     None => return Err(DwarfErrorKind::UnexpectedInline.into()),
                 };
 
@@ -867,12 +1459,13 @@ impl<'d, 'a> DwarfUnit<'d, 'a> {
                 // parent which belong to each range in the inlinee.
                 if let (Some(line), Some(file_id)) = (call_line, call_file) {
                     let file = self.resolve_file(file_id).unwrap_or_default();
-                    let lines = &mut parent.lines;
+                    let lines = parent_lines;
 
                     let mut index = 0;
                     for range in range_buf.iter() {
-                        let range_begin = offset(range.begin, self.inner.info.address_offset);
-                        let range_end = offset(range.end, self.inner.info.address_offset);
+                        let address_offset = self.inner.info.relocation.offset_at(range.begin);
+                        let range_begin = offset(range.begin, address_offset);
+                        let range_end = offset(range.end, address_offset);
 
                         // Check if there is a line record covering the start of this range,
                         // otherwise insert a new record pointing to the correct call location.
@@ -983,57 +1576,574 @@ impl<'d, 'a> DwarfUnit<'d, 'a> {
             let function = Function {
                 address: function_address,
                 size: function_size,
+                ranges: function_ranges,
                 name,
                 compilation_dir: self.compilation_dir(),
-                lines,
+                lines: LineRecords::new(),
                 inlinees: Vec::new(),
                 inline,
+                artificial,
+                noreturn,
+                calling_convention,
+                call_sites: Vec::new(),
+                entity,
             };
 
-            stack.push(depth, function)
+            stack.push(depth, function, lines)
         }
 
         // We're done, flush the remaining stack.
         stack.flush(0, &mut functions);
 
+        if merge_split_functions {
+            functions = merge_split_functions_by_origin(functions, &origins);
+        }
+
         Ok(functions)
     }
-}
 
-/// Converts a DWARF language number into our `Language` type.
-fn language_from_dwarf(language: gimli::DwLang) -> Language {
-    match language {
-        constants::DW_LANG_C => Language::C,
-        constants::DW_LANG_C11 => Language::C,
-        constants::DW_LANG_C89 => Language::C,
-        constants::DW_LANG_C99 => Language::C,
-        constants::DW_LANG_C_plus_plus => Language::Cpp,
-        constants::DW_LANG_C_plus_plus_03 => Language::Cpp,
-        constants::DW_LANG_C_plus_plus_11 => Language::Cpp,
-        constants::DW_LANG_C_plus_plus_14 => Language::Cpp,
-        constants::DW_LANG_D => Language::D,
-        constants::DW_LANG_Go => Language::Go,
-        constants::DW_LANG_ObjC => Language::ObjC,
-        constants::DW_LANG_ObjC_plus_plus => Language::ObjCpp,
-        constants::DW_LANG_Rust => Language::Rust,
-        constants::DW_LANG_Swift => Language::Swift,
-        _ => Language::Unknown,
+    /// Returns the relocated address ranges of a non-contiguous function.
+    ///
+    /// Adjacent ranges are joined. If the ranges turn out to be contiguous, the list is empty.
+    fn function_ranges(&self, range_buf: &[Range]) -> Vec<std::ops::Range<u64>> {
+        let mut ranges: Vec<std::ops::Range<u64>> = Vec::new();
+
+        for range in range_buf {
+            let address_offset = self.inner.info.relocation.offset_at(range.begin);
+            let begin = offset(range.begin, address_offset);
+            let end = offset(range.end, address_offset);
+
+            match ranges.last_mut() {
+                Some(last) if last.end == begin => last.end = end,
+                _ => ranges.push(begin..end),
+            }
+        }
+
+        if ranges.len() <= 1 {
+            ranges.clear();
+        }
+
+        ranges
     }
 }
 
-/// Data of a specific DWARF section.
-struct DwarfSectionData<'data, S> {
-    data: Cow<'data, [u8]>,
-    endianity: Endian,
-    _ph: PhantomData<S>,
+/// Returns the name of the function that a GCC `.cold` part was split off from.
+///
+/// For instance, this returns `foo` for `foo.cold` and `foo.cold.12`.
+fn cold_part_parent(name: &str) -> Option<&str> {
+    let name = match name.rsplit_once('.') {
+        Some((prefix, suffix))
+            if !suffix.is_empty() && suffix.bytes().all(|b| b.is_ascii_digit()) =>
+        {
+            prefix
+        }
+        _ => name,
+    };
+
+    match name.strip_suffix(".cold") {
+        Some(parent) if !parent.is_empty() => Some(parent),
+        _ => None,
+    }
 }
 
-impl<'data, S> DwarfSectionData<'data, S>
-where
-    S: gimli::read::Section<Slice<'data>>,
-{
-    /// Loads data for this section from the object file.
-    fn load<D>(dwarf: &D) -> Self
+/// Merges the parts of functions that have been split by the compiler into a single function.
+///
+/// Parts are matched by their abstract origin if declared, and otherwise by the name of a `.cold`
+/// part. The merged function retains the name and entry address of the first part that is not a
+/// cold part, and covers the address ranges, lines and inlinees of all parts.
+fn merge_split_functions_by_origin<'d>(
+    functions: Vec<Function<'d>>,
+    origins: &BTreeMap<u64, u64>,
+) -> Vec<Function<'d>> {
+    #[derive(Eq, Ord, PartialEq, PartialOrd)]
+    enum Key {
+        Origin(u64),
+        Name(String),
+    }
+
+    let key = |function: &Function<'_>| {
+        let origin = match function.entity {
+            Some(DebugEntity::DwarfDie(die)) => origins.get(&die).copied(),
+            _ => None,
+        };
+
+        match origin {
+            Some(origin) => Key::Origin(origin),
+            None => {
+                let name = function.name.as_str();
+                Key::Name(cold_part_parent(name).unwrap_or(name).to_owned())
+            }
+        }
+    };
+
+    let mut groups = BTreeMap::<Key, usize>::new();
+    let mut merged: Vec<Vec<Function<'d>>> = Vec::new();
+
+    for function in functions {
+        // Unnamed functions cannot be matched reliably.
+        if function.name.as_str().is_empty() {
+            merged.push(vec![function]);
+            continue;
+        }
+
+        let index = *groups.entry(key(&function)).or_insert_with(|| {
+            merged.push(Vec::new());
+            merged.len() - 1
+        });
+
+        merged[index].push(function);
+    }
+
+    merged.into_iter().map(merge_function_parts).collect()
+}
+
+/// Merges the parts of a split function into the first part that is not a `.cold` part.
+fn merge_function_parts(mut parts: Vec<Function<'_>>) -> Function<'_> {
+    if parts.len() == 1 {
+        return parts.pop().unwrap();
+    }
+
+    let primary = parts
+        .iter()
+        .position(|f| cold_part_parent(f.name.as_str()).is_none())
+        .unwrap_or(0);
+    let mut function = parts.remove(primary);
+
+    let mut ranges = function.address_ranges().collect::<Vec<_>>();
+    let mut lines = function.lines.iter().collect::<Vec<_>>();
+
+    for part in parts {
+        ranges.extend(part.address_ranges());
+
+        function.size += part.size;
+        lines.extend(part.lines.iter());
+        function.inlinees.extend(part.inlinees);
+        function.call_sites.extend(part.call_sites);
+        function.artificial &= part.artificial;
+        function.noreturn |= part.noreturn;
+    }
+
+    ranges.sort_by_key(|r| r.start);
+    function.ranges = ranges;
+    lines.sort_by_key(|l| l.address);
+    function.lines = lines.into_iter().collect();
+    function.inlinees.sort_by_key(|f| f.address);
+    function.call_sites.sort_by_key(|c| c.return_address);
+
+    function
+}
+
+/// Converts a DWARF language number into our `Language` type.
+fn language_from_dwarf(language: gimli::DwLang) -> Language {
+    match language {
+        constants::DW_LANG_C => Language::C,
+        constants::DW_LANG_C11 => Language::C,
+        constants::DW_LANG_C89 => Language::C,
+        constants::DW_LANG_C99 => Language::C,
+        constants::DW_LANG_C_plus_plus => Language::Cpp,
+        constants::DW_LANG_C_plus_plus_03 => Language::Cpp,
+        constants::DW_LANG_C_plus_plus_11 => Language::Cpp,
+        constants::DW_LANG_C_plus_plus_14 => Language::Cpp,
+        constants::DW_LANG_D => Language::D,
+        constants::DW_LANG_Go => Language::Go,
+        constants::DW_LANG_ObjC => Language::ObjC,
+        constants::DW_LANG_ObjC_plus_plus => Language::ObjCpp,
+        constants::DW_LANG_Rust => Language::Rust,
+        constants::DW_LANG_Swift => Language::Swift,
+        constants::DW_LANG_Kotlin => Language::Kotlin,
+        _ => Language::Unknown,
+    }
+}
+
+/// The maximum nesting depth of `DW_MACRO_import` entries.
+const MAX_MACRO_IMPORT_DEPTH: usize = 32;
+
+/// Entry types of the `.debug_macinfo` section, which are not defined by gimli.
+const DW_MACINFO_DEFINE: u8 = 0x01;
+const DW_MACINFO_UNDEF: u8 = 0x02;
+const DW_MACINFO_START_FILE: u8 = 0x03;
+const DW_MACINFO_END_FILE: u8 = 0x04;
+const DW_MACINFO_VENDOR_EXT: u8 = 0xff;
+
+/// Flags in the header of a `.debug_macro` unit.
+const DW_MACRO_OFFSET_SIZE_FLAG: u8 = 0x01;
+const DW_MACRO_DEBUG_LINE_OFFSET_FLAG: u8 = 0x02;
+const DW_MACRO_OPCODE_OPERANDS_TABLE_FLAG: u8 = 0x04;
+
+/// The type of a [`DwarfMacro`] entry.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DwarfMacroKind {
+    /// A macro definition, such as `#define FOO 1`.
+    Define,
+    /// The removal of a macro definition, such as `#undef FOO`.
+    Undef,
+    /// The start of an included source file.
+    StartFile,
+    /// The end of the source file started most recently.
+    EndFile,
+}
+
+/// An entry in the macro information of a compilation unit.
+///
+/// Entries are listed in the order of their preprocessor directives. Imported macro units are
+/// resolved transparently, so their entries appear in place of the import.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DwarfMacro<'data> {
+    /// The type of this entry.
+    pub kind: DwarfMacroKind,
+    /// The line of the directive in the current source file.
+    ///
+    /// This is `0` for predefined and command line macros, as well as for [`EndFile`] entries.
+    ///
+    /// [`EndFile`]: DwarfMacroKind::EndFile
+    pub line: u64,
+    /// The raw text of a definition, such as `FOO(a, b) a + b`, or the name of an undefined macro.
+    ///
+    /// This is empty for [`StartFile`] and [`EndFile`] entries.
+    ///
+    /// [`StartFile`]: DwarfMacroKind::StartFile
+    /// [`EndFile`]: DwarfMacroKind::EndFile
+    pub text: &'data [u8],
+    /// The source file included by a [`StartFile`] entry.
+    ///
+    /// [`StartFile`]: DwarfMacroKind::StartFile
+    pub file: Option<FileInfo<'data>>,
+}
+
+impl<'data> DwarfMacro<'data> {
+    /// Returns the length of the macro name within `text`.
+    fn name_len(&self) -> usize {
+        self.text
+            .iter()
+            .position(|&b| b == b' ' || b == b'(')
+            .unwrap_or(self.text.len())
+    }
+
+    /// Returns the length of the macro name and its parameter list within `text`.
+    fn signature_len(&self) -> usize {
+        let name_len = self.name_len();
+
+        if self.text.get(name_len) != Some(&b'(') {
+            return name_len;
+        }
+
+        match self.text[name_len..].iter().position(|&b| b == b')') {
+            Some(pos) => name_len + pos + 1,
+            None => self.text.len(),
+        }
+    }
+
+    /// The name of the macro, without its parameter list.
+    pub fn name(&self) -> Cow<'data, str> {
+        let text: &'data [u8] = self.text;
+        String::from_utf8_lossy(&text[..self.name_len()])
+    }
+
+    /// The replacement list of a macro definition.
+    ///
+    /// Returns `None` for entries other than definitions. Macros defined without a value, such as
+    /// `#define FOO`, have an empty value.
+    pub fn value(&self) -> Option<Cow<'data, str>> {
+        if self.kind != DwarfMacroKind::Define {
+            return None;
+        }
+
+        let text: &'data [u8] = self.text;
+        let value = text.get(self.signature_len() + 1..).unwrap_or_default();
+        Some(String::from_utf8_lossy(value))
+    }
+}
+
+/// Macro information of a single compilation unit.
+#[derive(Clone, Debug)]
+pub struct DwarfUnitMacros<'data> {
+    /// The name of the compilation unit, usually the path to its primary source file.
+    pub name: &'data [u8],
+    /// The path of the compilation directory.
+    pub compilation_dir: Cow<'data, [u8]>,
+    /// All macro entries of this unit in order.
+    pub macros: Vec<DwarfMacro<'data>>,
+}
+
+impl<'data> DwarfUnitMacros<'data> {
+    /// The name of the compilation unit as UTF-8 string.
+    pub fn name_str(&self) -> Cow<'data, str> {
+        String::from_utf8_lossy(self.name)
+    }
+}
+
+/// Parses macro information from the `.debug_macro` and `.debug_macinfo` sections.
+struct DwarfMacroParser<'d, 'a, F> {
+    inner: &'a DwarfInner<'d>,
+    debug_macro: Slice<'d>,
+    debug_macinfo: Slice<'d>,
+    str_offsets_base: gimli::DebugStrOffsetsBase<usize>,
+    resolve_file: F,
+    macros: Vec<DwarfMacro<'d>>,
+}
+
+impl<'d, 'a, F> DwarfMacroParser<'d, 'a, F>
+where
+    F: Fn(u64) -> Option<FileInfo<'d>>,
+{
+    /// Adds an entry for a definition or removal of a macro.
+    fn push_text(&mut self, kind: DwarfMacroKind, line: u64, text: Slice<'d>) {
+        self.macros.push(DwarfMacro {
+            kind,
+            line,
+            text: text.slice(),
+            file: None,
+        });
+    }
+
+    /// Adds an entry for the start of a source file.
+    fn push_start_file(&mut self, line: u64, file_id: u64) {
+        let file = (self.resolve_file)(file_id);
+        self.macros.push(DwarfMacro {
+            kind: DwarfMacroKind::StartFile,
+            line,
+            text: &[],
+            file,
+        });
+    }
+
+    /// Adds an entry for the end of a source file.
+    fn push_end_file(&mut self) {
+        self.macros.push(DwarfMacro {
+            kind: DwarfMacroKind::EndFile,
+            line: 0,
+            text: &[],
+            file: None,
+        });
+    }
+
+    /// Parses the pre-DWARF 5 macro information at the given offset in `.debug_macinfo`.
+    fn parse_macinfo(&mut self, offset: usize) -> Result<(), DwarfError> {
+        let mut reader = self.debug_macinfo;
+        reader.skip(offset)?;
+
+        loop {
+            match reader.read_u8()? {
+                0 => return Ok(()),
+                DW_MACINFO_DEFINE => {
+                    let line = reader.read_uleb128()?;
+                    let text = reader.read_null_terminated_slice()?;
+                    self.push_text(DwarfMacroKind::Define, line, text);
+                }
+                DW_MACINFO_UNDEF => {
+                    let line = reader.read_uleb128()?;
+                    let text = reader.read_null_terminated_slice()?;
+                    self.push_text(DwarfMacroKind::Undef, line, text);
+                }
+                DW_MACINFO_START_FILE => {
+                    let line = reader.read_uleb128()?;
+                    let file_id = reader.read_uleb128()?;
+                    self.push_start_file(line, file_id);
+                }
+                DW_MACINFO_END_FILE => self.push_end_file(),
+                DW_MACINFO_VENDOR_EXT => {
+                    reader.read_uleb128()?;
+                    reader.read_null_terminated_slice()?;
+                }
+                other => {
+                    let message = format!("unknown macinfo type {:#x}", other);
+                    return Err(DwarfError::new(DwarfErrorKind::CorruptedData, message));
+                }
+            }
+        }
+    }
+
+    /// Parses the DWARF 5 or GNU macro unit at the given offset in `.debug_macro`.
+    fn parse_macro(&mut self, offset: usize, depth: usize) -> Result<(), DwarfError> {
+        if depth > MAX_MACRO_IMPORT_DEPTH {
+            let message = "macro imports are nested too deeply";
+            return Err(DwarfError::new(DwarfErrorKind::CorruptedData, message));
+        }
+
+        let mut reader = self.debug_macro;
+        reader.skip(offset)?;
+
+        let version = reader.read_u16()?;
+        if version != 4 && version != 5 {
+            let message = format!("unsupported macro version {}", version);
+            return Err(DwarfError::new(DwarfErrorKind::CorruptedData, message));
+        }
+
+        let flags = reader.read_u8()?;
+        let format = if flags & DW_MACRO_OFFSET_SIZE_FLAG != 0 {
+            gimli::Format::Dwarf64
+        } else {
+            gimli::Format::Dwarf32
+        };
+
+        if flags & DW_MACRO_DEBUG_LINE_OFFSET_FLAG != 0 {
+            reader.read_offset(format)?;
+        }
+
+        // Vendor extensions declare their operands, so that consumers can skip over them.
+        let mut operands = Vec::new();
+        if flags & DW_MACRO_OPCODE_OPERANDS_TABLE_FLAG != 0 {
+            for _ in 0..reader.read_u8()? {
+                let opcode = reader.read_u8()?;
+                let mut forms = Vec::new();
+                for _ in 0..reader.read_uleb128()? {
+                    forms.push(constants::DwForm(reader.read_u8()?.into()));
+                }
+                operands.push((opcode, forms));
+            }
+        }
+
+        loop {
+            let opcode = reader.read_u8()?;
+            if opcode == 0 {
+                return Ok(());
+            }
+
+            match constants::DwMacro(opcode) {
+                constants::DW_MACRO_define | constants::DW_MACRO_undef => {
+                    let line = reader.read_uleb128()?;
+                    let text = reader.read_null_terminated_slice()?;
+                    self.push_text(macro_kind(opcode), line, text);
+                }
+                constants::DW_MACRO_define_strp | constants::DW_MACRO_undef_strp => {
+                    let line = reader.read_uleb128()?;
+                    let offset = gimli::DebugStrOffset(reader.read_offset(format)?);
+                    let text = self.inner.debug_str.get_str(offset)?;
+                    self.push_text(macro_kind(opcode), line, text);
+                }
+                constants::DW_MACRO_define_strx | constants::DW_MACRO_undef_strx => {
+                    let line = reader.read_uleb128()?;
+                    let index = gimli::DebugStrOffsetsIndex(reader.read_uleb128()? as usize);
+                    let offset = self.inner.debug_str_offsets.get_str_offset(
+                        format,
+                        self.str_offsets_base,
+                        index,
+                    )?;
+                    let text = self.inner.debug_str.get_str(offset)?;
+                    self.push_text(macro_kind(opcode), line, text);
+                }
+                constants::DW_MACRO_start_file => {
+                    let line = reader.read_uleb128()?;
+                    let file_id = reader.read_uleb128()?;
+                    self.push_start_file(line, file_id);
+                }
+                constants::DW_MACRO_end_file => self.push_end_file(),
+                constants::DW_MACRO_import => {
+                    let offset = reader.read_offset(format)?;
+                    self.parse_macro(offset, depth + 1)?;
+                }
+                // Strings and imports in a supplementary object file cannot be resolved.
+                constants::DW_MACRO_define_sup | constants::DW_MACRO_undef_sup => {
+                    reader.read_uleb128()?;
+                    reader.read_offset(format)?;
+                }
+                constants::DW_MACRO_import_sup => {
+                    reader.read_offset(format)?;
+                }
+                _ => match operands.iter().find(|(op, _)| *op == opcode) {
+                    Some((_, forms)) => {
+                        for form in forms {
+                            skip_macro_operand(&mut reader, *form, format)?;
+                        }
+                    }
+                    None => {
+                        let message = format!("unknown macro opcode {:#x}", opcode);
+                        return Err(DwarfError::new(DwarfErrorKind::CorruptedData, message));
+                    }
+                },
+            }
+        }
+    }
+}
+
+/// Returns whether a `.debug_macro` opcode defines or removes a macro.
+fn macro_kind(opcode: u8) -> DwarfMacroKind {
+    match constants::DwMacro(opcode) {
+        constants::DW_MACRO_undef
+        | constants::DW_MACRO_undef_strp
+        | constants::DW_MACRO_undef_strx => DwarfMacroKind::Undef,
+        _ => DwarfMacroKind::Define,
+    }
+}
+
+/// Skips an operand of a vendor-specific entry in `.debug_macro`.
+fn skip_macro_operand(
+    reader: &mut Slice<'_>,
+    form: constants::DwForm,
+    format: gimli::Format,
+) -> Result<(), DwarfError> {
+    match form {
+        constants::DW_FORM_data1 | constants::DW_FORM_flag | constants::DW_FORM_strx1 => {
+            reader.skip(1)?
+        }
+        constants::DW_FORM_data2 | constants::DW_FORM_strx2 => reader.skip(2)?,
+        constants::DW_FORM_strx3 => reader.skip(3)?,
+        constants::DW_FORM_data4 | constants::DW_FORM_strx4 => reader.skip(4)?,
+        constants::DW_FORM_data8 => reader.skip(8)?,
+        constants::DW_FORM_data16 => reader.skip(16)?,
+        constants::DW_FORM_udata | constants::DW_FORM_strx => {
+            reader.read_uleb128()?;
+        }
+        constants::DW_FORM_sdata => {
+            reader.read_sleb128()?;
+        }
+        constants::DW_FORM_string => {
+            reader.read_null_terminated_slice()?;
+        }
+        constants::DW_FORM_strp
+        | constants::DW_FORM_line_strp
+        | constants::DW_FORM_strp_sup
+        | constants::DW_FORM_sec_offset => {
+            reader.read_offset(format)?;
+        }
+        constants::DW_FORM_block1 => {
+            let len = reader.read_u8()?;
+            reader.skip(len.into())?;
+        }
+        constants::DW_FORM_block2 => {
+            let len = reader.read_u16()?;
+            reader.skip(len.into())?;
+        }
+        constants::DW_FORM_block4 => {
+            let len = reader.read_u32()?;
+            reader.skip(len as usize)?;
+        }
+        constants::DW_FORM_block => {
+            let len = reader.read_uleb128()?;
+            reader.skip(len as usize)?;
+        }
+        _ => {
+            let message = format!("unsupported macro operand form {}", form);
+            return Err(DwarfError::new(DwarfErrorKind::CorruptedData, message));
+        }
+    }
+
+    Ok(())
+}
+
+/// Converts a DWARF calling convention into a [`CallingConvention`].
+fn calling_convention_from_dwarf(cc: constants::DwCc) -> CallingConvention {
+    match cc {
+        constants::DW_CC_normal => CallingConvention::Normal,
+        constants::DW_CC_program => CallingConvention::Program,
+        constants::DW_CC_nocall => CallingConvention::NoCall,
+        other => CallingConvention::Other(other.0),
+    }
+}
+
+/// Data of a specific DWARF section.
+struct DwarfSectionData<'data, S> {
+    data: Cow<'data, [u8]>,
+    endianity: Endian,
+    _ph: PhantomData<S>,
+}
+
+impl<'data, S> DwarfSectionData<'data, S>
+where
+    S: gimli::read::Section<Slice<'data>>,
+{
+    /// Loads data for this section from the object file.
+    fn load<D>(dwarf: &D) -> Self
     where
         D: Dwarf<'data>,
     {
@@ -1072,9 +2182,21 @@ where
     }
 }
 
+/// Loads the data of a section that is not supported by gimli, or an empty slice.
+fn load_raw_section<'data, D>(dwarf: &D, name: &str) -> Cow<'data, [u8]>
+where
+    D: Dwarf<'data>,
+{
+    dwarf
+        .section(name)
+        .map(|section| section.data)
+        .unwrap_or_default()
+}
+
 /// All DWARF sections that are needed by `DwarfDebugSession`.
 struct DwarfSections<'data> {
     debug_abbrev: DwarfSectionData<'data, gimli::read::DebugAbbrev<Slice<'data>>>,
+    debug_aranges: DwarfSectionData<'data, gimli::read::DebugAranges<Slice<'data>>>,
     debug_info: DwarfSectionData<'data, gimli::read::DebugInfo<Slice<'data>>>,
     debug_line: DwarfSectionData<'data, gimli::read::DebugLine<Slice<'data>>>,
     debug_line_str: DwarfSectionData<'data, gimli::read::DebugLineStr<Slice<'data>>>,
@@ -1082,6 +2204,8 @@ struct DwarfSections<'data> {
     debug_str_offsets: DwarfSectionData<'data, gimli::read::DebugStrOffsets<Slice<'data>>>,
     debug_ranges: DwarfSectionData<'data, gimli::read::DebugRanges<Slice<'data>>>,
     debug_rnglists: DwarfSectionData<'data, gimli::read::DebugRngLists<Slice<'data>>>,
+    debug_macro: Cow<'data, [u8]>,
+    debug_macinfo: Cow<'data, [u8]>,
 }
 
 impl<'data> DwarfSections<'data> {
@@ -1092,6 +2216,7 @@ impl<'data> DwarfSections<'data> {
     {
         DwarfSections {
             debug_abbrev: DwarfSectionData::load(dwarf),
+            debug_aranges: DwarfSectionData::load(dwarf),
             debug_info: DwarfSectionData::load(dwarf),
             debug_line: DwarfSectionData::load(dwarf),
             debug_line_str: DwarfSectionData::load(dwarf),
@@ -1099,16 +2224,344 @@ impl<'data> DwarfSections<'data> {
             debug_str_offsets: DwarfSectionData::load(dwarf),
             debug_ranges: DwarfSectionData::load(dwarf),
             debug_rnglists: DwarfSectionData::load(dwarf),
+            // gimli does not provide section types for macro information.
+            debug_macro: load_raw_section(dwarf, "debug_macro"),
+            debug_macinfo: load_raw_section(dwarf, "debug_macinfo"),
+        }
+    }
+}
+
+/// A segment with an individual address offset in an [`AddressRelocation`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct RelocatedSegment {
+    start: u64,
+    end: u64,
+    offset: i64,
+}
+
+/// Converts addresses in DWARF debug information to relative addresses.
+///
+/// Usually, all addresses are relative to the load address of the object, so a single offset is
+/// subtracted from every address. Dylibs extracted from the dyld shared cache, however, can have
+/// segments that were slid independently of each other. For such objects, an individual offset can
+/// be declared for every segment. Addresses outside of all declared segments use the default offset.
+///
+/// # Example
+///
+/// ```
+/// use symbolic_debuginfo::dwarf::AddressRelocation;
+///
+/// let mut relocation = AddressRelocation::new(0x1000);
+/// relocation.add_segment(0x8000, 0x1000, 0x6000);
+///
+/// assert_eq!(relocation.relocate(0x1400), 0x400);
+/// assert_eq!(relocation.relocate(0x8400), 0x2400);
+/// // Addresses after the segment use the default offset again.
+/// assert_eq!(relocation.relocate(0x9000), 0x8000);
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct AddressRelocation {
+    default_offset: i64,
+    segments: Vec<RelocatedSegment>,
+}
+
+impl AddressRelocation {
+    /// Creates a relocation that subtracts the given offset from all addresses.
+    pub fn new(default_offset: i64) -> Self {
+        AddressRelocation {
+            default_offset,
+            segments: Vec::new(),
+        }
+    }
+
+    /// Declares an individual offset for the segment starting at `start` with the given `size`.
+    ///
+    /// Segments must not overlap.
+    pub fn add_segment(&mut self, start: u64, size: u64, offset: i64) {
+        let end = start.saturating_add(size);
+        let index = self
+            .segments
+            .partition_point(|segment| segment.start <= start);
+        self.segments
+            .insert(index, RelocatedSegment { start, end, offset });
+    }
+
+    /// Returns `true` if individual offsets have been declared for any segments.
+    pub fn has_segments(&self) -> bool {
+        !self.segments.is_empty()
+    }
+
+    /// Returns the offset that applies to the given address.
+    pub fn offset_at(&self, address: u64) -> i64 {
+        let index = self
+            .segments
+            .partition_point(|segment| segment.start <= address);
+
+        match index.checked_sub(1).map(|i| &self.segments[i]) {
+            Some(segment) if address < segment.end => segment.offset,
+            _ => self.default_offset,
+        }
+    }
+
+    /// Converts an address from the debug information to a relative address.
+    pub fn relocate(&self, address: u64) -> u64 {
+        offset(address, self.offset_at(address))
+    }
+}
+
+impl From<i64> for AddressRelocation {
+    fn from(offset: i64) -> Self {
+        Self::new(offset)
+    }
+}
+
+/// The maximum number of operations executed by [`evaluate_expression`].
+///
+/// Branches allow expressions to loop, so this guards against malformed expressions.
+const MAX_EXPRESSION_ITERATIONS: u32 = 10_000;
+
+/// Parameters for evaluating a DWARF expression with [`evaluate_expression`].
+///
+/// Only the byte order and address size are required. The frame base and canonical frame address
+/// are needed by expressions that describe the locations of variables and by CFI rules,
+/// respectively.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ExpressionContext {
+    /// The byte order of the target.
+    pub endian: Endian,
+    /// The size of an address on the target in bytes.
+    pub address_size: u8,
+    /// The DWARF version of the unit or CFI section the expression was read from.
+    pub version: u16,
+    /// The value of `DW_AT_frame_base` of the enclosing function, if known.
+    pub frame_base: Option<u64>,
+    /// The canonical frame address of the current frame, if known.
+    pub cfa: Option<u64>,
+}
+
+impl ExpressionContext {
+    /// Creates a context for DWARF 4 expressions with the given byte order and address size.
+    pub fn new(endian: Endian, address_size: u8) -> Self {
+        ExpressionContext {
+            endian,
+            address_size,
+            version: 4,
+            frame_base: None,
+            cfa: None,
+        }
+    }
+
+    fn address_mask(&self) -> u64 {
+        match self.address_size {
+            size if size >= 8 => !0,
+            size => (1 << (u64::from(size) * 8)) - 1,
+        }
+    }
+}
+
+/// The location of a piece of a value computed by [`evaluate_expression`].
+#[non_exhaustive]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ExpressionLocation {
+    /// The value has been optimized away.
+    Empty,
+    /// The value is stored in the register with the given DWARF register number.
+    Register(u16),
+    /// The value is stored in memory at the given address.
+    Address(u64),
+    /// The value has no location, but was computed by the expression.
+    Value(u64),
+    /// The value is given by the constant bytes.
+    Bytes(Vec<u8>),
+    /// The value is only known implicitly, for instance a pointer to an optimized-out variable.
+    Implicit,
+}
+
+/// A piece of a value computed by [`evaluate_expression`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExpressionPiece {
+    /// The size of the piece in bits, or `None` if the piece covers the entire value.
+    pub size_in_bits: Option<u64>,
+    /// The offset of the piece within its location in bits, if any.
+    pub bit_offset: Option<u64>,
+    /// Where the piece can be found.
+    pub location: ExpressionLocation,
+}
+
+/// Evaluates a raw DWARF expression.
+///
+/// The expression is read from `expression`, for instance the contents of a `DW_AT_location`
+/// attribute or a CFI rule. When the expression refers to the value of a register, `registers` is
+/// called with the DWARF register number. When it dereferences memory, `memory` is called with the
+/// address and the number of bytes to read, which is at most the address size. Return `None` from
+/// either to indicate that the value is not available, which aborts the evaluation.
+///
+/// Most expressions compute a single [`ExpressionPiece`]. Values that are split across several
+/// locations, such as structs in registers, yield one piece per part.
+///
+/// Operations that need information from the debug information entries, such as
+/// `DW_OP_call` or typed stack operations, are not supported and return an error of kind
+/// [`IncompleteExpression`](DwarfErrorKind::IncompleteExpression).
+///
+/// # Example
+///
+/// ```
+/// use symbolic_debuginfo::dwarf::{evaluate_expression, Endian, ExpressionContext, ExpressionLocation};
+///
+/// // DW_OP_breg7 (rsp) +16, DW_OP_deref
+/// let expression = [0x77, 0x10, 0x06];
+/// let context = ExpressionContext::new(Endian::Little, 8);
+///
+/// let pieces = evaluate_expression(
+///     &expression,
+///     context,
+///     |register| (register == 7).then_some(0x7000),
+///     |address, _size| (address == 0x7010).then_some(0x1234),
+/// )
+/// .unwrap();
+///
+/// assert_eq!(pieces[0].location, ExpressionLocation::Address(0x1234));
+/// ```
+pub fn evaluate_expression<R, M>(
+    expression: &[u8],
+    context: ExpressionContext,
+    mut registers: R,
+    mut memory: M,
+) -> Result<Vec<ExpressionPiece>, DwarfError>
+where
+    R: FnMut(u16) -> Option<u64>,
+    M: FnMut(u64, u8) -> Option<u64>,
+{
+    use gimli::read::{EvaluationResult, Location, Value};
+
+    let encoding = gimli::Encoding {
+        address_size: context.address_size,
+        format: gimli::Format::Dwarf32,
+        version: context.version,
+    };
+
+    let missing = || DwarfError::from(DwarfErrorKind::IncompleteExpression);
+    let mut evaluation =
+        gimli::read::Evaluation::new(Slice::new(expression, context.endian), encoding);
+    evaluation.set_max_iterations(MAX_EXPRESSION_ITERATIONS);
+    let mut result = evaluation.evaluate()?;
+
+    loop {
+        result = match result {
+            EvaluationResult::Complete => break,
+            EvaluationResult::RequiresMemory { address, size, .. } => {
+                let value = memory(address, size).ok_or_else(missing)?;
+                evaluation.resume_with_memory(Value::Generic(value))?
+            }
+            EvaluationResult::RequiresRegister { register, .. } => {
+                let value = registers(register.0).ok_or_else(missing)?;
+                evaluation.resume_with_register(Value::Generic(value))?
+            }
+            EvaluationResult::RequiresFrameBase => {
+                let frame_base = context.frame_base.ok_or_else(missing)?;
+                evaluation.resume_with_frame_base(frame_base)?
+            }
+            EvaluationResult::RequiresCallFrameCfa => {
+                let cfa = context.cfa.ok_or_else(missing)?;
+                evaluation.resume_with_call_frame_cfa(cfa)?
+            }
+            EvaluationResult::RequiresRelocatedAddress(address) => {
+                evaluation.resume_with_relocated_address(address)?
+            }
+            _ => return Err(missing()),
+        };
+    }
+
+    let mask = context.address_mask();
+    let pieces = evaluation
+        .result()
+        .into_iter()
+        .map(|piece| {
+            let location = match piece.location {
+                Location::Empty => ExpressionLocation::Empty,
+                Location::Register { register } => ExpressionLocation::Register(register.0),
+                Location::Address { address } => ExpressionLocation::Address(address),
+                Location::Value { value } => ExpressionLocation::Value(value.to_u64(mask)?),
+                Location::Bytes { value } => ExpressionLocation::Bytes(value.to_vec()),
+                Location::ImplicitPointer { .. } => ExpressionLocation::Implicit,
+            };
+
+            Ok(ExpressionPiece {
+                size_in_bits: piece.size_in_bits,
+                bit_offset: piece.bit_offset,
+                location,
+            })
+        })
+        .collect::<Result<_, GimliError>>()?;
+
+    Ok(pieces)
+}
+
+/// An address range covered by a compilation unit, relative to the image.
+#[derive(Clone, Copy, Debug)]
+struct UnitRange {
+    begin: u64,
+    end: u64,
+    /// The largest end address of this range and all ranges sorted before it.
+    max_end: u64,
+    index: usize,
+}
+
+/// Maps addresses to the compilation units covering them.
+#[derive(Debug, Default)]
+struct UnitIndex {
+    /// Ranges of all units, sorted by their begin address.
+    ranges: Vec<UnitRange>,
+    /// Units with unknown ranges, which have to be considered for every address.
+    unindexed: Vec<usize>,
+}
+
+impl UnitIndex {
+    /// Returns the sorted indexes of all units that may contain the given relative address.
+    fn find(&self, address: u64) -> Vec<usize> {
+        let range = address..address.saturating_add(1);
+        self.find_ranges(std::slice::from_ref(&range))
+    }
+
+    /// Returns the sorted indexes of all units that may overlap any of the given relative ranges.
+    fn find_ranges(&self, ranges: &[std::ops::Range<u64>]) -> Vec<usize> {
+        let mut indexes = self.unindexed.clone();
+
+        for requested in ranges {
+            if requested.start >= requested.end {
+                continue;
+            }
+
+            let end = self
+                .ranges
+                .partition_point(|range| range.begin < requested.end);
+            for range in self.ranges[..end].iter().rev() {
+                // Ranges may overlap, so stop only once no preceding range reaches the start.
+                if range.max_end <= requested.start {
+                    break;
+                }
+
+                if range.end > requested.start {
+                    indexes.push(range.index);
+                }
+            }
         }
+
+        indexes.sort_unstable();
+        indexes.dedup();
+        indexes
     }
 }
 
 struct DwarfInfo<'data> {
     inner: DwarfInner<'data>,
+    debug_macro: Slice<'data>,
+    debug_macinfo: Slice<'data>,
     headers: Vec<UnitHeader<'data>>,
     units: Vec<LazyCell<Option<Unit<'data>>>>,
+    unit_index: LazyCell<UnitIndex>,
     symbol_map: SymbolMap<'data>,
-    address_offset: i64,
+    relocation: AddressRelocation,
     kind: ObjectKind,
 }
 
@@ -1125,13 +2578,13 @@ impl<'d> DwarfInfo<'d> {
     pub fn parse(
         sections: &'d DwarfSections<'d>,
         symbol_map: SymbolMap<'d>,
-        address_offset: i64,
+        relocation: AddressRelocation,
         kind: ObjectKind,
     ) -> Result<Self, DwarfError> {
         let inner = gimli::read::Dwarf {
             debug_abbrev: sections.debug_abbrev.to_gimli(),
             debug_addr: Default::default(),
-            debug_aranges: Default::default(),
+            debug_aranges: sections.debug_aranges.to_gimli(),
             debug_info: sections.debug_info.to_gimli(),
             debug_line: sections.debug_line.to_gimli(),
             debug_line_str: sections.debug_line_str.to_gimli(),
@@ -1151,16 +2604,118 @@ impl<'d> DwarfInfo<'d> {
         let headers = inner.units().collect::<Vec<_>>()?;
         let units = headers.iter().map(|_| LazyCell::new()).collect();
 
+        let endianity = sections.debug_info.endianity;
+
         Ok(DwarfInfo {
             inner,
+            debug_macro: Slice::new(&sections.debug_macro, endianity),
+            debug_macinfo: Slice::new(&sections.debug_macinfo, endianity),
             headers,
             units,
+            unit_index: LazyCell::new(),
             symbol_map,
-            address_offset,
+            relocation,
             kind,
         })
     }
 
+    /// Returns the index of address ranges covered by compilation units, building it on first use.
+    fn unit_index(&self) -> &UnitIndex {
+        self.unit_index.borrow_with(|| self.build_unit_index())
+    }
+
+    /// Collects the address ranges of all compilation units.
+    ///
+    /// Ranges are read from `.debug_aranges` where possible, which does not require parsing the
+    /// units. Units missing from that section are parsed to read their `DW_AT_ranges` or
+    /// `DW_AT_low_pc` and `DW_AT_high_pc` attributes instead.
+    fn build_unit_index(&self) -> UnitIndex {
+        let mut covered = vec![false; self.headers.len()];
+        let mut ranges = Vec::new();
+
+        // A corrupted aranges section is ignored as a whole, since its entries cannot be trusted.
+        if self.read_aranges(&mut ranges, &mut covered).is_err() {
+            ranges.clear();
+            covered.fill(false);
+        }
+
+        let mut unindexed = Vec::new();
+        for (index, covered) in covered.into_iter().enumerate() {
+            if !covered && self.read_unit_ranges(index, &mut ranges).is_err() {
+                unindexed.push(index);
+            }
+        }
+
+        ranges.sort_by_key(|range| (range.begin, range.end));
+
+        let mut max_end = 0;
+        for range in &mut ranges {
+            max_end = max_end.max(range.end);
+            range.max_end = max_end;
+        }
+
+        UnitIndex { ranges, unindexed }
+    }
+
+    /// Reads unit ranges from `.debug_aranges` and marks the units listed there as covered.
+    fn read_aranges(
+        &self,
+        ranges: &mut Vec<UnitRange>,
+        covered: &mut [bool],
+    ) -> Result<(), GimliError> {
+        let mut headers = self.inner.debug_aranges.headers();
+        while let Some(header) = headers.next()? {
+            let offset = UnitSectionOffset::DebugInfoOffset(header.debug_info_offset());
+            let index = match self
+                .headers
+                .binary_search_by_key(&offset, UnitHeader::offset)
+            {
+                Ok(index) => index,
+                Err(_) => continue,
+            };
+
+            covered[index] = true;
+            let mut entries = header.entries();
+            while let Some(entry) = entries.next()? {
+                self.push_unit_range(ranges, entry.range(), index);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads the ranges of a unit from the attributes of its top-level DIE.
+    fn read_unit_ranges(
+        &self,
+        index: usize,
+        ranges: &mut Vec<UnitRange>,
+    ) -> Result<(), DwarfError> {
+        if let Some(unit) = self.get_unit(index)? {
+            let mut unit_ranges = self.inner.unit_ranges(unit)?;
+            while let Some(range) = unit_ranges.next()? {
+                self.push_unit_range(ranges, range, index);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Relocates a range of a unit and adds it to the index, skipping empty and eliminated ranges.
+    fn push_unit_range(&self, ranges: &mut Vec<UnitRange>, range: Range, index: usize) {
+        // Linkers set the address of eliminated code to zero, see `DwarfUnit::from_unit`.
+        if range.begin >= range.end || (range.begin == 0 && self.kind != ObjectKind::Relocatable) {
+            return;
+        }
+
+        let begin = self.relocation.relocate(range.begin);
+        ranges.push(UnitRange {
+            begin,
+            end: begin.saturating_add(range.end - range.begin),
+            max_end: 0,
+            index,
+        });
+    }
+
     /// Loads a compilation unit.
     fn get_unit(&self, index: usize) -> Result<Option<&Unit<'d>>, DwarfError> {
         // Silently ignore unit references out-of-bound
@@ -1178,7 +2733,7 @@ impl<'d> DwarfInfo<'d> {
             match self.inner.unit(header) {
                 Ok(unit) => Ok(Some(unit)),
                 Err(gimli::read::Error::MissingUnitDie) => Ok(None),
-                Err(error) => Err(DwarfError::from(error)),
+                Err(error) => Err(locate_error(DwarfError::from(error), header.offset())),
             }
         })?;
 
@@ -1211,11 +2766,23 @@ impl<'d> DwarfInfo<'d> {
     }
 
     /// Returns an iterator over all compilation units.
-    fn units(&'d self, bcsymbolmap: Option<&'d BcSymbolMap<'d>>) -> DwarfUnitIterator<'_> {
+    fn units(
+        &'d self,
+        bcsymbolmap: Option<&'d BcSymbolMap<'d>>,
+        normalizer: Option<&'d NameNormalizer>,
+        remapper: Option<&'d PathRemapper>,
+        interner: Option<&'d StringInterner>,
+        diagnostics: &'d Diagnostics,
+    ) -> DwarfUnitIterator<'_> {
         DwarfUnitIterator {
             info: self,
             bcsymbolmap,
+            normalizer,
+            remapper,
+            interner,
+            diagnostics,
             index: 0,
+            selection: None,
         }
     }
 }
@@ -1233,36 +2800,120 @@ impl fmt::Debug for DwarfInfo<'_> {
         f.debug_struct("DwarfInfo")
             .field("headers", &self.headers)
             .field("symbol_map", &self.symbol_map)
-            .field("address_offset", &self.address_offset)
+            .field("relocation", &self.relocation)
             .finish()
     }
 }
 
+/// Attaches the section and offset of a unit or DIE to an error.
+fn locate_error(error: DwarfError, offset: UnitSectionOffset) -> DwarfError {
+    match offset {
+        UnitSectionOffset::DebugInfoOffset(offset) => error.at(".debug_info", offset.0 as u64),
+        UnitSectionOffset::DebugTypesOffset(offset) => error.at(".debug_types", offset.0 as u64),
+    }
+}
+
 /// An iterator over compilation units in a DWARF object.
 struct DwarfUnitIterator<'s> {
     info: &'s DwarfInfo<'s>,
     bcsymbolmap: Option<&'s BcSymbolMap<'s>>,
+    normalizer: Option<&'s NameNormalizer>,
+    remapper: Option<&'s PathRemapper>,
+    interner: Option<&'s StringInterner>,
+    diagnostics: &'s Diagnostics,
     index: usize,
+    selection: Option<std::vec::IntoIter<usize>>,
+}
+
+impl<'s> DwarfUnitIterator<'s> {
+    /// Restricts this iterator to the units with the given indexes.
+    fn select(mut self, indexes: Vec<usize>) -> Self {
+        self.selection = Some(indexes.into_iter());
+        self
+    }
+
+    /// Returns the index of the next unit to parse.
+    fn next_index(&mut self) -> Option<usize> {
+        if let Some(ref mut selection) = self.selection {
+            return selection.next();
+        }
+
+        if self.index < self.info.headers.len() {
+            self.index += 1;
+            Some(self.index - 1)
+        } else {
+            None
+        }
+    }
+
+    /// Records an error as [`Diagnostic`] if collection mode is enabled.
+    ///
+    /// Returns the error back if it should be propagated instead.
+    fn recover(&self, error: DwarfError) -> Result<(), DwarfError> {
+        if self.diagnostics.is_enabled() {
+            self.diagnostics.push(error.to_diagnostic());
+            Ok(())
+        } else {
+            Err(error)
+        }
+    }
+
+    /// Records the warnings of a unit's line program as [`Diagnostic`]s.
+    fn record_line_warnings(&self, unit: &DwarfUnit<'s, 's>) {
+        if !self.diagnostics.is_enabled() {
+            return;
+        }
+
+        if let Some(ref program) = unit.line_program {
+            let offset = program.header.offset().0 as u64;
+            for warning in &program.warnings {
+                self.diagnostics.push(Diagnostic {
+                    location: ErrorLocation::new(".debug_line", offset),
+                    message: warning.to_string(),
+                });
+            }
+        }
+    }
 }
 
 impl<'s> Iterator for DwarfUnitIterator<'s> {
     type Item = Result<DwarfUnit<'s, 's>, DwarfError>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        while self.index < self.info.headers.len() {
-            let result = self.info.get_unit(self.index);
-            self.index += 1;
+        while let Some(index) = self.next_index() {
+            #[cfg(feature = "tracing")]
+            let _span = tracing::trace_span!("parse_dwarf_unit", index).entered();
+
+            let result = self.info.get_unit(index);
 
             let unit = match result {
                 Ok(Some(unit)) => unit,
                 Ok(None) => continue,
-                Err(error) => return Some(Err(error)),
+                Err(error) => match self.recover(error) {
+                    Ok(()) => continue,
+                    Err(error) => return Some(Err(error)),
+                },
             };
 
-            match DwarfUnit::from_unit(unit, self.info, self.bcsymbolmap) {
-                Ok(Some(unit)) => return Some(Ok(unit)),
+            let result = DwarfUnit::from_unit(
+                unit,
+                self.info,
+                self.bcsymbolmap,
+                self.normalizer,
+                self.remapper,
+                self.interner,
+            );
+
+            match result {
+                Ok(Some(unit)) => {
+                    self.record_line_warnings(&unit);
+                    return Some(Ok(unit));
+                }
                 Ok(None) => continue,
-                Err(error) => return Some(Err(error)),
+                Err(error) => match self.recover(locate_error(error, unit.header.offset())) {
+                    Ok(()) => continue,
+                    Err(error) => return Some(Err(error)),
+                },
             }
         }
 
@@ -1272,31 +2923,87 @@ impl<'s> Iterator for DwarfUnitIterator<'s> {
 
 impl std::iter::FusedIterator for DwarfUnitIterator<'_> {}
 
+/// Determines how top-level functions covering the same code are handled.
+///
+/// Used by [`FunctionsOptions`].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum OverlapPolicy {
+    /// Yields only the first top-level function for every distinct address range.
+    ///
+    /// Linkers deduplicate identical code, such as template instantiations emitted by multiple
+    /// compilation units, leaving several DIEs that describe the same range. This is the default.
+    #[default]
+    SkipDuplicates,
+    /// Yields every top-level function, even if another function covers the same range.
+    KeepAll,
+}
+
+/// Options controlling which functions are yielded by a [`DwarfDebugSession`].
+///
+/// The default options skip functions without code and functions that cover the same range as a
+/// previously yielded function, which is what symbol caches expect. Consumers that need to see
+/// every DIE, such as profilers, can disable both.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct FunctionsOptions {
+    /// Yields functions with `DW_AT_low_pc` equal to `DW_AT_high_pc`.
+    ///
+    /// Such functions have a size of zero. Their inlinees are yielded as well.
+    pub keep_zero_size: bool,
+    /// Determines how top-level functions covering the same range are handled.
+    pub dedupe_overlaps: OverlapPolicy,
+}
+
 /// A debugging session for DWARF debugging information.
 pub struct DwarfDebugSession<'data> {
     cell: SelfCell<Box<DwarfSections<'data>>, DwarfInfo<'data>>,
     bcsymbolmap: Option<Arc<BcSymbolMap<'data>>>,
+    normalizer: Option<NameNormalizer>,
+    path_remapper: Option<PathRemapper>,
+    functions_options: FunctionsOptions,
+    merge_split_functions: bool,
+    cache_function_names: bool,
+    interner: Option<StringInterner>,
+    max_inline_depth: Option<usize>,
+    load_address: u64,
+    limits: SessionLimits,
+    diagnostics: Diagnostics,
 }
 
 impl<'data> DwarfDebugSession<'data> {
     /// Parses a dwarf debugging information from the given DWARF file.
-    pub fn parse<D>(
+    ///
+    /// The `relocation` converts addresses in the debug information to addresses relative to the
+    /// object's load address. It is either a single offset subtracted from all addresses, or an
+    /// [`AddressRelocation`] with individual offsets for every segment.
+    pub fn parse<D, R>(
         dwarf: &D,
         symbol_map: SymbolMap<'data>,
-        address_offset: i64,
+        relocation: R,
         kind: ObjectKind,
     ) -> Result<Self, DwarfError>
     where
         D: Dwarf<'data>,
+        R: Into<AddressRelocation>,
     {
+        let relocation = relocation.into();
         let sections = DwarfSections::from_dwarf(dwarf);
         let cell = SelfCell::try_new(Box::new(sections), |sections| {
-            DwarfInfo::parse(unsafe { &*sections }, symbol_map, address_offset, kind)
+            DwarfInfo::parse(unsafe { &*sections }, symbol_map, relocation, kind)
         })?;
 
         Ok(DwarfDebugSession {
             cell,
             bcsymbolmap: None,
+            normalizer: None,
+            path_remapper: None,
+            functions_options: FunctionsOptions::default(),
+            merge_split_functions: false,
+            cache_function_names: false,
+            interner: None,
+            max_inline_depth: None,
+            load_address: 0,
+            limits: SessionLimits::default(),
+            diagnostics: Diagnostics::default(),
         })
     }
 
@@ -1309,10 +3016,127 @@ impl<'data> DwarfDebugSession<'data> {
         self.bcsymbolmap = symbolmap;
     }
 
+    /// Sets a [`NameNormalizer`] applied to the names of all functions in this debug session.
+    ///
+    /// Normalization is disabled by default. When enabled, compiler generated variants of a
+    /// function, such as GCC's `foo.cold` and `foo.isra.0` clones or C++ thunks, are reported under
+    /// the name of the original function.
+    pub fn set_name_normalizer(&mut self, normalizer: Option<NameNormalizer>) {
+        self.normalizer = normalizer;
+    }
+
+    /// Sets a [`PathRemapper`] applied to all compilation directories and file paths in this debug
+    /// session.
+    ///
+    /// Rules are matched against compilation directories and file paths individually, so a rule
+    /// only applies to a relative file path if it matches the compilation directory as a whole.
+    /// Remapping is disabled by default.
+    pub fn set_path_remapper(&mut self, remapper: Option<PathRemapper>) {
+        self.path_remapper = remapper;
+    }
+
+    /// Sets the [`FunctionsOptions`] used by [`functions`](Self::functions) and
+    /// [`functions_sorted`](Self::functions_sorted).
+    pub fn set_functions_options(&mut self, options: FunctionsOptions) {
+        self.functions_options = options;
+    }
+
+    /// Enables merging of functions that have been split into multiple parts by the compiler.
+    ///
+    /// GCC moves unlikely code of a function `foo` into a separate `foo.cold` part, which may be
+    /// emitted as a separate function. When enabled, such parts are matched by their abstract origin
+    /// or their name and reported as a single [`Function`] with multiple [`ranges`]. This is
+    /// disabled by default.
+    ///
+    /// [`ranges`]: Function::ranges
+    pub fn set_merge_split_functions(&mut self, merge: bool) {
+        self.merge_split_functions = merge;
+    }
+
+    /// Enables caching of function names resolved through abstract origins.
+    ///
+    /// Inlined functions refer to their declaration via `DW_AT_abstract_origin`, which may in turn
+    /// refer to a `DW_AT_specification`. When enabled, the name behind such a reference is resolved
+    /// once per compilation unit and reused for all inline instances of the same function, which
+    /// speeds up conversion of template-heavy C++ code at the cost of memory. This is disabled by
+    /// default.
+    pub fn set_cache_function_names(&mut self, cache: bool) {
+        self.cache_function_names = cache;
+    }
+
+    /// Enables interning of function names and file paths computed by this session.
+    ///
+    /// Names that have to be qualified, demangled or normalized, as well as remapped paths, are not
+    /// contained verbatim in the debug file and would otherwise be allocated for every function
+    /// that refers to them. When enabled, such strings are stored once in a [`StringInterner`]
+    /// owned by this session and borrowed by all yielded functions. This reduces memory when
+    /// keeping many functions of a large debug file alive, but retains all strings until the
+    /// session is dropped. This is disabled by default.
+    pub fn set_intern_strings(&mut self, intern: bool) {
+        if intern != self.interner.is_some() {
+            self.interner = intern.then(StringInterner::new);
+        }
+    }
+
+    /// Returns the [`StringInterner`] of this session, if interning is enabled.
+    pub fn interner(&self) -> Option<&StringInterner> {
+        self.interner.as_ref()
+    }
+
+    /// Limits the nesting depth of inlinees of all functions yielded by this session.
+    ///
+    /// Deeper inlinees are replaced by synthetic records as described in
+    /// [`Function::limit_inline_depth`]. This applies to both [`functions`](Self::functions) and
+    /// [`functions_sorted`](Self::functions_sorted). There is no limit by default.
+    pub fn set_max_inline_depth(&mut self, max_depth: Option<usize>) {
+        self.max_inline_depth = max_depth;
+    }
+
+    /// Reports the addresses of all functions and lines for an image loaded at `load_address`.
+    ///
+    /// By default, addresses are relative to the start of the image. When set, `load_address` is
+    /// added to all addresses yielded by [`functions`](Self::functions) and
+    /// [`functions_sorted`](Self::functions_sorted), so that they match the addresses of a running
+    /// process. For MachO images, `MachObject::debug_session_with_slide` computes this from the
+    /// slide of the image.
+    pub fn set_load_address(&mut self, load_address: u64) {
+        self.load_address = load_address;
+    }
+
+    /// Sets the [`SessionLimits`] enforced by the function iterators of this session.
+    ///
+    /// Limits apply to every iterator separately. Since
+    /// [`functions_sorted`](Self::functions_sorted) parses all units upfront, it enforces the
+    /// limits while parsing, before yielding the first function. There are no limits by default.
+    pub fn set_limits(&mut self, limits: SessionLimits) {
+        self.limits = limits;
+    }
+
+    /// Enables collection of [`Diagnostic`]s instead of failing on corrupted debug information.
+    ///
+    /// When enabled, compilation units that cannot be read are skipped by all iterators of this
+    /// session, and the errors are recorded along with warnings of line programs. Use
+    /// [`take_diagnostics`](Self::take_diagnostics) to retrieve them after iterating. This is
+    /// disabled by default.
+    pub fn set_collect_diagnostics(&mut self, collect: bool) {
+        self.diagnostics.set_enabled(collect);
+    }
+
+    /// Returns all [`Diagnostic`]s collected so far and clears them.
+    pub fn take_diagnostics(&self) -> Vec<Diagnostic> {
+        self.diagnostics.take()
+    }
+
     /// Returns an iterator over all source files in this debug file.
     pub fn files(&self) -> DwarfFileIterator<'_> {
         DwarfFileIterator {
-            units: self.cell.get().units(self.bcsymbolmap.as_deref()),
+            units: self.cell.get().units(
+                self.bcsymbolmap.as_deref(),
+                self.normalizer.as_ref(),
+                self.path_remapper.as_ref(),
+                self.interner.as_ref(),
+                &self.diagnostics,
+            ),
             files: DwarfUnitFileIterator::default(),
             finished: false,
         }
@@ -1321,10 +3145,180 @@ impl<'data> DwarfDebugSession<'data> {
     /// Returns an iterator over all functions in this debug file.
     pub fn functions(&self) -> DwarfFunctionIterator<'_> {
         DwarfFunctionIterator {
-            units: self.cell.get().units(self.bcsymbolmap.as_deref()),
+            units: self.cell.get().units(
+                self.bcsymbolmap.as_deref(),
+                self.normalizer.as_ref(),
+                self.path_remapper.as_ref(),
+                self.interner.as_ref(),
+                &self.diagnostics,
+            ),
             functions: Vec::new().into_iter(),
             range_buf: Vec::new(),
             seen_ranges: BTreeSet::new(),
+            options: self.functions_options,
+            merge_split_functions: self.merge_split_functions,
+            cache_function_names: self.cache_function_names,
+            max_inline_depth: self.max_inline_depth,
+            load_address: self.load_address,
+            limits: LimitTracker::new(self.limits),
+            finished: false,
+        }
+    }
+
+    /// Returns an iterator over all functions in this debug file in ascending address order.
+    ///
+    /// Unlike [`functions`](Self::functions), which yields functions in the order of their
+    /// compilation units, this iterator guarantees that functions are sorted by their start
+    /// address across all units. Functions with the same address are yielded in the order of their
+    /// compilation units. Inlinees are not affected.
+    ///
+    /// Since any unit may contain the lowest address, all units are parsed on the first call to
+    /// `next`.
+    pub fn functions_sorted(&self) -> DwarfSortedFunctionIterator<'_> {
+        DwarfSortedFunctionIterator {
+            units: Some(self.cell.get().units(
+                self.bcsymbolmap.as_deref(),
+                self.normalizer.as_ref(),
+                self.path_remapper.as_ref(),
+                self.interner.as_ref(),
+                &self.diagnostics,
+            )),
+            options: self.functions_options,
+            merge_split_functions: self.merge_split_functions,
+            cache_function_names: self.cache_function_names,
+            max_inline_depth: self.max_inline_depth,
+            load_address: self.load_address,
+            limits: LimitTracker::new(self.limits),
+            functions: Vec::new(),
+            heap: BinaryHeap::new(),
+        }
+    }
+
+    /// Returns all top-level functions that cover the given address.
+    ///
+    /// Only compilation units containing the address are converted. Their ranges are read from
+    /// `.debug_aranges` if present, or otherwise from the top-level DIE of every unit that is
+    /// missing there. This index is built on the first lookup and reused afterwards, which makes
+    /// repeated lookups much cheaper than scanning [`functions`](Self::functions).
+    ///
+    /// The address is interpreted like the addresses of yielded functions, including the
+    /// [load address](Self::set_load_address). Functions are returned in the order of their
+    /// compilation units, and the [`FunctionsOptions`] of this session apply. Use
+    /// [`Function::inlinees`] to resolve inlined functions at the address.
+    pub fn lookup(&self, address: u64) -> Result<Vec<Function<'_>>, DwarfError> {
+        let relative_address = match address.checked_sub(self.load_address) {
+            Some(relative_address) => relative_address,
+            None => return Ok(Vec::new()),
+        };
+
+        let info = self.cell.get();
+        let mut units = info
+            .units(
+                self.bcsymbolmap.as_deref(),
+                self.normalizer.as_ref(),
+                self.path_remapper.as_ref(),
+                self.interner.as_ref(),
+                &self.diagnostics,
+            )
+            .select(info.unit_index().find(relative_address));
+
+        let mut range_buf = Vec::new();
+        let mut seen_ranges = BTreeSet::new();
+        let mut matches = Vec::new();
+
+        while let Some(unit) = units.next() {
+            let result = unit?.functions(
+                &mut range_buf,
+                &mut seen_ranges,
+                self.functions_options,
+                self.merge_split_functions,
+                self.cache_function_names,
+            );
+
+            let functions = match result {
+                Ok(functions) => functions,
+                Err(error) => {
+                    units.recover(error)?;
+                    continue;
+                }
+            };
+
+            for function in functions {
+                if function.contains(relative_address) {
+                    matches.push(
+                        function
+                            .with_inline_depth_limit(self.max_inline_depth)
+                            .with_address_offset(self.load_address),
+                    );
+                }
+            }
+        }
+
+        Ok(matches)
+    }
+
+    /// Returns an iterator over the functions of all compilation units overlapping the given
+    /// address ranges.
+    ///
+    /// This is useful to convert only the parts of a large debug file that are relevant for a set
+    /// of addresses, for instance the frames of a crash. Units are selected using the same index as
+    /// [`lookup`](Self::lookup), and units whose ranges cannot be determined are always included.
+    /// Units are converted as a whole, so the iterator also yields functions of selected units
+    /// that lie outside of the requested ranges.
+    ///
+    /// The ranges are interpreted like the addresses of yielded functions, including the
+    /// [load address](Self::set_load_address). Empty ranges are ignored.
+    pub fn functions_for_ranges(
+        &self,
+        ranges: &[std::ops::Range<u64>],
+    ) -> DwarfFunctionIterator<'_> {
+        let relative_ranges: Vec<_> = ranges
+            .iter()
+            .filter(|range| range.end > self.load_address)
+            .map(|range| {
+                range.start.saturating_sub(self.load_address)..range.end - self.load_address
+            })
+            .collect();
+
+        let info = self.cell.get();
+        let mut functions = self.functions();
+        functions.units = functions
+            .units
+            .select(info.unit_index().find_ranges(&relative_ranges));
+        functions
+    }
+
+    /// Returns an iterator over the macro information of all compilation units.
+    ///
+    /// Units without macro information are skipped. Compilers usually only emit macro information
+    /// when explicitly requested, for instance with `-g3` or `-fdebug-macro`.
+    pub fn macros(&self) -> DwarfMacroIterator<'_> {
+        DwarfMacroIterator {
+            units: self.cell.get().units(
+                self.bcsymbolmap.as_deref(),
+                self.normalizer.as_ref(),
+                self.path_remapper.as_ref(),
+                self.interner.as_ref(),
+                &self.diagnostics,
+            ),
+            finished: false,
+        }
+    }
+
+    /// Returns an iterator over warnings encountered in the line programs of all compilation units.
+    ///
+    /// Malformed line programs, for instance with decreasing or overflowing addresses, do not fail
+    /// symbolication. Instead, affected rows are skipped or clamped and reported here. Units
+    /// without warnings are skipped.
+    pub fn line_warnings(&self) -> DwarfLineWarningIterator<'_> {
+        DwarfLineWarningIterator {
+            units: self.cell.get().units(
+                self.bcsymbolmap.as_deref(),
+                self.normalizer.as_ref(),
+                self.path_remapper.as_ref(),
+                self.interner.as_ref(),
+                &self.diagnostics,
+            ),
             finished: false,
         }
     }
@@ -1385,6 +3379,15 @@ fn resolve_byte_name<'s>(bcsymbolmap: Option<&'s BcSymbolMap<'s>>, s: &'s [u8])
         .unwrap_or(s)
 }
 
+/// Returns `true` if the path is an absolute Unix or Windows path.
+fn is_absolute_path(path: &[u8]) -> bool {
+    match path {
+        [b'/' | b'\\', ..] => true,
+        [letter, b':', ..] => letter.is_ascii_alphabetic(),
+        _ => false,
+    }
+}
+
 fn resolve_cow_name<'s>(bcsymbolmap: Option<&'s BcSymbolMap<'s>>, s: Cow<'s, str>) -> Cow<'s, str> {
     bcsymbolmap
         .and_then(|b| b.resolve_opt(s.as_bytes()))
@@ -1435,6 +3438,12 @@ pub struct DwarfFunctionIterator<'s> {
     functions: std::vec::IntoIter<Function<'s>>,
     range_buf: Vec<Range>,
     seen_ranges: BTreeSet<(u64, u64)>,
+    options: FunctionsOptions,
+    merge_split_functions: bool,
+    cache_function_names: bool,
+    max_inline_depth: Option<usize>,
+    load_address: u64,
+    limits: LimitTracker,
     finished: bool,
 }
 
@@ -1448,6 +3457,13 @@ impl<'s> Iterator for DwarfFunctionIterator<'s> {
 
         loop {
             if let Some(func) = self.functions.next() {
+                let func = func
+                    .with_inline_depth_limit(self.max_inline_depth)
+                    .with_address_offset(self.load_address);
+                if let Err(error) = self.limits.track(&func) {
+                    self.finished = true;
+                    return Some(Err(error.into()));
+                }
                 return Some(Ok(func));
             }
 
@@ -1457,9 +3473,20 @@ impl<'s> Iterator for DwarfFunctionIterator<'s> {
                 None => break,
             };
 
-            self.functions = match unit.functions(&mut self.range_buf, &mut self.seen_ranges) {
+            let functions = unit.functions(
+                &mut self.range_buf,
+                &mut self.seen_ranges,
+                self.options,
+                self.merge_split_functions,
+                self.cache_function_names,
+            );
+
+            self.functions = match functions {
                 Ok(functions) => functions.into_iter(),
-                Err(error) => return Some(Err(error)),
+                Err(error) => match self.units.recover(error) {
+                    Ok(()) => continue,
+                    Err(error) => return Some(Err(error)),
+                },
             };
         }
 
@@ -1469,3 +3496,669 @@ impl<'s> Iterator for DwarfFunctionIterator<'s> {
 }
 
 impl std::iter::FusedIterator for DwarfFunctionIterator<'_> {}
+
+/// An iterator over functions in a DWARF file in ascending address order.
+///
+/// Returned by [`DwarfDebugSession::functions_sorted`].
+pub struct DwarfSortedFunctionIterator<'s> {
+    units: Option<DwarfUnitIterator<'s>>,
+    options: FunctionsOptions,
+    merge_split_functions: bool,
+    cache_function_names: bool,
+    max_inline_depth: Option<usize>,
+    load_address: u64,
+    limits: LimitTracker,
+    functions: Vec<std::iter::Peekable<std::vec::IntoIter<Function<'s>>>>,
+    heap: BinaryHeap<Reverse<(u64, usize)>>,
+}
+
+impl<'s> DwarfSortedFunctionIterator<'s> {
+    /// Collects the sorted functions of all units and prepares them for merging.
+    fn collect_units(&mut self, mut units: DwarfUnitIterator<'s>) -> Result<(), DwarfError> {
+        let mut range_buf = Vec::new();
+        let mut seen_ranges = BTreeSet::new();
+
+        while let Some(unit) = units.next() {
+            let result = unit?.functions(
+                &mut range_buf,
+                &mut seen_ranges,
+                self.options,
+                self.merge_split_functions,
+                self.cache_function_names,
+            );
+
+            let mut functions = match result {
+                Ok(functions) => functions,
+                Err(error) => {
+                    units.recover(error)?;
+                    continue;
+                }
+            };
+
+            for function in &functions {
+                self.limits.track(function)?;
+            }
+
+            // A stable sort retains the order of functions with the same address within a unit.
+            functions.sort_by_key(|function| function.address);
+
+            if let Some(first) = functions.first() {
+                self.heap
+                    .push(Reverse((first.address, self.functions.len())));
+                self.functions.push(functions.into_iter().peekable());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<'s> Iterator for DwarfSortedFunctionIterator<'s> {
+    type Item = Result<Function<'s>, DwarfError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(units) = self.units.take() {
+            if let Err(error) = self.collect_units(units) {
+                self.functions.clear();
+                self.heap.clear();
+                return Some(Err(error));
+            }
+        }
+
+        let Reverse((_, index)) = self.heap.pop()?;
+        let functions = &mut self.functions[index];
+        let function = functions.next()?;
+
+        if let Some(next) = functions.peek() {
+            self.heap.push(Reverse((next.address, index)));
+        }
+
+        let function = function
+            .with_inline_depth_limit(self.max_inline_depth)
+            .with_address_offset(self.load_address);
+        Some(Ok(function))
+    }
+}
+
+impl std::iter::FusedIterator for DwarfSortedFunctionIterator<'_> {}
+
+/// An iterator over the macro information of compilation units in a DWARF file.
+pub struct DwarfMacroIterator<'s> {
+    units: DwarfUnitIterator<'s>,
+    finished: bool,
+}
+
+impl<'s> Iterator for DwarfMacroIterator<'s> {
+    type Item = Result<DwarfUnitMacros<'s>, DwarfError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        while let Some(result) = self.units.next() {
+            let unit = match result {
+                Ok(unit) => unit,
+                Err(error) => return Some(Err(error)),
+            };
+
+            let macros = match unit.macros() {
+                Ok(macros) if macros.is_empty() => continue,
+                Ok(macros) => macros,
+                Err(error) => match self.units.recover(error) {
+                    Ok(()) => continue,
+                    Err(error) => return Some(Err(error)),
+                },
+            };
+
+            let name = match unit.inner.unit.name {
+                Some(ref name) => resolve_byte_name(unit.bcsymbolmap, name.slice()),
+                None => &[],
+            };
+
+            return Some(Ok(DwarfUnitMacros {
+                name,
+                compilation_dir: unit.compilation_dir(),
+                macros,
+            }));
+        }
+
+        self.finished = true;
+        None
+    }
+}
+
+impl std::iter::FusedIterator for DwarfMacroIterator<'_> {}
+
+/// An iterator over line program warnings of compilation units in a DWARF file.
+pub struct DwarfLineWarningIterator<'s> {
+    units: DwarfUnitIterator<'s>,
+    finished: bool,
+}
+
+impl<'s> Iterator for DwarfLineWarningIterator<'s> {
+    type Item = Result<DwarfUnitLineWarnings<'s>, DwarfError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        for result in &mut self.units {
+            let unit = match result {
+                Ok(unit) => unit,
+                Err(error) => return Some(Err(error)),
+            };
+
+            let warnings = match unit.line_program {
+                Some(ref program) if !program.warnings.is_empty() => program.warnings.clone(),
+                _ => continue,
+            };
+
+            let name = match unit.inner.unit.name {
+                Some(ref name) => resolve_byte_name(unit.bcsymbolmap, name.slice()),
+                None => &[],
+            };
+
+            return Some(Ok(DwarfUnitLineWarnings {
+                name,
+                compilation_dir: unit.compilation_dir(),
+                warnings,
+            }));
+        }
+
+        self.finished = true;
+        None
+    }
+}
+
+impl std::iter::FusedIterator for DwarfLineWarningIterator<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use similar_asserts::assert_eq;
+
+    fn parser<'d>(
+        inner: &'d DwarfInner<'d>,
+        debug_macro: &'d [u8],
+        debug_macinfo: &'d [u8],
+    ) -> DwarfMacroParser<'d, 'd, impl Fn(u64) -> Option<FileInfo<'d>>> {
+        DwarfMacroParser {
+            inner,
+            debug_macro: Slice::new(debug_macro, Endian::Little),
+            debug_macinfo: Slice::new(debug_macinfo, Endian::Little),
+            str_offsets_base: gimli::DebugStrOffsetsBase(0),
+            resolve_file: |file_id| match file_id {
+                1 => Some(FileInfo {
+                    name: Cow::Borrowed(b"main.c"),
+                    dir: Cow::Borrowed(b"/src"),
+                    path_kind: FilePathKind::DirectoryRelative,
+                }),
+                _ => None,
+            },
+            macros: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_evaluate_expression() {
+        let context = ExpressionContext {
+            frame_base: Some(0x8000),
+            ..ExpressionContext::new(Endian::Little, 8)
+        };
+        let registers = |register| (register == 6).then_some(0x7000);
+        let memory = |address, _| (address == 0x6ff8).then_some(0xdead);
+
+        // DW_OP_fbreg -16
+        let pieces = evaluate_expression(&[0x91, 0x70], context, registers, memory).unwrap();
+        assert_eq!(pieces.len(), 1);
+        assert_eq!(pieces[0].location, ExpressionLocation::Address(0x7ff0));
+
+        // DW_OP_breg6 -8, DW_OP_deref, DW_OP_stack_value
+        let pieces =
+            evaluate_expression(&[0x76, 0x78, 0x06, 0x9f], context, registers, memory).unwrap();
+        assert_eq!(pieces[0].location, ExpressionLocation::Value(0xdead));
+
+        // DW_OP_reg3, DW_OP_piece 4, DW_OP_lit1, DW_OP_stack_value, DW_OP_piece 4
+        let pieces = evaluate_expression(
+            &[0x53, 0x93, 0x04, 0x31, 0x9f, 0x93, 0x04],
+            context,
+            registers,
+            memory,
+        )
+        .unwrap();
+        assert_eq!(
+            pieces,
+            vec![
+                ExpressionPiece {
+                    size_in_bits: Some(32),
+                    bit_offset: None,
+                    location: ExpressionLocation::Register(3),
+                },
+                ExpressionPiece {
+                    size_in_bits: Some(32),
+                    bit_offset: None,
+                    location: ExpressionLocation::Value(1),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_evaluate_expression_missing() {
+        let context = ExpressionContext::new(Endian::Little, 8);
+
+        // DW_OP_breg1 0 requires a register that is not available.
+        let error = evaluate_expression(&[0x71, 0x00], context, |_| None, |_, _| None).unwrap_err();
+        assert_eq!(error.kind(), DwarfErrorKind::IncompleteExpression);
+
+        // DW_OP_call_frame_cfa without a CFA.
+        let error = evaluate_expression(&[0x9c], context, |_| None, |_, _| None).unwrap_err();
+        assert_eq!(error.kind(), DwarfErrorKind::IncompleteExpression);
+
+        // DW_OP_skip -3 loops forever.
+        let error =
+            evaluate_expression(&[0x2f, 0xfd, 0xff], context, |_| None, |_, _| None).unwrap_err();
+        assert_eq!(error.kind(), DwarfErrorKind::CorruptedData);
+    }
+
+    fn summarize(macros: &[DwarfMacro<'_>]) -> Vec<(DwarfMacroKind, u64, String)> {
+        macros
+            .iter()
+            .map(|m| {
+                let text = match m.file {
+                    Some(ref file) => file.path_str(),
+                    None => String::from_utf8_lossy(m.text).into_owned(),
+                };
+                (m.kind, m.line, text)
+            })
+            .collect()
+    }
+
+    /// Assembles a DWARF 4 line program for 64-bit addresses with a single file `a.c`.
+    fn line_program(program: &[u8]) -> Vec<u8> {
+        #[rustfmt::skip]
+        let header: &[u8] = &[
+            // minimum_instruction_length, maximum_operations_per_instruction
+            0x01, 0x01,
+            // default_is_stmt, line_base, line_range, opcode_base
+            0x01, 0xfb, 0x0e, 0x0d,
+            // standard_opcode_lengths
+            0x00, 0x01, 0x01, 0x01, 0x01, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x01,
+            // no include directories
+            0x00,
+            // file a.c in directory 0, no mtime and size, end of files
+            b'a', b'.', b'c', 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+
+        let mut data = Vec::new();
+        let unit_length = 2 + 4 + header.len() + program.len();
+        data.extend_from_slice(&(unit_length as u32).to_le_bytes());
+        data.extend_from_slice(&4u16.to_le_bytes());
+        data.extend_from_slice(&(header.len() as u32).to_le_bytes());
+        data.extend_from_slice(header);
+        data.extend_from_slice(program);
+        data
+    }
+
+    /// Encodes `DW_LNE_set_address` followed by `DW_LNS_copy`, which emits a row.
+    fn emit_row(program: &mut Vec<u8>, address: u64) {
+        program.extend_from_slice(&[0x00, 0x09, 0x02]);
+        program.extend_from_slice(&address.to_le_bytes());
+        program.push(0x01);
+    }
+
+    fn prepare_line_program(data: &[u8]) -> Result<DwarfLineProgram<'_>, DwarfError> {
+        let debug_line = gimli::DebugLine::from(Slice::new(data, Endian::Little));
+        let program = debug_line.program(gimli::DebugLineOffset(0), 8, None, None)?;
+        Ok(DwarfLineProgram::prepare(program))
+    }
+
+    #[test]
+    fn test_is_absolute_path() {
+        assert!(is_absolute_path(b"/usr/include"));
+        assert!(is_absolute_path(b"C:\\src\\main.c"));
+        assert!(is_absolute_path(b"\\\\server\\share"));
+        assert!(!is_absolute_path(b"../src"));
+        assert!(!is_absolute_path(b"main.c"));
+        assert!(!is_absolute_path(b""));
+    }
+
+    #[test]
+    fn test_line_program_without_warnings() -> Result<(), DwarfError> {
+        let mut program = Vec::new();
+        emit_row(&mut program, 0x1000);
+        emit_row(&mut program, 0x1010);
+        // DW_LNE_end_sequence
+        program.extend_from_slice(&[0x00, 0x01, 0x01]);
+
+        let data = line_program(&program);
+        let line_program = prepare_line_program(&data)?;
+
+        assert_eq!(line_program.warnings, vec![]);
+        assert_eq!(line_program.sequences.len(), 1);
+        assert_eq!(line_program.sequences[0].start, 0x1000);
+        assert_eq!(line_program.sequences[0].end, 0x1010);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_line_program_warnings() -> Result<(), DwarfError> {
+        let mut program = Vec::new();
+        emit_row(&mut program, 0x1000);
+        emit_row(&mut program, 0x800);
+        emit_row(&mut program, u64::MAX);
+        // A truncated extended opcode that exceeds the program
+        program.extend_from_slice(&[0x00, 0x10]);
+
+        let data = line_program(&program);
+        let line_program = prepare_line_program(&data)?;
+
+        assert_eq!(
+            line_program.warnings,
+            vec![
+                DwarfLineWarning::DecreasingAddress {
+                    address: 0x800,
+                    previous: 0x1000,
+                },
+                DwarfLineWarning::InvalidProgram,
+                DwarfLineWarning::AddressOverflow { address: u64::MAX },
+            ]
+        );
+
+        let sequence = &line_program.sequences[0];
+        assert_eq!((sequence.start, sequence.end), (0x1000, u64::MAX));
+        assert_eq!(sequence.rows.len(), 2);
+        assert_eq!(sequence.rows[0].size, Some(u64::MAX - 0x1000));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_debug_macro() -> Result<(), DwarfError> {
+        let inner = DwarfInner {
+            debug_str: Slice::new(b"BAR 2\0", Endian::Little).into(),
+            ..Default::default()
+        };
+
+        #[rustfmt::skip]
+        let debug_macro: &[u8] = &[
+            // unit header: version 5, no flags
+            0x05, 0x00, 0x00,
+            // start_file line 0, file 1
+            0x03, 0x00, 0x01,
+            // define line 1
+            0x01, 0x01, b'F', b'O', b'O', b'(', b'a', b')', b' ', b'a', 0x00,
+            // define_strp line 2, offset 0
+            0x05, 0x02, 0x00, 0x00, 0x00, 0x00,
+            // import unit at offset 36
+            0x07, 0x24, 0x00, 0x00, 0x00,
+            // undef line 3
+            0x02, 0x03, b'F', b'O', b'O', 0x00,
+            // end_file, end of unit
+            0x04, 0x00,
+            // imported unit header: version 4 (GNU), no flags
+            0x04, 0x00, 0x00,
+            // define line 0
+            0x01, 0x00, b'B', b'A', b'Z', 0x00,
+            0x00,
+        ];
+
+        let mut parser = parser(&inner, debug_macro, &[]);
+        parser.parse_macro(0, 0)?;
+
+        assert_eq!(
+            summarize(&parser.macros),
+            vec![
+                (DwarfMacroKind::StartFile, 0, "/src/main.c".to_owned()),
+                (DwarfMacroKind::Define, 1, "FOO(a) a".to_owned()),
+                (DwarfMacroKind::Define, 2, "BAR 2".to_owned()),
+                (DwarfMacroKind::Define, 0, "BAZ".to_owned()),
+                (DwarfMacroKind::Undef, 3, "FOO".to_owned()),
+                (DwarfMacroKind::EndFile, 0, "".to_owned()),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_debug_macro_vendor_extension() {
+        let inner = DwarfInner::default();
+
+        #[rustfmt::skip]
+        let debug_macro: &[u8] = &[
+            // unit header: version 5, opcode operands table
+            0x05, 0x00, 0x04,
+            // one entry: opcode 0xe0 with operands udata and string
+            0x01, 0xe0, 0x02, 0x0f, 0x08,
+            // vendor entry
+            0xe0, 0x01, b'x', 0x00,
+            // define line 1
+            0x01, 0x01, b'A', 0x00,
+            // unknown vendor entry
+            0xe1, 0x00,
+        ];
+
+        let mut parser = parser(&inner, debug_macro, &[]);
+        let error = parser.parse_macro(0, 0).unwrap_err();
+        assert_eq!(error.kind(), DwarfErrorKind::CorruptedData);
+
+        assert_eq!(
+            summarize(&parser.macros),
+            vec![(DwarfMacroKind::Define, 1, "A".to_owned())]
+        );
+    }
+
+    #[test]
+    fn test_debug_macinfo() -> Result<(), DwarfError> {
+        let inner = DwarfInner::default();
+
+        #[rustfmt::skip]
+        let debug_macinfo: &[u8] = &[
+            // start_file line 0, file 1
+            0x03, 0x00, 0x01,
+            // define line 5
+            0x01, 0x05, b'X', b' ', b'1', 0x00,
+            // vendor extension
+            0xff, 0x01, b'v', 0x00,
+            // undef line 6
+            0x02, 0x06, b'X', 0x00,
+            // end_file, end of unit
+            0x04, 0x00,
+        ];
+
+        let mut parser = parser(&inner, &[], debug_macinfo);
+        parser.parse_macinfo(0)?;
+
+        assert_eq!(
+            summarize(&parser.macros),
+            vec![
+                (DwarfMacroKind::StartFile, 0, "/src/main.c".to_owned()),
+                (DwarfMacroKind::Define, 5, "X 1".to_owned()),
+                (DwarfMacroKind::Undef, 6, "X".to_owned()),
+                (DwarfMacroKind::EndFile, 0, "".to_owned()),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_macro_name_value() {
+        let define = |text: &'static [u8]| DwarfMacro {
+            kind: DwarfMacroKind::Define,
+            line: 1,
+            text,
+            file: None,
+        };
+
+        let function_like = define(b"MAX(a, b) ((a) > (b) ? (a) : (b))");
+        assert_eq!(function_like.name(), "MAX");
+        assert_eq!(
+            function_like.value().as_deref(),
+            Some("((a) > (b) ? (a) : (b))")
+        );
+
+        let object_like = define(b"VERSION 42");
+        assert_eq!(object_like.name(), "VERSION");
+        assert_eq!(object_like.value().as_deref(), Some("42"));
+
+        let empty = define(b"HAVE_CONFIG_H");
+        assert_eq!(empty.name(), "HAVE_CONFIG_H");
+        assert_eq!(empty.value().as_deref(), Some(""));
+
+        let undef = DwarfMacro {
+            kind: DwarfMacroKind::Undef,
+            ..define(b"VERSION")
+        };
+        assert_eq!(undef.name(), "VERSION");
+        assert_eq!(undef.value(), None);
+    }
+
+    fn split_function(name: &'static str, address: u64, size: u64, die: u64) -> Function<'static> {
+        Function {
+            address,
+            size,
+            ranges: Vec::new(),
+            name: Name::from(name),
+            compilation_dir: Cow::Borrowed(b""),
+            lines: vec![LineInfo {
+                address,
+                size: Some(size),
+                file: FileInfo {
+                    name: Cow::Borrowed(b"main.c"),
+                    dir: Cow::Borrowed(b""),
+                    path_kind: FilePathKind::CompDirRelative,
+                },
+                line: 1,
+            }]
+            .into(),
+            inlinees: Vec::new(),
+            inline: false,
+            artificial: false,
+            noreturn: false,
+            calling_convention: None,
+            call_sites: Vec::new(),
+            entity: Some(DebugEntity::DwarfDie(die)),
+        }
+    }
+
+    #[test]
+    fn test_cold_part_parent() {
+        assert_eq!(cold_part_parent("foo.cold"), Some("foo"));
+        assert_eq!(cold_part_parent("foo.cold.12"), Some("foo"));
+        assert_eq!(cold_part_parent("foo.part.0.cold"), Some("foo.part.0"));
+        assert_eq!(cold_part_parent("foo"), None);
+        assert_eq!(cold_part_parent("foo.isra.0"), None);
+        assert_eq!(cold_part_parent(".cold"), None);
+    }
+
+    #[test]
+    fn test_merge_split_functions_by_name() {
+        let functions = vec![
+            split_function("foo", 0x1000, 0x20, 1),
+            split_function("bar", 0x1020, 0x10, 2),
+            split_function("foo.cold", 0x8000, 0x8, 3),
+        ];
+
+        let merged = merge_split_functions_by_origin(functions, &BTreeMap::new());
+        assert_eq!(merged.len(), 2);
+
+        let foo = &merged[0];
+        assert_eq!(foo.name.as_str(), "foo");
+        assert_eq!(foo.address, 0x1000);
+        assert_eq!(foo.size, 0x28);
+        assert_eq!(foo.ranges, vec![0x1000..0x1020, 0x8000..0x8008]);
+        assert_eq!(foo.lines.len(), 2);
+        assert_eq!(foo.entity, Some(DebugEntity::DwarfDie(1)));
+
+        let bar = &merged[1];
+        assert_eq!(bar.name.as_str(), "bar");
+        assert!(bar.ranges.is_empty());
+    }
+
+    #[test]
+    fn test_merge_split_functions_by_origin() {
+        // The cold part is listed first and does not carry the name of its parent.
+        let functions = vec![
+            split_function("unlikely", 0x8000, 0x8, 1),
+            split_function("foo", 0x1000, 0x20, 2),
+            split_function("foo", 0x2000, 0x20, 3),
+        ];
+
+        let origins = [(1, 100), (2, 100), (3, 200)].iter().copied().collect();
+        let merged = merge_split_functions_by_origin(functions, &origins);
+        assert_eq!(merged.len(), 2);
+
+        assert_eq!(merged[0].address, 0x8000);
+        assert_eq!(merged[0].ranges, vec![0x1000..0x1020, 0x8000..0x8008]);
+        assert_eq!(merged[0].lines.first().unwrap().address, 0x1000);
+
+        assert_eq!(merged[1].address, 0x2000);
+        assert!(merged[1].ranges.is_empty());
+    }
+
+    #[test]
+    fn test_unit_index_find() {
+        use std::slice;
+
+        let unit_range = |begin, end, index| UnitRange {
+            begin,
+            end,
+            max_end: 0,
+            index,
+        };
+
+        // Unit 1 spans across the range of unit 2.
+        let mut ranges = vec![
+            unit_range(0x1000, 0x1100, 0),
+            unit_range(0x1100, 0x1800, 1),
+            unit_range(0x1200, 0x1300, 2),
+            unit_range(0x2000, 0x2100, 0),
+        ];
+
+        let mut max_end = 0;
+        for range in &mut ranges {
+            max_end = max_end.max(range.end);
+            range.max_end = max_end;
+        }
+
+        let index = UnitIndex {
+            ranges,
+            unindexed: vec![3],
+        };
+
+        assert_eq!(index.find(0x0fff), vec![3]);
+        assert_eq!(index.find(0x1000), vec![0, 3]);
+        assert_eq!(index.find(0x1250), vec![1, 2, 3]);
+        assert_eq!(index.find(0x1400), vec![1, 3]);
+        assert_eq!(index.find(0x1900), vec![3]);
+        assert_eq!(index.find(0x20ff), vec![0, 3]);
+
+        assert_eq!(index.find_ranges(&[]), vec![3]);
+        assert_eq!(
+            index.find_ranges(slice::from_ref(&(0x1000..0x1000))),
+            vec![3]
+        );
+        assert_eq!(
+            index.find_ranges(slice::from_ref(&(0x0f00..0x1001))),
+            vec![0, 3]
+        );
+        assert_eq!(
+            index.find_ranges(slice::from_ref(&(0x1400..0x2000))),
+            vec![1, 3]
+        );
+        assert_eq!(
+            index.find_ranges(&[0x1250..0x1260, 0x2000..0x2001]),
+            vec![0, 1, 2, 3]
+        );
+        assert_eq!(
+            index.find_ranges(slice::from_ref(&(0x1800..0x2000))),
+            vec![3]
+        );
+    }
+}
@@ -1,10 +1,12 @@
 //! Support for the Executable and Linkable Format, used on Linux.
 
 use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::convert::TryInto;
 use std::error::Error;
 use std::ffi::CStr;
 use std::fmt;
+use std::ops::Range;
 
 use core::cmp;
 use flate2::{Decompress, FlushDecompress};
@@ -16,14 +18,15 @@ use goblin::{
     container::{Container, Ctx},
     elf, strtab,
 };
-use scroll::Pread;
+use scroll::{Pread, Pwrite};
 use thiserror::Error;
 
-use symbolic_common::{Arch, AsSelf, CodeId, DebugId, Uuid};
+use symbolic_common::{Arch, AsSelf, CodeId, CpuFamily, DebugId, Uuid};
 
 use crate::base::*;
 use crate::dwarf::{Dwarf, DwarfDebugSession, DwarfError, DwarfSection, Endian};
-use crate::shared::Parse;
+use crate::go::{GoDebugSession, GoError};
+use crate::shared::{append_aligned, Parse};
 
 const UUID_SIZE: usize = 16;
 const PAGE_SIZE: usize = 4096;
@@ -45,12 +48,38 @@ const EF_MIPS_ABI_EABI64: u32 = 0x0000_4000;
 /// Any flag value that might indicate 64-bit MIPS.
 const MIPS_64_FLAGS: u32 = EF_MIPS_ABI_O64 | EF_MIPS_ABI_EABI64;
 
+/// Address of Android's packed `REL` relocation table.
+const DT_ANDROID_REL: u64 = 0x6000_000f;
+/// Size of Android's packed `REL` relocation table.
+const DT_ANDROID_RELSZ: u64 = 0x6000_0010;
+/// Address of Android's packed `RELA` relocation table.
+const DT_ANDROID_RELA: u64 = 0x6000_0011;
+/// Size of Android's packed `RELA` relocation table.
+const DT_ANDROID_RELASZ: u64 = 0x6000_0012;
+
+/// Magic at the start of Android's packed relocation tables.
+const ANDROID_PACKED_MAGIC: &[u8] = b"APS2";
+/// All relocations in the group share the same `r_info`.
+const RELOCATION_GROUPED_BY_INFO_FLAG: i64 = 1;
+/// All relocations in the group are at the same distance from each other.
+const RELOCATION_GROUPED_BY_OFFSET_DELTA_FLAG: i64 = 2;
+/// All relocations in the group share the same addend.
+const RELOCATION_GROUPED_BY_ADDEND_FLAG: i64 = 4;
+/// Relocations in the group have an addend.
+const RELOCATION_GROUP_HAS_ADDEND_FLAG: i64 = 8;
+
+/// Note type of the `.note.android.ident` note, declaring the API level and NDK version.
+const NT_ANDROID_TYPE_IDENT: u32 = 1;
+/// Size of the NDK version and build number fields in the `.note.android.ident` note.
+const ANDROID_NDK_FIELD_SIZE: usize = 64;
+
 /// An error when dealing with [`ElfObject`](struct.ElfObject.html).
 #[derive(Debug, Error)]
-#[error("invalid ELF file")]
+#[error("invalid ELF file{}", LocationSuffix(.location))]
 pub struct ElfError {
     #[source]
     source: Option<Box<dyn Error + Send + Sync + 'static>>,
+    location: Option<ErrorLocation>,
 }
 
 impl ElfError {
@@ -60,7 +89,26 @@ impl ElfError {
         E: Into<Box<dyn Error + Send + Sync>>,
     {
         let source = Some(source.into());
-        Self { source }
+        Self {
+            source,
+            location: None,
+        }
+    }
+
+    /// Returns the location in the file at which this error occurred, if known.
+    pub fn location(&self) -> Option<&ErrorLocation> {
+        self.location.as_ref()
+    }
+
+    /// Attaches a location to this error, unless a more specific location is already known.
+    fn at<S>(mut self, section: S, offset: u64) -> Self
+    where
+        S: Into<Cow<'static, str>>,
+    {
+        if self.location.is_none() {
+            self.location = Some(ErrorLocation::new(section, offset));
+        }
+        self
     }
 }
 
@@ -68,7 +116,9 @@ impl ElfError {
 pub struct ElfObject<'data> {
     elf: elf::Elf<'data>,
     data: &'data [u8],
+    options: ObjectOptions,
     is_malformed: bool,
+    android_relocs: Vec<elf::Reloc>,
 }
 
 impl<'data> ElfObject<'data> {
@@ -137,11 +187,19 @@ impl<'data> ElfObject<'data> {
     /// Tries to parse an ELF object from the given slice. Will return a partially parsed ELF object
     /// if at least the program and section headers can be parsed.
     pub fn parse(data: &'data [u8]) -> Result<Self, ElfError> {
-        let header =
-            elf::Elf::parse_header(data).map_err(|_| ElfError::new("ELF header unreadable"))?;
+        Self::parse_with(data, ObjectOptions::new())
+    }
+
+    /// Tries to parse an ELF object from the given slice with custom options.
+    ///
+    /// Disabling [`ObjectOptions::symbols`] skips the symbol tables, dynamic symbols, relocations
+    /// and symbol versions. The program and section headers are always parsed.
+    pub fn parse_with(data: &'data [u8], options: ObjectOptions) -> Result<Self, ElfError> {
+        let header = elf::Elf::parse_header(data)
+            .map_err(|_| ElfError::new("ELF header unreadable").at("ELF header", 0))?;
         // dummy Elf with only header
-        let mut obj =
-            elf::Elf::lazy_parse(header).map_err(|_| ElfError::new("cannot parse ELF header"))?;
+        let mut obj = elf::Elf::lazy_parse(header)
+            .map_err(|_| ElfError::new("cannot parse ELF header").at("ELF header", 0))?;
 
         let ctx = Ctx {
             container: if obj.is_64 {
@@ -165,7 +223,9 @@ impl<'data> ElfObject<'data> {
                     return Ok(ElfObject {
                         elf: obj,
                         data,
+                        options,
                         is_malformed: true,
+                        android_relocs: Vec::new(),
                     });
                 }
             };
@@ -173,7 +233,10 @@ impl<'data> ElfObject<'data> {
 
         obj.program_headers =
             elf::ProgramHeader::parse(data, header.e_phoff as usize, header.e_phnum as usize, ctx)
-                .map_err(|_| ElfError::new("unable to parse program headers"))?;
+                .map_err(|_| {
+                    ElfError::new("unable to parse program headers")
+                        .at("program headers", header.e_phoff)
+                })?;
 
         for ph in &obj.program_headers {
             if ph.p_type == elf::program_header::PT_INTERP && ph.p_filesz != 0 {
@@ -187,7 +250,10 @@ impl<'data> ElfObject<'data> {
 
         obj.section_headers =
             SectionHeader::parse(data, header.e_shoff as usize, header.e_shnum as usize, ctx)
-                .map_err(|_| ElfError::new("unable to parse section headers"))?;
+                .map_err(|_| {
+                    ElfError::new("unable to parse section headers")
+                        .at("section headers", header.e_shoff)
+                })?;
 
         let get_strtab = |section_headers: &[SectionHeader], section_idx: usize| {
             if section_idx >= section_headers.len() {
@@ -206,6 +272,10 @@ impl<'data> ElfObject<'data> {
         obj.syms = elf::Symtab::default();
         obj.strtab = Strtab::default();
         for shdr in &obj.section_headers {
+            if !options.symbols || options.exceeds_section_size(shdr.sh_size) {
+                continue;
+            }
+
             if shdr.sh_type as u32 == elf::section_header::SHT_SYMTAB {
                 let size = shdr.sh_entsize;
                 let count = if size == 0 { 0 } else { shdr.sh_size / size };
@@ -230,6 +300,7 @@ impl<'data> ElfObject<'data> {
         obj.dynrels = elf::RelocSection::default();
         obj.pltrelocs = elf::RelocSection::default();
         obj.dynstrtab = Strtab::default();
+        let mut android_relocs = Vec::new();
         let dynamic =
             return_partial_on_err!(|| elf::Dynamic::parse(data, &obj.program_headers, ctx));
         if let Some(ref dynamic) = dynamic {
@@ -248,54 +319,77 @@ impl<'data> ElfObject<'data> {
             if dyn_info.needed_count > 0 {
                 obj.libraries = dynamic.get_libraries(&obj.dynstrtab);
             }
-            // parse the dynamic relocations
-            obj.dynrelas = return_partial_on_err!(|| elf::RelocSection::parse(
-                data,
-                dyn_info.rela,
-                dyn_info.relasz,
-                true,
-                ctx
-            ));
-            obj.dynrels = return_partial_on_err!(|| elf::RelocSection::parse(
-                data,
-                dyn_info.rel,
-                dyn_info.relsz,
-                false,
-                ctx
-            ));
-            let is_rela = dyn_info.pltrel as u64 == elf::dynamic::DT_RELA;
-            obj.pltrelocs = return_partial_on_err!(|| elf::RelocSection::parse(
-                data,
-                dyn_info.jmprel,
-                dyn_info.pltrelsz,
-                is_rela,
-                ctx
-            ));
+            if options.symbols {
+                // parse the dynamic relocations
+                obj.dynrelas = return_partial_on_err!(|| elf::RelocSection::parse(
+                    data,
+                    dyn_info.rela,
+                    dyn_info.relasz,
+                    true,
+                    ctx
+                ));
+                obj.dynrels = return_partial_on_err!(|| elf::RelocSection::parse(
+                    data,
+                    dyn_info.rel,
+                    dyn_info.relsz,
+                    false,
+                    ctx
+                ));
+                let is_rela = dyn_info.pltrel as u64 == elf::dynamic::DT_RELA;
+                obj.pltrelocs = return_partial_on_err!(|| elf::RelocSection::parse(
+                    data,
+                    dyn_info.jmprel,
+                    dyn_info.pltrelsz,
+                    is_rela,
+                    ctx
+                ));
+                android_relocs = return_partial_on_err!(|| parse_android_relocs(
+                    data,
+                    &obj.program_headers,
+                    dynamic,
+                    ctx
+                ));
+
+                let mut num_syms = if let Some(gnu_hash) = dyn_info.gnu_hash {
+                    return_partial_on_err!(|| ElfObject::gnu_hash_len(data, gnu_hash as usize, ctx))
+                } else if let Some(hash) = dyn_info.hash {
+                    return_partial_on_err!(|| ElfObject::hash_len(
+                        data,
+                        hash as usize,
+                        header.e_machine,
+                        ctx
+                    ))
+                } else {
+                    0
+                };
+                let max_reloc_sym = obj
+                    .dynrelas
+                    .iter()
+                    .chain(obj.dynrels.iter())
+                    .chain(obj.pltrelocs.iter())
+                    .chain(android_relocs.iter().cloned())
+                    .fold(0, |num, reloc| cmp::max(num, reloc.r_sym));
+                if max_reloc_sym != 0 {
+                    num_syms = cmp::max(num_syms, max_reloc_sym + 1);
+                }
 
-            let mut num_syms = if let Some(gnu_hash) = dyn_info.gnu_hash {
-                return_partial_on_err!(|| ElfObject::gnu_hash_len(data, gnu_hash as usize, ctx))
-            } else if let Some(hash) = dyn_info.hash {
-                return_partial_on_err!(|| ElfObject::hash_len(
+                obj.dynsyms = return_partial_on_err!(|| elf::Symtab::parse(
                     data,
-                    hash as usize,
-                    header.e_machine,
+                    dyn_info.symtab,
+                    num_syms,
                     ctx
-                ))
-            } else {
-                0
-            };
-            let max_reloc_sym = obj
-                .dynrelas
-                .iter()
-                .chain(obj.dynrels.iter())
-                .chain(obj.pltrelocs.iter())
-                .fold(0, |num, reloc| cmp::max(num, reloc.r_sym));
-            if max_reloc_sym != 0 {
-                num_syms = cmp::max(num_syms, max_reloc_sym + 1);
+                ));
             }
+        }
 
-            obj.dynsyms =
-                return_partial_on_err!(|| elf::Symtab::parse(data, dyn_info.symtab, num_syms, ctx));
+        if !options.symbols {
+            return Ok(ElfObject {
+                elf: obj,
+                data,
+                options,
+                is_malformed: false,
+                android_relocs: Vec::new(),
+            });
         }
 
         obj.shdr_relocs = vec![];
@@ -333,7 +427,9 @@ impl<'data> ElfObject<'data> {
         Ok(ElfObject {
             elf: obj,
             data,
+            options,
             is_malformed: false,
+            android_relocs,
         })
     }
 
@@ -429,6 +525,13 @@ impl<'data> ElfObject<'data> {
                     Arch::Mips
                 }
             }
+            goblin::elf::header::EM_RISCV => {
+                if self.elf.is_64 {
+                    Arch::Riscv64
+                } else {
+                    Arch::Riscv32
+                }
+            }
             _ => Arch::Unknown,
         }
     }
@@ -488,6 +591,26 @@ impl<'data> ElfObject<'data> {
         0
     }
 
+    /// The link-time virtual address of the start of the image.
+    ///
+    /// This is the address at which the first byte of the file would be mapped, computed from the
+    /// first `PT_LOAD` segment. At runtime, an image is mapped at its runtime base address, and
+    /// the dynamic loader shifts all addresses by the difference between that base and this bias.
+    /// Use [`debug_session_at`](Self::debug_session_at) to resolve runtime addresses directly.
+    ///
+    /// The load bias is zero for regular shared libraries and PIE executables. It is non-zero for
+    /// non-PIC executables (`ET_EXEC`) and for shared objects that have been prelinked to a fixed
+    /// address.
+    pub fn load_bias(&self) -> u64 {
+        for phdr in &self.elf.program_headers {
+            if phdr.p_type == elf::program_header::PT_LOAD {
+                return phdr.p_vaddr.wrapping_sub(phdr.p_offset);
+            }
+        }
+
+        0
+    }
+
     /// Determines whether this object exposes a public symbol table.
     pub fn has_symbols(&self) -> bool {
         !self.elf.syms.is_empty() || !self.elf.dynsyms.is_empty()
@@ -506,13 +629,327 @@ impl<'data> ElfObject<'data> {
     }
 
     /// Returns an ordered map of symbols in the symbol table.
+    ///
+    /// This includes synthesized symbols for stubs in the procedure linkage table, see
+    /// [`plt_symbols`](Self::plt_symbols).
     pub fn symbol_map(&self) -> SymbolMap<'data> {
-        self.symbols().collect()
+        let mut map: SymbolMap<'data> = self.symbols().chain(self.plt_symbols()).collect();
+        map.set_sections(self.code_sections());
+        map
+    }
+
+    /// Returns the address ranges of executable sections relative to the load address.
+    fn code_sections(&self) -> Vec<Range<u64>> {
+        let load_addr = self.load_address();
+
+        self.elf
+            .section_headers
+            .iter()
+            .filter(|header| header.is_executable() && header.sh_addr >= load_addr)
+            .map(|header| {
+                let start = header.sh_addr - load_addr;
+                start..start.saturating_add(header.sh_size)
+            })
+            .collect()
+    }
+
+    /// Returns synthesized symbols for the stubs in the procedure linkage table (PLT).
+    ///
+    /// Calls to functions in other modules go through stubs in the PLT, which are not listed in the
+    /// symbol table. Each stub is named after its target with an `@plt` suffix, such as
+    /// `memcpy@plt`. Stubs of IFUNCs without a symbol are named after the resolver, such as
+    /// `*ABS*+0x1234@plt`.
+    ///
+    /// This supports the standard PLT layouts of x86, x86-64 and AArch64, including `.plt.sec`
+    /// emitted for Intel CET. For other architectures or unexpected layouts, the list is empty.
+    pub fn plt_symbols(&self) -> Vec<Symbol<'data>> {
+        let (header_size, entry_size) = match self.elf.header.e_machine {
+            elf::header::EM_386 | elf::header::EM_X86_64 => (16, 16),
+            elf::header::EM_AARCH64 => (32, 16),
+            _ => return Vec::new(),
+        };
+
+        // With Intel CET, calls go through the stubs in `.plt.sec`, which has no header. The lazy
+        // binding code remains in `.plt`.
+        let (section, header_size) = match self.section_header(".plt.sec") {
+            Some(section) => (section, 0),
+            None => match self.section_header(".plt") {
+                Some(section) => (section, header_size),
+                None => return Vec::new(),
+            },
+        };
+
+        // The stubs are laid out in the order of their relocations in `.rela.plt`. If the section
+        // cannot hold a stub for every relocation, the layout is not understood.
+        let count = self.elf.pltrelocs.len() as u64;
+        let capacity = section.sh_size.saturating_sub(header_size) / entry_size;
+        if count == 0 || count > capacity {
+            return Vec::new();
+        }
+
+        let load_addr = self.load_address();
+        let mut symbols = Vec::with_capacity(count as usize);
+
+        for (index, reloc) in self.elf.pltrelocs.iter().enumerate() {
+            let address = section.sh_addr + header_size + index as u64 * entry_size;
+            if address < load_addr {
+                continue;
+            }
+
+            let name = if reloc.r_sym != 0 {
+                let symbol = self.elf.dynsyms.get(reloc.r_sym);
+                match symbol.and_then(|symbol| self.elf.dynstrtab.get_at(symbol.st_name)) {
+                    Some(name) if !name.is_empty() => format!("{}@plt", name),
+                    _ => continue,
+                }
+            } else {
+                match reloc.r_addend {
+                    Some(addend) => format!("*ABS*+{:#x}@plt", addend),
+                    None => "*ABS*@plt".to_owned(),
+                }
+            };
+
+            symbols.push(Symbol {
+                name: Some(Cow::Owned(name)),
+                address: address - load_addr,
+                size: entry_size,
+            });
+        }
+
+        symbols
+    }
+
+    /// Returns an interval map of all function symbols, including aliases and overlapping symbols.
+    ///
+    /// On ARM, the Thumb bit is stripped from symbol addresses and recorded in the symbol flags.
+    pub fn symbol_interval_map(&self) -> SymbolIntervalMap<'data> {
+        let load_addr = self.load_address();
+        let is_arm = self.elf.header.e_machine == elf::header::EM_ARM;
+        let sections = &self.elf.section_headers;
+
+        let symbols = (self.elf.syms.iter().map(|sym| (sym, &self.elf.strtab)))
+            .chain(
+                self.elf
+                    .dynsyms
+                    .iter()
+                    .map(|sym| (sym, &self.elf.dynstrtab)),
+            )
+            .filter(|(symbol, _)| is_function_symbol(symbol, load_addr, sections))
+            .map(|(symbol, strtab)| {
+                let thumb = is_arm && symbol.st_value & 1 != 0;
+                let address = if thumb {
+                    symbol.st_value & !1
+                } else {
+                    symbol.st_value
+                };
+
+                let symbol_flags = SymbolFlags {
+                    thumb,
+                    local: symbol.st_bind() == elf::sym::STB_LOCAL,
+                };
+
+                let symbol = Symbol {
+                    name: strtab.get_at(symbol.st_name).map(Cow::Borrowed),
+                    address: address.saturating_sub(load_addr),
+                    size: symbol.st_size,
+                };
+
+                (symbol, symbol_flags)
+            })
+            .chain(
+                self.plt_symbols()
+                    .into_iter()
+                    .map(|symbol| (symbol, SymbolFlags::default())),
+            );
+
+        SymbolIntervalMap::from_symbols(symbols)
+    }
+
+    /// Returns all symbols exported from the dynamic symbol table.
+    ///
+    /// These are defined global and weak symbols with default or protected visibility.
+    pub fn exports(&self) -> Vec<Export<'data>> {
+        let load_addr = self.load_address();
+        let mut exports = Vec::new();
+
+        for symbol in self.elf.dynsyms.iter() {
+            if symbol.st_shndx == SHN_UNDEF || !is_dynamic_symbol(&symbol) {
+                continue;
+            }
+
+            let visibility = symbol.st_visibility();
+            if visibility != elf::sym::STV_DEFAULT && visibility != elf::sym::STV_PROTECTED {
+                continue;
+            }
+
+            if symbol.st_value < load_addr {
+                continue;
+            }
+
+            let name = match self.elf.dynstrtab.get_at(symbol.st_name) {
+                Some(name) if !name.is_empty() => name,
+                _ => continue,
+            };
+
+            exports.push(Export {
+                name: Cow::Borrowed(name),
+                address: symbol.st_value - load_addr,
+            });
+        }
+
+        exports
+    }
+
+    /// Returns all undefined symbols of the dynamic symbol table.
+    ///
+    /// The address of an import is the relocated GOT or PLT entry, if there is a dynamic relocation
+    /// referring to the symbol.
+    pub fn imports(&self) -> Vec<Import<'data>> {
+        let load_addr = self.load_address();
+
+        let mut slots = BTreeMap::new();
+        let relocs = (self.elf.pltrelocs.iter())
+            .chain(self.elf.dynrelas.iter())
+            .chain(self.elf.dynrels.iter());
+        for reloc in relocs {
+            if reloc.r_sym != 0 && reloc.r_offset >= load_addr {
+                slots
+                    .entry(reloc.r_sym)
+                    .or_insert(reloc.r_offset - load_addr);
+            }
+        }
+
+        let mut imports = Vec::new();
+        for (index, symbol) in self.elf.dynsyms.iter().enumerate() {
+            if symbol.st_shndx != SHN_UNDEF || !is_dynamic_symbol(&symbol) {
+                continue;
+            }
+
+            let name = match self.elf.dynstrtab.get_at(symbol.st_name) {
+                Some(name) if !name.is_empty() => name,
+                _ => continue,
+            };
+
+            imports.push(Import {
+                name: Cow::Borrowed(name),
+                library: None,
+                address: slots.get(&index).copied(),
+            });
+        }
+
+        imports
+    }
+
+    /// Returns the address of the entry point relative to the load address.
+    ///
+    /// Returns `None` if the ELF header does not declare an entry point, which is usually the case
+    /// for shared libraries and relocatable objects.
+    pub fn entry_point(&self) -> Option<u64> {
+        match self.elf.entry {
+            0 => None,
+            entry => entry.checked_sub(self.load_address()),
+        }
+    }
+
+    /// Returns the addresses of all functions in the `.preinit_array` and `.init_array` sections,
+    /// relative to the load address.
+    ///
+    /// The dynamic loader calls these functions in order before the entry point of the executable
+    /// or after loading a shared library.
+    pub fn initializers(&self) -> Vec<u64> {
+        let mut functions = self.function_array("preinit_array");
+        functions.extend(self.function_array("init_array"));
+        functions
+    }
+
+    /// Returns the addresses of all functions in the `.fini_array` section, relative to the load
+    /// address.
+    ///
+    /// The dynamic loader calls these functions in reverse order when the process exits or the
+    /// shared library is unloaded.
+    pub fn terminators(&self) -> Vec<u64> {
+        self.function_array("fini_array")
+    }
+
+    /// Reads the function pointers of an array section like `.init_array`.
+    ///
+    /// In position-independent objects, the pointers are subject to relative relocations. Their
+    /// addend takes precedence over the contents of the section, which some linkers leave zeroed.
+    /// This includes relocations from Android's packed relocation tables. Null pointers and the
+    /// legacy `-1` sentinels are skipped.
+    fn function_array(&self, name: &str) -> Vec<u64> {
+        let section = match self.find_section(name) {
+            Some((false, section)) => section,
+            _ => return Vec::new(),
+        };
+
+        let addends: BTreeMap<_, _> = self
+            .elf
+            .dynrelas
+            .iter()
+            .chain(self.android_relocs.iter().cloned())
+            .filter(|reloc| reloc.r_sym == 0)
+            .filter_map(|reloc| Some((reloc.r_offset, reloc.r_addend?)))
+            .collect();
+
+        let endian = if self.elf.little_endian {
+            scroll::LE
+        } else {
+            scroll::BE
+        };
+
+        let size = if self.elf.is_64 { 8 } else { 4 };
+        let load_addr = self.load_address();
+        let mut functions = Vec::new();
+
+        for (index, chunk) in section.data.chunks_exact(size).enumerate() {
+            let slot = section.address + (index * size) as u64;
+            let pointer = match addends.get(&slot) {
+                Some(&addend) => addend as u64,
+                None if self.elf.is_64 => chunk.pread_with::<u64>(0, endian).unwrap_or(0),
+                None => u64::from(chunk.pread_with::<u32>(0, endian).unwrap_or(0)),
+            };
+
+            let is_sentinel = pointer == u64::MAX || (!self.elf.is_64 && pointer == 0xffff_ffff);
+            if pointer == 0 || is_sentinel {
+                continue;
+            }
+
+            if let Some(address) = pointer.checked_sub(load_addr) {
+                functions.push(address);
+            }
+        }
+
+        functions
     }
 
     /// Determines whether this object contains debug information.
     pub fn has_debug_info(&self) -> bool {
-        self.has_section("debug_info")
+        self.has_section("debug_info") || self.has_go_pclntab()
+    }
+
+    /// Determines whether this object contains the function and line tables of the Go runtime.
+    ///
+    /// Go binaries retain these tables in the `.gopclntab` section even if DWARF has been stripped,
+    /// for instance with `-ldflags="-w"`. Use [`go_session`](Self::go_session) to read functions
+    /// from them.
+    pub fn has_go_pclntab(&self) -> bool {
+        self.has_section("gopclntab")
+    }
+
+    /// Constructs a debugging session for the function and line tables of the Go runtime.
+    ///
+    /// Returns an error if the object does not contain a valid `.gopclntab` section.
+    pub fn go_session(&self) -> Result<GoDebugSession<'data>, GoError> {
+        let data = match self.section("gopclntab") {
+            Some(DwarfSection {
+                data: Cow::Borrowed(data),
+                ..
+            }) => data,
+            _ => &[],
+        };
+
+        GoDebugSession::parse(data, self.load_address())
     }
 
     /// Constructs a debugging session.
@@ -532,6 +969,35 @@ impl<'data> ElfObject<'data> {
         DwarfDebugSession::parse(self, symbols, self.load_address() as i64, self.kind())
     }
 
+    /// Constructs a debugging session that reports runtime addresses.
+    ///
+    /// `runtime_base` is the address at which the start of this image has been mapped into a
+    /// process, such as the base address of a module in a minidump or the start of its first
+    /// mapping in `/proc/self/maps`. All addresses of functions, line records and symbols in the
+    /// session are translated using the [`load_bias`](Self::load_bias), so that they can be
+    /// compared to instruction addresses of that process directly.
+    pub fn debug_session_at(
+        &self,
+        runtime_base: u64,
+    ) -> Result<DwarfDebugSession<'data>, DwarfError> {
+        let load_bias = self.load_bias();
+        let shift = runtime_base
+            .wrapping_sub(load_bias)
+            .wrapping_add(self.load_address());
+
+        let symbols = self
+            .symbol_map()
+            .into_iter()
+            .map(|mut symbol| {
+                symbol.address = symbol.address.wrapping_add(shift);
+                symbol
+            })
+            .collect();
+
+        let relocation = load_bias.wrapping_sub(runtime_base) as i64;
+        DwarfDebugSession::parse(self, symbols, relocation, self.kind())
+    }
+
     /// Determines whether this object contains stack unwinding information.
     pub fn has_unwind_info(&self) -> bool {
         self.has_section("eh_frame") || self.has_section("debug_frame")
@@ -579,6 +1045,10 @@ impl<'data> ElfObject<'data> {
             (compression.ch_size, compressed)
         };
 
+        if self.options.exceeds_section_size(size) {
+            return None;
+        }
+
         let mut decompressed = Vec::with_capacity(size as usize);
         Decompress::new(true)
             .decompress_vec(compressed, &mut decompressed, FlushDecompress::Finish)
@@ -587,6 +1057,14 @@ impl<'data> ElfObject<'data> {
         Some(decompressed)
     }
 
+    /// Returns the header of the section with the given name, including the leading dot.
+    fn section_header(&self, name: &str) -> Option<&SectionHeader> {
+        self.elf
+            .section_headers
+            .iter()
+            .find(|header| self.elf.shdr_strtab.get_at(header.sh_name) == Some(name))
+    }
+
     /// Locates and reads a section in an ELF binary.
     fn find_section(&self, name: &str) -> Option<(bool, DwarfSection<'data>)> {
         for header in &self.elf.section_headers {
@@ -646,11 +1124,13 @@ impl<'data> ElfObject<'data> {
     fn find_build_id(&self) -> Option<&'data [u8]> {
         // First, search the note program headers (PT_NOTE) for a NT_GNU_BUILD_ID.
         // We swallow all errors during this process and simply fall back to the
-        // next method below.
-        if let Some(mut notes) = self.elf.iter_note_headers(self.data) {
-            while let Some(Ok(note)) = notes.next() {
-                if note.n_type == elf::note::NT_GNU_BUILD_ID {
-                    return Some(note.desc);
+        // next method below. Scanning note headers can be skipped via `ObjectOptions::notes`.
+        if self.options.notes {
+            if let Some(mut notes) = self.elf.iter_note_headers(self.data) {
+                while let Some(Ok(note)) = notes.next() {
+                    if is_build_id_note(&note) {
+                        return Some(note.desc);
+                    }
                 }
             }
         }
@@ -673,73 +1153,385 @@ impl<'data> ElfObject<'data> {
         None
     }
 
-    /// Converts an ELF object identifier into a `DebugId`.
-    ///
-    /// The identifier data is first truncated or extended to match 16 byte size of
-    /// Uuids. If the data is declared in little endian, the first three Uuid fields
-    /// are flipped to match the big endian expected by the breakpad processor.
+    /// Returns the Android identification note of this object.
     ///
-    /// The `DebugId::appendix` field is always `0` for ELF.
-    fn compute_debug_id(&self, identifier: &[u8]) -> DebugId {
-        // Make sure that we have exactly UUID_SIZE bytes available
-        let mut data = [0; UUID_SIZE];
-        let len = std::cmp::min(identifier.len(), UUID_SIZE);
-        data[0..len].copy_from_slice(&identifier[0..len]);
-
-        if self.elf.little_endian {
-            // The file ELF file targets a little endian architecture. Convert to
-            // network byte order (big endian) to match the Breakpad processor's
-            // expectations. For big endian object files, this is not needed.
-            data[0..4].reverse(); // uuid field 1
-            data[4..6].reverse(); // uuid field 2
-            data[6..8].reverse(); // uuid field 3
+    /// The Android NDK adds a `.note.android.ident` note to all binaries, declaring the minimum API
+    /// level of the target platform. Since NDK r14, it also contains the NDK version and build
+    /// number. Returns `None` if the object was not built with the NDK.
+    pub fn android_ident(&self) -> Option<ElfAndroidIdent<'data>> {
+        if self.options.notes {
+            if let Some(mut notes) = self.elf.iter_note_headers(self.data) {
+                while let Some(Ok(note)) = notes.next() {
+                    if let Some(ident) = self.parse_android_ident(&note) {
+                        return Some(ident);
+                    }
+                }
+            }
         }
 
-        Uuid::from_slice(&data)
-            .map(DebugId::from_uuid)
-            .unwrap_or_default()
-    }
-}
+        if let Some(mut notes) = self
+            .elf
+            .iter_note_sections(self.data, Some(".note.android.ident"))
+        {
+            while let Some(Ok(note)) = notes.next() {
+                if let Some(ident) = self.parse_android_ident(&note) {
+                    return Some(ident);
+                }
+            }
+        }
 
-impl fmt::Debug for ElfObject<'_> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("ElfObject")
-            .field("code_id", &self.code_id())
-            .field("debug_id", &self.debug_id())
-            .field("arch", &self.arch())
-            .field("kind", &self.kind())
-            .field("load_address", &format_args!("{:#x}", self.load_address()))
-            .field("has_symbols", &self.has_symbols())
-            .field("has_debug_info", &self.has_debug_info())
-            .field("has_unwind_info", &self.has_unwind_info())
-            .field("is_malformed", &self.is_malformed())
-            .finish()
+        None
     }
-}
 
-impl<'slf, 'data: 'slf> AsSelf<'slf> for ElfObject<'data> {
-    type Ref = ElfObject<'slf>;
+    /// Parses the contents of an Android identification note.
+    fn parse_android_ident(&self, note: &elf::note::Note<'data>) -> Option<ElfAndroidIdent<'data>> {
+        if note.name != "Android" || note.n_type != NT_ANDROID_TYPE_IDENT {
+            return None;
+        }
 
-    fn as_self(&'slf self) -> &Self::Ref {
-        self
-    }
-}
+        let endian = if self.elf.little_endian {
+            scroll::LE
+        } else {
+            scroll::BE
+        };
 
-impl<'data> Parse<'data> for ElfObject<'data> {
-    type Error = ElfError;
+        let api_level = note.desc.pread_with::<u32>(0, endian).ok()?;
+        let field = |index: usize| {
+            let start = 4 + index * ANDROID_NDK_FIELD_SIZE;
+            let bytes = note.desc.get(start..start + ANDROID_NDK_FIELD_SIZE)?;
+            let bytes = bytes.split(|&b| b == 0).next()?;
+            std::str::from_utf8(bytes).ok().filter(|s| !s.is_empty())
+        };
 
-    fn test(data: &[u8]) -> bool {
-        Self::test(data)
+        Some(ElfAndroidIdent {
+            api_level,
+            ndk_version: field(0),
+            ndk_build_number: field(1),
+        })
     }
 
-    fn parse(data: &'data [u8]) -> Result<Self, ElfError> {
-        Self::parse(data)
-    }
-}
+    /// Returns a copy of this object without debug information, similar to `strip --strip-debug`.
+    ///
+    /// This removes all DWARF and stabs sections, as well as relocation sections applying to them.
+    /// Loaded contents remain at their original offsets, which retains the build id and code of the
+    /// object. The symbol table is kept.
+    ///
+    /// Symbols in removed sections are converted to absolute symbols, since removing them would
+    /// change the indexes of all subsequent symbols.
+    pub fn strip_debug(&self) -> Result<Vec<u8>, ElfError> {
+        let ctx = self.container_ctx();
+        let sections = &self.elf.section_headers;
+        self.check_section_numbering()?;
+
+        let mut keep = sections
+            .iter()
+            .enumerate()
+            .map(|(index, section)| index == 0 || !self.is_debug_section(section))
+            .collect::<Vec<_>>();
+
+        // Relocations of removed sections cannot be applied anymore.
+        for (index, section) in sections.iter().enumerate() {
+            let is_reloc = section.sh_type == elf::section_header::SHT_REL
+                || section.sh_type == elf::section_header::SHT_RELA;
+            if is_reloc && !keep.get(section.sh_info as usize).copied().unwrap_or(true) {
+                keep[index] = false;
+            }
+        }
 
-impl<'data: 'object, 'object> ObjectLike<'data, 'object> for ElfObject<'data> {
-    type Error = DwarfError;
-    type Session = DwarfDebugSession<'data>;
+        let mut indexes = Vec::with_capacity(sections.len());
+        let mut next_index = 0;
+        for &kept in &keep {
+            indexes.push(if kept { Some(next_index) } else { None });
+            next_index += usize::from(kept);
+        }
+        let remap = |index: usize| indexes.get(index).copied().flatten();
+
+        // Keep the file header, program headers and all loaded contents in place. Everything else
+        // is moved behind them, which drops the contents of removed sections.
+        let mut preserved = elf::Header::size(ctx);
+        let phdr_size = elf::ProgramHeader::size(ctx);
+        let phdrs_end =
+            self.elf.header.e_phoff as usize + self.elf.program_headers.len() * phdr_size;
+        preserved = preserved.max(phdrs_end);
+        for phdr in &self.elf.program_headers {
+            preserved = preserved.max((phdr.p_offset + phdr.p_filesz) as usize);
+        }
+        for (section, _) in sections.iter().zip(&keep).filter(|(_, kept)| **kept) {
+            if section.is_alloc() && section.sh_type != elf::section_header::SHT_NOBITS {
+                preserved = preserved.max((section.sh_offset + section.sh_size) as usize);
+            }
+        }
+
+        let mut out = self
+            .data
+            .get(..preserved)
+            .ok_or_else(|| ElfError::new("loaded contents out of bounds"))?
+            .to_vec();
+
+        let mut headers = Vec::with_capacity(next_index);
+        for (section, _) in sections.iter().zip(&keep).filter(|(_, kept)| **kept) {
+            let mut header = section.clone();
+            let end = section.sh_offset + section.sh_size;
+            if section.sh_type != elf::section_header::SHT_NOBITS && end > preserved as u64 {
+                let data = self.section_data(section)?;
+                header.sh_offset = append_aligned(&mut out, data, section.sh_addralign) as u64;
+            }
+
+            header.sh_link = remap(section.sh_link as usize).unwrap_or(0) as u32;
+            let is_reloc = section.sh_type == elf::section_header::SHT_REL
+                || section.sh_type == elf::section_header::SHT_RELA;
+            if is_reloc || section.sh_flags & u64::from(elf::section_header::SHF_INFO_LINK) != 0 {
+                header.sh_info = remap(section.sh_info as usize).unwrap_or(0) as u32;
+            }
+
+            headers.push(header);
+        }
+
+        for header in &headers {
+            let is_symtab = header.sh_type == elf::section_header::SHT_SYMTAB
+                || header.sh_type == elf::section_header::SHT_DYNSYM;
+            if is_symtab {
+                remap_symbol_sections(&mut out, header, ctx, remap)?;
+            }
+        }
+
+        let mut file_header = self.elf.header;
+        file_header.e_shnum = headers.len() as u16;
+        file_header.e_shstrndx = remap(self.elf.header.e_shstrndx.into()).unwrap_or(0) as u16;
+        self.write_section_headers(out, file_header, &headers)
+    }
+
+    /// Returns a copy of this object that only contains debug information, similar to
+    /// `objcopy --only-keep-debug`.
+    ///
+    /// All loaded sections are converted to `SHT_NOBITS` sections without contents, except for
+    /// notes which retain the build id. Section and program headers keep their addresses, so that
+    /// the companion can be used in place of the original object to symbolicate addresses. The
+    /// remaining sections, including the symbol table and all debug sections, are copied.
+    pub fn debug_companion(&self) -> Result<Vec<u8>, ElfError> {
+        let ctx = self.container_ctx();
+        self.check_section_numbering()?;
+
+        let mut out = vec![0; elf::Header::size(ctx)];
+        let phdr_size = elf::ProgramHeader::size(ctx);
+        let phoff = append_aligned(
+            &mut out,
+            &vec![0; self.elf.program_headers.len() * phdr_size],
+            8,
+        );
+
+        // Copy note segments entirely, so that notes can be read through program headers.
+        let mut note_segments = Vec::new();
+        let mut phdrs = self.elf.program_headers.clone();
+        for phdr in &mut phdrs {
+            match phdr.p_type {
+                elf::program_header::PT_NOTE => {
+                    let range = phdr.file_range();
+                    let data = self
+                        .data
+                        .get(range.clone())
+                        .ok_or_else(|| ElfError::new("note segment out of bounds"))?;
+                    let offset = append_aligned(&mut out, data, phdr.p_align);
+                    note_segments.push((range, offset));
+                    phdr.p_offset = offset as u64;
+                }
+                elf::program_header::PT_PHDR => phdr.p_offset = phoff as u64,
+                _ => phdr.p_filesz = 0,
+            }
+        }
+
+        for (index, phdr) in phdrs.into_iter().enumerate() {
+            out.pwrite_with(phdr, phoff + index * phdr_size, ctx)
+                .map_err(ElfError::new)?;
+        }
+
+        let mut headers = Vec::with_capacity(self.elf.section_headers.len());
+        for section in &self.elf.section_headers {
+            let mut header = section.clone();
+
+            if section.sh_type == elf::section_header::SHT_NOBITS {
+                // Nothing to copy.
+            } else if section.sh_type == elf::section_header::SHT_NOTE {
+                let start = section.sh_offset as usize;
+                let segment = note_segments
+                    .iter()
+                    .find(|(range, _)| range.contains(&start));
+
+                header.sh_offset = match segment {
+                    Some((range, offset)) => (offset + start - range.start) as u64,
+                    None => {
+                        let data = self.section_data(section)?;
+                        append_aligned(&mut out, data, section.sh_addralign) as u64
+                    }
+                };
+            } else if section.is_alloc() {
+                header.sh_type = elf::section_header::SHT_NOBITS;
+                header.sh_offset = out.len() as u64;
+            } else if section.sh_size > 0 {
+                let data = self.section_data(section)?;
+                header.sh_offset = append_aligned(&mut out, data, section.sh_addralign) as u64;
+            }
+
+            headers.push(header);
+        }
+
+        let mut file_header = self.elf.header;
+        file_header.e_phoff = if self.elf.program_headers.is_empty() {
+            0
+        } else {
+            phoff as u64
+        };
+        self.write_section_headers(out, file_header, &headers)
+    }
+
+    /// Returns the container context for reading and writing structures of this object.
+    fn container_ctx(&self) -> Ctx {
+        let container = if self.elf.is_64 {
+            Container::Big
+        } else {
+            Container::Little
+        };
+
+        let endian = if self.elf.little_endian {
+            scroll::LE
+        } else {
+            scroll::BE
+        };
+
+        Ctx::new(container, endian)
+    }
+
+    /// Ensures that sections can be renumbered without extended section indexes.
+    fn check_section_numbering(&self) -> Result<(), ElfError> {
+        let header = &self.elf.header;
+        let extended = header.e_shstrndx == elf::section_header::SHN_XINDEX as u16
+            || (header.e_shnum == 0 && !self.elf.section_headers.is_empty())
+            || self.elf.section_headers.len() >= elf::section_header::SHN_LORESERVE as usize;
+
+        if extended {
+            Err(ElfError::new("extended section numbering is not supported"))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Checks whether a section contains debug information that is not loaded at runtime.
+    fn is_debug_section(&self, section: &SectionHeader) -> bool {
+        if section.is_alloc() {
+            return false;
+        }
+
+        let name = match self.elf.shdr_strtab.get_at(section.sh_name) {
+            Some(name) => name,
+            None => return false,
+        };
+
+        name.starts_with(".debug")
+            || name.starts_with(".zdebug")
+            || name.starts_with(".stab")
+            || name == ".gdb_index"
+            || name == ".line"
+    }
+
+    /// Returns the raw contents of a section.
+    fn section_data(&self, section: &SectionHeader) -> Result<&'data [u8], ElfError> {
+        let start = section.sh_offset as usize;
+        let end = start.saturating_add(section.sh_size as usize);
+        self.data
+            .get(start..end)
+            .ok_or_else(|| ElfError::new("section contents out of bounds"))
+    }
+
+    /// Appends section headers to the output and writes the file header.
+    fn write_section_headers(
+        &self,
+        mut out: Vec<u8>,
+        mut file_header: elf::Header,
+        headers: &[SectionHeader],
+    ) -> Result<Vec<u8>, ElfError> {
+        let ctx = self.container_ctx();
+        let shdr_size = SectionHeader::size(ctx);
+
+        let shoff = append_aligned(&mut out, &vec![0; headers.len() * shdr_size], 8);
+        for (index, header) in headers.iter().enumerate() {
+            out.pwrite_with(header.clone(), shoff + index * shdr_size, ctx)
+                .map_err(ElfError::new)?;
+        }
+
+        file_header.e_shoff = if headers.is_empty() { 0 } else { shoff as u64 };
+        out.pwrite_with(file_header, 0, ctx.le)
+            .map_err(ElfError::new)?;
+
+        Ok(out)
+    }
+
+    /// Converts an ELF object identifier into a `DebugId`.
+    ///
+    /// The identifier data is first truncated or extended to match 16 byte size of
+    /// Uuids. If the data is declared in little endian, the first three Uuid fields
+    /// are flipped to match the big endian expected by the breakpad processor.
+    ///
+    /// The `DebugId::appendix` field is always `0` for ELF.
+    fn compute_debug_id(&self, identifier: &[u8]) -> DebugId {
+        // Make sure that we have exactly UUID_SIZE bytes available
+        let mut data = [0; UUID_SIZE];
+        let len = std::cmp::min(identifier.len(), UUID_SIZE);
+        data[0..len].copy_from_slice(&identifier[0..len]);
+
+        if self.elf.little_endian {
+            // The file ELF file targets a little endian architecture. Convert to
+            // network byte order (big endian) to match the Breakpad processor's
+            // expectations. For big endian object files, this is not needed.
+            data[0..4].reverse(); // uuid field 1
+            data[4..6].reverse(); // uuid field 2
+            data[6..8].reverse(); // uuid field 3
+        }
+
+        Uuid::from_slice(&data)
+            .map(DebugId::from_uuid)
+            .unwrap_or_default()
+    }
+}
+
+impl fmt::Debug for ElfObject<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ElfObject")
+            .field("code_id", &self.code_id())
+            .field("debug_id", &self.debug_id())
+            .field("arch", &self.arch())
+            .field("kind", &self.kind())
+            .field("load_address", &format_args!("{:#x}", self.load_address()))
+            .field("has_symbols", &self.has_symbols())
+            .field("has_debug_info", &self.has_debug_info())
+            .field("has_unwind_info", &self.has_unwind_info())
+            .field("is_malformed", &self.is_malformed())
+            .finish()
+    }
+}
+
+impl<'slf, 'data: 'slf> AsSelf<'slf> for ElfObject<'data> {
+    type Ref = ElfObject<'slf>;
+
+    fn as_self(&'slf self) -> &Self::Ref {
+        self
+    }
+}
+
+impl<'data> Parse<'data> for ElfObject<'data> {
+    type Error = ElfError;
+
+    fn test(data: &[u8]) -> bool {
+        Self::test(data)
+    }
+
+    fn parse(data: &'data [u8]) -> Result<Self, ElfError> {
+        Self::parse(data)
+    }
+}
+
+impl<'data: 'object, 'object> ObjectLike<'data, 'object> for ElfObject<'data> {
+    type Error = DwarfError;
+    type Session = DwarfDebugSession<'data>;
     type SymbolIterator = ElfSymbolIterator<'data, 'object>;
 
     fn file_format(&self) -> FileFormat {
@@ -815,6 +1607,9 @@ impl<'data> Dwarf<'data> for ElfObject<'data> {
 
     fn section(&self, name: &str) -> Option<DwarfSection<'data>> {
         let (compressed, mut section) = self.find_section(name)?;
+        if self.options.exceeds_section_size(section.data.len() as u64) {
+            return None;
+        }
 
         if compressed {
             let decompressed = self.decompress_section(&section.data)?;
@@ -848,24 +1643,7 @@ impl<'data, 'object> Iterator for ElfSymbolIterator<'data, 'object> {
             sections: &[SectionHeader],
         ) -> Option<Symbol<'data>> {
             for symbol in symbols {
-                // Only check for function symbols.
-                if symbol.st_type() != elf::sym::STT_FUNC {
-                    continue;
-                }
-
-                // Sanity check of the symbol address. Since we only intend to iterate over function
-                // symbols, they need to be mapped after the image's load address.
-                if symbol.st_value < load_addr {
-                    continue;
-                }
-
-                let section = match symbol.st_shndx {
-                    self::SHN_UNDEF => None,
-                    index => sections.get(index),
-                };
-
-                // We are only interested in symbols pointing into sections with executable flag.
-                if !section.map_or(false, |header| header.is_executable()) {
+                if !is_function_symbol(&symbol, load_addr, sections) {
                     continue;
                 }
 
@@ -898,6 +1676,262 @@ impl<'data, 'object> Iterator for ElfSymbolIterator<'data, 'object> {
     }
 }
 
+/// Checks whether a dynamic symbol participates in dynamic linking.
+fn is_dynamic_symbol(symbol: &elf::Sym) -> bool {
+    let binding = symbol.st_bind();
+    let is_global = binding == elf::sym::STB_GLOBAL
+        || binding == elf::sym::STB_WEAK
+        || binding == elf::sym::STB_GNU_UNIQUE;
+
+    let ty = symbol.st_type();
+    is_global && ty != elf::sym::STT_SECTION && ty != elf::sym::STT_FILE
+}
+
+/// Checks whether the symbol is a function symbol pointing into an executable section.
+fn is_function_symbol(symbol: &elf::Sym, load_addr: u64, sections: &[SectionHeader]) -> bool {
+    // Only check for function symbols. The symbol of an IFUNC points to its resolver function.
+    let ty = symbol.st_type();
+    if ty != elf::sym::STT_FUNC && ty != elf::sym::STT_GNU_IFUNC {
+        return false;
+    }
+
+    // Sanity check of the symbol address. Since we only intend to iterate over function
+    // symbols, they need to be mapped after the image's load address.
+    if symbol.st_value < load_addr {
+        return false;
+    }
+
+    let section = match symbol.st_shndx {
+        self::SHN_UNDEF => None,
+        index => sections.get(index),
+    };
+
+    // We are only interested in symbols pointing into sections with executable flag.
+    section.map_or(false, |header| header.is_executable())
+}
+
+/// Checks whether a note contains a GNU build ID.
+///
+/// Notes of other vendors reuse the type of `NT_GNU_BUILD_ID`, for instance `NT_ANDROID_TYPE_KUSER`
+/// in Android binaries, so the name of the note must be checked as well.
+fn is_build_id_note(note: &elf::note::Note<'_>) -> bool {
+    note.n_type == elf::note::NT_GNU_BUILD_ID && note.name == "GNU"
+}
+
+/// Converts a virtual address into an offset in the file using the loadable segments.
+fn vm_to_offset(program_headers: &[elf::ProgramHeader], address: u64) -> Option<usize> {
+    let phdr = program_headers.iter().find(|phdr| {
+        phdr.p_type == elf::program_header::PT_LOAD
+            && phdr.p_vaddr <= address
+            && matches!(phdr.p_vaddr.checked_add(phdr.p_filesz), Some(end) if address < end)
+    })?;
+
+    let offset = address
+        .checked_sub(phdr.p_vaddr)?
+        .checked_add(phdr.p_offset)?;
+    offset.try_into().ok()
+}
+
+/// Reads Android's packed relocation tables declared in the dynamic section.
+///
+/// The Android linker supports compressing relocations with `-Wl,--pack-dyn-relocs=android`,
+/// which places them in `SHT_ANDROID_REL` or `SHT_ANDROID_RELA` sections instead of the regular
+/// `.rel.dyn` or `.rela.dyn`.
+fn parse_android_relocs(
+    data: &[u8],
+    program_headers: &[elf::ProgramHeader],
+    dynamic: &elf::Dynamic,
+    ctx: Ctx,
+) -> Result<Vec<elf::Reloc>, ElfError> {
+    let find_tag = |tag| {
+        dynamic
+            .dyns
+            .iter()
+            .find(|entry| entry.d_tag == tag)
+            .map(|entry| entry.d_val)
+    };
+
+    let tables = [
+        (DT_ANDROID_RELA, DT_ANDROID_RELASZ),
+        (DT_ANDROID_REL, DT_ANDROID_RELSZ),
+    ];
+
+    let mut relocs = Vec::new();
+    for (address_tag, size_tag) in tables {
+        let (address, size) = match (find_tag(address_tag), find_tag(size_tag)) {
+            (Some(address), Some(size)) => (address, size as usize),
+            _ => continue,
+        };
+
+        let table = vm_to_offset(program_headers, address)
+            .and_then(|offset| data.get(offset..offset.checked_add(size)?))
+            .ok_or_else(|| ElfError::new("packed relocations out of bounds"))?;
+
+        // Every relocation patches a pointer within the image, which bounds their number.
+        let max_count = data.len();
+        relocs.extend(decode_android_relocs(table, ctx.is_big(), max_count)?);
+    }
+
+    Ok(relocs)
+}
+
+/// Decodes an `APS2` packed relocation table.
+///
+/// The table is a sequence of SLEB128 numbers, starting with the number of relocations and the
+/// initial offset. Relocations are grouped, so that offset deltas, types and addends shared by all
+/// relocations in a group are only stored once. See `packed_reloc_iterator.h` in bionic.
+fn decode_android_relocs(
+    table: &[u8],
+    is_64: bool,
+    max_count: usize,
+) -> Result<Vec<elf::Reloc>, ElfError> {
+    let mut data = table
+        .strip_prefix(ANDROID_PACKED_MAGIC)
+        .ok_or_else(|| ElfError::new("invalid packed relocation magic"))?;
+
+    let mut read =
+        || read_sleb128(&mut data).ok_or_else(|| ElfError::new("truncated packed relocations"));
+
+    let count = read()?;
+    if count < 0 || count as u64 > max_count as u64 {
+        return Err(ElfError::new("invalid packed relocation count"));
+    }
+
+    // The count is only bounded by the size of the file. Relocations take at least one byte unless
+    // they are grouped, so do not reserve more than the size of the table for them upfront.
+    let mut remaining = count as u64;
+    let mut relocs = Vec::with_capacity(remaining.min(table.len() as u64) as usize);
+    let mut offset = read()?;
+    let mut info = 0i64;
+    let mut addend = 0i64;
+
+    while remaining > 0 {
+        let group_size = read()?;
+        if group_size <= 0 || group_size as u64 > remaining {
+            return Err(ElfError::new("invalid packed relocation group"));
+        }
+
+        let flags = read()?;
+        let has_flag = |flag| flags & flag != 0;
+        let grouped_by_addend = has_flag(RELOCATION_GROUPED_BY_ADDEND_FLAG);
+        let has_addend = has_flag(RELOCATION_GROUP_HAS_ADDEND_FLAG);
+
+        let offset_delta = if has_flag(RELOCATION_GROUPED_BY_OFFSET_DELTA_FLAG) {
+            Some(read()?)
+        } else {
+            None
+        };
+        let grouped_by_info = has_flag(RELOCATION_GROUPED_BY_INFO_FLAG);
+        if grouped_by_info {
+            info = read()?;
+        }
+        if has_addend && grouped_by_addend {
+            addend = addend.wrapping_add(read()?);
+        } else if !has_addend {
+            addend = 0;
+        }
+
+        for _ in 0..group_size {
+            offset = offset.wrapping_add(match offset_delta {
+                Some(delta) => delta,
+                None => read()?,
+            });
+            if !grouped_by_info {
+                info = read()?;
+            }
+            if has_addend && !grouped_by_addend {
+                addend = addend.wrapping_add(read()?);
+            }
+
+            let info = info as u64;
+            let (r_sym, r_type) = if is_64 {
+                ((info >> 32) as usize, info as u32)
+            } else {
+                ((info as u32 >> 8) as usize, info as u32 & 0xff)
+            };
+
+            relocs.push(elf::Reloc {
+                r_offset: offset as u64,
+                r_addend: if has_addend { Some(addend) } else { None },
+                r_sym,
+                r_type,
+            });
+        }
+
+        remaining -= group_size as u64;
+    }
+
+    Ok(relocs)
+}
+
+/// Reads a signed LEB128 number and advances the data past it.
+fn read_sleb128(data: &mut &[u8]) -> Option<i64> {
+    let mut result = 0i64;
+    let mut shift = 0;
+
+    loop {
+        let (&byte, rest) = data.split_first()?;
+        *data = rest;
+
+        if shift < 64 {
+            result |= i64::from(byte & 0x7f) << shift;
+        }
+        shift += 7;
+
+        if byte & 0x80 == 0 {
+            if shift < 64 && byte & 0x40 != 0 {
+                result |= -1 << shift;
+            }
+            return Some(result);
+        }
+    }
+}
+
+/// Updates the section indexes of all symbols in a symbol table after removing sections.
+///
+/// Symbols pointing into removed sections are converted to absolute symbols.
+fn remap_symbol_sections<F>(
+    out: &mut [u8],
+    header: &SectionHeader,
+    ctx: Ctx,
+    remap: F,
+) -> Result<(), ElfError>
+where
+    F: Fn(usize) -> Option<usize>,
+{
+    let symbol_size = elf::Sym::size(ctx.container);
+    let start = header.sh_offset as usize;
+
+    for index in 0..header.sh_size as usize / symbol_size {
+        let offset = start + index * symbol_size;
+        let mut symbol: elf::Sym = out.pread_with(offset, ctx).map_err(ElfError::new)?;
+
+        let section = symbol.st_shndx;
+        if section == SHN_UNDEF || section >= elf::section_header::SHN_LORESERVE as usize {
+            continue;
+        }
+
+        symbol.st_shndx = remap(section).unwrap_or(elf::section_header::SHN_ABS as usize);
+        out.pwrite_with(symbol, offset, ctx)
+            .map_err(ElfError::new)?;
+    }
+
+    Ok(())
+}
+
+/// The contents of the `.note.android.ident` note of an ELF object built with the Android NDK.
+///
+/// Returned by [`ElfObject::android_ident`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ElfAndroidIdent<'data> {
+    /// The minimum Android API level targeted by the object.
+    pub api_level: u32,
+    /// The version of the NDK, such as `r23b`.
+    pub ndk_version: Option<&'data str>,
+    /// The build number of the NDK.
+    pub ndk_build_number: Option<&'data str>,
+}
+
 /// Parsed debug link section.
 #[derive(Debug)]
 pub struct DebugLink<'data> {
@@ -1027,3 +2061,766 @@ pub struct DebugLinkError<'data> {
     /// The original data of the debug section.
     pub data: Cow<'data, [u8]>,
 }
+
+/// Note type of `struct elf_prstatus`, containing the registers of a thread.
+const NT_PRSTATUS: u32 = 1;
+/// Note type of the `NT_FILE` note, listing memory mapped files.
+const NT_FILE: u32 = 0x4649_4c45;
+
+/// Offset of `pr_cursig` within `struct elf_prstatus`.
+const PRSTATUS_CURSIG_OFFSET: usize = 12;
+/// Offset of `pr_pid` within `struct elf_prstatus` on 64-bit platforms.
+const PRSTATUS_PID_OFFSET: usize = 32;
+/// Offset of `pr_reg` within `struct elf_prstatus` on 64-bit platforms.
+const PRSTATUS_REG_OFFSET: usize = 112;
+
+/// Registers in `struct user_regs_struct` on x86_64, named like in Breakpad CFI.
+const AMD64_REGISTERS: &[&str] = &[
+    "$r15",
+    "$r14",
+    "$r13",
+    "$r12",
+    "$rbp",
+    "$rbx",
+    "$r11",
+    "$r10",
+    "$r9",
+    "$r8",
+    "$rax",
+    "$rcx",
+    "$rdx",
+    "$rsi",
+    "$rdi",
+    "$orig_rax",
+    "$rip",
+    "$cs",
+    "$eflags",
+    "$rsp",
+    "$ss",
+    "$fs_base",
+    "$gs_base",
+    "$ds",
+    "$es",
+    "$fs",
+    "$gs",
+];
+
+/// Registers in `struct user_pt_regs` on ARM64, named like in Breakpad CFI.
+const ARM64_REGISTERS: &[&str] = &[
+    "x0", "x1", "x2", "x3", "x4", "x5", "x6", "x7", "x8", "x9", "x10", "x11", "x12", "x13", "x14",
+    "x15", "x16", "x17", "x18", "x19", "x20", "x21", "x22", "x23", "x24", "x25", "x26", "x27",
+    "x28", "x29", "x30", "sp", "pc", "pstate",
+];
+
+/// A thread captured in an ELF core file.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CoreThread {
+    /// The identifier of the thread.
+    pub thread_id: u32,
+    /// The signal that was pending on this thread, or `0`.
+    pub signal: u32,
+    /// The values of the thread's general purpose registers.
+    ///
+    /// Registers are named like in Breakpad CFI, for instance `$rip` on x86_64 and `pc` on ARM64.
+    pub registers: Vec<(&'static str, u64)>,
+}
+
+impl CoreThread {
+    /// Parses a thread from the descriptor of an `NT_PRSTATUS` note.
+    ///
+    /// This reads the 64-bit little-endian layout of `struct elf_prstatus`. Returns `None` if the
+    /// architecture is not supported or the descriptor is too short.
+    pub fn from_prstatus(desc: &[u8], arch: Arch) -> Option<Self> {
+        let register_names = match arch.cpu_family() {
+            CpuFamily::Amd64 => AMD64_REGISTERS,
+            CpuFamily::Arm64 => ARM64_REGISTERS,
+            _ => return None,
+        };
+
+        let read_u32 = |offset: usize| desc.pread_with::<u32>(offset, scroll::LE).ok();
+
+        let registers = register_names
+            .iter()
+            .enumerate()
+            .map(|(index, name)| {
+                let value = desc.pread_with::<u64>(PRSTATUS_REG_OFFSET + index * 8, scroll::LE);
+                value.ok().map(|value| (*name, value))
+            })
+            .collect::<Option<_>>()?;
+
+        Some(CoreThread {
+            thread_id: read_u32(PRSTATUS_PID_OFFSET)?,
+            signal: read_u32(PRSTATUS_CURSIG_OFFSET)? & 0xffff,
+            registers,
+        })
+    }
+
+    /// Returns the value of the register with the given name.
+    pub fn register(&self, name: &str) -> Option<u64> {
+        self.registers
+            .iter()
+            .find(|(register, _)| *register == name)
+            .map(|(_, value)| *value)
+    }
+}
+
+/// A file mapped into memory of the process, as listed in the `NT_FILE` note.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CoreFileMapping<'data> {
+    /// The start address of the mapping.
+    pub start: u64,
+    /// The end address of the mapping (exclusive).
+    pub end: u64,
+    /// The offset of the mapping within the file.
+    pub offset: u64,
+    /// The path of the mapped file.
+    pub path: Cow<'data, str>,
+}
+
+impl<'data> CoreFileMapping<'data> {
+    /// Parses the descriptor of a 64-bit little-endian `NT_FILE` note into file mappings.
+    ///
+    /// Returns `None` if the note is malformed.
+    pub fn parse_note(desc: &'data [u8]) -> Option<Vec<Self>> {
+        let read_u64 = |offset: usize| desc.pread_with::<u64>(offset, scroll::LE).ok();
+
+        let count = read_u64(0)? as usize;
+        let page_size = read_u64(8)?;
+        let names_offset = count.checked_mul(24)?.checked_add(16)?;
+        let mut names = desc.get(names_offset..)?.split(|b| *b == 0);
+
+        let mut mappings = Vec::with_capacity(count.min(desc.len() / 24));
+        for index in 0..count {
+            let entry = 16 + index * 24;
+            mappings.push(CoreFileMapping {
+                start: read_u64(entry)?,
+                end: read_u64(entry + 8)?,
+                offset: read_u64(entry + 16)?.checked_mul(page_size)?,
+                path: String::from_utf8_lossy(names.next()?),
+            });
+        }
+
+        Some(mappings)
+    }
+}
+
+/// A segment of process memory captured in an ELF core file.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CoreSegment<'data> {
+    /// The start address of the segment.
+    pub address: u64,
+    /// The size of the segment in memory.
+    pub size: u64,
+    /// The contents of the segment.
+    ///
+    /// This can be shorter than `size` or even empty if the segment was not dumped entirely, for
+    /// instance for read-only file mappings.
+    pub data: &'data [u8],
+}
+
+/// An ELF core file, written when a process crashes on Linux.
+///
+/// Core files do not contain debug information. Instead, they contain the registers of all threads,
+/// the list of memory mapped files, and the contents of process memory. This allows to unwind the
+/// stacks of the crashed process, similar to a minidump.
+///
+/// Registers are only read from 64-bit little-endian core files for x86_64 and ARM64.
+///
+/// [`Object::parse`](crate::Object::parse) detects core files as [`ElfObject`] with
+/// [`ObjectKind::Dump`], since core files share the ELF container but carry no symbols or debug
+/// information to look up. `CoreObject` is a separate view of the process state instead of a
+/// variant of [`Object`](crate::Object).
+pub struct CoreObject<'data> {
+    elf: ElfObject<'data>,
+    threads: Vec<CoreThread>,
+    mappings: Vec<CoreFileMapping<'data>>,
+    segments: Vec<CoreSegment<'data>>,
+}
+
+impl<'data> CoreObject<'data> {
+    /// Tests whether the buffer could contain an ELF core file.
+    pub fn test(data: &[u8]) -> bool {
+        ElfObject::test(data)
+            && elf::Elf::parse_header(data)
+                .is_ok_and(|header| header.e_type == elf::header::ET_CORE)
+    }
+
+    /// Tries to parse an ELF core file from the given slice.
+    pub fn parse(data: &'data [u8]) -> Result<Self, ElfError> {
+        let elf = ElfObject::parse(data)?;
+        if elf.elf.header.e_type != elf::header::ET_CORE {
+            return Err(ElfError::new("not an ELF core file"));
+        }
+
+        let segments = elf
+            .elf
+            .program_headers
+            .iter()
+            .filter(|phdr| phdr.p_type == elf::program_header::PT_LOAD)
+            .map(|phdr| {
+                let start = phdr.p_offset as usize;
+                let end = start.checked_add(phdr.p_filesz as usize);
+                CoreSegment {
+                    address: phdr.p_vaddr,
+                    size: phdr.p_memsz,
+                    data: end.and_then(|end| data.get(start..end)).unwrap_or_default(),
+                }
+            })
+            .collect();
+
+        let is_64_le = elf.elf.is_64 && elf.elf.little_endian;
+        let arch = elf.arch();
+
+        let mut threads = Vec::new();
+        let mut mappings = Vec::new();
+
+        if let Some(notes) = elf.elf.iter_note_headers(data) {
+            for note in notes {
+                let note = note.map_err(ElfError::new)?;
+                if note.name != "CORE" {
+                    continue;
+                }
+
+                match note.n_type {
+                    NT_PRSTATUS if is_64_le => {
+                        if let Some(thread) = CoreThread::from_prstatus(note.desc, arch) {
+                            threads.push(thread);
+                        }
+                    }
+                    NT_FILE if is_64_le => {
+                        mappings = CoreFileMapping::parse_note(note.desc)
+                            .ok_or_else(|| ElfError::new("invalid NT_FILE note"))?;
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(CoreObject {
+            elf,
+            threads,
+            mappings,
+            segments,
+        })
+    }
+
+    /// The container file format, which is always `FileFormat::Elf`.
+    pub fn file_format(&self) -> FileFormat {
+        FileFormat::Elf
+    }
+
+    /// Core files do not have a code identifier.
+    pub fn code_id(&self) -> Option<CodeId> {
+        None
+    }
+
+    /// Core files do not have a debug identifier.
+    pub fn debug_id(&self) -> DebugId {
+        DebugId::default()
+    }
+
+    /// The CPU architecture of the crashed process.
+    pub fn arch(&self) -> Arch {
+        self.elf.arch()
+    }
+
+    /// The kind of this object, which is always `ObjectKind::Dump`.
+    pub fn kind(&self) -> ObjectKind {
+        ObjectKind::Dump
+    }
+
+    /// The address of the first memory segment.
+    pub fn load_address(&self) -> u64 {
+        self.elf.load_address()
+    }
+
+    /// Core files do not contain a symbol table.
+    pub fn has_symbols(&self) -> bool {
+        false
+    }
+
+    /// Returns an iterator over symbols, which is always empty for core files.
+    pub fn symbols(&self) -> ElfSymbolIterator<'data, '_> {
+        self.elf.symbols()
+    }
+
+    /// Returns an ordered map of symbols, which is always empty for core files.
+    pub fn symbol_map(&self) -> SymbolMap<'data> {
+        SymbolMap::new()
+    }
+
+    /// Core files do not contain debug information.
+    pub fn has_debug_info(&self) -> bool {
+        false
+    }
+
+    /// Constructs an empty debugging session.
+    pub fn debug_session(&self) -> Result<DwarfDebugSession<'data>, DwarfError> {
+        self.elf.debug_session()
+    }
+
+    /// Core files do not contain unwind information.
+    pub fn has_unwind_info(&self) -> bool {
+        false
+    }
+
+    /// Core files do not contain embedded sources.
+    pub fn has_sources(&self) -> bool {
+        false
+    }
+
+    /// Determines whether this object is malformed and was only partially parsed.
+    pub fn is_malformed(&self) -> bool {
+        self.elf.is_malformed()
+    }
+
+    /// Returns the raw data of the core file.
+    pub fn data(&self) -> &'data [u8] {
+        self.elf.data()
+    }
+
+    /// Returns all threads of the crashed process, in the order they appear in the core file.
+    ///
+    /// On Linux, the first thread is the one that received the crashing signal.
+    pub fn threads(&self) -> &[CoreThread] {
+        &self.threads
+    }
+
+    /// Returns all memory mapped files of the crashed process.
+    pub fn file_mappings(&self) -> &[CoreFileMapping<'data>] {
+        &self.mappings
+    }
+
+    /// Returns all memory segments captured in the core file.
+    pub fn memory_segments(&self) -> &[CoreSegment<'data>] {
+        &self.segments
+    }
+
+    /// Returns the captured memory starting at `address` up to the end of its segment.
+    ///
+    /// Passing the stack pointer of a thread returns its stack memory, which can be used to unwind
+    /// the thread. Returns `None` if the memory at this address was not captured.
+    pub fn memory_at(&self, address: u64) -> Option<&'data [u8]> {
+        let segment = self.segments.iter().find(|segment| {
+            address >= segment.address && address - segment.address < segment.size
+        })?;
+
+        let offset = (address - segment.address).try_into().ok()?;
+        segment.data.get(offset..).filter(|data| !data.is_empty())
+    }
+
+    /// Reads `len` bytes of captured memory at `address`.
+    pub fn read_memory(&self, address: u64, len: usize) -> Option<&'data [u8]> {
+        self.memory_at(address)?.get(..len)
+    }
+}
+
+impl fmt::Debug for CoreObject<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CoreObject")
+            .field("arch", &self.arch())
+            .field("threads", &self.threads.len())
+            .field("file_mappings", &self.mappings.len())
+            .field("memory_segments", &self.segments.len())
+            .field("is_malformed", &self.is_malformed())
+            .finish()
+    }
+}
+
+impl<'slf, 'data: 'slf> AsSelf<'slf> for CoreObject<'data> {
+    type Ref = CoreObject<'slf>;
+
+    fn as_self(&'slf self) -> &'slf Self::Ref {
+        self
+    }
+}
+
+impl<'data> Parse<'data> for CoreObject<'data> {
+    type Error = ElfError;
+
+    fn test(data: &[u8]) -> bool {
+        Self::test(data)
+    }
+
+    fn parse(data: &'data [u8]) -> Result<Self, ElfError> {
+        Self::parse(data)
+    }
+}
+
+impl<'data: 'object, 'object> ObjectLike<'data, 'object> for CoreObject<'data> {
+    type Error = DwarfError;
+    type Session = DwarfDebugSession<'data>;
+    type SymbolIterator = ElfSymbolIterator<'data, 'object>;
+
+    fn file_format(&self) -> FileFormat {
+        self.file_format()
+    }
+
+    fn code_id(&self) -> Option<CodeId> {
+        self.code_id()
+    }
+
+    fn debug_id(&self) -> DebugId {
+        self.debug_id()
+    }
+
+    fn arch(&self) -> Arch {
+        self.arch()
+    }
+
+    fn kind(&self) -> ObjectKind {
+        self.kind()
+    }
+
+    fn load_address(&self) -> u64 {
+        self.load_address()
+    }
+
+    fn has_symbols(&self) -> bool {
+        self.has_symbols()
+    }
+
+    fn symbols(&'object self) -> Self::SymbolIterator {
+        self.symbols()
+    }
+
+    fn symbol_map(&self) -> SymbolMap<'data> {
+        self.symbol_map()
+    }
+
+    fn has_debug_info(&self) -> bool {
+        self.has_debug_info()
+    }
+
+    fn debug_session(&self) -> Result<Self::Session, Self::Error> {
+        self.debug_session()
+    }
+
+    fn has_unwind_info(&self) -> bool {
+        self.has_unwind_info()
+    }
+
+    fn has_sources(&self) -> bool {
+        self.has_sources()
+    }
+
+    fn is_malformed(&self) -> bool {
+        self.is_malformed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(kind: u32, desc: &[u8]) -> Vec<u8> {
+        named_note("CORE", kind, desc)
+    }
+
+    fn named_note(name: &str, kind: u32, desc: &[u8]) -> Vec<u8> {
+        let mut note = Vec::new();
+        note.extend_from_slice(&(name.len() as u32 + 1).to_le_bytes());
+        note.extend_from_slice(&(desc.len() as u32).to_le_bytes());
+        note.extend_from_slice(&kind.to_le_bytes());
+        note.extend_from_slice(name.as_bytes());
+        note.push(0);
+        note.resize((note.len() + 3) & !3, 0);
+        note.extend_from_slice(desc);
+        note.resize((note.len() + 3) & !3, 0);
+        note
+    }
+
+    fn program_header(
+        kind: u32,
+        offset: usize,
+        address: u64,
+        filesz: usize,
+        memsz: u64,
+    ) -> Vec<u8> {
+        let mut phdr = Vec::new();
+        phdr.extend_from_slice(&kind.to_le_bytes());
+        phdr.extend_from_slice(&elf::program_header::PF_R.to_le_bytes());
+        for value in [offset as u64, address, address, filesz as u64, memsz, 4] {
+            phdr.extend_from_slice(&value.to_le_bytes());
+        }
+        phdr
+    }
+
+    /// Builds a minimal x86_64 core file with one thread, one file mapping and one segment.
+    fn build_core() -> Vec<u8> {
+        let mut prstatus = vec![0; PRSTATUS_REG_OFFSET];
+        prstatus[PRSTATUS_CURSIG_OFFSET] = 11;
+        prstatus[PRSTATUS_PID_OFFSET..PRSTATUS_PID_OFFSET + 4]
+            .copy_from_slice(&42u32.to_le_bytes());
+        for index in 0..AMD64_REGISTERS.len() as u64 {
+            prstatus.extend_from_slice(&(0x1000 + index).to_le_bytes());
+        }
+
+        let mut file = Vec::new();
+        for value in [1u64, 0x1000, 0x40_0000, 0x40_2000, 2] {
+            file.extend_from_slice(&value.to_le_bytes());
+        }
+        file.extend_from_slice(b"/usr/bin/crash\0");
+
+        let mut notes = note(NT_PRSTATUS, &prstatus);
+        notes.extend(note(NT_FILE, &file));
+
+        let memory: Vec<u8> = (0..16).collect();
+        let notes_offset = 64 + 2 * 56;
+        let memory_offset = notes_offset + notes.len();
+
+        let mut data = Vec::new();
+        data.extend_from_slice(b"\x7fELF\x02\x01\x01\0\0\0\0\0\0\0\0\0");
+        data.extend_from_slice(&elf::header::ET_CORE.to_le_bytes());
+        data.extend_from_slice(&elf::header::EM_X86_64.to_le_bytes());
+        data.extend_from_slice(&1u32.to_le_bytes());
+        for value in [0u64, 64, 0] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(&0u32.to_le_bytes());
+        for value in [64u16, 56, 2, 64, 0, 0] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+
+        let pt_note = elf::program_header::PT_NOTE;
+        let pt_load = elf::program_header::PT_LOAD;
+        data.extend(program_header(pt_note, notes_offset, 0, notes.len(), 0));
+        data.extend(program_header(
+            pt_load,
+            memory_offset,
+            0x7000,
+            memory.len(),
+            0x20,
+        ));
+        data.extend(notes);
+        data.extend(memory);
+        data
+    }
+
+    #[test]
+    fn test_core_object() {
+        let data = build_core();
+        assert!(CoreObject::test(&data));
+
+        let core = CoreObject::parse(&data).unwrap();
+        assert_eq!(core.kind(), ObjectKind::Dump);
+        assert_eq!(core.arch(), Arch::Amd64);
+
+        let threads = core.threads();
+        assert_eq!(threads.len(), 1);
+        assert_eq!(threads[0].thread_id, 42);
+        assert_eq!(threads[0].signal, 11);
+        assert_eq!(threads[0].register("$r15"), Some(0x1000));
+        assert_eq!(threads[0].register("$rip"), Some(0x1010));
+        assert_eq!(threads[0].register("$rsp"), Some(0x1013));
+
+        let mappings = core.file_mappings();
+        assert_eq!(mappings.len(), 1);
+        assert_eq!(mappings[0].start, 0x40_0000);
+        assert_eq!(mappings[0].end, 0x40_2000);
+        assert_eq!(mappings[0].offset, 0x2000);
+        assert_eq!(mappings[0].path, "/usr/bin/crash");
+
+        assert_eq!(core.memory_segments().len(), 1);
+        assert_eq!(core.memory_at(0x700c), Some(&[12, 13, 14, 15][..]));
+        assert_eq!(core.read_memory(0x7002, 2), Some(&[2, 3][..]));
+        assert_eq!(core.read_memory(0x700e, 4), None);
+        // Memory beyond the dumped file contents is not available.
+        assert_eq!(core.memory_at(0x7010), None);
+        assert_eq!(core.memory_at(0x8000), None);
+    }
+
+    #[test]
+    fn test_core_thread_from_prstatus() {
+        let mut prstatus = vec![0; PRSTATUS_REG_OFFSET];
+        prstatus[PRSTATUS_PID_OFFSET] = 7;
+        for index in 0..AMD64_REGISTERS.len() as u64 {
+            prstatus.extend_from_slice(&index.to_le_bytes());
+        }
+
+        let thread = CoreThread::from_prstatus(&prstatus, Arch::Amd64).unwrap();
+        assert_eq!(thread.thread_id, 7);
+        assert_eq!(thread.signal, 0);
+        assert_eq!(thread.register("$rip"), Some(16));
+
+        // The ARM64 register set is larger than the x86_64 one.
+        assert_eq!(CoreThread::from_prstatus(&prstatus, Arch::Arm64), None);
+        assert_eq!(CoreThread::from_prstatus(&prstatus, Arch::X86), None);
+    }
+
+    #[test]
+    fn test_not_a_core() {
+        let mut data = build_core();
+        data[16] = elf::header::ET_EXEC as u8;
+        assert!(!CoreObject::test(&data));
+        assert!(CoreObject::parse(&data).is_err());
+    }
+
+    /// Builds a minimal x86_64 shared library with the given notes in a `PT_NOTE` segment.
+    fn build_library(notes: &[u8]) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(b"\x7fELF\x02\x01\x01\0\0\0\0\0\0\0\0\0");
+        data.extend_from_slice(&elf::header::ET_DYN.to_le_bytes());
+        data.extend_from_slice(&elf::header::EM_X86_64.to_le_bytes());
+        data.extend_from_slice(&1u32.to_le_bytes());
+        for value in [0u64, 64, 0] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(&0u32.to_le_bytes());
+        for value in [64u16, 56, 1, 64, 0, 0] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+
+        let pt_note = elf::program_header::PT_NOTE;
+        data.extend(program_header(pt_note, 64 + 56, 0, notes.len(), 0));
+        data.extend_from_slice(notes);
+        data
+    }
+
+    #[test]
+    fn test_android_notes() {
+        let mut ident = 30u32.to_le_bytes().to_vec();
+        let mut version = b"r23b".to_vec();
+        version.resize(ANDROID_NDK_FIELD_SIZE, 0);
+        ident.extend(version);
+        let mut build_number = b"8768859".to_vec();
+        build_number.resize(ANDROID_NDK_FIELD_SIZE, 0);
+        ident.extend(build_number);
+
+        let mut notes = named_note("Android", NT_ANDROID_TYPE_IDENT, &ident);
+        // `NT_ANDROID_TYPE_KUSER` shares its type with `NT_GNU_BUILD_ID`.
+        notes.extend(named_note("Android", 3, &[0xff; 4]));
+        notes.extend(named_note("GNU", elf::note::NT_GNU_BUILD_ID, &[0xab; 20]));
+
+        let data = build_library(&notes);
+        let object = ElfObject::parse(&data).unwrap();
+
+        assert_eq!(
+            object.android_ident(),
+            Some(ElfAndroidIdent {
+                api_level: 30,
+                ndk_version: Some("r23b"),
+                ndk_build_number: Some("8768859"),
+            })
+        );
+        assert_eq!(object.code_id(), Some(CodeId::from_binary(&[0xab; 20])));
+    }
+
+    #[test]
+    fn test_android_notes_legacy() {
+        // Before NDK r14, the note only contains the API level.
+        let notes = named_note("Android", NT_ANDROID_TYPE_IDENT, &21u32.to_le_bytes());
+        let object_data = build_library(&notes);
+        let object = ElfObject::parse(&object_data).unwrap();
+
+        let ident = object.android_ident().unwrap();
+        assert_eq!(ident.api_level, 21);
+        assert_eq!(ident.ndk_version, None);
+        assert_eq!(object.code_id(), None);
+
+        let object_data = build_library(&[]);
+        let object = ElfObject::parse(&object_data).unwrap();
+        assert_eq!(object.android_ident(), None);
+    }
+
+    fn sleb128(value: i64, out: &mut Vec<u8>) {
+        let mut value = value;
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            let done = (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0);
+            out.push(if done { byte } else { byte | 0x80 });
+            if done {
+                break;
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_android_relocs() {
+        const R_X86_64_GLOB_DAT: i64 = 6;
+        const R_X86_64_RELATIVE: i64 = 8;
+
+        let mut table = ANDROID_PACKED_MAGIC.to_vec();
+        let grouped = RELOCATION_GROUPED_BY_OFFSET_DELTA_FLAG
+            | RELOCATION_GROUPED_BY_INFO_FLAG
+            | RELOCATION_GROUPED_BY_ADDEND_FLAG
+            | RELOCATION_GROUP_HAS_ADDEND_FLAG;
+        let values = [
+            3,
+            0x1000,
+            // Two relative relocations eight bytes apart, sharing their addend.
+            2,
+            grouped,
+            8,
+            R_X86_64_RELATIVE,
+            0x500,
+            // One symbol relocation with its own offset, info and addend.
+            1,
+            RELOCATION_GROUP_HAS_ADDEND_FLAG,
+            0x10,
+            (5 << 32) | R_X86_64_GLOB_DAT,
+            -0x500,
+        ];
+        for value in values {
+            sleb128(value, &mut table);
+        }
+
+        let relocs = decode_android_relocs(&table, true, 100).unwrap();
+        let relocs: Vec<_> = relocs
+            .iter()
+            .map(|r| (r.r_offset, r.r_sym, r.r_type, r.r_addend))
+            .collect();
+        assert_eq!(
+            relocs,
+            [
+                (0x1008, 0, 8, Some(0x500)),
+                (0x1010, 0, 8, Some(0x500)),
+                (0x1020, 5, 6, Some(0)),
+            ]
+        );
+
+        // Counts exceeding the limit and truncated tables are rejected.
+        assert!(decode_android_relocs(&table, true, 2).is_err());
+        assert!(decode_android_relocs(&table[..table.len() - 1], true, 100).is_err());
+        assert!(decode_android_relocs(b"APS1", true, 100).is_err());
+
+        // Huge counts are not trusted for preallocation.
+        let mut table = ANDROID_PACKED_MAGIC.to_vec();
+        sleb128(i64::MAX, &mut table);
+        sleb128(0x1000, &mut table);
+        assert!(decode_android_relocs(&table, true, usize::MAX).is_err());
+    }
+
+    #[test]
+    fn test_vm_to_offset() {
+        let phdr = |p_vaddr, p_filesz, p_offset| elf::ProgramHeader {
+            p_type: elf::program_header::PT_LOAD,
+            p_vaddr,
+            p_filesz,
+            p_offset,
+            ..Default::default()
+        };
+
+        let program_headers = [phdr(0x1000, 0x100, 0x200)];
+        assert_eq!(vm_to_offset(&program_headers, 0x1010), Some(0x210));
+        assert_eq!(vm_to_offset(&program_headers, 0x1100), None);
+
+        // Segments wrapping around the address space or the file are skipped.
+        let program_headers = [phdr(u64::MAX - 0x10, 0x100, 0)];
+        assert_eq!(vm_to_offset(&program_headers, u64::MAX - 8), None);
+        let program_headers = [phdr(0x1000, 0x100, u64::MAX)];
+        assert_eq!(vm_to_offset(&program_headers, 0x1010), None);
+    }
+
+    #[test]
+    fn test_read_sleb128() {
+        for value in [0, 1, -1, 63, 64, -64, -65, 0x1000, i64::MAX, i64::MIN] {
+            let mut data = Vec::new();
+            sleb128(value, &mut data);
+            let mut slice = &data[..];
+            assert_eq!(read_sleb128(&mut slice), Some(value));
+            assert!(slice.is_empty());
+        }
+    }
+}
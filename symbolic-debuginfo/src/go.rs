@@ -0,0 +1,1074 @@
+//! Support for the function and line tables of the Go runtime.
+//!
+//! Go binaries contain a `pclntab` section, which the runtime uses to print stack traces. It maps
+//! program counters to functions, files and line numbers, and is retained even if DWARF is
+//! stripped from the binary, for instance with `-ldflags="-w"`. The section is called
+//! `.gopclntab` in ELF files and `__gopclntab` in MachO files.
+//!
+//! The layout of the table has changed with Go 1.2, 1.16, 1.18 and 1.20. All of these versions are
+//! supported. Inlined functions are not reported, since the inline tree is stored in function
+//! data that is not versioned along with the table.
+
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+
+use thiserror::Error;
+
+use symbolic_common::{Language, Name, NameMangling, PathRemapper};
+
+use crate::base::*;
+
+/// Magic of tables written by Go 1.2 to 1.15.
+const MAGIC_GO12: u32 = 0xffff_fffb;
+/// Magic of tables written by Go 1.16 and 1.17.
+const MAGIC_GO116: u32 = 0xffff_fffa;
+/// Magic of tables written by Go 1.18 and 1.19.
+const MAGIC_GO118: u32 = 0xffff_fff0;
+/// Magic of tables written by Go 1.20 and later.
+const MAGIC_GO120: u32 = 0xffff_fff1;
+
+/// Name of the section used in error locations.
+const SECTION_NAME: &str = ".gopclntab";
+
+/// The error type for [`GoError`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GoErrorKind {
+    /// The table does not start with a known header.
+    InvalidHeader,
+    /// A record of the table points outside of the section.
+    OutOfBounds,
+    /// A limit of the debug session was exceeded, see [`SessionLimits`].
+    LimitExceeded,
+}
+
+impl fmt::Display for GoErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidHeader => write!(f, "invalid Go pclntab header"),
+            Self::OutOfBounds => write!(f, "Go pclntab record out of bounds"),
+            Self::LimitExceeded => write!(f, "debug session limit exceeded"),
+        }
+    }
+}
+
+/// An error when dealing with [`GoRuntimeInfo`].
+#[derive(Debug, Error)]
+#[error("{kind}{}", LocationSuffix(.location))]
+pub struct GoError {
+    kind: GoErrorKind,
+    #[source]
+    source: Option<Box<dyn Error + Send + Sync + 'static>>,
+    location: Option<ErrorLocation>,
+}
+
+impl GoError {
+    /// Creates an error that occurred at the given offset of the section.
+    fn at(kind: GoErrorKind, offset: usize) -> Self {
+        Self {
+            kind,
+            source: None,
+            location: Some(ErrorLocation::new(SECTION_NAME, offset as u64)),
+        }
+    }
+
+    /// Returns the corresponding [`GoErrorKind`] for this error.
+    pub fn kind(&self) -> GoErrorKind {
+        self.kind
+    }
+
+    /// Returns the location in the section at which this error occurred, if known.
+    pub fn location(&self) -> Option<&ErrorLocation> {
+        self.location.as_ref()
+    }
+}
+
+impl From<GoErrorKind> for GoError {
+    fn from(kind: GoErrorKind) -> Self {
+        Self {
+            kind,
+            source: None,
+            location: None,
+        }
+    }
+}
+
+impl From<LimitExceeded> for GoError {
+    fn from(e: LimitExceeded) -> Self {
+        Self {
+            kind: GoErrorKind::LimitExceeded,
+            source: Some(Box::new(e)),
+            location: None,
+        }
+    }
+}
+
+/// The version of the Go toolchain that wrote a [`GoRuntimeInfo`].
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum GoVersion {
+    /// Go 1.2 to 1.15.
+    Go12,
+    /// Go 1.16 and 1.17.
+    Go116,
+    /// Go 1.18 and 1.19.
+    Go118,
+    /// Go 1.20 and later.
+    Go120,
+}
+
+/// Reads integers with the byte order and pointer size of the binary.
+#[derive(Clone, Copy, Debug)]
+struct Reader {
+    big_endian: bool,
+    ptr_size: u8,
+}
+
+impl Reader {
+    fn u32(self, data: &[u8], offset: usize) -> Option<u32> {
+        let bytes = data.get(offset..offset.checked_add(4)?)?;
+        let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        Some(if self.big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        })
+    }
+
+    fn uintptr(self, data: &[u8], offset: usize) -> Option<u64> {
+        if self.ptr_size == 4 {
+            return self.u32(data, offset).map(u64::from);
+        }
+
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(data.get(offset..offset.checked_add(8)?)?);
+        Some(if self.big_endian {
+            u64::from_be_bytes(bytes)
+        } else {
+            u64::from_le_bytes(bytes)
+        })
+    }
+}
+
+/// Reads a null-terminated string at the given offset, or an empty string if it is invalid.
+fn read_str(data: &[u8], offset: usize) -> &str {
+    let bytes = data.get(offset..).unwrap_or_default();
+    let bytes = match bytes.iter().position(|b| *b == 0) {
+        Some(end) => &bytes[..end],
+        None => bytes,
+    };
+
+    std::str::from_utf8(bytes).unwrap_or_default()
+}
+
+/// Reads an unsigned LEB128 number and advances the slice past it.
+fn read_varint(data: &mut &[u8]) -> Option<u32> {
+    let mut value = 0u32;
+    let mut shift = 0;
+
+    while let Some((&byte, rest)) = data.split_first() {
+        *data = rest;
+        value |= u32::from(byte & 0x7f).checked_shl(shift)?;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+        shift += 7;
+    }
+
+    None
+}
+
+/// An iterator over the ranges of a `pcvalue` table.
+///
+/// Yields the start and end address of every range along with its value. Malformed tables end the
+/// iteration early.
+struct PcValueIter<'a> {
+    data: &'a [u8],
+    pc: u64,
+    value: i32,
+    quantum: u64,
+    first: bool,
+}
+
+impl<'a> PcValueIter<'a> {
+    fn new(data: &'a [u8], entry: u64, quantum: u8) -> Self {
+        PcValueIter {
+            data,
+            pc: entry,
+            value: -1,
+            quantum: quantum.into(),
+            first: true,
+        }
+    }
+}
+
+impl Iterator for PcValueIter<'_> {
+    type Item = (u64, u64, i32);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let uvdelta = read_varint(&mut self.data)?;
+        if uvdelta == 0 && !self.first {
+            return None;
+        }
+        self.first = false;
+
+        // Value deltas are zig-zag encoded.
+        let vdelta = if uvdelta & 1 != 0 {
+            !(uvdelta >> 1)
+        } else {
+            uvdelta >> 1
+        };
+
+        let pcdelta = u64::from(read_varint(&mut self.data)?) * self.quantum;
+        let start = self.pc;
+        self.pc = start.checked_add(pcdelta)?;
+        self.value = self.value.wrapping_add(vdelta as i32);
+        Some((start, self.pc, self.value))
+    }
+}
+
+/// The offsets of a function's records, read from its `_func` structure.
+struct FuncRecord {
+    entry: u64,
+    end: u64,
+    name_offset: usize,
+    pcfile: usize,
+    pcln: usize,
+    cu_offset: usize,
+}
+
+/// The function and line tables of the Go runtime, read from the `pclntab` section.
+///
+/// Use [`functions`](Self::functions) to read all functions along with their line records.
+/// Addresses are absolute virtual addresses, as written by the linker.
+pub struct GoRuntimeInfo<'data> {
+    data: &'data [u8],
+    version: GoVersion,
+    reader: Reader,
+    quantum: u8,
+    nfunc: usize,
+    text_start: u64,
+    funcnametab: &'data [u8],
+    cutab: &'data [u8],
+    filetab: &'data [u8],
+    pctab: &'data [u8],
+    functab: &'data [u8],
+}
+
+impl<'data> GoRuntimeInfo<'data> {
+    /// Tests whether the buffer could contain a Go `pclntab`.
+    pub fn test(data: &[u8]) -> bool {
+        GoRuntimeInfo::parse(data).is_ok()
+    }
+
+    /// Parses the contents of a `pclntab` section.
+    pub fn parse(data: &'data [u8]) -> Result<Self, GoError> {
+        let header = data
+            .get(..8)
+            .ok_or_else(|| GoError::at(GoErrorKind::InvalidHeader, 0))?;
+
+        let magic_bytes = [header[0], header[1], header[2], header[3]];
+        let (version, big_endian) = match (
+            u32::from_le_bytes(magic_bytes),
+            u32::from_be_bytes(magic_bytes),
+        ) {
+            (MAGIC_GO12, _) => (GoVersion::Go12, false),
+            (MAGIC_GO116, _) => (GoVersion::Go116, false),
+            (MAGIC_GO118, _) => (GoVersion::Go118, false),
+            (MAGIC_GO120, _) => (GoVersion::Go120, false),
+            (_, MAGIC_GO12) => (GoVersion::Go12, true),
+            (_, MAGIC_GO116) => (GoVersion::Go116, true),
+            (_, MAGIC_GO118) => (GoVersion::Go118, true),
+            (_, MAGIC_GO120) => (GoVersion::Go120, true),
+            _ => return Err(GoError::at(GoErrorKind::InvalidHeader, 0)),
+        };
+
+        let quantum = header[6];
+        let ptr_size = header[7];
+        if header[4] != 0 || header[5] != 0 || !matches!(quantum, 1 | 2 | 4) {
+            return Err(GoError::at(GoErrorKind::InvalidHeader, 4));
+        }
+        if !matches!(ptr_size, 4 | 8) {
+            return Err(GoError::at(GoErrorKind::InvalidHeader, 7));
+        }
+
+        let reader = Reader {
+            big_endian,
+            ptr_size,
+        };
+
+        let word = |index: usize| {
+            let offset = 8 + index * usize::from(ptr_size);
+            reader
+                .uintptr(data, offset)
+                .ok_or_else(|| GoError::at(GoErrorKind::OutOfBounds, offset))
+        };
+        let table = |field: usize| -> Result<&'data [u8], GoError> {
+            let offset = word(field)?;
+            usize::try_from(offset)
+                .ok()
+                .and_then(|offset| data.get(offset..))
+                .ok_or_else(|| GoError::at(GoErrorKind::OutOfBounds, offset as usize))
+        };
+
+        let mut info = GoRuntimeInfo {
+            data,
+            version,
+            reader,
+            quantum,
+            nfunc: 0,
+            text_start: 0,
+            funcnametab: data,
+            cutab: &[],
+            filetab: &[],
+            pctab: data,
+            functab: &[],
+        };
+
+        match version {
+            GoVersion::Go12 => {
+                let nfunc = usize::try_from(word(0)?).unwrap_or(usize::MAX);
+                let functab = &data[8 + usize::from(ptr_size)..];
+
+                // The offset of the file table follows the function table and the end address.
+                let filetab_field = nfunc
+                    .checked_mul(2)
+                    .and_then(|n| n.checked_add(1))
+                    .and_then(|n| n.checked_mul(usize::from(ptr_size)))
+                    .unwrap_or(usize::MAX);
+                let filetab = reader
+                    .u32(functab, filetab_field)
+                    .and_then(|offset| data.get(offset as usize..))
+                    .ok_or_else(|| GoError::at(GoErrorKind::OutOfBounds, 8))?;
+
+                info.nfunc = nfunc;
+                info.functab = functab;
+                info.filetab = filetab;
+            }
+            GoVersion::Go116 => {
+                info.nfunc = usize::try_from(word(0)?).unwrap_or(usize::MAX);
+                info.funcnametab = table(2)?;
+                info.cutab = table(3)?;
+                info.filetab = table(4)?;
+                info.pctab = table(5)?;
+                info.functab = table(6)?;
+            }
+            GoVersion::Go118 | GoVersion::Go120 => {
+                info.nfunc = usize::try_from(word(0)?).unwrap_or(usize::MAX);
+                info.text_start = word(2)?;
+                info.funcnametab = table(3)?;
+                info.cutab = table(4)?;
+                info.filetab = table(5)?;
+                info.pctab = table(6)?;
+                info.functab = table(7)?;
+            }
+        }
+
+        Ok(info)
+    }
+
+    /// The version of the Go toolchain that wrote this table.
+    pub fn version(&self) -> GoVersion {
+        self.version
+    }
+
+    /// The size of pointers in the binary, in bytes.
+    pub fn ptr_size(&self) -> u8 {
+        self.reader.ptr_size
+    }
+
+    /// The minimum instruction size of the architecture, by which all program counters are aligned.
+    pub fn quantum(&self) -> u8 {
+        self.quantum
+    }
+
+    /// The number of functions declared in this table.
+    pub fn function_count(&self) -> usize {
+        self.nfunc
+    }
+
+    /// Returns an iterator over all functions in this table, in ascending address order.
+    pub fn functions(&self) -> GoFunctionIterator<'data, '_> {
+        GoFunctionIterator {
+            info: self,
+            index: 0,
+            load_address: 0,
+            finished: false,
+        }
+    }
+
+    /// Returns all source files referenced by this table.
+    pub fn files(&self) -> Vec<FileInfo<'data>> {
+        let mut seen = BTreeSet::new();
+        let mut files = Vec::new();
+
+        for function in self.functions().flatten() {
+            for line in &function.lines {
+                if seen.insert(line.file.name.clone()) {
+                    files.push(line.file);
+                }
+            }
+        }
+
+        files
+    }
+
+    fn read_u32(&self, table: &[u8], offset: usize) -> Result<u32, GoError> {
+        self.reader
+            .u32(table, offset)
+            .ok_or_else(|| self.out_of_bounds(table, offset))
+    }
+
+    /// Creates an error for an offset into a table, located relative to the start of the section.
+    fn out_of_bounds(&self, table: &[u8], offset: usize) -> GoError {
+        let base = (table.as_ptr() as usize).saturating_sub(self.data.as_ptr() as usize);
+        GoError::at(GoErrorKind::OutOfBounds, base.saturating_add(offset))
+    }
+
+    /// Reads an entry of the function table.
+    ///
+    /// Entries alternate between function addresses and offsets of `_func` structures. Starting
+    /// with Go 1.18, both are 32-bit and addresses are relative to the start of the text section.
+    fn functab_entry(&self, index: usize) -> Result<u64, GoError> {
+        match self.version {
+            GoVersion::Go118 | GoVersion::Go120 => {
+                self.read_u32(self.functab, index * 4).map(u64::from)
+            }
+            GoVersion::Go12 | GoVersion::Go116 => {
+                let offset = index * usize::from(self.reader.ptr_size);
+                self.reader
+                    .uintptr(self.functab, offset)
+                    .ok_or_else(|| self.out_of_bounds(self.functab, offset))
+            }
+        }
+    }
+
+    /// Reads the address of the function at the given index of the function table.
+    fn function_address(&self, index: usize) -> Result<u64, GoError> {
+        let address = self.functab_entry(index * 2)?;
+        Ok(match self.version {
+            GoVersion::Go118 | GoVersion::Go120 => self.text_start.wrapping_add(address),
+            GoVersion::Go12 | GoVersion::Go116 => address,
+        })
+    }
+
+    /// Reads the `_func` structure of the function at the given index.
+    fn func_record(&self, index: usize) -> Result<FuncRecord, GoError> {
+        let entry = self.function_address(index)?;
+        // The table is terminated by the end address of the last function.
+        let end = self.function_address(index + 1)?;
+        let func_offset = self.functab_entry(index * 2 + 1)? as usize;
+
+        // Go 1.2 stores offsets relative to the start of the table, later versions relative to the
+        // function table.
+        let base = match self.version {
+            GoVersion::Go12 => self.data,
+            _ => self.functab,
+        };
+
+        let func = base
+            .get(func_offset..)
+            .ok_or_else(|| self.out_of_bounds(base, func_offset))?;
+
+        // The entry is followed by 32-bit fields: nameoff, args, deferreturn, pcsp, pcfile, pcln,
+        // npcdata and, starting with Go 1.16, cuOffset.
+        let fields = match self.version {
+            GoVersion::Go118 | GoVersion::Go120 => 4,
+            GoVersion::Go12 | GoVersion::Go116 => usize::from(self.reader.ptr_size),
+        };
+
+        let cu_offset = match self.version {
+            GoVersion::Go12 => 0,
+            _ => self.read_u32(func, fields + 28)?,
+        };
+
+        Ok(FuncRecord {
+            entry,
+            end,
+            name_offset: self.read_u32(func, fields)? as usize,
+            pcfile: self.read_u32(func, fields + 16)? as usize,
+            pcln: self.read_u32(func, fields + 20)? as usize,
+            cu_offset: cu_offset as usize,
+        })
+    }
+
+    /// Resolves a file number of a `pcfile` table.
+    fn file(&self, cu_offset: usize, file: i32) -> FileInfo<'data> {
+        let file = match usize::try_from(file) {
+            Ok(file) => file,
+            Err(_) => return FileInfo::default(),
+        };
+
+        let offset = match self.version {
+            // The first entry of the file table is the number of files.
+            GoVersion::Go12 => self.read_u32(self.filetab, file * 4).ok(),
+            _ => cu_offset
+                .checked_add(file)
+                .and_then(|index| self.read_u32(self.cutab, index * 4).ok())
+                .filter(|offset| *offset != u32::MAX),
+        };
+
+        let path = match offset {
+            Some(offset) if self.version == GoVersion::Go12 => read_str(self.data, offset as usize),
+            Some(offset) => read_str(self.filetab, offset as usize),
+            None => "",
+        };
+
+        let (dir, name) = symbolic_common::split_path_bytes(path.as_bytes());
+        FileInfo {
+            name: Cow::Borrowed(name),
+            dir: Cow::Borrowed(dir.unwrap_or_default()),
+            path_kind: FilePathKind::Unknown,
+        }
+    }
+
+    /// Combines the file and line tables of a function into line records.
+    fn lines(&self, record: &FuncRecord) -> Vec<LineInfo<'data>> {
+        let table = |offset: usize| {
+            let data = self.pctab.get(offset..).unwrap_or_default();
+            PcValueIter::new(data, record.entry, self.quantum)
+        };
+
+        // An offset of zero denotes a missing table.
+        let files: Vec<_> = match record.pcfile {
+            0 => Vec::new(),
+            offset => table(offset).collect(),
+        };
+
+        let mut lines: Vec<LineInfo<'data>> = Vec::new();
+        let mut current = None;
+        let mut file_index = 0;
+
+        let line_ranges = match record.pcln {
+            0 => None,
+            offset => Some(table(offset)),
+        };
+
+        for (start, end, line) in line_ranges.into_iter().flatten() {
+            while file_index < files.len() && files[file_index].1 <= start {
+                file_index += 1;
+            }
+
+            let mut index = file_index;
+            while let Some(&(file_start, file_end, file)) = files.get(index) {
+                if file_start >= end {
+                    break;
+                }
+
+                let address = start.max(file_start);
+                let size = end.min(file_end) - address;
+                if line >= 0 && file >= 0 && size > 0 {
+                    // Extend the previous record if it continues with the same location.
+                    match lines.last_mut() {
+                        Some(last)
+                            if current == Some((file, line))
+                                && last.address + last.size.unwrap_or(0) == address =>
+                        {
+                            last.size = Some(last.size.unwrap_or(0) + size);
+                        }
+                        _ => {
+                            current = Some((file, line));
+                            lines.push(LineInfo {
+                                address,
+                                size: Some(size),
+                                file: self.file(record.cu_offset, file),
+                                line: line as u64,
+                            });
+                        }
+                    }
+                }
+
+                if file_end >= end {
+                    break;
+                }
+                index += 1;
+            }
+        }
+
+        lines
+    }
+
+    /// Reads the function at the given index, with addresses relative to the load address.
+    fn function(&self, index: usize, load_address: u64) -> Result<Function<'data>, GoError> {
+        let mut record = self.func_record(index)?;
+        record.entry = record.entry.wrapping_sub(load_address);
+        record.end = record.end.wrapping_sub(load_address);
+
+        let name = match self.version {
+            GoVersion::Go12 => read_str(self.data, record.name_offset),
+            _ => read_str(self.funcnametab, record.name_offset),
+        };
+
+        Ok(Function {
+            address: record.entry,
+            size: record.end.saturating_sub(record.entry),
+            ranges: Vec::new(),
+            name: Name::new(name, NameMangling::Unmangled, Language::Go),
+            compilation_dir: Cow::Borrowed(b""),
+            lines: self.lines(&record).into(),
+            inlinees: Vec::new(),
+            inline: false,
+            artificial: false,
+            noreturn: false,
+            calling_convention: None,
+            call_sites: Vec::new(),
+            entity: None,
+        })
+    }
+}
+
+impl fmt::Debug for GoRuntimeInfo<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GoRuntimeInfo")
+            .field("version", &self.version)
+            .field("big_endian", &self.reader.big_endian)
+            .field("quantum", &self.quantum)
+            .field("ptr_size", &self.reader.ptr_size)
+            .field("nfunc", &self.nfunc)
+            .field("text_start", &format_args!("{:#x}", self.text_start))
+            .finish()
+    }
+}
+
+/// An iterator over functions in a [`GoRuntimeInfo`].
+///
+/// Reading stops after the first error.
+pub struct GoFunctionIterator<'data, 'a> {
+    info: &'a GoRuntimeInfo<'data>,
+    index: usize,
+    load_address: u64,
+    finished: bool,
+}
+
+impl<'data> Iterator for GoFunctionIterator<'data, '_> {
+    type Item = Result<Function<'data>, GoError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished || self.index >= self.info.nfunc {
+            return None;
+        }
+
+        let result = self.info.function(self.index, self.load_address);
+        self.index += 1;
+        self.finished = result.is_err();
+        Some(result)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.info.nfunc.saturating_sub(self.index);
+        (0, Some(remaining))
+    }
+}
+
+impl std::iter::FusedIterator for GoFunctionIterator<'_, '_> {}
+
+/// Debug session for the function and line tables of the Go runtime.
+///
+/// This is used for Go binaries without DWARF. There is no information on inlined functions.
+/// Returned by [`ElfObject::go_session`](crate::elf::ElfObject::go_session) and
+/// [`MachObject::go_session`](crate::macho::MachObject::go_session).
+pub struct GoDebugSession<'data> {
+    info: GoRuntimeInfo<'data>,
+    load_address: u64,
+    path_remapper: Option<PathRemapper>,
+    limits: SessionLimits,
+}
+
+impl<'data> GoDebugSession<'data> {
+    /// Parses the contents of a `pclntab` section.
+    ///
+    /// The `load_address` is subtracted from all addresses to make them relative to the object.
+    pub fn parse(data: &'data [u8], load_address: u64) -> Result<Self, GoError> {
+        Ok(GoDebugSession {
+            info: GoRuntimeInfo::parse(data)?,
+            load_address,
+            path_remapper: None,
+            limits: SessionLimits::default(),
+        })
+    }
+
+    /// Returns the underlying function and line tables.
+    pub fn runtime_info(&self) -> &GoRuntimeInfo<'data> {
+        &self.info
+    }
+
+    /// Sets a [`PathRemapper`] applied to all file paths in this debug session.
+    ///
+    /// Remapping is disabled by default.
+    pub fn set_path_remapper(&mut self, remapper: Option<PathRemapper>) {
+        self.path_remapper = remapper;
+    }
+
+    /// Sets the [`SessionLimits`] enforced by the function iterators of this session.
+    ///
+    /// There are no limits by default.
+    pub fn set_limits(&mut self, limits: SessionLimits) {
+        self.limits = limits;
+    }
+
+    /// Returns an iterator over all functions in this debug file.
+    pub fn functions(&self) -> GoSessionFunctionIterator<'_> {
+        GoSessionFunctionIterator {
+            functions: GoFunctionIterator {
+                load_address: self.load_address,
+                ..self.info.functions()
+            },
+            remapper: self.path_remapper.as_ref(),
+            limits: LimitTracker::new(self.limits),
+        }
+    }
+
+    /// Returns an iterator over all source files referenced by this debug file.
+    pub fn files(&self) -> GoFileIterator<'_> {
+        let files: Vec<_> = self
+            .info
+            .files()
+            .into_iter()
+            .map(|info| {
+                Ok(FileEntry {
+                    compilation_dir: Cow::Borrowed(b""),
+                    info: info.remap(self.path_remapper.as_ref()),
+                })
+            })
+            .collect();
+
+        files.into_iter()
+    }
+
+    /// Looks up a file's source contents by its full canonicalized path.
+    ///
+    /// The given path must be canonicalized.
+    pub fn source_by_path(&self, _path: &str) -> Result<Option<Cow<'_, str>>, GoError> {
+        Ok(None)
+    }
+}
+
+impl<'session> DebugSession<'session> for GoDebugSession<'_> {
+    type Error = GoError;
+    type FunctionIterator = GoSessionFunctionIterator<'session>;
+    type FileIterator = GoFileIterator<'session>;
+
+    fn functions(&'session self) -> Self::FunctionIterator {
+        self.functions()
+    }
+
+    fn files(&'session self) -> Self::FileIterator {
+        self.files()
+    }
+
+    fn source_by_path(&self, path: &str) -> Result<Option<Cow<'_, str>>, Self::Error> {
+        self.source_by_path(path)
+    }
+}
+
+/// An iterator over functions in a [`GoDebugSession`].
+pub struct GoSessionFunctionIterator<'s> {
+    functions: GoFunctionIterator<'s, 's>,
+    remapper: Option<&'s PathRemapper>,
+    limits: LimitTracker,
+}
+
+impl<'s> Iterator for GoSessionFunctionIterator<'s> {
+    type Item = Result<Function<'s>, GoError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.limits.is_exceeded() {
+            return None;
+        }
+
+        let function = match self.functions.next()? {
+            Ok(function) => function.remap(self.remapper),
+            Err(error) => return Some(Err(error)),
+        };
+
+        Some(match self.limits.track(&function) {
+            Ok(()) => Ok(function),
+            Err(error) => Err(error.into()),
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.functions.size_hint();
+        if self.limits.has_limits() {
+            (0, upper)
+        } else {
+            (lower, upper)
+        }
+    }
+}
+
+impl std::iter::FusedIterator for GoSessionFunctionIterator<'_> {}
+
+/// An iterator over source files referenced by a [`GoDebugSession`].
+pub type GoFileIterator<'s> = std::vec::IntoIter<Result<FileEntry<'s>, GoError>>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use similar_asserts::assert_eq;
+
+    /// Appends integers to a buffer with the given byte order.
+    struct Writer {
+        data: Vec<u8>,
+        big_endian: bool,
+    }
+
+    impl Writer {
+        fn new(big_endian: bool) -> Self {
+            Writer {
+                data: Vec::new(),
+                big_endian,
+            }
+        }
+
+        fn u32(&mut self, value: u32) {
+            let bytes = match self.big_endian {
+                true => value.to_be_bytes(),
+                false => value.to_le_bytes(),
+            };
+            self.data.extend_from_slice(&bytes);
+        }
+
+        fn u64(&mut self, value: u64) {
+            let bytes = match self.big_endian {
+                true => value.to_be_bytes(),
+                false => value.to_le_bytes(),
+            };
+            self.data.extend_from_slice(&bytes);
+        }
+
+        fn bytes(&mut self, bytes: &[u8]) -> usize {
+            let offset = self.data.len();
+            self.data.extend_from_slice(bytes);
+            offset
+        }
+
+        fn patch_u32(&mut self, offset: usize, value: u32) {
+            let bytes = match self.big_endian {
+                true => value.to_be_bytes(),
+                false => value.to_le_bytes(),
+            };
+            self.data[offset..offset + 4].copy_from_slice(&bytes);
+        }
+    }
+
+    // Line tables of two functions. The first function covers 0x20 bytes with lines 10 and 11, and
+    // switches from file 0 to file 1 at offset 0x10. The second covers 0x10 bytes of file 1 with
+    // lines 5 and 3.
+    const MAIN_PCFILE: &[u8] = &[0x02, 0x10, 0x02, 0x10, 0x00];
+    const MAIN_PCLN: &[u8] = &[0x16, 0x08, 0x02, 0x18, 0x00];
+    const HELPER_PCLN: &[u8] = &[0x0c, 0x04, 0x03, 0x0c, 0x00];
+
+    fn lines(function: &Function<'_>) -> Vec<(u64, Option<u64>, String, u64)> {
+        function
+            .lines
+            .iter()
+            .map(|line| (line.address, line.size, line.file.path_str(), line.line))
+            .collect()
+    }
+
+    fn check_functions(info: &GoRuntimeInfo<'_>, base: u64) {
+        let functions = info.functions().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(functions.len(), 2);
+
+        let main = &functions[0];
+        assert_eq!(main.name, "main.main");
+        assert_eq!(main.name.language(), Language::Go);
+        assert_eq!((main.address, main.size), (base, 0x20));
+        assert_eq!(
+            lines(main),
+            [
+                (base, Some(0x8), "/src/main.go".to_owned(), 10),
+                (base + 0x8, Some(0x8), "/src/main.go".to_owned(), 11),
+                (base + 0x10, Some(0x10), "/src/util.go".to_owned(), 11),
+            ]
+        );
+
+        let helper = &functions[1];
+        assert_eq!(helper.name, "main.helper");
+        assert_eq!((helper.address, helper.size), (base + 0x20, 0x10));
+        assert_eq!(
+            lines(helper),
+            [
+                (base + 0x20, Some(0x4), "/src/util.go".to_owned(), 5),
+                (base + 0x24, Some(0xc), "/src/util.go".to_owned(), 3),
+            ]
+        );
+    }
+
+    /// Builds a table in the format of Go 1.20 for a 64-bit little-endian binary.
+    fn go120_table(text_start: u64) -> Vec<u8> {
+        let mut w = Writer::new(false);
+        w.u32(MAGIC_GO120);
+        w.bytes(&[0, 0, 1, 8]);
+
+        let fields = w.data.len();
+        for _ in 0..8 {
+            w.u64(0);
+        }
+
+        let funcnametab = w.bytes(b"main.main\0main.helper\0");
+        let cutab = w.data.len();
+        w.u32(0);
+        w.u32(13);
+        let filetab = w.bytes(b"/src/main.go\0/src/util.go\0");
+        let pctab = w.bytes(&[0]);
+        let main_pcfile = w.bytes(MAIN_PCFILE) - pctab;
+        let main_pcln = w.bytes(MAIN_PCLN) - pctab;
+        // The second function only refers to the second file.
+        let helper_pcfile = w.bytes(&[0x04, 0x10, 0x00]) - pctab;
+        let helper_pcln = w.bytes(HELPER_PCLN) - pctab;
+
+        let functab = w.data.len();
+        for entry in [0, 0, 0x20, 0] {
+            w.u32(entry);
+        }
+        w.u32(0x30);
+
+        let records = [
+            (0, 0, main_pcfile, main_pcln),
+            (0x20, 10, helper_pcfile, helper_pcln),
+        ];
+        for (index, (entry, name, pcfile, pcln)) in records.iter().enumerate() {
+            let offset = w.data.len() - functab;
+            w.patch_u32(functab + index * 8 + 4, offset as u32);
+            for value in [
+                *entry,
+                *name,
+                0,
+                0,
+                0,
+                *pcfile as u32,
+                *pcln as u32,
+                0,
+                0,
+                0,
+            ] {
+                w.u32(value);
+            }
+        }
+
+        let header = [
+            2,
+            2,
+            text_start,
+            funcnametab as u64,
+            cutab as u64,
+            filetab as u64,
+            pctab as u64,
+            functab as u64,
+        ];
+        for (index, value) in header.iter().enumerate() {
+            let offset = fields + index * 8;
+            w.data[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+        }
+
+        w.data
+    }
+
+    #[test]
+    fn test_go120() {
+        let data = go120_table(0x40_1000);
+        let info = GoRuntimeInfo::parse(&data).unwrap();
+        assert_eq!(info.version(), GoVersion::Go120);
+        assert_eq!(info.ptr_size(), 8);
+        assert_eq!(info.quantum(), 1);
+        assert_eq!(info.function_count(), 2);
+        check_functions(&info, 0x40_1000);
+
+        let files: Vec<_> = info.files().iter().map(|f| f.path_str()).collect();
+        assert_eq!(files, ["/src/main.go", "/src/util.go"]);
+    }
+
+    #[test]
+    fn test_go12_big_endian() {
+        // Go 1.2 stores absolute addresses and offsets relative to the start of the table.
+        let mut w = Writer::new(true);
+        w.u32(MAGIC_GO12);
+        w.bytes(&[0, 0, 4, 4]);
+        w.u32(2);
+
+        let functab = w.data.len();
+        for entry in [0x1_0000, 0, 0x1_0020, 0] {
+            w.u32(entry);
+        }
+        w.u32(0x1_0030);
+        let filetab_field = w.data.len();
+        w.u32(0);
+
+        let names = w.bytes(b"main.main\0main.helper\0");
+        let files = w.bytes(b"/src/main.go\0/src/util.go\0") as u32;
+        let filetab = w.data.len();
+        for value in [3, files, files + 13] {
+            w.u32(value);
+        }
+        w.patch_u32(filetab_field, filetab as u32);
+
+        // File numbers start at 1, and the quantum of 4 scales all address deltas.
+        let main_pcfile = w.bytes(&[0x04, 0x04, 0x02, 0x04, 0x00]);
+        let main_pcln = w.bytes(&[0x16, 0x02, 0x02, 0x06, 0x00]);
+        let helper_pcfile = w.bytes(&[0x06, 0x04, 0x00]);
+        let helper_pcln = w.bytes(&[0x0c, 0x01, 0x03, 0x03, 0x00]);
+
+        let records = [
+            (0x1_0000, names, main_pcfile, main_pcln),
+            (0x1_0020, names + 10, helper_pcfile, helper_pcln),
+        ];
+        for (index, (entry, name, pcfile, pcln)) in records.iter().enumerate() {
+            let offset = w.data.len();
+            w.patch_u32(functab + index * 8 + 4, offset as u32);
+            for value in [
+                *entry,
+                *name as u32,
+                0,
+                0,
+                0,
+                *pcfile as u32,
+                *pcln as u32,
+                0,
+                0,
+            ] {
+                w.u32(value);
+            }
+        }
+
+        let info = GoRuntimeInfo::parse(&w.data).unwrap();
+        assert_eq!(info.version(), GoVersion::Go12);
+        assert_eq!(info.ptr_size(), 4);
+        check_functions(&info, 0x1_0000);
+    }
+
+    #[test]
+    fn test_session_load_address() {
+        let data = go120_table(0x40_1000);
+        let session = GoDebugSession::parse(&data, 0x40_0000).unwrap();
+        let functions = session.functions().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(functions[0].address, 0x1000);
+        assert_eq!(functions[0].lines.first().unwrap().address, 0x1000);
+        assert_eq!(functions[1].address, 0x1020);
+
+        let files = session.files().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].abs_path_str(), "/src/main.go");
+    }
+
+    #[test]
+    fn test_invalid() {
+        let error = GoRuntimeInfo::parse(b"\xfb\xff\xff\xff\x00\x00\x03\x08").unwrap_err();
+        assert_eq!(error.kind(), GoErrorKind::InvalidHeader);
+        assert!(!GoRuntimeInfo::test(b"\x7fELF"));
+
+        // A function table that points past the end of the section.
+        let mut data = go120_table(0);
+        data.truncate(data.len() - 8);
+        let info = GoRuntimeInfo::parse(&data).unwrap();
+        let mut functions = info.functions();
+        assert!(functions.next().unwrap().is_ok());
+        let error = functions.next().unwrap().unwrap_err();
+        assert_eq!(error.kind(), GoErrorKind::OutOfBounds);
+        assert!(functions.next().is_none());
+    }
+}
@@ -36,6 +36,7 @@
 #![warn(missing_docs)]
 
 mod base;
+mod lookup;
 #[cfg(all(
     feature = "breakpad",
     feature = "dwarf",
@@ -48,12 +49,34 @@ mod base;
 mod object;
 mod shared;
 
+#[cfg(all(
+    feature = "breakpad",
+    feature = "dwarf",
+    feature = "elf",
+    feature = "macho",
+    feature = "ms",
+    feature = "sourcebundle",
+    feature = "wasm"
+))]
+pub mod ar;
 #[cfg(feature = "breakpad")]
 pub mod breakpad;
+#[cfg(all(
+    feature = "breakpad",
+    feature = "dwarf",
+    feature = "elf",
+    feature = "macho",
+    feature = "ms",
+    feature = "sourcebundle",
+    feature = "wasm"
+))]
+pub mod debuginfod;
 #[cfg(feature = "dwarf")]
 pub mod dwarf;
 #[cfg(feature = "elf")]
 pub mod elf;
+#[cfg(any(feature = "elf", feature = "macho"))]
+pub mod go;
 #[cfg(feature = "macho")]
 pub mod macho;
 #[cfg(feature = "ms")]
@@ -62,10 +85,25 @@ pub mod pdb;
 pub mod pe;
 #[cfg(feature = "sourcebundle")]
 pub mod sourcebundle;
+#[cfg(any(feature = "ms", feature = "sourcebundle"))]
+pub mod sourcelink;
+#[cfg(feature = "ms")]
+pub mod srcsrv;
+#[cfg(all(
+    feature = "breakpad",
+    feature = "dwarf",
+    feature = "elf",
+    feature = "macho",
+    feature = "ms",
+    feature = "sourcebundle",
+    feature = "wasm"
+))]
+pub mod symsource;
 #[cfg(feature = "wasm")]
 pub mod wasm;
 
 pub use crate::base::*;
+pub use crate::lookup::*;
 #[cfg(all(
     feature = "breakpad",
     feature = "dwarf",
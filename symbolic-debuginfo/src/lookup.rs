@@ -0,0 +1,179 @@
+//! An owned index for repeated address lookups without building a symcache.
+
+use std::borrow::Cow;
+use std::fmt;
+
+use symbolic_common::Name;
+
+use crate::base::*;
+
+/// A frame resolved by [`ObjectLookup::lookup`].
+pub struct LookupFrame<'a> {
+    /// The name of the function or symbol covering the address.
+    pub name: Name<'a>,
+    /// The start address of the function or symbol.
+    pub function_address: u64,
+    /// The source file of the address, if line information is available.
+    pub file: Option<FileEntry<'a>>,
+    /// The line number of the address, or zero if unknown.
+    pub line: u64,
+    /// Specifies whether this frame was inlined into the next frame.
+    pub inline: bool,
+}
+
+impl fmt::Debug for LookupFrame<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LookupFrame")
+            .field("name", &self.name)
+            .field(
+                "function_address",
+                &format_args!("{:#x}", self.function_address),
+            )
+            .field("file", &self.file)
+            .field("line", &self.line)
+            .field("inline", &self.inline)
+            .finish()
+    }
+}
+
+/// An owned index of the symbols and functions of an object for repeated address lookups.
+///
+/// Building a SymCache requires writing and parsing a file, which is wasteful for tools that
+/// symbolicate a few addresses interactively. This index instead keeps the symbol table and all
+/// functions of an object in memory, sorted by address. Since it does not borrow from the object,
+/// the object's data can be dropped after creating the index.
+///
+/// Use [`Object::make_lookup`](crate::Object::make_lookup) to create an index for an object.
+#[derive(Clone, Debug, Default)]
+pub struct ObjectLookup {
+    symbols: SymbolMap<'static>,
+    functions: Vec<Function<'static>>,
+    max_ends: Vec<u64>,
+}
+
+impl ObjectLookup {
+    /// Creates an index from a symbol table and the functions of a debug session.
+    ///
+    /// Functions take precedence over symbols when looking up addresses. Inlinees of the functions
+    /// are resolved as additional frames.
+    pub fn new(symbols: SymbolMap<'static>, mut functions: Vec<Function<'static>>) -> Self {
+        functions.sort_by_key(|function| function.address);
+
+        let max_ends = functions
+            .iter()
+            .scan(0, |max_end, function| {
+                *max_end = function.ranges_end().max(*max_end);
+                Some(*max_end)
+            })
+            .collect();
+
+        ObjectLookup {
+            symbols,
+            functions,
+            max_ends,
+        }
+    }
+
+    /// Returns `true` if this index contains functions with line information.
+    pub fn has_line_info(&self) -> bool {
+        !self.functions.is_empty()
+    }
+
+    /// Returns the number of symbols in this index.
+    pub fn symbol_count(&self) -> usize {
+        self.symbols.len()
+    }
+
+    /// Returns the number of top-level functions in this index.
+    pub fn function_count(&self) -> usize {
+        self.functions.len()
+    }
+
+    /// Resolves the frames at the given relative address.
+    ///
+    /// If a function covers the address, this returns a frame for the function and one for every
+    /// inlinee containing the address, starting with the innermost inlinee. Otherwise, this returns
+    /// a single frame for the symbol covering the address without line information. If neither is
+    /// found, the returned list is empty.
+    pub fn lookup(&self, address: u64) -> Vec<LookupFrame<'_>> {
+        let mut function = match self.function_at(address) {
+            Some(function) => function,
+            None => return self.symbol_frame(address).into_iter().collect(),
+        };
+
+        let mut frames = vec![function_frame(function, address)];
+        while let Some(inlinee) = function
+            .inlinees
+            .iter()
+            .find(|inlinee| inlinee.contains(address))
+        {
+            frames.push(function_frame(inlinee, address));
+            function = inlinee;
+        }
+
+        frames.reverse();
+        frames
+    }
+
+    /// Returns the top-level function covering the address.
+    fn function_at(&self, address: u64) -> Option<&Function<'static>> {
+        let count = self
+            .functions
+            .partition_point(|function| function.address <= address);
+
+        // Walking backwards, stop as soon as no preceding function reaches beyond the address.
+        self.functions[..count]
+            .iter()
+            .zip(&self.max_ends)
+            .rev()
+            .take_while(|(_, max_end)| **max_end > address)
+            .map(|(function, _)| function)
+            .find(|function| function.contains(address))
+    }
+
+    /// Creates a frame for the symbol covering the address.
+    fn symbol_frame(&self, address: u64) -> Option<LookupFrame<'_>> {
+        let symbol = self.symbols.lookup(address)?;
+
+        Some(LookupFrame {
+            name: Name::from(symbol.name()?),
+            function_address: symbol.address,
+            file: None,
+            line: 0,
+            inline: false,
+        })
+    }
+}
+
+/// Creates a frame for a function with the line record covering the address.
+fn function_frame<'a>(function: &'a Function<'static>, address: u64) -> LookupFrame<'a> {
+    // Line records are not necessarily sorted, so pick the closest record before the address.
+    let line = function
+        .lines
+        .iter()
+        .filter(|line| match line.size {
+            Some(size) => line.address <= address && address < line.address.saturating_add(size),
+            None => line.address <= address,
+        })
+        .max_by_key(|line| line.address);
+
+    let name = &function.name;
+    let (file, line) = match line {
+        Some(line) => {
+            let entry = FileEntry {
+                compilation_dir: Cow::Borrowed(&function.compilation_dir),
+                info: line.file,
+            };
+            (Some(entry), line.line)
+        }
+        None => (None, 0),
+    };
+
+    LookupFrame {
+        name: Name::new(name.as_str(), name.mangling(), name.language()),
+        function_address: function.address,
+        file,
+        line,
+        inline: function.inline,
+    }
+}
@@ -1,5 +1,6 @@
 //! The Apple [`BcSymbolMap`] file format.
 
+use std::collections::BTreeMap;
 use std::error::Error;
 use std::fmt;
 use std::io::Cursor;
@@ -7,7 +8,7 @@ use std::iter::FusedIterator;
 use std::path::Path;
 
 use elementtree::Element;
-use symbolic_common::{AsSelf, DebugId, ParseDebugIdError};
+use symbolic_common::{AsSelf, ByteView, DebugId, ParseDebugIdError, SelfCell};
 use thiserror::Error;
 
 use super::SWIFT_HIDDEN_PREFIX;
@@ -33,6 +34,10 @@ enum BcSymbolMapErrorKind {
     InvalidHeader,
     /// The bitcode symbol map did contain invalid UTF-8.
     InvalidUtf8,
+    /// A file in a BCSymbolMap directory could not be read.
+    Io,
+    /// A UUID mapping PropertyList in a BCSymbolMap directory could not be parsed.
+    InvalidUuidMapping,
 }
 
 impl fmt::Display for BcSymbolMapErrorKind {
@@ -40,6 +45,8 @@ impl fmt::Display for BcSymbolMapErrorKind {
         match self {
             Self::InvalidHeader => write!(f, "no valid BCSymbolMap header was found"),
             Self::InvalidUtf8 => write!(f, "BCSymbolmap is not valid UTF-8"),
+            Self::Io => write!(f, "failed to read BCSymbolMap directory"),
+            Self::InvalidUuidMapping => write!(f, "invalid UUID mapping PropertyList"),
         }
     }
 }
@@ -66,6 +73,17 @@ pub struct BcSymbolMap<'d> {
     names: Vec<&'d str>,
 }
 
+impl BcSymbolMapError {
+    /// Creates a new BCSymbolMap error from a known kind of error and an arbitrary payload.
+    fn new<E>(kind: BcSymbolMapErrorKind, source: E) -> Self
+    where
+        E: Into<Box<dyn Error + Send + Sync>>,
+    {
+        let source = Some(source.into());
+        Self { kind, source }
+    }
+}
+
 impl From<BcSymbolMapErrorKind> for BcSymbolMapError {
     fn from(source: BcSymbolMapErrorKind) -> Self {
         Self {
@@ -179,12 +197,80 @@ impl<'d> BcSymbolMap<'d> {
         self.get(index)
     }
 
+    /// Returns the index of a name in this mapping.
+    ///
+    /// This is the reverse of [`BcSymbolMap::get`]. If the name occurs multiple times, the first
+    /// index is returned. This performs a linear search over all names.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use symbolic_debuginfo::macho::BcSymbolMap;
+    ///
+    /// // let data = std::fs::read("c8374b6d-6e96-34d8-ae38-efaa5fec424f.bcsymbolmap").unwrap();
+    /// # let data =
+    /// #     std::fs::read("tests/fixtures/c8374b6d-6e96-34d8-ae38-efaa5fec424f.bcsymbolmap")
+    /// #         .unwrap();
+    /// let map = BcSymbolMap::parse(&data).unwrap();
+    ///
+    /// assert_eq!(map.index_of("Sources/Sentry/Public/SentryMessage.h"), Some(43));
+    /// assert_eq!(map.index_of("not in the map"), None);
+    /// ```
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|candidate| *candidate == name)
+    }
+
+    /// Returns the obfuscated `__hidden#NNN_` placeholder of a name in this mapping.
+    ///
+    /// This is the reverse of [`BcSymbolMap::resolve`]. Returns `None` if the name is not
+    /// contained in this mapping.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use symbolic_debuginfo::macho::BcSymbolMap;
+    ///
+    /// // let data = std::fs::read("c8374b6d-6e96-34d8-ae38-efaa5fec424f.bcsymbolmap").unwrap();
+    /// # let data =
+    /// #     std::fs::read("tests/fixtures/c8374b6d-6e96-34d8-ae38-efaa5fec424f.bcsymbolmap")
+    /// #         .unwrap();
+    /// let map = BcSymbolMap::parse(&data).unwrap();
+    ///
+    /// let placeholder = map.obfuscate("Sources/Sentry/Public/SentryMessage.h").unwrap();
+    /// assert_eq!(placeholder, "__hidden#43_");
+    /// assert_eq!(map.resolve(&placeholder), "Sources/Sentry/Public/SentryMessage.h");
+    /// ```
+    pub fn obfuscate(&self, name: &str) -> Option<String> {
+        let index = self.index_of(name)?;
+        Some(format!("{}{}_", SWIFT_HIDDEN_PREFIX, index))
+    }
+
+    /// Returns the number of names in this bitcode symbol map.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Returns `true` if this bitcode symbol map does not contain any names.
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
     /// Returns an iterator over all the names in this bitcode symbol map.
     pub fn iter(&self) -> BcSymbolMapIterator<'_, 'd> {
         BcSymbolMapIterator {
             iter: self.names.iter(),
         }
     }
+
+    /// Returns an iterator over all the names in this bitcode symbol map along with their index.
+    ///
+    /// The index of a name is the number used in its `__hidden#NNN_` placeholder, which allows to
+    /// audit or merge mappings.
+    pub fn entries(&self) -> BcSymbolMapEntries<'_, 'd> {
+        BcSymbolMapEntries {
+            iter: self.names.iter().enumerate(),
+        }
+    }
 }
 
 /// Iterator over the names in a [`BcSymbolMap`].
@@ -208,6 +294,27 @@ impl<'a, 'd> Iterator for BcSymbolMapIterator<'a, 'd> {
 
 impl FusedIterator for BcSymbolMapIterator<'_, '_> {}
 
+/// Iterator over the indices and names in a [`BcSymbolMap`].
+///
+/// This struct is created by [`BcSymbolMap::entries`].
+pub struct BcSymbolMapEntries<'a, 'd> {
+    iter: std::iter::Enumerate<std::slice::Iter<'a, &'d str>>,
+}
+
+impl<'a, 'd> Iterator for BcSymbolMapEntries<'a, 'd> {
+    type Item = (usize, &'d str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(index, name)| (index, *name))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl FusedIterator for BcSymbolMapEntries<'_, '_> {}
+
 /// Error for handling or creating a [`UuidMapping`].
 #[derive(Debug, Error)]
 #[error("{kind}")]
@@ -410,6 +517,136 @@ fn uuid_from_xml_plist(plist: Element) -> Option<String> {
     raw_original
 }
 
+/// An owned [`BcSymbolMap`] along with the buffer it was parsed from.
+type OwnedBcSymbolMap = SelfCell<ByteView<'static>, BcSymbolMap<'static>>;
+
+/// A collection of [`BcSymbolMap`]s and [`UuidMapping`]s keyed by UUID.
+///
+/// Xcode stores the symbol maps of an archive in a `BCSymbolMaps` directory, where every file is
+/// named after the UUID of the object it belongs to, for instance
+/// `c8374b6d-6e96-34d8-ae38-efaa5fec424f.bcsymbolmap`. When Apple recompiles bitcode, the dSYM
+/// receives a new UUID, and `dSYMs/<object-id>/Contents/Resources/<dsym-uuid>.plist` maps it back
+/// to the original UUID under which the symbol map is stored.
+///
+/// Use [`MachArchive::with_symbolmaps`](crate::macho::MachArchive::with_symbolmaps) to load the
+/// matching symbol map into every object of an archive.
+#[derive(Default)]
+pub struct BcSymbolMapDirectory {
+    symbolmaps: BTreeMap<DebugId, OwnedBcSymbolMap>,
+    mappings: BTreeMap<DebugId, DebugId>,
+}
+
+impl BcSymbolMapDirectory {
+    /// Creates an empty collection.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads all symbol maps and UUID mappings from the given directory.
+    ///
+    /// See [`load_dir`](Self::load_dir) for the files that are considered.
+    pub fn open<P>(path: P) -> Result<Self, BcSymbolMapError>
+    where
+        P: AsRef<Path>,
+    {
+        let mut directory = Self::new();
+        directory.load_dir(path)?;
+        Ok(directory)
+    }
+
+    /// Loads all symbol maps and UUID mappings from the given directory.
+    ///
+    /// Files named `<uuid>.bcsymbolmap` are loaded as symbol maps, and files named `<uuid>.plist`
+    /// as UUID mappings of the dSYM with that UUID. All other files, such as `Info.plist`, are
+    /// ignored. This can be called repeatedly, for instance with the `BCSymbolMaps` directory of
+    /// an Xcode archive and the `Contents/Resources` directory of a dSYM.
+    pub fn load_dir<P>(&mut self, path: P) -> Result<(), BcSymbolMapError>
+    where
+        P: AsRef<Path>,
+    {
+        let entries = std::fs::read_dir(path)
+            .map_err(|e| BcSymbolMapError::new(BcSymbolMapErrorKind::Io, e))?;
+
+        for entry in entries {
+            let path = entry
+                .map_err(|e| BcSymbolMapError::new(BcSymbolMapErrorKind::Io, e))?
+                .path();
+
+            let uuid = match path.file_stem().and_then(|s| s.to_str()) {
+                Some(stem) => match stem.parse::<DebugId>() {
+                    Ok(uuid) => uuid,
+                    Err(_) => continue,
+                },
+                None => continue,
+            };
+
+            let extension = path.extension().and_then(|e| e.to_str());
+            if extension == Some("bcsymbolmap") {
+                let data = ByteView::open(&path)
+                    .map_err(|e| BcSymbolMapError::new(BcSymbolMapErrorKind::Io, e))?;
+                self.add_symbolmap(uuid, data)?;
+            } else if extension == Some("plist") {
+                let data = std::fs::read(&path)
+                    .map_err(|e| BcSymbolMapError::new(BcSymbolMapErrorKind::Io, e))?;
+                let mapping = UuidMapping::parse_plist(uuid, &data).map_err(|e| {
+                    BcSymbolMapError::new(BcSymbolMapErrorKind::InvalidUuidMapping, e)
+                })?;
+                self.add_uuid_mapping(mapping);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Parses and adds the symbol map for the object with the given UUID.
+    pub fn add_symbolmap(
+        &mut self,
+        uuid: DebugId,
+        data: ByteView<'static>,
+    ) -> Result<(), BcSymbolMapError> {
+        let symbolmap = SelfCell::try_new(data, |data| BcSymbolMap::parse(unsafe { &*data }))?;
+        self.symbolmaps.insert(uuid, symbolmap);
+        Ok(())
+    }
+
+    /// Adds a mapping from a dSYM UUID to the UUID of the original object.
+    pub fn add_uuid_mapping(&mut self, mapping: UuidMapping) {
+        self.mappings
+            .insert(mapping.dsym_uuid(), mapping.original_uuid());
+    }
+
+    /// Returns the symbol map for the object with the given debug identifier.
+    ///
+    /// If there is a [`UuidMapping`] for the identifier, the symbol map of the original UUID is
+    /// preferred. Otherwise, the symbol map stored under the identifier itself is returned.
+    pub fn get(&self, debug_id: DebugId) -> Option<&BcSymbolMap<'_>> {
+        self.mappings
+            .get(&debug_id)
+            .and_then(|original| self.symbolmaps.get(original))
+            .or_else(|| self.symbolmaps.get(&debug_id))
+            .map(|symbolmap| symbolmap.get())
+    }
+
+    /// Returns the number of symbol maps in this collection.
+    pub fn len(&self) -> usize {
+        self.symbolmaps.len()
+    }
+
+    /// Returns `true` if this collection contains no symbol maps.
+    pub fn is_empty(&self) -> bool {
+        self.symbolmaps.is_empty()
+    }
+}
+
+impl fmt::Debug for BcSymbolMapDirectory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BcSymbolMapDirectory")
+            .field("symbolmaps", &self.symbolmaps.keys().collect::<Vec<_>>())
+            .field("mappings", &self.mappings)
+            .finish()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -434,6 +671,30 @@ mod tests {
         assert_eq!(map.get(2), Some("-[SentryMessage serialize]"))
     }
 
+    #[test]
+    fn test_entries() {
+        let data = std::fs::read_to_string(
+            "tests/fixtures/c8374b6d-6e96-34d8-ae38-efaa5fec424f.bcsymbolmap",
+        )
+        .unwrap();
+        let map = BcSymbolMap::parse(data.as_bytes()).unwrap();
+
+        assert_eq!(map.entries().count(), map.len());
+        for (index, name) in map.entries() {
+            assert_eq!(map.get(index), Some(name));
+            // Names may be listed more than once, in which case the first index is found.
+            let first = map.index_of(name).unwrap();
+            assert!(first <= index);
+            assert_eq!(map.get(first), Some(name));
+        }
+
+        let (index, name) = map.entries().nth(2).unwrap();
+        assert_eq!(index, 2);
+        assert_eq!(name, "-[SentryMessage serialize]");
+        assert_eq!(map.obfuscate(name).as_deref(), Some("__hidden#2_"));
+        assert_eq!(map.obfuscate("__not_contained__"), None);
+    }
+
     #[test]
     fn test_iter() {
         let data = std::fs::read_to_string(
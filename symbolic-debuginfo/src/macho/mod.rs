@@ -1,29 +1,59 @@
 //! Support for Mach Objects, used on macOS and iOS.
 
 use std::borrow::Cow;
+use std::collections::{BTreeSet, HashSet};
 use std::error::Error;
 use std::fmt;
 use std::sync::Arc;
 
 use goblin::mach;
+use scroll::Pread;
 use smallvec::SmallVec;
 use thiserror::Error;
 
-use symbolic_common::{Arch, AsSelf, CodeId, DebugId, Uuid};
+use symbolic_common::{Arch, AsSelf, CodeId, DebugId, TypedCodeId, Uuid};
 
 use crate::base::*;
-use crate::dwarf::{Dwarf, DwarfDebugSession, DwarfError, DwarfSection, Endian};
+use crate::dwarf::{AddressRelocation, Dwarf, DwarfDebugSession, DwarfError, DwarfSection, Endian};
+use crate::go::{GoDebugSession, GoError};
 use crate::shared::{MonoArchive, MonoArchiveObjects, Parse};
 
 mod bcsymbolmap;
 pub mod compact;
+mod stabs;
+mod strip;
 
 pub use bcsymbolmap::*;
 pub use compact::*;
+pub use stabs::*;
 
 /// Prefix for hidden symbols from Apple BCSymbolMap builds.
 const SWIFT_HIDDEN_PREFIX: &str = "__hidden#";
 
+/// The bits of an arm64e pointer that hold the address.
+///
+/// The remaining upper bits of signed pointers, such as return addresses in stack traces, contain
+/// a pointer authentication code that must be stripped before symbolication.
+const ARM64E_ADDRESS_MASK: u64 = 0x0000_000f_ffff_ffff;
+
+/// The bit set in chained fixup pointers that bind to a symbol in another image.
+const CHAINED_PTR_BIND: u64 = 1 << 63;
+
+/// The maximum nesting depth of nodes in the dyld export trie.
+///
+/// Every edge of the trie consumes at least one character of a symbol name, so this bounds the
+/// length of the exported names that can be read.
+const MAX_EXPORT_TRIE_DEPTH: usize = 4096;
+
+/// Section type of module initializer function pointers.
+const S_MOD_INIT_FUNC_POINTERS: u32 = 0x9;
+
+/// Section type of module terminator function pointers.
+const S_MOD_TERM_FUNC_POINTERS: u32 = 0xa;
+
+/// Section type of 32-bit module initializer offsets relative to the start of the image.
+const S_INIT_FUNC_OFFSETS: u32 = 0x16;
+
 /// An error when dealing with [`MachObject`](struct.MachObject.html).
 #[derive(Debug, Error)]
 #[error("invalid MachO file")]
@@ -49,6 +79,12 @@ impl From<goblin::error::Error> for MachError {
     }
 }
 
+impl From<LimitExceeded> for MachError {
+    fn from(e: LimitExceeded) -> Self {
+        Self::new(e)
+    }
+}
+
 impl From<scroll::Error> for MachError {
     fn from(e: scroll::Error) -> Self {
         Self::new(e)
@@ -167,7 +203,7 @@ impl<'d> MachObject<'d> {
     /// header. This UUID is generated at compile / link time and is usually unique per compilation.
     pub fn code_id(&self) -> Option<CodeId> {
         let uuid = self.find_uuid()?;
-        Some(CodeId::from_binary(&uuid.as_bytes()[..]))
+        Some(TypedCodeId::MachO(uuid).into())
     }
 
     /// The debug information identifier of a MachO file.
@@ -239,6 +275,14 @@ impl<'d> MachObject<'d> {
     /// load address, so that the caller only has to deal with addresses relative to the actual
     /// start of the image.
     pub fn load_address(&self) -> u64 {
+        self.vmaddr()
+    }
+
+    /// The virtual memory address of the `__TEXT` segment, or `0` if there is no such segment.
+    ///
+    /// This is the preferred load address of the image, see [`load_address`](Self::load_address).
+    /// At runtime, the image is loaded at this address plus a random slide.
+    pub fn vmaddr(&self) -> u64 {
         for seg in &self.macho.segments {
             if seg.name().map(|name| name == "__TEXT").unwrap_or(false) {
                 return seg.vmaddr;
@@ -248,6 +292,29 @@ impl<'d> MachObject<'d> {
         0
     }
 
+    /// Computes the slide of this image from the address it was loaded at in a process.
+    ///
+    /// The slide is the distance between the actual `image_address`, as reported by dyld or a crash
+    /// report, and the preferred [`vmaddr`](Self::vmaddr).
+    pub fn slide(&self, image_address: u64) -> u64 {
+        image_address.wrapping_sub(self.vmaddr())
+    }
+
+    /// Converts an address in a running process into an address relative to this image.
+    ///
+    /// `slide` is the slide of the image in that process, see [`slide`](Self::slide). The
+    /// returned address can be used to look up `symbols` or functions of a `debug_session`. On
+    /// arm64e, pointer authentication codes are stripped from `address` first. Returns `None` if
+    /// the address lies before the start of the image.
+    pub fn rebase(&self, address: u64, slide: u64) -> Option<u64> {
+        let address = match self.arch() {
+            Arch::Arm64e => address & ARM64E_ADDRESS_MASK,
+            _ => address,
+        };
+
+        address.checked_sub(slide)?.checked_sub(self.vmaddr())
+    }
+
     /// Determines whether this object exposes a public symbol table.
     pub fn has_symbols(&self) -> bool {
         self.macho.symbols.is_some()
@@ -293,13 +360,339 @@ impl<'d> MachObject<'d> {
     }
 
     /// Returns an ordered map of symbols in the symbol table.
+    ///
+    /// Functions declared in the `LC_FUNCTION_STARTS` load command that are not covered by the
+    /// symbol table are added as symbols without a name. This retains correct function boundaries
+    /// in stripped binaries. See [`function_starts`](Self::function_starts).
     pub fn symbol_map(&self) -> SymbolMap<'d> {
-        self.symbols().collect()
+        let mut symbols = self.symbols().collect::<Vec<_>>();
+
+        let starts = self.function_starts();
+        if starts.is_empty() {
+            return self.with_code_sections(symbols.into());
+        }
+
+        let named = symbols
+            .iter()
+            .map(|symbol| symbol.address)
+            .collect::<BTreeSet<_>>();
+
+        // The size of the last function cannot be inferred from the next start address. Assume it
+        // extends to the end of its code section, unless a named symbol follows.
+        let text_end = self.text_section_end();
+        let last = starts.last().copied();
+
+        for address in starts {
+            if named.contains(&address) {
+                continue;
+            }
+
+            let size = match text_end {
+                Some(end) if Some(address) == last && named.range(address..).next().is_none() => {
+                    end.saturating_sub(address)
+                }
+                _ => 0,
+            };
+
+            symbols.push(Symbol {
+                name: None,
+                address,
+                size,
+            });
+        }
+
+        self.with_code_sections(symbols.into())
+    }
+
+    /// Adds the address ranges of sections containing instructions to a symbol map.
+    fn with_code_sections<'a>(&self, mut map: SymbolMap<'a>) -> SymbolMap<'a> {
+        let vmaddr = self.load_address();
+        let code =
+            mach::constants::S_ATTR_PURE_INSTRUCTIONS | mach::constants::S_ATTR_SOME_INSTRUCTIONS;
+
+        let sections = self
+            .macho
+            .segments
+            .iter()
+            // Stop at the first broken section header, since corrupt section counts would
+            // otherwise be iterated to the end.
+            .flat_map(|segment| segment.into_iter().map_while(Result::ok))
+            .filter(|(section, _data)| section.flags & code != 0 && section.addr >= vmaddr)
+            .map(|(section, _data)| {
+                let start = section.addr - vmaddr;
+                start..start.saturating_add(section.size)
+            });
+
+        map.set_sections(sections);
+        map
+    }
+
+    /// Returns the start addresses of all functions declared in the `LC_FUNCTION_STARTS` load
+    /// command, relative to the load address.
+    ///
+    /// The linker records the start of every function in this load command, which is retained
+    /// when the symbol table is stripped. Returns an empty list if the load command is missing or
+    /// malformed.
+    pub fn function_starts(&self) -> Vec<u64> {
+        let command = self
+            .macho
+            .load_commands
+            .iter()
+            .find_map(|cmd| match cmd.command {
+                mach::load_command::CommandVariant::FunctionStarts(ref command) => Some(command),
+                _ => None,
+            });
+
+        let command = match command {
+            Some(command) => command,
+            None => return Vec::new(),
+        };
+
+        let start = command.dataoff as usize;
+        let end = start.saturating_add(command.datasize as usize);
+        let data = match self.data.get(start..end) {
+            Some(data) => data,
+            None => return Vec::new(),
+        };
+
+        // The first entry is the offset of the first function from the start of the `__TEXT`
+        // segment, which is the load address. Every following entry is the delta to the previous
+        // function. The list is terminated by a zero delta.
+        let mut starts = Vec::new();
+        let mut address = 0u64;
+        let mut offset = 0;
+
+        while offset < data.len() {
+            let delta = match scroll::Uleb128::read(data, &mut offset) {
+                Ok(0) | Err(_) => break,
+                Ok(delta) => delta,
+            };
+
+            address = match address.checked_add(delta) {
+                Some(address) => address,
+                None => break,
+            };
+
+            starts.push(address);
+        }
+
+        starts
+    }
+
+    /// Returns the end address of the `__text` section relative to the load address.
+    fn text_section_end(&self) -> Option<u64> {
+        let vmaddr = self.load_address();
+
+        for segment in &self.macho.segments {
+            if segment.name().ok() != Some("__TEXT") {
+                continue;
+            }
+
+            for result in segment {
+                // Do not continue to iterate broken section headers, as `nsects` may be corrupt.
+                let (section, _data) = result.ok()?;
+                if section.name().ok() == Some("__text") {
+                    let end = section.addr.checked_add(section.size)?;
+                    return end.checked_sub(vmaddr);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Returns all symbols exported via the dyld export trie.
+    ///
+    /// Leading underscores are trimmed from symbol names, matching [`symbols`](Self::symbols).
+    ///
+    /// The trie is walked iteratively. Tries with cycles or nodes nested deeper than
+    /// `MAX_EXPORT_TRIE_DEPTH` are rejected as malformed. Re-exports as well as stubs and resolvers
+    /// have an address of `0`.
+    pub fn exports(&self) -> Result<Vec<Export<'d>>, MachError> {
+        let trie = match self.export_trie() {
+            Some(trie) => trie,
+            None => return Ok(Vec::new()),
+        };
+
+        parse_export_trie(trie)
+    }
+
+    /// Returns the data of the dyld export trie, if declared by a load command.
+    fn export_trie(&self) -> Option<&'d [u8]> {
+        let (offset, size) =
+            self.macho
+                .load_commands
+                .iter()
+                .rev()
+                .find_map(|cmd| match cmd.command {
+                    mach::load_command::CommandVariant::DyldInfo(ref command)
+                    | mach::load_command::CommandVariant::DyldInfoOnly(ref command) => {
+                        Some((command.export_off, command.export_size))
+                    }
+                    mach::load_command::CommandVariant::DyldExportsTrie(ref command) => {
+                        Some((command.dataoff, command.datasize))
+                    }
+                    _ => None,
+                })?;
+
+        let start = offset as usize;
+        self.data.get(start..start.checked_add(size as usize)?)
+    }
+
+    /// Returns all symbols imported from dynamic libraries via dyld bind opcodes.
+    ///
+    /// Leading underscores are trimmed from symbol names, matching [`symbols`](Self::symbols).
+    pub fn imports(&self) -> Result<Vec<Import<'d>>, MachError> {
+        let load_address = self.load_address();
+        let imports = self.macho.imports()?;
+
+        Ok(imports
+            .into_iter()
+            .map(|import| Import {
+                name: Cow::Owned(import.name.strip_prefix('_').unwrap_or(import.name).into()),
+                library: Some(Cow::Owned(import.dylib.into())),
+                address: import.address.checked_sub(load_address),
+            })
+            .collect())
+    }
+
+    /// Returns the address of the entry point relative to the load address.
+    ///
+    /// The entry point is declared by the `LC_MAIN` load command, or the initial thread state of
+    /// `LC_UNIXTHREAD` in older executables. Returns `None` for libraries and other objects without
+    /// an entry point.
+    pub fn entry_point(&self) -> Option<u64> {
+        match self.macho.entry {
+            0 => None,
+            entry => entry.checked_sub(self.load_address()),
+        }
+    }
+
+    /// Returns the addresses of all module initializers, relative to the load address.
+    ///
+    /// dyld calls these functions before the entry point of the executable or after loading a
+    /// library. They are declared in sections of type `S_MOD_INIT_FUNC_POINTERS`, or as offsets
+    /// in sections of type `S_INIT_FUNC_OFFSETS` by newer linkers.
+    pub fn initializers(&self) -> Vec<u64> {
+        self.function_pointers(S_MOD_INIT_FUNC_POINTERS)
+    }
+
+    /// Returns the addresses of all module terminators, relative to the load address.
+    ///
+    /// These functions are declared in sections of type `S_MOD_TERM_FUNC_POINTERS`.
+    pub fn terminators(&self) -> Vec<u64> {
+        self.function_pointers(S_MOD_TERM_FUNC_POINTERS)
+    }
+
+    /// Reads function pointers from all sections of the given type.
+    ///
+    /// Pointers encoded as chained fixups are reduced to their target. Binds to other images are
+    /// skipped along with null pointers.
+    fn function_pointers(&self, section_type: u32) -> Vec<u64> {
+        let load_addr = self.load_address();
+        let endian = if self.macho.little_endian {
+            scroll::LE
+        } else {
+            scroll::BE
+        };
+
+        let mut functions = Vec::new();
+        for segment in &self.macho.segments {
+            for section in segment.into_iter() {
+                // Do not continue to iterate broken section headers, as `nsects` may be corrupt.
+                let (header, data) = match section {
+                    Ok(section) => section,
+                    Err(_) => break,
+                };
+
+                let ty = header.flags & mach::constants::SECTION_TYPE;
+                if ty == S_INIT_FUNC_OFFSETS && section_type == S_MOD_INIT_FUNC_POINTERS {
+                    functions.extend(
+                        data.chunks_exact(4)
+                            .filter_map(|chunk| chunk.pread_with::<u32>(0, endian).ok())
+                            .map(u64::from),
+                    );
+                    continue;
+                }
+
+                if ty != section_type {
+                    continue;
+                }
+
+                for chunk in data.chunks_exact(if self.macho.is_64 { 8 } else { 4 }) {
+                    let pointer = if self.macho.is_64 {
+                        chunk.pread_with::<u64>(0, endian).unwrap_or(0)
+                    } else {
+                        u64::from(chunk.pread_with::<u32>(0, endian).unwrap_or(0))
+                    };
+
+                    // Chained fixups set the top bit for binds and store the target of rebases in
+                    // the low bits, either as address or as offset from the start of the image.
+                    if pointer == 0 || pointer & CHAINED_PTR_BIND != 0 {
+                        continue;
+                    }
+
+                    let target = if pointer & !ARM64E_ADDRESS_MASK != 0 {
+                        pointer & ARM64E_ADDRESS_MASK
+                    } else {
+                        pointer
+                    };
+
+                    functions.push(target.checked_sub(load_addr).unwrap_or(target));
+                }
+            }
+        }
+
+        functions
     }
 
     /// Determines whether this object contains debug information.
+    ///
+    /// STABS debugging information is not considered here, see [`has_stabs`](Self::has_stabs).
     pub fn has_debug_info(&self) -> bool {
-        self.has_section("debug_info")
+        self.has_section("debug_info") || self.has_go_pclntab()
+    }
+
+    /// Determines whether this object contains the function and line tables of the Go runtime.
+    ///
+    /// Go binaries retain these tables in the `__gopclntab` section even if DWARF has been stripped,
+    /// for instance with `-ldflags="-w"`. Use [`go_session`](Self::go_session) to read functions
+    /// from them.
+    pub fn has_go_pclntab(&self) -> bool {
+        self.has_section("gopclntab")
+    }
+
+    /// Constructs a debugging session for the function and line tables of the Go runtime.
+    ///
+    /// Returns an error if the object does not contain a valid `__gopclntab` section.
+    pub fn go_session(&self) -> Result<GoDebugSession<'d>, GoError> {
+        let data = match self.section("gopclntab") {
+            Some(DwarfSection {
+                data: Cow::Borrowed(data),
+                ..
+            }) => data,
+            _ => &[],
+        };
+
+        GoDebugSession::parse(data, self.load_address())
+    }
+
+    /// Determines whether this object declares functions with STABS entries in its symbol table.
+    ///
+    /// Symbol files from older toolchains only contain STABS instead of DWARF. Linked executables
+    /// also retain STABS entries as a debug map to the object files they were linked from. Use
+    /// [`stabs_session`](Self::stabs_session) to read functions from these entries.
+    pub fn has_stabs(&self) -> bool {
+        stabs::has_stab_functions(&self.macho)
+    }
+
+    /// Constructs a debugging session for STABS debugging information.
+    ///
+    /// The session yields one function for every `N_FUN` entry in the symbol table, including its
+    /// size, compilation directory and source file. Line records are only available if the object
+    /// contains `N_SLINE` entries. If the object does not contain any STABS, the session is empty.
+    pub fn stabs_session(&self) -> Result<StabsDebugSession<'d>, MachError> {
+        StabsDebugSession::parse(&self.macho, self.load_address(), self.bcsymbolmap.clone())
     }
 
     /// Constructs a debugging session.
@@ -309,19 +702,73 @@ impl<'d> MachObject<'d> {
     /// costly process, try to reuse the debugging session as long as possible.
     ///
     /// MachO files generally use DWARF debugging information, which is also used by ELF containers
-    /// on Linux.
+    /// on Linux. STABS debugging information is not considered by this session, see
+    /// [`stabs_session`](Self::stabs_session) instead.
     ///
     /// Constructing this session will also work if the object does not contain debugging
     /// information, in which case the session will be a no-op. This can be checked via
     /// [`has_debug_info`](struct.MachObject.html#method.has_debug_info).
     pub fn debug_session(&self) -> Result<DwarfDebugSession<'d>, DwarfError> {
-        let symbols = self.symbol_map();
-        let mut session =
-            DwarfDebugSession::parse(self, symbols, self.load_address() as i64, self.kind())?;
+        let mut symbols = self.symbol_map();
+        let relocation = self.address_relocation();
+
+        // Symbols in relocated segments must match the relocated addresses of functions.
+        if relocation.has_segments() {
+            let load_address = self.load_address();
+            symbols = symbols
+                .into_iter()
+                .map(|mut symbol| {
+                    symbol.address = relocation.relocate(symbol.address.wrapping_add(load_address));
+                    symbol
+                })
+                .collect();
+        }
+
+        let mut session = DwarfDebugSession::parse(self, symbols, relocation, self.kind())?;
         session.load_symbolmap(self.bcsymbolmap.clone());
         Ok(session)
     }
 
+    /// Constructs a debugging session that reports addresses of an image with the given slide.
+    ///
+    /// This is like [`debug_session`](Self::debug_session), except that addresses of functions and
+    /// lines are absolute addresses in the process the image was loaded into with `slide`. See
+    /// [`DwarfDebugSession::set_load_address`] for more information.
+    pub fn debug_session_with_slide(
+        &self,
+        slide: u64,
+    ) -> Result<DwarfDebugSession<'d>, DwarfError> {
+        let mut session = self.debug_session()?;
+        session.set_load_address(self.vmaddr().wrapping_add(slide));
+        Ok(session)
+    }
+
+    /// Returns the relocation of addresses in debug information to relative addresses.
+    ///
+    /// Usually, all segments are laid out contiguously after `__TEXT`, so that subtracting the load
+    /// address is sufficient. Dylibs extracted from the dyld shared cache, however, retain the
+    /// addresses of their segments in the cache, which have been slid independently. Executable
+    /// segments whose distance to `__TEXT` differs from their file offset are relocated based on
+    /// their file offset instead.
+    fn address_relocation(&self) -> AddressRelocation {
+        let load_address = self.load_address();
+        let mut relocation = AddressRelocation::new(load_address as i64);
+
+        for segment in &self.macho.segments {
+            // Segments in dSYMs have no file contents and retain the original layout.
+            if segment.filesize == 0 || segment.initprot & mach::constants::VM_PROT_EXECUTE == 0 {
+                continue;
+            }
+
+            if segment.vmaddr.wrapping_sub(load_address) != segment.fileoff {
+                let offset = segment.vmaddr.wrapping_sub(segment.fileoff) as i64;
+                relocation.add_segment(segment.vmaddr, segment.vmsize, offset);
+            }
+        }
+
+        relocation
+    }
+
     /// Determines whether this object contains stack unwinding information.
     pub fn has_unwind_info(&self) -> bool {
         self.has_section("eh_frame")
@@ -658,20 +1105,25 @@ enum MachObjectIteratorInner<'d, 'a> {
 }
 
 /// An iterator over objects in a [`MachArchive`](struct.MachArchive.html).
-pub struct MachObjectIterator<'d, 'a>(MachObjectIteratorInner<'d, 'a>);
+pub struct MachObjectIterator<'d, 'a> {
+    inner: MachObjectIteratorInner<'d, 'a>,
+    symbolmaps: Option<&'d BcSymbolMapDirectory>,
+}
 
 impl<'d, 'a> Iterator for MachObjectIterator<'d, 'a> {
     type Item = Result<MachObject<'d>, MachError>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        match self.0 {
+        let result = match self.inner {
             MachObjectIteratorInner::Single(ref mut iter) => iter.next(),
             MachObjectIteratorInner::Archive(ref mut iter) => iter.next(),
-        }
+        };
+
+        result.map(|result| result.map(|object| attach_symbolmap(object, self.symbolmaps)))
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        match self.0 {
+        match self.inner {
             MachObjectIteratorInner::Single(ref iter) => iter.size_hint(),
             MachObjectIteratorInner::Archive(ref iter) => iter.size_hint(),
         }
@@ -700,7 +1152,10 @@ enum MachArchiveInner<'d> {
 /// [`MachObject`]: struct.MachObject.html
 /// [`FatMachO`]: struct.FatMachO.html
 #[derive(Debug)]
-pub struct MachArchive<'d>(MachArchiveInner<'d>);
+pub struct MachArchive<'d> {
+    inner: MachArchiveInner<'d>,
+    symbolmaps: Option<&'d BcSymbolMapDirectory>,
+}
 
 impl<'d> MachArchive<'d> {
     /// Tests whether the buffer contains either a Mach Object or a Fat Mach Object.
@@ -745,26 +1200,67 @@ impl<'d> MachArchive<'d> {
 
     /// Tries to parse a Mach archive from the given slice.
     pub fn parse(data: &'d [u8]) -> Result<Self, MachError> {
-        Ok(Self(match Self::is_fat(data) {
+        let inner = match Self::is_fat(data) {
             Some(true) => MachArchiveInner::Archive(FatMachO::parse(data)?),
             // Fall back to mach parsing to receive a meaningful error message from goblin
             _ => MachArchiveInner::Single(MonoArchive::new(data)),
-        }))
+        };
+
+        Ok(Self {
+            inner,
+            symbolmaps: None,
+        })
+    }
+
+    /// Loads matching [`BcSymbolMap`]s into all objects of this archive.
+    ///
+    /// Every object returned by [`objects`](Self::objects) and
+    /// [`object_by_index`](Self::object_by_index) receives the symbol map that
+    /// [`BcSymbolMapDirectory::get`] returns for its debug identifier, if any. This resolves
+    /// hidden symbols of bitcode builds without having to match symbol maps to the objects of a
+    /// fat archive by hand.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use symbolic_debuginfo::macho::{BcSymbolMapDirectory, MachArchive};
+    ///
+    /// // let symbolmaps = BcSymbolMapDirectory::open("BCSymbolMaps").unwrap();
+    /// # let symbolmaps = BcSymbolMapDirectory::open("tests/fixtures").unwrap();
+    /// // let data = std::fs::read("dSYMs/.../Resources/DWARF/object").unwrap();
+    /// # let data =
+    /// #     std::fs::read("tests/fixtures/2d10c42f-591d-3265-b147-78ba0868073f.dwarf-hidden")
+    /// #         .unwrap();
+    /// let archive = MachArchive::parse(&data).unwrap().with_symbolmaps(&symbolmaps);
+    ///
+    /// let object = archive.objects().next().unwrap().unwrap();
+    /// let map = object.symbol_map();
+    /// let symbol = map.lookup(0x5a74).unwrap();
+    /// assert_eq!(symbol.name(), Some("-[SentryMessage initWithFormatted:]"));
+    /// ```
+    pub fn with_symbolmaps(mut self, symbolmaps: &'d BcSymbolMapDirectory) -> Self {
+        self.symbolmaps = Some(symbolmaps);
+        self
     }
 
     /// Returns an iterator over all objects contained in this archive.
     pub fn objects(&self) -> MachObjectIterator<'d, '_> {
-        MachObjectIterator(match self.0 {
+        let inner = match self.inner {
             MachArchiveInner::Single(ref inner) => MachObjectIteratorInner::Single(inner.objects()),
             MachArchiveInner::Archive(ref inner) => {
                 MachObjectIteratorInner::Archive(inner.objects())
             }
-        })
+        };
+
+        MachObjectIterator {
+            inner,
+            symbolmaps: self.symbolmaps,
+        }
     }
 
     /// Returns the number of objects in this archive.
     pub fn object_count(&self) -> usize {
-        match self.0 {
+        match self.inner {
             MachArchiveInner::Single(ref inner) => inner.object_count(),
             MachArchiveInner::Archive(ref inner) => inner.object_count(),
         }
@@ -775,23 +1271,105 @@ impl<'d> MachArchive<'d> {
     /// Returns `Ok(None)` if the index is out of bounds, or `Err` if the object exists but cannot
     /// be parsed.
     pub fn object_by_index(&self, index: usize) -> Result<Option<MachObject<'d>>, MachError> {
-        match self.0 {
-            MachArchiveInner::Single(ref inner) => inner.object_by_index(index),
-            MachArchiveInner::Archive(ref inner) => inner.object_by_index(index),
-        }
+        let object = match self.inner {
+            MachArchiveInner::Single(ref inner) => inner.object_by_index(index)?,
+            MachArchiveInner::Archive(ref inner) => inner.object_by_index(index)?,
+        };
+
+        Ok(object.map(|object| attach_symbolmap(object, self.symbolmaps)))
     }
 
     /// Returns whether this is a multi-object archive.
     ///
     /// This may also return true if there is only a single object inside the archive.
     pub fn is_multi(&self) -> bool {
-        match self.0 {
+        match self.inner {
             MachArchiveInner::Archive(_) => true,
             MachArchiveInner::Single(_) => false,
         }
     }
 }
 
+/// Walks the dyld export trie and returns all exported symbols in the order of the trie.
+///
+/// See [`MachObject::exports`].
+fn parse_export_trie(trie: &[u8]) -> Result<Vec<Export<'static>>, MachError> {
+    let malformed = |message: &str| {
+        MachError::from(goblin::error::Error::Malformed(format!(
+            "export trie: {}",
+            message
+        )))
+    };
+
+    let mut exports = Vec::new();
+    let mut visited = HashSet::new();
+    let mut stack = vec![(0usize, Vec::new(), 0usize)];
+
+    while let Some((node, name, depth)) = stack.pop() {
+        if node >= trie.len() {
+            continue;
+        }
+        if depth > MAX_EXPORT_TRIE_DEPTH {
+            return Err(malformed("nodes nested too deeply"));
+        }
+        if !visited.insert(node) {
+            return Err(malformed("cycle between nodes"));
+        }
+
+        let mut offset = node;
+        let terminal_size = scroll::Uleb128::read(trie, &mut offset)? as usize;
+        let mut children = offset.saturating_add(terminal_size);
+
+        if terminal_size > 0 {
+            let flags = scroll::Uleb128::read(trie, &mut offset)?;
+            let kind = flags & mach::exports::EXPORT_SYMBOL_FLAGS_KIND_MASK;
+            let reexport = flags & mach::exports::EXPORT_SYMBOL_FLAGS_REEXPORT != 0;
+            let stub = kind == mach::exports::EXPORT_SYMBOL_FLAGS_KIND_REGULAR
+                && flags & mach::exports::EXPORT_SYMBOL_FLAGS_STUB_AND_RESOLVER != 0;
+
+            let address = if reexport || stub {
+                0
+            } else {
+                scroll::Uleb128::read(trie, &mut offset)?
+            };
+
+            let name = std::str::from_utf8(&name).map_err(|_| malformed("invalid name"))?;
+            exports.push(Export {
+                name: Cow::Owned(name.strip_prefix('_').unwrap_or(name).to_owned()),
+                address,
+            });
+        }
+
+        let child_count = scroll::Uleb128::read(trie, &mut children)?;
+        let mut branches = Vec::new();
+        for _ in 0..child_count {
+            let label = trie.pread::<&str>(children)?;
+            children += label.len() + 1;
+            let child = scroll::Uleb128::read(trie, &mut children)? as usize;
+
+            let mut child_name = name.clone();
+            child_name.extend_from_slice(label.as_bytes());
+            branches.push((child, child_name, depth + 1));
+        }
+
+        // Visit children in the order they are declared in.
+        stack.extend(branches.into_iter().rev());
+    }
+
+    Ok(exports)
+}
+
+/// Loads the symbol map matching the object's debug identifier, if there is one.
+fn attach_symbolmap<'d>(
+    mut object: MachObject<'d>,
+    symbolmaps: Option<&'d BcSymbolMapDirectory>,
+) -> MachObject<'d> {
+    if let Some(symbolmap) = symbolmaps.and_then(|maps| maps.get(object.debug_id())) {
+        object.load_symbolmap(symbolmap.clone());
+    }
+    object
+}
+
 impl<'slf, 'd: 'slf> AsSelf<'slf> for MachArchive<'d> {
     type Ref = MachArchive<'slf>;
 
@@ -803,6 +1381,117 @@ impl<'slf, 'd: 'slf> AsSelf<'slf> for MachArchive<'d> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use symbolic_common::ByteView;
+
+    #[test]
+    fn test_export_trie() {
+        let data = std::fs::read("../symbolic-testutils/fixtures/macos/crash").unwrap();
+        let object = MachObject::parse(&data).unwrap();
+
+        let exports = object.exports().unwrap();
+        let expected = object.macho.exports().unwrap();
+        assert!(!exports.is_empty());
+        assert_eq!(exports.len(), expected.len());
+
+        for (export, expected) in exports.iter().zip(&expected) {
+            let name = expected.name.strip_prefix('_').unwrap_or(&expected.name);
+            assert_eq!(export.name, name);
+            assert_eq!(export.address, expected.offset);
+        }
+    }
+
+    #[test]
+    fn test_export_trie_cycle() {
+        // The root node has a single child "_", which points back to the root.
+        let trie = [0x00, 0x01, b'_', 0x00, 0x00];
+        assert!(parse_export_trie(&trie).is_err());
+    }
+
+    /// Creates an export trie with a chain of nodes, every edge adding an "a" to the name.
+    fn export_trie_chain(depth: usize) -> Vec<u8> {
+        let mut trie = Vec::new();
+        for index in 1..=depth {
+            // Child offsets are encoded as three-byte ULEBs so that every node has the same size.
+            let next = index * 7;
+            trie.extend_from_slice(&[0x00, 0x01, b'a', 0x00]);
+            trie.extend_from_slice(&[
+                (next & 0x7f) as u8 | 0x80,
+                ((next >> 7) & 0x7f) as u8 | 0x80,
+                (next >> 14) as u8,
+            ]);
+        }
+
+        // A terminal node with flags `0` and address `0x2a`, and no children.
+        trie.extend_from_slice(&[0x02, 0x00, 0x2a, 0x00]);
+        trie
+    }
+
+    #[test]
+    fn test_export_trie_depth() {
+        let exports = parse_export_trie(&export_trie_chain(MAX_EXPORT_TRIE_DEPTH)).unwrap();
+        assert_eq!(exports.len(), 1);
+        assert_eq!(exports[0].name.len(), MAX_EXPORT_TRIE_DEPTH);
+        assert_eq!(exports[0].address, 0x2a);
+
+        assert!(parse_export_trie(&export_trie_chain(MAX_EXPORT_TRIE_DEPTH + 1)).is_err());
+    }
+
+    #[test]
+    fn test_archive_with_symbolmaps() {
+        let object_data =
+            std::fs::read("tests/fixtures/2d10c42f-591d-3265-b147-78ba0868073f.dwarf-hidden")
+                .unwrap();
+        let map_data =
+            ByteView::open("tests/fixtures/c8374b6d-6e96-34d8-ae38-efaa5fec424f.bcsymbolmap")
+                .unwrap();
+        let plist_data =
+            std::fs::read("tests/fixtures/2d10c42f-591d-3265-b147-78ba0868073f.plist").unwrap();
+
+        let dsym_uuid: DebugId = "2d10c42f-591d-3265-b147-78ba0868073f".parse().unwrap();
+        let original_uuid: DebugId = "c8374b6d-6e96-34d8-ae38-efaa5fec424f".parse().unwrap();
+
+        // Without the UUID mapping, the symbol map is stored under a different UUID.
+        let mut symbolmaps = BcSymbolMapDirectory::new();
+        symbolmaps.add_symbolmap(original_uuid, map_data).unwrap();
+        let archive = MachArchive::parse(&object_data)
+            .unwrap()
+            .with_symbolmaps(&symbolmaps);
+        let object = archive.object_by_index(0).unwrap().unwrap();
+        assert_eq!(object.debug_id(), dsym_uuid);
+        assert_eq!(object.symbols().next().unwrap().name(), Some("__hidden#0_"));
+
+        let mapping = UuidMapping::parse_plist(dsym_uuid, &plist_data).unwrap();
+        symbolmaps.add_uuid_mapping(mapping);
+        let archive = MachArchive::parse(&object_data)
+            .unwrap()
+            .with_symbolmaps(&symbolmaps);
+
+        let object = archive.object_by_index(0).unwrap().unwrap();
+        let symbol = object.symbols().next().unwrap();
+        assert_eq!(symbol.name(), Some("-[SentryMessage initWithFormatted:]"));
+
+        let object = archive.objects().next().unwrap().unwrap();
+        let session = object.debug_session().unwrap();
+        let function = session.functions().next().unwrap().unwrap();
+        assert_eq!(&function.name, "-[SentryMessage initWithFormatted:]");
+    }
+
+    #[test]
+    fn test_bcsymbolmap_directory() {
+        let symbolmaps = BcSymbolMapDirectory::open("tests/fixtures").unwrap();
+        assert_eq!(symbolmaps.len(), 1);
+
+        let dsym_uuid = "2d10c42f-591d-3265-b147-78ba0868073f".parse().unwrap();
+        let symbolmap = symbolmaps.get(dsym_uuid).unwrap();
+        assert_eq!(
+            symbolmap.get(43),
+            Some("Sources/Sentry/Public/SentryMessage.h")
+        );
+
+        let original_uuid = "c8374b6d-6e96-34d8-ae38-efaa5fec424f".parse().unwrap();
+        assert!(symbolmaps.get(original_uuid).is_some());
+        assert!(symbolmaps.get(DebugId::default()).is_none());
+    }
 
     #[allow(deprecated)]
     #[test]
@@ -831,9 +1520,9 @@ mod tests {
         let mut functions = session.functions();
         let function = functions.next().unwrap().unwrap();
         assert_eq!(&function.name, "__hidden#0_");
-        assert_eq!(&function.compilation_dir, b"__hidden#41_");
+        assert_eq!(&*function.compilation_dir, b"__hidden#41_");
         assert_eq!(
-            &function.lines[0].file.path_str(),
+            &function.lines.first().unwrap().file.path_str(),
             "__hidden#41_/__hidden#42_"
         );
 
@@ -875,11 +1564,11 @@ mod tests {
         let function = functions.next().unwrap().unwrap();
         assert_eq!(&function.name, "-[SentryMessage initWithFormatted:]");
         assert_eq!(
-            &function.compilation_dir,
+            &*function.compilation_dir,
             b"/Users/philipphofmann/git-repos/sentry-cocoa"
         );
         assert_eq!(
-            &function.lines[0].file.path_str(),
+            &function.lines.first().unwrap().file.path_str(),
             "/Users/philipphofmann/git-repos/sentry-cocoa/Sources/Sentry/SentryMessage.m"
         );
 
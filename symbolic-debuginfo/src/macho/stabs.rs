@@ -0,0 +1,451 @@
+//! Support for STABS debugging information in the symbol table of MachO files.
+//!
+//! Before DWARF, debugging information on Apple platforms was stored as special symbol table
+//! entries. Even today, linked executables retain a "debug map" of STABS entries that point to the
+//! object files containing DWARF. Both forms declare functions with `N_FUN` entries within source
+//! files declared by `N_SO` and `N_SOL` entries.
+
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use goblin::mach;
+
+use symbolic_common::{Language, Name, NameMangling, PathRemapper};
+
+use super::{BcSymbolMap, MachError, SWIFT_HIDDEN_PREFIX};
+use crate::base::*;
+
+/// Returns `true` if the symbol table declares at least one function with STABS.
+pub(crate) fn has_stab_functions(macho: &mach::MachO<'_>) -> bool {
+    macho.symbols().any(|symbol| match symbol {
+        Ok((name, nlist)) => nlist.n_type == mach::symbols::N_FUN && !name.is_empty(),
+        Err(_) => false,
+    })
+}
+
+/// Splits the name of a function from its STABS type descriptor, such as `main:F(0,1)`.
+///
+/// Objective-C method names contain colons themselves, so the descriptor is searched after the
+/// closing bracket.
+fn function_name(stab: &str) -> &str {
+    let start = if stab.starts_with("-[") || stab.starts_with("+[") {
+        stab.find(']').unwrap_or(0)
+    } else {
+        0
+    };
+
+    match stab[start..].find(':') {
+        Some(index) => &stab[..start + index],
+        None => stab,
+    }
+}
+
+/// A function declared by an `N_FUN` entry that has not been closed yet.
+struct PendingFunction<'data> {
+    address: u64,
+    name: &'data str,
+    compilation_dir: &'data [u8],
+    lines: Vec<LineInfo<'data>>,
+}
+
+/// Parses STABS entries of the symbol table into functions.
+struct StabsParser<'data> {
+    vmaddr: u64,
+    symbolmap: Option<Arc<BcSymbolMap<'data>>>,
+    source_dir: &'data str,
+    source_name: &'data str,
+    compilation_dir: &'data [u8],
+    file: Option<FileInfo<'data>>,
+    function: Option<PendingFunction<'data>>,
+    functions: Vec<Function<'data>>,
+}
+
+impl<'data> StabsParser<'data> {
+    fn new(vmaddr: u64, symbolmap: Option<Arc<BcSymbolMap<'data>>>) -> Self {
+        Self {
+            vmaddr,
+            symbolmap,
+            source_dir: "",
+            source_name: "",
+            compilation_dir: &[],
+            file: None,
+            function: None,
+            functions: Vec::new(),
+        }
+    }
+
+    fn parse<I>(mut self, symbols: I) -> Result<Vec<Function<'data>>, MachError>
+    where
+        I: IntoIterator<Item = goblin::error::Result<(&'data str, mach::symbols::Nlist)>>,
+    {
+        for symbol in symbols {
+            let (name, nlist) = symbol?;
+            if !nlist.is_stab() {
+                continue;
+            }
+
+            match nlist.n_type {
+                mach::symbols::N_SO => self.source_file(name),
+                mach::symbols::N_SOL => self.included_file(name),
+                mach::symbols::N_FUN if name.is_empty() => self.end_function(nlist.n_value),
+                mach::symbols::N_FUN => self.begin_function(name, nlist.n_value),
+                mach::symbols::N_SLINE => self.line(nlist.n_value, nlist.n_desc),
+                _ => {}
+            }
+        }
+
+        self.close_function(None);
+        Ok(self.functions)
+    }
+
+    /// Handles an `N_SO` entry, which either starts or ends a compilation unit.
+    fn source_file(&mut self, name: &'data str) {
+        if name.is_empty() {
+            self.close_function(None);
+            self.source_dir = "";
+            self.source_name = "";
+            self.compilation_dir = &[];
+            self.file = None;
+        } else if name.ends_with('/') {
+            self.source_dir = name;
+            self.compilation_dir = name.as_bytes();
+        } else {
+            self.source_name = name;
+            self.file = Some(FileInfo::from_path(name.as_bytes()));
+        }
+    }
+
+    /// Handles an `N_SOL` entry, which switches the file of subsequent line records.
+    ///
+    /// In debug maps, these entries follow the function declaration and name the file containing
+    /// the function.
+    fn included_file(&mut self, path: &'data str) {
+        // Linkers emit the directory of the main source file in `N_SO`, while included paths are
+        // relative to the actual compilation directory. If the included file is the main source
+        // file, the compilation directory can be recovered from the difference of both paths.
+        let compilation_dir = path
+            .strip_suffix(self.source_name)
+            .filter(|dir| dir.ends_with('/') && !path.starts_with('/'))
+            .and_then(|dir| self.source_dir.strip_suffix(dir));
+
+        if let Some(compilation_dir) = compilation_dir {
+            self.compilation_dir = compilation_dir.as_bytes();
+            if let Some(ref mut function) = self.function {
+                function.compilation_dir = self.compilation_dir;
+            }
+        }
+
+        self.file = Some(FileInfo::from_path(path.as_bytes()));
+    }
+
+    fn begin_function(&mut self, name: &'data str, address: u64) {
+        // Older toolchains do not emit an end marker, so the previous function ends here.
+        let address = address.wrapping_sub(self.vmaddr);
+        self.close_function(Some(address));
+
+        self.function = Some(PendingFunction {
+            address,
+            name: function_name(name),
+            compilation_dir: self.compilation_dir,
+            lines: Vec::new(),
+        });
+    }
+
+    fn end_function(&mut self, size: u64) {
+        let end = match self.function {
+            Some(ref function) => function.address.saturating_add(size),
+            None => return,
+        };
+
+        self.close_function(Some(end));
+    }
+
+    /// Handles an `N_SLINE` entry, whose address may be relative to the start of the function.
+    fn line(&mut self, value: u64, line: u16) {
+        let function = match self.function {
+            Some(ref mut function) => function,
+            None => return,
+        };
+
+        let address = match value.checked_sub(self.vmaddr) {
+            Some(address) if address >= function.address => address,
+            _ => function.address.saturating_add(value),
+        };
+
+        function.lines.push(LineInfo {
+            address,
+            size: None,
+            file: self.file.clone().unwrap_or_default(),
+            line: line.into(),
+        });
+    }
+
+    fn close_function(&mut self, end: Option<u64>) {
+        let pending = match self.function.take() {
+            Some(pending) => pending,
+            None => return,
+        };
+
+        let mut lines = pending.lines;
+        lines.sort_by_key(|line| line.address);
+
+        let end = end
+            .or_else(|| lines.last().map(|line| line.address + 1))
+            .unwrap_or(pending.address);
+
+        // Without line records, the function is still associated with the file declaring it.
+        if lines.is_empty() {
+            if let Some(ref file) = self.file {
+                lines.push(LineInfo {
+                    address: pending.address,
+                    size: None,
+                    file: file.clone(),
+                    line: 0,
+                });
+            }
+        }
+
+        for index in 0..lines.len() {
+            let next = lines.get(index + 1).map_or(end, |line| line.address);
+            lines[index].size = next.checked_sub(lines[index].address);
+        }
+
+        let mut name = pending.name;
+        if let Some(ref symbolmap) = self.symbolmap {
+            name = symbolmap.resolve(name);
+        }
+
+        // Trim leading underscores from mangled C++ names, like in the symbol table.
+        if let Some(tail) = name.strip_prefix('_') {
+            if !name.starts_with(SWIFT_HIDDEN_PREFIX) {
+                name = tail;
+            }
+        }
+
+        self.functions.push(Function {
+            address: pending.address,
+            size: end.saturating_sub(pending.address),
+            ranges: Vec::new(),
+            name: Name::new(name, NameMangling::Unknown, Language::from_mangled(name)),
+            compilation_dir: Cow::Borrowed(pending.compilation_dir),
+            lines: lines.into(),
+            inlinees: Vec::new(),
+            inline: false,
+            artificial: false,
+            noreturn: false,
+            calling_convention: None,
+            call_sites: Vec::new(),
+            entity: None,
+        });
+    }
+}
+
+/// Debug session for STABS debugging information in MachO files.
+///
+/// STABS only declare functions and their line records. There is no information on inlined
+/// functions. Returned by [`MachObject::stabs_session`](super::MachObject::stabs_session).
+pub struct StabsDebugSession<'data> {
+    functions: Vec<Function<'data>>,
+    path_remapper: Option<PathRemapper>,
+    limits: SessionLimits,
+}
+
+impl<'data> StabsDebugSession<'data> {
+    pub(crate) fn parse(
+        macho: &mach::MachO<'data>,
+        vmaddr: u64,
+        symbolmap: Option<Arc<BcSymbolMap<'data>>>,
+    ) -> Result<Self, MachError> {
+        let functions = StabsParser::new(vmaddr, symbolmap).parse(macho.symbols())?;
+        Ok(StabsDebugSession {
+            functions,
+            path_remapper: None,
+            limits: SessionLimits::default(),
+        })
+    }
+
+    /// Sets a [`PathRemapper`] applied to all compilation directories and file paths in this debug
+    /// session.
+    ///
+    /// Remapping is disabled by default.
+    pub fn set_path_remapper(&mut self, remapper: Option<PathRemapper>) {
+        self.path_remapper = remapper;
+    }
+
+    /// Sets the [`SessionLimits`] enforced by the function iterators of this session.
+    ///
+    /// There are no limits by default.
+    pub fn set_limits(&mut self, limits: SessionLimits) {
+        self.limits = limits;
+    }
+
+    /// Returns an iterator over all functions in this debug file.
+    pub fn functions(&self) -> StabsFunctionIterator<'_> {
+        StabsFunctionIterator {
+            functions: self.functions.iter(),
+            remapper: self.path_remapper.as_ref(),
+            limits: LimitTracker::new(self.limits),
+        }
+    }
+
+    /// Returns an iterator over all source files referenced by this debug file.
+    pub fn files(&self) -> StabsFileIterator<'_> {
+        let mut seen = BTreeSet::new();
+        let mut files = Vec::new();
+
+        for function in &self.functions {
+            let function = function.clone().remap(self.path_remapper.as_ref());
+            for line in &function.lines {
+                let key = (
+                    function.compilation_dir.clone(),
+                    line.file.dir.clone(),
+                    line.file.name.clone(),
+                );
+                if seen.insert(key) {
+                    files.push(Ok(FileEntry {
+                        compilation_dir: function.compilation_dir.clone(),
+                        info: line.file,
+                    }));
+                }
+            }
+        }
+
+        files.into_iter()
+    }
+
+    /// Looks up a file's source contents by its full canonicalized path.
+    ///
+    /// The given path must be canonicalized.
+    pub fn source_by_path(&self, _path: &str) -> Result<Option<Cow<'_, str>>, MachError> {
+        Ok(None)
+    }
+}
+
+impl<'session> DebugSession<'session> for StabsDebugSession<'_> {
+    type Error = MachError;
+    type FunctionIterator = StabsFunctionIterator<'session>;
+    type FileIterator = StabsFileIterator<'session>;
+
+    fn functions(&'session self) -> Self::FunctionIterator {
+        self.functions()
+    }
+
+    fn files(&'session self) -> Self::FileIterator {
+        self.files()
+    }
+
+    fn source_by_path(&self, path: &str) -> Result<Option<Cow<'_, str>>, Self::Error> {
+        self.source_by_path(path)
+    }
+}
+
+/// An iterator over functions declared by STABS in a MachO file.
+pub struct StabsFunctionIterator<'s> {
+    functions: std::slice::Iter<'s, Function<'s>>,
+    remapper: Option<&'s PathRemapper>,
+    limits: LimitTracker,
+}
+
+impl<'s> Iterator for StabsFunctionIterator<'s> {
+    type Item = Result<Function<'s>, MachError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.limits.is_exceeded() {
+            return None;
+        }
+
+        let function = self.functions.next()?.clone().remap(self.remapper);
+        Some(match self.limits.track(&function) {
+            Ok(()) => Ok(function),
+            Err(error) => Err(error.into()),
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.functions.size_hint();
+        if self.limits.has_limits() {
+            (0, upper)
+        } else {
+            (lower, upper)
+        }
+    }
+}
+
+/// An iterator over source files referenced by STABS in a MachO file.
+pub type StabsFileIterator<'s> = std::vec::IntoIter<Result<FileEntry<'s>, MachError>>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use mach::symbols::{Nlist, N_FUN, N_SLINE, N_SO, N_SOL};
+
+    fn stab(
+        n_type: u8,
+        name: &str,
+        n_desc: u16,
+        n_value: u64,
+    ) -> goblin::error::Result<(&str, Nlist)> {
+        let nlist = Nlist {
+            n_strx: 0,
+            n_type,
+            n_sect: 0,
+            n_desc,
+            n_value,
+        };
+
+        Ok((name, nlist))
+    }
+
+    #[test]
+    fn test_line_records() {
+        // Function-relative line records of two functions without end markers.
+        let symbols = vec![
+            stab(N_SO, "/src/", 0, 0),
+            stab(N_SO, "main.c", 0, 0),
+            stab(N_FUN, "main:F(0,1)", 0, 0x1000),
+            stab(N_SLINE, "", 3, 0),
+            stab(N_SOL, "util.h", 0, 0),
+            stab(N_SLINE, "", 7, 0x8),
+            stab(N_FUN, "_helper:f(0,1)", 0, 0x1020),
+            stab(N_SLINE, "", 12, 0),
+            stab(N_FUN, "", 0, 0x10),
+            stab(N_SO, "", 0, 0),
+        ];
+
+        let functions = StabsParser::new(0, None).parse(symbols).unwrap();
+        assert_eq!(functions.len(), 2);
+
+        let main = &functions[0];
+        assert_eq!((main.address, main.size), (0x1000, 0x20));
+        assert_eq!(main.name, "main");
+        assert_eq!(&*main.compilation_dir, b"/src/");
+
+        let lines: Vec<_> = main
+            .lines
+            .iter()
+            .map(|line| (line.address, line.size, line.file.path_str(), line.line))
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                (0x1000, Some(0x8), "main.c".to_owned(), 3),
+                (0x1008, Some(0x18), "util.h".to_owned(), 7),
+            ]
+        );
+
+        let helper = &functions[1];
+        assert_eq!((helper.address, helper.size), (0x1020, 0x10));
+        assert_eq!(helper.name, "helper");
+        assert_eq!(helper.lines.len(), 1);
+    }
+
+    #[test]
+    fn test_function_name() {
+        assert_eq!(function_name("_main"), "_main");
+        assert_eq!(function_name("main:F(0,1)"), "main");
+        assert_eq!(function_name("-[Foo bar:baz:]"), "-[Foo bar:baz:]");
+        assert_eq!(function_name("+[Foo bar:]:f(0,2)"), "+[Foo bar:]");
+    }
+}
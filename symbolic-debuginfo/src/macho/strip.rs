@@ -0,0 +1,397 @@
+//! Removal and extraction of DWARF debug information in MachO files.
+
+use std::convert::TryFrom;
+use std::ops::Range;
+
+use goblin::mach::header::{MH_DSYM, SIZEOF_HEADER_32, SIZEOF_HEADER_64};
+use goblin::mach::load_command::{self as lc, CommandVariant, LoadCommand, Section32, Section64};
+use goblin::mach::symbols::{SIZEOF_NLIST_32, SIZEOF_NLIST_64};
+use scroll::{Endian, Pread, Pwrite};
+
+use super::{MachError, MachObject};
+use crate::shared::append_aligned;
+
+/// The name of the segment holding DWARF sections.
+const DWARF_SEGMENT: &[u8] = b"__DWARF";
+
+/// The alignment of segment contents in debug companions.
+const SEGMENT_ALIGN: u64 = 0x1000;
+
+/// Load commands retained in debug companions.
+///
+/// These are the same commands that `dsymutil` writes into dSYM companions.
+const COMPANION_COMMANDS: &[u32] = &[
+    lc::LC_SEGMENT,
+    lc::LC_SEGMENT_64,
+    lc::LC_UUID,
+    lc::LC_SYMTAB,
+    lc::LC_BUILD_VERSION,
+    lc::LC_VERSION_MIN_MACOSX,
+    lc::LC_VERSION_MIN_IPHONEOS,
+    lc::LC_VERSION_MIN_TVOS,
+    lc::LC_VERSION_MIN_WATCHOS,
+];
+
+impl<'d> MachObject<'d> {
+    /// Returns a copy of this object without the `__DWARF` segment.
+    ///
+    /// Linkers only emit this segment if DWARF is not moved into a dSYM companion, for instance
+    /// with the Go toolchain. All file offsets following the removed segment are updated, while
+    /// the UUID and symbol table are retained. If the object does not contain DWARF, an unchanged
+    /// copy is returned.
+    ///
+    /// Since this changes the contents of the file, an existing code signature becomes invalid and
+    /// needs to be renewed.
+    pub fn strip_debug(&self) -> Result<Vec<u8>, MachError> {
+        let endian = self.endian();
+
+        let dwarf = self
+            .macho
+            .load_commands
+            .iter()
+            .find(|command| is_dwarf_segment(&command.command));
+
+        let dwarf = match dwarf {
+            Some(dwarf) => dwarf,
+            None => return Ok(self.data.to_vec()),
+        };
+
+        let commands_end = self.header_size() + self.macho.header.sizeofcmds as usize;
+        let removed = match segment_file_range(&dwarf.command) {
+            Some(range) if range.start >= commands_end && range.end <= self.data.len() => range,
+            _ => return Err(MachError::new("__DWARF segment out of bounds")),
+        };
+
+        let removed_size = (removed.end - removed.start) as u64;
+        let shift = |offset: u64| {
+            if offset >= removed.end as u64 {
+                offset - removed_size
+            } else {
+                offset
+            }
+        };
+
+        let mut out = self.data.to_vec();
+        for command in &self.macho.load_commands {
+            if command.offset != dwarf.offset {
+                self.shift_offsets(&mut out, command, shift)?;
+            }
+        }
+
+        out.drain(removed);
+
+        // Remove the load command, but pad the end of the command area to retain the offsets of
+        // sections in the first segment.
+        let command_size = dwarf.command.cmdsize();
+        out.drain(dwarf.offset..dwarf.offset + command_size);
+        let padding = commands_end - command_size;
+        out.splice(padding..padding, std::iter::repeat_n(0, command_size));
+
+        let header = &self.macho.header;
+        out.pwrite_with(header.ncmds as u32 - 1, 16, endian)?;
+        out.pwrite_with(header.sizeofcmds - command_size as u32, 20, endian)?;
+
+        Ok(out)
+    }
+
+    /// Returns a copy of this object that only contains debug information, similar to a dSYM.
+    ///
+    /// The companion retains all segments and sections with their addresses, the UUID, platform
+    /// version and symbol table. Only the contents of the `__DWARF` segment and symbol table are
+    /// copied. All other load commands are dropped, and the file type is changed to `MH_DSYM`.
+    pub fn debug_companion(&self) -> Result<Vec<u8>, MachError> {
+        let endian = self.endian();
+        let header_size = self.header_size();
+
+        let commands = self
+            .macho
+            .load_commands
+            .iter()
+            .filter(|command| COMPANION_COMMANDS.contains(&command.command.cmd()))
+            .collect::<Vec<_>>();
+
+        let commands_size = commands
+            .iter()
+            .map(|command| command.command.cmdsize())
+            .sum::<usize>();
+
+        let mut out = self.contents(0..header_size)?.to_vec();
+        out.resize(header_size + commands_size, 0);
+
+        let mut cursor = header_size;
+        for command in commands.iter() {
+            let size = command.command.cmdsize();
+            let raw = self.contents(command.offset..command.offset + size)?;
+            out[cursor..cursor + size].copy_from_slice(raw);
+
+            match command.command {
+                CommandVariant::Segment32(mut segment) => {
+                    let start = segment.fileoff as usize;
+                    let contents = if is_dwarf_segment(&command.command) {
+                        let data = self.contents(start..start + segment.filesize as usize)?;
+                        Some(append_aligned(&mut out, data, SEGMENT_ALIGN))
+                    } else {
+                        None
+                    };
+
+                    segment.fileoff = contents.unwrap_or(0) as u32;
+                    if contents.is_none() {
+                        segment.filesize = 0;
+                    }
+
+                    out.pwrite_with(segment, cursor, endian)?;
+                    relocate_sections(&mut out, cursor, segment.nsects, false, endian, |offset| {
+                        contents.map(|base| base + offset - start)
+                    })?;
+                }
+                CommandVariant::Segment64(mut segment) => {
+                    let start = segment.fileoff as usize;
+                    let contents = if is_dwarf_segment(&command.command) {
+                        let data = self.contents(start..start + segment.filesize as usize)?;
+                        Some(append_aligned(&mut out, data, SEGMENT_ALIGN))
+                    } else {
+                        None
+                    };
+
+                    segment.fileoff = contents.unwrap_or(0) as u64;
+                    if contents.is_none() {
+                        segment.filesize = 0;
+                    }
+
+                    out.pwrite_with(segment, cursor, endian)?;
+                    relocate_sections(&mut out, cursor, segment.nsects, true, endian, |offset| {
+                        contents.map(|base| base + offset - start)
+                    })?;
+                }
+                CommandVariant::Symtab(mut symtab) => {
+                    let nlist_size = if self.macho.is_64 {
+                        SIZEOF_NLIST_64
+                    } else {
+                        SIZEOF_NLIST_32
+                    };
+
+                    let start = symtab.symoff as usize;
+                    let symbols =
+                        self.contents(start..start + symtab.nsyms as usize * nlist_size)?;
+                    symtab.symoff = append_aligned(&mut out, symbols, 8) as u32;
+
+                    let start = symtab.stroff as usize;
+                    let strings = self.contents(start..start + symtab.strsize as usize)?;
+                    symtab.stroff = append_aligned(&mut out, strings, 1) as u32;
+
+                    out.pwrite_with(symtab, cursor, endian)?;
+                }
+                _ => (),
+            }
+
+            cursor += size;
+        }
+
+        out.pwrite_with(MH_DSYM, 12, endian)?;
+        out.pwrite_with(commands.len() as u32, 16, endian)?;
+        out.pwrite_with(commands_size as u32, 20, endian)?;
+
+        Ok(out)
+    }
+
+    /// Returns the byte order of this object.
+    fn endian(&self) -> Endian {
+        if self.macho.little_endian {
+            scroll::LE
+        } else {
+            scroll::BE
+        }
+    }
+
+    /// Returns the size of the MachO header preceding the load commands.
+    fn header_size(&self) -> usize {
+        if self.macho.is_64 {
+            SIZEOF_HEADER_64
+        } else {
+            SIZEOF_HEADER_32
+        }
+    }
+
+    /// Returns a range of the raw file contents.
+    fn contents(&self, range: Range<usize>) -> Result<&'d [u8], MachError> {
+        self.data
+            .get(range)
+            .ok_or_else(|| MachError::new("load command contents out of bounds"))
+    }
+
+    /// Updates all file offsets declared by a load command after removing contents.
+    fn shift_offsets<F>(
+        &self,
+        out: &mut [u8],
+        command: &LoadCommand,
+        shift: F,
+    ) -> Result<(), MachError>
+    where
+        F: Fn(u64) -> u64,
+    {
+        let endian = self.endian();
+        let shift32 = |offset: u32| shift(offset.into()) as u32;
+        let offset = command.offset;
+
+        match command.command {
+            CommandVariant::Segment32(mut segment) => {
+                segment.fileoff = shift32(segment.fileoff);
+                out.pwrite_with(segment, offset, endian)?;
+                shift_sections(out, offset, segment.nsects, false, endian, shift32)?;
+            }
+            CommandVariant::Segment64(mut segment) => {
+                segment.fileoff = shift(segment.fileoff);
+                out.pwrite_with(segment, offset, endian)?;
+                shift_sections(out, offset, segment.nsects, true, endian, shift32)?;
+            }
+            CommandVariant::Symtab(mut symtab) => {
+                symtab.symoff = shift32(symtab.symoff);
+                symtab.stroff = shift32(symtab.stroff);
+                out.pwrite_with(symtab, offset, endian)?;
+            }
+            CommandVariant::Dysymtab(mut dysymtab) => {
+                dysymtab.tocoff = shift32(dysymtab.tocoff);
+                dysymtab.modtaboff = shift32(dysymtab.modtaboff);
+                dysymtab.extrefsymoff = shift32(dysymtab.extrefsymoff);
+                dysymtab.indirectsymoff = shift32(dysymtab.indirectsymoff);
+                dysymtab.extreloff = shift32(dysymtab.extreloff);
+                dysymtab.locreloff = shift32(dysymtab.locreloff);
+                out.pwrite_with(dysymtab, offset, endian)?;
+            }
+            CommandVariant::DyldInfo(mut info) | CommandVariant::DyldInfoOnly(mut info) => {
+                info.rebase_off = shift32(info.rebase_off);
+                info.bind_off = shift32(info.bind_off);
+                info.weak_bind_off = shift32(info.weak_bind_off);
+                info.lazy_bind_off = shift32(info.lazy_bind_off);
+                info.export_off = shift32(info.export_off);
+                out.pwrite_with(info, offset, endian)?;
+            }
+            CommandVariant::CodeSignature(mut data)
+            | CommandVariant::SegmentSplitInfo(mut data)
+            | CommandVariant::FunctionStarts(mut data)
+            | CommandVariant::DataInCode(mut data)
+            | CommandVariant::DylibCodeSignDrs(mut data)
+            | CommandVariant::LinkerOptimizationHint(mut data)
+            | CommandVariant::DyldExportsTrie(mut data)
+            | CommandVariant::DyldChainedFixups(mut data) => {
+                data.dataoff = shift32(data.dataoff);
+                out.pwrite_with(data, offset, endian)?;
+            }
+            CommandVariant::EncryptionInfo32(mut info) => {
+                info.cryptoff = shift32(info.cryptoff);
+                out.pwrite_with(info, offset, endian)?;
+            }
+            CommandVariant::EncryptionInfo64(mut info) => {
+                info.cryptoff = shift32(info.cryptoff);
+                out.pwrite_with(info, offset, endian)?;
+            }
+            CommandVariant::TwolevelHints(mut hints) => {
+                hints.offset = shift32(hints.offset);
+                out.pwrite_with(hints, offset, endian)?;
+            }
+            _ => (),
+        }
+
+        Ok(())
+    }
+}
+
+/// Checks whether a load command declares the `__DWARF` segment.
+fn is_dwarf_segment(command: &CommandVariant) -> bool {
+    let name = match command {
+        CommandVariant::Segment32(segment) => &segment.segname,
+        CommandVariant::Segment64(segment) => &segment.segname,
+        _ => return false,
+    };
+
+    name.split(|&b| b == 0).next() == Some(DWARF_SEGMENT)
+}
+
+/// Returns the range of file contents covered by a segment command.
+fn segment_file_range(command: &CommandVariant) -> Option<Range<usize>> {
+    let (fileoff, filesize) = match command {
+        CommandVariant::Segment32(segment) => (segment.fileoff.into(), segment.filesize.into()),
+        CommandVariant::Segment64(segment) => (segment.fileoff, segment.filesize),
+        _ => return None,
+    };
+
+    let start = usize::try_from(fileoff).ok()?;
+    let end = start.checked_add(usize::try_from(filesize).ok()?)?;
+    Some(start..end)
+}
+
+/// Applies a function to the contents and relocation offsets of all sections of a segment.
+fn shift_sections<F>(
+    out: &mut [u8],
+    command_offset: usize,
+    nsects: u32,
+    is_64: bool,
+    endian: Endian,
+    shift: F,
+) -> Result<(), MachError>
+where
+    F: Fn(u32) -> u32,
+{
+    for index in 0..nsects as usize {
+        if is_64 {
+            let offset =
+                command_offset + lc::SIZEOF_SEGMENT_COMMAND_64 + index * lc::SIZEOF_SECTION_64;
+            let mut section: Section64 = out.pread_with(offset, endian)?;
+            section.offset = shift(section.offset);
+            section.reloff = shift(section.reloff);
+            out.pwrite_with(section, offset, endian)?;
+        } else {
+            let offset =
+                command_offset + lc::SIZEOF_SEGMENT_COMMAND_32 + index * lc::SIZEOF_SECTION_32;
+            let mut section: Section32 = out.pread_with(offset, endian)?;
+            section.offset = shift(section.offset);
+            section.reloff = shift(section.reloff);
+            out.pwrite_with(section, offset, endian)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Moves the contents of all sections of a segment in a debug companion.
+///
+/// The function receives the original file offset of a section and returns its new offset, or
+/// `None` if the contents are dropped. Relocations are always dropped.
+fn relocate_sections<F>(
+    out: &mut [u8],
+    command_offset: usize,
+    nsects: u32,
+    is_64: bool,
+    endian: Endian,
+    relocate: F,
+) -> Result<(), MachError>
+where
+    F: Fn(usize) -> Option<usize>,
+{
+    let relocate = |offset: u32| match offset {
+        0 => 0,
+        offset => relocate(offset as usize).unwrap_or(0) as u32,
+    };
+
+    for index in 0..nsects as usize {
+        if is_64 {
+            let offset =
+                command_offset + lc::SIZEOF_SEGMENT_COMMAND_64 + index * lc::SIZEOF_SECTION_64;
+            let mut section: Section64 = out.pread_with(offset, endian)?;
+            section.offset = relocate(section.offset);
+            section.reloff = 0;
+            section.nreloc = 0;
+            out.pwrite_with(section, offset, endian)?;
+        } else {
+            let offset =
+                command_offset + lc::SIZEOF_SEGMENT_COMMAND_32 + index * lc::SIZEOF_SECTION_32;
+            let mut section: Section32 = out.pread_with(offset, endian)?;
+            section.offset = relocate(section.offset);
+            section.reloff = 0;
+            section.nreloc = 0;
+            out.pwrite_with(section, offset, endian)?;
+        }
+    }
+
+    Ok(())
+}
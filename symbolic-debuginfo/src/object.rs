@@ -3,13 +3,16 @@
 use std::borrow::Cow;
 use std::error::Error;
 use std::fmt;
+use std::io::Write;
 
-use symbolic_common::{Arch, AsSelf, CodeId, DebugId};
+use symbolic_common::{Arch, AsSelf, CodeId, DebugId, PathRemapper};
 
 use crate::base::*;
 use crate::breakpad::*;
 use crate::dwarf::*;
 use crate::elf::*;
+use crate::go::*;
+use crate::lookup::ObjectLookup;
 use crate::macho::*;
 use crate::pdb::*;
 use crate::pe::*;
@@ -119,6 +122,176 @@ impl Error for ObjectError {
     }
 }
 
+/// The kind of an [`ObjectIdentifiersError`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ObjectIdentifiersErrorKind {
+    /// The object or one of the objects in an archive could not be parsed.
+    BadObject,
+
+    /// An ELF object does not contain a GNU build ID note.
+    ///
+    /// The debug identifier of such objects is derived from a hash of the text section, which is
+    /// not stable across builds and cannot be used to reliably match debug files.
+    MissingBuildId,
+
+    /// The object does not declare a debug identifier.
+    MissingDebugId,
+
+    /// Multiple objects in an archive share the same debug identifier.
+    ConflictingIdentifiers,
+}
+
+impl fmt::Display for ObjectIdentifiersErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BadObject => write!(f, "invalid object file"),
+            Self::MissingBuildId => write!(f, "missing build id in elf object"),
+            Self::MissingDebugId => write!(f, "missing debug identifier"),
+            Self::ConflictingIdentifiers => write!(f, "conflicting debug identifiers in archive"),
+        }
+    }
+}
+
+/// An error when extracting [`ObjectIdentifiers`] from an object or archive.
+#[derive(Debug, thiserror::Error)]
+#[error("{kind}")]
+pub struct ObjectIdentifiersError {
+    kind: ObjectIdentifiersErrorKind,
+    #[source]
+    source: Option<Box<dyn Error + Send + Sync + 'static>>,
+}
+
+impl ObjectIdentifiersError {
+    /// Creates a new identifiers error from a known kind of error as well as an arbitrary error
+    /// payload.
+    fn new<E>(kind: ObjectIdentifiersErrorKind, source: E) -> Self
+    where
+        E: Into<Box<dyn Error + Send + Sync>>,
+    {
+        let source = Some(source.into());
+        Self { kind, source }
+    }
+
+    /// Returns the corresponding [`ObjectIdentifiersErrorKind`] for this error.
+    pub fn kind(&self) -> ObjectIdentifiersErrorKind {
+        self.kind
+    }
+}
+
+impl From<ObjectIdentifiersErrorKind> for ObjectIdentifiersError {
+    fn from(kind: ObjectIdentifiersErrorKind) -> Self {
+        Self { kind, source: None }
+    }
+}
+
+impl From<ObjectError> for ObjectIdentifiersError {
+    fn from(e: ObjectError) -> Self {
+        Self::new(ObjectIdentifiersErrorKind::BadObject, e)
+    }
+}
+
+/// Identifying information of an object file.
+///
+/// This bundles the code and debug identifiers of an object together with its architecture and
+/// kind, as returned by [`Object::identifiers`] and [`Archive::identifiers`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ObjectIdentifiers {
+    /// The code identifier of the object, if the file format declares one.
+    pub code_id: Option<CodeId>,
+    /// The debug identifier of the object.
+    pub debug_id: DebugId,
+    /// The CPU architecture of the object.
+    pub arch: Arch,
+    /// The kind of the object.
+    pub kind: ObjectKind,
+}
+
+/// The kind of a [`ValidationIssue`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ValidationIssueKind {
+    /// Parts of the debug information could not be read.
+    CorruptDebugInfo,
+
+    /// Two functions cover overlapping address ranges.
+    OverlappingFunctions,
+
+    /// Line records of a function lie outside of the function's address ranges.
+    LineOutsideFunction,
+
+    /// Line records of a function refer to a file that is not declared in the debug information.
+    MissingFile,
+
+    /// A function in the debug information extends past the end of its symbol.
+    SymbolMismatch,
+}
+
+impl fmt::Display for ValidationIssueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CorruptDebugInfo => write!(f, "corrupt debug information"),
+            Self::OverlappingFunctions => write!(f, "overlapping functions"),
+            Self::LineOutsideFunction => write!(f, "line records outside of function"),
+            Self::MissingFile => write!(f, "missing file"),
+            Self::SymbolMismatch => write!(f, "symbol mismatch"),
+        }
+    }
+}
+
+/// A defect found by [`Object::validate`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidationIssue {
+    /// The kind of this issue.
+    pub kind: ValidationIssueKind,
+    /// The relative address at which the issue was found, if it is associated with code.
+    pub address: Option<u64>,
+    /// A human readable description of the issue.
+    pub message: String,
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.kind)?;
+        if let Some(address) = self.address {
+            write!(f, " at {address:#x}")?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// The result of validating an object file with [`Object::validate`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    /// The number of top-level functions that were checked.
+    pub functions: usize,
+    /// All issues found in the object, ordered by kind and address.
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    /// Returns `true` if no issues were found.
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Returns an iterator over all issues of the given kind.
+    pub fn issues_of(
+        &self,
+        kind: ValidationIssueKind,
+    ) -> impl Iterator<Item = &ValidationIssue> + '_ {
+        self.issues.iter().filter(move |issue| issue.kind == kind)
+    }
+
+    fn push(&mut self, kind: ValidationIssueKind, address: Option<u64>, message: String) {
+        self.issues.push(ValidationIssue {
+            kind,
+            address,
+            message,
+        });
+    }
+}
+
 /// Tries to infer the object type from the start of the given buffer.
 ///
 /// If `archive` is set to `true`, multi architecture objects will be allowed. Otherwise, only
@@ -199,6 +372,25 @@ impl<'data> Object<'data> {
 
     /// Tries to parse a supported object from the given slice.
     pub fn parse(data: &'data [u8]) -> Result<Self, ObjectError> {
+        Self::parse_with(data, ObjectOptions::new())
+    }
+
+    /// Tries to parse a supported object from the given slice with custom options.
+    ///
+    /// Use this to skip expensive parts of parsing, for instance when only the identifiers of a
+    /// large object are needed. See [`ObjectOptions`] for which formats honor the options.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use symbolic_debuginfo::{Object, ObjectOptions};
+    ///
+    /// # let data = std::fs::read("../symbolic-testutils/fixtures/linux/crash").unwrap();
+    /// let options = ObjectOptions::new().symbols(false);
+    /// let object = Object::parse_with(&data, options).unwrap();
+    /// assert!(!object.debug_id().is_nil());
+    /// ```
+    pub fn parse_with(data: &'data [u8], options: ObjectOptions) -> Result<Self, ObjectError> {
         macro_rules! parse_object {
             ($kind:ident, $file:ident, $data:expr) => {
                 Object::$kind($file::parse(data).map_err(ObjectError::transparent)?)
@@ -207,7 +399,9 @@ impl<'data> Object<'data> {
 
         let object = match Self::peek(data) {
             FileFormat::Breakpad => parse_object!(Breakpad, BreakpadObject, data),
-            FileFormat::Elf => parse_object!(Elf, ElfObject, data),
+            FileFormat::Elf => {
+                Object::Elf(ElfObject::parse_with(data, options).map_err(ObjectError::transparent)?)
+            }
             FileFormat::MachO => parse_object!(MachO, MachObject, data),
             FileFormat::Pdb => parse_object!(Pdb, PdbObject, data),
             FileFormat::Pe => parse_object!(Pe, PeObject, data),
@@ -266,6 +460,156 @@ impl<'data> Object<'data> {
         match_inner!(self, Object(ref o) => o.load_address())
     }
 
+    /// Extracts all identifiers of this object in a single call.
+    ///
+    /// In contrast to calling [`code_id`](Self::code_id) and [`debug_id`](Self::debug_id)
+    /// individually, this validates the identifiers and returns an error if they are not suitable
+    /// to reliably identify the object:
+    ///
+    ///  - [`MissingBuildId`](ObjectIdentifiersErrorKind::MissingBuildId) if this is an ELF object
+    ///    without a GNU build ID note.
+    ///  - [`MissingDebugId`](ObjectIdentifiersErrorKind::MissingDebugId) if the object does not
+    ///    declare a debug identifier, such as a MachO file without `LC_UUID`.
+    pub fn identifiers(&self) -> Result<ObjectIdentifiers, ObjectIdentifiersError> {
+        let code_id = self.code_id();
+        if let Object::Elf(_) = self {
+            if code_id.is_none() {
+                return Err(ObjectIdentifiersErrorKind::MissingBuildId.into());
+            }
+        }
+
+        let debug_id = self.debug_id();
+        if debug_id.is_nil() {
+            return Err(ObjectIdentifiersErrorKind::MissingDebugId.into());
+        }
+
+        Ok(ObjectIdentifiers {
+            code_id,
+            debug_id,
+            arch: self.arch(),
+            kind: self.kind(),
+        })
+    }
+
+    /// Checks the debug information of this object for defects.
+    ///
+    /// This reads all functions from the [`debug_session`](Self::debug_session) and returns a
+    /// [`ValidationReport`] listing:
+    ///
+    ///  - Parts of the debug information that could not be read.
+    ///  - Functions with overlapping address ranges.
+    ///  - Line records that lie outside of the address ranges of their function.
+    ///  - Line records referring to files that are not declared in the debug information.
+    ///  - Functions that extend past the end of the symbol at the same address.
+    ///
+    /// Defects do not prevent symbolication, but they usually lead to wrong or missing source
+    /// locations. An error is only returned if the debug session cannot be created at all.
+    pub fn validate(&self) -> Result<ValidationReport, ObjectError> {
+        let mut report = ValidationReport::default();
+        let mut session = self.debug_session()?;
+        session.set_collect_diagnostics(true);
+
+        let symbols = self.symbol_map();
+        let mut ranges = Vec::new();
+
+        for function in session.functions() {
+            let function = match function {
+                Ok(function) => function,
+                Err(error) => {
+                    let kind = ValidationIssueKind::CorruptDebugInfo;
+                    report.push(kind, None, error.to_string());
+                    break;
+                }
+            };
+
+            report.functions += 1;
+            let name = function.name.as_str();
+            let function_ranges = function.address_ranges().collect::<Vec<_>>();
+
+            let mut outside = None;
+            let mut outside_count = 0;
+            let mut missing_count = 0;
+            for line in &function.lines {
+                if !function_ranges.iter().any(|r| r.contains(&line.address)) {
+                    outside.get_or_insert(line.address);
+                    outside_count += 1;
+                }
+                if line.file.name.is_empty() {
+                    missing_count += 1;
+                }
+            }
+
+            if let Some(address) = outside {
+                report.push(
+                    ValidationIssueKind::LineOutsideFunction,
+                    Some(address),
+                    format!("{outside_count} line records of `{name}` lie outside of its range"),
+                );
+            }
+
+            if missing_count > 0 {
+                report.push(
+                    ValidationIssueKind::MissingFile,
+                    Some(function.address),
+                    format!("{missing_count} line records of `{name}` refer to an unknown file"),
+                );
+            }
+
+            if let Some(symbol) = symbols.lookup_exact(function.address) {
+                if function.ranges.is_empty() && symbol.size != 0 && symbol.size < function.size {
+                    report.push(
+                        ValidationIssueKind::SymbolMismatch,
+                        Some(function.address),
+                        format!(
+                            "`{name}` has size {:#x}, but symbol `{}` only covers {:#x}",
+                            function.size,
+                            symbol.name().unwrap_or("<unknown>"),
+                            symbol.size
+                        ),
+                    );
+                }
+            }
+
+            for range in function_ranges {
+                if range.start < range.end {
+                    ranges.push((range.clone(), name.to_owned()));
+                }
+            }
+        }
+
+        ranges.sort_by_key(|(range, _)| (range.start, range.end));
+        let mut previous: Option<&(std::ops::Range<u64>, String)> = None;
+        for current in &ranges {
+            if let Some(prev) = previous {
+                let (ref range, ref name) = *current;
+                let (ref prev_range, ref prev_name) = *prev;
+                if range.start < prev_range.end && range != prev_range {
+                    report.push(
+                        ValidationIssueKind::OverlappingFunctions,
+                        Some(range.start),
+                        format!("`{name}` overlaps `{prev_name}` at {:#x}", prev_range.start),
+                    );
+                }
+
+                if range.end <= prev_range.end {
+                    continue;
+                }
+            }
+
+            previous = Some(current);
+        }
+
+        for diagnostic in session.take_diagnostics() {
+            let kind = ValidationIssueKind::CorruptDebugInfo;
+            report.push(kind, None, diagnostic.to_string());
+        }
+
+        report
+            .issues
+            .sort_by_key(|issue| (issue.kind as u8, issue.address));
+        Ok(report)
+    }
+
     /// Determines whether this object exposes a public symbol table.
     pub fn has_symbols(&self) -> bool {
         match_inner!(self, Object(ref o) => o.has_symbols())
@@ -281,6 +625,78 @@ impl<'data> Object<'data> {
         match_inner!(self, Object(ref o) => o.symbol_map())
     }
 
+    /// Returns an interval map of all symbols in the symbol table, including aliases.
+    pub fn symbol_interval_map(&self) -> SymbolIntervalMap<'data> {
+        match *self {
+            Object::Elf(ref o) => o.symbol_interval_map(),
+            _ => self.symbols().collect(),
+        }
+    }
+
+    /// Returns all symbols exported by this object for dynamic linking.
+    ///
+    /// This is supported for ELF, MachO and PE files. Other formats return an empty list.
+    pub fn exports(&self) -> Result<Vec<Export<'data>>, ObjectError> {
+        match *self {
+            Object::Elf(ref o) => Ok(o.exports()),
+            Object::MachO(ref o) => o.exports().map_err(ObjectError::transparent),
+            Object::Pe(ref o) => Ok(o.exports()),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// Returns all symbols imported by this object from shared libraries.
+    ///
+    /// This is supported for ELF, MachO and PE files. Other formats return an empty list.
+    pub fn imports(&self) -> Result<Vec<Import<'data>>, ObjectError> {
+        match *self {
+            Object::Elf(ref o) => Ok(o.imports()),
+            Object::MachO(ref o) => o.imports().map_err(ObjectError::transparent),
+            Object::Pe(ref o) => Ok(o.imports()),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// Returns the address of the entry point relative to the load address.
+    ///
+    /// This is supported for ELF, MachO and PE files. Other formats, as well as libraries without
+    /// an entry point, return `None`.
+    pub fn entry_point(&self) -> Option<u64> {
+        match *self {
+            Object::Elf(ref o) => o.entry_point(),
+            Object::MachO(ref o) => o.entry_point(),
+            Object::Pe(ref o) => o.entry_point(),
+            _ => None,
+        }
+    }
+
+    /// Returns the addresses of functions called when the image is loaded, relative to the load
+    /// address.
+    ///
+    /// These are the init arrays of ELF files, module initializers of MachO files, and TLS
+    /// callbacks of PE files. Other formats return an empty list.
+    pub fn initializers(&self) -> Vec<u64> {
+        match *self {
+            Object::Elf(ref o) => o.initializers(),
+            Object::MachO(ref o) => o.initializers(),
+            Object::Pe(ref o) => o.initializers(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns the addresses of functions called when the image is unloaded, relative to the load
+    /// address.
+    ///
+    /// These are the fini arrays of ELF files and module terminators of MachO files. Other
+    /// formats return an empty list.
+    pub fn terminators(&self) -> Vec<u64> {
+        match *self {
+            Object::Elf(ref o) => o.terminators(),
+            Object::MachO(ref o) => o.terminators(),
+            _ => Vec::new(),
+        }
+    }
+
     /// Determines whether this object contains debug information.
     pub fn has_debug_info(&self) -> bool {
         match_inner!(self, Object(ref o) => o.has_debug_info())
@@ -305,10 +721,24 @@ impl<'data> Object<'data> {
                 .debug_session()
                 .map(ObjectDebugSession::Breakpad)
                 .map_err(ObjectError::transparent),
+            // Fall back to the tables of the Go runtime for binaries with stripped DWARF.
+            Object::Elf(ref o) if !o.has_section("debug_info") && o.has_go_pclntab() => o
+                .go_session()
+                .map(ObjectDebugSession::Go)
+                .map_err(ObjectError::transparent),
             Object::Elf(ref o) => o
                 .debug_session()
                 .map(ObjectDebugSession::Dwarf)
                 .map_err(ObjectError::transparent),
+            Object::MachO(ref o) if !o.has_section("debug_info") && o.has_go_pclntab() => o
+                .go_session()
+                .map(ObjectDebugSession::Go)
+                .map_err(ObjectError::transparent),
+            // Fall back to STABS for objects from older toolchains that do not contain DWARF.
+            Object::MachO(ref o) if !o.has_section("debug_info") && o.has_stabs() => o
+                .stabs_session()
+                .map(ObjectDebugSession::Stabs)
+                .map_err(ObjectError::transparent),
             Object::MachO(ref o) => o
                 .debug_session()
                 .map(ObjectDebugSession::Dwarf)
@@ -317,6 +747,11 @@ impl<'data> Object<'data> {
                 .debug_session()
                 .map(ObjectDebugSession::Pdb)
                 .map_err(ObjectError::transparent),
+            // Binaries built with MinGW carry DWARF instead of referring to a PDB.
+            Object::Pe(ref o) if o.has_debug_info() => o
+                .dwarf_session()
+                .map(ObjectDebugSession::Dwarf)
+                .map_err(ObjectError::transparent),
             Object::Pe(ref o) => o
                 .debug_session()
                 .map(ObjectDebugSession::Pe)
@@ -332,6 +767,31 @@ impl<'data> Object<'data> {
         }
     }
 
+    /// Builds an owned index of the symbols and functions of this object.
+    ///
+    /// The [`ObjectLookup`] resolves addresses to functions, inlinees and lines without writing a
+    /// SymCache, which suits tools that symbolicate addresses interactively. Functions are only
+    /// loaded if the object [contains debug information](Self::has_debug_info). Otherwise, the
+    /// index resolves addresses to symbols only.
+    pub fn make_lookup(&self) -> Result<ObjectLookup, ObjectError> {
+        let symbols = self
+            .symbol_map()
+            .into_iter()
+            .map(Symbol::into_owned)
+            .collect();
+
+        let functions = if self.has_debug_info() {
+            self.debug_session()?
+                .functions()
+                .map(|function| function.map(Function::into_owned))
+                .collect::<Result<_, _>>()?
+        } else {
+            Vec::new()
+        };
+
+        Ok(ObjectLookup::new(symbols, functions))
+    }
+
     /// Determines whether this object contains stack unwinding information.
     pub fn has_unwind_info(&self) -> bool {
         match_inner!(self, Object(ref o) => o.has_unwind_info())
@@ -347,6 +807,13 @@ impl<'data> Object<'data> {
         match_inner!(self, Object(ref o) => o.is_malformed())
     }
 
+    /// Returns the kinds of information contained in this object.
+    ///
+    /// This is a cheap way to classify objects, for instance to decide which files to upload.
+    pub fn features(&self) -> ObjectFeatures {
+        match_inner!(self, Object(ref o) => o.features())
+    }
+
     /// Returns the raw data of the underlying buffer.
     pub fn data(&self) -> &'data [u8] {
         match_inner!(self, Object(ref o) => o.data())
@@ -421,6 +888,82 @@ impl<'data: 'object, 'object> ObjectLike<'data, 'object> for Object<'data> {
     fn is_malformed(&self) -> bool {
         self.is_malformed()
     }
+
+    fn features(&self) -> ObjectFeatures {
+        self.features()
+    }
+}
+
+/// Writes modified copies of object files.
+///
+/// This can split debug information off a binary, similar to `objcopy --strip-debug` and
+/// `objcopy --only-keep-debug`. Both outputs retain the identifiers of the original object, so
+/// that debug files can still be matched to their binaries. This is supported for ELF and MachO
+/// files. Other formats return an error.
+///
+/// # Example
+///
+/// ```no_run
+/// use symbolic_debuginfo::{Object, ObjectWriter};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let data = std::fs::read("crash")?;
+/// let object = Object::parse(&data)?;
+///
+/// let mut writer = ObjectWriter::new(std::fs::File::create("crash.debug")?);
+/// writer.write_debug_companion(&object)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ObjectWriter<W> {
+    writer: W,
+}
+
+impl<W> ObjectWriter<W>
+where
+    W: Write,
+{
+    /// Creates a new writer into the given output.
+    pub fn new(writer: W) -> Self {
+        ObjectWriter { writer }
+    }
+
+    /// Writes a copy of the object without debug information.
+    ///
+    /// The symbol table is retained so that stack traces can still be symbolicated by name.
+    pub fn write_stripped(&mut self, object: &Object<'_>) -> Result<(), ObjectError> {
+        let data = match *object {
+            Object::Elf(ref o) => o.strip_debug().map_err(ObjectError::transparent)?,
+            Object::MachO(ref o) => o.strip_debug().map_err(ObjectError::transparent)?,
+            _ => return Err(ObjectError::new(ObjectErrorRepr::UnsupportedObject)),
+        };
+
+        self.write_all(&data)
+    }
+
+    /// Writes a copy of the object that only contains debug information and symbols.
+    pub fn write_debug_companion(&mut self, object: &Object<'_>) -> Result<(), ObjectError> {
+        let data = match *object {
+            Object::Elf(ref o) => o.debug_companion().map_err(ObjectError::transparent)?,
+            Object::MachO(ref o) => o.debug_companion().map_err(ObjectError::transparent)?,
+            _ => return Err(ObjectError::new(ObjectErrorRepr::UnsupportedObject)),
+        };
+
+        self.write_all(&data)
+    }
+
+    /// Returns the underlying output.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn write_all(&mut self, data: &[u8]) -> Result<(), ObjectError> {
+        self.writer
+            .write_all(data)
+            .and_then(|()| self.writer.flush())
+            .map_err(ObjectError::transparent)
+    }
 }
 
 /// A generic debugging session.
@@ -429,9 +972,11 @@ impl<'data: 'object, 'object> ObjectLike<'data, 'object> for Object<'data> {
 pub enum ObjectDebugSession<'d> {
     Breakpad(BreakpadDebugSession<'d>),
     Dwarf(DwarfDebugSession<'d>),
+    Go(GoDebugSession<'d>),
     Pdb(PdbDebugSession<'d>),
     Pe(PeDebugSession<'d>),
     SourceBundle(SourceBundleDebugSession<'d>),
+    Stabs(StabsDebugSession<'d>),
 }
 
 impl<'d> ObjectDebugSession<'d> {
@@ -446,11 +991,13 @@ impl<'d> ObjectDebugSession<'d> {
         match *self {
             ObjectDebugSession::Breakpad(ref s) => ObjectFunctionIterator::Breakpad(s.functions()),
             ObjectDebugSession::Dwarf(ref s) => ObjectFunctionIterator::Dwarf(s.functions()),
+            ObjectDebugSession::Go(ref s) => ObjectFunctionIterator::Go(s.functions()),
             ObjectDebugSession::Pdb(ref s) => ObjectFunctionIterator::Pdb(s.functions()),
             ObjectDebugSession::Pe(ref s) => ObjectFunctionIterator::Pe(s.functions()),
             ObjectDebugSession::SourceBundle(ref s) => {
                 ObjectFunctionIterator::SourceBundle(s.functions())
             }
+            ObjectDebugSession::Stabs(ref s) => ObjectFunctionIterator::Stabs(s.functions()),
         }
     }
 
@@ -459,9 +1006,117 @@ impl<'d> ObjectDebugSession<'d> {
         match *self {
             ObjectDebugSession::Breakpad(ref s) => ObjectFileIterator::Breakpad(s.files()),
             ObjectDebugSession::Dwarf(ref s) => ObjectFileIterator::Dwarf(s.files()),
+            ObjectDebugSession::Go(ref s) => ObjectFileIterator::Go(s.files()),
             ObjectDebugSession::Pdb(ref s) => ObjectFileIterator::Pdb(s.files()),
             ObjectDebugSession::Pe(ref s) => ObjectFileIterator::Pe(s.files()),
             ObjectDebugSession::SourceBundle(ref s) => ObjectFileIterator::SourceBundle(s.files()),
+            ObjectDebugSession::Stabs(ref s) => ObjectFileIterator::Stabs(s.files()),
+        }
+    }
+
+    /// Sets a [`PathRemapper`] applied to all compilation directories and file paths in this debug
+    /// session.
+    ///
+    /// This is supported for Breakpad, DWARF, Go, PDB and STABS debug sessions. PE files do not contain
+    /// file information, and source bundles already store the paths under which their sources are
+    /// looked up, so both are left unchanged.
+    pub fn set_path_remapper(&mut self, remapper: Option<PathRemapper>) {
+        match *self {
+            ObjectDebugSession::Breakpad(ref mut s) => s.set_path_remapper(remapper),
+            ObjectDebugSession::Dwarf(ref mut s) => s.set_path_remapper(remapper),
+            ObjectDebugSession::Go(ref mut s) => s.set_path_remapper(remapper),
+            ObjectDebugSession::Pdb(ref mut s) => s.set_path_remapper(remapper),
+            ObjectDebugSession::Stabs(ref mut s) => s.set_path_remapper(remapper),
+            ObjectDebugSession::Pe(_) | ObjectDebugSession::SourceBundle(_) => {}
+        }
+    }
+
+    /// Enables collection of [`Diagnostic`]s instead of failing on corrupted debug information.
+    ///
+    /// This is supported for Breakpad, DWARF and PDB debug sessions. See
+    /// [`DwarfDebugSession::set_collect_diagnostics`] for more information.
+    pub fn set_collect_diagnostics(&mut self, collect: bool) {
+        match *self {
+            ObjectDebugSession::Breakpad(ref mut s) => s.set_collect_diagnostics(collect),
+            ObjectDebugSession::Dwarf(ref mut s) => s.set_collect_diagnostics(collect),
+            ObjectDebugSession::Pdb(ref mut s) => s.set_collect_diagnostics(collect),
+            _ => {}
+        }
+    }
+
+    /// Enables interning of function names and file paths computed by this session.
+    ///
+    /// This is supported for DWARF and PDB debug sessions. See
+    /// [`DwarfDebugSession::set_intern_strings`] for more information.
+    pub fn set_intern_strings(&mut self, intern: bool) {
+        match *self {
+            ObjectDebugSession::Dwarf(ref mut s) => s.set_intern_strings(intern),
+            ObjectDebugSession::Pdb(ref mut s) => s.set_intern_strings(intern),
+            _ => {}
+        }
+    }
+
+    /// Limits the nesting depth of inlinees of all functions yielded by this session.
+    ///
+    /// This is supported for DWARF and PDB debug sessions. Other debug sessions do not yield
+    /// inlinees. See [`Function::limit_inline_depth`] for more information.
+    pub fn set_max_inline_depth(&mut self, max_depth: Option<usize>) {
+        match *self {
+            ObjectDebugSession::Dwarf(ref mut s) => s.set_max_inline_depth(max_depth),
+            ObjectDebugSession::Pdb(ref mut s) => s.set_max_inline_depth(max_depth),
+            _ => {}
+        }
+    }
+
+    /// Sets the [`SessionLimits`] enforced by the function iterators of this session.
+    ///
+    /// Once a limit is exceeded, the function iterator yields an error carrying [`LimitExceeded`]
+    /// as its source and stops. Source bundles do not contain functions and ignore the limits.
+    /// There are no limits by default.
+    pub fn set_limits(&mut self, limits: SessionLimits) {
+        match *self {
+            ObjectDebugSession::Breakpad(ref mut s) => s.set_limits(limits),
+            ObjectDebugSession::Dwarf(ref mut s) => s.set_limits(limits),
+            ObjectDebugSession::Go(ref mut s) => s.set_limits(limits),
+            ObjectDebugSession::Pdb(ref mut s) => s.set_limits(limits),
+            ObjectDebugSession::Pe(ref mut s) => s.set_limits(limits),
+            ObjectDebugSession::SourceBundle(_) => {}
+            ObjectDebugSession::Stabs(ref mut s) => s.set_limits(limits),
+        }
+    }
+
+    /// Reports the addresses of all functions and lines for an image loaded at `load_address`.
+    ///
+    /// This is supported for DWARF debug sessions. Other debug sessions always yield addresses
+    /// relative to the start of the image. See [`DwarfDebugSession::set_load_address`] for more
+    /// information.
+    pub fn set_load_address(&mut self, load_address: u64) {
+        if let ObjectDebugSession::Dwarf(ref mut s) = *self {
+            s.set_load_address(load_address);
+        }
+    }
+
+    /// Returns all [`Diagnostic`]s collected so far and clears them.
+    ///
+    /// Debug sessions that do not support collection mode always return an empty list.
+    pub fn take_diagnostics(&self) -> Vec<Diagnostic> {
+        match *self {
+            ObjectDebugSession::Breakpad(ref s) => s.take_diagnostics(),
+            ObjectDebugSession::Dwarf(ref s) => s.take_diagnostics(),
+            ObjectDebugSession::Pdb(ref s) => s.take_diagnostics(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Looks up the remote URL of a file by its full canonicalized path.
+    ///
+    /// This is supported for PDBs with embedded SourceLink information and source bundles. All
+    /// other debug sessions return `None`.
+    pub fn source_url_by_path(&self, path: &str) -> Option<Cow<'_, str>> {
+        match *self {
+            ObjectDebugSession::Pdb(ref s) => s.source_url_by_path(path),
+            ObjectDebugSession::SourceBundle(ref s) => s.source_url_by_path(path),
+            _ => None,
         }
     }
 
@@ -476,6 +1131,9 @@ impl<'d> ObjectDebugSession<'d> {
             ObjectDebugSession::Dwarf(ref s) => {
                 s.source_by_path(path).map_err(ObjectError::transparent)
             }
+            ObjectDebugSession::Go(ref s) => {
+                s.source_by_path(path).map_err(ObjectError::transparent)
+            }
             ObjectDebugSession::Pdb(ref s) => {
                 s.source_by_path(path).map_err(ObjectError::transparent)
             }
@@ -485,6 +1143,9 @@ impl<'d> ObjectDebugSession<'d> {
             ObjectDebugSession::SourceBundle(ref s) => {
                 s.source_by_path(path).map_err(ObjectError::transparent)
             }
+            ObjectDebugSession::Stabs(ref s) => {
+                s.source_by_path(path).map_err(ObjectError::transparent)
+            }
         }
     }
 }
@@ -507,14 +1168,50 @@ impl<'session> DebugSession<'session> for ObjectDebugSession<'_> {
     }
 }
 
+macro_rules! impl_dyn_debug_session {
+    ($($ty:ident),*) => {
+        $(
+            impl DynDebugSession for $ty<'_> {
+                fn functions(&self) -> DynIterator<'_, Result<Function<'_>, DynDebugSessionError>> {
+                    Box::new(self.functions().map(|result| result.map_err(Into::into)))
+                }
+
+                fn files(&self) -> DynIterator<'_, Result<FileEntry<'_>, DynDebugSessionError>> {
+                    Box::new(self.files().map(|result| result.map_err(Into::into)))
+                }
+
+                fn source_by_path(
+                    &self,
+                    path: &str,
+                ) -> Result<Option<Cow<'_, str>>, DynDebugSessionError> {
+                    self.source_by_path(path).map_err(Into::into)
+                }
+            }
+        )*
+    };
+}
+
+impl_dyn_debug_session!(
+    ObjectDebugSession,
+    BreakpadDebugSession,
+    DwarfDebugSession,
+    GoDebugSession,
+    PdbDebugSession,
+    PeDebugSession,
+    SourceBundleDebugSession,
+    StabsDebugSession
+);
+
 /// An iterator over functions in an [`Object`](enum.Object.html).
 #[allow(missing_docs)]
 pub enum ObjectFunctionIterator<'s> {
     Breakpad(BreakpadFunctionIterator<'s>),
     Dwarf(DwarfFunctionIterator<'s>),
+    Go(GoSessionFunctionIterator<'s>),
     Pdb(PdbFunctionIterator<'s>),
     Pe(PeFunctionIterator<'s>),
     SourceBundle(SourceBundleFunctionIterator<'s>),
+    Stabs(StabsFunctionIterator<'s>),
 }
 
 impl<'s> Iterator for ObjectFunctionIterator<'s> {
@@ -528,6 +1225,9 @@ impl<'s> Iterator for ObjectFunctionIterator<'s> {
             ObjectFunctionIterator::Dwarf(ref mut i) => {
                 Some(i.next()?.map_err(ObjectError::transparent))
             }
+            ObjectFunctionIterator::Go(ref mut i) => {
+                Some(i.next()?.map_err(ObjectError::transparent))
+            }
             ObjectFunctionIterator::Pdb(ref mut i) => {
                 Some(i.next()?.map_err(ObjectError::transparent))
             }
@@ -537,6 +1237,9 @@ impl<'s> Iterator for ObjectFunctionIterator<'s> {
             ObjectFunctionIterator::SourceBundle(ref mut i) => {
                 Some(i.next()?.map_err(ObjectError::transparent))
             }
+            ObjectFunctionIterator::Stabs(ref mut i) => {
+                Some(i.next()?.map_err(ObjectError::transparent))
+            }
         }
     }
 }
@@ -547,9 +1250,11 @@ impl<'s> Iterator for ObjectFunctionIterator<'s> {
 pub enum ObjectFileIterator<'s> {
     Breakpad(BreakpadFileIterator<'s>),
     Dwarf(DwarfFileIterator<'s>),
+    Go(GoFileIterator<'s>),
     Pdb(PdbFileIterator<'s>),
     Pe(PeFileIterator<'s>),
     SourceBundle(SourceBundleFileIterator<'s>),
+    Stabs(StabsFileIterator<'s>),
 }
 
 impl<'s> Iterator for ObjectFileIterator<'s> {
@@ -563,11 +1268,15 @@ impl<'s> Iterator for ObjectFileIterator<'s> {
             ObjectFileIterator::Dwarf(ref mut i) => {
                 Some(i.next()?.map_err(ObjectError::transparent))
             }
+            ObjectFileIterator::Go(ref mut i) => Some(i.next()?.map_err(ObjectError::transparent)),
             ObjectFileIterator::Pdb(ref mut i) => Some(i.next()?.map_err(ObjectError::transparent)),
             ObjectFileIterator::Pe(ref mut i) => Some(i.next()?.map_err(ObjectError::transparent)),
             ObjectFileIterator::SourceBundle(ref mut i) => {
                 Some(i.next()?.map_err(ObjectError::transparent))
             }
+            ObjectFileIterator::Stabs(ref mut i) => {
+                Some(i.next()?.map_err(ObjectError::transparent))
+            }
         }
     }
 }
@@ -712,6 +1421,35 @@ impl<'d> Archive<'d> {
     pub fn is_multi(&self) -> bool {
         match_inner!(self.0, ArchiveInner(ref a) => a.is_multi())
     }
+
+    /// Extracts the identifiers of all objects in this archive.
+    ///
+    /// Each object is validated as described in [`Object::identifiers`]. Additionally, this returns
+    /// [`ConflictingIdentifiers`](ObjectIdentifiersErrorKind::ConflictingIdentifiers) if two
+    /// objects in a fat archive share the same debug identifier, and
+    /// [`BadObject`](ObjectIdentifiersErrorKind::BadObject) if any of the objects cannot be parsed.
+    pub fn identifiers(&self) -> Result<Vec<ObjectIdentifiers>, ObjectIdentifiersError> {
+        let mut identifiers = Vec::<ObjectIdentifiers>::with_capacity(self.object_count());
+
+        for object in self.objects() {
+            let ids = object?.identifiers()?;
+
+            if let Some(other) = identifiers.iter().find(|i| i.debug_id == ids.debug_id) {
+                let message = format!(
+                    "debug id {} used by both {} and {}",
+                    ids.debug_id, other.arch, ids.arch
+                );
+                return Err(ObjectIdentifiersError::new(
+                    ObjectIdentifiersErrorKind::ConflictingIdentifiers,
+                    message,
+                ));
+            }
+
+            identifiers.push(ids);
+        }
+
+        Ok(identifiers)
+    }
 }
 
 impl<'slf, 'd: 'slf> AsSelf<'slf> for Archive<'d> {
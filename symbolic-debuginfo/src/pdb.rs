@@ -4,28 +4,33 @@ use std::borrow::Cow;
 use std::cell::{RefCell, RefMut};
 use std::cmp::Ordering;
 use std::collections::btree_map::{BTreeMap, Entry};
+use std::convert::TryFrom;
 use std::error::Error;
 use std::fmt;
-use std::io::Cursor;
+use std::io;
+use std::ops::Range;
 use std::sync::Arc;
 
 use lazycell::LazyCell;
 use parking_lot::RwLock;
 use pdb::{
     AddressMap, FallibleIterator, InlineSiteSymbol, ItemIndex, LineProgram, MachineType, Module,
-    ModuleInfo, PdbInternalSectionOffset, ProcedureSymbol, SymbolData,
+    ModuleInfo, PdbInternalSectionOffset, ProcedureSymbol, SymbolData, SymbolIndex,
 };
 use smallvec::SmallVec;
 use thiserror::Error;
 
 use symbolic_common::{
-    Arch, AsSelf, CodeId, CpuFamily, DebugId, Language, Name, NameMangling, SelfCell, Uuid,
+    Arch, AsSelf, CodeId, CpuFamily, DebugId, Language, Name, NameMangling, PathRemapper, SelfCell,
+    Uuid,
 };
 
 use crate::base::*;
 use crate::shared::{FunctionStack, Parse};
+use crate::sourcelink::SourceLinkMappings;
+use crate::srcsrv::{SourceServerFile, SourceServerMappings};
 
-type Pdb<'data> = pdb::PDB<'data, Cursor<&'data [u8]>>;
+type Pdb<'data> = pdb::PDB<'data, PdbSource<'data>>;
 
 const MAGIC_BIG: &[u8] = b"Microsoft C/C++ MSF 7.00\r\n\x1a\x44\x53\x00\x00\x00";
 
@@ -45,6 +50,9 @@ pub enum PdbErrorKind {
 
     /// Formatting of a type name failed.
     FormattingFailed,
+
+    /// A limit of the debug session was exceeded, see [`SessionLimits`].
+    LimitExceeded,
 }
 
 impl fmt::Display for PdbErrorKind {
@@ -53,17 +61,19 @@ impl fmt::Display for PdbErrorKind {
             Self::BadObject => write!(f, "invalid pdb file"),
             Self::UnexpectedInline => write!(f, "unexpected inline function without parent"),
             Self::FormattingFailed => write!(f, "failed to format type name"),
+            Self::LimitExceeded => write!(f, "debug session limit exceeded"),
         }
     }
 }
 
 /// An error when dealing with [`PdbObject`](struct.PdbObject.html).
 #[derive(Debug, Error)]
-#[error("{kind}")]
+#[error("{kind}{}", LocationSuffix(.location))]
 pub struct PdbError {
     kind: PdbErrorKind,
     #[source]
     source: Option<Box<dyn Error + Send + Sync + 'static>>,
+    location: Option<ErrorLocation>,
 }
 
 impl PdbError {
@@ -74,18 +84,42 @@ impl PdbError {
         E: Into<Box<dyn Error + Send + Sync>>,
     {
         let source = Some(source.into());
-        Self { kind, source }
+        Self {
+            kind,
+            source,
+            location: None,
+        }
     }
 
     /// Returns the corresponding [`PdbErrorKind`] for this error.
     pub fn kind(&self) -> PdbErrorKind {
         self.kind
     }
+
+    /// Returns the location in the file at which this error occurred, if known.
+    pub fn location(&self) -> Option<&ErrorLocation> {
+        self.location.as_ref()
+    }
+
+    /// Converts this error into a [`Diagnostic`].
+    pub(crate) fn to_diagnostic(&self) -> Diagnostic {
+        Diagnostic::from_error(self.kind, self.source.as_deref(), self.location.as_ref())
+    }
 }
 
 impl From<PdbErrorKind> for PdbError {
     fn from(kind: PdbErrorKind) -> Self {
-        Self { kind, source: None }
+        Self {
+            kind,
+            source: None,
+            location: None,
+        }
+    }
+}
+
+impl From<LimitExceeded> for PdbError {
+    fn from(e: LimitExceeded) -> Self {
+        Self::new(PdbErrorKind::LimitExceeded, e)
     }
 }
 
@@ -101,6 +135,108 @@ impl From<fmt::Error> for PdbError {
     }
 }
 
+/// A [`pdb::Source`] that reads pages of a PDB directly from memory.
+///
+/// The pdb crate requests streams as a list of pages. If these pages are laid out contiguously in
+/// the file, the view borrows them without copying. Only streams with scattered pages are copied
+/// into a buffer. Combined with a memory mapped [`ByteView`](symbolic_common::ByteView), this
+/// only loads the pages of streams that are actually accessed, which keeps the memory footprint
+/// of large PDBs low.
+///
+/// Pages are addressed with 64-bit offsets, so this supports PDBs larger than 4 GiB, which use
+/// page sizes above the default of 4 KiB.
+#[derive(Clone, Copy)]
+pub struct PdbSource<'data> {
+    data: &'data [u8],
+}
+
+impl<'data> PdbSource<'data> {
+    /// Creates a source for the PDB contained in `data`.
+    pub fn new(data: &'data [u8]) -> Self {
+        Self { data }
+    }
+
+    /// Returns the given range of the data, or an error if it is out of bounds.
+    fn slice(&self, offset: u64, size: usize) -> Result<&'data [u8], io::Error> {
+        usize::try_from(offset)
+            .ok()
+            .and_then(|start| self.data.get(start..start.checked_add(size)?))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "page out of bounds"))
+    }
+}
+
+impl fmt::Debug for PdbSource<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PdbSource")
+            .field("len", &self.data.len())
+            .finish()
+    }
+}
+
+impl<'data> pdb::Source<'data> for PdbSource<'data> {
+    fn view(
+        &mut self,
+        slices: &[pdb::SourceSlice],
+    ) -> Result<Box<dyn pdb::SourceView<'data>>, io::Error> {
+        let start = slices.first().map_or(0, |slice| slice.offset);
+        let mut end = start;
+        let contiguous = slices.iter().all(|slice| {
+            let adjacent = slice.offset == end;
+            end = slice.offset.saturating_add(slice.size as u64);
+            adjacent
+        });
+
+        let view = if contiguous {
+            PdbSourceView::Borrowed(self.slice(start, (end - start) as usize)?)
+        } else {
+            let size = slices.iter().map(|slice| slice.size).sum();
+            let mut bytes = Vec::with_capacity(size);
+            for slice in slices {
+                bytes.extend_from_slice(self.slice(slice.offset, slice.size)?);
+            }
+            PdbSourceView::Owned(bytes)
+        };
+
+        // SAFETY: The pdb crate requires boxed views to be `'static`, which only affects the
+        // lifetime bound of the trait object. The view type still names `'data`, so the view can
+        // never outlive the data borrowed by this source.
+        Ok(unsafe {
+            std::mem::transmute::<
+                Box<dyn pdb::SourceView<'data> + 'data>,
+                Box<dyn pdb::SourceView<'data>>,
+            >(Box::new(view))
+        })
+    }
+}
+
+/// A view of pages returned by [`PdbSource`].
+///
+/// Contiguous pages are borrowed from the data of the source, scattered pages are copied.
+enum PdbSourceView<'data> {
+    Borrowed(&'data [u8]),
+    Owned(Vec<u8>),
+}
+
+impl fmt::Debug for PdbSourceView<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (kind, len) = match self {
+            Self::Borrowed(bytes) => ("Borrowed", bytes.len()),
+            Self::Owned(bytes) => ("Owned", bytes.len()),
+        };
+
+        f.debug_tuple(kind).field(&len).finish()
+    }
+}
+
+impl<'data> pdb::SourceView<'data> for PdbSourceView<'data> {
+    fn as_slice(&self) -> &[u8] {
+        match self {
+            Self::Borrowed(bytes) => bytes,
+            Self::Owned(bytes) => bytes,
+        }
+    }
+}
+
 /// Program Database, the debug companion format on Windows.
 ///
 /// This object is a sole debug companion to [`PeObject`](../pdb/struct.PdbObject.html).
@@ -112,10 +248,10 @@ pub struct PdbObject<'data> {
     data: &'data [u8],
 }
 
-// NB: The pdb crate simulates mmap behavior on any Read + Seek type. This implementation requires
-// mutability of the `Source` and uses trait objects without a Send + Sync barrier. We know that we
-// only instanciate `&[u8]` as source. Whenever we mutate the reader (to read a new module stream),
-// we acquire a write lock on the PDB, which should be sufficient.
+// NB: The pdb crate requires mutability of the `Source` and uses trait objects without a Send +
+// Sync barrier. We know that we only instanciate `PdbSource` over `&[u8]` as source. Whenever we
+// mutate the reader (to read a new module stream), we acquire a write lock on the PDB, which should
+// be sufficient.
 unsafe impl Send for PdbObject<'_> {}
 unsafe impl Sync for PdbObject<'_> {}
 
@@ -129,7 +265,7 @@ impl<'data> PdbObject<'data> {
 
     /// Tries to parse a PDB object from the given slice.
     pub fn parse(data: &'data [u8]) -> Result<Self, PdbError> {
-        let mut pdb = Pdb::open(Cursor::new(data))?;
+        let mut pdb = Pdb::open(PdbSource::new(data))?;
         let dbi = pdb.debug_information()?;
         let pdbi = pdb.pdb_information()?;
         let pubi = pdb.global_symbols()?;
@@ -259,6 +395,34 @@ impl<'data> PdbObject<'data> {
         self.data
     }
 
+    /// Returns the SourceLink mappings embedded in the `sourcelink` stream of this PDB.
+    ///
+    /// Returns empty mappings if the PDB does not contain SourceLink information.
+    pub fn source_links(&self) -> Result<SourceLinkMappings, PdbError> {
+        let stream = match self.pdb.write().named_stream(b"sourcelink") {
+            Ok(stream) => stream,
+            Err(pdb::Error::StreamNameNotFound) => return Ok(SourceLinkMappings::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        SourceLinkMappings::parse(stream.as_slice())
+            .map_err(|e| PdbError::new(PdbErrorKind::BadObject, e))
+    }
+
+    /// Returns the source server mappings embedded in the `srcsrv` stream of this PDB.
+    ///
+    /// Returns empty mappings if the PDB is not source indexed.
+    pub fn source_server(&self) -> Result<SourceServerMappings, PdbError> {
+        let stream = match self.pdb.write().named_stream(b"srcsrv") {
+            Ok(stream) => stream,
+            Err(pdb::Error::StreamNameNotFound) => return Ok(SourceServerMappings::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        SourceServerMappings::parse(stream.as_slice())
+            .map_err(|e| PdbError::new(PdbErrorKind::BadObject, e))
+    }
+
     #[doc(hidden)]
     pub fn inner(&self) -> &RwLock<Pdb<'data>> {
         &self.pdb
@@ -536,13 +700,38 @@ impl<'d> PdbDebugInfo<'d> {
     }
 
     /// Returns an iterator over all compilation units (modules).
-    fn units(&'d self) -> PdbUnitIterator<'_> {
+    fn units(&'d self, diagnostics: &'d Diagnostics) -> PdbUnitIterator<'_> {
         PdbUnitIterator {
             debug_info: self,
+            diagnostics,
             index: 0,
         }
     }
 
+    /// Attaches the name of a module and the index of a symbol within it to an error.
+    ///
+    /// Symbol indexes are byte offsets into the module's symbol stream.
+    fn locate_error(
+        &self,
+        mut error: PdbError,
+        module_index: usize,
+        symbol: Option<SymbolIndex>,
+    ) -> PdbError {
+        if error.location.is_none() {
+            let name = match self.modules.get(module_index) {
+                Some(module) => module.module_name().into_owned(),
+                None => format!("module {}", module_index),
+            };
+
+            error.location = Some(ErrorLocation {
+                section: Some(name.into()),
+                offset: symbol.map(|index| u64::from(index.0)),
+            });
+        }
+
+        error
+    }
+
     fn get_module(&'d self, index: usize) -> Result<Option<&ModuleInfo<'_>>, PdbError> {
         // Silently ignore module references out-of-bound
         let cell = match self.module_infos.get(index) {
@@ -623,6 +812,13 @@ impl<'slf, 'd: 'slf> AsSelf<'slf> for PdbDebugInfo<'d> {
 /// Debug session for PDB objects.
 pub struct PdbDebugSession<'d> {
     cell: SelfCell<Box<PdbStreams<'d>>, PdbDebugInfo<'d>>,
+    source_links: SourceLinkMappings,
+    source_server: SourceServerMappings,
+    path_remapper: Option<PathRemapper>,
+    interner: Option<StringInterner>,
+    max_inline_depth: Option<usize>,
+    limits: SessionLimits,
+    diagnostics: Diagnostics,
 }
 
 impl<'d> PdbDebugSession<'d> {
@@ -632,15 +828,84 @@ impl<'d> PdbDebugSession<'d> {
             PdbDebugInfo::build(pdb, unsafe { &*streams })
         })?;
 
-        Ok(PdbDebugSession { cell })
+        // Invalid SourceLink information should not prevent reading debug information.
+        let source_links = pdb.source_links().unwrap_or_default();
+        let source_server = pdb.source_server().unwrap_or_default();
+
+        Ok(PdbDebugSession {
+            cell,
+            source_links,
+            source_server,
+            path_remapper: None,
+            interner: None,
+            max_inline_depth: None,
+            limits: SessionLimits::default(),
+            diagnostics: Diagnostics::default(),
+        })
+    }
+
+    /// Sets a [`PathRemapper`] applied to all file paths in this debug session.
+    ///
+    /// Remapping is disabled by default. SourceLink mappings are not affected, so
+    /// [`source_url_by_path`](Self::source_url_by_path) still expects the original paths.
+    pub fn set_path_remapper(&mut self, remapper: Option<PathRemapper>) {
+        self.path_remapper = remapper;
+    }
+
+    /// Enables collection of [`Diagnostic`]s instead of failing on corrupted debug information.
+    ///
+    /// When enabled, modules that cannot be read are skipped by all iterators of this session, and
+    /// the errors are recorded along with inline sites that had to be skipped. Use
+    /// [`take_diagnostics`](Self::take_diagnostics) to retrieve them after iterating. This is
+    /// disabled by default.
+    pub fn set_collect_diagnostics(&mut self, collect: bool) {
+        self.diagnostics.set_enabled(collect);
+    }
+
+    /// Enables interning of function names and file paths computed by this session.
+    ///
+    /// PDBs store most function names as references to type information, so names are formatted
+    /// for every function that refers to them. When enabled, such strings are stored once in a
+    /// [`StringInterner`] owned by this session and borrowed by all yielded functions. Interned
+    /// strings are retained until the session is dropped. This is disabled by default.
+    pub fn set_intern_strings(&mut self, intern: bool) {
+        if intern != self.interner.is_some() {
+            self.interner = intern.then(StringInterner::new);
+        }
+    }
+
+    /// Returns the [`StringInterner`] of this session, if interning is enabled.
+    pub fn interner(&self) -> Option<&StringInterner> {
+        self.interner.as_ref()
+    }
+
+    /// Limits the nesting depth of inlinees of all functions yielded by this session.
+    ///
+    /// Deeper inlinees are replaced by synthetic records as described in
+    /// [`Function::limit_inline_depth`]. There is no limit by default.
+    pub fn set_max_inline_depth(&mut self, max_depth: Option<usize>) {
+        self.max_inline_depth = max_depth;
+    }
+
+    /// Sets the [`SessionLimits`] enforced by the function iterators of this session.
+    ///
+    /// There are no limits by default.
+    pub fn set_limits(&mut self, limits: SessionLimits) {
+        self.limits = limits;
+    }
+
+    /// Returns all [`Diagnostic`]s collected so far and clears them.
+    pub fn take_diagnostics(&self) -> Vec<Diagnostic> {
+        self.diagnostics.take()
     }
 
     /// Returns an iterator over all source files in this debug file.
     pub fn files(&self) -> PdbFileIterator<'_> {
         PdbFileIterator {
             debug_info: self.cell.get(),
-            units: self.cell.get().units(),
+            units: self.cell.get().units(&self.diagnostics),
             files: pdb::FileIterator::default(),
+            remapper: self.path_remapper.as_ref(),
             finished: false,
         }
     }
@@ -648,8 +913,12 @@ impl<'d> PdbDebugSession<'d> {
     /// Returns an iterator over all functions in this debug file.
     pub fn functions(&self) -> PdbFunctionIterator<'_> {
         PdbFunctionIterator {
-            units: self.cell.get().units(),
+            units: self.cell.get().units(&self.diagnostics),
             functions: Vec::new().into_iter(),
+            remapper: self.path_remapper.as_ref(),
+            interner: self.interner.as_ref(),
+            max_inline_depth: self.max_inline_depth,
+            limits: LimitTracker::new(self.limits),
             finished: false,
         }
     }
@@ -660,6 +929,28 @@ impl<'d> PdbDebugSession<'d> {
     pub fn source_by_path(&self, _path: &str) -> Result<Option<Cow<'_, str>>, PdbError> {
         Ok(None)
     }
+
+    /// Looks up the remote URL of a file by its full canonicalized path.
+    ///
+    /// The URL is resolved from the SourceLink mappings embedded in the PDB, see
+    /// [`PdbObject::source_links`]. If there is no matching mapping, the URL is taken from the
+    /// source server stream when the file can be downloaded directly via HTTP.
+    pub fn source_url_by_path(&self, path: &str) -> Option<Cow<'_, str>> {
+        if let Some(url) = self.source_links.resolve(path) {
+            return Some(Cow::Owned(url));
+        }
+
+        let file = self.source_server.resolve(path)?;
+        file.url().map(|url| Cow::Owned(url.to_owned()))
+    }
+
+    /// Looks up how to retrieve a file from version control by its full canonicalized path.
+    ///
+    /// The information is resolved from the source server stream embedded in the PDB, see
+    /// [`PdbObject::source_server`].
+    pub fn source_server_file_by_path(&self, path: &str) -> Option<SourceServerFile> {
+        self.source_server.resolve(path)
+    }
 }
 
 impl<'session> DebugSession<'session> for PdbDebugSession<'_> {
@@ -924,6 +1215,8 @@ impl<'u, 'd> TypeFormatter<'u, 'd> {
 
 struct Unit<'s> {
     debug_info: &'s PdbDebugInfo<'s>,
+    diagnostics: &'s Diagnostics,
+    module_index: usize,
     module: &'s pdb::ModuleInfo<'s>,
     imports: pdb::CrossModuleImports<'s>,
 }
@@ -931,12 +1224,16 @@ struct Unit<'s> {
 impl<'s> Unit<'s> {
     fn load(
         debug_info: &'s PdbDebugInfo<'s>,
+        diagnostics: &'s Diagnostics,
+        module_index: usize,
         module: &'s pdb::ModuleInfo<'s>,
     ) -> Result<Self, PdbError> {
         let imports = module.imports()?;
 
         Ok(Self {
             debug_info,
+            diagnostics,
+            module_index,
             module,
             imports,
         })
@@ -958,14 +1255,14 @@ impl<'s> Unit<'s> {
         &self,
         mut line_iter: I,
         program: &LineProgram<'s>,
-    ) -> Result<Vec<LineInfo<'s>>, PdbError>
+    ) -> Result<LineRecords<'s>, PdbError>
     where
         I: FallibleIterator<Item = pdb::LineInfo>,
         PdbError: From<I::Error>,
     {
         let address_map = &self.debug_info.address_map;
 
-        let mut lines = Vec::new();
+        let mut lines = LineRecords::new();
         while let Some(line_info) = line_iter.next()? {
             let rva = match line_info.offset.to_rva(address_map) {
                 Some(rva) => u64::from(rva.0),
@@ -985,8 +1282,16 @@ impl<'s> Unit<'s> {
         Ok(lines)
     }
 
+    fn entity(&self, index: SymbolIndex) -> DebugEntity {
+        DebugEntity::PdbSymbol {
+            module: self.module_index as u32,
+            symbol: index.0,
+        }
+    }
+
     fn handle_procedure(
         &self,
+        index: SymbolIndex,
         proc: ProcedureSymbol<'s>,
         program: &LineProgram<'s>,
     ) -> Result<Option<Function<'s>>, PdbError> {
@@ -1014,16 +1319,23 @@ impl<'s> Unit<'s> {
         Ok(Some(Function {
             address,
             size: proc.len.into(),
+            ranges: Vec::new(),
             name,
-            compilation_dir: &[],
+            compilation_dir: Cow::Borrowed(&[]),
             lines,
             inlinees: Vec::new(),
             inline: false,
+            artificial: false,
+            noreturn: proc.flags.never,
+            calling_convention: None,
+            call_sites: Vec::new(),
+            entity: Some(self.entity(index)),
         }))
     }
 
     fn handle_inlinee(
         &self,
+        index: SymbolIndex,
         inline_site: InlineSiteSymbol<'s>,
         parent_offset: PdbInternalSectionOffset,
         inlinee: &pdb::Inlinee<'s>,
@@ -1035,19 +1347,18 @@ impl<'s> Unit<'s> {
         // If there are no line records, skip this inline function completely. Apparently, it was
         // eliminated by the compiler, and cannot be hit by the program anymore. For `symbolic`,
         // such functions do not have any use.
-        let start = match lines.iter().map(|line| line.address).min() {
-            Some(address) => address,
+        let mut ranges = inlinee_ranges(&lines);
+        let address = match ranges.first() {
+            Some(range) => range.start,
             None => return Ok(None),
         };
 
-        let end = match lines
-            .iter()
-            .map(|line| line.address + line.size.unwrap_or(1))
-            .max()
-        {
-            Some(address) => address,
-            None => return Ok(None),
-        };
+        // The size spans from the first to the last line of the inline site, including code of the
+        // caller interleaved with it. Individual ranges are reported in `Function::ranges`.
+        let size = ranges.last().map_or(0, |range| range.end) - address;
+        if ranges.len() == 1 {
+            ranges.clear();
+        }
 
         let mut formatter = TypeFormatter::new(self);
         let name = Name::new(
@@ -1057,17 +1368,37 @@ impl<'s> Unit<'s> {
         );
 
         Ok(Some(Function {
-            address: start,
-            size: end - start,
+            address,
+            size,
+            ranges,
             name,
-            compilation_dir: &[],
+            compilation_dir: Cow::Borrowed(&[]),
             lines,
             inlinees: Vec::new(),
             inline: true,
+            artificial: false,
+            noreturn: false,
+            calling_convention: None,
+            call_sites: Vec::new(),
+            entity: Some(self.entity(index)),
         }))
     }
 
+    /// Collects all functions within this module.
+    ///
+    /// Errors are located at the last symbol read, or at the module if no symbol has been read.
     fn functions(&self) -> Result<Vec<Function<'s>>, PdbError> {
+        let mut symbol_index = None;
+        self.collect_functions(&mut symbol_index).map_err(|e| {
+            self.debug_info
+                .locate_error(e, self.module_index, symbol_index)
+        })
+    }
+
+    fn collect_functions(
+        &self,
+        symbol_index: &mut Option<SymbolIndex>,
+    ) -> Result<Vec<Function<'s>>, PdbError> {
         let program = self.module.line_program()?;
         let mut symbols = self.module.symbols()?;
 
@@ -1089,6 +1420,7 @@ impl<'s> Unit<'s> {
         let mut proc_offsets = SmallVec::<[_; 3]>::new();
 
         while let Some(symbol) = symbols.next()? {
+            *symbol_index = Some(symbol.index());
             if inc_next {
                 depth += 1;
             }
@@ -1120,7 +1452,7 @@ impl<'s> Unit<'s> {
             let function = match symbol.parse() {
                 Ok(SymbolData::Procedure(proc)) => {
                     proc_offsets.push((depth, proc.offset));
-                    self.handle_procedure(proc, &program)?
+                    self.handle_procedure(symbol.index(), proc, &program)?
                 }
                 Ok(SymbolData::InlineSite(site)) => {
                     let parent_offset = proc_offsets
@@ -1140,9 +1472,26 @@ impl<'s> Unit<'s> {
                         // recover from these broken annotations.
                         // For that reason, we skip these inlinees completely so we do not fail
                         // processing the complete pdb file.
-                        self.handle_inlinee(site, parent_offset, inlinee, &program)
-                            .ok()
-                            .flatten()
+                        let result = self.handle_inlinee(
+                            symbol.index(),
+                            site,
+                            parent_offset,
+                            inlinee,
+                            &program,
+                        );
+
+                        match result {
+                            Ok(function) => function,
+                            Err(error) => {
+                                let error = self.debug_info.locate_error(
+                                    error,
+                                    self.module_index,
+                                    Some(symbol.index()),
+                                );
+                                self.diagnostics.push(error.to_diagnostic());
+                                None
+                            }
+                        }
                     } else {
                         None
                     }
@@ -1153,7 +1502,7 @@ impl<'s> Unit<'s> {
             };
 
             match function {
-                Some(function) => stack.push(depth, function),
+                Some(function) => stack.push(depth, function, Vec::new()),
                 None => skipped_depth = Some(depth),
             }
         }
@@ -1165,27 +1514,75 @@ impl<'s> Unit<'s> {
     }
 }
 
+/// Computes the address ranges covered by the line records of an inline site.
+///
+/// The code of an inlined function may be interleaved with the code of its caller, so the ranges
+/// are not necessarily contiguous. Adjacent and overlapping line records are merged.
+fn inlinee_ranges(lines: &LineRecords<'_>) -> Vec<Range<u64>> {
+    let mut line_ranges: Vec<_> = lines
+        .iter()
+        .map(|line| line.address..line.address + line.size.unwrap_or(1))
+        .collect();
+    line_ranges.sort_by_key(|range| range.start);
+
+    let mut ranges: Vec<Range<u64>> = Vec::new();
+    for range in line_ranges {
+        match ranges.last_mut() {
+            Some(last) if last.end >= range.start => last.end = last.end.max(range.end),
+            _ => ranges.push(range),
+        }
+    }
+
+    ranges
+}
+
 struct PdbUnitIterator<'s> {
     debug_info: &'s PdbDebugInfo<'s>,
+    diagnostics: &'s Diagnostics,
     index: usize,
 }
 
+impl<'s> PdbUnitIterator<'s> {
+    /// Records an error as [`Diagnostic`] if collection mode is enabled.
+    ///
+    /// Returns the error back if it should be propagated instead.
+    fn recover(&self, error: PdbError) -> Result<(), PdbError> {
+        if self.diagnostics.is_enabled() {
+            self.diagnostics.push(error.to_diagnostic());
+            Ok(())
+        } else {
+            Err(error)
+        }
+    }
+}
+
 impl<'s> Iterator for PdbUnitIterator<'s> {
     type Item = Result<Unit<'s>, PdbError>;
 
     fn next(&mut self) -> Option<Self::Item> {
         let debug_info = self.debug_info;
         while self.index < debug_info.modules.len() {
-            let result = debug_info.get_module(self.index);
+            let index = self.index;
             self.index += 1;
 
-            let module = match result {
-                Ok(Some(module)) => module,
-                Ok(None) => continue,
-                Err(error) => return Some(Err(error)),
-            };
+            let result = debug_info
+                .get_module(index)
+                .and_then(|module| match module {
+                    Some(module) => {
+                        Unit::load(debug_info, self.diagnostics, index, module).map(Some)
+                    }
+                    None => Ok(None),
+                })
+                .map_err(|error| debug_info.locate_error(error, index, None));
 
-            return Some(Unit::load(debug_info, module));
+            match result {
+                Ok(Some(unit)) => return Some(Ok(unit)),
+                Ok(None) => continue,
+                Err(error) => match self.recover(error) {
+                    Ok(()) => continue,
+                    Err(error) => return Some(Err(error)),
+                },
+            }
         }
 
         None
@@ -1197,6 +1594,7 @@ pub struct PdbFileIterator<'s> {
     debug_info: &'s PdbDebugInfo<'s>,
     units: PdbUnitIterator<'s>,
     files: pdb::FileIterator<'s>,
+    remapper: Option<&'s PathRemapper>,
     finished: bool,
 }
 
@@ -1214,8 +1612,8 @@ impl<'s> Iterator for PdbFileIterator<'s> {
                     .map_err(|err| err.into())
                     .and_then(|i| self.debug_info.file_info(i))
                     .map(|info| FileEntry {
-                        compilation_dir: &[],
-                        info,
+                        compilation_dir: Cow::Borrowed(&[]),
+                        info: info.remap(self.remapper),
                     });
 
                 return Some(result);
@@ -1229,7 +1627,15 @@ impl<'s> Iterator for PdbFileIterator<'s> {
 
             let line_program = match unit.module.line_program() {
                 Ok(line_program) => line_program,
-                Err(error) => return Some(Err(error.into())),
+                Err(error) => {
+                    let error = self
+                        .debug_info
+                        .locate_error(error.into(), unit.module_index, None);
+                    match self.units.recover(error) {
+                        Ok(()) => continue,
+                        Err(error) => return Some(Err(error)),
+                    }
+                }
             };
 
             self.files = line_program.files();
@@ -1244,6 +1650,10 @@ impl<'s> Iterator for PdbFileIterator<'s> {
 pub struct PdbFunctionIterator<'s> {
     units: PdbUnitIterator<'s>,
     functions: std::vec::IntoIter<Function<'s>>,
+    remapper: Option<&'s PathRemapper>,
+    interner: Option<&'s StringInterner>,
+    max_inline_depth: Option<usize>,
+    limits: LimitTracker,
     finished: bool,
 }
 
@@ -1257,6 +1667,14 @@ impl<'s> Iterator for PdbFunctionIterator<'s> {
 
         loop {
             if let Some(func) = self.functions.next() {
+                let func = func
+                    .with_inline_depth_limit(self.max_inline_depth)
+                    .remap(self.remapper)
+                    .intern(self.interner);
+                if let Err(error) = self.limits.track(&func) {
+                    self.finished = true;
+                    return Some(Err(error.into()));
+                }
                 return Some(Ok(func));
             }
 
@@ -1268,7 +1686,10 @@ impl<'s> Iterator for PdbFunctionIterator<'s> {
 
             self.functions = match unit.functions() {
                 Ok(functions) => functions.into_iter(),
-                Err(error) => return Some(Err(error)),
+                Err(error) => match self.units.recover(error) {
+                    Ok(()) => continue,
+                    Err(error) => return Some(Err(error)),
+                },
             };
         }
 
@@ -1,21 +1,70 @@
 //! Support for Portable Executables, an extension of COFF used on Windows.
 
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::error::Error;
 use std::fmt;
-use std::marker::PhantomData;
+use std::ops::Range;
 
 use goblin::pe;
+use scroll::{Pread, LE};
 use thiserror::Error;
 
-use symbolic_common::{Arch, AsSelf, CodeId, DebugId, Uuid};
+use symbolic_common::{
+    Arch, AsSelf, CodeId, DebugId, Language, Name, NameMangling, TypedCodeId, Uuid,
+};
 
 use crate::base::*;
+use crate::dwarf::{Dwarf, DwarfDebugSession, DwarfError, DwarfSection, Endian};
 use crate::shared::Parse;
 
 pub use goblin::pe::exception::*;
 pub use goblin::pe::section_table::SectionTable;
 
+/// Placeholder used for functions without a matching export.
+const UNKNOWN_NAME: &str = "<unknown>";
+
+/// The image can handle a high entropy 64-bit virtual address space.
+const IMAGE_DLLCHARACTERISTICS_HIGH_ENTROPY_VA: u16 = 0x0020;
+/// The image can be relocated at load time.
+const IMAGE_DLLCHARACTERISTICS_DYNAMIC_BASE: u16 = 0x0040;
+/// The image is compatible with data execution prevention.
+const IMAGE_DLLCHARACTERISTICS_NX_COMPAT: u16 = 0x0100;
+/// The image does not use structured exception handling.
+const IMAGE_DLLCHARACTERISTICS_NO_SEH: u16 = 0x0400;
+/// The image supports Control Flow Guard.
+const IMAGE_DLLCHARACTERISTICS_GUARD_CF: u16 = 0x4000;
+
+/// The image performs Control Flow Guard checks on indirect calls.
+pub const IMAGE_GUARD_CF_INSTRUMENTED: u32 = 0x0000_0100;
+/// The image performs Control Flow Guard checks on writes.
+pub const IMAGE_GUARD_CFW_INSTRUMENTED: u32 = 0x0000_0200;
+/// The image contains a table of valid indirect call targets.
+pub const IMAGE_GUARD_CF_FUNCTION_TABLE_PRESENT: u32 = 0x0000_0400;
+/// The image does not use the `/GS` security cookie.
+pub const IMAGE_GUARD_SECURITY_COOKIE_UNUSED: u32 = 0x0000_0800;
+/// The image supports read-only delay load import address tables.
+pub const IMAGE_GUARD_PROTECT_DELAYLOAD_IAT: u32 = 0x0000_1000;
+/// The image contains suppressed export information.
+pub const IMAGE_GUARD_CF_EXPORT_SUPPRESSION_INFO_PRESENT: u32 = 0x0000_4000;
+/// The image enables suppression of exports.
+pub const IMAGE_GUARD_CF_ENABLE_EXPORT_SUPPRESSION: u32 = 0x0000_8000;
+/// The image contains a table of valid `longjmp` targets.
+pub const IMAGE_GUARD_CF_LONGJUMP_TABLE_PRESENT: u32 = 0x0001_0000;
+/// The image contains a table of valid exception handler continuation targets.
+pub const IMAGE_GUARD_EH_CONTINUATION_TABLE_PRESENT: u32 = 0x0040_0000;
+/// The number of extra metadata bytes per entry in the Control Flow Guard function table.
+pub const IMAGE_GUARD_CF_FUNCTION_TABLE_SIZE_MASK: u32 = 0xF000_0000;
+/// Shift of [`IMAGE_GUARD_CF_FUNCTION_TABLE_SIZE_MASK`].
+pub const IMAGE_GUARD_CF_FUNCTION_TABLE_SIZE_SHIFT: u32 = 28;
+
+/// The resource type of version information.
+const RT_VERSION: u32 = 16;
+/// Set in resource directory entries that point to another directory.
+const RESOURCE_SUBDIRECTORY: u32 = 0x8000_0000;
+/// The signature of `VS_FIXEDFILEINFO`.
+const VS_FFI_SIGNATURE: u32 = 0xFEEF_04BD;
+
 /// An error when dealing with [`PEObject`](struct.PEObject.html).
 #[derive(Debug, Error)]
 #[error("invalid PE file")]
@@ -35,6 +84,12 @@ impl PeError {
     }
 }
 
+impl From<LimitExceeded> for PeError {
+    fn from(e: LimitExceeded) -> Self {
+        Self::new(e)
+    }
+}
+
 /// Detects if the PE is a packer stub.
 ///
 /// Such files usually only contain empty stubs in their `.pdata` and `.text` sections, and unwind
@@ -59,7 +114,9 @@ fn is_pe_stub(pe: &pe::PE<'_>) -> bool {
 /// container, [`PdbObject`]. The PE file contains a reference to the PDB and vice versa to verify
 /// that the files belong together.
 ///
-/// While in rare instances, PE files might contain debug information, this case is not supported.
+/// Binaries built with MinGW or other GCC-based toolchains carry DWARF debugging information in
+/// sections of the PE file instead. For these, [`dwarf_session`](Self::dwarf_session) reads
+/// functions and line information.
 ///
 /// [`PdbObject`]: ../pdb/struct.PdbObject.html
 pub struct PeObject<'data> {
@@ -71,7 +128,6 @@ pub struct PeObject<'data> {
 impl<'data> PeObject<'data> {
     /// Tests whether the buffer could contain an PE object.
     pub fn test(data: &[u8]) -> bool {
-        use scroll::{Pread, LE};
         matches!(
             (&data[0..2]).pread_with::<u16>(0, LE),
             Ok(pe::header::DOS_MAGIC)
@@ -99,10 +155,12 @@ impl<'data> PeObject<'data> {
         let header = &self.pe.header;
         let optional_header = header.optional_header.as_ref()?;
 
-        let timestamp = header.coff_header.time_date_stamp;
-        let size_of_image = optional_header.windows_fields.size_of_image;
-        let string = format!("{:08x}{:x}", timestamp, size_of_image);
-        Some(CodeId::new(string))
+        let code_id = TypedCodeId::Pe {
+            timestamp: header.coff_header.time_date_stamp,
+            size_of_image: optional_header.windows_fields.size_of_image,
+        };
+
+        Some(code_id.into())
     }
 
     /// The debug information identifier of this PE.
@@ -188,14 +246,135 @@ impl<'data> PeObject<'data> {
 
     /// Returns an ordered map of symbols in the symbol table.
     pub fn symbol_map(&self) -> SymbolMap<'data> {
-        self.symbols().collect()
+        let mut map: SymbolMap<'data> = self.symbols().collect();
+        map.set_sections(self.code_sections());
+        map
+    }
+
+    /// Returns the address ranges of executable sections relative to the image base.
+    fn code_sections(&self) -> Vec<Range<u64>> {
+        let code = pe::section_table::IMAGE_SCN_CNT_CODE | pe::section_table::IMAGE_SCN_MEM_EXECUTE;
+
+        self.pe
+            .sections
+            .iter()
+            .filter(|section| section.characteristics & code != 0)
+            .map(|section| {
+                let start = u64::from(section.virtual_address);
+                start..start + u64::from(section.virtual_size)
+            })
+            .collect()
+    }
+
+    /// Returns all named symbols in the export directory.
+    ///
+    /// Forwarded exports, which refer to a symbol in another library, are skipped.
+    pub fn exports(&self) -> Vec<Export<'data>> {
+        self.pe
+            .exports
+            .iter()
+            .filter(|export| export.reexport.is_none())
+            .filter_map(|export| {
+                Some(Export {
+                    name: Cow::Borrowed(export.name?),
+                    address: export.rva as u64,
+                })
+            })
+            .collect()
+    }
+
+    /// Returns all symbols in the import directory.
+    ///
+    /// Symbols imported by ordinal are named `ORDINAL <n>`.
+    pub fn imports(&self) -> Vec<Import<'data>> {
+        self.pe
+            .imports
+            .iter()
+            .map(|import| Import {
+                name: import.name.clone(),
+                library: Some(Cow::Borrowed(import.dll)),
+                address: Some(import.offset as u64),
+            })
+            .collect()
+    }
+
+    /// Returns the address of the entry point relative to the load address.
+    ///
+    /// For executables, this is the startup routine of the C runtime. For DLLs, it is the
+    /// `DllMain` routine. Returns `None` if the optional header does not declare an entry point.
+    pub fn entry_point(&self) -> Option<u64> {
+        match self.pe.entry {
+            0 => None,
+            entry => Some(entry as u64),
+        }
+    }
+
+    /// Returns the addresses of all TLS callbacks, relative to the load address.
+    ///
+    /// The PE format has no generic initializer arrays, since the C runtime calls static
+    /// constructors from its entry point. The loader calls TLS callbacks before the entry point,
+    /// however, which makes them the equivalent of initializers in other formats. Returns an empty
+    /// list if the TLS directory is missing or cannot be read.
+    pub fn initializers(&self) -> Vec<u64> {
+        self.tls_callbacks().unwrap_or_default()
     }
 
-    /// Determines whether this object contains debug information.
+    /// Returns the addresses of all terminators, relative to the load address.
     ///
-    /// This is always `false`, as debug information is not supported for PE files.
+    /// This is always empty, as the PE format does not declare terminators. Functions registered
+    /// with `atexit` are maintained by the C runtime.
+    pub fn terminators(&self) -> Vec<u64> {
+        Vec::new()
+    }
+
+    /// Reads the null-terminated list of callbacks from the TLS directory.
+    fn tls_callbacks(&self) -> Option<Vec<u64>> {
+        let optional_header = self.pe.header.optional_header.as_ref()?;
+        let directory = (*optional_header.data_directories.get_tls_table())?;
+        let file_alignment = optional_header.windows_fields.file_alignment;
+
+        let offset = self.rva_to_offset(directory.virtual_address as u64, file_alignment)?;
+        let data = self.data.get(offset..)?;
+
+        let is_64 = self.pe.is_64;
+        let read_ptr = |data: &[u8], offset: usize| -> Option<u64> {
+            if is_64 {
+                data.pread_with::<u64>(offset, LE).ok()
+            } else {
+                data.pread_with::<u32>(offset, LE).ok().map(u64::from)
+            }
+        };
+
+        // `AddressOfCallBacks` follows the start and end of the raw data and the address of the
+        // TLS index, all of which are pointer-sized.
+        let pointer_size = if is_64 { 8 } else { 4 };
+        let callbacks = read_ptr(data, 3 * pointer_size)?;
+
+        let image_base = self.pe.image_base as u64;
+        let rva = callbacks.checked_sub(image_base)?;
+        let offset = self.rva_to_offset(rva, file_alignment)?;
+        let data = self.data.get(offset..)?;
+
+        let mut functions = Vec::new();
+        let mut offset = 0;
+        while let Some(callback) = read_ptr(data, offset) {
+            if callback == 0 {
+                break;
+            }
+
+            functions.extend(callback.checked_sub(image_base));
+            offset += pointer_size;
+        }
+
+        Some(functions)
+    }
+
+    /// Determines whether this object contains DWARF debug information.
+    ///
+    /// This is only the case for binaries built by GCC-based toolchains like MinGW. Other PE files
+    /// refer to a separate [`PdbObject`](crate::pdb::PdbObject) instead.
     pub fn has_debug_info(&self) -> bool {
-        false
+        self.has_section("debug_info")
     }
 
     /// Determines whether this object contains embedded source.
@@ -208,9 +387,84 @@ impl<'data> PeObject<'data> {
         false
     }
 
-    /// Constructs a no-op debugging session.
+    /// Constructs a debugging session for the DWARF debug information in this PE file.
+    ///
+    /// Addresses in the session are relative to the [`load_address`](Self::load_address). If the
+    /// file does not contain DWARF, the session is empty, which can be checked with
+    /// [`has_debug_info`](Self::has_debug_info).
+    pub fn dwarf_session(&self) -> Result<DwarfDebugSession<'data>, DwarfError> {
+        let symbols = self.symbol_map();
+        DwarfDebugSession::parse(self, symbols, self.load_address() as i64, self.kind())
+    }
+
+    /// Constructs a debugging session.
+    ///
+    /// Since most PE files do not carry debug information, the session synthesizes functions from
+    /// the exception directory. See [`PeDebugSession`] for more information. To read DWARF debug
+    /// information of MinGW binaries, use [`dwarf_session`](Self::dwarf_session) instead.
     pub fn debug_session(&self) -> Result<PeDebugSession<'data>, PeError> {
-        Ok(PeDebugSession { _ph: PhantomData })
+        Ok(PeDebugSession {
+            functions: self.runtime_functions()?,
+            limits: SessionLimits::default(),
+        })
+    }
+
+    /// Synthesizes functions from the `RUNTIME_FUNCTION` entries of the exception directory.
+    ///
+    /// Entries with chained unwind information describe fragments of another function, such as
+    /// separated cold code, and are skipped. Functions are named after an export at their start
+    /// address, if there is one.
+    fn runtime_functions(&self) -> Result<Vec<Function<'data>>, PeError> {
+        let exception_data = match self.exception_data() {
+            Some(exception_data) => exception_data,
+            None => return Ok(Vec::new()),
+        };
+
+        let exports: HashMap<_, _> = self
+            .pe
+            .exports
+            .iter()
+            .filter_map(|export| Some((export.rva as u64, export.name?)))
+            .collect();
+
+        let mut functions = Vec::new();
+        for runtime_function in exception_data.functions() {
+            let runtime_function = runtime_function.map_err(PeError::new)?;
+            if runtime_function.end_address <= runtime_function.begin_address {
+                continue;
+            }
+
+            // An odd unwind info address refers to the entry of the primary function.
+            if runtime_function.unwind_info_address % 2 != 0 {
+                continue;
+            }
+
+            let unwind_info = exception_data.get_unwind_info(runtime_function, self.sections());
+            if matches!(unwind_info, Ok(ref info) if info.chained_info.is_some()) {
+                continue;
+            }
+
+            let address = runtime_function.begin_address as u64;
+            let name = exports.get(&address).copied().unwrap_or(UNKNOWN_NAME);
+
+            functions.push(Function {
+                address,
+                size: (runtime_function.end_address - runtime_function.begin_address) as u64,
+                ranges: Vec::new(),
+                name: Name::new(name, NameMangling::Unknown, Language::Unknown),
+                compilation_dir: Cow::Borrowed(&[]),
+                lines: LineRecords::new(),
+                inlinees: Vec::new(),
+                inline: false,
+                artificial: false,
+                noreturn: false,
+                calling_convention: None,
+                call_sites: Vec::new(),
+                entity: None,
+            });
+        }
+
+        Ok(functions)
     }
 
     /// Determines whether this object contains stack unwinding information.
@@ -229,13 +483,183 @@ impl<'data> PeObject<'data> {
     }
 
     /// Returns exception data containing unwind information.
+    ///
+    /// Returns `None` if the exception directory does not fit into the file.
     pub fn exception_data(&self) -> Option<&ExceptionData<'_>> {
         if self.is_stub {
-            None
+            return None;
+        }
+
+        let exception_data = self.pe.exception_data.as_ref()?;
+
+        // goblin slices the runtime functions without checking the directory against the file
+        // and panics if it extends past the end. Treat such a directory as missing.
+        let optional_header = self.pe.header.optional_header.as_ref()?;
+        let directory = (*optional_header.data_directories.get_exception_table())?;
+        let file_alignment = optional_header.windows_fields.file_alignment;
+
+        let offset = self.rva_to_offset(directory.virtual_address as u64, file_alignment)?;
+        let end = offset.checked_add(directory.size as usize)?;
+        if end > self.data.len() {
+            return None;
+        }
+
+        Some(exception_data)
+    }
+
+    /// Returns the load configuration directory of this PE file.
+    ///
+    /// The load configuration declares runtime mitigations applied by the loader, such as the
+    /// `/GS` security cookie, safe exception handlers and the Control Flow Guard function table.
+    /// Returns `None` if the directory is missing or cannot be read.
+    pub fn load_config(&self) -> Option<PeLoadConfig<'data>> {
+        let optional_header = self.pe.header.optional_header.as_ref()?;
+        let directory = (*optional_header.data_directories.get_load_config_table())?;
+        let file_alignment = optional_header.windows_fields.file_alignment;
+
+        let offset = self.rva_to_offset(directory.virtual_address as u64, file_alignment)?;
+        let data = self.data.get(offset..)?;
+
+        // The size declared in the directory itself determines which fields are present. Fields
+        // beyond this size were added in later versions of the structure and are read as zero.
+        let size: u32 = data.pread_with(0, LE).ok()?;
+        let data = data.get(..size as usize).unwrap_or(data);
+
+        let is_64 = self.pe.is_64;
+        let read_u32 = |offset: usize| data.pread_with::<u32>(offset, LE).unwrap_or(0);
+        let read_ptr = |offset32: usize, offset64: usize| {
+            if is_64 {
+                data.pread_with::<u64>(offset64, LE).unwrap_or(0)
+            } else {
+                u64::from(read_u32(offset32))
+            }
+        };
+
+        let guard_flags = if is_64 { read_u32(144) } else { read_u32(88) };
+        let guard_cf_function_table = read_ptr(80, 128);
+        let guard_cf_function_count = read_ptr(84, 136);
+
+        let stride = 4
+            + ((guard_flags & IMAGE_GUARD_CF_FUNCTION_TABLE_SIZE_MASK)
+                >> IMAGE_GUARD_CF_FUNCTION_TABLE_SIZE_SHIFT) as usize;
+
+        let guard_cf_functions = (guard_cf_function_table as usize)
+            .checked_sub(self.pe.image_base)
+            .and_then(|rva| self.rva_to_offset(rva as u64, file_alignment))
+            .and_then(|offset| {
+                let len = (guard_cf_function_count as usize).checked_mul(stride)?;
+                self.data.get(offset..offset.checked_add(len)?)
+            })
+            .unwrap_or_default();
+
+        Some(PeLoadConfig {
+            time_date_stamp: read_u32(4),
+            security_cookie: read_ptr(60, 88),
+            se_handler_table: read_ptr(64, 96),
+            se_handler_count: read_ptr(68, 104),
+            guard_cf_check_function_pointer: read_ptr(72, 112),
+            guard_cf_dispatch_function_pointer: read_ptr(76, 120),
+            guard_flags,
+            guard_cf_functions,
+            guard_cf_function_stride: stride,
+        })
+    }
+
+    /// Returns the security mitigations declared by this PE file.
+    ///
+    /// The flags are derived from the `DllCharacteristics` of the optional header and the load
+    /// configuration. See [`PeSecurityFlags`] for more information.
+    pub fn security_flags(&self) -> PeSecurityFlags {
+        let characteristics = match self.pe.header.optional_header {
+            Some(ref optional_header) => optional_header.windows_fields.dll_characteristics,
+            None => 0,
+        };
+
+        let load_config = self.load_config();
+        let has = |flag: u16| characteristics & flag != 0;
+
+        PeSecurityFlags {
+            aslr: has(IMAGE_DLLCHARACTERISTICS_DYNAMIC_BASE),
+            high_entropy_aslr: has(IMAGE_DLLCHARACTERISTICS_DYNAMIC_BASE)
+                && has(IMAGE_DLLCHARACTERISTICS_HIGH_ENTROPY_VA),
+            dep: has(IMAGE_DLLCHARACTERISTICS_NX_COMPAT),
+            control_flow_guard: has(IMAGE_DLLCHARACTERISTICS_GUARD_CF)
+                && matches!(load_config, Some(ref c) if c.has_control_flow_guard()),
+            no_seh: has(IMAGE_DLLCHARACTERISTICS_NO_SEH),
+            safe_seh: matches!(load_config, Some(ref c) if c.se_handler_table != 0),
+            security_cookie: matches!(load_config, Some(ref c) if c.has_security_cookie()),
+        }
+    }
+
+    /// Returns the version information resource of this PE file.
+    ///
+    /// This is the `VERSIONINFO` resource shown in the file properties on Windows. If the resource
+    /// is declared for multiple languages, the first one is returned. Returns `None` if the file
+    /// does not contain version information or the resource cannot be read.
+    pub fn version_info(&self) -> Option<PeVersionInfo> {
+        let optional_header = self.pe.header.optional_header.as_ref()?;
+        let directory = (*optional_header.data_directories.get_resource_table())?;
+        let file_alignment = optional_header.windows_fields.file_alignment;
+
+        let offset = self.rva_to_offset(directory.virtual_address as u64, file_alignment)?;
+        let resources = self.data.get(offset..)?;
+        let resources = resources
+            .get(..directory.size as usize)
+            .unwrap_or(resources);
+
+        // Resources are nested by type, name and language. Pick the first name and language.
+        let names = resource_entry(resources, 0, Some(RT_VERSION))?;
+        let languages = resource_entry(resources, subdirectory(names)?, None)?;
+        let entry = resource_entry(resources, subdirectory(languages)?, None)?;
+        if entry & RESOURCE_SUBDIRECTORY != 0 {
+            return None;
+        }
+
+        let rva: u32 = resources.pread_with(entry as usize, LE).ok()?;
+        let size: u32 = resources.pread_with(entry as usize + 4, LE).ok()?;
+
+        let offset = self.rva_to_offset(rva as u64, file_alignment)?;
+        let data = self.data.get(offset..offset.checked_add(size as usize)?)?;
+        PeVersionInfo::parse(data)
+    }
+
+    /// Returns the range of data appended after the last section of this PE file.
+    ///
+    /// This overlay is not mapped into memory by the loader. It commonly contains the Authenticode
+    /// signature, the COFF symbol table of MinGW builds or payloads of installers and
+    /// self-extracting archives. Returns `None` if there is no data after the last section.
+    pub fn overlay_range(&self) -> Option<std::ops::Range<usize>> {
+        let headers_end = match self.pe.header.optional_header {
+            Some(ref optional_header) => optional_header.windows_fields.size_of_headers as usize,
+            None => 0,
+        };
+
+        let end = self
+            .pe
+            .sections
+            .iter()
+            .map(|section| section.pointer_to_raw_data as usize + section.size_of_raw_data as usize)
+            .fold(headers_end, usize::max);
+
+        if end < self.data.len() {
+            Some(end..self.data.len())
         } else {
-            self.pe.exception_data.as_ref()
+            None
         }
     }
+
+    /// Returns the data appended after the last section of this PE file.
+    ///
+    /// See [`overlay_range`](Self::overlay_range) for more information.
+    pub fn overlay(&self) -> Option<&'data [u8]> {
+        self.overlay_range().map(|range| &self.data[range])
+    }
+
+    /// Converts a relative virtual address into an offset in the file data.
+    fn rva_to_offset(&self, rva: u64, file_alignment: u32) -> Option<usize> {
+        let options = pe::options::ParseOptions::default();
+        pe::utils::find_offset(rva as usize, &self.pe.sections, file_alignment, &options)
+    }
 }
 
 impl fmt::Debug for PeObject<'_> {
@@ -337,6 +761,380 @@ impl<'data: 'object, 'object> ObjectLike<'data, 'object> for PeObject<'data> {
     }
 }
 
+impl<'data> Dwarf<'data> for PeObject<'data> {
+    fn endianity(&self) -> Endian {
+        // PE files are always little-endian, including on big-endian architectures.
+        Endian::Little
+    }
+
+    fn raw_section(&self, name: &str) -> Option<DwarfSection<'data>> {
+        for section in &self.pe.sections {
+            // Names longer than eight characters, such as `.debug_info`, are stored in the COFF
+            // string table and have been resolved by the parser.
+            match section.name() {
+                Ok(section_name) if section_name.strip_prefix('.') == Some(name) => (),
+                _ => continue,
+            }
+
+            // The raw size is padded to the file alignment, which would leave garbage after the
+            // actual section contents. The virtual size is the exact size, unless it is zero.
+            let size = match section.virtual_size {
+                0 => section.size_of_raw_data,
+                size => size.min(section.size_of_raw_data),
+            };
+
+            let offset = section.pointer_to_raw_data as usize;
+            let data = self.data.get(offset..offset.checked_add(size as usize)?)?;
+
+            // A corrupt image base can place the section outside of the address space.
+            let address =
+                match (self.pe.image_base as u64).checked_add(section.virtual_address.into()) {
+                    Some(address) => address,
+                    None => continue,
+                };
+
+            return Some(DwarfSection {
+                data: Cow::Borrowed(data),
+                address,
+                offset: offset as u64,
+                align: 4096,
+            });
+        }
+
+        None
+    }
+}
+
+/// Security mitigations declared by a PE file.
+///
+/// These flags only state that the image opts into a mitigation. Whether the mitigation is
+/// actually enforced depends on the operating system and process configuration.
+///
+/// Returned by [`PeObject::security_flags`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct PeSecurityFlags {
+    /// The image can be relocated to a random address (`/DYNAMICBASE`).
+    pub aslr: bool,
+    /// The image supports 64-bit address space layout randomization (`/HIGHENTROPYVA`).
+    pub high_entropy_aslr: bool,
+    /// The image is compatible with data execution prevention (`/NXCOMPAT`).
+    pub dep: bool,
+    /// The image is instrumented with Control Flow Guard (`/guard:cf`).
+    pub control_flow_guard: bool,
+    /// The image declares a table of safe exception handlers (`/SAFESEH`, x86 only).
+    pub safe_seh: bool,
+    /// The image does not use structured exception handling.
+    pub no_seh: bool,
+    /// The image uses a `/GS` security cookie to detect stack buffer overruns.
+    pub security_cookie: bool,
+}
+
+/// The load configuration directory of a PE file.
+///
+/// Addresses in this structure are virtual addresses, which include the image base. Fields that
+/// are not present in the load configuration of older images are zero.
+///
+/// Returned by [`PeObject::load_config`].
+#[derive(Clone, Debug)]
+pub struct PeLoadConfig<'data> {
+    /// The timestamp of the load configuration.
+    pub time_date_stamp: u32,
+    /// Virtual address of the `/GS` security cookie.
+    pub security_cookie: u64,
+    /// Virtual address of the table of safe exception handlers.
+    pub se_handler_table: u64,
+    /// Number of entries in the table of safe exception handlers.
+    pub se_handler_count: u64,
+    /// Virtual address of the pointer to the Control Flow Guard check function.
+    pub guard_cf_check_function_pointer: u64,
+    /// Virtual address of the pointer to the Control Flow Guard dispatch function.
+    pub guard_cf_dispatch_function_pointer: u64,
+    /// Control Flow Guard flags, a combination of the `IMAGE_GUARD_*` constants.
+    pub guard_flags: u32,
+    guard_cf_functions: &'data [u8],
+    guard_cf_function_stride: usize,
+}
+
+impl<'data> PeLoadConfig<'data> {
+    /// Determines whether the image is instrumented with Control Flow Guard.
+    pub fn has_control_flow_guard(&self) -> bool {
+        self.guard_flags & IMAGE_GUARD_CF_INSTRUMENTED != 0
+    }
+
+    /// Determines whether the image uses a `/GS` security cookie.
+    pub fn has_security_cookie(&self) -> bool {
+        self.security_cookie != 0 && self.guard_flags & IMAGE_GUARD_SECURITY_COOKIE_UNUSED == 0
+    }
+
+    /// Returns an iterator over the valid indirect call targets of the Control Flow Guard function
+    /// table.
+    ///
+    /// The table is empty if the image is not instrumented with Control Flow Guard.
+    pub fn guard_cf_functions(&self) -> PeGuardFunctionIterator<'data> {
+        PeGuardFunctionIterator {
+            chunks: self
+                .guard_cf_functions
+                .chunks_exact(self.guard_cf_function_stride),
+        }
+    }
+}
+
+/// A valid indirect call target in the Control Flow Guard function table.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PeGuardFunction {
+    /// Relative address of the function.
+    pub rva: u32,
+    /// Metadata flags of this entry, if the table declares any.
+    pub flags: u8,
+}
+
+/// An iterator over the Control Flow Guard function table.
+///
+/// Returned by [`PeLoadConfig::guard_cf_functions`].
+#[derive(Clone, Debug)]
+pub struct PeGuardFunctionIterator<'data> {
+    chunks: std::slice::ChunksExact<'data, u8>,
+}
+
+impl Iterator for PeGuardFunctionIterator<'_> {
+    type Item = PeGuardFunction;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let chunk = self.chunks.next()?;
+        Some(PeGuardFunction {
+            rva: chunk.pread_with(0, LE).ok()?,
+            flags: chunk.get(4).copied().unwrap_or(0),
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+}
+
+impl std::iter::FusedIterator for PeGuardFunctionIterator<'_> {}
+
+/// Returns the `OffsetToData` of the first entry in a resource directory with the given ID.
+///
+/// If no ID is given, the first entry is returned regardless of its name.
+fn resource_entry(resources: &[u8], offset: usize, id: Option<u32>) -> Option<u32> {
+    let named: u16 = resources.pread_with(offset + 12, LE).ok()?;
+    let ids: u16 = resources.pread_with(offset + 14, LE).ok()?;
+
+    (0..named as usize + ids as usize).find_map(|index| {
+        let entry = offset + 16 + index * 8;
+        let name: u32 = resources.pread_with(entry, LE).ok()?;
+        match id {
+            Some(id) if name != id => None,
+            _ => resources.pread_with(entry + 4, LE).ok(),
+        }
+    })
+}
+
+/// Returns the offset of the resource directory referenced by an entry.
+fn subdirectory(offset_to_data: u32) -> Option<usize> {
+    if offset_to_data & RESOURCE_SUBDIRECTORY != 0 {
+        Some((offset_to_data & !RESOURCE_SUBDIRECTORY) as usize)
+    } else {
+        None
+    }
+}
+
+/// A four-part version number, such as `10.0.19041.1`.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct PeVersion {
+    /// The major version.
+    pub major: u16,
+    /// The minor version.
+    pub minor: u16,
+    /// The build number.
+    pub build: u16,
+    /// The revision number.
+    pub revision: u16,
+}
+
+impl PeVersion {
+    /// Creates a version from the most and least significant halves stored in version resources.
+    fn from_parts(ms: u32, ls: u32) -> Self {
+        PeVersion {
+            major: (ms >> 16) as u16,
+            minor: ms as u16,
+            build: (ls >> 16) as u16,
+            revision: ls as u16,
+        }
+    }
+}
+
+impl fmt::Display for PeVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}.{}.{}.{}",
+            self.major, self.minor, self.build, self.revision
+        )
+    }
+}
+
+/// The version information resource of a PE file.
+///
+/// Numeric versions are read from the fixed file information, which is present in all version
+/// resources. Strings, such as the company and product name, are optional and not validated.
+///
+/// Returned by [`PeObject::version_info`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PeVersionInfo {
+    /// The binary version of the file.
+    pub file_version: Option<PeVersion>,
+    /// The binary version of the product this file is distributed with.
+    pub product_version: Option<PeVersion>,
+    strings: Vec<(String, String)>,
+}
+
+impl PeVersionInfo {
+    /// Parses a `VS_VERSIONINFO` structure.
+    fn parse(data: &[u8]) -> Option<Self> {
+        let root = VersionBlocks(data).next()?;
+        if root.key != "VS_VERSION_INFO" {
+            return None;
+        }
+
+        let mut info = PeVersionInfo::default();
+
+        let read = |offset| root.value.pread_with::<u32>(offset, LE).ok();
+        if read(0) == Some(VS_FFI_SIGNATURE) {
+            let version = |ms, ls| Some(PeVersion::from_parts(read(ms)?, read(ls)?));
+            info.file_version = version(8, 12);
+            info.product_version = version(16, 20);
+        }
+
+        let string_tables = VersionBlocks(root.children)
+            .filter(|block| block.key == "StringFileInfo")
+            .flat_map(|block| VersionBlocks(block.children));
+
+        for table in string_tables {
+            for string in VersionBlocks(table.children) {
+                if info.get(&string.key).is_none() {
+                    let value = decode_utf16(string.value);
+                    info.strings.push((string.key, value));
+                }
+            }
+        }
+
+        Some(info)
+    }
+
+    /// Returns the string with the given key, such as `"CompanyName"`.
+    ///
+    /// If strings are declared for multiple languages, the value of the first language is
+    /// returned.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.strings
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns an iterator over all keys and values of version strings.
+    pub fn strings(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.strings.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// The name of the company that produced the file.
+    pub fn company_name(&self) -> Option<&str> {
+        self.get("CompanyName")
+    }
+
+    /// The name of the product this file is distributed with.
+    pub fn product_name(&self) -> Option<&str> {
+        self.get("ProductName")
+    }
+
+    /// The description of the file shown to users.
+    pub fn file_description(&self) -> Option<&str> {
+        self.get("FileDescription")
+    }
+
+    /// The version of the file as a string, which may contain additional text.
+    pub fn file_version_string(&self) -> Option<&str> {
+        self.get("FileVersion")
+    }
+
+    /// The version of the product as a string, which may contain additional text.
+    pub fn product_version_string(&self) -> Option<&str> {
+        self.get("ProductVersion")
+    }
+}
+
+/// A block in a `VS_VERSIONINFO` structure.
+struct VersionBlock<'a> {
+    key: String,
+    value: &'a [u8],
+    children: &'a [u8],
+}
+
+/// An iterator over consecutive blocks in a `VS_VERSIONINFO` structure.
+///
+/// Blocks are aligned to four bytes, so the data must start at an aligned offset.
+struct VersionBlocks<'a>(&'a [u8]);
+
+impl<'a> Iterator for VersionBlocks<'a> {
+    type Item = VersionBlock<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let length: u16 = self.0.pread_with(0, LE).ok()?;
+        let value_length: u16 = self.0.pread_with(2, LE).ok()?;
+        let is_text: u16 = self.0.pread_with(4, LE).ok()?;
+
+        let block = match self.0.get(..length as usize) {
+            Some(block) if length >= 6 => block,
+            _ => {
+                self.0 = &[];
+                return None;
+            }
+        };
+        self.0 = self.0.get(align4(length as usize)..).unwrap_or_default();
+
+        let key_length = block[6..]
+            .chunks_exact(2)
+            .position(|c| c == [0, 0])
+            .unwrap_or((block.len() - 6) / 2);
+        let key = decode_utf16(&block[6..6 + key_length * 2]);
+
+        // The length of text values is given in characters. Some compilers write the length in
+        // bytes instead, so clamp the value to the block.
+        let value_start = align4(6 + key_length * 2 + 2).min(block.len());
+        let value_length = match is_text {
+            1 => value_length as usize * 2,
+            _ => value_length as usize,
+        };
+        let value_end = (value_start + value_length).min(block.len());
+        let children_start = align4(value_end).min(block.len());
+
+        Some(VersionBlock {
+            key,
+            value: &block[value_start..value_end],
+            children: &block[children_start..],
+        })
+    }
+}
+
+/// Rounds an offset up to the next multiple of four.
+fn align4(offset: usize) -> usize {
+    (offset + 3) & !3
+}
+
+/// Decodes a little-endian UTF-16 string, stripping trailing NUL characters.
+fn decode_utf16(bytes: &[u8]) -> String {
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .collect();
+
+    String::from_utf16_lossy(&units)
+        .trim_end_matches('\0')
+        .to_owned()
+}
+
 /// An iterator over symbols in the PE file.
 ///
 /// Returned by [`PeObject::symbols`](struct.PeObject.html#method.symbols).
@@ -358,17 +1156,30 @@ impl<'data, 'object> Iterator for PeSymbolIterator<'data, 'object> {
 
 /// Debug session for PE objects.
 ///
-/// Since debug information in PE containers is not supported, this session consists of NoOps and
-/// always returns empty results.
+/// Since debug information in PE containers is not supported, this session synthesizes functions
+/// from the `RUNTIME_FUNCTION` entries of the exception directory (`.pdata`). These functions only
+/// carry their address range and, if available, the name of a matching export. They do not contain
+/// line information or inlinees. Files and sources are always empty.
 #[derive(Debug)]
 pub struct PeDebugSession<'data> {
-    _ph: PhantomData<&'data ()>,
+    functions: Vec<Function<'data>>,
+    limits: SessionLimits,
 }
 
 impl<'data> PeDebugSession<'data> {
+    /// Sets the [`SessionLimits`] enforced by the function iterators of this session.
+    ///
+    /// There are no limits by default.
+    pub fn set_limits(&mut self, limits: SessionLimits) {
+        self.limits = limits;
+    }
+
     /// Returns an iterator over all functions in this debug file.
     pub fn functions(&self) -> PeFunctionIterator<'_> {
-        std::iter::empty()
+        PeFunctionIterator {
+            functions: self.functions.iter(),
+            limits: LimitTracker::new(self.limits),
+        }
     }
 
     /// Returns an iterator over all source files referenced by this debug file.
@@ -403,7 +1214,164 @@ impl<'session> DebugSession<'session> for PeDebugSession<'_> {
 }
 
 /// An iterator over functions in a PE file.
-pub type PeFunctionIterator<'s> = std::iter::Empty<Result<Function<'s>, PeError>>;
+pub struct PeFunctionIterator<'s> {
+    functions: std::slice::Iter<'s, Function<'s>>,
+    limits: LimitTracker,
+}
+
+impl<'s> Iterator for PeFunctionIterator<'s> {
+    type Item = Result<Function<'s>, PeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.limits.is_exceeded() {
+            return None;
+        }
+
+        let function = self.functions.next()?.clone();
+        Some(match self.limits.track(&function) {
+            Ok(()) => Ok(function),
+            Err(error) => Err(error.into()),
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.functions.size_hint();
+        if self.limits.has_limits() {
+            (0, upper)
+        } else {
+            (lower, upper)
+        }
+    }
+}
 
 /// An iterator over source files in a PE file.
 pub type PeFileIterator<'s> = std::iter::Empty<Result<FileEntry<'s>, PeError>>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_guard_cf_functions() {
+        // Entries with one byte of metadata, as declared by the size bits of the guard flags.
+        let table = [
+            0x00, 0x10, 0x00, 0x00, 0x01, 0x40, 0x20, 0x00, 0x00, 0x00, 0xff,
+        ];
+        let load_config = PeLoadConfig {
+            time_date_stamp: 0,
+            security_cookie: 0,
+            se_handler_table: 0,
+            se_handler_count: 0,
+            guard_cf_check_function_pointer: 0,
+            guard_cf_dispatch_function_pointer: 0,
+            guard_flags: IMAGE_GUARD_CF_INSTRUMENTED | IMAGE_GUARD_CF_FUNCTION_TABLE_PRESENT,
+            guard_cf_functions: &table,
+            guard_cf_function_stride: 5,
+        };
+
+        let functions: Vec<_> = load_config.guard_cf_functions().collect();
+        assert_eq!(
+            functions,
+            vec![
+                PeGuardFunction {
+                    rva: 0x1000,
+                    flags: 1
+                },
+                PeGuardFunction {
+                    rva: 0x2040,
+                    flags: 0
+                },
+            ]
+        );
+    }
+
+    /// Encodes a `VS_VERSIONINFO` block with the given value and children.
+    fn version_block(key: &str, value: &[u8], is_text: bool, children: &[Vec<u8>]) -> Vec<u8> {
+        let mut block = vec![0; 6];
+        for unit in key.encode_utf16().chain(Some(0)) {
+            block.extend_from_slice(&unit.to_le_bytes());
+        }
+        block.resize(align4(block.len()), 0);
+        block.extend_from_slice(value);
+        for child in children {
+            block.resize(align4(block.len()), 0);
+            block.extend_from_slice(child);
+        }
+
+        let value_length = if is_text {
+            value.len() / 2
+        } else {
+            value.len()
+        };
+        let length = block.len() as u16;
+        block[0..2].copy_from_slice(&length.to_le_bytes());
+        block[2..4].copy_from_slice(&(value_length as u16).to_le_bytes());
+        block[4..6].copy_from_slice(&(is_text as u16).to_le_bytes());
+        block
+    }
+
+    fn version_string(key: &str, value: &str) -> Vec<u8> {
+        let value: Vec<u8> = value
+            .encode_utf16()
+            .chain(Some(0))
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        version_block(key, &value, true, &[])
+    }
+
+    #[test]
+    fn test_version_info() {
+        let mut fixed = vec![0; 52];
+        fixed[0..4].copy_from_slice(&VS_FFI_SIGNATURE.to_le_bytes());
+        fixed[8..12].copy_from_slice(&0x0001_0002u32.to_le_bytes());
+        fixed[12..16].copy_from_slice(&0x0003_0004u32.to_le_bytes());
+        fixed[16..20].copy_from_slice(&0x0005_0000u32.to_le_bytes());
+
+        let table = version_block(
+            "040904b0",
+            &[],
+            true,
+            &[
+                version_string("CompanyName", "Example Inc."),
+                version_string("ProductName", "Example"),
+                version_string("FileVersion", "1.2.3.4 (release)"),
+            ],
+        );
+        let other = version_block(
+            "040704b0",
+            &[],
+            true,
+            &[version_string("CompanyName", "Beispiel")],
+        );
+        let strings = version_block("StringFileInfo", &[], true, &[table, other]);
+        let vars = version_block("VarFileInfo", &[], true, &[]);
+        let root = version_block("VS_VERSION_INFO", &fixed, false, &[vars, strings]);
+
+        let info = PeVersionInfo::parse(&root).unwrap();
+        assert_eq!(info.file_version.unwrap().to_string(), "1.2.3.4");
+        assert_eq!(
+            info.product_version,
+            Some(PeVersion {
+                major: 5,
+                minor: 0,
+                build: 0,
+                revision: 0
+            })
+        );
+        assert_eq!(info.company_name(), Some("Example Inc."));
+        assert_eq!(info.product_name(), Some("Example"));
+        assert_eq!(info.file_version_string(), Some("1.2.3.4 (release)"));
+        assert_eq!(info.file_description(), None);
+        assert_eq!(info.strings().count(), 3);
+    }
+
+    #[test]
+    fn test_version_info_invalid() {
+        assert_eq!(PeVersionInfo::parse(&[]), None);
+        assert_eq!(PeVersionInfo::parse(&[0xff; 8]), None);
+
+        let root = version_block("VS_VERSION_INFO", &[0; 8], false, &[]);
+        assert_eq!(PeVersionInfo::parse(&root), Some(PeVersionInfo::default()));
+        assert_eq!(PeVersionInfo::parse(&root[..root.len() - 4]), None);
+    }
+}
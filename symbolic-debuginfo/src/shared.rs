@@ -15,11 +15,15 @@ pub trait Parse<'data>: Sized {
 }
 
 #[cfg(any(feature = "dwarf", feature = "ms"))]
-use crate::base::Function;
+use crate::base::{Function, LineInfo};
 
 /// A stack for assembling function trees from lists of nested functions.
+///
+/// Line records of functions on the stack are kept in a `Vec`, so that they can still be modified
+/// while processing inlinees. They are appended to the compact [`Function::lines`] when the
+/// function is flushed.
 #[cfg(any(feature = "dwarf", feature = "ms"))]
-pub struct FunctionStack<'a>(Vec<(isize, Function<'a>)>);
+pub struct FunctionStack<'a>(Vec<(isize, Function<'a>, Vec<LineInfo<'a>>)>);
 
 #[cfg(any(feature = "dwarf", feature = "ms"))]
 impl<'a> FunctionStack<'a> {
@@ -31,13 +35,21 @@ impl<'a> FunctionStack<'a> {
     /// Pushes a new function onto the stack at the given depth.
     ///
     /// This assumes that `flush` has been called previously.
-    pub fn push(&mut self, depth: isize, function: Function<'a>) {
-        self.0.push((depth, function));
+    pub fn push(&mut self, depth: isize, function: Function<'a>, lines: Vec<LineInfo<'a>>) {
+        self.0.push((depth, function, lines));
     }
 
     /// Peeks at the current top function (deepest inlining level).
     pub fn peek_mut(&mut self) -> Option<&mut Function<'a>> {
-        self.0.last_mut().map(|&mut (_, ref mut function)| function)
+        self.0
+            .last_mut()
+            .map(|&mut (_, ref mut function, _)| function)
+    }
+
+    /// Peeks at the pending line records of the current top function.
+    #[cfg(feature = "dwarf")]
+    pub fn peek_lines_mut(&mut self) -> Option<&mut Vec<LineInfo<'a>>> {
+        self.0.last_mut().map(|&mut (_, _, ref mut lines)| lines)
     }
 
     /// Flushes all functions up to the given depth into the destination.
@@ -53,19 +65,25 @@ impl<'a> FunctionStack<'a> {
         let len = self.0.len();
 
         // Fast path if the last item is already a parent of the current depth.
-        if self.0.last().map_or(false, |&(d, _)| d < depth) {
+        if self.0.last().map_or(false, |&(d, _, _)| d < depth) {
             return;
         }
 
         // Search for the first function that lies at or beyond the specified depth.
-        let cutoff = self.0.iter().position(|&(d, _)| d >= depth).unwrap_or(len);
+        let cutoff = self
+            .0
+            .iter()
+            .position(|&(d, _, _)| d >= depth)
+            .unwrap_or(len);
 
         // Pull functions from the stack. Inline functions are folded into their parents
         // transitively, while regular functions are returned. This also works when functions and
         // inlines are interleaved.
         let mut inlinee = None;
         for _ in cutoff..len {
-            let (_, mut function) = self.0.pop().unwrap();
+            let (_, mut function, lines) = self.0.pop().unwrap();
+            function.lines.extend(lines);
+            function.lines.shrink_to_fit();
             if let Some(inlinee) = inlinee.take() {
                 function.inlinees.push(inlinee);
             }
@@ -85,3 +103,21 @@ impl<'a> FunctionStack<'a> {
         }
     }
 }
+
+/// Appends data to a buffer at the next offset with the given alignment.
+///
+/// The gap before the data is padded with zeros. Returns the offset at which the data starts.
+#[cfg(any(feature = "elf", feature = "macho"))]
+pub fn append_aligned(buffer: &mut Vec<u8>, data: &[u8], align: u64) -> usize {
+    let align = align.max(1) as usize;
+    let offset = buffer.len().div_ceil(align) * align;
+    buffer.resize(offset, 0);
+    buffer.extend_from_slice(data);
+    offset
+}
+
+/// Normalizes a path for case-insensitive matching independent of the path separator.
+#[cfg(any(feature = "ms", feature = "sourcebundle"))]
+pub fn normalize_path(path: &str) -> String {
+    path.replace('\\', "/").to_lowercase()
+}
@@ -5,11 +5,23 @@
 //! contents to debug files.
 //!
 //! Source bundles are ZIP archives with a well-defined internal structure. Most importantly, they
-//! contain source files in a nested directory structure. Additionally, there is meta data
-//! associated to every source file, which allows to store additional properties, such as the
-//! original file system path, a web URL, and custom headers.
+//! contain the contents of source files, as well as a manifest listing the files by their path in
+//! a nested directory structure. Additionally, there is meta data associated to every source file,
+//! which allows to store additional properties, such as the original file system path, a web URL,
+//! and custom headers.
 //!
-//! The internal structure is as follows:
+//! Starting with version 3, file contents are stored by their SHA-1 hash. The manifest maps each
+//! file to its content blob, so that files with identical contents are only stored once:
+//!
+//! ```txt
+//! manifest.json
+//! blobs/
+//!   3f786850e387550fdab836ed7e6dc881de23001b
+//!   89e6c98d92887913cadf06b2adb97f26cde4849b
+//! ```
+//!
+//! Older bundles store each file at its path in the archive instead. These are still supported
+//! when reading:
 //!
 //! ```txt
 //! manifest.json
@@ -36,10 +48,12 @@
 
 use std::borrow::Cow;
 use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::convert::TryFrom;
 use std::error::Error;
 use std::fmt;
 use std::fs::{File, OpenOptions};
 use std::io::{BufReader, BufWriter, Read, Seek, Write};
+use std::ops::Range;
 use std::path::Path;
 use std::sync::Arc;
 
@@ -50,24 +64,32 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use zip::{write::FileOptions, ZipWriter};
 
-use symbolic_common::{Arch, AsSelf, CodeId, DebugId};
+use symbolic_common::{
+    source_context, Arch, AsSelf, CodeId, DebugId, SourceContext, SourceContextOptions,
+};
 
 use crate::base::*;
 use crate::shared::Parse;
+use crate::sourcelink::SourceLinkMappings;
 use crate::{DebugSession, ObjectKind, ObjectLike};
 
 /// Magic bytes of a source bundle. They are prepended to the ZIP file.
 static BUNDLE_MAGIC: [u8; 4] = *b"SYSB";
 
 /// Version of the bundle and manifest format.
-static BUNDLE_VERSION: u32 = 2;
+///
+/// Version 3 introduced content-addressed storage of files in [`BLOBS_PATH`].
+static BUNDLE_VERSION: u32 = 3;
 
 /// Relative path to the manifest file in the bundle file.
 static MANIFEST_PATH: &str = "manifest.json";
 
-/// Path at which files will be written into the bundle.
+/// Path prefix of logical file paths in the manifest.
 static FILES_PATH: &str = "files";
 
+/// Path at which file contents will be written into the bundle, named by their content hash.
+static BLOBS_PATH: &str = "blobs";
+
 lazy_static::lazy_static! {
     static ref SANE_PATH_RE: Regex = Regex::new(r#":?[/\\]+"#).unwrap();
 }
@@ -176,6 +198,10 @@ pub struct SourceFileInfo {
 
     #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
     headers: BTreeMap<String, String>,
+
+    /// Path to the file contents in the archive, if different from the file's path.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    blob: String,
 }
 
 impl SourceFileInfo {
@@ -309,6 +335,10 @@ struct SourceBundleManifest {
     #[serde(default)]
     pub files: BTreeMap<String, SourceFileInfo>,
 
+    /// SourceLink mappings from source paths to URL templates.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub source_links: BTreeMap<String, String>,
+
     /// Arbitrary attributes to include in the bundle.
     #[serde(flatten)]
     pub attributes: BTreeMap<String, String>,
@@ -482,6 +512,7 @@ impl<'data> SourceBundle<'data> {
             manifest: self.manifest.clone(),
             archive: self.archive.clone(),
             files_by_path: LazyCell::new(),
+            source_links: LazyCell::new(),
         })
     }
 
@@ -509,6 +540,29 @@ impl<'data> SourceBundle<'data> {
     pub fn is_empty(&self) -> bool {
         self.manifest.files.is_empty()
     }
+
+    /// Reads the source contents of a file at the given byte range of the bundle's data.
+    ///
+    /// The range must have been obtained from
+    /// [`SourceBundleDebugSession::source_range_by_path`] for the same bundle. In contrast to
+    /// looking up files by their path, this does not need to read the bundle's manifest or file
+    /// index.
+    pub fn source_by_range(&self, range: Range<u64>) -> Result<String, SourceBundleError> {
+        let mut entry = usize::try_from(range.start)
+            .ok()
+            .zip(usize::try_from(range.end).ok())
+            .and_then(|(start, end)| self.data.get(start..end))
+            .ok_or(SourceBundleErrorKind::BadZip)?;
+
+        let mut file = zip::read::read_zipfile_from_stream(&mut entry)
+            .map_err(|e| SourceBundleError::new(SourceBundleErrorKind::BadZip, e))?
+            .ok_or(SourceBundleErrorKind::BadZip)?;
+
+        let mut source_content = String::new();
+        file.read_to_string(&mut source_content)
+            .map_err(|e| SourceBundleError::new(SourceBundleErrorKind::BadZip, e))?;
+        Ok(source_content)
+    }
 }
 
 impl<'slf, 'data: 'slf> AsSelf<'slf> for SourceBundle<'data> {
@@ -601,6 +655,7 @@ pub struct SourceBundleDebugSession<'data> {
     manifest: Arc<SourceBundleManifest>,
     archive: Arc<Mutex<zip::read::ZipArchive<std::io::Cursor<&'data [u8]>>>>,
     files_by_path: LazyCell<HashMap<String, String>>,
+    source_links: LazyCell<SourceLinkMappings>,
 }
 
 impl<'data> SourceBundleDebugSession<'data> {
@@ -616,6 +671,17 @@ impl<'data> SourceBundleDebugSession<'data> {
         std::iter::empty()
     }
 
+    /// Returns the SourceLink mappings stored in this bundle.
+    pub fn source_links(&self) -> &SourceLinkMappings {
+        self.source_links.borrow_with(|| {
+            let mut mappings = SourceLinkMappings::new();
+            for (pattern, url) in &self.manifest.source_links {
+                mappings.add(pattern, url);
+            }
+            mappings
+        })
+    }
+
     /// Create a reverse mapping of source paths to ZIP paths.
     fn get_files_by_path(&self) -> HashMap<String, String> {
         let files = &self.manifest.files;
@@ -638,11 +704,23 @@ impl<'data> SourceBundleDebugSession<'data> {
             .map(|zip_path| zip_path.as_str())
     }
 
+    /// Get the path of the archive entry holding the contents of a file in the bundle.
+    ///
+    /// If the file's contents are stored in a content-addressed blob, this is the blob's path.
+    fn blob_path_by_zip_path<'z>(&'z self, zip_path: &'z str) -> &'z str {
+        match self.manifest.files.get(zip_path) {
+            Some(info) if !info.blob.is_empty() => info.blob.as_str(),
+            _ => zip_path,
+        }
+    }
+
     /// Get source by the path of a file in the bundle.
+    ///
+    /// If the file's contents are stored in a content-addressed blob, the blob is read instead.
     fn source_by_zip_path(&self, zip_path: &str) -> Result<Option<String>, SourceBundleError> {
         let mut archive = self.archive.lock();
         let mut file = archive
-            .by_name(zip_path)
+            .by_name(self.blob_path_by_zip_path(zip_path))
             .map_err(|e| SourceBundleError::new(SourceBundleErrorKind::BadZip, e))?;
         let mut source_content = String::new();
 
@@ -663,6 +741,67 @@ impl<'data> SourceBundleDebugSession<'data> {
         self.source_by_zip_path(zip_path)
             .map(|opt| opt.map(Cow::Owned))
     }
+
+    /// Looks up the byte range of a file's contents within the bundle by its full canonicalized
+    /// path.
+    ///
+    /// The range covers the archive entry of the file within [`SourceBundle::data`], including
+    /// its header. Files with identical contents share the same range. Pass the range to
+    /// [`SourceBundle::source_by_range`] to read the file without looking up its path again.
+    pub fn source_range_by_path(
+        &self,
+        path: &str,
+    ) -> Result<Option<Range<u64>>, SourceBundleError> {
+        let zip_path = match self.zip_path_by_source_path(path) {
+            Some(zip_path) => zip_path,
+            None => return Ok(None),
+        };
+
+        let mut archive = self.archive.lock();
+        let file = archive
+            .by_name(self.blob_path_by_zip_path(zip_path))
+            .map_err(|e| SourceBundleError::new(SourceBundleErrorKind::BadZip, e))?;
+
+        Ok(Some(
+            file.header_start()..file.data_start() + file.compressed_size(),
+        ))
+    }
+
+    /// Looks up the lines surrounding a line of a file by its full canonicalized path.
+    ///
+    /// The `line` is 1-based. Returns `None` if the file is not contained in the bundle or the line
+    /// exceeds its length. See [`source_context`] for more information.
+    pub fn source_context_by_path(
+        &self,
+        path: &str,
+        line: u32,
+        options: SourceContextOptions,
+    ) -> Result<Option<SourceContext<'static>>, SourceBundleError> {
+        let source = match self.source_by_path(path)? {
+            Some(source) => source,
+            None => return Ok(None),
+        };
+
+        let context = source_context(source.as_bytes(), line, options);
+        Ok(context.map(SourceContext::into_owned))
+    }
+
+    /// Looks up the remote URL of a file by its full canonicalized path.
+    ///
+    /// This returns the URL stored for a file in the bundle, if any. Otherwise, the URL is
+    /// resolved from the bundle's [SourceLink mappings](Self::source_links). This allows to
+    /// retrieve sources that are hosted remotely instead of embedded in the bundle.
+    pub fn source_url_by_path(&self, path: &str) -> Option<Cow<'_, str>> {
+        let file_url = self
+            .zip_path_by_source_path(path)
+            .and_then(|zip_path| self.manifest.files.get(zip_path))
+            .and_then(SourceFileInfo::url);
+
+        match file_url {
+            Some(url) => Some(Cow::Borrowed(url)),
+            None => self.source_links().resolve(path).map(Cow::Owned),
+        }
+    }
 }
 
 impl<'data, 'session> DebugSession<'session> for SourceBundleDebugSession<'data> {
@@ -694,7 +833,7 @@ impl<'s> Iterator for SourceBundleFileIterator<'s> {
     fn next(&mut self) -> Option<Self::Item> {
         let source_file = self.files.next()?;
         Some(Ok(FileEntry {
-            compilation_dir: &[],
+            compilation_dir: Cow::Borrowed(&[]),
             info: FileInfo::from_path(source_file.path.as_bytes()),
         }))
     }
@@ -756,6 +895,7 @@ where
     W: Seek + Write,
 {
     manifest: SourceBundleManifest,
+    blobs: BTreeSet<String>,
     writer: ZipWriter<W>,
 }
 
@@ -772,6 +912,7 @@ where
 
         Ok(SourceBundleWriter {
             manifest: SourceBundleManifest::new(),
+            blobs: BTreeSet::new(),
             writer: ZipWriter::new(writer),
         })
     }
@@ -818,6 +959,20 @@ where
             .map(String::as_str)
     }
 
+    /// Adds SourceLink mappings to resolve remote URLs of files that are not embedded.
+    ///
+    /// Mappings are written to the manifest when the bundle is [finished]. Existing mappings for
+    /// the same pattern are replaced.
+    ///
+    /// [finished]: struct.SourceBundleWriter.html#method.finish
+    pub fn add_source_links(&mut self, mappings: &SourceLinkMappings) {
+        for (pattern, url) in mappings.iter() {
+            self.manifest
+                .source_links
+                .insert(pattern.to_owned(), url.to_owned());
+        }
+    }
+
     /// Determines whether a file at the given path has been added already.
     pub fn has_file<S>(&self, path: S) -> bool
     where
@@ -848,13 +1003,16 @@ where
     /// # Ok(()) }
     /// ```
     ///
+    /// File contents are stored by their content hash. If a file with identical contents has been
+    /// added before, only the manifest entry is added and the contents are shared.
+    ///
     /// Returns `Ok(true)` if the file was successfully added, or `Ok(false)` if the file aready
     /// existed. Otherwise, an error is returned if writing the file fails.
     pub fn add_file<S, R>(
         &mut self,
         path: S,
         mut file: R,
-        info: SourceFileInfo,
+        mut info: SourceFileInfo,
     ) -> Result<(), SourceBundleError>
     where
         S: AsRef<str>,
@@ -863,12 +1021,22 @@ where
         let full_path = self.file_path(path.as_ref());
         let unique_path = self.unique_path(full_path);
 
-        self.writer
-            .start_file(unique_path.clone(), FileOptions::default())
-            .map_err(|e| SourceBundleError::new(SourceBundleErrorKind::WriteFailed, e))?;
-        std::io::copy(&mut file, &mut self.writer)
+        let mut contents = Vec::new();
+        file.read_to_end(&mut contents)
             .map_err(|e| SourceBundleError::new(SourceBundleErrorKind::WriteFailed, e))?;
 
+        let blob_path = self.blob_path(&contents);
+        if !self.blobs.contains(&blob_path) {
+            self.writer
+                .start_file(blob_path.clone(), FileOptions::default())
+                .map_err(|e| SourceBundleError::new(SourceBundleErrorKind::WriteFailed, e))?;
+            self.writer
+                .write_all(&contents)
+                .map_err(|e| SourceBundleError::new(SourceBundleErrorKind::WriteFailed, e))?;
+            self.blobs.insert(blob_path.clone());
+        }
+
+        info.blob = blob_path;
         self.manifest.files.insert(unique_path, info);
         Ok(())
     }
@@ -970,6 +1138,12 @@ where
         format!("{}/{}", FILES_PATH, path)
     }
 
+    /// Returns the content-addressed path for the given file contents within the source bundle.
+    fn blob_path(&self, contents: &[u8]) -> String {
+        let digest = sha1::Sha1::from(contents).digest();
+        format!("{}/{}", BLOBS_PATH, digest)
+    }
+
     /// Returns a unique path for a file.
     ///
     /// Returns the path if the file does not exist already. Otherwise, a counter is appended to the
@@ -1060,6 +1234,178 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_deduplicate_contents() -> Result<(), SourceBundleError> {
+        let mut writer = Cursor::new(Vec::new());
+        let mut bundle = SourceBundleWriter::start(&mut writer)?;
+
+        let mut info = SourceFileInfo::new();
+        info.set_path("/a/foo.h".into());
+        bundle.add_file("a/foo.h", &b"#pragma once"[..], info)?;
+
+        let mut info = SourceFileInfo::new();
+        info.set_path("/b/foo.h".into());
+        bundle.add_file("b/foo.h", &b"#pragma once"[..], info)?;
+
+        let mut info = SourceFileInfo::new();
+        info.set_path("/b/bar.h".into());
+        bundle.add_file("b/bar.h", &b"int bar();"[..], info)?;
+
+        bundle.finish()?;
+        let data = writer.into_inner();
+
+        let bundle = SourceBundle::parse(&data)?;
+        // The manifest and two blobs.
+        assert_eq!(bundle.archive.lock().len(), 3);
+
+        let session = bundle.debug_session()?;
+        assert_eq!(session.files().count(), 3);
+        assert_eq!(
+            session.source_by_path("/a/foo.h")?.as_deref(),
+            Some("#pragma once")
+        );
+        assert_eq!(
+            session.source_by_path("/b/foo.h")?.as_deref(),
+            Some("#pragma once")
+        );
+        assert_eq!(
+            session.source_by_path("/b/bar.h")?.as_deref(),
+            Some("int bar();")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_source_context_by_path() -> Result<(), SourceBundleError> {
+        let mut writer = Cursor::new(Vec::new());
+        let mut bundle = SourceBundleWriter::start(&mut writer)?;
+
+        let mut info = SourceFileInfo::new();
+        info.set_path("/src/main.c".into());
+        let source = &b"#include <stdio.h>\nint main() {\n  return 0;\n}\n"[..];
+        bundle.add_file("src/main.c", source, info)?;
+
+        bundle.finish()?;
+        let data = writer.into_inner();
+
+        let bundle = SourceBundle::parse(&data)?;
+        let session = bundle.debug_session()?;
+        let options = SourceContextOptions::new().context_lines(1);
+
+        let context = session
+            .source_context_by_path("/src/main.c", 3, options)?
+            .unwrap();
+        assert_eq!(context.pre_context, ["int main() {"]);
+        assert_eq!(context.context_line, "  return 0;");
+        assert_eq!(context.post_context, ["}"]);
+
+        assert_eq!(
+            session.source_context_by_path("/src/main.c", 5, options)?,
+            None
+        );
+        assert_eq!(
+            session.source_context_by_path("/src/other.c", 1, options)?,
+            None
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_source_by_range() -> Result<(), SourceBundleError> {
+        let mut writer = Cursor::new(Vec::new());
+        let mut bundle = SourceBundleWriter::start(&mut writer)?;
+
+        for (path, contents) in [
+            ("/a/foo.h", "#pragma once"),
+            ("/b/foo.h", "#pragma once"),
+            ("/b/bar.h", "int bar();"),
+        ] {
+            let mut info = SourceFileInfo::new();
+            info.set_path(path.into());
+            bundle.add_file(&path[1..], contents.as_bytes(), info)?;
+        }
+
+        bundle.finish()?;
+        let data = writer.into_inner();
+
+        let bundle = SourceBundle::parse(&data)?;
+        let session = bundle.debug_session()?;
+
+        let foo_a = session.source_range_by_path("/a/foo.h")?.unwrap();
+        let foo_b = session.source_range_by_path("/b/foo.h")?.unwrap();
+        let bar = session.source_range_by_path("/b/bar.h")?.unwrap();
+        assert_eq!(foo_a, foo_b);
+        assert_ne!(foo_a, bar);
+        assert_eq!(session.source_range_by_path("/c/baz.h")?, None);
+
+        assert_eq!(bundle.source_by_range(foo_a)?, "#pragma once");
+        assert_eq!(bundle.source_by_range(bar.clone())?, "int bar();");
+
+        let len = data.len() as u64;
+        assert!(bundle.source_by_range(len..len + 1).is_err());
+        assert!(bundle.source_by_range(bar.start + 1..bar.end).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_path_based_bundle() -> Result<(), Box<dyn std::error::Error>> {
+        let mut writer = Cursor::new(Vec::new());
+        let header = SourceBundleHeader {
+            version: 2,
+            ..Default::default()
+        };
+        writer.write_all(header.as_bytes())?;
+
+        let mut zip = ZipWriter::new(writer);
+        zip.start_file("files/foo.txt", FileOptions::default())?;
+        zip.write_all(b"foo")?;
+        zip.start_file(MANIFEST_PATH, FileOptions::default())?;
+        zip.write_all(br#"{"files":{"files/foo.txt":{"type":"source","path":"/foo.txt"}}}"#)?;
+        let data = zip.finish()?.into_inner();
+
+        let bundle = SourceBundle::parse(&data)?;
+        let session = bundle.debug_session()?;
+        assert_eq!(session.source_by_path("/foo.txt")?.as_deref(), Some("foo"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_source_links() -> Result<(), SourceBundleError> {
+        let mut writer = Cursor::new(Vec::new());
+        let mut bundle = SourceBundleWriter::start(&mut writer)?;
+
+        let mut info = SourceFileInfo::new();
+        info.set_path("/src/embedded.c".into());
+        info.set_url("https://example.com/embedded.c".into());
+        bundle.add_file("src/embedded.c", &b"int main();"[..], info)?;
+
+        let mut mappings = SourceLinkMappings::new();
+        mappings.add("/src/*", "https://example.com/raw/*");
+        bundle.add_source_links(&mappings);
+        bundle.finish()?;
+
+        let data = writer.into_inner();
+        let bundle = SourceBundle::parse(&data)?;
+        let session = bundle.debug_session()?;
+
+        assert_eq!(session.source_links(), &mappings);
+        assert_eq!(
+            session.source_url_by_path("/src/embedded.c").as_deref(),
+            Some("https://example.com/embedded.c")
+        );
+        assert_eq!(
+            session.source_url_by_path("/src/remote/file.c").as_deref(),
+            Some("https://example.com/raw/remote/file.c")
+        );
+        assert_eq!(session.source_url_by_path("/other/file.c"), None);
+
+        Ok(())
+    }
+
     #[test]
     fn test_bundle_paths() {
         assert_eq!(sanitize_bundle_path("foo"), "foo");
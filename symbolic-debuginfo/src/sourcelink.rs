@@ -0,0 +1,233 @@
+//! Support for SourceLink, which maps local source file paths to remote URLs.
+//!
+//! [SourceLink] is a JSON document embedded in Portable PDBs and Windows PDBs. It maps paths, or
+//! path prefixes ending in `*`, to URL templates. The remainder of a path matched by `*` is
+//! substituted into the `*` of the URL:
+//!
+//! ```json
+//! {
+//!   "documents": {
+//!     "C:\\src\\*": "https://raw.githubusercontent.com/org/repo/3a1c5d/*"
+//!   }
+//! }
+//! ```
+//!
+//! The same mappings can also be constructed manually to resolve URLs for sources that are hosted
+//! on a web server, such as a GitHub Enterprise instance, instead of embedding them.
+//!
+//! [SourceLink]: https://github.com/dotnet/designs/blob/main/accepted/2020/diagnostics/source-link.md
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::error::Error;
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::shared::normalize_path;
+
+/// An error when parsing [`SourceLinkMappings`].
+#[derive(Debug, Error)]
+#[error("invalid source link document")]
+pub struct SourceLinkError {
+    #[source]
+    source: Option<Box<dyn Error + Send + Sync + 'static>>,
+}
+
+impl SourceLinkError {
+    /// Creates a new SourceLink error from an arbitrary error payload.
+    fn new<E>(source: E) -> Self
+    where
+        E: Into<Box<dyn Error + Send + Sync>>,
+    {
+        let source = Some(source.into());
+        Self { source }
+    }
+}
+
+/// The JSON representation of a SourceLink document.
+#[derive(Deserialize)]
+struct SourceLinkDocument {
+    documents: BTreeMap<String, String>,
+}
+
+/// A single mapping from a path pattern to a URL template.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct SourceLinkMapping {
+    /// The original pattern, as declared.
+    pattern: String,
+    /// The normalized pattern without trailing wildcard used for matching.
+    key: String,
+    /// Whether the pattern ends in a wildcard.
+    wildcard: bool,
+    /// The URL template.
+    url: String,
+}
+
+/// A set of SourceLink mappings to resolve remote URLs of source files.
+///
+/// See the [module level documentation](self) for more information.
+///
+/// # Example
+///
+/// ```
+/// use symbolic_debuginfo::sourcelink::SourceLinkMappings;
+///
+/// let mut mappings = SourceLinkMappings::new();
+/// mappings.add("C:\\src\\*", "https://example.com/repo/*");
+///
+/// assert_eq!(
+///     mappings.resolve("C:\\src\\lib\\main.cs").as_deref(),
+///     Some("https://example.com/repo/lib/main.cs")
+/// );
+/// assert_eq!(mappings.resolve("D:\\other.cs"), None);
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SourceLinkMappings {
+    mappings: Vec<SourceLinkMapping>,
+}
+
+impl SourceLinkMappings {
+    /// Creates an empty set of mappings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses mappings from a SourceLink JSON document.
+    pub fn parse(json: &[u8]) -> Result<Self, SourceLinkError> {
+        let document: SourceLinkDocument =
+            serde_json::from_slice(json).map_err(SourceLinkError::new)?;
+
+        let mut mappings = Self::new();
+        for (pattern, url) in &document.documents {
+            mappings.add(pattern, url);
+        }
+
+        Ok(mappings)
+    }
+
+    /// Adds a mapping from a path pattern to a URL template.
+    ///
+    /// If the pattern ends in `*`, it matches all paths starting with the pattern. The matched
+    /// remainder of the path is then substituted into the `*` of the URL template. Otherwise, the
+    /// pattern only matches the exact path. Matching is case-insensitive and treats forward and
+    /// backward slashes as equal.
+    ///
+    /// If the pattern has been added before, its URL is replaced.
+    pub fn add(&mut self, pattern: &str, url: &str) {
+        let (key, wildcard) = match pattern.strip_suffix('*') {
+            Some(prefix) => (normalize_path(prefix), true),
+            None => (normalize_path(pattern), false),
+        };
+
+        self.mappings.retain(|m| m.pattern != pattern);
+        self.mappings.push(SourceLinkMapping {
+            pattern: pattern.to_owned(),
+            key,
+            wildcard,
+            url: url.to_owned(),
+        });
+
+        // Prefer the most specific pattern, and exact matches over wildcards.
+        self.mappings
+            .sort_by_key(|m| Reverse((m.key.len(), !m.wildcard)));
+    }
+
+    /// Returns the number of mappings.
+    pub fn len(&self) -> usize {
+        self.mappings.len()
+    }
+
+    /// Returns `true` if there are no mappings.
+    pub fn is_empty(&self) -> bool {
+        self.mappings.is_empty()
+    }
+
+    /// Returns an iterator over all path patterns and their URL templates.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.mappings
+            .iter()
+            .map(|m| (m.pattern.as_str(), m.url.as_str()))
+    }
+
+    /// Resolves the remote URL of the source file at the given path.
+    ///
+    /// Returns `None` if no mapping matches the path.
+    pub fn resolve(&self, path: &str) -> Option<String> {
+        let normalized = normalize_path(path);
+
+        for mapping in &self.mappings {
+            if !mapping.wildcard {
+                if normalized == mapping.key {
+                    return Some(mapping.url.clone());
+                }
+                continue;
+            }
+
+            // Lowercasing may change the byte length of non-ASCII paths, so the remainder is only
+            // taken from the original path if the lengths line up.
+            if normalized.starts_with(&mapping.key) && normalized.len() == path.len() {
+                let remainder = path[mapping.key.len()..].replace('\\', "/");
+                return Some(mapping.url.replacen('*', &remainder, 1));
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use similar_asserts::assert_eq;
+
+    #[test]
+    fn test_parse() -> Result<(), SourceLinkError> {
+        let json = br#"{
+            "documents": {
+                "C:\\src\\*": "https://example.com/repo/*",
+                "C:\\src\\vendor\\*": "https://example.com/vendor/*",
+                "C:\\generated.cs": "https://example.com/generated.cs"
+            }
+        }"#;
+
+        let mappings = SourceLinkMappings::parse(json)?;
+        assert_eq!(mappings.len(), 3);
+
+        assert_eq!(
+            mappings.resolve("c:\\SRC\\main.cs").as_deref(),
+            Some("https://example.com/repo/main.cs")
+        );
+        assert_eq!(
+            mappings.resolve("C:/src/vendor/lib/lib.cs").as_deref(),
+            Some("https://example.com/vendor/lib/lib.cs")
+        );
+        assert_eq!(
+            mappings.resolve("C:\\generated.cs").as_deref(),
+            Some("https://example.com/generated.cs")
+        );
+        assert_eq!(mappings.resolve("C:\\generated.cs.bak"), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(SourceLinkMappings::parse(b"{}").is_err());
+        assert!(SourceLinkMappings::parse(b"not json").is_err());
+    }
+
+    #[test]
+    fn test_replace_mapping() {
+        let mut mappings = SourceLinkMappings::new();
+        mappings.add("/src/*", "https://a.example.com/*");
+        mappings.add("/src/*", "https://b.example.com/*");
+
+        assert_eq!(mappings.len(), 1);
+        assert_eq!(
+            mappings.resolve("/src/main.rs").as_deref(),
+            Some("https://b.example.com/main.rs")
+        );
+    }
+}
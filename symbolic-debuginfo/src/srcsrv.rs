@@ -0,0 +1,386 @@
+//! Support for source server streams, which describe how to retrieve source files of a PDB.
+//!
+//! Source-indexed Windows PDBs contain a `srcsrv` stream written by the [source server] tools.
+//! The stream is a text document divided into sections. It declares variables that are expanded
+//! for every source file to compute a target path or URL, and optionally a command that extracts
+//! the file from version control:
+//!
+//! ```text
+//! SRCSRV: ini ------------------------------------------------
+//! VERSION=2
+//! VERCTRL=http
+//! SRCSRV: variables ------------------------------------------
+//! SRCSRVTRG=https://example.com/%var2%/%var3%
+//! SRCSRV: source files ---------------------------------------
+//! c:\src\main.cpp*3a1c5d*src/main.cpp
+//! SRCSRV: end ------------------------------------------------
+//! ```
+//!
+//! Every line in the source files section lists the local path of a file followed by additional
+//! fields, separated by `*`. These are available as `%var1%`, `%var2%` and so on in variables.
+//!
+//! [source server]: https://docs.microsoft.com/en-us/windows/win32/debug/source-server-and-source-indexing
+
+use std::collections::BTreeMap;
+use std::str;
+
+use thiserror::Error;
+
+use crate::shared::normalize_path;
+
+/// The maximum depth of nested variable references.
+const MAX_EXPANSION_DEPTH: usize = 16;
+
+/// An error when parsing [`SourceServerMappings`].
+#[derive(Debug, Error)]
+#[error("invalid source server stream: {reason}")]
+pub struct SourceServerError {
+    reason: &'static str,
+}
+
+impl SourceServerError {
+    fn new(reason: &'static str) -> Self {
+        Self { reason }
+    }
+}
+
+/// Information on how to retrieve a single source file from a source server.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SourceServerFile {
+    /// The expanded target of the file, usually a local path or a URL.
+    pub target: String,
+    /// The expanded command that extracts the file to `target`, if any.
+    pub command: Option<String>,
+}
+
+impl SourceServerFile {
+    /// Returns the target as URL if it can be downloaded directly via HTTP.
+    pub fn url(&self) -> Option<&str> {
+        let is_http = self.target.starts_with("http://") || self.target.starts_with("https://");
+        if self.command.is_none() && is_http {
+            Some(&self.target)
+        } else {
+            None
+        }
+    }
+}
+
+/// The sections of a source server stream.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Section {
+    None,
+    Ini,
+    Variables,
+    SourceFiles,
+    Other,
+}
+
+/// Source file mappings parsed from a source server stream.
+///
+/// See the [module level documentation](self) for more information.
+///
+/// # Example
+///
+/// ```
+/// use symbolic_debuginfo::srcsrv::SourceServerMappings;
+///
+/// let stream = b"SRCSRV: ini -----\r\n\
+///     VERSION=2\r\n\
+///     VERCTRL=http\r\n\
+///     SRCSRV: variables -----\r\n\
+///     SRCSRVTRG=https://example.com/%var2%\r\n\
+///     SRCSRV: source files -----\r\n\
+///     c:\\src\\main.cpp*src/main.cpp\r\n\
+///     SRCSRV: end -----\r\n";
+///
+/// let mappings = SourceServerMappings::parse(stream).unwrap();
+/// let file = mappings.resolve("C:\\src\\main.cpp").unwrap();
+/// assert_eq!(file.url(), Some("https://example.com/src/main.cpp"));
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SourceServerMappings {
+    ini: BTreeMap<String, String>,
+    variables: BTreeMap<String, String>,
+    files: BTreeMap<String, Vec<String>>,
+}
+
+impl SourceServerMappings {
+    /// Creates empty mappings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses mappings from the contents of a `srcsrv` stream.
+    pub fn parse(data: &[u8]) -> Result<Self, SourceServerError> {
+        let text = str::from_utf8(data).map_err(|_| SourceServerError::new("not utf-8"))?;
+
+        let mut mappings = Self::new();
+        let mut section = Section::None;
+
+        for line in text.lines() {
+            let line = line.trim_end_matches('\r');
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            if let Some(header) = line.strip_prefix("SRCSRV:") {
+                let name = header.trim().trim_end_matches('-').trim();
+                section = match name.to_lowercase().as_str() {
+                    "ini" => Section::Ini,
+                    "variables" => Section::Variables,
+                    "source files" => Section::SourceFiles,
+                    "end" => break,
+                    _ => Section::Other,
+                };
+                continue;
+            }
+
+            match section {
+                Section::None => return Err(SourceServerError::new("missing section header")),
+                Section::Ini | Section::Variables => {
+                    let (key, value) = line
+                        .split_once('=')
+                        .ok_or_else(|| SourceServerError::new("invalid variable"))?;
+                    let target = if section == Section::Ini {
+                        &mut mappings.ini
+                    } else {
+                        &mut mappings.variables
+                    };
+                    target.insert(key.trim().to_lowercase(), value.to_owned());
+                }
+                Section::SourceFiles => {
+                    let fields: Vec<String> = line.split('*').map(str::to_owned).collect();
+                    mappings.files.insert(normalize_path(&fields[0]), fields);
+                }
+                Section::Other => (),
+            }
+        }
+
+        if section == Section::None {
+            return Err(SourceServerError::new("missing section header"));
+        }
+
+        Ok(mappings)
+    }
+
+    /// Returns the version of the source server stream, if declared.
+    pub fn version(&self) -> Option<&str> {
+        self.ini.get("version").map(String::as_str)
+    }
+
+    /// Returns the name of the version control system, such as `http`, `tfs` or `perforce`.
+    pub fn version_control(&self) -> Option<&str> {
+        self.ini
+            .get("verctrl")
+            .or_else(|| self.variables.get("verctrl"))
+            .map(String::as_str)
+    }
+
+    /// Returns the number of indexed source files.
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Returns `true` if there are no indexed source files.
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Returns an iterator over the local paths of all indexed source files.
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.files.values().map(|fields| fields[0].as_str())
+    }
+
+    /// Resolves how to retrieve the source file at the given local path.
+    ///
+    /// The path is matched case-insensitively and treats forward and backward slashes as equal.
+    /// Returns `None` if the file is not indexed or no target is declared. Variables that cannot
+    /// be resolved, such as the built-in `%targ%`, are retained in the output verbatim.
+    pub fn resolve(&self, path: &str) -> Option<SourceServerFile> {
+        let fields = self.files.get(&normalize_path(path))?;
+
+        let target = self.expand_variable("srcsrvtrg", fields, 0)?;
+        let command = self
+            .expand_variable("srcsrvcmd", fields, 0)
+            .filter(|command| !command.trim().is_empty());
+
+        Some(SourceServerFile { target, command })
+    }
+
+    /// Expands the variable with the given lowercase name.
+    fn expand_variable(&self, name: &str, fields: &[String], depth: usize) -> Option<String> {
+        if let Some(index) = name.strip_prefix("var") {
+            let index: usize = index.parse().ok()?;
+            return fields.get(index.checked_sub(1)?).cloned();
+        }
+
+        let value = self.variables.get(name)?;
+        if depth >= MAX_EXPANSION_DEPTH {
+            return Some(value.clone());
+        }
+
+        Some(self.expand(value, fields, depth + 1))
+    }
+
+    /// Expands all variables and functions in the given template.
+    fn expand(&self, template: &str, fields: &[String], depth: usize) -> String {
+        let mut output = String::with_capacity(template.len());
+        let mut rest = template;
+
+        while let Some(start) = rest.find('%') {
+            output.push_str(&rest[..start]);
+            let after = &rest[start + 1..];
+
+            let end = match after.find('%') {
+                Some(end) => end,
+                None => {
+                    rest = &rest[start..];
+                    break;
+                }
+            };
+
+            let name = after[..end].to_lowercase();
+            let mut tail = &after[end + 1..];
+
+            let expanded = match name.as_str() {
+                "fnvar" | "fnbksl" | "fnfile" => match split_argument(tail) {
+                    Some((argument, remainder)) => {
+                        tail = remainder;
+                        let argument = self.expand(argument, fields, depth);
+                        Some(match name.as_str() {
+                            "fnvar" => self
+                                .expand_variable(&argument.trim().to_lowercase(), fields, depth)
+                                .unwrap_or_default(),
+                            "fnbksl" => argument.replace('/', "\\"),
+                            _ => argument
+                                .rsplit(&['\\', '/'][..])
+                                .next()
+                                .unwrap_or_default()
+                                .to_owned(),
+                        })
+                    }
+                    None => None,
+                },
+                _ => self.expand_variable(&name, fields, depth),
+            };
+
+            match expanded {
+                Some(value) => output.push_str(&value),
+                None => output.push_str(&rest[start..start + end + 2]),
+            }
+
+            rest = tail;
+        }
+
+        output.push_str(rest);
+        output
+    }
+}
+
+/// Splits a parenthesized function argument from the start of the input.
+fn split_argument(input: &str) -> Option<(&str, &str)> {
+    let input = input.strip_prefix('(')?;
+    let mut depth = 0;
+
+    for (index, c) in input.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return Some((&input[..index], &input[index + 1..])),
+            ')' => depth -= 1,
+            _ => (),
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use similar_asserts::assert_eq;
+
+    const HTTP_STREAM: &[u8] = b"SRCSRV: ini ------------------------------------------------\r
+VERSION=2\r
+VERCTRL=http\r
+SRCSRV: variables ------------------------------------------\r
+HTTP_ALIAS=https://example.com/raw\r
+HTTP_EXTRACT_TARGET=%HTTP_ALIAS%/%var2%/%var3%\r
+SRCSRVTRG=%HTTP_EXTRACT_TARGET%\r
+SRCSRV: source files ---------------------------------------\r
+c:\\src\\main.cpp*3a1c5d*src/main.cpp\r
+c:\\src\\lib\\util.h*3a1c5d*src/lib/util.h\r
+SRCSRV: end ------------------------------------------------\r
+";
+
+    #[test]
+    fn test_parse_http() -> Result<(), SourceServerError> {
+        let mappings = SourceServerMappings::parse(HTTP_STREAM)?;
+        assert_eq!(mappings.version(), Some("2"));
+        assert_eq!(mappings.version_control(), Some("http"));
+        assert_eq!(mappings.len(), 2);
+
+        let file = mappings.resolve("C:/SRC/lib/util.h").unwrap();
+        assert_eq!(file.target, "https://example.com/raw/3a1c5d/src/lib/util.h");
+        assert_eq!(file.command, None);
+        assert_eq!(
+            file.url(),
+            Some("https://example.com/raw/3a1c5d/src/lib/util.h")
+        );
+
+        assert_eq!(mappings.resolve("c:\\src\\other.cpp"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_command() -> Result<(), SourceServerError> {
+        let stream = b"SRCSRV: ini ------------------------------------------------
+VERSION=1
+VERCTRL=Team Foundation Server
+SRCSRV: variables ------------------------------------------
+TFS_EXTRACT_TARGET=%targ%\\%var2%\\%fnfile%(%var1%)
+TFS_EXTRACT_CMD=tf.exe view /version:%var3% /noprompt \"$%fnbksl%(%var4%)\" /output:%SRCSRVTRG%
+SRCSRVTRG=%fnvar%(TFS_EXTRACT_TARGET)
+SRCSRVCMD=%TFS_EXTRACT_CMD%
+SRCSRV: source files ---------------------------------------
+C:\\build\\app\\main.cs*TFS*42*/Project/app/main.cs
+SRCSRV: end ------------------------------------------------
+";
+
+        let mappings = SourceServerMappings::parse(stream)?;
+        assert_eq!(mappings.version_control(), Some("Team Foundation Server"));
+
+        let file = mappings.resolve("C:\\build\\app\\main.cs").unwrap();
+        assert_eq!(file.target, "%targ%\\TFS\\main.cs");
+        assert_eq!(
+            file.command.as_deref(),
+            Some("tf.exe view /version:42 /noprompt \"$\\Project\\app\\main.cs\" /output:%targ%\\TFS\\main.cs")
+        );
+        assert_eq!(file.url(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_recursive_variable() -> Result<(), SourceServerError> {
+        let stream = b"SRCSRV: variables ------------------------------------------
+SRCSRVTRG=%SRCSRVTRG%/x
+SRCSRV: source files ---------------------------------------
+main.c
+SRCSRV: end ------------------------------------------------
+";
+
+        let mappings = SourceServerMappings::parse(stream)?;
+        let file = mappings.resolve("main.c").unwrap();
+        assert!(file.target.ends_with("/x"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(SourceServerMappings::parse(b"").is_err());
+        assert!(SourceServerMappings::parse(b"VERSION=1").is_err());
+        assert!(SourceServerMappings::parse(b"SRCSRV: ini ---\nnot a variable").is_err());
+        assert!(SourceServerMappings::parse(b"\xff\xfe").is_err());
+    }
+}
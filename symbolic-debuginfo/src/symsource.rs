@@ -0,0 +1,554 @@
+//! Directory layouts of symbol servers and local symbol stores.
+//!
+//! Symbol files are usually stored in a directory hierarchy keyed by the identifiers of the
+//! object they belong to. Several conventions exist for this, which are described by
+//! [`DirectoryLayout`]:
+//!
+//!  - **Breakpad**: The layout used by Breakpad's `symupload` and Mozilla's symbol server, which
+//!    stores Breakpad symbol files as `<debug_file>/<BREAKPAD_ID>/<debug_file>.sym`.
+//!  - **Symstore**: The layout of Microsoft's `symstore.exe` and symbol servers, keyed by the PDB
+//!    signature or the PE timestamp and image size. ELF and MachO files are keyed according to the
+//!    SSQP conventions.
+//!  - **Debuginfod**: The layout of [debuginfod](crate::debuginfod) servers, keyed by the GNU build
+//!    id of ELF files.
+//!  - **Unified**: The unified symbol server layout, which stores all file types of an object under
+//!    a single identifier.
+//!
+//! A [`SymbolSource`] combines a layout with a location, such as a local directory or a remote
+//! server, to build the full paths or URLs of candidate files:
+//!
+//! ```
+//! use symbolic_debuginfo::symsource::{DirectoryLayout, ObjectId, SymbolFileType, SymbolSource};
+//!
+//! let id = ObjectId {
+//!     debug_id: "3249d99d-0c40-4931-8610-f4e4fb0b6936-1".parse().ok(),
+//!     debug_file: Some("C:\\projects\\crash.pdb".into()),
+//!     ..Default::default()
+//! };
+//!
+//! let source = SymbolSource::http("https://symbols.example.org/", DirectoryLayout::Symstore);
+//! assert_eq!(
+//!     source.candidates(SymbolFileType::Pdb, &id),
+//!     vec!["https://symbols.example.org/crash.pdb/3249D99D0C4049318610F4E4FB0B69361/crash.pdb"]
+//! );
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use symbolic_common::{CodeId, CodeIdFormat, DebugId, TypedCodeId};
+
+use crate::base::*;
+use crate::debuginfod::DebuginfodArtifact;
+use crate::Object;
+
+/// The identifiers of an object used to locate its symbol files.
+///
+/// Depending on the file type and layout, only some of these identifiers are required. Missing
+/// identifiers result in fewer candidate paths.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ObjectId {
+    /// The code identifier of the executable or library.
+    pub code_id: Option<CodeId>,
+    /// The path or name of the executable or library.
+    pub code_file: Option<String>,
+    /// The debug identifier of the debug companion file.
+    pub debug_id: Option<DebugId>,
+    /// The path or name of the debug companion file.
+    pub debug_file: Option<String>,
+}
+
+impl ObjectId {
+    /// Creates identifiers from a parsed object.
+    ///
+    /// File names are not part of all object formats and need to be filled in separately.
+    pub fn from_object(object: &Object<'_>) -> Self {
+        let debug_id = object.debug_id();
+
+        ObjectId {
+            code_id: object.code_id(),
+            code_file: None,
+            debug_id: Some(debug_id).filter(|id| !id.is_nil()),
+            debug_file: None,
+        }
+    }
+
+    /// Returns the file name of the code file without its directory.
+    fn code_file_basename(&self) -> Option<&str> {
+        self.code_file.as_deref().map(basename)
+    }
+
+    /// Returns the file name of the debug file without its directory.
+    fn debug_file_basename(&self) -> Option<&str> {
+        self.debug_file.as_deref().map(basename)
+    }
+}
+
+/// Returns the last component of a Windows or POSIX path.
+fn basename(path: &str) -> &str {
+    path.rsplit(['/', '\\']).next().unwrap_or(path)
+}
+
+/// The type of symbol file to locate.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum SymbolFileType {
+    /// A Windows PDB file.
+    Pdb,
+    /// A Windows PE executable or library.
+    Pe,
+    /// A MachO executable or library.
+    MachCode,
+    /// A MachO debug companion file, such as the DWARF file in a dSYM.
+    MachDebug,
+    /// An ELF executable or library.
+    ElfCode,
+    /// An ELF debug companion file.
+    ElfDebug,
+    /// A Breakpad symbol file.
+    Breakpad,
+    /// A source bundle.
+    SourceBundle,
+}
+
+impl SymbolFileType {
+    /// The container format of files of this type.
+    pub fn file_format(self) -> FileFormat {
+        match self {
+            Self::Pdb => FileFormat::Pdb,
+            Self::Pe => FileFormat::Pe,
+            Self::MachCode | Self::MachDebug => FileFormat::MachO,
+            Self::ElfCode | Self::ElfDebug => FileFormat::Elf,
+            Self::Breakpad => FileFormat::Breakpad,
+            Self::SourceBundle => FileFormat::SourceBundle,
+        }
+    }
+}
+
+/// A convention for storing symbol files in a directory hierarchy.
+///
+/// See the [module level documentation](self) for a description of the layouts.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum DirectoryLayout {
+    /// The Breakpad symbol store layout.
+    Breakpad,
+    /// The Microsoft symbol server layout, extended with SSQP keys for ELF and MachO.
+    Symstore,
+    /// The debuginfod server layout.
+    Debuginfod,
+    /// The unified symbol server layout.
+    Unified,
+}
+
+impl DirectoryLayout {
+    /// Builds the relative paths of a symbol file in this layout.
+    ///
+    /// Returns an empty list if the layout does not support the file type, or if identifiers
+    /// required by the layout are missing. Paths always use forward slashes.
+    pub fn paths(self, file_type: SymbolFileType, id: &ObjectId) -> Vec<String> {
+        let path = match self {
+            Self::Breakpad => breakpad_path(file_type, id),
+            Self::Symstore => symstore_path(file_type, id),
+            Self::Debuginfod => debuginfod_path(file_type, id),
+            Self::Unified => unified_path(file_type, id),
+        };
+
+        path.into_iter().collect()
+    }
+}
+
+/// Formats a debug identifier in the Breakpad format, which is also used for PDB signatures.
+fn breakpad_id(debug_id: &DebugId) -> String {
+    debug_id.breakpad().to_string()
+}
+
+/// Formats the UUID of a MachO debug identifier as lowercase hexadecimal string.
+fn mach_uuid(debug_id: &DebugId) -> String {
+    debug_id.uuid().to_simple_ref().to_string()
+}
+
+fn breakpad_path(file_type: SymbolFileType, id: &ObjectId) -> Option<String> {
+    if file_type != SymbolFileType::Breakpad {
+        return None;
+    }
+
+    let debug_file = id
+        .debug_file_basename()
+        .or_else(|| id.code_file_basename())?;
+    let debug_id = id.debug_id.as_ref()?;
+
+    let name = match debug_file.rsplit_once('.') {
+        Some((name, ext)) if ext.eq_ignore_ascii_case("pdb") => name,
+        _ => debug_file,
+    };
+
+    Some(format!(
+        "{}/{}/{}.sym",
+        debug_file,
+        breakpad_id(debug_id),
+        name
+    ))
+}
+
+fn symstore_path(file_type: SymbolFileType, id: &ObjectId) -> Option<String> {
+    match file_type {
+        SymbolFileType::Pdb => {
+            let debug_file = id.debug_file_basename()?;
+            let signature = breakpad_id(id.debug_id.as_ref()?);
+            Some(format!("{0}/{1}/{0}", debug_file, signature))
+        }
+        SymbolFileType::Pe => {
+            let code_file = id.code_file_basename()?;
+            let code_id = TypedCodeId::from_code_id(CodeIdFormat::Pe, id.code_id.as_ref()?).ok()?;
+            let signature = code_id.symstore_signature();
+            Some(format!("{0}/{1}/{0}", code_file, signature))
+        }
+        SymbolFileType::ElfCode => {
+            let code_file = id.code_file_basename()?;
+            let code_id = id.code_id.as_ref()?;
+            Some(format!("{0}/elf-buildid-{1}/{0}", code_file, code_id))
+        }
+        SymbolFileType::ElfDebug => {
+            let code_id = id.code_id.as_ref()?;
+            Some(format!("_.debug/elf-buildid-sym-{}/_.debug", code_id))
+        }
+        SymbolFileType::MachCode => {
+            let code_file = id.code_file_basename()?;
+            let uuid = mach_uuid(id.debug_id.as_ref()?);
+            Some(format!("{0}/mach-uuid-{1}/{0}", code_file, uuid))
+        }
+        SymbolFileType::MachDebug => {
+            let uuid = mach_uuid(id.debug_id.as_ref()?);
+            Some(format!("_.dwarf/mach-uuid-sym-{}/_.dwarf", uuid))
+        }
+        SymbolFileType::Breakpad | SymbolFileType::SourceBundle => None,
+    }
+}
+
+fn debuginfod_path(file_type: SymbolFileType, id: &ObjectId) -> Option<String> {
+    let artifact = match file_type {
+        SymbolFileType::ElfCode => DebuginfodArtifact::Executable,
+        SymbolFileType::ElfDebug => DebuginfodArtifact::DebugInfo,
+        _ => return None,
+    };
+
+    let code_id = id.code_id.as_ref().filter(|id| !id.is_nil())?;
+    Some(format!(
+        "buildid/{}/{}",
+        code_id.as_str().to_ascii_lowercase(),
+        artifact.path()
+    ))
+}
+
+fn unified_path(file_type: SymbolFileType, id: &ObjectId) -> Option<String> {
+    let (key, kind) = match file_type {
+        SymbolFileType::ElfCode => (id.code_id.as_ref()?.to_string(), "executable"),
+        SymbolFileType::ElfDebug => (id.code_id.as_ref()?.to_string(), "debuginfo"),
+        SymbolFileType::MachCode => (mach_uuid(id.debug_id.as_ref()?), "executable"),
+        SymbolFileType::MachDebug => (mach_uuid(id.debug_id.as_ref()?), "debuginfo"),
+        SymbolFileType::Pe => (id.code_id.as_ref()?.to_string(), "executable"),
+        SymbolFileType::Pdb => (id.debug_id.as_ref()?.breakpad().to_string(), "debuginfo"),
+        SymbolFileType::Breakpad => (id.debug_id.as_ref()?.breakpad().to_string(), "breakpad"),
+        SymbolFileType::SourceBundle => {
+            (id.debug_id.as_ref()?.breakpad().to_string(), "sourcebundle")
+        }
+    };
+
+    let key = key.to_ascii_lowercase();
+    if key.len() < 3 {
+        return None;
+    }
+
+    Some(format!("{}/{}/{}", &key[..2], &key[2..], kind))
+}
+
+/// A location that stores symbol files in a [`DirectoryLayout`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SymbolSource {
+    /// A directory on the local file system.
+    Local {
+        /// The root directory of the symbol store.
+        root: PathBuf,
+        /// The layout of files below the root.
+        layout: DirectoryLayout,
+    },
+    /// An HTTP server.
+    Http {
+        /// The base URL of the symbol server.
+        url: String,
+        /// The layout of files below the base URL.
+        layout: DirectoryLayout,
+    },
+    /// A Google Cloud Storage bucket.
+    Gcs {
+        /// The name of the bucket.
+        bucket: String,
+        /// An optional prefix of all object keys in the bucket.
+        prefix: String,
+        /// The layout of object keys below the prefix.
+        layout: DirectoryLayout,
+    },
+}
+
+impl SymbolSource {
+    /// Creates a source for a local directory.
+    pub fn local<P: Into<PathBuf>>(root: P, layout: DirectoryLayout) -> Self {
+        Self::Local {
+            root: root.into(),
+            layout,
+        }
+    }
+
+    /// Creates a source for an HTTP server.
+    pub fn http<S: Into<String>>(url: S, layout: DirectoryLayout) -> Self {
+        Self::Http {
+            url: url.into(),
+            layout,
+        }
+    }
+
+    /// Creates a source for a Google Cloud Storage bucket.
+    pub fn gcs<B, P>(bucket: B, prefix: P, layout: DirectoryLayout) -> Self
+    where
+        B: Into<String>,
+        P: Into<String>,
+    {
+        Self::Gcs {
+            bucket: bucket.into(),
+            prefix: prefix.into(),
+            layout,
+        }
+    }
+
+    /// The directory layout of this source.
+    pub fn layout(&self) -> DirectoryLayout {
+        match *self {
+            Self::Local { layout, .. } | Self::Http { layout, .. } | Self::Gcs { layout, .. } => {
+                layout
+            }
+        }
+    }
+
+    /// Builds the locations of candidate files for the given object.
+    ///
+    /// These are file system paths for local sources, URLs for HTTP sources and `gs://` URLs for
+    /// Google Cloud Storage sources.
+    pub fn candidates(&self, file_type: SymbolFileType, id: &ObjectId) -> Vec<String> {
+        let paths = self.layout().paths(file_type, id);
+
+        match self {
+            Self::Local { root, .. } => paths
+                .iter()
+                .map(|path| root.join(path).to_string_lossy().into_owned())
+                .collect(),
+            Self::Http { url, .. } => {
+                let base = url.trim_end_matches('/');
+                paths
+                    .iter()
+                    .map(|path| format!("{}/{}", base, path))
+                    .collect()
+            }
+            Self::Gcs { bucket, prefix, .. } => {
+                let prefix = prefix.trim_matches('/');
+                paths
+                    .iter()
+                    .map(|path| match prefix {
+                        "" => format!("gs://{}/{}", bucket, path),
+                        _ => format!("gs://{}/{}/{}", bucket, prefix, path),
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// Returns the path of the first existing candidate file in a local source.
+    ///
+    /// Remote sources cannot be probed without network access and always return `None`. Use
+    /// [`candidates`](Self::candidates) to fetch them instead.
+    pub fn probe(&self, file_type: SymbolFileType, id: &ObjectId) -> Option<PathBuf> {
+        let root = match self {
+            Self::Local { root, .. } => root,
+            _ => return None,
+        };
+
+        self.layout()
+            .paths(file_type, id)
+            .into_iter()
+            .map(|path| root.join(Path::new(&path)))
+            .find(|path| path.is_file())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use similar_asserts::assert_eq;
+
+    fn pdb_id() -> ObjectId {
+        ObjectId {
+            code_id: Some(CodeId::new("5ab380779000".into())),
+            code_file: Some("C:\\projects\\crash.exe".into()),
+            debug_id: "3249d99d-0c40-4931-8610-f4e4fb0b6936-1".parse().ok(),
+            debug_file: Some("C:\\projects\\crash.pdb".into()),
+        }
+    }
+
+    fn elf_id() -> ObjectId {
+        ObjectId {
+            code_id: Some(CodeId::new(
+                "f1c3bcc0279865fe3058404b2831d9e64135386c".into(),
+            )),
+            code_file: Some("/usr/lib/libcrash.so".into()),
+            debug_id: "c0bcc3f1-9827-fe65-3058-404b2831d9e6".parse().ok(),
+            debug_file: None,
+        }
+    }
+
+    fn mach_id() -> ObjectId {
+        ObjectId {
+            code_id: Some(CodeId::new("67e9247c814e392ba027dbde6748fcbf".into())),
+            code_file: Some("/Applications/Crash.app/Contents/MacOS/crash".into()),
+            debug_id: "67e9247c-814e-392b-a027-dbde6748fcbf".parse().ok(),
+            debug_file: None,
+        }
+    }
+
+    #[test]
+    fn test_breakpad() {
+        let layout = DirectoryLayout::Breakpad;
+        assert_eq!(
+            layout.paths(SymbolFileType::Breakpad, &pdb_id()),
+            vec!["crash.pdb/3249D99D0C4049318610F4E4FB0B69361/crash.sym"]
+        );
+        assert_eq!(
+            layout.paths(SymbolFileType::Breakpad, &elf_id()),
+            vec!["libcrash.so/C0BCC3F19827FE653058404B2831D9E60/libcrash.so.sym"]
+        );
+        assert!(layout.paths(SymbolFileType::Pdb, &pdb_id()).is_empty());
+    }
+
+    #[test]
+    fn test_symstore() {
+        let layout = DirectoryLayout::Symstore;
+        assert_eq!(
+            layout.paths(SymbolFileType::Pdb, &pdb_id()),
+            vec!["crash.pdb/3249D99D0C4049318610F4E4FB0B69361/crash.pdb"]
+        );
+        assert_eq!(
+            layout.paths(SymbolFileType::Pe, &pdb_id()),
+            vec!["crash.exe/5AB380779000/crash.exe"]
+        );
+        assert_eq!(
+            layout.paths(SymbolFileType::ElfCode, &elf_id()),
+            vec!["libcrash.so/elf-buildid-f1c3bcc0279865fe3058404b2831d9e64135386c/libcrash.so"]
+        );
+        assert_eq!(
+            layout.paths(SymbolFileType::ElfDebug, &elf_id()),
+            vec!["_.debug/elf-buildid-sym-f1c3bcc0279865fe3058404b2831d9e64135386c/_.debug"]
+        );
+        assert_eq!(
+            layout.paths(SymbolFileType::MachCode, &mach_id()),
+            vec!["crash/mach-uuid-67e9247c814e392ba027dbde6748fcbf/crash"]
+        );
+        assert_eq!(
+            layout.paths(SymbolFileType::MachDebug, &mach_id()),
+            vec!["_.dwarf/mach-uuid-sym-67e9247c814e392ba027dbde6748fcbf/_.dwarf"]
+        );
+    }
+
+    #[test]
+    fn test_symstore_pe_signature() {
+        let layout = DirectoryLayout::Symstore;
+        let mut id = pdb_id();
+
+        id.code_id = Some(CodeId::new("5ab380771a000".into()));
+        assert_eq!(
+            layout.paths(SymbolFileType::Pe, &id),
+            vec!["crash.exe/5AB380771a000/crash.exe"]
+        );
+
+        id.code_id = Some(CodeId::new("5ab38077".into()));
+        assert!(layout.paths(SymbolFileType::Pe, &id).is_empty());
+    }
+
+    #[test]
+    fn test_debuginfod() {
+        let layout = DirectoryLayout::Debuginfod;
+        assert_eq!(
+            layout.paths(SymbolFileType::ElfDebug, &elf_id()),
+            vec!["buildid/f1c3bcc0279865fe3058404b2831d9e64135386c/debuginfo"]
+        );
+        assert_eq!(
+            layout.paths(SymbolFileType::ElfCode, &elf_id()),
+            vec!["buildid/f1c3bcc0279865fe3058404b2831d9e64135386c/executable"]
+        );
+        assert!(layout.paths(SymbolFileType::Pdb, &pdb_id()).is_empty());
+    }
+
+    #[test]
+    fn test_unified() {
+        let layout = DirectoryLayout::Unified;
+        assert_eq!(
+            layout.paths(SymbolFileType::ElfDebug, &elf_id()),
+            vec!["f1/c3bcc0279865fe3058404b2831d9e64135386c/debuginfo"]
+        );
+        assert_eq!(
+            layout.paths(SymbolFileType::Pdb, &pdb_id()),
+            vec!["32/49d99d0c4049318610f4e4fb0b69361/debuginfo"]
+        );
+        assert_eq!(
+            layout.paths(SymbolFileType::Breakpad, &pdb_id()),
+            vec!["32/49d99d0c4049318610f4e4fb0b69361/breakpad"]
+        );
+        assert_eq!(
+            layout.paths(SymbolFileType::MachCode, &mach_id()),
+            vec!["67/e9247c814e392ba027dbde6748fcbf/executable"]
+        );
+    }
+
+    #[test]
+    fn test_missing_identifiers() {
+        let id = ObjectId::default();
+        assert!(DirectoryLayout::Symstore
+            .paths(SymbolFileType::Pdb, &id)
+            .is_empty());
+        assert!(DirectoryLayout::Unified
+            .paths(SymbolFileType::ElfCode, &id)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_source_candidates() {
+        let gcs = SymbolSource::gcs("symbols", "/prefix/", DirectoryLayout::Unified);
+        assert_eq!(
+            gcs.candidates(SymbolFileType::ElfCode, &elf_id()),
+            vec!["gs://symbols/prefix/f1/c3bcc0279865fe3058404b2831d9e64135386c/executable"]
+        );
+
+        let http = SymbolSource::http("http://localhost:8002/", DirectoryLayout::Debuginfod);
+        assert_eq!(
+            http.candidates(SymbolFileType::ElfCode, &elf_id()),
+            vec![
+                "http://localhost:8002/buildid/f1c3bcc0279865fe3058404b2831d9e64135386c/executable"
+            ]
+        );
+        assert_eq!(http.probe(SymbolFileType::ElfCode, &elf_id()), None);
+    }
+
+    #[test]
+    fn test_probe_local() {
+        let root = std::env::temp_dir().join(format!("symsource-{}", std::process::id()));
+        let path = root.join("crash.pdb/3249D99D0C4049318610F4E4FB0B69361/crash.pdb");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, b"").unwrap();
+
+        let source = SymbolSource::local(&root, DirectoryLayout::Symstore);
+        let probed = source.probe(SymbolFileType::Pdb, &pdb_id());
+        let missing = source.probe(SymbolFileType::Pe, &pdb_id());
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(probed, Some(path));
+        assert_eq!(missing, None);
+    }
+}
@@ -31,6 +31,7 @@ pub enum WasmError {
 pub struct WasmObject<'data> {
     dwarf_sections: Vec<(&'data str, &'data [u8])>,
     funcs: Vec<Symbol<'data>>,
+    num_imported_funcs: u32,
     build_id: Option<&'data [u8]>,
     data: &'data [u8],
     code_offset: u64,
@@ -149,6 +150,46 @@ impl<'data> WasmObject<'data> {
     pub fn code_offset(&self) -> u64 {
         self.code_offset
     }
+
+    /// Resolves a function index and an offset within that function to an address.
+    ///
+    /// Some runtimes report frames in stack traces as the index of the function in the module and
+    /// the offset of the instruction relative to the start of the function body, instead of a byte
+    /// offset in the module. The function index counts imported functions first, as defined by the
+    /// WebAssembly specification.
+    ///
+    /// The returned address can be used to look up symbols and debug information of this object.
+    /// Returns `None` if the index refers to an imported or unknown function, or if the offset
+    /// exceeds the function body.
+    pub fn function_address(&self, function_index: u32, offset: u64) -> Option<u64> {
+        let local_index = function_index.checked_sub(self.num_imported_funcs)?;
+        let function = self.funcs.get(local_index as usize)?;
+
+        if offset >= function.size {
+            return None;
+        }
+
+        Some(function.address + offset)
+    }
+
+    /// Resolves an address to the index of the containing function and the offset within it.
+    ///
+    /// This is the inverse of [`function_address`](Self::function_address).
+    pub fn function_index(&self, address: u64) -> Option<(u32, u64)> {
+        let local_index = match self.funcs.binary_search_by_key(&address, |f| f.address) {
+            Ok(index) => index,
+            Err(0) => return None,
+            Err(next) => next - 1,
+        };
+
+        let function = &self.funcs[local_index];
+        if address >= function.address + function.size {
+            return None;
+        }
+
+        let function_index = self.num_imported_funcs.checked_add(local_index as u32)?;
+        Some((function_index, address - function.address))
+    }
 }
 
 impl fmt::Debug for WasmObject<'_> {
@@ -221,6 +221,7 @@ impl<'data> super::WasmObject<'data> {
         Ok(Self {
             dwarf_sections,
             funcs,
+            num_imported_funcs,
             build_id,
             data,
             code_offset,
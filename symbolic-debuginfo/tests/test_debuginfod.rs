@@ -0,0 +1,70 @@
+use symbolic_common::{ByteView, CodeId};
+use symbolic_debuginfo::debuginfod::{
+    load_object, verify_object, DebuginfodArtifact, DebuginfodErrorKind,
+};
+use symbolic_debuginfo::FileFormat;
+use symbolic_testutils::fixture;
+
+type Error = Box<dyn std::error::Error>;
+
+const BUILD_ID: &str = "f1c3bcc0279865fe3058404b2831d9e64135386c";
+
+#[test]
+fn test_verify_debuginfo() -> Result<(), Error> {
+    let view = ByteView::open(fixture("linux/crash.debug"))?;
+    let build_id = CodeId::new(BUILD_ID.into());
+
+    let object = verify_object(&view, &build_id, DebuginfodArtifact::DebugInfo)?;
+    assert_eq!(object.code_id(), Some(build_id));
+
+    Ok(())
+}
+
+#[test]
+fn test_verify_build_id_mismatch() -> Result<(), Error> {
+    let view = ByteView::open(fixture("linux/crash.debug"))?;
+    let build_id = CodeId::new("0123456789abcdef".into());
+
+    let error = verify_object(&view, &build_id, DebuginfodArtifact::DebugInfo).unwrap_err();
+    assert_eq!(error.kind(), DebuginfodErrorKind::BuildIdMismatch);
+
+    Ok(())
+}
+
+#[test]
+fn test_verify_missing_debug_info() -> Result<(), Error> {
+    let view = ByteView::open(fixture("linux/crash"))?;
+    let build_id = CodeId::new(BUILD_ID.into());
+
+    verify_object(&view, &build_id, DebuginfodArtifact::Executable)?;
+
+    let error = verify_object(&view, &build_id, DebuginfodArtifact::DebugInfo).unwrap_err();
+    assert_eq!(error.kind(), DebuginfodErrorKind::MissingDebugInfo);
+
+    Ok(())
+}
+
+#[test]
+fn test_verify_unexpected_format() -> Result<(), Error> {
+    let view = ByteView::open(fixture("windows/crash.pdb"))?;
+    let build_id = CodeId::new(BUILD_ID.into());
+
+    let error = verify_object(&view, &build_id, DebuginfodArtifact::DebugInfo).unwrap_err();
+    assert_eq!(
+        error.kind(),
+        DebuginfodErrorKind::UnexpectedFormat(FileFormat::Pdb)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_load_object() -> Result<(), Error> {
+    let view = ByteView::open(fixture("linux/crash.debug"))?;
+    let build_id = CodeId::new(BUILD_ID.into());
+
+    let object = load_object(view, &build_id, DebuginfodArtifact::DebugInfo)?;
+    assert!(object.get().has_debug_info());
+
+    Ok(())
+}
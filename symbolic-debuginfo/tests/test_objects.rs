@@ -1,7 +1,18 @@
-use std::{ffi::CString, fmt};
-
-use symbolic_common::ByteView;
-use symbolic_debuginfo::{elf::ElfObject, FileEntry, Function, Object, SymbolMap};
+use std::{borrow::Cow, ffi::CString, fmt};
+
+use symbolic_common::{ByteView, NameNormalizer, PathRemapper};
+use symbolic_debuginfo::{
+    ar::{StaticLibrary, StaticLibraryErrorKind},
+    breakpad::{BreakpadErrorKind, BreakpadObject},
+    dwarf::{FunctionsOptions, OverlapPolicy},
+    elf::ElfObject,
+    macho::MachObject,
+    pe::{PeObject, PeSecurityFlags},
+    wasm::WasmObject,
+    Archive, DynDebugSession, FileEntry, FileFormat, FilePathKind, Function, LimitExceeded,
+    LimitKind, Object, ObjectFeatures, ObjectKind, ObjectOptions, ObjectWriter, SessionLimits,
+    SymbolMap, ValidationIssueKind,
+};
 use symbolic_testutils::fixture;
 
 use similar_asserts::assert_eq;
@@ -104,6 +115,30 @@ fn test_breakpad() -> Result<(), Error> {
     Ok(())
 }
 
+#[test]
+fn test_object_features() -> Result<(), Error> {
+    let features = |name| -> Result<ObjectFeatures, Error> {
+        let view = ByteView::open(fixture(name))?;
+        let features = Object::parse(&view)?.features();
+        Ok(features)
+    };
+
+    assert_eq!(
+        features("windows/crash.sym")?,
+        ObjectFeatures::SYMBOLS | ObjectFeatures::DEBUG_INFO | ObjectFeatures::UNWIND_INFO
+    );
+    assert_eq!(
+        features("linux/crash")?,
+        ObjectFeatures::SYMBOLS | ObjectFeatures::UNWIND_INFO
+    );
+    assert_eq!(
+        features("wasm/simple.wasm")?,
+        ObjectFeatures::SYMBOLS | ObjectFeatures::DEBUG_INFO
+    );
+
+    Ok(())
+}
+
 #[test]
 fn test_breakpad_symbols() -> Result<(), Error> {
     let view = ByteView::open(fixture("windows/crash.sym"))?;
@@ -128,6 +163,36 @@ fn test_breakpad_files() -> Result<(), Error> {
     Ok(())
 }
 
+#[test]
+fn test_breakpad_path_remapper() -> Result<(), Error> {
+    let view = ByteView::open(fixture("windows/crash.sym"))?;
+    let object = Object::parse(&view)?;
+
+    let mut remapper = PathRemapper::new();
+    remapper.add("c:\\projects\\breakpad-tools", "D:\\src");
+
+    let mut session = object.debug_session()?;
+    session.set_path_remapper(Some(remapper));
+
+    let files = session.files().collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(
+        files[2].abs_path_str(),
+        "D:\\src\\deps\\breakpad\\src\\common\\scoped_ptr.h"
+    );
+    assert!(files[0]
+        .abs_path_str()
+        .starts_with("c:\\program files (x86)"));
+
+    let functions = session.functions().collect::<Result<Vec<_>, _>>()?;
+    let remapped = functions
+        .iter()
+        .flat_map(|function| function.lines.iter())
+        .any(|line| line.file.path_str().starts_with("D:\\src\\"));
+    assert!(remapped);
+
+    Ok(())
+}
+
 #[test]
 fn test_breakpad_functions() -> Result<(), Error> {
     let view = ByteView::open(fixture("windows/crash.sym"))?;
@@ -140,6 +205,72 @@ fn test_breakpad_functions() -> Result<(), Error> {
     Ok(())
 }
 
+/// Collects addresses, names and line records of all functions of a Breakpad object.
+fn breakpad_functions(object: &BreakpadObject<'_>) -> Result<Vec<String>, Error> {
+    let session = object.debug_session()?;
+    let mut functions = Vec::new();
+
+    for function in session.functions() {
+        let function = function?;
+        functions.push(format!("{:x} {}", function.address, function.name));
+
+        for line in function.lines.iter() {
+            let path = line.file.path_str();
+            functions.push(format!("  {:x} {} {}", line.address, line.line, path));
+        }
+    }
+
+    Ok(functions)
+}
+
+#[test]
+fn test_breakpad_split_merge() -> Result<(), Error> {
+    let view = ByteView::open(fixture("macos/crash.sym"))?;
+    let object = BreakpadObject::parse(&view)?;
+
+    let split = object.split()?;
+    let symbols = BreakpadObject::parse(&split.symbols)?;
+    let stack = BreakpadObject::parse(&split.stack)?;
+
+    assert_eq!(symbols.debug_id(), object.debug_id());
+    assert!(symbols.has_debug_info());
+    assert!(!symbols.has_unwind_info());
+    assert!(!stack.has_debug_info());
+    assert!(stack.has_unwind_info());
+
+    // Merging the parts in any order, including duplicates, restores all records.
+    let merged = BreakpadObject::merge(vec![&stack, &symbols, &object])?;
+    let merged = BreakpadObject::parse(&merged)?;
+
+    assert_eq!(breakpad_functions(&merged)?, breakpad_functions(&object)?);
+    assert_eq!(
+        merged.symbols().collect::<Vec<_>>(),
+        object.symbols().collect::<Vec<_>>()
+    );
+    assert_eq!(
+        merged.cfi_records()?.iter().collect::<Vec<_>>(),
+        object.cfi_records()?.iter().collect::<Vec<_>>()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_breakpad_merge_mismatch() -> Result<(), Error> {
+    let macos = ByteView::open(fixture("macos/crash.sym"))?;
+    let windows = ByteView::open(fixture("windows/crash.sym"))?;
+
+    let objects = [
+        BreakpadObject::parse(&macos)?,
+        BreakpadObject::parse(&windows)?,
+    ];
+
+    let error = BreakpadObject::merge(&objects).unwrap_err();
+    assert_eq!(error.kind(), BreakpadErrorKind::DebugIdMismatch);
+
+    Ok(())
+}
+
 #[test]
 fn test_elf_executable() -> Result<(), Error> {
     let view = ByteView::open(fixture("linux/crash"))?;
@@ -209,6 +340,34 @@ fn test_elf_symbols() -> Result<(), Error> {
     Ok(())
 }
 
+#[test]
+fn test_elf_symbols_sized() -> Result<(), Error> {
+    let view = ByteView::open(fixture("linux/crash.debug"))?;
+    let object = Object::parse(&view)?;
+
+    let symbols = object.symbol_map();
+    assert!(!symbols.sections().is_empty());
+
+    let sized = symbols.iter_sized().collect::<Vec<_>>();
+    assert_eq!(sized.len(), symbols.len());
+
+    for symbol in &sized {
+        let section = symbols
+            .sections()
+            .iter()
+            .find(|section| section.contains(&symbol.address))
+            .expect("symbol outside of code sections");
+
+        assert!(symbol.size > 0, "{:?} has no size", symbol);
+        assert!(symbol.address + symbol.size <= section.end);
+    }
+
+    let named = symbols.iter_named().collect::<Vec<_>>();
+    assert!(named.iter().any(|symbol| symbol.name.as_str() == "main"));
+
+    Ok(())
+}
+
 #[test]
 fn test_elf_files() -> Result<(), Error> {
     let view = ByteView::open(fixture("linux/crash.debug"))?;
@@ -223,202 +382,1375 @@ fn test_elf_files() -> Result<(), Error> {
 }
 
 #[test]
-fn test_elf_functions() -> Result<(), Error> {
+fn test_elf_file_path_kinds() -> Result<(), Error> {
     let view = ByteView::open(fixture("linux/crash.debug"))?;
     let object = Object::parse(&view)?;
 
     let session = object.debug_session()?;
-    let functions = session.functions().collect::<Result<Vec<_>, _>>()?;
-    insta::assert_debug_snapshot!("elf_functions", FunctionsDebug(&functions[..10], 0));
+    let files = session.files().collect::<Result<Vec<_>, _>>()?;
+
+    let kinds = files[..3]
+        .iter()
+        .map(|file| (file.path_str(), file.path_kind))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        kinds,
+        [
+            (
+                "../linux/main.cpp".to_owned(),
+                FilePathKind::CompDirRelative
+            ),
+            (
+                "/usr/include/x86_64-linux-gnu/bits/stdio2.h".to_owned(),
+                FilePathKind::DirectoryRelative
+            ),
+            (
+                "../deps/breakpad/src/client/linux/handler/minidump_descriptor.h".to_owned(),
+                FilePathKind::CompDirRelative
+            ),
+        ]
+    );
 
     Ok(())
 }
 
-fn elf_debug_crc() -> Result<u32, Error> {
-    Ok(u32::from_str_radix(
-        std::fs::read_to_string(fixture("linux/elf_debuglink/gen/debug_info.txt.crc"))?.trim(),
-        16,
-    )?)
-}
+#[test]
+fn test_elf_path_remapper() -> Result<(), Error> {
+    let view = ByteView::open(fixture("linux/crash.debug"))?;
+    let object = Object::parse(&view)?;
 
-fn check_debug_info(filename: &'static str, debug_info: &'static str) -> Result<(), Error> {
-    let view = ByteView::open(fixture("linux/elf_debuglink/gen/".to_owned() + filename))?;
+    let mut remapper = PathRemapper::new();
+    remapper.add("/work", "/src");
 
-    let object = ElfObject::parse(&view)?;
+    let mut session = object.debug_session()?;
+    session.set_path_remapper(Some(remapper));
 
-    let debug_link = object
-        .debug_link()
-        .map_err(|err| err.kind)?
-        .expect("debug link not found");
+    let files = session.files().collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(files.len(), 1012);
+    assert_eq!(files[0].abs_path_str(), "/src/linux/main.cpp");
+    assert_eq!(
+        files[1].abs_path_str(),
+        "/usr/include/x86_64-linux-gnu/bits/stdio2.h"
+    );
 
-    assert_eq!(debug_link.filename(), CString::new(debug_info)?.as_c_str(),);
-    assert_eq!(debug_link.crc(), elf_debug_crc()?);
+    let functions = session.functions().collect::<Result<Vec<_>, _>>()?;
+    let function = &functions[0];
+    let line = function.lines.iter().next().unwrap();
+    let file = FileEntry {
+        compilation_dir: function.compilation_dir.clone(),
+        info: line.file,
+    };
+    assert_eq!(file.abs_path_str(), "/src/linux/main.cpp");
 
     Ok(())
 }
 
 #[test]
-fn test_elf_debug_link() -> Result<(), Error> {
-    check_debug_info("elf_with_debuglink", "debug_info.txt")
+fn test_elf_collect_diagnostics() -> Result<(), Error> {
+    let view = ByteView::open(fixture("linux/crash.debug"))?;
+    let object = Object::parse(&view)?;
+
+    let expected = object.debug_session()?.functions().count();
+
+    let mut session = object.debug_session()?;
+    session.set_collect_diagnostics(true);
+    let functions = session.functions().collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(functions.len(), expected);
+    assert_eq!(session.take_diagnostics(), Vec::new());
+
+    Ok(())
 }
 
 #[test]
-fn test_elf_debug_link_none() -> Result<(), Error> {
-    let view = ByteView::open(fixture("linux/elf_debuglink/gen/elf_without_debuglink"))?;
+fn test_elf_limit_functions() -> Result<(), Error> {
+    let view = ByteView::open(fixture("linux/crash.debug"))?;
+    let object = Object::parse(&view)?;
 
-    let object = ElfObject::parse(&view)?;
+    let mut session = object.debug_session()?;
+    session.set_limits(SessionLimits {
+        max_functions: Some(1),
+        ..Default::default()
+    });
 
-    let debug_link = object.debug_link().map_err(|err| err.kind)?;
-    assert!(debug_link.is_none(), "debug link unexpectedly found");
+    let mut functions = session.functions();
+    assert!(functions.next().unwrap().is_ok());
+
+    let error = functions.next().unwrap().unwrap_err();
+    let exceeded = LimitExceeded::find(&error).expect("limit error");
+    assert_eq!(exceeded.kind(), LimitKind::Functions);
+    assert_eq!(exceeded.limit(), 1);
+
+    assert!(functions.next().is_none());
 
     Ok(())
 }
 
 #[test]
-// Test that the size of the debug_info filename doesn't influence the result.
-fn test_elf_debug_link_padding() -> Result<(), Error> {
-    check_debug_info("elf_with1_debuglink", "debug_info1.txt")?;
-    check_debug_info("elf_with12_debuglink", "debug_info12.txt")?;
-    check_debug_info("elf_with123_debuglink", "debug_info123.txt")
-}
+fn test_elf_limit_lines_per_function() -> Result<(), Error> {
+    let view = ByteView::open(fixture("linux/crash.debug"))?;
+    let object = ElfObject::parse(&view)?;
 
-#[test]
-// Test with a compressed gnu_debuglink section. This exerts the "Owned" path of the code,
-// while without compression the "Borrowed" is exerted.
-fn test_elf_debug_link_compressed() -> Result<(), Error> {
-    check_debug_info("elf_with_compressed_debuglink", "debug_info.txt")
+    let mut session = object.debug_session()?;
+    session.set_limits(SessionLimits {
+        max_lines_per_function: Some(0),
+        ..Default::default()
+    });
+
+    let error = session
+        .functions()
+        .find_map(Result::err)
+        .expect("limit error");
+    let exceeded = LimitExceeded::find(&error).expect("limit error");
+    assert_eq!(exceeded.kind(), LimitKind::LinesPerFunction);
+
+    Ok(())
 }
 
 #[test]
-fn test_mach_executable() -> Result<(), Error> {
-    let view = ByteView::open(fixture("macos/crash"))?;
-    let object = Object::parse(&view)?;
+fn test_elf_limit_total_bytes() -> Result<(), Error> {
+    let view = ByteView::open(fixture("linux/crash.debug"))?;
+    let object = ElfObject::parse(&view)?;
 
-    insta::assert_debug_snapshot!(object, @r###"
-   ⋮MachO(
-   ⋮    MachObject {
-   ⋮        code_id: Some(
-   ⋮            CodeId(67e9247c814e392ba027dbde6748fcbf),
-   ⋮        ),
-   ⋮        debug_id: DebugId {
-   ⋮            uuid: "67e9247c-814e-392b-a027-dbde6748fcbf",
-   ⋮            appendix: 0,
-   ⋮        },
-   ⋮        arch: Amd64,
-   ⋮        kind: Executable,
-   ⋮        load_address: 0x100000000,
-   ⋮        has_symbols: true,
-   ⋮        has_debug_info: false,
-   ⋮        has_unwind_info: true,
-   ⋮        is_malformed: false,
-   ⋮    },
-   ⋮)
-    "###);
+    let mut session = object.debug_session()?;
+    session.set_limits(SessionLimits {
+        max_total_bytes: Some(1024),
+        ..Default::default()
+    });
+
+    let results = session.functions().collect::<Vec<_>>();
+    let error = results.last().unwrap().as_ref().unwrap_err();
+    let exceeded = LimitExceeded::find(error).expect("limit error");
+    assert_eq!(exceeded.kind(), LimitKind::TotalBytes);
+    assert_eq!(exceeded.limit(), 1024);
+
+    // Unlimited sessions still yield every function.
+    let unlimited = object.debug_session()?.functions().count();
+    assert!(results.len() < unlimited);
 
     Ok(())
 }
 
 #[test]
-fn test_mach_dsym() -> Result<(), Error> {
-    let view = ByteView::open(fixture("macos/crash.dSYM/Contents/Resources/DWARF/crash"))?;
-    let object = Object::parse(&view)?;
+fn test_breakpad_limit_functions() -> Result<(), Error> {
+    let view = ByteView::open(fixture("windows/crash.sym"))?;
+    let object = BreakpadObject::parse(&view)?;
 
-    insta::assert_debug_snapshot!(object, @r###"
-   ⋮MachO(
-   ⋮    MachObject {
-   ⋮        code_id: Some(
-   ⋮            CodeId(67e9247c814e392ba027dbde6748fcbf),
-   ⋮        ),
-   ⋮        debug_id: DebugId {
-   ⋮            uuid: "67e9247c-814e-392b-a027-dbde6748fcbf",
-   ⋮            appendix: 0,
-   ⋮        },
-   ⋮        arch: Amd64,
-   ⋮        kind: Debug,
-   ⋮        load_address: 0x100000000,
-   ⋮        has_symbols: true,
-   ⋮        has_debug_info: true,
-   ⋮        has_unwind_info: false,
-   ⋮        is_malformed: false,
-   ⋮    },
-   ⋮)
-    "###);
+    let mut session = object.debug_session()?;
+    session.set_limits(SessionLimits {
+        max_functions: Some(3),
+        ..Default::default()
+    });
+
+    let results = session.functions().collect::<Vec<_>>();
+    assert_eq!(results.len(), 4);
+    assert!(results[..3].iter().all(Result::is_ok));
+
+    let error = results[3].as_ref().unwrap_err();
+    assert_eq!(error.kind(), BreakpadErrorKind::LimitExceeded);
+    assert_eq!(
+        LimitExceeded::find(error).map(LimitExceeded::kind),
+        Some(LimitKind::Functions)
+    );
 
     Ok(())
 }
 
 #[test]
-fn test_mach_symbols() -> Result<(), Error> {
-    let view = ByteView::open(fixture("macos/crash"))?;
+fn test_validate_clean() -> Result<(), Error> {
+    let view = ByteView::open(fixture("linux/crash.debug"))?;
     let object = Object::parse(&view)?;
 
-    let symbols = object.symbol_map();
-    insta::assert_debug_snapshot!("mach_symbols", SymbolsDebug(&symbols));
+    let report = object.validate()?;
+    assert!(report.functions > 0);
+    assert!(report.is_valid(), "{:?}", report.issues);
 
     Ok(())
 }
 
 #[test]
-fn test_mach_files() -> Result<(), Error> {
-    let view = ByteView::open(fixture("macos/crash.dSYM/Contents/Resources/DWARF/crash"))?;
-    let object = Object::parse(&view)?;
+fn test_validate_issues() -> Result<(), Error> {
+    let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash\n\
+        FILE 0 /src/foo.c\n\
+        FUNC 1000 20 0 foo\n\
+        1000 10 1 0\n\
+        1030 10 2 0\n\
+        FUNC 1010 20 0 bar\n\
+        1010 10 3 7\n\
+        PUBLIC 1000 0 foo\n\
+        PUBLIC 1008 0 foo_alias\n";
+    let object = Object::parse(data)?;
+
+    let report = object.validate()?;
+    assert_eq!(report.functions, 2);
+
+    let kinds = report
+        .issues
+        .iter()
+        .map(|issue| (issue.kind, issue.address))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        [
+            (ValidationIssueKind::OverlappingFunctions, Some(0x1010)),
+            (ValidationIssueKind::LineOutsideFunction, Some(0x1030)),
+            (ValidationIssueKind::MissingFile, Some(0x1010)),
+            (ValidationIssueKind::SymbolMismatch, Some(0x1000)),
+        ]
+    );
 
-    let session = object.debug_session()?;
-    let files = session.files().collect::<Result<Vec<_>, _>>()?;
-    assert_eq!(files.len(), 554);
-    insta::assert_debug_snapshot!("mach_files", FilesDebug(&files[..10]));
+    let overlap = &report.issues[0];
+    assert_eq!(
+        overlap.to_string(),
+        "overlapping functions at 0x1010: `bar` overlaps `foo` at 0x1000"
+    );
 
     Ok(())
 }
 
 #[test]
-fn test_mach_functions() -> Result<(), Error> {
-    let view = ByteView::open(fixture("macos/crash.dSYM/Contents/Resources/DWARF/crash"))?;
+fn test_elf_intern_strings() -> Result<(), Error> {
+    let view = ByteView::open(fixture("linux/crash.debug"))?;
     let object = Object::parse(&view)?;
 
-    let session = object.debug_session()?;
+    let mut remapper = PathRemapper::new();
+    remapper.add("/work", "/src");
+
+    let mut session = object.debug_session()?;
+    session.set_path_remapper(Some(remapper.clone()));
+    let expected = session.functions().collect::<Result<Vec<_>, _>>()?;
+
+    let mut session = object.debug_session()?;
+    session.set_path_remapper(Some(remapper));
+    session.set_intern_strings(true);
     let functions = session.functions().collect::<Result<Vec<_>, _>>()?;
-    insta::assert_debug_snapshot!("mach_functions", FunctionsDebug(&functions[..10], 0));
+    assert_eq!(format!("{functions:?}"), format!("{expected:?}"));
+
+    // The remapped compilation directory is shared by all functions of a unit.
+    let first = &functions[0];
+    assert_eq!(first.compilation_dir, b"/src/linux".as_slice());
+    assert!(matches!(first.compilation_dir, Cow::Borrowed(_)));
+    let other = functions
+        .iter()
+        .find(|f| f.compilation_dir == first.compilation_dir && f.address != first.address)
+        .unwrap();
+    assert_eq!(
+        first.compilation_dir.as_ptr(),
+        other.compilation_dir.as_ptr()
+    );
 
     Ok(())
 }
 
 #[test]
-fn test_pe_32() -> Result<(), Error> {
-    let view = ByteView::open(fixture("windows/crash.exe"))?;
+fn test_elf_functions() -> Result<(), Error> {
+    let view = ByteView::open(fixture("linux/crash.debug"))?;
     let object = Object::parse(&view)?;
 
-    insta::assert_debug_snapshot!(object, @r###"
-   ⋮Pe(
-   ⋮    PeObject {
-   ⋮        code_id: Some(
-   ⋮            CodeId(5ab380779000),
-   ⋮        ),
-   ⋮        debug_id: DebugId {
-   ⋮            uuid: "3249d99d-0c40-4931-8610-f4e4fb0b6936",
-   ⋮            appendix: 1,
-   ⋮        },
-   ⋮        debug_file_name: Some(
-   ⋮            "C:\\projects\\breakpad-tools\\windows\\Release\\crash.pdb",
-   ⋮        ),
-   ⋮        arch: X86,
-   ⋮        kind: Executable,
-   ⋮        load_address: 0x400000,
-   ⋮        has_symbols: false,
-   ⋮        has_debug_info: false,
-   ⋮        has_unwind_info: false,
-   ⋮        is_malformed: false,
-   ⋮    },
-   ⋮)
-    "###);
+    let session = object.debug_session()?;
+    let functions = session.functions().collect::<Result<Vec<_>, _>>()?;
+    insta::assert_debug_snapshot!("elf_functions", FunctionsDebug(&functions[..10], 0));
 
     Ok(())
 }
 
 #[test]
-fn test_pe_64() -> Result<(), Error> {
-    let view = ByteView::open(fixture("windows/CrashWithException.exe"))?;
-    let object = Object::parse(&view)?;
+fn test_elf_debug_session_at() -> Result<(), Error> {
+    let view = ByteView::open(fixture("linux/crash.debug"))?;
+    let object = ElfObject::parse(&view)?;
+    assert_eq!(object.load_bias(), 0x400000);
+
+    let runtime_base = 0x7f12_3400_0000;
+    let expected = object.debug_session()?;
+    let session = object.debug_session_at(runtime_base)?;
+
+    let mut count = 0;
+    for (expected, function) in expected.functions().zip(session.functions()) {
+        let (expected, function) = (expected?, function?);
+        assert_eq!(function.address, expected.address + runtime_base);
+        assert_eq!(function.name, expected.name);
+
+        for (expected, line) in expected.lines.iter().zip(function.lines.iter()) {
+            assert_eq!(line.address, expected.address + runtime_base);
+        }
+
+        count += 1;
+    }
+    assert!(count > 0);
+
+    Ok(())
+}
+
+#[test]
+fn test_elf_artificial_functions() -> Result<(), Error> {
+    let view = ByteView::open(fixture("linux/crash.debug"))?;
+    let object = Object::parse(&view)?;
+
+    let session = object.debug_session()?;
+    let functions = session.functions().collect::<Result<Vec<_>, _>>()?;
+
+    // The implicitly defined destructor is generated by the compiler.
+    let destructor = functions
+        .iter()
+        .find(|f| f.name.as_str() == "_ZN15google_breakpad18MinidumpDescriptorD1Ev")
+        .expect("destructor");
+    assert!(destructor.artificial);
+
+    let main = functions
+        .iter()
+        .find(|f| f.name.as_str() == "main")
+        .expect("main");
+    assert!(!main.artificial);
+
+    // Fortified libc wrappers are declared with `__attribute__((artificial))`.
+    let printf = functions
+        .iter()
+        .flat_map(|f| &f.inlinees)
+        .find(|f| f.name.as_str() == "printf")
+        .expect("inlined printf");
+    assert!(printf.artificial);
+
+    Ok(())
+}
+
+#[test]
+fn test_elf_plt_symbols() -> Result<(), Error> {
+    let view = ByteView::open(fixture("linux/crash"))?;
+    let object = ElfObject::parse(&view)?;
+
+    let plt = object.plt_symbols();
+    assert_eq!(plt.len(), 54);
+    assert_eq!(plt[0].name(), Some("_ZSt20__throw_length_errorPKc@plt"));
+    assert_eq!(plt[0].address, 0x1590);
+    assert_eq!(plt[0].size, 0x10);
+    assert_eq!(plt[1].name(), Some("memset@plt"));
+    assert_eq!(plt[1].address, 0x15a0);
+
+    let symbols = object.symbol_map();
+    let symbol = symbols.lookup(0x15a4).expect("PLT stub in symbol map");
+    assert_eq!(symbol.name(), Some("memset@plt"));
+
+    Ok(())
+}
+
+#[test]
+fn test_elf_functions_sorted() -> Result<(), Error> {
+    let view = ByteView::open(fixture("linux/crash.debug"))?;
+    let object = ElfObject::parse(&view)?;
+
+    let session = object.debug_session()?;
+    let sorted = session.functions_sorted().collect::<Result<Vec<_>, _>>()?;
+    assert!(sorted.windows(2).all(|w| w[0].address <= w[1].address));
+
+    let mut functions = session.functions().collect::<Result<Vec<_>, _>>()?;
+    functions.sort_by_key(|f| f.address);
+    assert_eq!(sorted.len(), functions.len());
+
+    for (a, b) in sorted.iter().zip(&functions) {
+        assert_eq!((a.address, a.name.as_str()), (b.address, b.name.as_str()));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_elf_lookup() -> Result<(), Error> {
+    let view = ByteView::open(fixture("linux/crash.debug"))?;
+    let object = ElfObject::parse(&view)?;
+
+    let session = object.debug_session()?;
+    let functions = session.functions().collect::<Result<Vec<_>, _>>()?;
+    assert!(!functions.is_empty());
+
+    for function in &functions {
+        for address in [function.address, function.end_address() - 1] {
+            let matches = session.lookup(address)?;
+            assert!(matches
+                .iter()
+                .any(|m| m.address == function.address && m.name == function.name));
+            assert!(matches
+                .iter()
+                .all(|m| m.address <= address && address < m.end_address()));
+        }
+    }
+
+    assert!(session.lookup(0)?.is_empty());
+    assert!(session.lookup(u64::MAX)?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_elf_lookup_load_address() -> Result<(), Error> {
+    let view = ByteView::open(fixture("linux/crash.debug"))?;
+    let object = ElfObject::parse(&view)?;
+
+    let mut session = object.debug_session()?;
+    let function = session.functions().next().unwrap()?.into_owned();
+
+    session.set_load_address(0x10000);
+    let matches = session.lookup(function.address + 0x10000)?;
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].address, function.address + 0x10000);
+    assert_eq!(matches[0].name, function.name);
+
+    assert!(session.lookup(function.address)?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_elf_functions_for_ranges() -> Result<(), Error> {
+    let view = ByteView::open(fixture("linux/crash.debug"))?;
+    let object = ElfObject::parse(&view)?;
+
+    let mut session = object.debug_session()?;
+    let total = session.functions().count();
+    let function = session.functions().last().unwrap()?.into_owned();
+
+    session.set_load_address(0x10000);
+    let start = function.address + 0x10000;
+    let functions = session
+        .functions_for_ranges(std::slice::from_ref(&(start..start + 1)))
+        .collect::<Result<Vec<_>, _>>()?;
+    assert!(functions.len() <= total);
+    assert!(functions
+        .iter()
+        .any(|f| f.address == start && f.name == function.name));
+
+    let empty = session
+        .functions_for_ranges(std::slice::from_ref(&(0..0x10000)))
+        .count();
+    assert!(empty < total);
+
+    Ok(())
+}
+
+#[test]
+fn test_elf_make_lookup() -> Result<(), Error> {
+    let view = ByteView::open(fixture("linux/crash.debug"))?;
+    let object = Object::parse(&view)?;
+
+    let lookup = object.make_lookup()?;
+    assert!(lookup.has_line_info());
+
+    let session = object.debug_session()?;
+    for function in session.functions() {
+        let function = function?;
+        let line = match function.lines.first() {
+            Some(line) => line,
+            None => continue,
+        };
+
+        let frames = lookup.lookup(line.address);
+        let outer = frames.last().expect("function frame");
+        assert_eq!(outer.name.as_str(), function.name.as_str());
+        assert_eq!(outer.function_address, function.address);
+        assert!(!outer.inline);
+        assert!(frames[..frames.len() - 1].iter().all(|frame| frame.inline));
+
+        let innermost = &frames[0];
+        assert!(innermost.file.is_some());
+        assert_ne!(innermost.line, 0);
+    }
+
+    assert!(lookup.lookup(0).is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_elf_make_lookup_symbols() -> Result<(), Error> {
+    let view = ByteView::open(fixture("linux/crash"))?;
+    let object = Object::parse(&view)?;
+
+    let lookup = object.make_lookup()?;
+    assert!(!lookup.has_line_info());
+    assert!(lookup.symbol_count() > 0);
+
+    let symbol = object
+        .symbol_map()
+        .into_iter()
+        .find(|s| s.name.is_some())
+        .unwrap()
+        .into_owned();
+
+    // The index remains usable after the object has been dropped.
+    drop(object);
+    drop(view);
+
+    let frames = lookup.lookup(symbol.address);
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0].name.as_str(), symbol.name().unwrap());
+    assert!(frames[0].file.is_none());
+    assert_eq!(frames[0].line, 0);
+
+    Ok(())
+}
+
+#[test]
+fn test_elf_cached_function_names() -> Result<(), Error> {
+    fn collect_names(functions: &[Function<'_>], names: &mut Vec<String>) {
+        for function in functions {
+            names.push(function.name.to_string());
+            collect_names(&function.inlinees, names);
+        }
+    }
+
+    let view = ByteView::open(fixture("linux/crash.debug"))?;
+    let object = ElfObject::parse(&view)?;
+
+    let session = object.debug_session()?;
+    let functions = session.functions().collect::<Result<Vec<_>, _>>()?;
+    let mut expected = Vec::new();
+    collect_names(&functions, &mut expected);
+    assert!(functions.iter().any(|f| !f.inlinees.is_empty()));
+
+    let mut session = object.debug_session()?;
+    session.set_cache_function_names(true);
+    let functions = session.functions().collect::<Result<Vec<_>, _>>()?;
+    let mut names = Vec::new();
+    collect_names(&functions, &mut names);
+
+    assert_eq!(names, expected);
+
+    Ok(())
+}
+
+#[test]
+fn test_elf_functions_options() -> Result<(), Error> {
+    let view = ByteView::open(fixture("linux/crash.debug"))?;
+    let object = ElfObject::parse(&view)?;
+
+    let session = object.debug_session()?;
+    let deduped = session.functions().collect::<Result<Vec<_>, _>>()?;
+
+    let mut session = object.debug_session()?;
+    session.set_functions_options(FunctionsOptions {
+        keep_zero_size: true,
+        dedupe_overlaps: OverlapPolicy::KeepAll,
+    });
+    let all = session.functions().collect::<Result<Vec<_>, _>>()?;
+    assert!(all.len() > deduped.len());
+
+    let mut ranges = std::collections::BTreeSet::new();
+    assert!(deduped.iter().all(|f| ranges.insert((f.address, f.size))));
+    assert!(all.iter().all(|f| ranges.contains(&(f.address, f.size))));
+
+    Ok(())
+}
+
+#[test]
+fn test_elf_normalized_function_names() -> Result<(), Error> {
+    let view = ByteView::open(fixture("linux/crash.debug"))?;
+    let object = ElfObject::parse(&view)?;
+
+    let is_clone = |f: &Function<'_>| {
+        let name = f.name.as_str();
+        name.contains(".constprop.") || name.contains(".isra.") || name.contains(".part.")
+    };
+
+    let session = object.debug_session()?;
+    let functions = session.functions().collect::<Result<Vec<_>, _>>()?;
+    assert!(functions.iter().any(is_clone));
+
+    let mut session = object.debug_session()?;
+    session.set_name_normalizer(Some(NameNormalizer::new()));
+    let functions = session.functions().collect::<Result<Vec<_>, _>>()?;
+    assert!(!functions.iter().any(is_clone));
+
+    assert!(functions
+        .iter()
+        .any(|f| f.name.as_str() == "_ZN15google_breakpad13PageAllocator7FreeAllEv"));
+
+    Ok(())
+}
+
+#[test]
+fn test_elf_parse_without_symbols() -> Result<(), Error> {
+    let view = ByteView::open(fixture("linux/crash"))?;
+    let full = Object::parse(&view)?;
+    let object = Object::parse_with(&view, ObjectOptions::new().symbols(false).notes(false))?;
+
+    assert_eq!(object.debug_id(), full.debug_id());
+    assert_eq!(object.code_id(), full.code_id());
+    assert_eq!(object.arch(), full.arch());
+    assert!(!object.has_symbols());
+    assert_eq!(object.symbols().count(), 0);
+    assert!(!object.is_malformed());
+
+    Ok(())
+}
+
+#[test]
+fn test_elf_max_section_size() -> Result<(), Error> {
+    let view = ByteView::open(fixture("linux/crash.debug"))?;
+    let options = ObjectOptions::new().max_section_size(Some(16));
+    let object = Object::parse_with(&view, options)?;
+
+    assert!(!object.has_symbols());
+    let session = object.debug_session()?;
+    assert_eq!(session.functions().count(), 0);
+
+    Ok(())
+}
+
+fn elf_debug_crc() -> Result<u32, Error> {
+    Ok(u32::from_str_radix(
+        std::fs::read_to_string(fixture("linux/elf_debuglink/gen/debug_info.txt.crc"))?.trim(),
+        16,
+    )?)
+}
+
+fn check_debug_info(filename: &'static str, debug_info: &'static str) -> Result<(), Error> {
+    let view = ByteView::open(fixture("linux/elf_debuglink/gen/".to_owned() + filename))?;
+
+    let object = ElfObject::parse(&view)?;
+
+    let debug_link = object
+        .debug_link()
+        .map_err(|err| err.kind)?
+        .expect("debug link not found");
+
+    assert_eq!(debug_link.filename(), CString::new(debug_info)?.as_c_str(),);
+    assert_eq!(debug_link.crc(), elf_debug_crc()?);
+
+    Ok(())
+}
+
+#[test]
+fn test_elf_call_sites() -> Result<(), Error> {
+    let view = ByteView::open(fixture("linux/crash.debug"))?;
+    let object = Object::parse(&view)?;
+    let session = object.debug_session()?;
+
+    let functions = session.functions().collect::<Result<Vec<_>, _>>()?;
+    let function = |name| {
+        functions
+            .iter()
+            .find(|f| f.name.as_str().contains(name))
+            .unwrap()
+    };
+
+    let thread_entry = function("ExceptionHandler11ThreadEntry");
+    let do_dump = function("ExceptionHandler6DoDump");
+    let call_site = thread_entry
+        .call_sites
+        .iter()
+        .find(|c| c.target == Some(do_dump.address))
+        .unwrap();
+    assert_eq!(call_site.return_address, 0x3055);
+    assert!(!call_site.tail_call);
+
+    let handle_signal = function("ExceptionHandler12HandleSignal");
+    let generate_dump = function("ExceptionHandler12GenerateDump");
+    let call_site = handle_signal
+        .call_sites
+        .iter()
+        .find(|c| c.target == Some(generate_dump.address))
+        .unwrap();
+    assert!(call_site.tail_call);
+
+    Ok(())
+}
+
+#[test]
+fn test_breakpad_outlined_functions() -> Result<(), Error> {
+    let data = b"MODULE mac arm64 67E9247C814E392BA027DBDE6748FCBF0 crash\n\
+        FUNC 1000 10 0 main\n\
+        FUNC 1010 8 0 OUTLINED_FUNCTION_0\n";
+    let object = Object::parse(data)?;
+    let session = object.debug_session()?;
+
+    let functions = session.functions().collect::<Result<Vec<_>, _>>()?;
+    assert!(!functions[0].artificial);
+    assert!(functions[1].is_outlined());
+    assert!(functions[1].artificial);
+
+    Ok(())
+}
+
+#[test]
+fn test_elf_debug_link() -> Result<(), Error> {
+    check_debug_info("elf_with_debuglink", "debug_info.txt")
+}
+
+#[test]
+fn test_elf_debug_link_none() -> Result<(), Error> {
+    let view = ByteView::open(fixture("linux/elf_debuglink/gen/elf_without_debuglink"))?;
+
+    let object = ElfObject::parse(&view)?;
+
+    let debug_link = object.debug_link().map_err(|err| err.kind)?;
+    assert!(debug_link.is_none(), "debug link unexpectedly found");
+
+    Ok(())
+}
+
+#[test]
+// Test that the size of the debug_info filename doesn't influence the result.
+fn test_elf_debug_link_padding() -> Result<(), Error> {
+    check_debug_info("elf_with1_debuglink", "debug_info1.txt")?;
+    check_debug_info("elf_with12_debuglink", "debug_info12.txt")?;
+    check_debug_info("elf_with123_debuglink", "debug_info123.txt")
+}
+
+#[test]
+// Test with a compressed gnu_debuglink section. This exerts the "Owned" path of the code,
+// while without compression the "Borrowed" is exerted.
+fn test_elf_debug_link_compressed() -> Result<(), Error> {
+    check_debug_info("elf_with_compressed_debuglink", "debug_info.txt")
+}
+
+#[test]
+fn test_elf_entry_point() -> Result<(), Error> {
+    let view = ByteView::open(fixture("linux/crash"))?;
+    let object = Object::parse(&view)?;
+    let symbols = object.symbol_map();
+    let name = |addr| symbols.lookup_exact(addr).and_then(|s| s.name());
+
+    let entry_point = object.entry_point().unwrap();
+    assert_eq!(name(entry_point), Some("_start"));
+
+    let initializers = object.initializers();
+    assert_eq!(initializers.len(), 1);
+    assert_eq!(name(initializers[0]), Some("frame_dummy"));
+
+    let terminators = object.terminators();
+    assert_eq!(terminators.len(), 1);
+    assert_eq!(name(terminators[0]), Some("__do_global_dtors_aux"));
+
+    Ok(())
+}
+
+#[test]
+fn test_elf_imports() -> Result<(), Error> {
+    let view = ByteView::open(fixture("linux/crash"))?;
+    let object = Object::parse(&view)?;
+
+    assert!(object.exports()?.is_empty());
+
+    let imports = object.imports()?;
+    let memset = imports.iter().find(|i| i.name == "memset").unwrap();
+    assert_eq!(memset.library, None);
+    assert!(memset.address.is_some());
+
+    Ok(())
+}
+
+#[test]
+fn test_elf_strip_debug() -> Result<(), Error> {
+    let view = ByteView::open(fixture("linux/crash.debug"))?;
+    let object = Object::parse(&view)?;
+    assert!(object.has_debug_info());
+
+    let mut writer = ObjectWriter::new(Vec::new());
+    writer.write_stripped(&object)?;
+    let data = writer.into_inner();
+    let stripped = Object::parse(&data)?;
+
+    assert!(!stripped.has_debug_info());
+    assert_eq!(stripped.debug_id(), object.debug_id());
+    assert_eq!(stripped.code_id(), object.code_id());
+    assert_eq!(stripped.symbol_map().len(), object.symbol_map().len());
+
+    Ok(())
+}
+
+#[test]
+fn test_elf_debug_companion() -> Result<(), Error> {
+    let view = ByteView::open(fixture("linux/crash"))?;
+    let object = Object::parse(&view)?;
+
+    let mut writer = ObjectWriter::new(Vec::new());
+    writer.write_debug_companion(&object)?;
+    let data = writer.into_inner();
+    let companion = Object::parse(&data)?;
+
+    assert_eq!(companion.debug_id(), object.debug_id());
+    assert_eq!(companion.code_id(), object.code_id());
+    assert_eq!(companion.load_address(), object.load_address());
+    assert_eq!(companion.has_debug_info(), object.has_debug_info());
+
+    // PLT stubs are synthesized from relocations, which are not retained.
+    assert_eq!(companion.symbols().count(), object.symbols().count());
+    assert!(!companion.has_unwind_info());
+
+    Ok(())
+}
+
+#[test]
+fn test_object_writer_unsupported() -> Result<(), Error> {
+    let view = ByteView::open(fixture("windows/crash.sym"))?;
+    let object = Object::parse(&view)?;
+
+    let mut writer = ObjectWriter::new(Vec::new());
+    assert!(writer.write_stripped(&object).is_err());
+    assert!(writer.into_inner().is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_mach_executable() -> Result<(), Error> {
+    let view = ByteView::open(fixture("macos/crash"))?;
+    let object = Object::parse(&view)?;
+
+    insta::assert_debug_snapshot!(object, @r###"
+   ⋮MachO(
+   ⋮    MachObject {
+   ⋮        code_id: Some(
+   ⋮            CodeId(67e9247c814e392ba027dbde6748fcbf),
+   ⋮        ),
+   ⋮        debug_id: DebugId {
+   ⋮            uuid: "67e9247c-814e-392b-a027-dbde6748fcbf",
+   ⋮            appendix: 0,
+   ⋮        },
+   ⋮        arch: Amd64,
+   ⋮        kind: Executable,
+   ⋮        load_address: 0x100000000,
+   ⋮        has_symbols: true,
+   ⋮        has_debug_info: false,
+   ⋮        has_unwind_info: true,
+   ⋮        is_malformed: false,
+   ⋮    },
+   ⋮)
+    "###);
+
+    Ok(())
+}
+
+#[test]
+fn test_mach_exports_imports() -> Result<(), Error> {
+    let view = ByteView::open(fixture("macos/crash"))?;
+    let object = Object::parse(&view)?;
+
+    let exports = object.exports()?;
+    let export = exports
+        .iter()
+        .find(|e| e.name == "_ZN15google_breakpad18MinidumpFileWriterC2Ev")
+        .unwrap();
+    assert_eq!(export.address, 0xd20);
+
+    let imports = object.imports()?;
+    let import = imports
+        .iter()
+        .find(|i| i.name == "__CFConstantStringClassReference")
+        .unwrap();
+    assert_eq!(
+        import.library.as_deref(),
+        Some("/System/Library/Frameworks/CoreFoundation.framework/Versions/A/CoreFoundation")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_mach_entry_point() -> Result<(), Error> {
+    let view = ByteView::open(fixture("macos/crash"))?;
+    let object = Object::parse(&view)?;
+    let symbols = object.symbol_map();
+
+    let entry_point = object.entry_point().unwrap();
+    let symbol = symbols.lookup_exact(entry_point).unwrap();
+    assert_eq!(symbol.name(), Some("main"));
+
+    Ok(())
+}
+
+#[test]
+fn test_mach_dsym() -> Result<(), Error> {
+    let view = ByteView::open(fixture("macos/crash.dSYM/Contents/Resources/DWARF/crash"))?;
+    let object = Object::parse(&view)?;
+
+    insta::assert_debug_snapshot!(object, @r###"
+   ⋮MachO(
+   ⋮    MachObject {
+   ⋮        code_id: Some(
+   ⋮            CodeId(67e9247c814e392ba027dbde6748fcbf),
+   ⋮        ),
+   ⋮        debug_id: DebugId {
+   ⋮            uuid: "67e9247c-814e-392b-a027-dbde6748fcbf",
+   ⋮            appendix: 0,
+   ⋮        },
+   ⋮        arch: Amd64,
+   ⋮        kind: Debug,
+   ⋮        load_address: 0x100000000,
+   ⋮        has_symbols: true,
+   ⋮        has_debug_info: true,
+   ⋮        has_unwind_info: false,
+   ⋮        is_malformed: false,
+   ⋮    },
+   ⋮)
+    "###);
+
+    Ok(())
+}
+
+#[test]
+fn test_mach_symbols() -> Result<(), Error> {
+    let view = ByteView::open(fixture("macos/crash"))?;
+    let object = Object::parse(&view)?;
+
+    let symbols = object.symbol_map();
+    insta::assert_debug_snapshot!("mach_symbols", SymbolsDebug(&symbols));
+
+    Ok(())
+}
+
+#[test]
+fn test_mach_stabs() -> Result<(), Error> {
+    let view = ByteView::open(fixture("macos/crash"))?;
+    let object = MachObject::parse(&view)?;
+    assert!(object.has_stabs());
+
+    // The executable retains a debug map, which declares functions and their source files.
+    let session = object.stabs_session()?;
+    let functions = session.functions().collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(functions.len(), 194);
+
+    let function = &functions[0];
+    assert_eq!(function.address, 0xd20);
+    assert_eq!(function.size, 0x20);
+    assert_eq!(
+        function.name,
+        "_ZN15google_breakpad18MinidumpFileWriterC2Ev"
+    );
+
+    let line = function.lines.iter().next().unwrap();
+    let file = FileEntry {
+        compilation_dir: function.compilation_dir.clone(),
+        info: line.file,
+    };
+    assert_eq!(
+        file.abs_path_str(),
+        "/Users/travis/build/getsentry/breakpad-tools/deps/breakpad/src/client/minidump_file_writer.cc"
+    );
+
+    // Without DWARF, the generic debug session falls back to STABS.
+    let object = Object::parse(&view)?;
+    let session = object.debug_session()?;
+    assert_eq!(session.functions().count(), 194);
+    assert!(session
+        .files()
+        .filter_map(Result::ok)
+        .any(|file| file.abs_path_str()
+            == "/Users/travis/build/getsentry/breakpad-tools/macos/main.cpp"));
+
+    let view = ByteView::open(fixture("macos/crash.dSYM/Contents/Resources/DWARF/crash"))?;
+    let object = MachObject::parse(&view)?;
+    assert!(!object.has_stabs());
+
+    Ok(())
+}
+
+#[test]
+fn test_mach_function_starts() -> Result<(), Error> {
+    let view = ByteView::open(fixture("macos/crash"))?;
+    let object = MachObject::parse(&view)?;
+
+    let starts = object.function_starts();
+    assert!(!starts.is_empty());
+    assert!(starts.windows(2).all(|w| w[0] < w[1]));
+
+    // Every function in the symbol table is also declared in the function starts. The Mach header
+    // symbol at the load address is not a function.
+    for symbol in object.symbols().filter(|symbol| symbol.address != 0) {
+        assert!(starts.contains(&symbol.address), "{:?}", symbol);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_mach_stripped_symbols() -> Result<(), Error> {
+    let mut data = std::fs::read(fixture("macos/crash"))?;
+
+    // Strip the symbol table by setting `nsyms` of `LC_SYMTAB` to zero.
+    let read_u32 = |data: &[u8], offset: usize| {
+        u32::from_le_bytes([
+            data[offset],
+            data[offset + 1],
+            data[offset + 2],
+            data[offset + 3],
+        ])
+    };
+    let ncmds = read_u32(&data, 16);
+    let mut offset = 32;
+    for _ in 0..ncmds {
+        if read_u32(&data, offset) == 0x2 {
+            data[offset + 12..offset + 16].copy_from_slice(&[0; 4]);
+        }
+        offset += read_u32(&data, offset + 4) as usize;
+    }
+
+    let object = MachObject::parse(&data)?;
+    assert_eq!(object.symbols().count(), 0);
+
+    let symbols = object.symbol_map();
+    let starts = object.function_starts();
+    assert_eq!(symbols.len(), starts.len());
+
+    for (symbol, start) in symbols.iter().zip(&starts) {
+        assert_eq!(symbol.name, None);
+        assert_eq!(symbol.address, *start);
+        assert!(symbol.size > 0);
+    }
+
+    Ok(())
+}
+
+/// Sets the section count of the `__TEXT` segment in a 64-bit Mach-O file and renames its first
+/// section, so that lookups by section name have to iterate all sections.
+fn corrupt_mach_text_sections(data: &mut [u8], nsects: u32) {
+    use std::convert::TryInto;
+
+    let read_u32 = |data: &[u8], offset: usize| {
+        u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+    };
+
+    let ncmds = read_u32(data, 16);
+    let mut offset = 32;
+    for _ in 0..ncmds {
+        // LC_SEGMENT_64
+        if read_u32(data, offset) == 0x19 && data[offset + 8..].starts_with(b"__TEXT\0") {
+            data[offset + 64..offset + 68].copy_from_slice(&nsects.to_le_bytes());
+            data[offset + 72..offset + 80].copy_from_slice(b"__broken");
+        }
+        offset += read_u32(data, offset + 4) as usize;
+    }
+}
+
+#[test]
+fn test_mach_corrupt_section_count() -> Result<(), Error> {
+    let mut data = std::fs::read(fixture("macos/crash"))?;
+    corrupt_mach_text_sections(&mut data, u32::MAX);
+
+    // Iterating sections must stop at the first broken section header instead of attempting to
+    // read all of them.
+    let object = MachObject::parse(&data)?;
+    assert!(!object.symbol_map().is_empty());
+    object.initializers();
+    object.terminators();
+
+    Ok(())
+}
+
+#[test]
+fn test_mach_segment_relocation() -> Result<(), Error> {
+    let path = fixture("macos/crash.dSYM/Contents/Resources/DWARF/crash");
+    let original = std::fs::read(&path)?;
+    let mut data = original.clone();
+
+    // Give `__TEXT` file contents at an offset that differs from its distance to the load address,
+    // like segments of dylibs extracted from the dyld shared cache. `LC_SEGMENT_64` stores the
+    // segment name at offset 8, followed by `vmaddr`, `vmsize`, `fileoff` and `filesize`.
+    let read_u32 = |data: &[u8], offset: usize| {
+        u32::from_le_bytes([
+            data[offset],
+            data[offset + 1],
+            data[offset + 2],
+            data[offset + 3],
+        ])
+    };
+    let slide = 0x1000u64;
+    let ncmds = read_u32(&data, 16);
+    let mut offset = 32;
+    for _ in 0..ncmds {
+        if read_u32(&data, offset) == 0x19 && data[offset + 8..].starts_with(b"__TEXT\0") {
+            data[offset + 40..offset + 48].copy_from_slice(&slide.to_le_bytes());
+            data[offset + 48..offset + 56].copy_from_slice(&slide.to_le_bytes());
+        }
+        offset += read_u32(&data, offset + 4) as usize;
+    }
+
+    let original = MachObject::parse(&original)?;
+    let relocated = MachObject::parse(&data)?;
+    assert_eq!(relocated.load_address(), original.load_address());
+
+    let original_session = original.debug_session()?;
+    let relocated_session = relocated.debug_session()?;
+    let original_functions = original_session
+        .functions()
+        .collect::<Result<Vec<_>, _>>()?;
+    let relocated_functions = relocated_session
+        .functions()
+        .collect::<Result<Vec<_>, _>>()?;
+
+    assert!(!original_functions.is_empty());
+    assert_eq!(original_functions.len(), relocated_functions.len());
+    for (original, relocated) in original_functions.iter().zip(&relocated_functions) {
+        assert_eq!(original.name, relocated.name);
+        assert_eq!(original.address + slide, relocated.address);
+        assert_eq!(original.size, relocated.size);
+
+        for (original, relocated) in original.lines.iter().zip(&relocated.lines) {
+            assert_eq!(original.address + slide, relocated.address);
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_mach_strip_debug() -> Result<(), Error> {
+    let view = ByteView::open(fixture("macos/crash.dSYM/Contents/Resources/DWARF/crash"))?;
+    let object = Object::parse(&view)?;
+    assert!(object.has_debug_info());
+
+    let mut writer = ObjectWriter::new(Vec::new());
+    writer.write_stripped(&object)?;
+    let data = writer.into_inner();
+    let stripped = Object::parse(&data)?;
+
+    assert!(data.len() < view.len());
+    assert!(!stripped.has_debug_info());
+    assert_eq!(stripped.debug_id(), object.debug_id());
+    assert_eq!(stripped.symbol_map().len(), object.symbol_map().len());
+
+    Ok(())
+}
+
+#[test]
+fn test_mach_debug_companion() -> Result<(), Error> {
+    let view = ByteView::open(fixture("macos/crash.dSYM/Contents/Resources/DWARF/crash"))?;
+    let object = Object::parse(&view)?;
+
+    let mut writer = ObjectWriter::new(Vec::new());
+    writer.write_debug_companion(&object)?;
+    let data = writer.into_inner();
+    let companion = Object::parse(&data)?;
+
+    assert_eq!(companion.kind(), ObjectKind::Debug);
+    assert_eq!(companion.debug_id(), object.debug_id());
+    assert_eq!(companion.load_address(), object.load_address());
+    assert_eq!(companion.symbol_map().len(), object.symbol_map().len());
+
+    let session = companion.debug_session()?;
+    let files = session.files().collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(files.len(), 554);
+
+    Ok(())
+}
+
+#[test]
+fn test_mach_debug_companion_executable() -> Result<(), Error> {
+    let view = ByteView::open(fixture("macos/crash"))?;
+    let object = Object::parse(&view)?;
+
+    let mut writer = ObjectWriter::new(Vec::new());
+    writer.write_debug_companion(&object)?;
+    let data = writer.into_inner();
+    let companion = Object::parse(&data)?;
+
+    assert_eq!(companion.kind(), ObjectKind::Debug);
+    assert_eq!(companion.debug_id(), object.debug_id());
+    assert_eq!(companion.code_id(), object.code_id());
+    assert_eq!(companion.symbol_map().len(), object.symbol_map().len());
+    assert!(!companion.has_unwind_info());
+
+    Ok(())
+}
+
+#[test]
+fn test_mach_files() -> Result<(), Error> {
+    let view = ByteView::open(fixture("macos/crash.dSYM/Contents/Resources/DWARF/crash"))?;
+    let object = Object::parse(&view)?;
+
+    let session = object.debug_session()?;
+    let files = session.files().collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(files.len(), 554);
+    insta::assert_debug_snapshot!("mach_files", FilesDebug(&files[..10]));
+
+    Ok(())
+}
+
+#[test]
+fn test_mach_functions() -> Result<(), Error> {
+    let view = ByteView::open(fixture("macos/crash.dSYM/Contents/Resources/DWARF/crash"))?;
+    let object = Object::parse(&view)?;
+
+    let session = object.debug_session()?;
+    let functions = session.functions().collect::<Result<Vec<_>, _>>()?;
+    insta::assert_debug_snapshot!("mach_functions", FunctionsDebug(&functions[..10], 0));
+
+    Ok(())
+}
+
+#[test]
+fn test_mach_slide() -> Result<(), Error> {
+    let view = ByteView::open(fixture("macos/crash.dSYM/Contents/Resources/DWARF/crash"))?;
+    let mach = match Object::parse(&view)? {
+        Object::MachO(mach) => mach,
+        _ => panic!("expected a MachO object"),
+    };
+
+    assert_eq!(mach.vmaddr(), 0x1_0000_0000);
+
+    let slide = mach.slide(0x1_0a00_0000);
+    assert_eq!(slide, 0xa00_0000);
+    assert_eq!(mach.rebase(0x1_0a00_1234, slide), Some(0x1234));
+    assert_eq!(mach.rebase(0x1234, slide), None);
+
+    let session = mach.debug_session()?;
+    let functions = session.functions().collect::<Result<Vec<_>, _>>()?;
+
+    let slid_session = mach.debug_session_with_slide(slide)?;
+    let slid_functions = slid_session.functions().collect::<Result<Vec<_>, _>>()?;
+
+    assert_eq!(functions.len(), slid_functions.len());
+    for (function, slid) in functions.iter().zip(&slid_functions) {
+        assert_eq!(slid.address, function.address + 0x1_0a00_0000);
+        assert_eq!(
+            mach.rebase(slid.address, slide),
+            Some(function.address),
+            "rebasing {}",
+            function.name
+        );
+
+        let lines = function
+            .lines
+            .iter()
+            .map(|line| line.address + 0x1_0a00_0000);
+        let slid_lines = slid.lines.iter().map(|line| line.address);
+        assert!(lines.eq(slid_lines));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_object_identifiers() -> Result<(), Error> {
+    let view = ByteView::open(fixture("linux/crash"))?;
+    let object = Object::parse(&view)?;
+    let ids = object.identifiers()?;
+
+    assert_eq!(ids.code_id, object.code_id());
+    assert_eq!(ids.debug_id, object.debug_id());
+    assert_eq!(ids.arch, object.arch());
+    assert_eq!(ids.kind, ObjectKind::Executable);
+
+    Ok(())
+}
+
+#[test]
+fn test_archive_identifiers() -> Result<(), Error> {
+    let view = ByteView::open(fixture("macos/crash.dSYM/Contents/Resources/DWARF/crash"))?;
+    let archive = Archive::parse(&view)?;
+    let ids = archive.identifiers()?;
+
+    assert_eq!(ids.len(), archive.object_count());
+    assert_eq!(
+        ids[0].debug_id.to_string(),
+        "67e9247c-814e-392b-a027-dbde6748fcbf"
+    );
+    assert_eq!(ids[0].kind, ObjectKind::Debug);
+
+    Ok(())
+}
+
+#[test]
+fn test_pe_32() -> Result<(), Error> {
+    let view = ByteView::open(fixture("windows/crash.exe"))?;
+    let object = Object::parse(&view)?;
+
+    insta::assert_debug_snapshot!(object, @r###"
+   ⋮Pe(
+   ⋮    PeObject {
+   ⋮        code_id: Some(
+   ⋮            CodeId(5ab380779000),
+   ⋮        ),
+   ⋮        debug_id: DebugId {
+   ⋮            uuid: "3249d99d-0c40-4931-8610-f4e4fb0b6936",
+   ⋮            appendix: 1,
+   ⋮        },
+   ⋮        debug_file_name: Some(
+   ⋮            "C:\\projects\\breakpad-tools\\windows\\Release\\crash.pdb",
+   ⋮        ),
+   ⋮        arch: X86,
+   ⋮        kind: Executable,
+   ⋮        load_address: 0x400000,
+   ⋮        has_symbols: false,
+   ⋮        has_debug_info: false,
+   ⋮        has_unwind_info: false,
+   ⋮        is_malformed: false,
+   ⋮    },
+   ⋮)
+    "###);
+
+    Ok(())
+}
+
+#[test]
+fn test_pe_entry_point() -> Result<(), Error> {
+    let view = ByteView::open(fixture("windows/crash.exe"))?;
+    let object = Object::parse(&view)?;
+
+    assert_eq!(object.entry_point(), Some(0x2e15));
+    assert!(object.terminators().is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_pe_dwarf() -> Result<(), Error> {
+    let view = ByteView::open(fixture("windows/mingw.exe"))?;
+    let object = Object::parse(&view)?;
+
+    assert_eq!(object.kind(), ObjectKind::Executable);
+    assert!(object.has_debug_info());
+
+    let session = object.debug_session()?;
+    let functions = session.functions().collect::<Result<Vec<_>, _>>()?;
+    let names: Vec<_> = functions.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(names, ["_start", "add"]);
+
+    let function = &functions[1];
+    assert_eq!(function.address, 0x1000);
+    let line = function.lines.iter().next().unwrap();
+    assert_eq!(line.file.name_str(), "main.c");
+    assert_eq!(line.line, 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_pe_dwarf_image_base_overflow() -> Result<(), Error> {
+    let mut data = std::fs::read(fixture("windows/mingw.exe"))?;
+
+    // Move the image base of the PE32+ optional header to the end of the address space, so that
+    // the addresses of all sections overflow.
+    let pe_offset = u32::from_le_bytes([data[0x3c], data[0x3d], data[0x3e], data[0x3f]]) as usize;
+    let image_base_offset = pe_offset + 24 + 24;
+    data[image_base_offset..image_base_offset + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+
+    let object = Object::parse(&data)?;
+    assert!(!object.has_debug_info());
+
+    Ok(())
+}
+
+#[test]
+fn test_pe_imports() -> Result<(), Error> {
+    let view = ByteView::open(fixture("windows/crash.exe"))?;
+    let object = Object::parse(&view)?;
+
+    assert!(object.exports()?.is_empty());
+
+    let imports = object.imports()?;
+    assert_eq!(imports.len(), 78);
+
+    let import = imports.iter().find(|i| i.name == "CloseHandle").unwrap();
+    assert_eq!(import.library.as_deref(), Some("KERNEL32.dll"));
+    assert!(import.address.is_some());
+
+    Ok(())
+}
+
+#[test]
+fn test_pe_64() -> Result<(), Error> {
+    let view = ByteView::open(fixture("windows/CrashWithException.exe"))?;
+    let object = Object::parse(&view)?;
 
     insta::assert_debug_snapshot!(object, @r###"
    ⋮Pe(
@@ -450,6 +1782,117 @@ fn test_pe_64() -> Result<(), Error> {
 // NB: No test for PE symbols because our executable does not export any symbols
 // NB: No test for PE functions because we can only read debug info from PDBs
 
+#[test]
+fn test_pe_load_config() -> Result<(), Error> {
+    let view = ByteView::open(fixture("windows/crash.exe"))?;
+    let object = PeObject::parse(&view)?;
+
+    let load_config = object.load_config().expect("load config");
+    assert_eq!(load_config.security_cookie, 0x406000);
+    assert_eq!(load_config.se_handler_table, 0x404950);
+    assert_eq!(load_config.se_handler_count, 6);
+    assert!(load_config.has_control_flow_guard());
+    assert_eq!(load_config.guard_cf_functions().count(), 0);
+
+    assert_eq!(
+        object.security_flags(),
+        PeSecurityFlags {
+            aslr: true,
+            high_entropy_aslr: false,
+            dep: true,
+            control_flow_guard: false,
+            safe_seh: true,
+            no_seh: false,
+            security_cookie: true,
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_pe_64_security_flags() -> Result<(), Error> {
+    let view = ByteView::open(fixture("windows/CrashWithException.exe"))?;
+    let object = PeObject::parse(&view)?;
+
+    let load_config = object.load_config().expect("load config");
+    assert_eq!(load_config.security_cookie, 0x1_4000_5008);
+    assert_eq!(
+        load_config.guard_cf_dispatch_function_pointer,
+        0x1_4000_31c8
+    );
+
+    let flags = object.security_flags();
+    assert!(flags.aslr && flags.high_entropy_aslr && flags.dep && flags.security_cookie);
+    assert!(!flags.safe_seh);
+
+    Ok(())
+}
+
+#[test]
+fn test_pe_overlay() -> Result<(), Error> {
+    // MinGW appends the COFF symbol table after the last section.
+    let view = ByteView::open(fixture("windows/mingw.exe"))?;
+    let object = PeObject::parse(&view)?;
+    assert_eq!(object.overlay_range(), Some(5632..5867));
+    assert_eq!(object.overlay().map(<[u8]>::len), Some(235));
+
+    let view = ByteView::open(fixture("windows/crash.exe"))?;
+    let object = PeObject::parse(&view)?;
+    assert_eq!(object.overlay_range(), None);
+
+    let mut data = view.to_vec();
+    data.extend_from_slice(b"payload");
+    let object = PeObject::parse(&data)?;
+    assert_eq!(object.overlay(), Some(&b"payload"[..]));
+
+    Ok(())
+}
+
+#[test]
+fn test_pe_version_info_missing() -> Result<(), Error> {
+    // This executable has resources, but no version information.
+    let view = ByteView::open(fixture("windows/crash.exe"))?;
+    let object = PeObject::parse(&view)?;
+    assert_eq!(object.version_info(), None);
+
+    Ok(())
+}
+
+#[test]
+fn test_pe_functions() -> Result<(), Error> {
+    let view = ByteView::open(fixture("windows/CrashWithException.exe"))?;
+    let object = Object::parse(&view)?;
+
+    let session = object.debug_session()?;
+    let functions = session.functions().collect::<Result<Vec<_>, _>>()?;
+
+    insta::assert_debug_snapshot!("pe_functions", FunctionsDebug(&functions[..10], 0));
+
+    Ok(())
+}
+
+#[test]
+fn test_pe_exception_directory_out_of_bounds() -> Result<(), Error> {
+    let mut data = std::fs::read(fixture("windows/CrashWithException.exe"))?;
+
+    // Grow the exception directory of the PE32+ optional header past the end of the file, keeping
+    // its size a multiple of the runtime function size.
+    let pe_offset = u32::from_le_bytes([data[0x3c], data[0x3d], data[0x3e], data[0x3f]]) as usize;
+    let size_offset = pe_offset + 24 + 112 + 3 * 8 + 4;
+    let size = (data.len() as u32 / 12 + 1) * 12;
+    data[size_offset..size_offset + 4].copy_from_slice(&size.to_le_bytes());
+
+    let object = PeObject::parse(&data)?;
+    assert!(object.exception_data().is_none());
+    assert!(!object.has_unwind_info());
+
+    let session = object.debug_session()?;
+    assert_eq!(session.functions().count(), 0);
+
+    Ok(())
+}
+
 #[test]
 fn test_pdb() -> Result<(), Error> {
     let view = ByteView::open(fixture("windows/crash.pdb"))?;
@@ -475,6 +1918,174 @@ fn test_pdb() -> Result<(), Error> {
     Ok(())
 }
 
+#[test]
+fn test_pdb_source_links() -> Result<(), Error> {
+    let view = ByteView::open(fixture("windows/crash.pdb"))?;
+    let object = Object::parse(&view)?;
+
+    let session = object.debug_session()?;
+    assert_eq!(session.source_url_by_path("c:\\projects\\crash.cpp"), None);
+
+    Ok(())
+}
+
+#[test]
+fn test_pdb_source() -> Result<(), Error> {
+    use symbolic_debuginfo::pdb::pdb::{Source, SourceSlice};
+    use symbolic_debuginfo::pdb::PdbSource;
+
+    let view = ByteView::open(fixture("windows/crash.pdb"))?;
+    let mut source = PdbSource::new(&view);
+
+    let page = |index: u64| SourceSlice {
+        offset: index * 0x1000,
+        size: 0x1000,
+    };
+
+    // Contiguous pages are borrowed, scattered pages are copied.
+    let contiguous = source.view(&[page(1), page(2)])?;
+    assert_eq!(contiguous.as_slice(), &view[0x1000..0x3000]);
+    assert_eq!(format!("{:?}", contiguous), "Borrowed(8192)");
+
+    let scattered = source.view(&[page(2), page(1)])?;
+    assert_eq!(&scattered.as_slice()[..0x1000], &view[0x2000..0x3000]);
+    assert_eq!(&scattered.as_slice()[0x1000..], &view[0x1000..0x2000]);
+    assert_eq!(format!("{:?}", scattered), "Owned(8192)");
+
+    let out_of_bounds = page(view.len() as u64 / 0x1000 + 1);
+    assert!(source.view(&[out_of_bounds]).is_err());
+
+    Ok(())
+}
+
+/// Reads the streams of an MSF file, see [`write_msf`].
+fn read_msf(data: &[u8]) -> Vec<Option<Vec<u8>>> {
+    use std::convert::TryInto;
+
+    let u32_at = |bytes: &[u8], offset: usize| {
+        u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+    };
+
+    let page_size = u32_at(data, 32) as usize;
+    let read_pages = |numbers: &[u8], size: usize| {
+        let mut bytes = Vec::new();
+        for number in numbers.chunks_exact(4).take(size.div_ceil(page_size)) {
+            let offset = u32_at(number, 0) as usize * page_size;
+            bytes.extend_from_slice(&data[offset..offset + page_size]);
+        }
+        bytes.truncate(size);
+        bytes
+    };
+
+    let directory_size = u32_at(data, 44) as usize;
+    let directory_pages = directory_size.div_ceil(page_size);
+    let page_list = read_pages(&data[52..], directory_pages * 4);
+    let directory = read_pages(&page_list, directory_size);
+
+    let count = u32_at(&directory, 0) as usize;
+    let mut numbers = &directory[4 + count * 4..];
+    (0..count)
+        .map(|index| match u32_at(&directory, 4 + index * 4) {
+            u32::MAX => None,
+            size => {
+                let stream = read_pages(numbers, size as usize);
+                numbers = &numbers[(size as usize).div_ceil(page_size) * 4..];
+                Some(stream)
+            }
+        })
+        .collect()
+}
+
+/// Writes streams into an MSF file with the given page size, starting at page `first_page`.
+///
+/// Pages of all streams and the stream directory are laid out contiguously. Only the header is
+/// written to the first page, and all pages in between are left empty.
+fn write_msf(
+    file: &mut std::fs::File,
+    streams: &[Option<Vec<u8>>],
+    page_size: usize,
+    first_page: u32,
+) -> std::io::Result<()> {
+    use std::io::{Seek, SeekFrom, Write};
+
+    let mut next_page = first_page;
+    let mut write_pages = |file: &mut std::fs::File, bytes: &[u8]| -> std::io::Result<Vec<u8>> {
+        let mut numbers = Vec::new();
+        file.seek(SeekFrom::Start(u64::from(next_page) * page_size as u64))?;
+        file.write_all(bytes)?;
+        for _ in 0..bytes.len().div_ceil(page_size) {
+            numbers.extend_from_slice(&next_page.to_le_bytes());
+            next_page += 1;
+        }
+        Ok(numbers)
+    };
+
+    let mut sizes = Vec::new();
+    let mut numbers = Vec::new();
+    for stream in streams {
+        match stream {
+            Some(bytes) => {
+                sizes.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+                numbers.extend(write_pages(file, bytes)?);
+            }
+            None => sizes.extend_from_slice(&u32::MAX.to_le_bytes()),
+        }
+    }
+
+    let mut directory = (streams.len() as u32).to_le_bytes().to_vec();
+    directory.extend(sizes);
+    directory.extend(numbers);
+    let directory_pages = write_pages(file, &directory)?;
+    let page_list = write_pages(file, &directory_pages)?;
+
+    let mut header = b"Microsoft C/C++ MSF 7.00\r\n\x1a\x44\x53\x00\x00\x00".to_vec();
+    for field in &[page_size as u32, 1, next_page, directory.len() as u32, 0] {
+        header.extend_from_slice(&field.to_le_bytes());
+    }
+    header.extend(page_list);
+
+    file.seek(SeekFrom::Start(0))?;
+    file.write_all(&header)?;
+    file.set_len(u64::from(next_page) * page_size as u64)
+}
+
+// This writes a sparse file larger than 4 GiB, which requires a 64-bit address space to map.
+#[test]
+#[cfg(all(unix, target_pointer_width = "64"))]
+fn test_pdb_big_pages() -> Result<(), Error> {
+    let view = ByteView::open(fixture("windows/crash.pdb"))?;
+    let object = Object::parse(&view)?;
+
+    // Move all streams past 4 GiB and use pages of 8 KiB, like large PDBs written by LLVM.
+    let page_size = 0x2000;
+    let first_page = ((1u64 << 32) / page_size as u64) as u32;
+    let mut file = tempfile::tempfile()?;
+    write_msf(&mut file, &read_msf(&view), page_size, first_page)?;
+
+    let big_view = ByteView::map_file(file)?;
+    assert!(big_view.len() > 1 << 32);
+
+    let big_object = Object::parse(&big_view)?;
+    assert_eq!(big_object.file_format(), FileFormat::Pdb);
+    assert_eq!(big_object.debug_id(), object.debug_id());
+    assert_eq!(big_object.arch(), object.arch());
+    assert_eq!(big_object.symbol_map().len(), object.symbol_map().len());
+
+    let function_names = |object: &Object<'_>| -> Result<Vec<String>, Error> {
+        let session = object.debug_session()?;
+        let mut names = Vec::new();
+        for function in session.functions() {
+            names.push(function?.name.to_string());
+        }
+        Ok(names)
+    };
+    let names = function_names(&object)?;
+    assert!(!names.is_empty());
+    assert_eq!(function_names(&big_object)?, names);
+
+    Ok(())
+}
+
 #[test]
 fn test_pdb_symbols() -> Result<(), Error> {
     let view = ByteView::open(fixture("windows/crash.pdb"))?;
@@ -511,6 +2122,27 @@ fn test_pdb_functions() -> Result<(), Error> {
     Ok(())
 }
 
+#[test]
+fn test_pdb_noreturn_functions() -> Result<(), Error> {
+    let view = ByteView::open(fixture("windows/crash.pdb"))?;
+    let object = Object::parse(&view)?;
+
+    let session = object.debug_session()?;
+    let functions = session.functions().collect::<Result<Vec<_>, _>>()?;
+    let find = |name: &str| {
+        functions
+            .iter()
+            .find(|f| f.name.as_str() == name)
+            .expect("function")
+    };
+
+    assert!(find("__report_gsfailure").noreturn);
+    assert!(find("__scrt_fastfail").noreturn);
+    assert!(!find("main").noreturn);
+
+    Ok(())
+}
+
 #[test]
 fn test_pdb_anonymous_namespace() -> Result<(), Error> {
     // Regression test for ?A0x<hash> namespaces
@@ -537,6 +2169,33 @@ fn test_pdb_anonymous_namespace() -> Result<(), Error> {
     Ok(())
 }
 
+#[test]
+fn test_pdb_inlinee_ranges() -> Result<(), Error> {
+    fn find<'a, 'd>(functions: &'a [Function<'d>], address: u64) -> Option<&'a Function<'d>> {
+        functions.iter().find_map(|f| {
+            if f.inline && f.address == address {
+                Some(f)
+            } else {
+                find(&f.inlinees, address)
+            }
+        })
+    }
+
+    let view = ByteView::open(fixture("windows/crash.pdb"))?;
+    let object = Object::parse(&view)?;
+
+    let session = object.debug_session()?;
+    let functions = session.functions().collect::<Result<Vec<_>, _>>()?;
+
+    // The inlined code is interleaved with code of the caller.
+    let assign = find(&functions, 0x115f).expect("inlined assign at 0x115f");
+    assert_eq!(assign.name, "std::_WChar_traits<wchar_t>::assign");
+    assert_eq!(assign.ranges, [0x115f..0x1161, 0x1168..0x116b]);
+    assert_eq!(assign.size, 0xc);
+
+    Ok(())
+}
+
 #[test]
 fn test_wasm_symbols() -> Result<(), Error> {
     let view = ByteView::open(fixture("wasm/simple.wasm"))?;
@@ -573,3 +2232,177 @@ fn test_wasm_line_program() -> Result<(), Error> {
 
     Ok(())
 }
+
+#[test]
+fn test_wasm_function_index() -> Result<(), Error> {
+    let view = ByteView::open(fixture("wasm/simple.wasm"))?;
+    let object = WasmObject::parse(&view)?;
+
+    let (index, offset) = object.function_index(0x8b).expect("internal_func");
+    assert_eq!(offset, 0);
+    assert_eq!(object.function_address(index, 0), Some(0x8b));
+    assert_eq!(object.function_address(index, 4), Some(0x8f));
+    assert_eq!(object.function_index(0x8f), Some((index, 4)));
+
+    let symbol = object.symbol_map().lookup(0x8f).cloned().expect("symbol");
+    assert_eq!(symbol.name(), Some("internal_func"));
+
+    assert_eq!(object.function_address(index, 1 << 32), None);
+    assert_eq!(object.function_address(index + 1, 0), None);
+    assert_eq!(object.function_index(0x8a), None);
+
+    Ok(())
+}
+
+#[test]
+fn test_dyn_debug_sessions() -> Result<(), Error> {
+    let elf_view = ByteView::open(fixture("linux/crash.debug"))?;
+    let elf = ElfObject::parse(&elf_view)?;
+    let pdb_view = ByteView::open(fixture("windows/crash.pdb"))?;
+    let pdb = Object::parse(&pdb_view)?;
+
+    let sessions: Vec<Box<dyn DynDebugSession>> = vec![
+        Box::new(elf.debug_session()?),
+        Box::new(pdb.debug_session()?),
+    ];
+
+    let elf_functions = elf.debug_session()?.functions().count();
+    let pdb_functions = pdb.debug_session()?.functions().count();
+
+    let counts = sessions
+        .iter()
+        .map(|session| session.functions().filter(Result::is_ok).count())
+        .collect::<Vec<_>>();
+    assert_eq!(counts, vec![elf_functions, pdb_functions]);
+
+    for session in &sessions {
+        assert!(session.files().next().is_some());
+        assert!(matches!(
+            session.source_by_path("does/not/exist.c"),
+            Ok(None)
+        ));
+    }
+
+    Ok(())
+}
+
+/// Appends an `ar` member header and the contents to an archive.
+fn push_ar_member(archive: &mut Vec<u8>, name: &str, contents: &[u8]) {
+    let header = format!(
+        "{:<16}{:<12}{:<6}{:<6}{:<8}{:<10}`\n",
+        name,
+        0,
+        0,
+        0,
+        644,
+        contents.len()
+    );
+    archive.extend_from_slice(header.as_bytes());
+    archive.extend_from_slice(contents);
+    if contents.len() % 2 == 1 {
+        archive.push(b'\n');
+    }
+}
+
+#[test]
+fn test_static_library_gnu() -> Result<(), Error> {
+    let object = ByteView::open(fixture("linux/crash.debug"))?;
+
+    let mut data = b"!<arch>\n".to_vec();
+    push_ar_member(&mut data, "/", &[0; 4]);
+    push_ar_member(&mut data, "//", b"a_very_long_member_name.o/\n");
+    push_ar_member(&mut data, "short.o/", &object);
+    push_ar_member(&mut data, "/0", &object);
+    push_ar_member(&mut data, "notes.txt/", b"hello");
+
+    let library = StaticLibrary::parse(&data)?;
+    assert!(!library.is_thin());
+
+    let members = library.members().collect::<Result<Vec<_>, _>>()?;
+    let names: Vec<_> = members.iter().map(|m| m.name()).collect();
+    assert_eq!(names, ["short.o", "a_very_long_member_name.o", "notes.txt"]);
+
+    assert_eq!(members[0].size(), object.len());
+    assert_eq!(members[0].object()?.file_format(), FileFormat::Elf);
+    assert_eq!(
+        members[2].object().unwrap_err().kind(),
+        StaticLibraryErrorKind::BadObject
+    );
+
+    // The object declares a build id, which is used for both copies.
+    let debug_id = Object::parse(&object)?.debug_id();
+    assert_eq!(members[0].debug_id(), debug_id);
+    assert_eq!(members[1].debug_id(), debug_id);
+
+    // Other members receive a synthesized, stable identifier.
+    assert!(!members[2].debug_id().is_nil());
+    assert_eq!(members[2].debug_id(), members[2].debug_id());
+    assert_ne!(members[2].debug_id(), debug_id);
+
+    Ok(())
+}
+
+#[test]
+fn test_static_library_bsd() -> Result<(), Error> {
+    let mut symdef = b"__.SYMDEF SORTED\0\0\0\0".to_vec();
+    symdef.extend_from_slice(&[0; 8]);
+
+    let mut member = b"a_very_long_member_name.o\0\0\0".to_vec();
+    member.extend_from_slice(b"contents");
+
+    let mut data = b"!<arch>\n".to_vec();
+    push_ar_member(&mut data, "#1/20", &symdef);
+    push_ar_member(&mut data, "#1/28", &member);
+
+    let library = StaticLibrary::parse(&data)?;
+    let members = library.members().collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(members.len(), 1);
+    assert_eq!(members[0].name(), "a_very_long_member_name.o");
+    assert_eq!(members[0].data(), Some(&b"contents"[..]));
+
+    Ok(())
+}
+
+#[test]
+fn test_static_library_thin() -> Result<(), Error> {
+    let mut data = b"!<thin>\n".to_vec();
+    push_ar_member(&mut data, "/", &[0; 4]);
+    push_ar_member(&mut data, "//", b"build/first.o/\nbuild/second.o/\n");
+    data.extend_from_slice(
+        format!(
+            "{:<16}{:<12}{:<6}{:<6}{:<8}{:<10}`\n",
+            "/0", 0, 0, 0, 644, 1234
+        )
+        .as_bytes(),
+    );
+    data.extend_from_slice(
+        format!(
+            "{:<16}{:<12}{:<6}{:<6}{:<8}{:<10}`\n",
+            "/15", 0, 0, 0, 644, 42
+        )
+        .as_bytes(),
+    );
+
+    let library = StaticLibrary::parse(&data)?;
+    assert!(library.is_thin());
+
+    let members = library.members().collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(members.len(), 2);
+    assert_eq!(members[0].name(), "build/first.o");
+    assert_eq!(members[0].size(), 1234);
+    assert_eq!(members[0].data(), None);
+    assert_eq!(members[1].name(), "build/second.o");
+    assert_eq!(
+        members[1].object().unwrap_err().kind(),
+        StaticLibraryErrorKind::ThinMember
+    );
+    assert_ne!(members[0].debug_id(), members[1].debug_id());
+
+    Ok(())
+}
+
+#[test]
+fn test_static_library_bad_magic() {
+    let error = StaticLibrary::parse(b"!<arc>\n").unwrap_err();
+    assert_eq!(error.kind(), StaticLibraryErrorKind::BadMagic);
+}
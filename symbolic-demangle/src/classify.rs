@@ -0,0 +1,215 @@
+//! Classification of stack frames by their demangled function names.
+
+use std::borrow::Cow;
+
+use symbolic_common::{Language, Name};
+
+use crate::{Demangle, DemangleOptions};
+
+/// Rules for frames raising Rust panics.
+///
+/// Functions like `Option::unwrap` are annotated with `#[track_caller]`, so the panic message
+/// reports the location of their caller. Including them here trims stack traces down to the same
+/// frame.
+const RUST_PANIC_FRAMES: &[&str] = &[
+    "core::panicking",
+    "core::option::expect_failed",
+    "core::option::unwrap_failed",
+    "core::option::Option<T>::expect",
+    "core::option::Option<T>::unwrap",
+    "core::result::unwrap_failed",
+    "core::result::Result<T,E>::expect",
+    "core::result::Result<T,E>::unwrap",
+    "core::result::Result<T,E>::expect_err",
+    "core::result::Result<T,E>::unwrap_err",
+    "core::slice::index::slice_end_index_len_fail",
+    "core::slice::index::slice_index_order_fail",
+    "core::slice::index::slice_start_index_len_fail",
+    "core::str::slice_error_fail",
+    "core::cell::panic_already_borrowed",
+    "core::cell::panic_already_mutably_borrowed",
+    "std::panicking::begin_panic",
+    "std::panicking::begin_panic_fmt",
+    "std::panicking::begin_panic_handler",
+    "std::panicking::default_hook",
+    "std::panicking::rust_panic",
+    "std::panicking::rust_panic_with_hook",
+    "std::sys_common::backtrace::__rust_end_short_backtrace",
+    "std::sys::backtrace::__rust_end_short_backtrace",
+    "std::process::abort",
+    "rust_begin_unwind",
+    "rust_panic",
+    "__rust_start_panic",
+];
+
+/// Rules for frames throwing C++ exceptions or terminating the program.
+const CPP_PANIC_FRAMES: &[&str] = &[
+    "__cxa_throw",
+    "__cxa_rethrow",
+    "_CxxThrowException",
+    "std::terminate",
+    "std::__terminate",
+    "__cxxabiv1::__terminate",
+    "__gnu_cxx::__verbose_terminate_handler",
+];
+
+/// Rules for frames aborting the process from C.
+const C_PANIC_FRAMES: &[&str] = &[
+    "abort",
+    "raise",
+    "gsignal",
+    "pthread_kill",
+    "__pthread_kill",
+    "__pthread_kill_implementation",
+    "__pthread_kill_internal",
+    "__assert_fail",
+    "__assert_fail_base",
+    "__assert_rtn",
+];
+
+/// The classification of a stack frame by a [`FrameClassifier`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum FrameKind {
+    /// Regular code that is not covered by any rule.
+    Normal,
+    /// Machinery raising a panic, exception or abort after an error has been detected.
+    ///
+    /// These frames are the same for every error of their kind and can be trimmed from the top of
+    /// stack traces.
+    Panic,
+}
+
+/// A rule matching function names of a language.
+#[derive(Clone, Debug)]
+struct FrameRule {
+    language: Language,
+    path: Cow<'static, str>,
+    kind: FrameKind,
+}
+
+impl FrameRule {
+    /// Checks whether the demangled name is the path of this rule or an item within it.
+    ///
+    /// Generic arguments, hashes and parameters following the path are ignored.
+    fn matches(&self, name: &str) -> bool {
+        match name.strip_prefix(self.path.as_ref()) {
+            Some(rest) => {
+                rest.is_empty()
+                    || rest.starts_with("::")
+                    || rest.starts_with('<')
+                    || rest.starts_with('(')
+            }
+            None => false,
+        }
+    }
+}
+
+/// Classifies stack frames by their function names.
+///
+/// Names are demangled without parameters and then matched against path rules of their language.
+/// A rule matches the path itself as well as all items nested within it, so the rule
+/// `core::panicking` matches `core::panicking::panic_fmt`. Rules of all languages apply to names
+/// with an unknown language, such as unmangled symbols.
+///
+/// The built-in rules recognize the machinery of Rust panics, C++ exceptions and C aborts. Use
+/// [`add_rule`](Self::add_rule) to extend them.
+///
+/// # Example
+///
+/// ```
+/// # #[cfg(feature = "rust")] {
+/// use symbolic_common::Name;
+/// use symbolic_demangle::{FrameClassifier, FrameKind};
+///
+/// let classifier = FrameClassifier::new();
+/// let frames = [
+///     Name::from("_ZN4core9panicking9panic_fmt17h0123456789abcdefE"),
+///     Name::from("_ZN4core6result13unwrap_failed17h0123456789abcdefE"),
+///     Name::from("_ZN5crash4main17h0123456789abcdefE"),
+/// ];
+///
+/// assert_eq!(classifier.classify(&frames[0]), FrameKind::Panic);
+/// assert_eq!(classifier.panic_frames(&frames), 2);
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct FrameClassifier {
+    rules: Vec<FrameRule>,
+}
+
+impl FrameClassifier {
+    /// Creates a classifier with the built-in rules.
+    pub fn new() -> Self {
+        let mut classifier = Self::empty();
+
+        let builtins = [
+            (Language::Rust, RUST_PANIC_FRAMES),
+            (Language::Cpp, CPP_PANIC_FRAMES),
+            (Language::C, C_PANIC_FRAMES),
+        ];
+
+        for (language, paths) in builtins {
+            for path in paths {
+                classifier.add_rule(language, *path, FrameKind::Panic);
+            }
+        }
+
+        classifier
+    }
+
+    /// Creates a classifier without any rules.
+    pub fn empty() -> Self {
+        FrameClassifier { rules: Vec::new() }
+    }
+
+    /// Adds a rule that classifies functions of a language at the given path.
+    ///
+    /// Rules are checked in the order they were added, and the first matching rule wins.
+    pub fn add_rule<P>(&mut self, language: Language, path: P, kind: FrameKind)
+    where
+        P: Into<Cow<'static, str>>,
+    {
+        self.rules.push(FrameRule {
+            language,
+            path: path.into(),
+            kind,
+        });
+    }
+
+    /// Classifies a frame by its function name.
+    pub fn classify(&self, name: &Name<'_>) -> FrameKind {
+        let language = name.detect_language();
+        let demangled = name.try_demangle(DemangleOptions::name_only());
+
+        self.rules
+            .iter()
+            .filter(|rule| language == Language::Unknown || rule.language == language)
+            .find(|rule| rule.matches(&demangled))
+            .map_or(FrameKind::Normal, |rule| rule.kind)
+    }
+
+    /// Returns the number of frames to trim from the top of a stack trace.
+    ///
+    /// The frames are expected in order from the innermost to the outermost call. This skips all
+    /// frames up to and including the last [panic frame](FrameKind::Panic), so the next frame is
+    /// the one that raised the error. Frames without a rule in between, such as internal helpers
+    /// of the runtime, are skipped as well. If there is no panic frame, this returns `0`.
+    pub fn panic_frames<'a, 'n: 'a, I>(&self, frames: I) -> usize
+    where
+        I: IntoIterator<Item = &'a Name<'n>>,
+    {
+        frames
+            .into_iter()
+            .enumerate()
+            .filter(|(_, name)| self.classify(name) == FrameKind::Panic)
+            .last()
+            .map_or(0, |(index, _)| index + 1)
+    }
+}
+
+impl Default for FrameClassifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -6,6 +6,8 @@
 //! - Rust (both `legacy` and `v0`) (`features = ["rust"]`)
 //! - Swift (up to Swift 5.3) (`features = ["swift"]`)
 //! - ObjC (only symbol detection)
+//! - Dart (cleanup of names emitted by the Dart VM, selected via `Language::Dart`)
+//! - Kotlin (Kotlin/Native and cleanup of Kotlin/JVM synthetic names)
 //!
 //! As the demangling schemes for the languages are different, the supported demangling features are
 //! inconsistent. For example, argument types were not encoded in legacy Rust mangling and thus not
@@ -34,13 +36,19 @@
 #![warn(missing_docs)]
 
 use std::borrow::Cow;
+use std::collections::BTreeMap;
 #[cfg(feature = "swift")]
 use std::ffi::{CStr, CString};
+use std::fmt;
 #[cfg(feature = "swift")]
 use std::os::raw::{c_char, c_int};
 
 use symbolic_common::{Language, Name, NameMangling};
 
+mod classify;
+
+pub use crate::classify::*;
+
 #[cfg(feature = "swift")]
 const SYMBOLIC_SWIFT_FEATURE_RETURN_TYPE: c_int = 0x1;
 #[cfg(feature = "swift")]
@@ -121,6 +129,95 @@ impl DemangleOptions {
     }
 }
 
+/// The reason why a name could not be demangled, see [`DemangleError`].
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[non_exhaustive]
+pub enum DemangleErrorKind {
+    /// The language of the name could not be detected.
+    UnknownLanguage,
+    /// Demangling is not supported for the language, or its feature is not enabled.
+    UnsupportedLanguage,
+    /// The name is not a valid mangled name in its language.
+    InvalidName,
+    /// The name exceeds the recursion or output length limits of the demangler.
+    TooComplex,
+}
+
+impl fmt::Display for DemangleErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownLanguage => write!(f, "unknown language"),
+            Self::UnsupportedLanguage => write!(f, "unsupported language"),
+            Self::InvalidName => write!(f, "invalid mangled name"),
+            Self::TooComplex => write!(f, "mangled name too complex"),
+        }
+    }
+}
+
+/// An error returned when a name cannot be demangled.
+///
+/// Besides the [kind](DemangleErrorKind) of error, this carries a human readable reason provided
+/// by the language's demangler and, if known, the byte offset in the mangled name where demangling
+/// failed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DemangleError {
+    kind: DemangleErrorKind,
+    language: Language,
+    position: Option<usize>,
+    reason: Cow<'static, str>,
+}
+
+impl DemangleError {
+    fn new<R>(kind: DemangleErrorKind, language: Language, reason: R) -> Self
+    where
+        R: Into<Cow<'static, str>>,
+    {
+        Self {
+            kind,
+            language,
+            position: None,
+            reason: reason.into(),
+        }
+    }
+
+    /// Returns the corresponding [`DemangleErrorKind`] for this error.
+    pub fn kind(&self) -> DemangleErrorKind {
+        self.kind
+    }
+
+    /// Returns the language that was used to demangle the name.
+    ///
+    /// This is `Language::Unknown` if the language could not be detected.
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    /// Returns the byte offset in the mangled name where demangling failed, if known.
+    ///
+    /// Not all demangler backends report positions. Currently, this is only available for MSVC
+    /// names and names containing nul bytes.
+    pub fn position(&self) -> Option<usize> {
+        self.position
+    }
+
+    /// Returns a human readable description of the error.
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+}
+
+impl fmt::Display for DemangleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.kind, self.reason)?;
+        if let Some(position) = self.position {
+            write!(f, " at offset {}", position)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for DemangleError {}
+
 fn is_maybe_objc(ident: &str) -> bool {
     (ident.starts_with("-[") || ident.starts_with("+[")) && ident.ends_with(']')
 }
@@ -161,7 +258,7 @@ fn is_maybe_swift(_ident: &str) -> bool {
 }
 
 #[cfg(feature = "msvc")]
-fn try_demangle_msvc(ident: &str, opts: DemangleOptions) -> Option<String> {
+fn try_demangle_msvc(ident: &str, opts: DemangleOptions) -> Result<String, DemangleError> {
     use msvc_demangler::DemangleFlags as MsvcFlags;
 
     // the flags are bitflags
@@ -174,12 +271,21 @@ fn try_demangle_msvc(ident: &str, opts: DemangleOptions) -> Option<String> {
         flags |= MsvcFlags::NAME_ONLY;
     }
 
-    msvc_demangler::demangle(ident, flags).ok()
+    msvc_demangler::demangle(ident, flags).map_err(|error| DemangleError {
+        kind: DemangleErrorKind::InvalidName,
+        language: Language::Cpp,
+        position: error.offset(),
+        reason: error.to_string().into(),
+    })
 }
 
 #[cfg(not(feature = "msvc"))]
-fn try_demangle_msvc(_ident: &str, _opts: DemangleOptions) -> Option<String> {
-    None
+fn try_demangle_msvc(_ident: &str, _opts: DemangleOptions) -> Result<String, DemangleError> {
+    Err(DemangleError::new(
+        DemangleErrorKind::UnsupportedLanguage,
+        Language::Cpp,
+        "MSVC demangling is not enabled",
+    ))
 }
 
 /// Removes a suffix consisting of $ followed by 32 hex digits, if there is one,
@@ -231,13 +337,14 @@ impl std::fmt::Write for BoundedString {
     }
 }
 
-fn try_demangle_cpp(ident: &str, opts: DemangleOptions) -> Option<String> {
+fn try_demangle_cpp(ident: &str, opts: DemangleOptions) -> Result<String, DemangleError> {
     if is_maybe_msvc(ident) {
         return try_demangle_msvc(ident, opts);
     }
 
     #[cfg(feature = "cpp")]
     {
+        use cpp_demangle::error::Error as CppError;
         use cpp_demangle::{DemangleOptions as CppOptions, ParseOptions, Symbol as CppSymbol};
 
         let stripped = strip_hash_suffix(ident);
@@ -247,7 +354,13 @@ fn try_demangle_cpp(ident: &str, opts: DemangleOptions) -> Option<String> {
             &ParseOptions::default().recursion_limit(192), // default is 96
         ) {
             Ok(symbol) => symbol,
-            Err(_) => return None,
+            Err(error) => {
+                let kind = match error {
+                    CppError::TooMuchRecursion => DemangleErrorKind::TooComplex,
+                    _ => DemangleErrorKind::InvalidName,
+                };
+                return Err(DemangleError::new(kind, Language::Cpp, error.to_string()));
+            }
         };
 
         let mut cpp_options = CppOptions::new().recursion_limit(192); // default is 128
@@ -262,36 +375,59 @@ fn try_demangle_cpp(ident: &str, opts: DemangleOptions) -> Option<String> {
         // lead to a "Billion laughs attack".
         let mut buf = BoundedString::new(4096);
 
-        symbol
-            .structured_demangle(&mut buf, &cpp_options)
-            .ok()
-            .map(|_| buf.into_inner())
+        match symbol.structured_demangle(&mut buf, &cpp_options) {
+            Ok(()) => Ok(buf.into_inner()),
+            Err(_) => Err(DemangleError::new(
+                DemangleErrorKind::TooComplex,
+                Language::Cpp,
+                "demangled name exceeds the length or recursion limit",
+            )),
+        }
     }
     #[cfg(not(feature = "cpp"))]
     {
-        None
+        Err(DemangleError::new(
+            DemangleErrorKind::UnsupportedLanguage,
+            Language::Cpp,
+            "C++ demangling is not enabled",
+        ))
     }
 }
 
 #[cfg(feature = "rust")]
-fn try_demangle_rust(ident: &str, _opts: DemangleOptions) -> Option<String> {
+fn try_demangle_rust(ident: &str, _opts: DemangleOptions) -> Result<String, DemangleError> {
     match rustc_demangle::try_demangle(ident) {
-        Ok(demangled) => Some(format!("{:#}", demangled)),
-        Err(_) => None,
+        Ok(demangled) => Ok(format!("{:#}", demangled)),
+        Err(_) => Err(DemangleError::new(
+            DemangleErrorKind::InvalidName,
+            Language::Rust,
+            "not a valid Rust symbol",
+        )),
     }
 }
 
 #[cfg(not(feature = "rust"))]
-fn try_demangle_rust(_ident: &str, _opts: DemangleOptions) -> Option<String> {
-    None
+fn try_demangle_rust(_ident: &str, _opts: DemangleOptions) -> Result<String, DemangleError> {
+    Err(DemangleError::new(
+        DemangleErrorKind::UnsupportedLanguage,
+        Language::Rust,
+        "Rust demangling is not enabled",
+    ))
 }
 
 #[cfg(feature = "swift")]
-fn try_demangle_swift(ident: &str, opts: DemangleOptions) -> Option<String> {
+fn try_demangle_swift(ident: &str, opts: DemangleOptions) -> Result<String, DemangleError> {
     let mut buf = vec![0; 4096];
     let sym = match CString::new(ident) {
         Ok(sym) => sym,
-        Err(_) => return None,
+        Err(error) => {
+            return Err(DemangleError {
+                kind: DemangleErrorKind::InvalidName,
+                language: Language::Swift,
+                position: Some(error.nul_position()),
+                reason: "name contains a nul byte".into(),
+            })
+        }
     };
 
     let mut features = 0;
@@ -304,31 +440,232 @@ fn try_demangle_swift(ident: &str, opts: DemangleOptions) -> Option<String> {
 
     unsafe {
         match symbolic_demangle_swift(sym.as_ptr(), buf.as_mut_ptr(), buf.len(), features) {
-            0 => None,
-            _ => Some(CStr::from_ptr(buf.as_ptr()).to_string_lossy().to_string()),
+            0 => Err(DemangleError::new(
+                DemangleErrorKind::InvalidName,
+                Language::Swift,
+                "not a valid Swift symbol",
+            )),
+            _ => Ok(CStr::from_ptr(buf.as_ptr()).to_string_lossy().to_string()),
         }
     }
 }
 
 #[cfg(not(feature = "swift"))]
-fn try_demangle_swift(_ident: &str, _opts: DemangleOptions) -> Option<String> {
-    None
+fn try_demangle_swift(_ident: &str, _opts: DemangleOptions) -> Result<String, DemangleError> {
+    Err(DemangleError::new(
+        DemangleErrorKind::UnsupportedLanguage,
+        Language::Swift,
+        "Swift demangling is not enabled",
+    ))
 }
 
 fn demangle_objc(ident: &str, _opts: DemangleOptions) -> String {
     ident.to_string()
 }
 
-fn try_demangle_objcpp(ident: &str, opts: DemangleOptions) -> Option<String> {
+fn try_demangle_objcpp(ident: &str, opts: DemangleOptions) -> Result<String, DemangleError> {
     if is_maybe_objc(ident) {
-        Some(demangle_objc(ident, opts))
+        Ok(demangle_objc(ident, opts))
     } else if is_maybe_cpp(ident) {
         try_demangle_cpp(ident, opts)
     } else {
-        None
+        Err(DemangleError::new(
+            DemangleErrorKind::InvalidName,
+            Language::ObjCpp,
+            "neither an Objective-C nor a C++ symbol",
+        ))
     }
 }
 
+/// Returns `true` if names of this language are cleaned up even if they are not mangled.
+///
+/// The Dart and Kotlin compilers decorate plain function names with synthetic prefixes and
+/// suffixes, which are removed regardless of the [`NameMangling`].
+fn prettifies_unmangled(language: Language) -> bool {
+    matches!(language, Language::Dart | Language::Kotlin)
+}
+
+/// Prefixes the Dart VM adds to implicit closures wrapping a function.
+const DART_CLOSURE_PREFIXES: &[&str] = &["[tear-off] ", "[tear-off-extractor] "];
+
+/// Removes the library key from a private Dart name, such as `_State@1234`.
+fn strip_dart_library_key(segment: &str) -> &str {
+    match segment.rfind('@') {
+        Some(index) if index + 1 < segment.len() => {
+            let key = &segment[index + 1..];
+            if key.bytes().all(|b| b.is_ascii_digit()) {
+                &segment[..index]
+            } else {
+                segment
+            }
+        }
+        _ => segment,
+    }
+}
+
+/// Cleans up a function name emitted by the Dart VM's AOT compiler.
+///
+/// Dart names are not mangled, but carry decorations internal to the VM:
+///  - Implicit closures are prefixed with `[tear-off]`, dynamic invocation forwarders with `dyn:`.
+///  - Getters, setters and field initializers are prefixed with `get:`, `set:` and `init:`.
+///  - Private names carry the key of their library, such as `_State@1234`.
+///  - Extension members are separated with `|`, and unnamed constructors end with a dot.
+///
+/// The result is rendered as `Class.method`.
+fn demangle_dart(ident: &str, _opts: DemangleOptions) -> String {
+    let mut ident = ident;
+    while let Some(stripped) = DART_CLOSURE_PREFIXES
+        .iter()
+        .find_map(|prefix| ident.strip_prefix(prefix))
+    {
+        ident = stripped;
+    }
+
+    let mut demangled = String::with_capacity(ident.len());
+    for segment in ident.split(['.', '|']) {
+        if segment.is_empty() {
+            continue;
+        }
+
+        let segment = segment.strip_prefix("dyn:").unwrap_or(segment);
+        let (segment, suffix) = if let Some(getter) = segment.strip_prefix("get:") {
+            (getter, "")
+        } else if let Some(setter) = segment.strip_prefix("set:") {
+            (setter, "=")
+        } else if let Some(field) = segment.strip_prefix("init:") {
+            (field, "")
+        } else {
+            (segment, "")
+        };
+
+        if !demangled.is_empty() {
+            demangled.push('.');
+        }
+        demangled.push_str(strip_dart_library_key(segment));
+        demangled.push_str(suffix);
+    }
+
+    demangled
+}
+
+/// Demangles a Kotlin/Native name of the form `kfun:package.Class#method(params){}ReturnType`.
+fn try_demangle_kotlin_native(mangled: &str, opts: DemangleOptions) -> Option<String> {
+    let (owner, signature) = mangled.split_at(mangled.find('#')?);
+    let signature = &signature[1..];
+
+    let params_start = signature.find('(')?;
+    let params_end = params_start + signature[params_start..].find("){")?;
+    let type_params_end = params_end + signature[params_end..].find('}')?;
+
+    let name = &signature[..params_start];
+    let params = &signature[params_start + 1..params_end];
+    let return_type = &signature[type_params_end + 1..];
+
+    let mut demangled = String::with_capacity(mangled.len());
+    if !owner.is_empty() {
+        demangled.push_str(owner);
+        demangled.push('.');
+    }
+    demangled.push_str(name);
+
+    if opts.parameters {
+        demangled.push('(');
+        demangled.push_str(&params.replace(';', ", "));
+        demangled.push(')');
+    }
+
+    if opts.return_type && !return_type.is_empty() {
+        demangled.push_str(": ");
+        demangled.push_str(return_type);
+    }
+
+    Some(demangled)
+}
+
+/// Removes the suffix the Kotlin compiler appends to functions taking or returning inline classes.
+///
+/// Such functions end in `-impl` or a dash followed by a 7 character hash, for example
+/// `getValue-impl` or `foo-7hs9sdf`.
+fn strip_kotlin_hash_suffix(name: &str) -> &str {
+    if let Some(stripped) = name.strip_suffix("-impl") {
+        return stripped;
+    }
+
+    let len = name.len();
+    if len > 8 && name.is_char_boundary(len - 8) {
+        let (stripped, hash) = name.split_at(len - 8);
+        let is_hash = hash[1..]
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'_');
+        if hash.starts_with('-') && is_hash {
+            return stripped;
+        }
+    }
+
+    name
+}
+
+/// Demangles Kotlin/Native names and cleans up synthetic Kotlin/JVM functions.
+///
+/// Kotlin/JVM names are not mangled, but the compiler generates synthetic accessors prefixed with
+/// `access$`, wrappers for default arguments suffixed with `$default`, and hash suffixes for
+/// functions involving inline classes. These are removed from the function name.
+fn try_demangle_kotlin(ident: &str, opts: DemangleOptions) -> Result<String, DemangleError> {
+    if let Some(mangled) = ident.strip_prefix("kfun:") {
+        return try_demangle_kotlin_native(mangled, opts).ok_or_else(|| {
+            DemangleError::new(
+                DemangleErrorKind::InvalidName,
+                Language::Kotlin,
+                "malformed Kotlin/Native function signature",
+            )
+        });
+    }
+
+    let (owner, name) = match ident.rfind('.') {
+        Some(index) => ident.split_at(index + 1),
+        None => ("", ident),
+    };
+
+    let name = name.strip_prefix("access$").unwrap_or(name);
+    let name = name.strip_suffix("$default").unwrap_or(name);
+    Ok(format!("{}{}", owner, strip_kotlin_hash_suffix(name)))
+}
+
+/// Demangles a name, returning `Ok(None)` if it is passed through unchanged.
+///
+/// Names are passed through if they are not mangled, or if they are MD5 hashes of mangled names
+/// that cannot be demangled.
+fn demangle_name(name: &Name<'_>, opts: DemangleOptions) -> Result<Option<String>, DemangleError> {
+    let unmangled = matches!(name.mangling(), NameMangling::Unmangled)
+        && !prettifies_unmangled(name.language());
+    if unmangled || is_maybe_md5(name.as_str()) {
+        return Ok(None);
+    }
+
+    let ident = name.as_str();
+    let demangled = match name.detect_language() {
+        Language::ObjC => Ok(demangle_objc(ident, opts)),
+        Language::ObjCpp => try_demangle_objcpp(ident, opts),
+        Language::Rust => try_demangle_rust(ident, opts),
+        Language::Cpp => try_demangle_cpp(ident, opts),
+        Language::Swift => try_demangle_swift(ident, opts),
+        Language::Dart => Ok(demangle_dart(ident, opts)),
+        Language::Kotlin => try_demangle_kotlin(ident, opts),
+        Language::Unknown => Err(DemangleError::new(
+            DemangleErrorKind::UnknownLanguage,
+            Language::Unknown,
+            "cannot detect the language of the name",
+        )),
+        language => Err(DemangleError::new(
+            DemangleErrorKind::UnsupportedLanguage,
+            language,
+            "demangling is not supported for this language",
+        )),
+    };
+
+    demangled.map(Some)
+}
+
 /// An extension trait on `Name` for demangling names.
 ///
 /// See the [module level documentation] for a list of supported languages.
@@ -432,26 +769,25 @@ impl<'a> Demangle for Name<'a> {
             return Language::Swift;
         }
 
+        if self.as_str().starts_with("kfun:") {
+            return Language::Kotlin;
+        }
+
         Language::Unknown
     }
 
     fn demangle(&self, opts: DemangleOptions) -> Option<String> {
-        if matches!(self.mangling(), NameMangling::Unmangled) || is_maybe_md5(self.as_str()) {
-            return Some(self.to_string());
-        }
-
-        match self.detect_language() {
-            Language::ObjC => Some(demangle_objc(self.as_str(), opts)),
-            Language::ObjCpp => try_demangle_objcpp(self.as_str(), opts),
-            Language::Rust => try_demangle_rust(self.as_str(), opts),
-            Language::Cpp => try_demangle_cpp(self.as_str(), opts),
-            Language::Swift => try_demangle_swift(self.as_str(), opts),
-            _ => None,
+        match demangle_name(self, opts) {
+            Ok(Some(demangled)) => Some(demangled),
+            Ok(None) => Some(self.to_string()),
+            Err(_) => None,
         }
     }
 
     fn try_demangle(&self, opts: DemangleOptions) -> Cow<'_, str> {
-        if matches!(self.mangling(), NameMangling::Unmangled) {
+        if matches!(self.mangling(), NameMangling::Unmangled)
+            && !prettifies_unmangled(self.language())
+        {
             return Cow::Borrowed(self.as_str());
         }
         match self.demangle(opts) {
@@ -481,6 +817,176 @@ pub fn demangle(ident: &str) -> Cow<'_, str> {
     }
 }
 
+/// Determines how a [`Demangler`] handles names that cannot be demangled.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum DemangleMode {
+    /// Falls back to the original name, like [`Demangle::try_demangle`].
+    #[default]
+    Lenient,
+    /// Returns a [`DemangleError`] describing why the name could not be demangled.
+    Strict,
+}
+
+/// Statistics on the names processed by a [`Demangler`].
+///
+/// Every name is counted in exactly one of [`demangled`](Self::demangled),
+/// [`unmangled`](Self::unmangled) and [`failed`](Self::failed). Failures are additionally broken
+/// down by their [`DemangleErrorKind`] and the language they were attempted in.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DemangleStats {
+    demangled: u64,
+    unmangled: u64,
+    failed: u64,
+    failed_by_kind: BTreeMap<DemangleErrorKind, u64>,
+    failed_by_language: BTreeMap<Language, u64>,
+}
+
+impl DemangleStats {
+    /// The total number of names processed.
+    pub fn total(&self) -> u64 {
+        self.demangled + self.unmangled + self.failed
+    }
+
+    /// The number of names that were demangled successfully.
+    pub fn demangled(&self) -> u64 {
+        self.demangled
+    }
+
+    /// The number of names that were passed through because they are not mangled.
+    pub fn unmangled(&self) -> u64 {
+        self.unmangled
+    }
+
+    /// The number of names that could not be demangled.
+    pub fn failed(&self) -> u64 {
+        self.failed
+    }
+
+    /// The number of names that could not be demangled for the given reason.
+    pub fn failed_with(&self, kind: DemangleErrorKind) -> u64 {
+        self.failed_by_kind.get(&kind).copied().unwrap_or(0)
+    }
+
+    /// The number of names in the given language that could not be demangled.
+    pub fn failed_in(&self, language: Language) -> u64 {
+        self.failed_by_language.get(&language).copied().unwrap_or(0)
+    }
+
+    /// Returns an iterator over the number of failures for every error kind that occurred.
+    pub fn failures(&self) -> impl Iterator<Item = (DemangleErrorKind, u64)> + '_ {
+        self.failed_by_kind
+            .iter()
+            .map(|(kind, count)| (*kind, *count))
+    }
+
+    /// Adds the counters of another instance to these statistics.
+    ///
+    /// This allows to combine the statistics of multiple demanglers, for instance when demangling
+    /// on multiple threads.
+    pub fn merge(&mut self, other: &Self) {
+        self.demangled += other.demangled;
+        self.unmangled += other.unmangled;
+        self.failed += other.failed;
+        for (kind, count) in &other.failed_by_kind {
+            *self.failed_by_kind.entry(*kind).or_default() += count;
+        }
+        for (language, count) in &other.failed_by_language {
+            *self.failed_by_language.entry(*language).or_default() += count;
+        }
+    }
+
+    fn record_failure(&mut self, error: &DemangleError) {
+        self.failed += 1;
+        *self.failed_by_kind.entry(error.kind()).or_default() += 1;
+        *self.failed_by_language.entry(error.language()).or_default() += 1;
+    }
+}
+
+/// A demangler that reports errors and keeps statistics on the names it processes.
+///
+/// In [lenient](DemangleMode::Lenient) mode, names that cannot be demangled are returned
+/// unchanged, just like [`Demangle::try_demangle`]. In [strict](DemangleMode::Strict) mode, a
+/// [`DemangleError`] describes why demangling failed. In both modes, the outcome of every name is
+/// recorded in [`DemangleStats`].
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "cpp")] {
+/// use symbolic_common::Name;
+/// use symbolic_demangle::{DemangleErrorKind, DemangleMode, DemangleOptions, Demangler};
+///
+/// let mut demangler = Demangler::new(DemangleOptions::name_only()).mode(DemangleMode::Strict);
+///
+/// let name = Name::from("_ZN3foo3barEv");
+/// assert_eq!(demangler.demangle(&name).unwrap(), "foo::bar");
+///
+/// let error = demangler.demangle(&Name::from("_ZN3foo")).unwrap_err();
+/// assert_eq!(error.kind(), DemangleErrorKind::InvalidName);
+///
+/// assert_eq!(demangler.stats().demangled(), 1);
+/// assert_eq!(demangler.stats().failed_with(DemangleErrorKind::InvalidName), 1);
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Demangler {
+    options: DemangleOptions,
+    mode: DemangleMode,
+    stats: DemangleStats,
+}
+
+impl Demangler {
+    /// Creates a new lenient demangler with the given options.
+    pub fn new(options: DemangleOptions) -> Self {
+        Self {
+            options,
+            mode: DemangleMode::default(),
+            stats: DemangleStats::default(),
+        }
+    }
+
+    /// Sets how names that cannot be demangled are handled.
+    pub fn mode(mut self, mode: DemangleMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Demangles the name and records the outcome in the statistics.
+    ///
+    /// Unmangled names are returned as they are. If demangling fails, this returns the original
+    /// name in [lenient](DemangleMode::Lenient) mode and an error in [strict](DemangleMode::Strict)
+    /// mode.
+    pub fn demangle<'n>(&mut self, name: &'n Name<'_>) -> Result<Cow<'n, str>, DemangleError> {
+        match demangle_name(name, self.options) {
+            Ok(Some(demangled)) => {
+                self.stats.demangled += 1;
+                Ok(Cow::Owned(demangled))
+            }
+            Ok(None) => {
+                self.stats.unmangled += 1;
+                Ok(Cow::Borrowed(name.as_str()))
+            }
+            Err(error) => {
+                self.stats.record_failure(&error);
+                match self.mode {
+                    DemangleMode::Lenient => Ok(Cow::Borrowed(name.as_str())),
+                    DemangleMode::Strict => Err(error),
+                }
+            }
+        }
+    }
+
+    /// Returns the statistics of all names demangled so far.
+    pub fn stats(&self) -> &DemangleStats {
+        &self.stats
+    }
+
+    /// Returns the statistics of all names demangled so far and resets them.
+    pub fn take_stats(&mut self) -> DemangleStats {
+        std::mem::take(&mut self.stats)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -0,0 +1,108 @@
+//! Tests for classifying stack frames by their function names.
+
+use symbolic_common::{Language, Name, NameMangling};
+use symbolic_demangle::{FrameClassifier, FrameKind};
+
+use similar_asserts::assert_eq;
+
+fn classify(name: &str) -> FrameKind {
+    FrameClassifier::new().classify(&Name::from(name))
+}
+
+#[test]
+fn test_rust_panic_frames() {
+    assert_eq!(
+        classify("_ZN4core9panicking9panic_fmt17h0123456789abcdefE"),
+        FrameKind::Panic
+    );
+    assert_eq!(
+        classify("_ZN3std9panicking20rust_panic_with_hook17h0123456789abcdefE"),
+        FrameKind::Panic
+    );
+    assert_eq!(
+        classify("_ZN4core6option15Option$LT$T$GT$6unwrap17h0123456789abcdefE"),
+        FrameKind::Panic
+    );
+    assert_eq!(
+        classify("_ZN3std10sys_common9backtrace26__rust_end_short_backtrace17h0123456789abcdefE"),
+        FrameKind::Panic
+    );
+    assert_eq!(classify("rust_begin_unwind"), FrameKind::Panic);
+}
+
+#[test]
+fn test_rust_normal_frames() {
+    assert_eq!(
+        classify("_ZN5crash4main17h0123456789abcdefE"),
+        FrameKind::Normal
+    );
+    // `catch_unwind` is part of every thread's entry point and does not raise panics.
+    assert_eq!(
+        classify("_ZN3std9panicking3try17h0123456789abcdefE"),
+        FrameKind::Normal
+    );
+    // Only whole path segments match.
+    assert_eq!(
+        classify("_ZN4core14panicking_mine3foo17h0123456789abcdefE"),
+        FrameKind::Normal
+    );
+}
+
+#[test]
+fn test_demangled_names() {
+    let name = Name::new(
+        "core::result::unwrap_failed",
+        NameMangling::Unmangled,
+        Language::Rust,
+    );
+    assert_eq!(FrameClassifier::new().classify(&name), FrameKind::Panic);
+
+    // Rules only apply to names of their language.
+    let name = Name::new("abort", NameMangling::Unmangled, Language::Rust);
+    assert_eq!(FrameClassifier::new().classify(&name), FrameKind::Normal);
+}
+
+#[test]
+fn test_cpp_and_c_frames() {
+    assert_eq!(classify("_ZSt9terminatev"), FrameKind::Panic);
+    assert_eq!(classify("__cxa_throw"), FrameKind::Panic);
+    assert_eq!(classify("abort"), FrameKind::Panic);
+    assert_eq!(classify("_ZN3foo5abortEv"), FrameKind::Normal);
+}
+
+#[test]
+fn test_custom_rules() {
+    let mut classifier = FrameClassifier::empty();
+    assert_eq!(classifier.classify(&Name::from("abort")), FrameKind::Normal);
+
+    classifier.add_rule(Language::Cpp, "my::fatal", FrameKind::Panic);
+    assert_eq!(
+        classifier.classify(&Name::from("_ZN2my5fatal6reportEv")),
+        FrameKind::Panic
+    );
+}
+
+#[test]
+fn test_panic_frames() {
+    let classifier = FrameClassifier::new();
+    let names = |names: &[&'static str]| names.iter().map(|n| Name::from(*n)).collect::<Vec<_>>();
+
+    let frames = names(&[
+        "__pthread_kill_implementation",
+        "raise",
+        "abort",
+        "_ZN3std3sys4unix14abort_internal17h0123456789abcdefE",
+        "_ZN3std9panicking20rust_panic_with_hook17h0123456789abcdefE",
+        "_ZN4core9panicking9panic_fmt17h0123456789abcdefE",
+        "_ZN5crash4main17h0123456789abcdefE",
+        "_ZN3std9panicking3try17h0123456789abcdefE",
+    ]);
+    assert_eq!(classifier.panic_frames(&frames[3..]), 3);
+    assert_eq!(classifier.panic_frames(&frames), 6);
+
+    let frames = names(&["_ZN5crash4main17h0123456789abcdefE"]);
+    assert_eq!(classifier.panic_frames(&frames), 0);
+
+    let frames = names(&["rust_begin_unwind", "rust_panic"]);
+    assert_eq!(classifier.panic_frames(&frames), 2);
+}
@@ -0,0 +1,44 @@
+//! Dart Name Cleanup Tests
+//! Names were taken from DWARF emitted by the Dart VM's AOT compiler.
+
+#[macro_use]
+mod utils;
+
+use symbolic_common::{Language, Name, NameMangling};
+use symbolic_demangle::{Demangle, DemangleOptions};
+
+use similar_asserts::assert_eq;
+
+#[test]
+fn test_demangle_dart() {
+    assert_demangle!(Language::Dart, DemangleOptions::complete(), {
+        "main" => "main",
+        "MyApp.build" => "MyApp.build",
+        "_MyHomePageState@17160356._incrementCounter" => "_MyHomePageState._incrementCounter",
+        "_MyHomePageState@17160356.build.<anonymous closure>" => "_MyHomePageState.build.<anonymous closure>",
+        "[tear-off] _MyHomePageState@17160356._incrementCounter" => "_MyHomePageState._incrementCounter",
+        "[tear-off-extractor] _Future@4048458.get:then" => "_Future.then",
+        "State.get:context" => "State.context",
+        "_Counter@1234.set:value" => "_Counter.value=",
+        "Config.init:defaults" => "Config.defaults",
+        "List.dyn:add" => "List.add",
+        "StringUtils|capitalize" => "StringUtils.capitalize",
+        "Point." => "Point",
+        "Point.fromJson" => "Point.fromJson",
+        "user@example" => "user@example",
+    });
+}
+
+#[test]
+fn test_demangle_dart_unmangled() {
+    let name = Name::new(
+        "_MyHomePageState@17160356.get:widget",
+        NameMangling::Unmangled,
+        Language::Dart,
+    );
+
+    assert_eq!(
+        name.try_demangle(DemangleOptions::name_only()),
+        "_MyHomePageState.widget"
+    );
+}
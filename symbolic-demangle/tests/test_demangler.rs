@@ -0,0 +1,98 @@
+//! Tests for strict demangling and demangling statistics.
+
+use symbolic_common::{Language, Name, NameMangling};
+use symbolic_demangle::{DemangleErrorKind, DemangleMode, DemangleOptions, Demangler};
+
+use similar_asserts::assert_eq;
+
+#[test]
+fn test_lenient_fallback() {
+    let mut demangler = Demangler::new(DemangleOptions::name_only());
+
+    let name = Name::from("_ZN3foo");
+    assert_eq!(demangler.demangle(&name).unwrap(), "_ZN3foo");
+
+    let stats = demangler.stats();
+    assert_eq!(stats.total(), 1);
+    assert_eq!(stats.failed(), 1);
+    assert_eq!(stats.failed_with(DemangleErrorKind::InvalidName), 1);
+    assert_eq!(stats.failed_in(Language::Cpp), 1);
+}
+
+#[test]
+fn test_strict_invalid_cpp() {
+    let mut demangler = Demangler::new(DemangleOptions::name_only()).mode(DemangleMode::Strict);
+
+    let error = demangler.demangle(&Name::from("_ZN3foo")).unwrap_err();
+    assert_eq!(error.kind(), DemangleErrorKind::InvalidName);
+    assert_eq!(error.language(), Language::Cpp);
+    assert!(!error.reason().is_empty());
+}
+
+#[test]
+fn test_strict_invalid_msvc_position() {
+    let mut demangler = Demangler::new(DemangleOptions::complete()).mode(DemangleMode::Strict);
+
+    let error = demangler.demangle(&Name::from("?foo@@YAX")).unwrap_err();
+    assert_eq!(error.kind(), DemangleErrorKind::InvalidName);
+    assert_eq!(error.language(), Language::Cpp);
+    assert!(error.position().is_some());
+}
+
+#[test]
+fn test_strict_unknown_language() {
+    let mut demangler = Demangler::new(DemangleOptions::complete()).mode(DemangleMode::Strict);
+
+    let error = demangler.demangle(&Name::from("main")).unwrap_err();
+    assert_eq!(error.kind(), DemangleErrorKind::UnknownLanguage);
+    assert_eq!(error.language(), Language::Unknown);
+    assert_eq!(error.position(), None);
+}
+
+#[test]
+fn test_strict_unsupported_language() {
+    let mut demangler = Demangler::new(DemangleOptions::complete()).mode(DemangleMode::Strict);
+
+    let name = Name::new("main.main", NameMangling::Mangled, Language::Go);
+    let error = demangler.demangle(&name).unwrap_err();
+    assert_eq!(error.kind(), DemangleErrorKind::UnsupportedLanguage);
+    assert_eq!(error.language(), Language::Go);
+}
+
+#[test]
+fn test_stats() {
+    let mut demangler = Demangler::new(DemangleOptions::name_only()).mode(DemangleMode::Strict);
+
+    let names = [
+        Name::from("_ZN3foo3barEv"),
+        Name::from("_ZN3std2io4Read11read_to_end17hb85a0f6802e14499E"),
+        Name::new("foo", NameMangling::Unmangled, Language::C),
+        Name::from("??@8ba8d245c9eca390356129098dbe9f73@"),
+        Name::from("_ZN3foo"),
+        Name::from("unknown"),
+    ];
+
+    for name in &names {
+        demangler.demangle(name).ok();
+    }
+
+    let stats = demangler.take_stats();
+    assert_eq!(stats.total(), 6);
+    assert_eq!(stats.demangled(), 2);
+    assert_eq!(stats.unmangled(), 2);
+    assert_eq!(stats.failed(), 2);
+    assert_eq!(
+        stats.failures().collect::<Vec<_>>(),
+        [
+            (DemangleErrorKind::UnknownLanguage, 1),
+            (DemangleErrorKind::InvalidName, 1)
+        ]
+    );
+
+    assert_eq!(demangler.stats().total(), 0);
+
+    let mut merged = stats.clone();
+    merged.merge(&stats);
+    assert_eq!(merged.total(), 12);
+    assert_eq!(merged.failed_with(DemangleErrorKind::InvalidName), 2);
+}
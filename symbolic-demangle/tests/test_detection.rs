@@ -58,6 +58,11 @@ fn test_ambiguous_cpp_rust() {
     );
 }
 
+#[test]
+fn test_kotlin_native() {
+    assert_language("kfun:com.example#main(){}", Language::Kotlin);
+}
+
 #[cfg(feature = "swift")]
 mod swift_tests {
     use super::*;
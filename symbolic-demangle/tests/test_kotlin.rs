@@ -0,0 +1,42 @@
+//! Kotlin Demangling Tests
+//! Kotlin/Native names were taken from binaries compiled with Kotlin 1.6.
+
+#[macro_use]
+mod utils;
+
+use symbolic_common::Language;
+use symbolic_demangle::DemangleOptions;
+
+#[test]
+fn test_demangle_kotlin_native() {
+    assert_demangle!(Language::Kotlin, DemangleOptions::complete(), {
+        "kfun:#main(){}" => "main()",
+        "kfun:#main(kotlin.Array<kotlin.String>){}" => "main(kotlin.Array<kotlin.String>)",
+        "kfun:com.example.Greeting#greeting(){}kotlin.String" => "com.example.Greeting.greeting(): kotlin.String",
+        "kfun:com.example#sum(kotlin.Int;kotlin.Int){}kotlin.Int" => "com.example.sum(kotlin.Int, kotlin.Int): kotlin.Int",
+        "kfun:kotlin.collections.ArrayList#get(kotlin.Int){}1:0" => "kotlin.collections.ArrayList.get(kotlin.Int): 1:0",
+        "kfun:com.example#identity(0:0){0\u{a7}<kotlin.Any?>}0:0" => "com.example.identity(0:0): 0:0",
+        "kfun:com.example.Counter#<get-value>(){}kotlin.Int" => "com.example.Counter.<get-value>(): kotlin.Int",
+        "kfun:invalid" => "<demangling failed>",
+    });
+}
+
+#[test]
+fn test_demangle_kotlin_native_short() {
+    assert_demangle!(Language::Kotlin, DemangleOptions::name_only(), {
+        "kfun:#main(){}" => "main",
+        "kfun:com.example#sum(kotlin.Int;kotlin.Int){}kotlin.Int" => "com.example.sum",
+    });
+}
+
+#[test]
+fn test_demangle_kotlin_jvm() {
+    assert_demangle!(Language::Kotlin, DemangleOptions::complete(), {
+        "com.example.MainActivity.onCreate" => "com.example.MainActivity.onCreate",
+        "com.example.MainActivity.access$render" => "com.example.MainActivity.render",
+        "com.example.Api.fetch$default" => "com.example.Api.fetch",
+        "com.example.Duration.getValue-impl" => "com.example.Duration.getValue",
+        "com.example.TimerKt.schedule-LRDsOJo" => "com.example.TimerKt.schedule",
+        "com.example.Api.fetch-remote" => "com.example.Api.fetch-remote",
+    });
+}
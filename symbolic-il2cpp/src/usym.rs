@@ -8,7 +8,7 @@ use std::str::FromStr;
 use std::{fmt, mem, ptr};
 
 use symbolic_common::{Arch, DebugId};
-use symbolic_debuginfo::FileInfo;
+use symbolic_debuginfo::{FileInfo, FilePathKind};
 use thiserror::Error;
 
 /// The error type for [`UsymError`].
@@ -431,8 +431,9 @@ impl<'a> UsymSymbols<'a> {
                 // the debuginfo crate
                 let (dir, name) = symbolic_common::split_path_bytes(file_bytes);
                 Some(FileInfo {
-                    name,
-                    dir: dir.unwrap_or_default(),
+                    name: Cow::Borrowed(name),
+                    dir: Cow::Borrowed(dir.unwrap_or_default()),
+                    path_kind: FilePathKind::Unknown,
                 })
             }
         };
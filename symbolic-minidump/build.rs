@@ -17,6 +17,14 @@ fn main() {
         assert!(status.success(), "Failed to install git submodules");
     }
 
+    // RISC-V stack walking requires a Breakpad revision with `StackFrameRISCV` and
+    // `StackFrameRISCV64`. Older checkouts process the remaining architectures without it.
+    let riscv = Path::new("third_party/breakpad/src/processor/stackwalker_riscv.cc").exists();
+    println!("cargo:rustc-check-cfg=cfg(breakpad_riscv)");
+    if riscv {
+        println!("cargo:rustc-cfg=breakpad_riscv");
+    }
+
     cc::Build::new()
         .warnings(false)
         .flag_if_supported("-Wno-tautological-constant-out-of-range-compare")
@@ -36,7 +44,8 @@ fn main() {
         .file("third_party/breakpad/src/third_party/libdisasm/x86_operand_list.c")
         .compile("disasm");
 
-    cc::Build::new()
+    let mut build = cc::Build::new();
+    build
         .cpp(true)
         .warnings(false)
         .flag_if_supported("-std=c++11")
@@ -83,8 +92,17 @@ fn main() {
         .file("third_party/breakpad/src/processor/symbolic_constants_win.cc")
         // Symbolic bindings
         .file("cpp/c_string.cpp")
+        .file("cpp/cfi_cache_resolver.cpp")
         .file("cpp/data_structures.cpp")
         .file("cpp/mmap_symbol_supplier.cpp")
-        .file("cpp/processor.cpp")
-        .compile("breakpad");
+        .file("cpp/processor.cpp");
+
+    if riscv {
+        build
+            .define("SYMBOLIC_BREAKPAD_RISCV", None)
+            .file("third_party/breakpad/src/processor/stackwalker_riscv.cc")
+            .file("third_party/breakpad/src/processor/stackwalker_riscv64.cc");
+    }
+
+    build.compile("breakpad");
 }
@@ -0,0 +1,91 @@
+#include <memory>
+
+#include "cpp/cfi_cache_resolver.h"
+#include "google_breakpad/processor/code_module.h"
+#include "google_breakpad/processor/stack_frame.h"
+#include "processor/cfi_frame_info.h"
+#include "processor/windows_frame_info.h"
+
+using google_breakpad::BasicSourceLineResolver;
+using google_breakpad::CFIFrameInfo;
+using google_breakpad::CFIFrameInfoParseHandler;
+using google_breakpad::CFIRuleParser;
+using google_breakpad::StackFrame;
+using google_breakpad::WindowsFrameInfo;
+
+CfiCacheResolver::CfiCacheResolver(size_t symbol_count,
+                                   const symbol_entry_t *symbols) {
+    for (const symbol_entry_t *entry = symbols; entry < symbols + symbol_count;
+         ++entry) {
+        if (entry->cfi_cache != nullptr && entry->cfi_lookup != nullptr) {
+            entries[entry->debug_identifier] = entry;
+        }
+    }
+}
+
+const symbol_entry_t *CfiCacheResolver::FindEntry(const StackFrame *frame) {
+    if (frame->module == nullptr) {
+        return nullptr;
+    }
+
+    auto it = entries.find(frame->module->debug_identifier());
+    return it == entries.end() ? nullptr : it->second;
+}
+
+bool CfiCacheResolver::Lookup(const symbol_entry_t *entry,
+                              const StackFrame *frame,
+                              int kind,
+                              std::string *rules) {
+    uint64_t address = frame->instruction - frame->module->base_address();
+
+    int found_kind = CFI_LOOKUP_NONE;
+    size_t size = 0;
+    const char *data =
+        entry->cfi_lookup(entry->cfi_cache, address, &found_kind, &size);
+
+    if (found_kind != kind) {
+        return false;
+    }
+
+    rules->assign(data, size);
+    return true;
+}
+
+WindowsFrameInfo *CfiCacheResolver::FindWindowsFrameInfo(
+    const StackFrame *frame) {
+    const symbol_entry_t *entry = FindEntry(frame);
+    if (entry == nullptr) {
+        return BasicSourceLineResolver::FindWindowsFrameInfo(frame);
+    }
+
+    std::string record;
+    if (!Lookup(entry, frame, CFI_LOOKUP_WIN, &record)) {
+        return nullptr;
+    }
+
+    int type = 0;
+    uint64_t rva = 0;
+    uint64_t code_size = 0;
+    return WindowsFrameInfo::ParseFromString(record, type, rva, code_size);
+}
+
+CFIFrameInfo *CfiCacheResolver::FindCFIFrameInfo(const StackFrame *frame) {
+    const symbol_entry_t *entry = FindEntry(frame);
+    if (entry == nullptr) {
+        return BasicSourceLineResolver::FindCFIFrameInfo(frame);
+    }
+
+    std::string rules;
+    if (!Lookup(entry, frame, CFI_LOOKUP_CFI, &rules)) {
+        return nullptr;
+    }
+
+    std::unique_ptr<CFIFrameInfo> frame_info(new CFIFrameInfo());
+    CFIFrameInfoParseHandler handler(frame_info.get());
+    CFIRuleParser parser(&handler);
+    if (!parser.Parse(rules)) {
+        return nullptr;
+    }
+
+    return frame_info.release();
+}
@@ -0,0 +1,38 @@
+#ifndef SENTRY_CFI_CACHE_RESOLVER_H
+#define SENTRY_CFI_CACHE_RESOLVER_H
+
+#include <map>
+#include <string>
+
+#include "cpp/processor.h"
+#include "google_breakpad/processor/basic_source_line_resolver.h"
+
+/// Source line resolver that looks up unwind rules of binary CFI caches
+/// directly, instead of parsing them from Breakpad ASCII symbols. Modules
+/// without a binary cache are handled by the BasicSourceLineResolver.
+class CfiCacheResolver : public google_breakpad::BasicSourceLineResolver {
+   public:
+    explicit CfiCacheResolver(size_t symbol_count,
+                              const symbol_entry_t *symbols);
+
+    virtual ~CfiCacheResolver() {
+    }
+
+    virtual google_breakpad::WindowsFrameInfo *FindWindowsFrameInfo(
+        const google_breakpad::StackFrame *frame);
+
+    virtual google_breakpad::CFIFrameInfo *FindCFIFrameInfo(
+        const google_breakpad::StackFrame *frame);
+
+   private:
+    const symbol_entry_t *FindEntry(const google_breakpad::StackFrame *frame);
+
+    bool Lookup(const symbol_entry_t *entry,
+                const google_breakpad::StackFrame *frame,
+                int kind,
+                std::string *rules);
+
+    std::map<std::string, const symbol_entry_t *> entries;
+};
+
+#endif
@@ -13,6 +13,10 @@ using google_breakpad::StackFrameARM64;
 using google_breakpad::StackFrameMIPS;
 using google_breakpad::StackFramePPC;
 using google_breakpad::StackFramePPC64;
+#ifdef SYMBOLIC_BREAKPAD_RISCV
+using google_breakpad::StackFrameRISCV;
+using google_breakpad::StackFrameRISCV64;
+#endif
 using google_breakpad::StackFrameX86;
 
 void process_state_delete(process_state_t *state) {
@@ -205,6 +209,82 @@ int stack_frame_trust(const stack_frame_t *frame) {
     return stack_frame_t::cast(frame)->trust;
 }
 
+#ifdef SYMBOLIC_BREAKPAD_RISCV
+/// Appends the recovered registers of a 32-bit or 64-bit RISC-V frame.
+///
+/// Both frame types declare the same validity flags and register names, and
+/// only differ in the size of their register values.
+template <typename Frame>
+static void push_riscv_registers(const Frame *frame,
+                                 uint8_t reg_size,
+                                 std::vector<regval_t> &registers) {
+    if (frame->context_validity & Frame::CONTEXT_VALID_PC)
+        registers.push_back({"pc", frame->context.pc, reg_size});
+    if (frame->context_validity & Frame::CONTEXT_VALID_RA)
+        registers.push_back({"ra", frame->context.ra, reg_size});
+    if (frame->context_validity & Frame::CONTEXT_VALID_SP)
+        registers.push_back({"sp", frame->context.sp, reg_size});
+    if (frame->context_validity & Frame::CONTEXT_VALID_GP)
+        registers.push_back({"gp", frame->context.gp, reg_size});
+    if (frame->context_validity & Frame::CONTEXT_VALID_TP)
+        registers.push_back({"tp", frame->context.tp, reg_size});
+    if (frame->context_validity & Frame::CONTEXT_VALID_T0)
+        registers.push_back({"t0", frame->context.t0, reg_size});
+    if (frame->context_validity & Frame::CONTEXT_VALID_T1)
+        registers.push_back({"t1", frame->context.t1, reg_size});
+    if (frame->context_validity & Frame::CONTEXT_VALID_T2)
+        registers.push_back({"t2", frame->context.t2, reg_size});
+    if (frame->context_validity & Frame::CONTEXT_VALID_S0)
+        registers.push_back({"s0", frame->context.s0, reg_size});
+    if (frame->context_validity & Frame::CONTEXT_VALID_S1)
+        registers.push_back({"s1", frame->context.s1, reg_size});
+    if (frame->context_validity & Frame::CONTEXT_VALID_A0)
+        registers.push_back({"a0", frame->context.a0, reg_size});
+    if (frame->context_validity & Frame::CONTEXT_VALID_A1)
+        registers.push_back({"a1", frame->context.a1, reg_size});
+    if (frame->context_validity & Frame::CONTEXT_VALID_A2)
+        registers.push_back({"a2", frame->context.a2, reg_size});
+    if (frame->context_validity & Frame::CONTEXT_VALID_A3)
+        registers.push_back({"a3", frame->context.a3, reg_size});
+    if (frame->context_validity & Frame::CONTEXT_VALID_A4)
+        registers.push_back({"a4", frame->context.a4, reg_size});
+    if (frame->context_validity & Frame::CONTEXT_VALID_A5)
+        registers.push_back({"a5", frame->context.a5, reg_size});
+    if (frame->context_validity & Frame::CONTEXT_VALID_A6)
+        registers.push_back({"a6", frame->context.a6, reg_size});
+    if (frame->context_validity & Frame::CONTEXT_VALID_A7)
+        registers.push_back({"a7", frame->context.a7, reg_size});
+    if (frame->context_validity & Frame::CONTEXT_VALID_S2)
+        registers.push_back({"s2", frame->context.s2, reg_size});
+    if (frame->context_validity & Frame::CONTEXT_VALID_S3)
+        registers.push_back({"s3", frame->context.s3, reg_size});
+    if (frame->context_validity & Frame::CONTEXT_VALID_S4)
+        registers.push_back({"s4", frame->context.s4, reg_size});
+    if (frame->context_validity & Frame::CONTEXT_VALID_S5)
+        registers.push_back({"s5", frame->context.s5, reg_size});
+    if (frame->context_validity & Frame::CONTEXT_VALID_S6)
+        registers.push_back({"s6", frame->context.s6, reg_size});
+    if (frame->context_validity & Frame::CONTEXT_VALID_S7)
+        registers.push_back({"s7", frame->context.s7, reg_size});
+    if (frame->context_validity & Frame::CONTEXT_VALID_S8)
+        registers.push_back({"s8", frame->context.s8, reg_size});
+    if (frame->context_validity & Frame::CONTEXT_VALID_S9)
+        registers.push_back({"s9", frame->context.s9, reg_size});
+    if (frame->context_validity & Frame::CONTEXT_VALID_S10)
+        registers.push_back({"s10", frame->context.s10, reg_size});
+    if (frame->context_validity & Frame::CONTEXT_VALID_S11)
+        registers.push_back({"s11", frame->context.s11, reg_size});
+    if (frame->context_validity & Frame::CONTEXT_VALID_T3)
+        registers.push_back({"t3", frame->context.t3, reg_size});
+    if (frame->context_validity & Frame::CONTEXT_VALID_T4)
+        registers.push_back({"t4", frame->context.t4, reg_size});
+    if (frame->context_validity & Frame::CONTEXT_VALID_T5)
+        registers.push_back({"t5", frame->context.t5, reg_size});
+    if (frame->context_validity & Frame::CONTEXT_VALID_T6)
+        registers.push_back({"t6", frame->context.t6, reg_size});
+}
+#endif
+
 regval_t *stack_frame_registers(const stack_frame_t *frame,
                                 uint32_t family,
                                 size_t *size_out) {
@@ -545,6 +625,21 @@ regval_t *stack_frame_registers(const stack_frame_t *frame,
             break;
         }
 
+#ifdef SYMBOLIC_BREAKPAD_RISCV
+        case 11: {  // Riscv32
+            push_riscv_registers(
+                reinterpret_cast<const StackFrameRISCV *>(frame), 4, registers);
+            break;
+        }
+
+        case 12: {  // Riscv64
+            push_riscv_registers(
+                reinterpret_cast<const StackFrameRISCV64 *>(frame), 8,
+                registers);
+            break;
+        }
+#endif
+
         case 0:  // Unknown
         default:
             break;  // leave registers empty
@@ -8,10 +8,15 @@ using google_breakpad::SystemInfo;
 
 MmapSymbolSupplier::MmapSymbolSupplier(size_t symbol_count,
                                        const symbol_entry_t *symbols) {
+    // Binary CFI caches come without symbol data. They are still registered
+    // with an empty symbol file, so that the module counts as loaded, while
+    // the CfiCacheResolver looks up their unwind rules.
     for (const symbol_entry_t *entry = symbols; entry < symbols + symbol_count;
          ++entry) {
         cache[entry->debug_identifier] =
-            std::string(entry->symbol_data, entry->symbol_size);
+            entry->symbol_size > 0
+                ? std::string(entry->symbol_data, entry->symbol_size)
+                : std::string();
     }
 }
 
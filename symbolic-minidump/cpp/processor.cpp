@@ -1,14 +1,13 @@
-#include "google_breakpad/processor/basic_source_line_resolver.h"
 #include "google_breakpad/processor/minidump.h"
 #include "google_breakpad/processor/minidump_processor.h"
 #include "google_breakpad/processor/process_state.h"
 
+#include "cpp/cfi_cache_resolver.h"
 #include "cpp/data_definitions.h"
 #include "cpp/memstream.h"
 #include "cpp/mmap_symbol_supplier.h"
 #include "cpp/processor.h"
 
-using google_breakpad::BasicSourceLineResolver;
 using google_breakpad::Minidump;
 using google_breakpad::MinidumpProcessor;
 using google_breakpad::ProcessState;
@@ -29,7 +28,7 @@ process_state_t *process_minidump(const char *buffer,
         return nullptr;
     }
 
-    BasicSourceLineResolver resolver;
+    CfiCacheResolver resolver(symbol_count, symbols);
     MmapSymbolSupplier supplier(symbol_count, symbols);
     MinidumpProcessor processor(&supplier, &resolver);
 
@@ -2,12 +2,31 @@
 #define SENTRY_PROCESSOR_H
 
 #include <cstddef>
+#include <cstdint>
 #include "cpp/data_structures.h"
 
 #ifdef __cplusplus
 extern "C" {
 #endif
 
+/// No unwind rules cover the looked up address
+#define CFI_LOOKUP_NONE 0
+
+/// The lookup returned the rules of a STACK CFI record
+#define CFI_LOOKUP_CFI 1
+
+/// The lookup returned the fields of a STACK WIN record
+#define CFI_LOOKUP_WIN 2
+
+/// Looks up the unwind rules covering a module-relative address in a binary
+/// CFI cache. Returns a buffer of size_out bytes and stores one of the
+/// CFI_LOOKUP_* constants in kind_out. The buffer is valid until the next
+/// lookup in the same cache.
+typedef const char *(*cfi_lookup_t)(const void *cfi_cache,
+                                    uint64_t address,
+                                    int *kind_out,
+                                    size_t *size_out);
+
 /// Data transfer object for symbols in memory
 struct symbol_entry_t {
     /// The debug identifier of the code module these symbols are for
@@ -18,6 +37,12 @@ struct symbol_entry_t {
 
     /// Raw data of the symbol file passed to the symbolizer
     const char *symbol_data;
+
+    /// Opaque binary CFI cache, or NULL if symbol_data contains the CFI
+    const void *cfi_cache;
+
+    /// Callback to look up unwind rules in cfi_cache
+    cfi_lookup_t cfi_lookup;
 };
 
 /// Reads a minidump from a memory buffer and processes it. Returns an owning
@@ -0,0 +1,679 @@
+//! Parsing of Apple crash reports.
+//!
+//! Apple platforms write crash reports in two formats:
+//!
+//!  - The JSON-based `.ips` format, used since iOS 15 and macOS 12. It consists of a single-line
+//!    JSON header, followed by a JSON document containing the actual report.
+//!  - The legacy plain text `.crash` format.
+//!
+//! Both formats are parsed into an [`AppleCrashReport`], which contains threads with their stack
+//! frames, the list of loaded binary images, and information on the exception. The structure
+//! mirrors the `ProcessState` of a processed minidump.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde_json::Value;
+use thiserror::Error;
+
+use symbolic_common::{Arch, DebugId};
+
+use crate::utils::{file_name, parse_hex};
+
+lazy_static! {
+    static ref THREAD_RE: Regex =
+        Regex::new(r"^Thread (\d+)( Crashed)?:(?: (?:Dispatch queue: )?(.*))?$").unwrap();
+    static ref THREAD_NAME_RE: Regex =
+        Regex::new(r"^Thread (\d+) name:\s+(?:Dispatch queue: )?(.*)$").unwrap();
+    static ref THREAD_STATE_RE: Regex =
+        Regex::new(r"^Thread (\d+) crashed with .* Thread State").unwrap();
+    static ref REGISTER_RE: Regex = Regex::new(r"(\w+):\s*(0x[0-9a-fA-F]+)").unwrap();
+    static ref FRAME_RE: Regex =
+        Regex::new(r"^\d+\s+(.+?)\s+(0x[0-9a-fA-F]+)\s+(.*?)(?: \+ (\d+))?$").unwrap();
+    static ref IMAGE_RE: Regex = Regex::new(
+        r"^\s*(0x[0-9a-fA-F]+)\s*-\s*(0x[0-9a-fA-F]+)\s+\+?(.+?)\s+(\S+)\s+<([0-9a-fA-F-]+)>\s+(.*)$"
+    )
+    .unwrap();
+    static ref ADDRESS_RE: Regex = Regex::new(r"at (0x[0-9a-fA-F]+)").unwrap();
+}
+
+/// The error type for [`AppleCrashReportError`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AppleCrashReportErrorKind {
+    /// The `.ips` report does not contain valid JSON.
+    InvalidJson,
+    /// The report is neither a valid `.ips` nor a legacy `.crash` report.
+    UnknownFormat,
+}
+
+impl fmt::Display for AppleCrashReportErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidJson => write!(f, "invalid JSON in ips report"),
+            Self::UnknownFormat => write!(f, "unknown crash report format"),
+        }
+    }
+}
+
+/// An error when parsing an Apple crash report.
+#[derive(Debug, Error)]
+#[error("{kind}")]
+pub struct AppleCrashReportError {
+    kind: AppleCrashReportErrorKind,
+    #[source]
+    source: Option<Box<dyn Error + Send + Sync + 'static>>,
+}
+
+impl AppleCrashReportError {
+    /// Creates a new Apple crash report error from a known kind of error as well as an arbitrary
+    /// error payload.
+    fn new<E>(kind: AppleCrashReportErrorKind, source: E) -> Self
+    where
+        E: Into<Box<dyn Error + Send + Sync>>,
+    {
+        let source = Some(source.into());
+        Self { kind, source }
+    }
+
+    /// Returns the corresponding [`AppleCrashReportErrorKind`] for this error.
+    pub fn kind(&self) -> AppleCrashReportErrorKind {
+        self.kind
+    }
+}
+
+impl From<AppleCrashReportErrorKind> for AppleCrashReportError {
+    fn from(kind: AppleCrashReportErrorKind) -> Self {
+        Self { kind, source: None }
+    }
+}
+
+/// Information on the exception that caused the crash.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExceptionInfo {
+    /// The Mach exception type, such as `EXC_BAD_ACCESS`.
+    pub ty: Option<String>,
+    /// The POSIX signal, such as `SIGSEGV`.
+    pub signal: Option<String>,
+    /// The exception subtype, such as `KERN_INVALID_ADDRESS at 0x0`.
+    pub subtype: Option<String>,
+    /// The raw exception codes.
+    pub codes: Option<String>,
+    /// The address that caused the crash, if known.
+    pub crash_address: Option<u64>,
+}
+
+/// A stack frame of a thread in an [`AppleCrashReport`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Frame {
+    /// The absolute instruction address.
+    pub instruction: u64,
+    /// The index of the binary image containing the instruction.
+    pub module: Option<usize>,
+    /// The symbol name, if the report was symbolicated.
+    pub symbol: Option<String>,
+    /// The offset of the instruction from the start of the symbol.
+    pub symbol_offset: Option<u64>,
+}
+
+/// A thread in an [`AppleCrashReport`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Thread {
+    /// The thread identifier, or its index in legacy reports.
+    pub thread_id: u64,
+    /// The name of the thread.
+    pub name: Option<String>,
+    /// The name of the dispatch queue the thread was running.
+    pub queue: Option<String>,
+    /// Whether this thread crashed.
+    pub crashed: bool,
+    /// The stack frames of this thread, starting with the innermost frame.
+    pub frames: Vec<Frame>,
+    /// The register values of this thread, usually only available for the crashed thread.
+    pub registers: BTreeMap<String, u64>,
+}
+
+/// A binary image loaded into the crashed process.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BinaryImage {
+    /// The address at which the image is loaded.
+    pub base_address: u64,
+    /// The size of the image in memory.
+    pub size: u64,
+    /// The name of the image.
+    pub name: String,
+    /// The full path of the image.
+    pub path: Option<String>,
+    /// The architecture of the image.
+    pub arch: Option<String>,
+    /// The debug identifier, derived from the image's UUID.
+    pub debug_id: Option<DebugId>,
+}
+
+impl BinaryImage {
+    /// Returns true if the given address lies within this image.
+    pub fn contains(&self, address: u64) -> bool {
+        address >= self.base_address && address - self.base_address < self.size
+    }
+}
+
+/// A parsed Apple crash report.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AppleCrashReport {
+    /// The unique identifier of this crash report.
+    pub incident_identifier: Option<String>,
+    /// The date and time of the crash, as written in the report.
+    pub timestamp: Option<String>,
+    /// The name of the crashed process.
+    pub process_name: Option<String>,
+    /// The path of the crashed executable.
+    pub process_path: Option<String>,
+    /// The name and version of the operating system, such as `iPhone OS 15.0`.
+    pub os_version: Option<String>,
+    /// The build number of the operating system.
+    pub os_build: Option<String>,
+    /// The CPU architecture of the crashed process.
+    pub cpu_arch: Arch,
+    /// Information on the exception.
+    pub exception: Option<ExceptionInfo>,
+    /// All threads of the process.
+    pub threads: Vec<Thread>,
+    /// All loaded binary images.
+    pub binary_images: Vec<BinaryImage>,
+    /// Additional header fields not covered by the fields above.
+    pub metadata: BTreeMap<String, String>,
+}
+
+impl AppleCrashReport {
+    /// Parses an Apple crash report in either the `.ips` or the legacy `.crash` format.
+    pub fn parse(data: &[u8]) -> Result<Self, AppleCrashReportError> {
+        let text = String::from_utf8_lossy(data);
+        if text.trim_start().starts_with('{') {
+            Self::parse_ips(&text)
+        } else {
+            Self::parse_legacy(&text)
+        }
+    }
+
+    /// Returns the index of the thread that crashed.
+    pub fn requesting_thread(&self) -> Option<usize> {
+        self.threads.iter().position(|thread| thread.crashed)
+    }
+
+    /// Returns the reason of the crash, combining the exception type and signal.
+    pub fn crash_reason(&self) -> Option<String> {
+        let exception = self.exception.as_ref()?;
+        match (&exception.ty, &exception.signal) {
+            (Some(ty), Some(signal)) => Some(format!("{} / {}", ty, signal)),
+            (Some(reason), None) | (None, Some(reason)) => Some(reason.clone()),
+            (None, None) => None,
+        }
+    }
+
+    /// Parses the JSON-based `.ips` format.
+    fn parse_ips(text: &str) -> Result<Self, AppleCrashReportError> {
+        let invalid_json =
+            |e| AppleCrashReportError::new(AppleCrashReportErrorKind::InvalidJson, e);
+
+        // The report starts with a single-line header, followed by the report body.
+        let (header, body) = match text.trim_start().split_once('\n') {
+            Some((header, body)) if !body.trim().is_empty() => (header, body),
+            _ => (text, text),
+        };
+        let header: Value = serde_json::from_str(header).map_err(invalid_json)?;
+        let body: Value = serde_json::from_str(body).map_err(invalid_json)?;
+
+        let mut report = AppleCrashReport {
+            incident_identifier: json_string(&body["incident"])
+                .or_else(|| json_string(&header["incident_id"])),
+            timestamp: json_string(&body["captureTime"])
+                .or_else(|| json_string(&header["timestamp"])),
+            process_name: json_string(&body["procName"])
+                .or_else(|| json_string(&header["app_name"])),
+            process_path: json_string(&body["procPath"]),
+            os_version: json_string(&body["osVersion"]["train"]),
+            os_build: json_string(&body["osVersion"]["build"]),
+            cpu_arch: parse_code_type(body["cpuType"].as_str().unwrap_or_default()),
+            ..Default::default()
+        };
+
+        if let Some(exception) = body["exception"].as_object() {
+            let subtype = exception.get("subtype").and_then(json_string);
+            report.exception = Some(ExceptionInfo {
+                ty: exception.get("type").and_then(json_string),
+                signal: exception.get("signal").and_then(json_string),
+                crash_address: subtype.as_deref().and_then(parse_crash_address),
+                subtype,
+                codes: exception.get("codes").and_then(json_string),
+            });
+        }
+
+        for image in body["usedImages"].as_array().into_iter().flatten() {
+            let path = json_string(&image["path"]);
+            let name = json_string(&image["name"])
+                .or_else(|| path.as_deref().map(file_name).map(str::to_owned))
+                .unwrap_or_default();
+
+            report.binary_images.push(BinaryImage {
+                base_address: image["base"].as_u64().unwrap_or_default(),
+                size: image["size"].as_u64().unwrap_or_default(),
+                name,
+                path,
+                arch: json_string(&image["arch"]),
+                debug_id: image["uuid"].as_str().and_then(|uuid| uuid.parse().ok()),
+            });
+        }
+
+        let faulting_thread = body["faultingThread"].as_u64().map(|index| index as usize);
+        for (index, thread) in body["threads"].as_array().into_iter().flatten().enumerate() {
+            let mut frames = Vec::new();
+            for frame in thread["frames"].as_array().into_iter().flatten() {
+                let module = frame["imageIndex"].as_u64().map(|index| index as usize);
+                let base = module
+                    .and_then(|index| report.binary_images.get(index))
+                    .map_or(0, |image| image.base_address);
+
+                frames.push(Frame {
+                    instruction: base + frame["imageOffset"].as_u64().unwrap_or_default(),
+                    module,
+                    symbol: json_string(&frame["symbol"]),
+                    symbol_offset: frame["symbolLocation"].as_u64(),
+                });
+            }
+
+            report.threads.push(Thread {
+                thread_id: thread["id"].as_u64().unwrap_or(index as u64),
+                name: json_string(&thread["name"]),
+                queue: json_string(&thread["queue"]),
+                crashed: thread["triggered"].as_bool().unwrap_or(false)
+                    || faulting_thread == Some(index),
+                frames,
+                registers: parse_thread_state(&thread["threadState"]),
+            });
+        }
+
+        if let Some(header) = header.as_object() {
+            for (key, value) in header {
+                if let Some(value) = json_string(value) {
+                    report.metadata.insert(key.clone(), value);
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Parses the legacy plain text `.crash` format.
+    fn parse_legacy(text: &str) -> Result<Self, AppleCrashReportError> {
+        let mut report = AppleCrashReport::default();
+        let mut exception = ExceptionInfo::default();
+        let mut crashed_thread = None;
+        let mut thread_names = BTreeMap::new();
+        let mut frame_images = Vec::new();
+
+        let mut lines = text.lines().peekable();
+        while let Some(line) = lines.next() {
+            let line = line.trim_end();
+
+            if let Some(caps) = THREAD_NAME_RE.captures(line) {
+                if let Ok(thread_id) = caps[1].parse::<u64>() {
+                    thread_names.insert(thread_id, caps[2].trim().to_owned());
+                }
+            } else if let Some(caps) = THREAD_RE.captures(line) {
+                let thread_id = caps[1].parse().unwrap_or_default();
+                let mut thread = Thread {
+                    thread_id,
+                    crashed: caps.get(2).is_some(),
+                    queue: caps.get(3).map(|m| m.as_str().trim().to_owned()),
+                    ..Default::default()
+                };
+
+                let mut images = Vec::new();
+                while let Some(caps) = lines.peek().and_then(|line| FRAME_RE.captures(line)) {
+                    thread.frames.push(Frame {
+                        instruction: parse_hex(&caps[2]).unwrap_or_default(),
+                        module: None,
+                        // Unsymbolicated frames contain `<image base> + <offset>` instead.
+                        symbol: Some(caps[3].to_owned()).filter(|s| !s.starts_with("0x")),
+                        symbol_offset: caps.get(4).and_then(|m| m.as_str().parse().ok()),
+                    });
+                    // Modules are resolved once all binary images are known.
+                    images.push(caps[1].to_owned());
+                    lines.next();
+                }
+
+                frame_images.push(images);
+                report.threads.push(thread);
+            } else if THREAD_STATE_RE.is_match(line) {
+                let thread_id = THREAD_STATE_RE
+                    .captures(line)
+                    .and_then(|caps| caps[1].parse::<u64>().ok());
+                let mut registers = BTreeMap::new();
+                while let Some(line) = lines.peek().filter(|line| !line.trim().is_empty()) {
+                    for caps in REGISTER_RE.captures_iter(line) {
+                        if let Some(value) = parse_hex(&caps[2]) {
+                            registers.insert(caps[1].to_owned(), value);
+                        }
+                    }
+                    lines.next();
+                }
+
+                let thread = report
+                    .threads
+                    .iter_mut()
+                    .find(|thread| Some(thread.thread_id) == thread_id);
+                if let Some(thread) = thread {
+                    thread.registers = registers;
+                }
+            } else if line.starts_with("Binary Images:") {
+                for line in &mut lines {
+                    let caps = match IMAGE_RE.captures(line) {
+                        Some(caps) => caps,
+                        None => continue,
+                    };
+
+                    let base_address = parse_hex(&caps[1]).unwrap_or_default();
+                    let end_address = parse_hex(&caps[2]).unwrap_or_default();
+                    report.binary_images.push(BinaryImage {
+                        base_address,
+                        size: (end_address + 1).saturating_sub(base_address),
+                        name: caps[3].to_owned(),
+                        path: Some(caps[6].trim().to_owned()),
+                        arch: Some(caps[4].to_owned()),
+                        debug_id: caps[5].parse().ok(),
+                    });
+                }
+            } else if let Some((key, value)) = line.split_once(':') {
+                let value = value.trim();
+                if key.starts_with(' ') || value.is_empty() {
+                    continue;
+                }
+
+                let value_string = Some(value.to_owned());
+                match key {
+                    "Incident Identifier" => report.incident_identifier = value_string,
+                    "Date/Time" => report.timestamp = value_string,
+                    "Process" => {
+                        let name = value.split(" [").next().unwrap_or(value);
+                        report.process_name = Some(name.to_owned());
+                    }
+                    "Path" => report.process_path = value_string,
+                    "Code Type" => report.cpu_arch = parse_code_type(value),
+                    "OS Version" => {
+                        let (version, build) = parse_legacy_os_version(value);
+                        report.os_version = Some(version);
+                        report.os_build = build;
+                    }
+                    "Exception Type" => {
+                        let (ty, signal) = match value.split_once(" (") {
+                            Some((ty, signal)) => (ty, Some(signal.trim_end_matches(')'))),
+                            None => (value, None),
+                        };
+                        exception.ty = Some(ty.to_owned());
+                        exception.signal = signal.map(str::to_owned);
+                    }
+                    "Exception Subtype" => {
+                        exception.crash_address = parse_crash_address(value);
+                        exception.subtype = value_string;
+                    }
+                    "Exception Codes" => exception.codes = value_string,
+                    "Crashed Thread" | "Triggered by Thread" => {
+                        crashed_thread =
+                            value.split_whitespace().next().and_then(|t| t.parse().ok());
+                    }
+                    _ => {
+                        report.metadata.insert(key.to_owned(), value.to_owned());
+                    }
+                }
+            }
+        }
+
+        if report.threads.is_empty() && report.binary_images.is_empty() {
+            return Err(AppleCrashReportErrorKind::UnknownFormat.into());
+        }
+
+        for (thread, images) in report.threads.iter_mut().zip(frame_images) {
+            thread.name = thread_names.get(&thread.thread_id).cloned();
+            thread.crashed |= crashed_thread == Some(thread.thread_id);
+            if thread.queue.as_deref() == Some("") {
+                thread.queue = None;
+            }
+
+            for (frame, image_name) in thread.frames.iter_mut().zip(images) {
+                let images = &report.binary_images;
+                frame.module = images
+                    .iter()
+                    .position(|image| image.contains(frame.instruction))
+                    .or_else(|| images.iter().position(|image| image.name == image_name));
+            }
+        }
+
+        if exception != ExceptionInfo::default() {
+            report.exception = Some(exception);
+        }
+
+        Ok(report)
+    }
+}
+
+fn json_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) if !s.is_empty() => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+fn parse_thread_state(state: &Value) -> BTreeMap<String, u64> {
+    let mut registers = BTreeMap::new();
+    let state = match state.as_object() {
+        Some(state) => state,
+        None => return registers,
+    };
+
+    for (name, value) in state {
+        if let Some(value) = value["value"].as_u64() {
+            registers.insert(name.clone(), value);
+        } else if let Some(values) = value.as_array() {
+            // General purpose registers are listed as an array, e.g. `x` on ARM64.
+            for (index, value) in values.iter().enumerate() {
+                if let Some(value) = value["value"].as_u64() {
+                    registers.insert(format!("{}{}", name, index), value);
+                }
+            }
+        }
+    }
+
+    registers
+}
+
+fn parse_crash_address(subtype: &str) -> Option<u64> {
+    let caps = ADDRESS_RE.captures(subtype)?;
+    parse_hex(&caps[1])
+}
+
+fn parse_code_type(code_type: &str) -> Arch {
+    let code_type = code_type.split(" (").next().unwrap_or_default();
+    match code_type {
+        "ARM-64" | "ARM64" | "arm64" => Arch::Arm64,
+        "ARM64E" | "arm64e" => Arch::Arm64e,
+        "ARM" | "ARM-32" | "arm" => Arch::Arm,
+        "X86-64" | "X86_64" | "x86_64" => Arch::Amd64,
+        "X86" | "x86" => Arch::X86,
+        _ => Arch::Unknown,
+    }
+}
+
+/// Splits `iPhone OS 14.4 (18D52)` into the version and the build.
+fn parse_legacy_os_version(value: &str) -> (String, Option<String>) {
+    match value.rsplit_once(" (") {
+        Some((version, build)) => (
+            version.to_owned(),
+            Some(build.trim_end_matches(')').to_owned()),
+        ),
+        None => (value.to_owned(), None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IPS_REPORT: &str = r#"{"app_name":"Example","timestamp":"2021-10-05 12:00:00.00 +0200","bug_type":"309","os_version":"iPhone OS 15.0 (19A346)","incident_id":"9F5A3B7C-2C3E-4D5F-8A9B-0C1D2E3F4A5B"}
+{
+  "incident" : "9F5A3B7C-2C3E-4D5F-8A9B-0C1D2E3F4A5B",
+  "captureTime" : "2021-10-05 12:00:00.0000 +0200",
+  "procName" : "Example",
+  "procPath" : "/private/var/containers/Bundle/Application/Example.app/Example",
+  "osVersion" : {"train" : "iPhone OS 15.0", "build" : "19A346"},
+  "cpuType" : "ARM-64",
+  "exception" : {"type" : "EXC_BAD_ACCESS", "signal" : "SIGSEGV", "subtype" : "KERN_INVALID_ADDRESS at 0x0000000000000010", "codes" : "0x0000000000000001, 0x0000000000000010"},
+  "faultingThread" : 1,
+  "threads" : [
+    {"id" : 1001, "queue" : "com.apple.main-thread", "frames" : [{"imageOffset" : 4096, "imageIndex" : 1}]},
+    {"id" : 1002, "triggered" : true, "name" : "worker", "threadState" : {"x" : [{"value" : 1}, {"value" : 2}], "pc" : {"value" : 4295000064}, "sp" : {"value" : 6000000000}}, "frames" : [{"imageOffset" : 32768, "symbol" : "crash", "symbolLocation" : 12, "imageIndex" : 0}]}
+  ],
+  "usedImages" : [
+    {"base" : 4294967296, "size" : 65536, "uuid" : "d8e9a5c7-4f0b-3e2a-9c1d-6b7a8f9e0d1c", "path" : "/private/var/containers/Bundle/Application/Example.app/Example", "name" : "Example", "arch" : "arm64"},
+    {"base" : 6442450944, "size" : 32768, "uuid" : "0a1b2c3d-4e5f-6071-8293-a4b5c6d7e8f9", "path" : "/usr/lib/system/libsystem_kernel.dylib", "name" : "libsystem_kernel.dylib", "arch" : "arm64e"}
+  ]
+}"#;
+
+    const LEGACY_REPORT: &str = r#"Incident Identifier: 5C3B2A19-8F7E-4D6C-B5A4-3928170F6E5D
+Hardware Model:      iPhone12,1
+Process:             Example [1234]
+Path:                /private/var/containers/Bundle/Application/Example.app/Example
+Code Type:           ARM-64 (Native)
+Date/Time:           2021-03-01 10:00:00.0000 +0100
+OS Version:          iPhone OS 14.4 (18D52)
+
+Exception Type:  EXC_BAD_ACCESS (SIGSEGV)
+Exception Subtype: KERN_INVALID_ADDRESS at 0x0000000000000010
+Exception Codes: 0x0000000000000001, 0x0000000000000010
+Triggered by Thread:  1
+
+Thread 0 name:  Dispatch queue: com.apple.main-thread
+Thread 0:
+0   libsystem_kernel.dylib        	0x0000000180001000 mach_msg_trap + 8
+1   Example                       	0x0000000100008000 0x100000000 + 32768
+
+Thread 1 name:  worker
+Thread 1 Crashed:
+0   Example                       	0x000000010000800c crash + 12
+
+Thread 1 crashed with ARM Thread State (64-bit):
+    x0: 0x0000000000000001   x1: 0x0000000000000002
+    fp: 0x000000016f000000   lr: 0x0000000100008000
+    sp: 0x000000016efff000   pc: 0x000000010000800c
+
+Binary Images:
+0x100000000 - 0x10000ffff Example arm64  <d8e9a5c74f0b3e2a9c1d6b7a8f9e0d1c> /private/var/containers/Bundle/Application/Example.app/Example
+0x180000000 - 0x180007fff libsystem_kernel.dylib arm64e  <0a1b2c3d4e5f60718293a4b5c6d7e8f9> /usr/lib/system/libsystem_kernel.dylib
+"#;
+
+    #[test]
+    fn test_parse_ips() {
+        let report = AppleCrashReport::parse(IPS_REPORT.as_bytes()).unwrap();
+
+        assert_eq!(report.process_name.as_deref(), Some("Example"));
+        assert_eq!(report.os_version.as_deref(), Some("iPhone OS 15.0"));
+        assert_eq!(report.os_build.as_deref(), Some("19A346"));
+        assert_eq!(report.cpu_arch, Arch::Arm64);
+        assert_eq!(
+            report.metadata.get("bug_type").map(String::as_str),
+            Some("309")
+        );
+
+        let exception = report.exception.as_ref().unwrap();
+        assert_eq!(exception.crash_address, Some(0x10));
+        assert_eq!(
+            report.crash_reason().as_deref(),
+            Some("EXC_BAD_ACCESS / SIGSEGV")
+        );
+
+        assert_eq!(report.binary_images.len(), 2);
+        assert_eq!(
+            report.binary_images[0].debug_id,
+            Some("d8e9a5c7-4f0b-3e2a-9c1d-6b7a8f9e0d1c".parse().unwrap())
+        );
+
+        assert_eq!(report.requesting_thread(), Some(1));
+        let main = &report.threads[0];
+        assert!(!main.crashed);
+        assert_eq!(main.queue.as_deref(), Some("com.apple.main-thread"));
+        assert_eq!(main.frames[0].instruction, 0x1_8000_1000);
+        assert_eq!(main.frames[0].module, Some(1));
+
+        let crashed = &report.threads[1];
+        assert_eq!(crashed.thread_id, 1002);
+        assert_eq!(crashed.name.as_deref(), Some("worker"));
+        assert_eq!(crashed.frames[0].instruction, 0x1_0000_8000);
+        assert_eq!(crashed.frames[0].symbol.as_deref(), Some("crash"));
+        assert_eq!(crashed.frames[0].symbol_offset, Some(12));
+        assert_eq!(crashed.registers.get("x1"), Some(&2));
+        assert_eq!(crashed.registers.get("pc"), Some(&0x1_0000_8000));
+    }
+
+    #[test]
+    fn test_parse_legacy() {
+        let report = AppleCrashReport::parse(LEGACY_REPORT.as_bytes()).unwrap();
+
+        assert_eq!(
+            report.incident_identifier.as_deref(),
+            Some("5C3B2A19-8F7E-4D6C-B5A4-3928170F6E5D")
+        );
+        assert_eq!(report.process_name.as_deref(), Some("Example"));
+        assert_eq!(report.os_version.as_deref(), Some("iPhone OS 14.4"));
+        assert_eq!(report.os_build.as_deref(), Some("18D52"));
+        assert_eq!(report.cpu_arch, Arch::Arm64);
+        assert_eq!(
+            report.metadata.get("Hardware Model").map(String::as_str),
+            Some("iPhone12,1")
+        );
+
+        let exception = report.exception.as_ref().unwrap();
+        assert_eq!(exception.ty.as_deref(), Some("EXC_BAD_ACCESS"));
+        assert_eq!(exception.signal.as_deref(), Some("SIGSEGV"));
+        assert_eq!(exception.crash_address, Some(0x10));
+
+        assert_eq!(report.binary_images.len(), 2);
+        let image = &report.binary_images[1];
+        assert_eq!(image.name, "libsystem_kernel.dylib");
+        assert_eq!(image.base_address, 0x1_8000_0000);
+        assert_eq!(image.size, 0x8000);
+        assert_eq!(
+            image.debug_id,
+            Some("0a1b2c3d-4e5f-6071-8293-a4b5c6d7e8f9".parse().unwrap())
+        );
+
+        assert_eq!(report.threads.len(), 2);
+        assert_eq!(report.requesting_thread(), Some(1));
+
+        let main = &report.threads[0];
+        assert_eq!(main.name.as_deref(), Some("com.apple.main-thread"));
+        assert_eq!(main.frames.len(), 2);
+        assert_eq!(main.frames[0].symbol.as_deref(), Some("mach_msg_trap"));
+        assert_eq!(main.frames[0].module, Some(1));
+        assert_eq!(main.frames[1].symbol, None);
+        assert_eq!(main.frames[1].module, Some(0));
+
+        let crashed = &report.threads[1];
+        assert_eq!(crashed.name.as_deref(), Some("worker"));
+        assert_eq!(crashed.frames[0].symbol_offset, Some(12));
+        assert_eq!(crashed.registers.get("pc"), Some(&0x1_0000_800c));
+        assert_eq!(crashed.registers.get("x1"), Some(&2));
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        let error = AppleCrashReport::parse(b"{ not json").unwrap_err();
+        assert_eq!(error.kind(), AppleCrashReportErrorKind::InvalidJson);
+
+        let error = AppleCrashReport::parse(b"hello world").unwrap_err();
+        assert_eq!(error.kind(), AppleCrashReportErrorKind::UnknownFormat);
+    }
+}
@@ -17,6 +17,7 @@
 //! [processor]: ../processor/index.html
 //! [`CfiCache`]: struct.CfiCache.html
 
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::convert::TryInto;
 use std::error::Error;
@@ -27,7 +28,11 @@ use std::ops::Range;
 use thiserror::Error;
 
 use symbolic_common::{Arch, ByteView, CpuFamily, UnknownArchError};
-use symbolic_debuginfo::breakpad::{BreakpadError, BreakpadObject, BreakpadStackRecord};
+use symbolic_debuginfo::breakpad::{
+    BreakpadCfiEntry, BreakpadCfiRule, BreakpadCfiTable, BreakpadError, BreakpadObject,
+    BreakpadStackCfiRecord, BreakpadStackRecord, BreakpadStackRecords, BreakpadStackWinRecord,
+    BreakpadStackWinRecordType, BreakpadUnwindRules,
+};
 use symbolic_debuginfo::dwarf::gimli::{
     BaseAddresses, CfaRule, CieOrFde, DebugFrame, EhFrame, Error as GimliError,
     FrameDescriptionEntry, Reader, ReaderOffset, Register, RegisterRule, UnwindContext,
@@ -52,7 +57,7 @@ use symbolic_debuginfo::{Object, ObjectError, ObjectLike};
 pub const CFICACHE_MAGIC: u32 = u32::from_be_bytes(*b"CFIC");
 
 /// The latest version of the file format.
-pub const CFICACHE_LATEST_VERSION: u32 = 2;
+pub const CFICACHE_LATEST_VERSION: u32 = 3;
 
 // The preamble are 8 bytes, a 4-byte magic and 4 bytes for the version.
 // The 4-byte magic should be read as little endian to check for endian mismatch.
@@ -61,6 +66,7 @@ pub const CFICACHE_LATEST_VERSION: u32 = 2;
 //
 // 1: Initial ASCII-only implementation
 // 2: Implementation with a versioned preamble
+// 3: Binary format with sorted address ranges and deduplicated rules
 
 /// Used to detect empty runtime function entries in PEs.
 const EMPTY_FUNCTION: RuntimeFunction = RuntimeFunction {
@@ -93,6 +99,9 @@ pub enum CfiErrorKind {
 
     /// Invalid magic bytes in the cfi cache header.
     BadFileMagic,
+
+    /// The cfi cache has an unsupported version or is corrupted.
+    BadFormat,
 }
 
 impl fmt::Display for CfiErrorKind {
@@ -105,6 +114,7 @@ impl fmt::Display for CfiErrorKind {
             Self::InvalidAddress => write!(f, "invalid cfi address"),
             Self::WriteFailed => write!(f, "failed to write cfi"),
             Self::BadFileMagic => write!(f, "bad cfi cache magic"),
+            Self::BadFormat => write!(f, "invalid cfi cache format"),
         }
     }
 }
@@ -243,6 +253,48 @@ impl<U> UnwindInfo<U> {
     }
 }
 
+/// A rule recovering the caller's value of a register, such as `.cfa: $rsp 8 +`.
+struct UnwindRule {
+    register: Cow<'static, str>,
+    expression: String,
+}
+
+impl UnwindRule {
+    fn new<R>(register: R, expression: String) -> Self
+    where
+        R: Into<Cow<'static, str>>,
+    {
+        UnwindRule {
+            register: register.into(),
+            expression,
+        }
+    }
+}
+
+/// A row of an unwind table, containing the rules that change at its address.
+struct UnwindRow {
+    address: u64,
+    rules: Vec<UnwindRule>,
+}
+
+/// Receives unwind information extracted by a [`CfiExtractor`].
+///
+/// This is implemented by [`AsciiCfiWriter`] to write Breakpad `STACK` records, and by
+/// [`CfiCacheV3Writer`] to build binary CFI caches without going through the text format.
+trait UnwindSink {
+    /// Adds an unwind table covering `size` bytes from `start`.
+    ///
+    /// Each row lists the rules that change at its address, relative to the previous rows. A row
+    /// at `start` corresponds to a `STACK CFI INIT` record, all other rows to `STACK CFI` records.
+    fn add_cfi(&mut self, start: u64, size: u64, rows: &[UnwindRow]) -> Result<(), CfiError>;
+
+    /// Adds a `STACK CFI INIT` record and its deltas from a Breakpad symbol file.
+    fn add_cfi_record(&mut self, record: &BreakpadStackCfiRecord<'_>) -> Result<(), CfiError>;
+
+    /// Adds a `STACK WIN` record.
+    fn add_win(&mut self, record: &BreakpadStackWinRecord<'_>) -> Result<(), CfiError>;
+}
+
 /// A service that converts call frame information (CFI) from an object file to Breakpad ASCII
 /// format and writes it to the given writer.
 ///
@@ -292,6 +344,95 @@ impl<W: Write> AsciiCfiWriter<W> {
 
     /// Extracts CFI from the given object file.
     pub fn process(&mut self, object: &Object<'_>) -> Result<(), CfiError> {
+        CfiExtractor { sink: self }.process(object)
+    }
+
+    /// Returns the wrapped writer from this instance.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> UnwindSink for AsciiCfiWriter<W> {
+    fn add_cfi(&mut self, start: u64, size: u64, rows: &[UnwindRow]) -> Result<(), CfiError> {
+        for row in rows {
+            if row.address == start {
+                write!(self.inner, "STACK CFI INIT {:x} {:x}", start, size)?;
+            } else {
+                write!(self.inner, "STACK CFI {:x}", row.address)?;
+            }
+
+            for rule in &row.rules {
+                write!(self.inner, " {}: {}", rule.register, rule.expression)?;
+            }
+
+            writeln!(self.inner)?;
+        }
+
+        Ok(())
+    }
+
+    fn add_cfi_record(&mut self, record: &BreakpadStackCfiRecord<'_>) -> Result<(), CfiError> {
+        writeln!(
+            self.inner,
+            "STACK CFI INIT {:x} {:x} {}",
+            record.start, record.size, record.init_rules
+        )?;
+
+        for d in record.deltas().flatten() {
+            writeln!(self.inner, "STACK CFI {:x} {}", d.address, d.rules)?;
+        }
+
+        Ok(())
+    }
+
+    fn add_win(&mut self, record: &BreakpadStackWinRecord<'_>) -> Result<(), CfiError> {
+        Ok(write_win_record(&mut self.inner, record)?)
+    }
+}
+
+/// Writes a `STACK WIN` record in Breakpad ASCII format.
+fn write_win_record<W: Write>(mut writer: W, r: &BreakpadStackWinRecord<'_>) -> io::Result<()> {
+    write!(writer, "STACK WIN ")?;
+    write_win_fields(&mut writer, r)?;
+    writeln!(writer)
+}
+
+/// Writes the fields of a `STACK WIN` record following the `STACK WIN` prefix.
+pub(crate) fn write_win_fields<W: Write>(
+    mut writer: W,
+    r: &BreakpadStackWinRecord<'_>,
+) -> io::Result<()> {
+    write!(
+        writer,
+        "{:x} {:x} {:x} {:x} {:x} {:x} {:x} {:x} {:x} {} {}",
+        win_type_to_u8(r.ty),
+        r.code_start,
+        r.code_size,
+        r.prolog_size,
+        r.epilog_size,
+        r.params_size,
+        r.saved_regs_size,
+        r.locals_size,
+        r.max_stack_size,
+        if r.program_string.is_some() { "1" } else { "0" },
+        if let Some(ps) = r.program_string {
+            ps
+        } else if r.uses_base_pointer {
+            "1"
+        } else {
+            "0"
+        }
+    )
+}
+
+/// Extracts unwind information from object files and passes it to an [`UnwindSink`].
+struct CfiExtractor<'s, S> {
+    sink: &'s mut S,
+}
+
+impl<S: UnwindSink> CfiExtractor<'_, S> {
+    fn process(&mut self, object: &Object<'_>) -> Result<(), CfiError> {
         match object {
             Object::Breakpad(o) => self.process_breakpad(o),
             Object::MachO(o) => self.process_macho(o),
@@ -303,48 +444,11 @@ impl<W: Write> AsciiCfiWriter<W> {
         }
     }
 
-    /// Returns the wrapped writer from this instance.
-    pub fn into_inner(self) -> W {
-        self.inner
-    }
-
     fn process_breakpad(&mut self, object: &BreakpadObject<'_>) -> Result<(), CfiError> {
         for record in object.stack_records() {
             match record? {
-                BreakpadStackRecord::Cfi(r) => {
-                    writeln!(
-                        self.inner,
-                        "STACK CFI INIT {:x} {:x} {}",
-                        r.start, r.size, r.init_rules
-                    )?;
-
-                    for d in r.deltas().flatten() {
-                        writeln!(self.inner, "STACK CFI {:x} {}", d.address, d.rules)?;
-                    }
-
-                    Ok(())
-                }
-                BreakpadStackRecord::Win(r) => writeln!(
-                    self.inner,
-                    "STACK WIN {} {:x} {:x} {:x} {:x} {:x} {:x} {:x} {:x} {} {}",
-                    r.ty as usize,
-                    r.code_start,
-                    r.code_size,
-                    r.prolog_size,
-                    r.epilog_size,
-                    r.params_size,
-                    r.saved_regs_size,
-                    r.locals_size,
-                    r.max_stack_size,
-                    if r.program_string.is_some() { "1" } else { "0" },
-                    if let Some(ps) = r.program_string {
-                        ps
-                    } else if r.uses_base_pointer {
-                        "1"
-                    } else {
-                        "0"
-                    }
-                ),
+                BreakpadStackRecord::Cfi(r) => self.sink.add_cfi_record(&r),
+                BreakpadStackRecord::Win(r) => self.sink.add_win(&r),
             }?
         }
 
@@ -415,28 +519,24 @@ impl<W: Write> AsciiCfiWriter<W> {
         R: Reader + Eq,
         U: UnwindSection<R>,
     {
-        fn write_reg_name<W: Write>(
-            writer: &mut W,
+        fn reg_name(
             register: CompactCfiRegister,
             iter: &CompactUnwindInfoIter,
             cpu_family: CpuFamily,
-        ) -> Result<(), CfiError> {
+        ) -> Cow<'static, str> {
             if register.is_cfa() {
-                write!(writer, ".cfa")?;
+                ".cfa".into()
             } else if register == CompactCfiRegister::instruction_pointer() {
-                write!(writer, ".ra")?;
+                ".ra".into()
             } else {
                 // For whatever reason breakpad doesn't prefix registers with $ on ARM.
                 match cpu_family {
                     CpuFamily::Arm32 | CpuFamily::Arm64 | CpuFamily::Arm64_32 => {
-                        write!(writer, "{}", register.name(iter).unwrap())?;
-                    }
-                    _ => {
-                        write!(writer, "${}", register.name(iter).unwrap())?;
+                        register.name(iter).unwrap().into()
                     }
+                    _ => format!("${}", register.name(iter).unwrap()).into(),
                 }
             }
-            Ok(())
         }
         // Preload the symbols as this is expensive to do in the loop.
         let symbols = object.symbol_map();
@@ -470,13 +570,17 @@ impl<W: Write> AsciiCfiWriter<W> {
                     // code will detect the bogus return address and fall back to frame pointers or
                     // scanning either way.
 
-                    let start_addr = entry.instruction_address;
+                    let start_addr = entry.instruction_address.into();
                     if let CpuFamily::Amd64 = object.arch().cpu_family() {
-                        writeln!(
-                            self.inner,
-                            "STACK CFI INIT {:x} {:x} .cfa: $rsp 8 + .ra: .cfa -8 + ^",
-                            start_addr, entry.len
-                        )?;
+                        let rules = vec![
+                            UnwindRule::new(".cfa", "$rsp 8 +".into()),
+                            UnwindRule::new(".ra", ".cfa -8 + ^".into()),
+                        ];
+                        let row = UnwindRow {
+                            address: start_addr,
+                            rules,
+                        };
+                        self.sink.add_cfi(start_addr, entry.len.into(), &[row])?;
                     }
                 }
                 CompactUnwindOp::UseDwarfFde { offset_in_eh_frame } => {
@@ -501,26 +605,25 @@ impl<W: Write> AsciiCfiWriter<W> {
                                 let rsp_offset = 72;
                                 let rip_offset = 144;
 
-                                write!(
-                                    self.inner,
-                                    "STACK CFI INIT {:x} {:x} ",
-                                    start_addr, entry.len
-                                )?;
-                                write!(
-                                    self.inner,
-                                    "$rbp: $rbx {} + ^ {} + ^ ",
-                                    mc_offset, rbp_offset
-                                )?;
-                                write!(
-                                    self.inner,
-                                    ".cfa: $rbx {} + ^ {} + ^ ",
-                                    mc_offset, rsp_offset,
-                                )?;
-                                writeln!(
-                                    self.inner,
-                                    ".ra: $rbx {} + ^ {} + ^",
-                                    mc_offset, rip_offset
-                                )?;
+                                let rules = vec![
+                                    UnwindRule::new(
+                                        "$rbp",
+                                        format!("$rbx {} + ^ {} + ^", mc_offset, rbp_offset),
+                                    ),
+                                    UnwindRule::new(
+                                        ".cfa",
+                                        format!("$rbx {} + ^ {} + ^", mc_offset, rsp_offset),
+                                    ),
+                                    UnwindRule::new(
+                                        ".ra",
+                                        format!("$rbx {} + ^ {} + ^", mc_offset, rip_offset),
+                                    ),
+                                ];
+                                let row = UnwindRow {
+                                    address: start_addr,
+                                    rules,
+                                };
+                                self.sink.add_cfi(start_addr, entry.len.into(), &[row])?;
                             } else {
                                 self.process_fde(info, &mut ctx, &fde)?;
                             }
@@ -529,10 +632,8 @@ impl<W: Write> AsciiCfiWriter<W> {
                 }
                 CompactUnwindOp::CfiOps(ops) => {
                     // We just need to output a bunch of CFI expressions in a single CFI INIT
-                    let mut line = Vec::new();
-                    let start_addr = entry.instruction_address;
-                    let length = entry.len;
-                    write!(line, "STACK CFI INIT {:x} {:x} ", start_addr, length)?;
+                    let start_addr = entry.instruction_address.into();
+                    let mut rules = Vec::new();
 
                     for instruction in ops {
                         // These two operations differ only in whether there should
@@ -551,20 +652,23 @@ impl<W: Write> AsciiCfiWriter<W> {
                             } => (dest_reg, src_reg, offset_from_src, false),
                         };
 
-                        write_reg_name(&mut line, dest_reg, &iter, cpu_family)?;
-                        write!(line, ": ")?;
-                        write_reg_name(&mut line, src_reg, &iter, cpu_family)?;
-                        write!(line, " {} + ", offset)?;
+                        let mut expression =
+                            format!("{} {} +", reg_name(src_reg, &iter, cpu_family), offset);
                         if should_deref {
-                            write!(line, "^ ")?;
+                            expression.push_str(" ^");
                         }
-                    }
 
-                    let line = line.strip_suffix(b" ").unwrap_or(&line);
+                        rules.push(UnwindRule::new(
+                            reg_name(dest_reg, &iter, cpu_family),
+                            expression,
+                        ));
+                    }
 
-                    self.inner
-                        .write_all(line)
-                        .and_then(|_| writeln!(self.inner))?;
+                    let row = UnwindRow {
+                        address: start_addr,
+                        rules,
+                    };
+                    self.sink.add_cfi(start_addr, entry.len.into(), &[row])?;
                 }
             }
         }
@@ -645,94 +749,73 @@ impl<W: Write> AsciiCfiWriter<W> {
             // subsequent occurrences. Only registers with changed rules will be written.
             let mut rule_cache = HashMap::new();
             let mut cfa_cache = None;
+            let mut unwind_rows = Vec::with_capacity(rows.len());
 
-            // Write records for every entry in the unwind table.
+            // Collect the changed rules for every entry in the unwind table.
             for row in &rows {
-                let mut written = false;
-                let mut line = Vec::new();
-
-                // Depending on whether this is the first row or any subsequent row, print a INIT or
-                // normal STACK CFI record.
-                if row.start_address() == start {
-                    let start_addr = start - info.load_address;
-                    write!(line, "STACK CFI INIT {:x} {:x}", start_addr, length)?;
-                } else {
-                    let start_addr = row.start_address() - info.load_address;
-                    write!(line, "STACK CFI {:x}", start_addr)?;
-                }
+                let mut rules = Vec::new();
 
-                // Write the mandatory CFA rule for this row, followed by optional register rules.
+                // Add the mandatory CFA rule for this row, followed by optional register rules.
                 // The actual formatting of the rules depends on their rule type.
                 if cfa_cache != Some(row.cfa()) {
                     cfa_cache = Some(row.cfa());
-                    written |= Self::write_cfa_rule(&mut line, info.arch, row.cfa())?;
+                    rules.extend(Self::cfa_rule(info.arch, row.cfa()));
                 }
 
-                // Print only registers that have changed rules to their previous occurrence to
+                // Add only registers that have changed rules to their previous occurrence to
                 // reduce the number of rules per row. Then, cache the new occurrence for the next
                 // row.
                 for &(register, ref rule) in row.registers() {
-                    if !rule_cache.get(&register).map_or(false, |c| c == &rule) {
+                    if rule_cache.get(&register) != Some(&rule) {
                         rule_cache.insert(register, rule);
-                        written |=
-                            Self::write_register_rule(&mut line, info.arch, register, rule, ra)?;
+                        rules.extend(Self::register_rule(info.arch, register, rule, ra));
                     }
                 }
 
-                if written {
-                    self.inner
-                        .write_all(&line)
-                        .and_then(|_| writeln!(self.inner))?;
+                if !rules.is_empty() {
+                    unwind_rows.push(UnwindRow {
+                        address: row.start_address() - info.load_address,
+                        rules,
+                    });
                 }
             }
+
+            self.sink
+                .add_cfi(start - info.load_address, length, &unwind_rows)?;
         }
 
         Ok(())
     }
 
-    fn write_cfa_rule<R: Reader, T: Write>(
-        mut target: T,
-        arch: Arch,
-        rule: &CfaRule<R>,
-    ) -> Result<bool, CfiError> {
+    fn cfa_rule<R: Reader>(arch: Arch, rule: &CfaRule<R>) -> Option<UnwindRule> {
         let formatted = match rule {
             CfaRule::RegisterAndOffset { register, offset } => {
-                match arch.cpu_family().cfi_register_name(register.0) {
-                    Some(register) => format!("{} {} +", register, *offset),
-                    None => return Ok(false),
-                }
+                let register = arch.cpu_family().cfi_register_name(register.0)?;
+                format!("{} {} +", register, *offset)
             }
-            CfaRule::Expression(_) => return Ok(false),
+            CfaRule::Expression(_) => return None,
         };
 
-        write!(target, " .cfa: {}", formatted)?;
-        Ok(true)
+        Some(UnwindRule::new(".cfa", formatted))
     }
 
-    fn write_register_rule<R: Reader, T: Write>(
-        mut target: T,
+    fn register_rule<R: Reader>(
         arch: Arch,
         register: Register,
         rule: &RegisterRule<R>,
         ra: Register,
-    ) -> Result<bool, CfiError> {
+    ) -> Option<UnwindRule> {
         let formatted = match rule {
-            RegisterRule::Undefined => return Ok(false),
-            RegisterRule::SameValue => match arch.cpu_family().cfi_register_name(register.0) {
-                Some(reg) => reg.into(),
-                None => return Ok(false),
-            },
+            RegisterRule::Undefined => return None,
+            RegisterRule::SameValue => arch.cpu_family().cfi_register_name(register.0)?.into(),
             RegisterRule::Offset(offset) => format!(".cfa {} + ^", offset),
             RegisterRule::ValOffset(offset) => format!(".cfa {} +", offset),
             RegisterRule::Register(register) => {
-                match arch.cpu_family().cfi_register_name(register.0) {
-                    Some(reg) => reg.into(),
-                    None => return Ok(false),
-                }
+                arch.cpu_family().cfi_register_name(register.0)?.into()
             }
-            RegisterRule::Expression(_) => return Ok(false),
-            RegisterRule::ValExpression(_) => return Ok(false),
-            RegisterRule::Architectural => return Ok(false),
+            RegisterRule::Expression(_) => return None,
+            RegisterRule::ValExpression(_) => return None,
+            RegisterRule::Architectural => return None,
         };
 
         // Breakpad requires an explicit name for the return address register. In all other cases,
@@ -740,14 +823,10 @@ impl<W: Write> AsciiCfiWriter<W> {
         let register_name = if register == ra {
             ".ra"
         } else {
-            match arch.cpu_family().cfi_register_name(register.0) {
-                Some(reg) => reg,
-                None => return Ok(false),
-            }
+            arch.cpu_family().cfi_register_name(register.0)?
         };
 
-        write!(target, " {}: {}", register_name, formatted)?;
-        Ok(true)
+        Some(UnwindRule::new(register_name, formatted))
     }
 
     fn process_pdb(&mut self, pdb: &PdbObject<'_>) -> Result<(), CfiError> {
@@ -814,7 +893,7 @@ impl<W: Write> AsciiCfiWriter<W> {
                 && prolog_ranges[0].end == code_ranges[0].start;
 
             if is_contiguous {
-                self.write_pdb_stackinfo(
+                self.add_pdb_stackinfo(
                     string_table.as_ref(),
                     &frame,
                     prolog_ranges[0].start,
@@ -827,17 +906,11 @@ impl<W: Write> AsciiCfiWriter<W> {
                 code_ranges.sort_unstable_by_key(|range| range.start);
 
                 for Range { start, end } in prolog_ranges {
-                    self.write_pdb_stackinfo(
-                        string_table.as_ref(),
-                        &frame,
-                        start,
-                        end,
-                        end - start,
-                    )?;
+                    self.add_pdb_stackinfo(string_table.as_ref(), &frame, start, end, end - start)?;
                 }
 
                 for Range { start, end } in code_ranges {
-                    self.write_pdb_stackinfo(string_table.as_ref(), &frame, start, end, 0)?;
+                    self.add_pdb_stackinfo(string_table.as_ref(), &frame, start, end, 0)?;
                 }
             }
 
@@ -847,7 +920,7 @@ impl<W: Write> AsciiCfiWriter<W> {
         Ok(())
     }
 
-    fn write_pdb_stackinfo(
+    fn add_pdb_stackinfo(
         &mut self,
         string_table: Option<&StringTable<'_>>,
         frame: &FrameData,
@@ -855,45 +928,29 @@ impl<W: Write> AsciiCfiWriter<W> {
         end: Rva,
         prolog_size: u32,
     ) -> Result<(), CfiError> {
-        let code_size = end - start;
-        let has_program = frame.program.is_some() && string_table.is_some();
-
-        write!(
-            self.inner,
-            "STACK WIN {:x} {:x} {:x} {:x} {:x} {:x} {:x} {:x} {:x} {} ",
-            frame.ty as u8,
-            start.0,
-            code_size,
-            prolog_size,
-            0, // epilog_size
-            frame.params_size,
-            frame.saved_regs_size,
-            frame.locals_size,
-            frame.max_stack_size.unwrap_or(0),
-            if has_program { 1 } else { 0 },
-        )?;
-
-        match frame.program {
-            Some(ref prog_ref) => {
-                let string_table = match string_table {
-                    Some(string_table) => string_table,
-                    None => return Ok(writeln!(self.inner)?),
-                };
-
-                let program_string = prog_ref.to_string_lossy(string_table)?;
-
-                writeln!(self.inner, "{}", program_string.trim())?;
+        let program_string = match (frame.program, string_table) {
+            (Some(ref prog_ref), Some(string_table)) => {
+                Some(prog_ref.to_string_lossy(string_table)?)
             }
-            None => {
-                writeln!(
-                    self.inner,
-                    "{}",
-                    if frame.uses_base_pointer { 1 } else { 0 }
-                )?;
-            }
-        }
+            _ => None,
+        };
 
-        Ok(())
+        let record = BreakpadStackWinRecord {
+            ty: win_type_from_u8(frame.ty as u8),
+            code_start: start.0,
+            code_size: end - start,
+            // The prolog is a part of the frame's original prolog, which fits into a `u16`.
+            prolog_size: prolog_size.try_into().unwrap_or(u16::MAX),
+            epilog_size: 0,
+            params_size: frame.params_size,
+            saved_regs_size: frame.saved_regs_size,
+            locals_size: frame.locals_size,
+            max_stack_size: frame.max_stack_size.unwrap_or(0),
+            uses_base_pointer: frame.uses_base_pointer,
+            program_string: program_string.as_deref().map(str::trim),
+        };
+
+        self.sink.add_win(&record)
     }
 
     fn process_pe(&mut self, pe: &PeObject<'_>) -> Result<(), CfiError> {
@@ -903,7 +960,6 @@ impl<W: Write> AsciiCfiWriter<W> {
             None => return Ok(()),
         };
 
-        let mut cfa_reg = Vec::new();
         let mut saved_regs = Vec::new();
         let mut unwind_codes = Vec::new();
 
@@ -926,7 +982,7 @@ impl<W: Write> AsciiCfiWriter<W> {
                 continue;
             }
 
-            cfa_reg.clear();
+            let mut cfa_rule = None;
             saved_regs.clear();
 
             let mut next_function = Some(function);
@@ -951,36 +1007,29 @@ impl<W: Write> AsciiCfiWriter<W> {
                 for code in unwind_codes.iter().rev() {
                     match code.operation {
                         UnwindOperation::SaveNonVolatile(reg, offset) => {
-                            match offset {
+                            let expression = match offset {
                                 // If the Frame Register field in the UNWIND_INFO is zero,
                                 // this offset is from RSP.
                                 StackFrameOffset::RSP(offset) => {
-                                    write!(
-                                        &mut saved_regs,
-                                        " {}: .cfa {} - ^",
-                                        reg.name(),
-                                        stack_size.saturating_sub(offset)
-                                    )?;
+                                    format!(".cfa {} - ^", stack_size.saturating_sub(offset))
                                 }
                                 // If the Frame Register field is nonzero, this offset is from where
                                 // RSP was located when the FP register was established.
                                 // It equals the FP register minus the FP register offset
                                 // (16 * the scaled frame register offset in the UNWIND_INFO).
-                                StackFrameOffset::FP(offset) => {
-                                    write!(
-                                        &mut saved_regs,
-                                        " {}: {} {} + ^",
-                                        reg.name(),
-                                        unwind_info.frame_register.name(),
-                                        offset.saturating_sub(unwind_info.frame_register_offset)
-                                    )?;
-                                }
+                                StackFrameOffset::FP(offset) => format!(
+                                    "{} {} + ^",
+                                    unwind_info.frame_register.name(),
+                                    offset.saturating_sub(unwind_info.frame_register_offset)
+                                ),
                             };
+                            saved_regs.push(UnwindRule::new(reg.name(), expression));
                         }
                         UnwindOperation::PushNonVolatile(reg) => {
                             // $reg = .cfa - current_offset
                             stack_size += 8;
-                            write!(&mut saved_regs, " {}: .cfa {} - ^", reg.name(), stack_size)?;
+                            let expression = format!(".cfa {} - ^", stack_size);
+                            saved_regs.push(UnwindRule::new(reg.name(), expression));
                         }
                         UnwindOperation::Alloc(size) => {
                             stack_size += size;
@@ -992,22 +1041,18 @@ impl<W: Write> AsciiCfiWriter<W> {
                             let offset =
                                 stack_size.saturating_sub(unwind_info.frame_register_offset);
                             // Set the `.cfa = $fp + offset`
-                            write!(
-                                &mut cfa_reg,
-                                ".cfa: {} {} +",
-                                unwind_info.frame_register.name(),
-                                offset
-                            )?;
+                            let expression =
+                                format!("{} {} +", unwind_info.frame_register.name(), offset);
+                            cfa_rule.get_or_insert(UnwindRule::new(".cfa", expression));
                         }
 
                         UnwindOperation::PushMachineFrame(is_error) => {
                             let rsp_offset = stack_size + 16;
                             let rip_offset = stack_size + 40;
-                            write!(
-                                &mut saved_regs,
-                                " $rsp: .cfa {} - ^ .ra: .cfa {} - ^",
-                                rsp_offset, rip_offset,
-                            )?;
+                            let rsp_expression = format!(".cfa {} - ^", rsp_offset);
+                            let rip_expression = format!(".cfa {} - ^", rip_offset);
+                            saved_regs.push(UnwindRule::new("$rsp", rsp_expression));
+                            saved_regs.push(UnwindRule::new(".ra", rip_expression));
                             stack_size += 40;
                             machine_frame_offset = stack_size;
                             stack_size += if is_error { 8 } else { 0 };
@@ -1021,22 +1066,22 @@ impl<W: Write> AsciiCfiWriter<W> {
                 next_function = unwind_info.chained_info;
             }
 
-            if cfa_reg.is_empty() {
-                write!(&mut cfa_reg, ".cfa: $rsp {} +", stack_size)?;
-            }
+            let cfa_rule = cfa_rule
+                .unwrap_or_else(|| UnwindRule::new(".cfa", format!("$rsp {} +", stack_size)));
             if machine_frame_offset == 0 {
-                write!(&mut saved_regs, " .ra: .cfa 8 - ^")?;
+                saved_regs.push(UnwindRule::new(".ra", ".cfa 8 - ^".into()));
             }
 
-            write!(
-                self.inner,
-                "STACK CFI INIT {:x} {:x} ",
-                function.begin_address,
-                function.end_address - function.begin_address,
-            )?;
-            self.inner.write_all(&cfa_reg)?;
-            self.inner.write_all(&saved_regs)?;
-            writeln!(self.inner)?;
+            let start = function.begin_address.into();
+            let mut rules = vec![cfa_rule];
+            rules.append(&mut saved_regs);
+            let row = UnwindRow {
+                address: start,
+                rules,
+            };
+
+            let size = function.end_address - function.begin_address;
+            self.sink.add_cfi(start, size.into(), &[row])?;
         }
 
         Ok(())
@@ -1062,9 +1107,484 @@ impl<'a> CfiCacheV1<'a> {
     }
 }
 
+// Layout of the binary format (version 3), following the 8-byte preamble. All integers are
+// written in native endianness:
+//
+// - Header: number of entries, rule references, rules and Windows records, the size of the string
+//   table and a reserved field, each as `u32`.
+// - Entries: sorted by start address. Each entry has a `u64` start address and size, a `u8` kind,
+//   a `u8` Windows record type, two reserved bytes, and a `u32` index and count. For CFI entries,
+//   these point into the rule references. For Windows entries, the index points into the Windows
+//   records.
+// - Rule references: `u32` indexes into the rules. Each entry lists the full set of rules in
+//   effect for its range, with `STACK CFI` deltas already applied.
+// - Rules: deduplicated pairs of register name and postfix expression, each as a `u32` offset and
+//   length into the string table.
+// - Windows records: the fields of a `STACK WIN` record and the program string.
+// - String table: deduplicated UTF-8 strings.
+
+const V3_HEADER_SIZE: usize = 24;
+const V3_ENTRY_SIZE: usize = 32;
+const V3_RULE_REF_SIZE: usize = 4;
+const V3_RULE_SIZE: usize = 16;
+const V3_WIN_SIZE: usize = 28;
+
+const V3_KIND_CFI: u8 = 0;
+const V3_KIND_WIN: u8 = 1;
+
+const V3_WIN_USES_BASE_POINTER: u16 = 1;
+const V3_WIN_HAS_PROGRAM: u16 = 2;
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset + 2)?;
+    Some(u16::from_ne_bytes(bytes.try_into().unwrap()))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_ne_bytes(bytes.try_into().unwrap()))
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Option<u64> {
+    let bytes = data.get(offset..offset + 8)?;
+    Some(u64::from_ne_bytes(bytes.try_into().unwrap()))
+}
+
+fn win_type_to_u8(ty: BreakpadStackWinRecordType) -> u8 {
+    match ty {
+        BreakpadStackWinRecordType::Unknown => u8::MAX,
+        ty => ty as u8,
+    }
+}
+
+fn win_type_from_u8(ty: u8) -> BreakpadStackWinRecordType {
+    match ty {
+        0 => BreakpadStackWinRecordType::Fpo,
+        1 => BreakpadStackWinRecordType::Trap,
+        2 => BreakpadStackWinRecordType::Tss,
+        3 => BreakpadStackWinRecordType::Standard,
+        4 => BreakpadStackWinRecordType::FrameData,
+        _ => BreakpadStackWinRecordType::Unknown,
+    }
+}
+
+fn to_u32(value: usize) -> Result<u32, CfiError> {
+    value
+        .try_into()
+        .map_err(|e| CfiError::new(CfiErrorKind::WriteFailed, e))
+}
+
+/// An entry of the binary CFI cache format, before entries are sorted and written.
+struct CfiCacheV3Entry {
+    start: u64,
+    size: u64,
+    kind: u8,
+    win_type: u8,
+    index: u32,
+    count: u32,
+}
+
+/// The offset and length of a string in the string table of a binary CFI cache.
+type StringLocation = (u32, u32);
+
+/// Serializes unwind tables into the binary CFI cache format.
+#[derive(Default)]
+struct CfiCacheV3Writer {
+    entries: Vec<CfiCacheV3Entry>,
+    rule_refs: Vec<u8>,
+    rules: Vec<u8>,
+    win_records: Vec<u8>,
+    strings: Vec<u8>,
+    string_map: HashMap<String, StringLocation>,
+    rule_map: HashMap<(StringLocation, StringLocation), u32>,
+    num_rules: u32,
+}
+
+impl CfiCacheV3Writer {
+    fn insert_string(&mut self, string: &str) -> Result<StringLocation, CfiError> {
+        if let Some(&location) = self.string_map.get(string) {
+            return Ok(location);
+        }
+
+        let location = (to_u32(self.strings.len())?, to_u32(string.len())?);
+        self.strings.extend_from_slice(string.as_bytes());
+        self.string_map.insert(string.to_owned(), location);
+        Ok(location)
+    }
+
+    /// Inserts a rule and returns the location of its register name along with its index.
+    fn insert_rule(
+        &mut self,
+        register: &str,
+        expression: &str,
+    ) -> Result<(StringLocation, u32), CfiError> {
+        let register = self.insert_string(register)?;
+        let expression = self.insert_string(expression)?;
+        if let Some(&index) = self.rule_map.get(&(register, expression)) {
+            return Ok((register, index));
+        }
+
+        self.rules.extend_from_slice(&register.0.to_ne_bytes());
+        self.rules.extend_from_slice(&register.1.to_ne_bytes());
+        self.rules.extend_from_slice(&expression.0.to_ne_bytes());
+        self.rules.extend_from_slice(&expression.1.to_ne_bytes());
+
+        let index = self.num_rules;
+        self.num_rules += 1;
+        self.rule_map.insert((register, expression), index);
+        Ok((register, index))
+    }
+
+    fn add_cfi_entry(&mut self, range: Range<u64>, rules: &[u32]) -> Result<(), CfiError> {
+        let index = to_u32(self.rule_refs.len() / V3_RULE_REF_SIZE)?;
+        for rule_index in rules {
+            self.rule_refs.extend_from_slice(&rule_index.to_ne_bytes());
+        }
+
+        self.entries.push(CfiCacheV3Entry {
+            start: range.start,
+            size: range.end - range.start,
+            kind: V3_KIND_CFI,
+            win_type: 0,
+            index,
+            count: to_u32(rules.len())?,
+        });
+        Ok(())
+    }
+
+    fn add_entry(&mut self, entry: &BreakpadCfiEntry<'_>) -> Result<(), CfiError> {
+        match entry.rules {
+            BreakpadUnwindRules::Cfi(ref rules) => {
+                let mut indexes = Vec::with_capacity(rules.len());
+                for rule in rules {
+                    indexes.push(self.insert_rule(rule.register, rule.expression)?.1);
+                }
+                self.add_cfi_entry(entry.range.clone(), &indexes)
+            }
+            BreakpadUnwindRules::Win(ref record) => self.add_win(record),
+        }
+    }
+
+    fn write_to<W: Write>(mut self, mut writer: W) -> Result<(), CfiError> {
+        let header = [
+            to_u32(self.entries.len())?,
+            to_u32(self.rule_refs.len() / V3_RULE_REF_SIZE)?,
+            self.num_rules,
+            to_u32(self.win_records.len() / V3_WIN_SIZE)?,
+            to_u32(self.strings.len())?,
+            0,
+        ];
+
+        for field in &header {
+            writer.write_all(&field.to_ne_bytes())?;
+        }
+
+        self.entries.sort_by_key(|entry| entry.start);
+        for entry in &self.entries {
+            writer.write_all(&entry.start.to_ne_bytes())?;
+            writer.write_all(&entry.size.to_ne_bytes())?;
+            writer.write_all(&[entry.kind, entry.win_type, 0, 0])?;
+            writer.write_all(&entry.index.to_ne_bytes())?;
+            writer.write_all(&entry.count.to_ne_bytes())?;
+            writer.write_all(&0u32.to_ne_bytes())?;
+        }
+
+        writer.write_all(&self.rule_refs)?;
+        writer.write_all(&self.rules)?;
+        writer.write_all(&self.win_records)?;
+        writer.write_all(&self.strings)?;
+        Ok(())
+    }
+}
+
+impl UnwindSink for CfiCacheV3Writer {
+    fn add_cfi(&mut self, start: u64, size: u64, rows: &[UnwindRow]) -> Result<(), CfiError> {
+        let end = start.saturating_add(size);
+
+        // The rules in effect, along with the location of their register names. Rules of later
+        // rows replace earlier rules for the same register.
+        let mut rules: Vec<(StringLocation, u32)> = Vec::new();
+        let mut indexes = Vec::new();
+
+        for (i, row) in rows.iter().enumerate() {
+            if row.address < start || row.address >= end {
+                continue;
+            }
+
+            for rule in &row.rules {
+                let (register, index) = self.insert_rule(&rule.register, &rule.expression)?;
+                match rules.iter_mut().find(|(r, _)| *r == register) {
+                    Some(existing) => existing.1 = index,
+                    None => rules.push((register, index)),
+                }
+            }
+
+            let row_end = rows.get(i + 1).map_or(end, |next| next.address.min(end));
+            if row_end > row.address {
+                indexes.clear();
+                indexes.extend(rules.iter().map(|&(_, index)| index));
+                self.add_cfi_entry(row.address..row_end, &indexes)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn add_cfi_record(&mut self, record: &BreakpadStackCfiRecord<'_>) -> Result<(), CfiError> {
+        let record = BreakpadStackRecord::Cfi(record.clone());
+        for entry in &BreakpadCfiTable::from_records(std::iter::once(Ok(record)))? {
+            self.add_entry(entry)?;
+        }
+        Ok(())
+    }
+
+    fn add_win(&mut self, record: &BreakpadStackWinRecord<'_>) -> Result<(), CfiError> {
+        let range = record.code_range();
+        if range.is_empty() {
+            return Ok(());
+        }
+
+        let index = to_u32(self.win_records.len() / V3_WIN_SIZE)?;
+        let mut flags = 0;
+        if record.uses_base_pointer {
+            flags |= V3_WIN_USES_BASE_POINTER;
+        }
+        let (program_offset, program_len) = match record.program_string {
+            Some(program) => {
+                flags |= V3_WIN_HAS_PROGRAM;
+                self.insert_string(program)?
+            }
+            None => (0, 0),
+        };
+
+        let win = &mut self.win_records;
+        win.extend_from_slice(&record.prolog_size.to_ne_bytes());
+        win.extend_from_slice(&record.epilog_size.to_ne_bytes());
+        win.extend_from_slice(&record.params_size.to_ne_bytes());
+        win.extend_from_slice(&record.saved_regs_size.to_ne_bytes());
+        win.extend_from_slice(&flags.to_ne_bytes());
+        win.extend_from_slice(&record.locals_size.to_ne_bytes());
+        win.extend_from_slice(&record.max_stack_size.to_ne_bytes());
+        win.extend_from_slice(&program_offset.to_ne_bytes());
+        win.extend_from_slice(&program_len.to_ne_bytes());
+
+        self.entries.push(CfiCacheV3Entry {
+            start: range.start.into(),
+            size: (range.end - range.start).into(),
+            kind: V3_KIND_WIN,
+            win_type: win_type_to_u8(record.ty),
+            index,
+            count: 1,
+        });
+        Ok(())
+    }
+}
+
+/// A binary CFI cache that is evaluated directly from its underlying buffer.
+struct CfiCacheV3<'a> {
+    byteview: ByteView<'a>,
+    num_entries: usize,
+    num_rule_refs: usize,
+    rule_refs_offset: usize,
+    rules_offset: usize,
+    win_records_offset: usize,
+    strings_offset: usize,
+    strings_len: usize,
+}
+
+impl<'a> CfiCacheV3<'a> {
+    fn parse(byteview: ByteView<'a>) -> Result<Self, CfiError> {
+        let data = byteview.get(8..).unwrap_or_default();
+        let header = data.get(..V3_HEADER_SIZE).ok_or(CfiErrorKind::BadFormat)?;
+
+        let field = |index: usize| read_u32(header, index * 4).unwrap_or_default() as usize;
+        let num_entries = field(0);
+        let num_rule_refs = field(1);
+        let num_rules = field(2);
+        let num_win_records = field(3);
+        let strings_len = field(4);
+
+        // The counts are untrusted, so compute the section offsets without overflowing.
+        let section_end = |offset: usize, count: usize, size: usize| {
+            count
+                .checked_mul(size)
+                .and_then(|len| offset.checked_add(len))
+                .ok_or(CfiErrorKind::BadFormat)
+        };
+
+        let entries_offset = 8 + V3_HEADER_SIZE;
+        let rule_refs_offset = section_end(entries_offset, num_entries, V3_ENTRY_SIZE)?;
+        let rules_offset = section_end(rule_refs_offset, num_rule_refs, V3_RULE_REF_SIZE)?;
+        let win_records_offset = section_end(rules_offset, num_rules, V3_RULE_SIZE)?;
+        let strings_offset = section_end(win_records_offset, num_win_records, V3_WIN_SIZE)?;
+
+        if byteview.len() < section_end(strings_offset, strings_len, 1)? {
+            return Err(CfiErrorKind::BadFormat.into());
+        }
+
+        Ok(CfiCacheV3 {
+            byteview,
+            num_entries,
+            num_rule_refs,
+            rule_refs_offset,
+            rules_offset,
+            win_records_offset,
+            strings_offset,
+            strings_len,
+        })
+    }
+
+    fn raw(&self) -> &[u8] {
+        &self.byteview
+    }
+
+    fn entry_start(&self, index: usize) -> Option<u64> {
+        read_u64(&self.byteview, 8 + V3_HEADER_SIZE + index * V3_ENTRY_SIZE)
+    }
+
+    fn string(&self, offset: u32, len: u32) -> Option<&str> {
+        let start = offset as usize;
+        let end = start.checked_add(len as usize)?;
+        if end > self.strings_len {
+            return None;
+        }
+
+        let bytes = self
+            .byteview
+            .get(self.strings_offset + start..self.strings_offset + end)?;
+        std::str::from_utf8(bytes).ok()
+    }
+
+    fn rule(&self, index: usize) -> Option<BreakpadCfiRule<'_>> {
+        let offset = self.rules_offset + index * V3_RULE_SIZE;
+        if offset >= self.win_records_offset {
+            return None;
+        }
+
+        let data = &self.byteview;
+        Some(BreakpadCfiRule {
+            register: self.string(read_u32(data, offset)?, read_u32(data, offset + 4)?)?,
+            expression: self.string(read_u32(data, offset + 8)?, read_u32(data, offset + 12)?)?,
+        })
+    }
+
+    fn win_record(
+        &self,
+        index: usize,
+        ty: u8,
+        range: &Range<u64>,
+    ) -> Option<BreakpadStackWinRecord<'_>> {
+        let offset = self.win_records_offset + index * V3_WIN_SIZE;
+        if offset >= self.strings_offset {
+            return None;
+        }
+
+        let data = &self.byteview;
+        let flags = read_u16(data, offset + 10)?;
+        let program_string = if flags & V3_WIN_HAS_PROGRAM != 0 {
+            Some(self.string(read_u32(data, offset + 20)?, read_u32(data, offset + 24)?)?)
+        } else {
+            None
+        };
+
+        Some(BreakpadStackWinRecord {
+            ty: win_type_from_u8(ty),
+            code_start: range.start.try_into().ok()?,
+            code_size: (range.end - range.start).try_into().ok()?,
+            prolog_size: read_u16(data, offset)?,
+            epilog_size: read_u16(data, offset + 2)?,
+            params_size: read_u32(data, offset + 4)?,
+            saved_regs_size: read_u16(data, offset + 8)?,
+            locals_size: read_u32(data, offset + 12)?,
+            max_stack_size: read_u32(data, offset + 16)?,
+            uses_base_pointer: flags & V3_WIN_USES_BASE_POINTER != 0,
+            program_string,
+        })
+    }
+
+    fn entry(&self, index: usize) -> Option<BreakpadCfiEntry<'_>> {
+        if index >= self.num_entries {
+            return None;
+        }
+
+        let offset = 8 + V3_HEADER_SIZE + index * V3_ENTRY_SIZE;
+        let data = &self.byteview;
+        let start = read_u64(data, offset)?;
+        let size = read_u64(data, offset + 8)?;
+        let kind = *data.get(offset + 16)?;
+        let win_type = *data.get(offset + 17)?;
+        let first = read_u32(data, offset + 20)? as usize;
+        let count = read_u32(data, offset + 24)? as usize;
+        let range = start..start.checked_add(size)?;
+
+        let rules = match kind {
+            V3_KIND_CFI => {
+                // Validate the count against the rule references before allocating.
+                let refs = first..first.checked_add(count)?;
+                if refs.end > self.num_rule_refs {
+                    return None;
+                }
+
+                let mut rules = Vec::with_capacity(count);
+                for ref_index in refs {
+                    let ref_offset = self.rule_refs_offset + ref_index * V3_RULE_REF_SIZE;
+                    rules.push(self.rule(read_u32(data, ref_offset)? as usize)?);
+                }
+                BreakpadUnwindRules::Cfi(rules)
+            }
+            V3_KIND_WIN => BreakpadUnwindRules::Win(self.win_record(first, win_type, &range)?),
+            _ => return None,
+        };
+
+        Some(BreakpadCfiEntry { range, rules })
+    }
+
+    fn lookup(&self, address: u64) -> Option<BreakpadCfiEntry<'_>> {
+        // Binary search for the first entry starting after the address.
+        let mut low = 0;
+        let mut high = self.num_entries;
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if self.entry_start(mid)? <= address {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+
+        (0..low)
+            .rev()
+            .filter_map(|index| self.entry(index))
+            .find(|entry| entry.range.contains(&address))
+    }
+
+    fn write_ascii<W: Write>(&self, mut writer: W) -> Result<(), io::Error> {
+        for index in 0..self.num_entries {
+            let entry = match self.entry(index) {
+                Some(entry) => entry,
+                None => continue,
+            };
+
+            match entry.rules {
+                BreakpadUnwindRules::Cfi(_) => writeln!(
+                    writer,
+                    "STACK CFI INIT {:x} {:x} {}",
+                    entry.range.start,
+                    entry.range.end - entry.range.start,
+                    entry.cfi_rules_string().unwrap_or_default()
+                )?,
+                BreakpadUnwindRules::Win(ref r) => write_win_record(&mut writer, r)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
 enum CfiCacheInner<'a> {
     Unversioned(CfiCacheV1<'a>),
     Versioned(u32, CfiCacheV1<'a>),
+    Binary(CfiCacheV3<'a>),
 }
 
 /// A cache file for call frame information (CFI).
@@ -1072,6 +1592,11 @@ enum CfiCacheInner<'a> {
 /// The default way to use this cache is to construct it from an `Object` and save it to a file.
 /// Then, load it from the file and pass it to the minidump processor.
 ///
+/// Since version 3, caches use a binary format with unwind rules sorted by address. Rules can be
+/// looked up directly from a memory-mapped file via [`lookup`](Self::lookup) without parsing the
+/// entire cache. Caches in the older text-based formats can still be loaded and passed to the
+/// processor.
+///
 /// ```rust,no_run
 /// use std::fs::File;
 /// use symbolic_common::ByteView;
@@ -1104,12 +1629,14 @@ pub struct CfiCache<'a> {
 impl CfiCache<'static> {
     /// Construct a CFI cache from an `Object`.
     pub fn from_object(object: &Object<'_>) -> Result<Self, CfiError> {
+        let mut writer = CfiCacheV3Writer::default();
+        CfiExtractor { sink: &mut writer }.process(object)?;
+
         let mut buffer = vec![];
         write_preamble(&mut buffer, CFICACHE_LATEST_VERSION)?;
-        AsciiCfiWriter::new(&mut buffer).process(object)?;
+        writer.write_to(&mut buffer)?;
 
-        let byteview = ByteView::from_vec(buffer);
-        let inner = CfiCacheInner::Versioned(CFICACHE_LATEST_VERSION, CfiCacheV1 { byteview });
+        let inner = CfiCacheInner::Binary(CfiCacheV3::parse(ByteView::from_vec(buffer))?);
         Ok(CfiCache { inner })
     }
 }
@@ -1131,7 +1658,11 @@ impl<'a> CfiCache<'a> {
             let magic = u32::from_ne_bytes(preamble[0..4].try_into().unwrap());
             if magic == CFICACHE_MAGIC {
                 let version = u32::from_ne_bytes(preamble[4..8].try_into().unwrap());
-                let inner = CfiCacheInner::Versioned(version, CfiCacheV1 { byteview });
+                let inner = match version {
+                    1 | 2 => CfiCacheInner::Versioned(version, CfiCacheV1 { byteview }),
+                    CFICACHE_LATEST_VERSION => CfiCacheInner::Binary(CfiCacheV3::parse(byteview)?),
+                    _ => return Err(CfiErrorKind::BadFormat.into()),
+                };
                 return Ok(CfiCache { inner });
             }
         }
@@ -1144,6 +1675,7 @@ impl<'a> CfiCache<'a> {
         match self.inner {
             CfiCacheInner::Unversioned(_) => 1,
             CfiCacheInner::Versioned(version, _) => version,
+            CfiCacheInner::Binary(_) => CFICACHE_LATEST_VERSION,
         }
     }
 
@@ -1152,20 +1684,310 @@ impl<'a> CfiCache<'a> {
         self.version() == CFICACHE_LATEST_VERSION
     }
 
+    /// Returns whether this cache uses the binary format, which supports [`lookup`](Self::lookup).
+    #[cfg(feature = "processor")]
+    pub(crate) fn is_binary(&self) -> bool {
+        matches!(self.inner, CfiCacheInner::Binary(_))
+    }
+
     /// Returns the raw buffer of the cache file.
+    ///
+    /// For caches in the binary format, this is not the Breakpad ASCII format. Use
+    /// [`to_breakpad_cfi`](Self::to_breakpad_cfi) to obtain `STACK` records instead.
     pub fn as_slice(&self) -> &[u8] {
         match self.inner {
             CfiCacheInner::Unversioned(ref v1) => v1.raw(),
             CfiCacheInner::Versioned(_, ref v1) => &v1.raw()[8..],
+            CfiCacheInner::Binary(ref v3) => &v3.raw()[8..],
+        }
+    }
+
+    /// Returns the contents of this cache as Breakpad `STACK CFI` and `STACK WIN` records.
+    ///
+    /// For text-based caches, this borrows the underlying buffer. Binary caches are converted
+    /// into `STACK CFI INIT` records with all deltas applied.
+    pub fn to_breakpad_cfi(&self) -> Cow<'_, [u8]> {
+        match self.inner {
+            CfiCacheInner::Binary(ref v3) => {
+                let mut buffer = Vec::new();
+                // Writing into a vector cannot fail.
+                v3.write_ascii(&mut buffer).ok();
+                Cow::Owned(buffer)
+            }
+            _ => Cow::Borrowed(self.as_slice()),
+        }
+    }
+
+    /// Looks up the unwind rules in effect at the given module-relative address.
+    ///
+    /// The lookup is performed directly on the cache's buffer. Returns `None` if no rules cover
+    /// the address, or if this cache uses a text-based format older than version 3. Such caches
+    /// should be regenerated.
+    pub fn lookup(&self, address: u64) -> Option<BreakpadCfiEntry<'_>> {
+        match self.inner {
+            CfiCacheInner::Binary(ref v3) => v3.lookup(address),
+            _ => None,
         }
     }
 
     /// Writes the cache to the given writer.
     pub fn write_to<W: Write>(&self, mut writer: W) -> Result<(), io::Error> {
-        if let CfiCacheInner::Versioned(version, _) = self.inner {
-            write_preamble(&mut writer, version)?;
+        if !matches!(self.inner, CfiCacheInner::Unversioned(_)) {
+            write_preamble(&mut writer, self.version())?;
         }
         io::copy(&mut self.as_slice(), &mut writer)?;
         Ok(())
     }
 }
+
+/// The kind of inconsistency reported in an [`UnwindIssue`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum UnwindIssueKind {
+    /// Code of a function is not covered by any unwind information.
+    ///
+    /// Stackwalkers fall back to frame pointers or stack scanning in this range.
+    Missing,
+    /// Multiple unwind entries cover the same code.
+    ///
+    /// Stackwalkers pick one of the entries, which may not describe the actual frame layout.
+    Overlapping,
+}
+
+impl fmt::Display for UnwindIssueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing => write!(f, "missing unwind information"),
+            Self::Overlapping => write!(f, "overlapping unwind information"),
+        }
+    }
+}
+
+/// A range of code with missing or overlapping unwind information.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UnwindIssue {
+    /// The kind of inconsistency.
+    pub kind: UnwindIssueKind,
+    /// The affected range of addresses, relative to the module's load address.
+    pub range: Range<u64>,
+    /// The name of the function containing the start of the range, if known.
+    pub function: Option<String>,
+}
+
+/// A function boundary registered with an [`UnwindChecker`].
+#[derive(Clone, Debug)]
+struct CheckedFunction {
+    range: Range<u64>,
+    name: Option<String>,
+}
+
+/// Cross-validates unwind information against function boundaries.
+///
+/// Hand-written assembly frequently lacks CFI directives, or declares them with wrong bounds. The
+/// resulting gaps only become visible once a crash in such code produces a broken stack trace.
+/// This checker reports all code within known functions that is not covered by unwind
+/// information, as well as ranges covered by more than one unwind entry.
+///
+/// Use [`from_object`](Self::from_object) to check an object file, or register function and
+/// unwind ranges from other sources manually.
+///
+/// Note that leaf functions do not require unwind information on some platforms, such as x86_64
+/// Windows. They are reported as missing nevertheless. Likewise, symbols without a size in the
+/// object file extend up to the next symbol, so alignment padding at their end is reported as
+/// missing, too.
+///
+/// ```rust,no_run
+/// use symbolic_common::ByteView;
+/// use symbolic_debuginfo::Object;
+/// use symbolic_minidump::cfi::UnwindChecker;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let view = ByteView::open("/path/to/object")?;
+/// let object = Object::parse(&view)?;
+///
+/// for issue in UnwindChecker::from_object(&object)?.check() {
+///     println!("{:#x}..{:#x}: {}", issue.range.start, issue.range.end, issue.kind);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct UnwindChecker {
+    functions: Vec<CheckedFunction>,
+    unwind_ranges: Vec<Range<u64>>,
+    nested_ranges: Vec<Range<u64>>,
+}
+
+impl UnwindChecker {
+    /// Creates a checker without any functions or unwind information.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a checker from the functions and unwind information of an object.
+    ///
+    /// Function boundaries are taken from debug information, complemented by symbols outside of
+    /// any function in the debug information. Unwind information is read the same way as for
+    /// [`CfiCache::from_object`].
+    ///
+    /// `STACK WIN` records of x86 Windows binaries describe the stages of a function's prolog
+    /// with nested ranges, so they only count towards coverage and are never reported as
+    /// overlapping.
+    pub fn from_object(object: &Object<'_>) -> Result<Self, CfiError> {
+        let mut checker = Self::new();
+
+        let mut ascii = vec![];
+        AsciiCfiWriter::new(&mut ascii).process(object)?;
+        let table = BreakpadCfiTable::from_records(BreakpadStackRecords::new(&ascii))?;
+        for entry in &table {
+            match entry.rules {
+                BreakpadUnwindRules::Cfi(_) => checker.add_unwind_range(entry.range.clone()),
+                BreakpadUnwindRules::Win(_) => checker.nested_ranges.push(entry.range.clone()),
+            }
+        }
+
+        if object.has_debug_info() {
+            let session = object.debug_session()?;
+            for function in session.functions() {
+                let function = function?;
+                let name = Some(function.name.as_str());
+                for range in function.address_ranges() {
+                    checker.add_function(name, range);
+                }
+            }
+        }
+
+        // Assembly code usually has symbols, but no function entries in debug information.
+        let mut debug_ranges: Vec<_> = checker.functions.iter().map(|f| f.range.clone()).collect();
+        debug_ranges.sort_by_key(|range| range.start);
+
+        for symbol in object.symbol_map().iter() {
+            let index = debug_ranges.partition_point(|range| range.start <= symbol.address);
+            let is_debug_function = debug_ranges[..index]
+                .iter()
+                .any(|range| range.contains(&symbol.address));
+
+            if symbol.size > 0 && !is_debug_function {
+                let range = symbol.address..symbol.address + symbol.size;
+                checker.add_function(symbol.name(), range);
+            }
+        }
+
+        Ok(checker)
+    }
+
+    /// Registers the code range of a function.
+    ///
+    /// Functions split into multiple ranges can be registered once per range. Empty ranges are
+    /// ignored.
+    pub fn add_function(&mut self, name: Option<&str>, range: Range<u64>) {
+        if !range.is_empty() {
+            self.functions.push(CheckedFunction {
+                range,
+                name: name.map(str::to_owned),
+            });
+        }
+    }
+
+    /// Registers a range of code covered by a single unwind entry.
+    ///
+    /// Empty ranges are ignored.
+    pub fn add_unwind_range(&mut self, range: Range<u64>) {
+        if !range.is_empty() {
+            self.unwind_ranges.push(range);
+        }
+    }
+
+    /// Returns the number of registered functions.
+    pub fn function_count(&self) -> usize {
+        self.functions.len()
+    }
+
+    /// Returns the number of registered unwind entries.
+    pub fn unwind_range_count(&self) -> usize {
+        self.unwind_ranges.len() + self.nested_ranges.len()
+    }
+
+    /// Returns all inconsistencies between the functions and unwind information.
+    ///
+    /// Issues are sorted by their start address. Code outside of registered functions is never
+    /// reported as missing, but unwind entries may overlap anywhere.
+    pub fn check(&self) -> Vec<UnwindIssue> {
+        let mut functions = self.functions.clone();
+        functions.sort_by_key(|function| (function.range.start, function.range.end));
+
+        let mut unwind_ranges = self.unwind_ranges.clone();
+        unwind_ranges.sort_by_key(|range| (range.start, range.end));
+
+        let mut issues = Vec::new();
+
+        // Merge the unwind ranges into disjoint ranges, recording overlaps along the way.
+        let mut covered: Vec<Range<u64>> = Vec::new();
+        for range in unwind_ranges {
+            match covered.last_mut() {
+                Some(last) if range.start < last.end => {
+                    let overlap = range.start..range.end.min(last.end);
+                    issues.push(UnwindIssue {
+                        kind: UnwindIssueKind::Overlapping,
+                        function: function_name(&functions, overlap.start),
+                        range: overlap,
+                    });
+                    last.end = last.end.max(range.end);
+                }
+                _ => covered.push(range),
+            }
+        }
+
+        if !self.nested_ranges.is_empty() {
+            covered.extend(self.nested_ranges.iter().cloned());
+            covered.sort_by_key(|range| range.start);
+            covered.dedup_by(|next, last| {
+                let overlaps = next.start <= last.end;
+                if overlaps {
+                    last.end = last.end.max(next.end);
+                }
+                overlaps
+            });
+        }
+
+        for function in &functions {
+            let index = covered.partition_point(|range| range.end <= function.range.start);
+            let mut start = function.range.start;
+
+            for range in &covered[index..] {
+                if range.start >= function.range.end {
+                    break;
+                }
+                if range.start > start {
+                    issues.push(UnwindIssue {
+                        kind: UnwindIssueKind::Missing,
+                        range: start..range.start,
+                        function: function.name.clone(),
+                    });
+                }
+                start = start.max(range.end);
+            }
+
+            if start < function.range.end {
+                issues.push(UnwindIssue {
+                    kind: UnwindIssueKind::Missing,
+                    range: start..function.range.end,
+                    function: function.name.clone(),
+                });
+            }
+        }
+
+        issues.sort_by_key(|issue| (issue.range.start, issue.range.end));
+        issues
+    }
+}
+
+/// Returns the name of the last function starting before and containing the address.
+fn function_name(functions: &[CheckedFunction], address: u64) -> Option<String> {
+    let index = functions.partition_point(|function| function.range.start <= address);
+    functions[..index]
+        .iter()
+        .rev()
+        .find(|function| function.range.contains(&address))
+        .and_then(|function| function.name.clone())
+}
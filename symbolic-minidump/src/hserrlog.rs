@@ -0,0 +1,765 @@
+//! Parsing of HotSpot fatal error logs.
+//!
+//! When the HotSpot JVM encounters a fatal error, such as a segmentation fault in native code, it
+//! writes a plain text `hs_err_pid<pid>.log` file. Among other things, this log contains the
+//! native and Java stack of the crashed thread, its registers, a list of all threads and the
+//! loaded dynamic libraries.
+//!
+//! The log is parsed into an [`HsErrLog`], which contains threads with their stack frames, the
+//! list of loaded libraries, and information on the signal. Native frames are resolved to absolute
+//! instruction addresses where possible, so that they can be symbolicated like the frames of a
+//! processed minidump.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use thiserror::Error;
+
+use symbolic_common::Arch;
+
+use crate::utils::{file_name, parse_hex};
+
+lazy_static! {
+    static ref SIGNAL_RE: Regex = Regex::new(
+        r"^#\s+(\S+) \((0x[0-9a-fA-F]+)\) at pc=(0x[0-9a-fA-F]+), pid=(\d+), tid=(0x[0-9a-fA-F]+|\d+)"
+    )
+    .unwrap();
+    static ref VM_ARCH_RE: Regex =
+        Regex::new(r"\b(?:linux|windows|bsd|solaris|aix)-(\w+)\b").unwrap();
+    static ref CURRENT_THREAD_RE: Regex =
+        Regex::new(r"^Current thread \((0x[0-9a-fA-F]+)\):\s+(.*)$").unwrap();
+    static ref THREAD_RE: Regex =
+        Regex::new(r"^(=>)?\s*(0x[0-9a-fA-F]+)\s+(?:\(exited\)\s+)?(\w+)(?:\s+.*)?$").unwrap();
+    static ref THREAD_NAME_RE: Regex = Regex::new(r#"^\w+\s+"(.*?)""#).unwrap();
+    static ref THREAD_STATE_RE: Regex = Regex::new(r"\[(_thread_\w+)").unwrap();
+    static ref THREAD_ID_RE: Regex = Regex::new(r"\bid=(\d+)").unwrap();
+    static ref FRAME_RE: Regex = Regex::new(r"^([CVvJjA])\s+(.*)$").unwrap();
+    static ref LIBRARY_FRAME_RE: Regex =
+        Regex::new(r"^\[(.+?)\+0x([0-9a-fA-F]+)\](?:\s+(.*))?$").unwrap();
+    static ref ADDRESS_FRAME_RE: Regex = Regex::new(r"^0x([0-9a-fA-F]+)(?:\s+(.*))?$").unwrap();
+    static ref COMPILED_FRAME_RE: Regex =
+        Regex::new(r"^(?:\d+%?\s+)?(?:[cC][12]\s+|jvmci\s+)?(\S+)(?:.*?@ 0x([0-9a-fA-F]+))?")
+            .unwrap();
+    static ref SYMBOL_OFFSET_RE: Regex = Regex::new(r"^(.*)\+0x([0-9a-fA-F]+)$").unwrap();
+    static ref SIGINFO_ADDRESS_RE: Regex =
+        Regex::new(r"(?:si_addr: |address )(0x[0-9a-fA-F]+)").unwrap();
+    static ref REGISTER_RE: Regex = Regex::new(r"(\w+)\s*=\s*(0x[0-9a-fA-F]+)").unwrap();
+    static ref MAPS_LIBRARY_RE: Regex = Regex::new(
+        r"^([0-9a-fA-F]+)-([0-9a-fA-F]+)\s+\S{4}\s+[0-9a-fA-F]+\s+\S+\s+\d+\s*(.*)$"
+    )
+    .unwrap();
+    static ref RANGE_LIBRARY_RE: Regex =
+        Regex::new(r"^0x([0-9a-fA-F]+)\s+-\s+0x([0-9a-fA-F]+)\s+(.+)$").unwrap();
+    static ref BASE_LIBRARY_RE: Regex = Regex::new(r"^0x([0-9a-fA-F]+)\s+(.+)$").unwrap();
+}
+
+/// The error type for [`HsErrLogError`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HsErrLogErrorKind {
+    /// The data is not a HotSpot fatal error log.
+    UnknownFormat,
+}
+
+impl fmt::Display for HsErrLogErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownFormat => write!(f, "unknown fatal error log format"),
+        }
+    }
+}
+
+/// An error when parsing a HotSpot fatal error log.
+#[derive(Debug, Error)]
+#[error("{kind}")]
+pub struct HsErrLogError {
+    kind: HsErrLogErrorKind,
+    #[source]
+    source: Option<Box<dyn Error + Send + Sync + 'static>>,
+}
+
+impl HsErrLogError {
+    /// Returns the corresponding [`HsErrLogErrorKind`] for this error.
+    pub fn kind(&self) -> HsErrLogErrorKind {
+        self.kind
+    }
+}
+
+impl From<HsErrLogErrorKind> for HsErrLogError {
+    fn from(kind: HsErrLogErrorKind) -> Self {
+        Self { kind, source: None }
+    }
+}
+
+/// The kind of a stack [`Frame`], as denoted by the first column of the log.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FrameKind {
+    /// Native code, marked with `C`.
+    Native,
+    /// Code of the JVM itself, marked with `V`.
+    Vm,
+    /// Code generated by the JVM, such as stubs, marked with `v`.
+    VmGenerated,
+    /// Java code compiled by the JIT compiler, marked with `J`.
+    CompiledJava,
+    /// Java code compiled ahead of time, marked with `A`.
+    AotCompiledJava,
+    /// Interpreted Java code, marked with `j`.
+    InterpretedJava,
+}
+
+impl FrameKind {
+    fn from_marker(marker: &str) -> Option<Self> {
+        Some(match marker {
+            "C" => Self::Native,
+            "V" => Self::Vm,
+            "v" => Self::VmGenerated,
+            "J" => Self::CompiledJava,
+            "A" => Self::AotCompiledJava,
+            "j" => Self::InterpretedJava,
+            _ => return None,
+        })
+    }
+
+    /// Returns true if this frame executes machine code that can be symbolicated natively.
+    pub fn is_native(self) -> bool {
+        matches!(self, Self::Native | Self::Vm)
+    }
+}
+
+/// A stack frame of a thread in an [`HsErrLog`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    /// The kind of code executed by this frame.
+    pub kind: FrameKind,
+    /// The absolute instruction address, if known.
+    pub instruction: Option<u64>,
+    /// The index of the library containing the instruction.
+    pub module: Option<usize>,
+    /// The name of the library containing the instruction, as written in the frame.
+    pub module_name: Option<String>,
+    /// The offset of the instruction from the start of the library.
+    pub module_offset: Option<u64>,
+    /// The symbol or Java method name, if available.
+    pub symbol: Option<String>,
+    /// The offset of the instruction from the start of the symbol.
+    pub symbol_offset: Option<u64>,
+}
+
+impl Frame {
+    /// Parses a single frame line, such as `C  [libc.so.6+0x18b6d5]  memmove+0x35`.
+    fn parse(line: &str) -> Option<Self> {
+        let caps = FRAME_RE.captures(line.trim())?;
+        let kind = FrameKind::from_marker(&caps[1])?;
+        let text = caps[2].trim();
+
+        let mut frame = Frame {
+            kind,
+            instruction: None,
+            module: None,
+            module_name: None,
+            module_offset: None,
+            symbol: None,
+            symbol_offset: None,
+        };
+
+        match kind {
+            FrameKind::CompiledJava | FrameKind::AotCompiledJava => {
+                if let Some(caps) = COMPILED_FRAME_RE.captures(text) {
+                    frame.symbol = Some(caps[1].to_owned());
+                    frame.instruction = caps.get(2).and_then(|m| parse_hex(m.as_str()));
+                }
+            }
+            FrameKind::InterpretedJava => {
+                frame.symbol = text.split_whitespace().next().map(str::to_owned);
+            }
+            _ => {
+                let symbol = if let Some(caps) = LIBRARY_FRAME_RE.captures(text) {
+                    frame.module_name = Some(caps[1].to_owned());
+                    frame.module_offset = parse_hex(&caps[2]);
+                    caps.get(3).map(|m| m.as_str())
+                } else if let Some(caps) = ADDRESS_FRAME_RE.captures(text) {
+                    frame.instruction = parse_hex(&caps[1]);
+                    caps.get(2).map(|m| m.as_str())
+                } else {
+                    Some(text)
+                };
+
+                let symbol = symbol.map(str::trim).filter(|s| !s.is_empty());
+                match symbol.and_then(|s| SYMBOL_OFFSET_RE.captures(s)) {
+                    Some(caps) => {
+                        frame.symbol = Some(caps[1].to_owned());
+                        frame.symbol_offset = parse_hex(&caps[2]);
+                    }
+                    None => frame.symbol = symbol.map(str::to_owned),
+                }
+            }
+        }
+
+        Some(frame)
+    }
+}
+
+/// A thread in an [`HsErrLog`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Thread {
+    /// The address of the JVM's thread object.
+    pub address: u64,
+    /// The operating system's thread identifier, if known.
+    pub thread_id: Option<u64>,
+    /// The type of thread, such as `JavaThread` or `VMThread`.
+    pub ty: String,
+    /// The name of the thread.
+    pub name: Option<String>,
+    /// The JVM state of the thread, such as `_thread_in_native`.
+    pub state: Option<String>,
+    /// Whether this thread crashed.
+    pub crashed: bool,
+    /// The native stack frames of this thread, starting with the innermost frame.
+    ///
+    /// The log only contains stacks of the crashed thread. If the native stack could not be
+    /// walked, this contains the Java frames instead.
+    pub frames: Vec<Frame>,
+    /// The Java stack frames of this thread, starting with the innermost frame.
+    pub java_frames: Vec<Frame>,
+    /// The register values of this thread, only available for the crashed thread.
+    pub registers: BTreeMap<String, u64>,
+}
+
+impl Thread {
+    /// Parses the description of a thread following its address, such as
+    /// `JavaThread "main" [_thread_in_native, id=12346, stack(0x1000,0x2000)]`.
+    fn parse(address: u64, description: &str) -> Self {
+        Thread {
+            address,
+            thread_id: THREAD_ID_RE
+                .captures(description)
+                .and_then(|caps| caps[1].parse().ok()),
+            ty: description
+                .split_whitespace()
+                .find(|s| *s != "(exited)")
+                .unwrap_or_default()
+                .to_owned(),
+            name: THREAD_NAME_RE
+                .captures(description.trim_start_matches("(exited) "))
+                .map(|caps| caps[1].to_owned()),
+            state: THREAD_STATE_RE
+                .captures(description)
+                .map(|caps| caps[1].to_owned()),
+            ..Default::default()
+        }
+    }
+}
+
+/// A dynamic library loaded into the crashed process.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Library {
+    /// The address at which the library is loaded.
+    pub base_address: u64,
+    /// The size of the library in memory, or `0` if unknown.
+    pub size: u64,
+    /// The file name of the library.
+    pub name: String,
+    /// The full path of the library.
+    pub path: String,
+}
+
+impl Library {
+    /// Returns true if the given address lies within this library.
+    pub fn contains(&self, address: u64) -> bool {
+        address >= self.base_address && address - self.base_address < self.size
+    }
+}
+
+/// A parsed HotSpot fatal error log (`hs_err_pid<pid>.log`).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HsErrLog {
+    /// The signal or exception that caused the crash, such as `SIGSEGV` or
+    /// `EXCEPTION_ACCESS_VIOLATION`.
+    pub signal: Option<String>,
+    /// The numeric code of the signal or exception.
+    pub signal_code: Option<u64>,
+    /// The program counter at the time of the crash.
+    pub pc: Option<u64>,
+    /// The address that caused the crash, if known.
+    pub crash_address: Option<u64>,
+    /// The process identifier.
+    pub pid: Option<u64>,
+    /// The version of the Java Runtime Environment.
+    pub jre_version: Option<String>,
+    /// The name and configuration of the Java VM.
+    pub vm: Option<String>,
+    /// The CPU architecture of the crashed process.
+    pub cpu_arch: Arch,
+    /// The operating system, as reported by `uname` or the OS release.
+    pub os: Option<String>,
+    /// The frame in which the crash occurred, as reported in the log's header.
+    pub problematic_frame: Option<Frame>,
+    /// All threads of the process.
+    pub threads: Vec<Thread>,
+    /// All loaded dynamic libraries.
+    pub libraries: Vec<Library>,
+}
+
+/// The section of the log that is currently being parsed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Section {
+    Other,
+    NativeFrames,
+    JavaFrames,
+    Registers,
+    Threads,
+    Libraries,
+}
+
+impl HsErrLog {
+    /// Parses a HotSpot fatal error log.
+    pub fn parse(data: &[u8]) -> Result<Self, HsErrLogError> {
+        let text = String::from_utf8_lossy(data);
+
+        let mut log = HsErrLog::default();
+        let mut current = None;
+        let mut tid = None;
+        let mut section = Section::Other;
+        let mut lines = text.lines();
+
+        while let Some(line) = lines.next() {
+            let line = line.trim_end();
+
+            // Sections end at the first empty line, except for thread lists which contain empty
+            // lines between the lists of Java and other threads.
+            if line.is_empty() {
+                if section != Section::Threads {
+                    section = Section::Other;
+                }
+                continue;
+            }
+
+            if let Some(caps) = SIGNAL_RE.captures(line) {
+                log.signal = Some(caps[1].to_owned());
+                log.signal_code = parse_hex(&caps[2]);
+                log.pc = parse_hex(&caps[3]);
+                log.pid = caps[4].parse().ok();
+                tid = parse_number(&caps[5]);
+            } else if let Some(version) = line.strip_prefix("# JRE version:") {
+                log.jre_version = Some(version.trim().to_owned());
+            } else if let Some(vm) = line.strip_prefix("# Java VM:") {
+                log.cpu_arch = VM_ARCH_RE
+                    .captures(vm)
+                    .map_or(Arch::Unknown, |caps| parse_vm_arch(&caps[1]));
+                log.vm = Some(vm.trim().to_owned());
+            } else if line.starts_with("# Problematic frame:") {
+                if let Some(next) = lines.next() {
+                    log.problematic_frame = Frame::parse(next.trim_start_matches('#'));
+                }
+            } else if let Some(caps) = CURRENT_THREAD_RE.captures(line) {
+                let address = parse_hex(&caps[1]).unwrap_or_default();
+                let mut thread = Thread::parse(address, &caps[2]);
+                thread.crashed = true;
+                current = Some(thread);
+            } else if line.starts_with("Native frames:") {
+                section = Section::NativeFrames;
+            } else if line.starts_with("Java frames:") {
+                section = Section::JavaFrames;
+            } else if line.starts_with("Registers:") {
+                section = Section::Registers;
+            } else if line.starts_with("Java Threads:") || line.starts_with("Other Threads:") {
+                section = Section::Threads;
+            } else if line.starts_with("Dynamic libraries:") {
+                section = Section::Libraries;
+            } else if line.starts_with("siginfo:") {
+                log.crash_address = SIGINFO_ADDRESS_RE
+                    .captures(line)
+                    .and_then(|caps| parse_hex(&caps[1]));
+            } else if let Some(uname) = line.strip_prefix("uname:") {
+                log.os = Some(uname.trim().to_owned());
+            } else if let Some(os) = line.strip_prefix("OS:").filter(|os| !os.trim().is_empty()) {
+                if log.os.is_none() {
+                    log.os = Some(os.trim().to_owned());
+                }
+            } else {
+                match section {
+                    Section::NativeFrames | Section::JavaFrames => {
+                        let thread = current.get_or_insert_with(|| Thread {
+                            crashed: true,
+                            ..Default::default()
+                        });
+                        if let Some(frame) = Frame::parse(line) {
+                            match section {
+                                Section::NativeFrames => thread.frames.push(frame),
+                                _ => thread.java_frames.push(frame),
+                            }
+                        }
+                    }
+                    Section::Registers => {
+                        if let Some(thread) = current.as_mut() {
+                            for caps in REGISTER_RE.captures_iter(line) {
+                                if let Some(value) = parse_hex(&caps[2]) {
+                                    thread.registers.insert(caps[1].to_owned(), value);
+                                }
+                            }
+                        }
+                    }
+                    Section::Threads => match THREAD_RE.captures(line) {
+                        Some(caps) => {
+                            let address = parse_hex(&caps[2]).unwrap_or_default();
+                            let description = line[caps.get(2).unwrap().end()..].trim();
+                            let mut thread = Thread::parse(address, description);
+                            thread.crashed = caps.get(1).is_some();
+                            log.threads.push(thread);
+                        }
+                        // Lists end with a summary, such as `Total: 12`.
+                        None if !line.starts_with(' ') => section = Section::Other,
+                        None => (),
+                    },
+                    Section::Libraries => log.add_library(line),
+                    Section::Other => (),
+                }
+            }
+        }
+
+        if let Some(mut current) = current {
+            current.thread_id = current.thread_id.or(tid);
+            if current.frames.is_empty() {
+                current.frames = current.java_frames.clone();
+            }
+
+            // Merge the stack of the current thread into the thread list, if it is listed.
+            let listed = log.threads.iter_mut().find(|thread| {
+                thread.crashed || (current.address != 0 && thread.address == current.address)
+            });
+
+            match listed {
+                Some(thread) => {
+                    thread.crashed = true;
+                    thread.thread_id = thread.thread_id.or(current.thread_id);
+                    thread.frames = current.frames;
+                    thread.java_frames = current.java_frames;
+                    thread.registers = current.registers;
+                }
+                None => log.threads.insert(0, current),
+            }
+        }
+
+        if log.signal.is_none() && log.threads.is_empty() && log.libraries.is_empty() {
+            return Err(HsErrLogErrorKind::UnknownFormat.into());
+        }
+
+        let libraries = &log.libraries;
+        let frames = log
+            .threads
+            .iter_mut()
+            .flat_map(|thread| thread.frames.iter_mut().chain(&mut thread.java_frames))
+            .chain(&mut log.problematic_frame);
+
+        for frame in frames {
+            resolve_frame(frame, libraries);
+        }
+
+        Ok(log)
+    }
+
+    /// Returns the index of the thread that crashed.
+    pub fn requesting_thread(&self) -> Option<usize> {
+        self.threads.iter().position(|thread| thread.crashed)
+    }
+
+    /// Parses a line of the dynamic libraries section and adds it to the list of libraries.
+    ///
+    /// On Linux, libraries are listed as memory mappings in the format of `/proc/self/maps`, which
+    /// are merged into a single library per file. On Windows, libraries are listed with their
+    /// address range, and on macOS only with their base address.
+    fn add_library(&mut self, line: &str) {
+        let (start, end, path) = if let Some(caps) = MAPS_LIBRARY_RE.captures(line) {
+            let start = parse_hex(&caps[1]).unwrap_or_default();
+            let end = parse_hex(&caps[2]).unwrap_or_default();
+            (start, Some(end), caps[3].trim().to_owned())
+        } else if let Some(caps) = RANGE_LIBRARY_RE.captures(line) {
+            let start = parse_hex(&caps[1]).unwrap_or_default();
+            let end = parse_hex(&caps[2]).unwrap_or_default();
+            (start, Some(end), caps[3].trim().to_owned())
+        } else if let Some(caps) = BASE_LIBRARY_RE.captures(line) {
+            let start = parse_hex(&caps[1]).unwrap_or_default();
+            (start, None, caps[2].trim().to_owned())
+        } else {
+            return;
+        };
+
+        // Anonymous mappings and special regions like `[heap]` do not correspond to libraries.
+        if path.is_empty() || path.starts_with('[') {
+            return;
+        }
+
+        if let Some(library) = self.libraries.iter_mut().find(|lib| lib.path == path) {
+            let library_end = library.base_address + library.size;
+            let end = end.unwrap_or(library_end).max(library_end);
+            library.base_address = library.base_address.min(start);
+            library.size = end - library.base_address;
+            return;
+        }
+
+        self.libraries.push(Library {
+            base_address: start,
+            size: end.map_or(0, |end| end.saturating_sub(start)),
+            name: file_name(&path).to_owned(),
+            path,
+        });
+    }
+}
+
+/// Resolves the library and absolute instruction address of a frame.
+fn resolve_frame(frame: &mut Frame, libraries: &[Library]) {
+    if let Some(ref module_name) = frame.module_name {
+        frame.module = libraries.iter().position(|lib| &lib.name == module_name);
+        if let (Some(index), Some(offset)) = (frame.module, frame.module_offset) {
+            frame.instruction = Some(libraries[index].base_address + offset);
+        }
+    } else if let Some(instruction) = frame.instruction {
+        frame.module = libraries.iter().position(|lib| lib.contains(instruction));
+        if let Some(index) = frame.module {
+            frame.module_offset = Some(instruction - libraries[index].base_address);
+        }
+    }
+}
+
+/// Parses a decimal number, or a hexadecimal number prefixed with `0x`.
+fn parse_number(string: &str) -> Option<u64> {
+    if string.starts_with("0x") {
+        parse_hex(string)
+    } else {
+        string.parse().ok()
+    }
+}
+
+fn parse_vm_arch(arch: &str) -> Arch {
+    match arch {
+        "amd64" | "x86_64" => Arch::Amd64,
+        "x86" | "i386" | "i486" | "i586" | "i686" => Arch::X86,
+        "aarch64" | "arm64" => Arch::Arm64,
+        "arm" => Arch::Arm,
+        _ => Arch::Unknown,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LINUX_LOG: &str = r#"#
+# A fatal error has been detected by the Java Runtime Environment:
+#
+#  SIGSEGV (0xb) at pc=0x00007f1c9d6b96d5, pid=12345, tid=12346
+#
+# JRE version: OpenJDK Runtime Environment (11.0.2+9) (build 11.0.2+9)
+# Java VM: OpenJDK 64-Bit Server VM (11.0.2+9, mixed mode, tiered, compressed oops, g1 gc, linux-amd64)
+# Problematic frame:
+# C  [libc.so.6+0x18b6d5]  __memmove_avx_unaligned_erms+0x35
+#
+# If you would like to submit a bug report, please visit:
+#   https://bugreport.java.com/bugreport/crash.jsp
+#
+
+---------------  S U M M A R Y ------------
+
+Command Line: Example
+
+---------------  T H R E A D  ---------------
+
+Current thread (0x00007f1c98015000):  JavaThread "main" [_thread_in_native, id=12346, stack(0x00007f1ca0b7e000,0x00007f1ca0c7f000)]
+
+Stack: [0x00007f1ca0b7e000,0x00007f1ca0c7f000],  sp=0x00007f1ca0c7d1e8,  free space=1020k
+Native frames: (J=compiled Java code, A=aot compiled Java code, j=interpreted, Vv=VM code, C=native code)
+C  [libc.so.6+0x18b6d5]  __memmove_avx_unaligned_erms+0x35
+V  [libjvm.so+0x5d1a3c]
+C  [libnative.so+0x1040]  Java_Example_crash+0x10
+j  Example.crash()V+0
+J 123 c2 Example.run()V (23 bytes) @ 0x00007f1c8d1ab3ec [0x00007f1c8d1ab2a0+0x000000000000014c]
+v  ~StubRoutines::call_stub
+
+Java frames: (J=compiled Java code, j=interpreted, Vv=VM code)
+j  Example.crash()V+0
+j  Example.main([Ljava/lang/String;)V+0
+
+siginfo: si_signo: 11 (SIGSEGV), si_code: 1 (SEGV_MAPERR), si_addr: 0x0000000000000000
+
+Registers:
+RAX=0x0000000000000000, RBX=0x00007f1c98015000, RCX=0x0000000000000010, RDX=0x0000000000000010
+RSP=0x00007f1ca0c7d1e8, RBP=0x00007f1ca0c7d220, RSI=0x0000000000000000, RDI=0x00007f1ca0c7d200
+RIP=0x00007f1c9d6b96d5, EFLAGS=0x0000000000010283
+
+---------------  P R O C E S S  ---------------
+
+Threads class SMR info:
+_java_thread_list=0x00007f1c98200000, length=2, elements={
+0x00007f1c98015000, 0x00007f1c98123000
+}
+
+Java Threads: ( => current thread )
+=>0x00007f1c98015000 JavaThread "main" [_thread_in_native, id=12346, stack(0x00007f1ca0b7e000,0x00007f1ca0c7f000)]
+  0x00007f1c98123000 JavaThread "Reference Handler" daemon [_thread_blocked, id=12353, stack(0x00007f1c7c1fe000,0x00007f1c7c2ff000)]
+
+Other Threads:
+  0x00007f1c98110000 VMThread "VM Thread" [stack: 0x00007f1c7c300000,0x00007f1c7c400000] [id=12352]
+
+Dynamic libraries:
+55d3c6a00000-55d3c6a01000 r-xp 00000000 08:01 1234                       /usr/lib/jvm/java-11/bin/java
+7f1c9d52e000-7f1c9d6e5000 r-xp 00000000 08:01 2345                       /lib/x86_64-linux-gnu/libc.so.6
+7f1c9d6e5000-7f1c9d8e5000 ---p 001b7000 08:01 2345                       /lib/x86_64-linux-gnu/libc.so.6
+7f1c9d8e5000-7f1c9d8ea000 rw-p 001b7000 08:01 2345                       /lib/x86_64-linux-gnu/libc.so.6
+7f1c9e000000-7f1c9f000000 r-xp 00000000 08:01 3456                       /usr/lib/jvm/java-11/lib/server/libjvm.so
+7f1ca0000000-7f1ca0002000 r-xp 00000000 08:01 4567                       /tmp/libnative.so
+7f1ca1000000-7f1ca1021000 rw-p 00000000 00:00 0                          [heap]
+
+---------------  S Y S T E M  ---------------
+
+uname: Linux 5.4.0-42-generic #46-Ubuntu SMP Fri Jul 10 00:24:02 UTC 2020 x86_64
+"#;
+
+    const WINDOWS_LOG: &str = r#"#
+# A fatal error has been detected by the Java Runtime Environment:
+#
+#  EXCEPTION_ACCESS_VIOLATION (0xc0000005) at pc=0x00007ffb2b5c1040, pid=4242, tid=0x0000000000001a2c
+#
+# JRE version: Java(TM) SE Runtime Environment (8.0_201-b09) (build 1.8.0_201-b09)
+# Java VM: Java HotSpot(TM) 64-Bit Server VM (25.201-b09 mixed mode windows-amd64 compressed oops)
+# Problematic frame:
+# C  [native.dll+0x1040]
+#
+
+---------------  T H R E A D  ---------------
+
+Current thread (0x0000000002a5e000):  JavaThread "main" [_thread_in_native, id=6700, stack(0x0000000002900000,0x0000000002a00000)]
+
+siginfo: ExceptionCode=0xc0000005, reading address 0x0000000000000000
+
+Registers:
+RAX=0x0000000000000000, RBX=0x0000000002a5e000, RCX=0x0000000000000000
+RIP=0x00007ffb2b5c1040, EFLAGS=0x0000000000010246
+
+Stack: [0x0000000002900000,0x0000000002a00000],  sp=0x00000000029ff1f0,  free space=1020k
+Native frames: (J=compiled Java code, j=interpreted, Vv=VM code, C=native code)
+C  [native.dll+0x1040]
+C  0x00000000031a7f94
+
+---------------  P R O C E S S  ---------------
+
+Dynamic libraries:
+0x00007ff6a2e30000 - 0x00007ff6a2e67000 	C:\Program Files\Java\jre1.8.0_201\bin\java.exe
+0x00007ffb2b5c0000 - 0x00007ffb2b5d0000 	C:\Example\native.dll
+"#;
+
+    #[test]
+    fn test_parse_linux() {
+        let log = HsErrLog::parse(LINUX_LOG.as_bytes()).unwrap();
+
+        assert_eq!(log.signal.as_deref(), Some("SIGSEGV"));
+        assert_eq!(log.signal_code, Some(0xb));
+        assert_eq!(log.pc, Some(0x7f1c_9d6b_96d5));
+        assert_eq!(log.crash_address, Some(0));
+        assert_eq!(log.pid, Some(12345));
+        assert_eq!(log.cpu_arch, Arch::Amd64);
+        assert_eq!(
+            log.jre_version.as_deref(),
+            Some("OpenJDK Runtime Environment (11.0.2+9) (build 11.0.2+9)")
+        );
+        assert!(log.os.as_deref().unwrap().starts_with("Linux 5.4.0"));
+
+        let problematic = log.problematic_frame.as_ref().unwrap();
+        assert_eq!(problematic.instruction, log.pc);
+        assert_eq!(
+            problematic.symbol.as_deref(),
+            Some("__memmove_avx_unaligned_erms")
+        );
+
+        assert_eq!(log.libraries.len(), 4);
+        let libc = &log.libraries[1];
+        assert_eq!(libc.name, "libc.so.6");
+        assert_eq!(libc.base_address, 0x7f1c_9d52_e000);
+        assert_eq!(libc.size, 0x7f1c_9d8e_a000 - 0x7f1c_9d52_e000);
+
+        assert_eq!(log.threads.len(), 3);
+        assert_eq!(log.requesting_thread(), Some(0));
+
+        let thread = &log.threads[0];
+        assert_eq!(thread.name.as_deref(), Some("main"));
+        assert_eq!(thread.ty, "JavaThread");
+        assert_eq!(thread.thread_id, Some(12346));
+        assert_eq!(thread.state.as_deref(), Some("_thread_in_native"));
+        assert_eq!(thread.registers.get("RIP"), Some(&0x7f1c_9d6b_96d5));
+        assert_eq!(thread.registers.len(), 10);
+        assert_eq!(thread.frames.len(), 6);
+        assert_eq!(thread.java_frames.len(), 2);
+
+        let frame = &thread.frames[0];
+        assert_eq!(frame.kind, FrameKind::Native);
+        assert_eq!(frame.instruction, Some(0x7f1c_9d6b_96d5));
+        assert_eq!(frame.module, Some(1));
+        assert_eq!(frame.symbol_offset, Some(0x35));
+
+        let frame = &thread.frames[1];
+        assert_eq!(frame.kind, FrameKind::Vm);
+        assert_eq!(frame.instruction, Some(0x7f1c_9e5d_1a3c));
+        assert_eq!(frame.symbol, None);
+
+        let frame = &thread.frames[3];
+        assert_eq!(frame.kind, FrameKind::InterpretedJava);
+        assert_eq!(frame.symbol.as_deref(), Some("Example.crash()V+0"));
+        assert_eq!(frame.instruction, None);
+
+        let frame = &thread.frames[4];
+        assert_eq!(frame.kind, FrameKind::CompiledJava);
+        assert_eq!(frame.symbol.as_deref(), Some("Example.run()V"));
+        assert_eq!(frame.instruction, Some(0x7f1c_8d1a_b3ec));
+        assert_eq!(frame.module, None);
+
+        let frame = &thread.frames[5];
+        assert_eq!(frame.kind, FrameKind::VmGenerated);
+        assert_eq!(frame.symbol.as_deref(), Some("~StubRoutines::call_stub"));
+
+        let thread = &log.threads[1];
+        assert_eq!(thread.name.as_deref(), Some("Reference Handler"));
+        assert_eq!(thread.state.as_deref(), Some("_thread_blocked"));
+        assert!(!thread.crashed);
+        assert!(thread.frames.is_empty());
+
+        let thread = &log.threads[2];
+        assert_eq!(thread.ty, "VMThread");
+        assert_eq!(thread.name.as_deref(), Some("VM Thread"));
+        assert_eq!(thread.thread_id, Some(12352));
+    }
+
+    #[test]
+    fn test_parse_windows() {
+        let log = HsErrLog::parse(WINDOWS_LOG.as_bytes()).unwrap();
+
+        assert_eq!(log.signal.as_deref(), Some("EXCEPTION_ACCESS_VIOLATION"));
+        assert_eq!(log.signal_code, Some(0xc000_0005));
+        assert_eq!(log.crash_address, Some(0));
+        assert_eq!(log.cpu_arch, Arch::Amd64);
+
+        assert_eq!(log.libraries.len(), 2);
+        assert_eq!(log.libraries[1].name, "native.dll");
+        assert_eq!(log.libraries[1].size, 0x10000);
+
+        assert_eq!(log.threads.len(), 1);
+        let thread = &log.threads[0];
+        assert!(thread.crashed);
+        assert_eq!(thread.thread_id, Some(6700));
+        assert_eq!(thread.registers.len(), 5);
+
+        assert_eq!(thread.frames.len(), 2);
+        assert_eq!(thread.frames[0].instruction, Some(0x7ffb_2b5c_1040));
+        assert_eq!(thread.frames[0].module, Some(1));
+        assert_eq!(thread.frames[1].instruction, Some(0x31a_7f94));
+        assert_eq!(thread.frames[1].module, None);
+    }
+
+    #[test]
+    fn test_parse_unknown() {
+        let error = HsErrLog::parse(b"hello world").unwrap_err();
+        assert_eq!(error.kind(), HsErrLogErrorKind::UnknownFormat);
+    }
+}
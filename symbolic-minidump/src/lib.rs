@@ -2,10 +2,24 @@
 
 #![warn(missing_docs)]
 
-#[cfg(feature = "processor")]
+#[cfg(any(
+    feature = "processor",
+    feature = "applecrashreport",
+    feature = "hserrlog"
+))]
 mod utils;
 
 pub mod cfi;
+pub mod minidump;
 
 #[cfg(feature = "processor")]
 pub mod processor;
+
+#[cfg(feature = "writer")]
+pub mod writer;
+
+#[cfg(feature = "applecrashreport")]
+pub mod applecrashreport;
+
+#[cfg(feature = "hserrlog")]
+pub mod hserrlog;
@@ -0,0 +1,1176 @@
+//! Inspection of minidump headers and streams without processing.
+//!
+//! The root type is [`Minidump`], which reads the header and stream directory of a minidump and
+//! provides typed readers for streams that describe the crashed system and process. In contrast to
+//! [`ProcessState`], this does not walk stacks or load symbols, and is therefore cheap enough to
+//! triage large numbers of minidumps before processing them.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use symbolic_minidump::minidump::Minidump;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let data = std::fs::read("crash.dmp")?;
+//! let minidump = Minidump::parse(&data)?;
+//!
+//! for stream in minidump.streams() {
+//!     println!("{:?}: {} bytes at {:#x}", stream.stream_type, stream.size, stream.offset);
+//! }
+//!
+//! if let Some(info) = minidump.system_info()? {
+//!     println!("{} {} on {:?}", info.os_name(), info.os_version(), info.cpu_family());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`ProcessState`]: ../processor/struct.ProcessState.html
+
+use std::convert::{TryFrom, TryInto};
+use std::error::Error;
+use std::fmt;
+
+use thiserror::Error;
+
+use symbolic_common::CpuFamily;
+
+/// The minidump signature, `MDMP` in little-endian.
+const MINIDUMP_SIGNATURE: u32 = 0x504d_444d;
+
+/// Size of `MDRawHeader`.
+const HEADER_SIZE: usize = 32;
+
+/// Size of `MDRawDirectory`.
+const DIRECTORY_SIZE: usize = 12;
+
+/// Size of `MDRawSystemInfo`.
+const SYSTEM_INFO_SIZE: usize = 56;
+
+/// Size of the exception record in `MDRawExceptionStream` up to the exception address.
+const EXCEPTION_SIZE: usize = 32;
+
+/// Size of `MDMemoryDescriptor`, which is an entry of the memory list.
+const MEMORY_DESCRIPTOR_SIZE: usize = 16;
+
+/// Size of `MDMemoryDescriptor64`, which is an entry of the 64-bit memory list.
+const MEMORY_DESCRIPTOR64_SIZE: usize = 16;
+
+/// Size of `MDRawModule`, which is an entry of the module list.
+const MODULE_SIZE: usize = 108;
+
+/// Offset of the fields in `MDRawModule` following the module name.
+const MODULE_NAME_END: usize = 24;
+
+const MISC_INFO_PROCESS_ID: u32 = 0x1;
+const MISC_INFO_PROCESS_TIMES: u32 = 0x2;
+const MISC_INFO_PROCESSOR_POWER_INFO: u32 = 0x4;
+
+/// The kind of a [`MinidumpError`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MinidumpErrorKind {
+    /// The data does not start with a valid minidump header.
+    InvalidHeader,
+
+    /// The stream directory points outside of the minidump.
+    InvalidDirectory,
+
+    /// A stream is truncated or contains invalid data.
+    InvalidStream,
+
+    /// The minidump exceeds the maximum size of 4 GiB when writing.
+    TooLarge,
+}
+
+impl fmt::Display for MinidumpErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidHeader => write!(f, "invalid minidump header"),
+            Self::InvalidDirectory => write!(f, "invalid minidump stream directory"),
+            Self::InvalidStream => write!(f, "invalid minidump stream"),
+            Self::TooLarge => write!(f, "minidump too large"),
+        }
+    }
+}
+
+/// An error returned when inspecting minidumps.
+#[derive(Debug, Error)]
+#[error("{kind}")]
+pub struct MinidumpError {
+    kind: MinidumpErrorKind,
+    #[source]
+    source: Option<Box<dyn Error + Send + Sync + 'static>>,
+}
+
+impl MinidumpError {
+    /// Creates a new minidump error from a known kind of error as well as an arbitrary error
+    /// payload.
+    fn new<E>(kind: MinidumpErrorKind, source: E) -> Self
+    where
+        E: Into<Box<dyn Error + Send + Sync>>,
+    {
+        let source = Some(source.into());
+        Self { kind, source }
+    }
+
+    /// Returns the corresponding [`MinidumpErrorKind`] for this error.
+    pub fn kind(&self) -> MinidumpErrorKind {
+        self.kind
+    }
+}
+
+impl From<MinidumpErrorKind> for MinidumpError {
+    fn from(kind: MinidumpErrorKind) -> Self {
+        Self { kind, source: None }
+    }
+}
+
+/// The type of a stream in a minidump.
+///
+/// Streams that are not known to symbolic are reported as [`Unknown`](Self::Unknown) with their
+/// raw type.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MinidumpStreamType {
+    /// An unused directory entry.
+    Unused,
+    /// Threads of the process and their CPU contexts.
+    ThreadList,
+    /// Modules loaded into the process.
+    ModuleList,
+    /// Captured memory regions, usually thread stacks.
+    MemoryList,
+    /// Information on the exception or signal that caused the crash.
+    Exception,
+    /// The operating system and CPU of the crashed system.
+    SystemInfo,
+    /// Threads with additional backing store memory.
+    ThreadExList,
+    /// Captured memory regions of full memory dumps.
+    Memory64List,
+    /// An ANSI comment.
+    CommentA,
+    /// A Unicode comment.
+    CommentW,
+    /// Open handles of the process.
+    HandleData,
+    /// Function tables of dynamically generated code.
+    FunctionTable,
+    /// Modules that were unloaded from the process.
+    UnloadedModuleList,
+    /// Process times, process identifier and processor power information.
+    MiscInfo,
+    /// Attributes of memory regions in the address space of the process.
+    MemoryInfoList,
+    /// Additional information on threads.
+    ThreadInfoList,
+    /// Operations on handles of the process.
+    HandleOperationList,
+    /// Memory usage of the system.
+    SystemMemoryInfo,
+    /// Memory usage of the process.
+    ProcessVmCounters,
+    /// Breakpad information on the dumping and requesting threads.
+    BreakpadInfo,
+    /// Information on a failed assertion, written by Breakpad on Windows.
+    AssertionInfo,
+    /// Contents of `/proc/cpuinfo` on Linux.
+    LinuxCpuInfo,
+    /// Contents of `/proc/<pid>/status` on Linux.
+    LinuxProcStatus,
+    /// Contents of `/etc/lsb-release` on Linux.
+    LinuxLsbRelease,
+    /// Contents of `/proc/<pid>/cmdline` on Linux.
+    LinuxCmdLine,
+    /// Contents of `/proc/<pid>/environ` on Linux.
+    LinuxEnviron,
+    /// Contents of `/proc/<pid>/auxv` on Linux.
+    LinuxAuxv,
+    /// Contents of `/proc/<pid>/maps` on Linux.
+    LinuxMaps,
+    /// The dynamic linker's debug information on Linux.
+    LinuxDsoDebug,
+    /// Crashpad annotations and client information.
+    CrashpadInfo,
+    /// A stream type not known to symbolic.
+    Unknown(u32),
+}
+
+impl MinidumpStreamType {
+    /// Returns the stream type for its numeric value.
+    pub fn from_u32(value: u32) -> Self {
+        match value {
+            0 => Self::Unused,
+            3 => Self::ThreadList,
+            4 => Self::ModuleList,
+            5 => Self::MemoryList,
+            6 => Self::Exception,
+            7 => Self::SystemInfo,
+            8 => Self::ThreadExList,
+            9 => Self::Memory64List,
+            10 => Self::CommentA,
+            11 => Self::CommentW,
+            12 => Self::HandleData,
+            13 => Self::FunctionTable,
+            14 => Self::UnloadedModuleList,
+            15 => Self::MiscInfo,
+            16 => Self::MemoryInfoList,
+            17 => Self::ThreadInfoList,
+            18 => Self::HandleOperationList,
+            21 => Self::SystemMemoryInfo,
+            22 => Self::ProcessVmCounters,
+            0x4767_0001 => Self::BreakpadInfo,
+            0x4767_0002 => Self::AssertionInfo,
+            0x4767_0003 => Self::LinuxCpuInfo,
+            0x4767_0004 => Self::LinuxProcStatus,
+            0x4767_0005 => Self::LinuxLsbRelease,
+            0x4767_0006 => Self::LinuxCmdLine,
+            0x4767_0007 => Self::LinuxEnviron,
+            0x4767_0008 => Self::LinuxAuxv,
+            0x4767_0009 => Self::LinuxMaps,
+            0x4767_000a => Self::LinuxDsoDebug,
+            0x4350_0001 => Self::CrashpadInfo,
+            other => Self::Unknown(other),
+        }
+    }
+
+    /// Returns the numeric value of this stream type.
+    pub fn to_u32(self) -> u32 {
+        match self {
+            Self::Unused => 0,
+            Self::ThreadList => 3,
+            Self::ModuleList => 4,
+            Self::MemoryList => 5,
+            Self::Exception => 6,
+            Self::SystemInfo => 7,
+            Self::ThreadExList => 8,
+            Self::Memory64List => 9,
+            Self::CommentA => 10,
+            Self::CommentW => 11,
+            Self::HandleData => 12,
+            Self::FunctionTable => 13,
+            Self::UnloadedModuleList => 14,
+            Self::MiscInfo => 15,
+            Self::MemoryInfoList => 16,
+            Self::ThreadInfoList => 17,
+            Self::HandleOperationList => 18,
+            Self::SystemMemoryInfo => 21,
+            Self::ProcessVmCounters => 22,
+            Self::BreakpadInfo => 0x4767_0001,
+            Self::AssertionInfo => 0x4767_0002,
+            Self::LinuxCpuInfo => 0x4767_0003,
+            Self::LinuxProcStatus => 0x4767_0004,
+            Self::LinuxLsbRelease => 0x4767_0005,
+            Self::LinuxCmdLine => 0x4767_0006,
+            Self::LinuxEnviron => 0x4767_0007,
+            Self::LinuxAuxv => 0x4767_0008,
+            Self::LinuxMaps => 0x4767_0009,
+            Self::LinuxDsoDebug => 0x4767_000a,
+            Self::CrashpadInfo => 0x4350_0001,
+            Self::Unknown(value) => value,
+        }
+    }
+}
+
+/// An entry of the stream directory of a minidump.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MinidumpStream {
+    /// The type of the stream.
+    pub stream_type: MinidumpStreamType,
+    /// The size of the stream in bytes.
+    pub size: u32,
+    /// The offset of the stream from the start of the minidump.
+    pub offset: u32,
+}
+
+/// An iterator over the streams of a [`Minidump`].
+///
+/// This is returned by [`Minidump::streams`].
+#[derive(Clone, Debug)]
+pub struct MinidumpStreams<'d> {
+    reader: Reader<'d>,
+    offset: usize,
+    remaining: usize,
+}
+
+impl Iterator for MinidumpStreams<'_> {
+    type Item = MinidumpStream;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let stream = MinidumpStream {
+            stream_type: MinidumpStreamType::from_u32(self.reader.u32(self.offset)?),
+            size: self.reader.u32(self.offset + 4)?,
+            offset: self.reader.u32(self.offset + 8)?,
+        };
+
+        self.offset += DIRECTORY_SIZE;
+        self.remaining -= 1;
+        Some(stream)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for MinidumpStreams<'_> {}
+
+/// Information on the operating system and CPU of the crashed system.
+///
+/// This is read from the [`SystemInfo`](MinidumpStreamType::SystemInfo) stream.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MinidumpSystemInfo {
+    /// The raw processor architecture, such as `9` for AMD64.
+    pub processor_architecture: u16,
+    /// The processor level, which is the family for x86 CPUs.
+    pub processor_level: u16,
+    /// The processor revision, which encodes model and stepping for x86 CPUs.
+    pub processor_revision: u16,
+    /// The number of processors.
+    pub cpu_count: u8,
+    /// The raw product type, which distinguishes workstations and servers on Windows.
+    pub product_type: u8,
+    /// The major version of the operating system.
+    pub major_version: u32,
+    /// The minor version of the operating system.
+    pub minor_version: u32,
+    /// The build number of the operating system.
+    pub build_number: u32,
+    /// The raw platform identifier, such as `0x8201` for Linux.
+    pub platform_id: u32,
+    /// Additional version information, such as the service pack on Windows or the kernel version
+    /// on Linux.
+    pub csd_version: Option<String>,
+    /// The CPU vendor identifier for x86 CPUs, such as `GenuineIntel`.
+    pub cpu_vendor: Option<String>,
+}
+
+impl MinidumpSystemInfo {
+    /// Returns the CPU family of the crashed system.
+    pub fn cpu_family(&self) -> CpuFamily {
+        match self.processor_architecture {
+            0 => CpuFamily::Intel32,
+            1 => CpuFamily::Mips32,
+            3 => CpuFamily::Ppc32,
+            5 => CpuFamily::Arm32,
+            9 => CpuFamily::Amd64,
+            12 | 0x8003 => CpuFamily::Arm64,
+            0x8002 => CpuFamily::Ppc64,
+            0x8004 => CpuFamily::Mips64,
+            0x8005 => CpuFamily::Riscv32,
+            0x8006 => CpuFamily::Riscv64,
+            _ => CpuFamily::Unknown,
+        }
+    }
+
+    /// Returns the name of the operating system, using the same names as the Breakpad processor.
+    ///
+    /// Returns `"unknown"` for unknown platforms.
+    pub fn os_name(&self) -> &'static str {
+        match self.platform_id {
+            0..=2 => "Windows NT",
+            0x8000 => "Unix",
+            0x8101 => "Mac OS X",
+            0x8102 => "iOS",
+            0x8201 => "Linux",
+            0x8202 => "Solaris",
+            0x8203 => "Android",
+            0x8204 => "PS3",
+            0x8205 => "NaCl",
+            0x8206 => "Fuchsia",
+            _ => "unknown",
+        }
+    }
+
+    /// Returns the version of the operating system, such as `10.15.7 19H2`.
+    ///
+    /// The CSD version is appended if it is not empty.
+    pub fn os_version(&self) -> String {
+        let version = format!(
+            "{}.{}.{}",
+            self.major_version, self.minor_version, self.build_number
+        );
+
+        match self.csd_version.as_deref() {
+            Some(csd_version) if !csd_version.is_empty() => format!("{} {}", version, csd_version),
+            _ => version,
+        }
+    }
+}
+
+/// Information on the crashed process.
+///
+/// This is read from the [`MiscInfo`](MinidumpStreamType::MiscInfo) stream. Fields are `None` if
+/// the minidump does not contain them.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MinidumpMiscInfo {
+    /// The identifier of the process.
+    pub process_id: Option<u32>,
+    /// The creation time of the process as UNIX timestamp.
+    pub process_create_time: Option<u32>,
+    /// The time the process spent in user mode, in seconds.
+    pub process_user_time: Option<u32>,
+    /// The time the process spent in kernel mode, in seconds.
+    pub process_kernel_time: Option<u32>,
+    /// The maximum frequency of the processor in MHz.
+    pub processor_max_mhz: Option<u32>,
+    /// The current frequency of the processor in MHz.
+    pub processor_current_mhz: Option<u32>,
+}
+
+/// Information on the exception or signal that caused the crash.
+///
+/// This is read from the [`Exception`](MinidumpStreamType::Exception) stream.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MinidumpException {
+    /// The identifier of the thread that caused the exception.
+    pub thread_id: u32,
+    /// The platform-specific exception code, such as the signal number on Linux.
+    pub code: u32,
+    /// The platform-specific exception flags, such as the signal code on Linux.
+    pub flags: u32,
+    /// The address at which the exception occurred.
+    pub address: u64,
+}
+
+/// A module loaded into the crashed process.
+///
+/// This is read from the [`ModuleList`](MinidumpStreamType::ModuleList) stream by
+/// [`Minidump::modules`]. Modules can be corrected and written back with
+/// [`Minidump::with_modules`]. Fields that are not exposed here, such as the CodeView record
+/// carrying the module's debug identifier, are retained when writing modules that were read from a
+/// minidump.
+#[derive(Clone, PartialEq, Eq)]
+pub struct MinidumpModule {
+    /// The address at which the module was loaded.
+    pub base_address: u64,
+    /// The size of the module's image in memory.
+    pub size: u32,
+    /// The checksum of the module's image, or zero.
+    pub checksum: u32,
+    /// The timestamp of the module's image, or zero.
+    pub timestamp: u32,
+    /// The path to the module's code file.
+    pub name: String,
+    /// The raw fields of `MDRawModule` following the module name.
+    raw: [u8; MODULE_SIZE - MODULE_NAME_END],
+}
+
+impl MinidumpModule {
+    /// Creates a module that is not backed by a record in a minidump.
+    ///
+    /// When written, the module does not carry version information or a debug identifier.
+    pub fn new(name: impl Into<String>, base_address: u64, size: u32) -> Self {
+        MinidumpModule {
+            base_address,
+            size,
+            checksum: 0,
+            timestamp: 0,
+            name: name.into(),
+            raw: [0; MODULE_SIZE - MODULE_NAME_END],
+        }
+    }
+
+    /// Returns the address of the first byte after the module's image.
+    pub fn end_address(&self) -> u64 {
+        self.base_address.saturating_add(self.size.into())
+    }
+
+    /// Returns `true` if the address is within the module's image.
+    pub fn contains(&self, address: u64) -> bool {
+        (self.base_address..self.end_address()).contains(&address)
+    }
+}
+
+impl fmt::Debug for MinidumpModule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MinidumpModule")
+            .field("base_address", &format_args!("{:#x}", self.base_address))
+            .field("size", &format_args!("{:#x}", self.size))
+            .field("checksum", &format_args!("{:#x}", self.checksum))
+            .field("timestamp", &self.timestamp)
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+/// A region of memory of the crashed process captured in a minidump.
+///
+/// The contents of the region are borrowed from the minidump and are not copied. This is returned
+/// by [`Minidump::memory_regions`].
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct MinidumpMemoryRegion<'d> {
+    /// The address of the first byte of the region in the crashed process.
+    pub base_address: u64,
+    data: &'d [u8],
+}
+
+impl<'d> MinidumpMemoryRegion<'d> {
+    /// Returns the size of the region in bytes.
+    pub fn size(&self) -> u64 {
+        self.data.len() as u64
+    }
+
+    /// Returns the address of the first byte after the region.
+    pub fn end_address(&self) -> u64 {
+        self.base_address.saturating_add(self.size())
+    }
+
+    /// Returns `true` if the region contains the given address.
+    pub fn contains(&self, address: u64) -> bool {
+        address >= self.base_address && address < self.end_address()
+    }
+
+    /// Returns the contents of the region.
+    pub fn data(&self) -> &'d [u8] {
+        self.data
+    }
+
+    /// Returns `size` bytes starting at `address`.
+    ///
+    /// Returns `None` if the range is not fully contained in this region.
+    pub fn read(&self, address: u64, size: usize) -> Option<&'d [u8]> {
+        let start = usize::try_from(address.checked_sub(self.base_address)?).ok()?;
+        self.data.get(start..start.checked_add(size)?)
+    }
+}
+
+impl fmt::Debug for MinidumpMemoryRegion<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MinidumpMemoryRegion")
+            .field("base_address", &format_args!("{:#x}", self.base_address))
+            .field("size", &self.size())
+            .finish()
+    }
+}
+
+/// An iterator over the memory regions of a [`Minidump`].
+///
+/// Descriptors are read lazily from the [`MemoryList`](MinidumpStreamType::MemoryList) and
+/// [`Memory64List`](MinidumpStreamType::Memory64List) streams, and the contents of each region are
+/// borrowed from the minidump. Iterating the regions of full-memory dumps, which can span hundreds
+/// of megabytes, therefore does not allocate. This is returned by [`Minidump::memory_regions`].
+#[derive(Clone, Debug)]
+pub struct MinidumpMemoryRegions<'d> {
+    reader: Reader<'d>,
+    list: Option<Reader<'d>>,
+    list64: Option<Reader<'d>>,
+    index: usize,
+    index64: usize,
+    rva64: u64,
+}
+
+impl<'d> MinidumpMemoryRegions<'d> {
+    fn region(&self, base_address: u64, rva: u64, size: u64) -> Option<MinidumpMemoryRegion<'d>> {
+        let start = usize::try_from(rva).ok()?;
+        let end = start.checked_add(usize::try_from(size).ok()?)?;
+        let data = self.reader.data.get(start..end)?;
+        Some(MinidumpMemoryRegion { base_address, data })
+    }
+
+    fn next_region(&mut self) -> Option<MinidumpMemoryRegion<'d>> {
+        if let Some(list) = self.list {
+            let offset = 4 + self.index * MEMORY_DESCRIPTOR_SIZE;
+            self.index += 1;
+
+            if let Some(base_address) = list.u64(offset) {
+                let size = list.u32(offset + 8)?;
+                let rva = list.u32(offset + 12)?;
+                return self.region(base_address, rva.into(), size.into());
+            }
+
+            self.list = None;
+        }
+
+        let list64 = self.list64?;
+        let offset = 16 + self.index64 * MEMORY_DESCRIPTOR64_SIZE;
+        self.index64 += 1;
+
+        // All regions of the 64-bit memory list are stored back to back, starting at a common
+        // base RVA. Each region's offset is the sum of the sizes of all preceding regions.
+        let base_address = list64.u64(offset)?;
+        let size = list64.u64(offset + 8)?;
+        let rva = self.rva64;
+        self.rva64 = rva.checked_add(size)?;
+
+        self.region(base_address, rva, size)
+    }
+}
+
+impl<'d> Iterator for MinidumpMemoryRegions<'d> {
+    type Item = MinidumpMemoryRegion<'d>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let region = self.next_region();
+        if region.is_none() {
+            // Stop at the first truncated or invalid descriptor.
+            self.list = None;
+            self.list64 = None;
+        }
+        region
+    }
+}
+
+impl std::iter::FusedIterator for MinidumpMemoryRegions<'_> {}
+
+/// Reads integers from a minidump in its byte order.
+#[derive(Clone, Copy)]
+struct Reader<'d> {
+    data: &'d [u8],
+    big_endian: bool,
+}
+
+impl fmt::Debug for Reader<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Reader")
+            .field("len", &self.data.len())
+            .field("big_endian", &self.big_endian)
+            .finish()
+    }
+}
+
+impl<'d> Reader<'d> {
+    fn bytes<const N: usize>(&self, offset: usize) -> Option<[u8; N]> {
+        let bytes = self.data.get(offset..offset.checked_add(N)?)?;
+        Some(bytes.try_into().unwrap())
+    }
+
+    fn u8(&self, offset: usize) -> Option<u8> {
+        self.data.get(offset).copied()
+    }
+
+    fn u16(&self, offset: usize) -> Option<u16> {
+        let bytes = self.bytes(offset)?;
+        Some(if self.big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        })
+    }
+
+    fn u32(&self, offset: usize) -> Option<u32> {
+        let bytes = self.bytes(offset)?;
+        Some(if self.big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        })
+    }
+
+    fn u64(&self, offset: usize) -> Option<u64> {
+        let bytes = self.bytes(offset)?;
+        Some(if self.big_endian {
+            u64::from_be_bytes(bytes)
+        } else {
+            u64::from_le_bytes(bytes)
+        })
+    }
+
+    /// Limits the data of this reader to the given length.
+    fn truncate(self, len: usize) -> Self {
+        Reader {
+            data: self.data.get(..len).unwrap_or(self.data),
+            big_endian: self.big_endian,
+        }
+    }
+
+    /// Reads a length-prefixed UTF-16 `MDString` at the given offset.
+    fn string(&self, offset: usize) -> Result<String, MinidumpError> {
+        let length = self.u32(offset).ok_or(MinidumpErrorKind::InvalidStream)? as usize;
+
+        let units = (0..length / 2)
+            .map(|index| self.u16(offset + 4 + index * 2))
+            .collect::<Option<Vec<_>>>()
+            .ok_or(MinidumpErrorKind::InvalidStream)?;
+
+        String::from_utf16(&units)
+            .map_err(|e| MinidumpError::new(MinidumpErrorKind::InvalidStream, e))
+    }
+}
+
+/// Appends integers to a buffer in the byte order of a minidump.
+struct Writer {
+    data: Vec<u8>,
+    big_endian: bool,
+}
+
+impl Writer {
+    /// Returns the offset of the next byte written, which is its RVA.
+    fn rva(&self) -> Result<u32, MinidumpError> {
+        u32::try_from(self.data.len())
+            .map_err(|e| MinidumpError::new(MinidumpErrorKind::TooLarge, e))
+    }
+
+    /// Pads the buffer with zeros to a multiple of four bytes.
+    fn align(&mut self) {
+        let padded = (self.data.len() + 3) & !3;
+        self.data.resize(padded, 0);
+    }
+
+    fn set_u32(&mut self, offset: usize, value: u32) {
+        let bytes = if self.big_endian {
+            value.to_be_bytes()
+        } else {
+            value.to_le_bytes()
+        };
+        self.data[offset..offset + 4].copy_from_slice(&bytes);
+    }
+
+    fn set_u64(&mut self, offset: usize, value: u64) {
+        let bytes = if self.big_endian {
+            value.to_be_bytes()
+        } else {
+            value.to_le_bytes()
+        };
+        self.data[offset..offset + 8].copy_from_slice(&bytes);
+    }
+
+    fn u32(&mut self, value: u32) {
+        let offset = self.data.len();
+        self.data.resize(offset + 4, 0);
+        self.set_u32(offset, value);
+    }
+
+    /// Writes a length-prefixed and nul-terminated UTF-16 `MDString` and returns its RVA.
+    fn string(&mut self, string: &str) -> Result<u32, MinidumpError> {
+        self.align();
+        let rva = self.rva()?;
+
+        let units: Vec<u16> = string.encode_utf16().collect();
+        self.u32((units.len() * 2) as u32);
+        for unit in units.into_iter().chain(Some(0)) {
+            let bytes = if self.big_endian {
+                unit.to_be_bytes()
+            } else {
+                unit.to_le_bytes()
+            };
+            self.data.extend_from_slice(&bytes);
+        }
+
+        Ok(rva)
+    }
+
+    /// Writes an `MDRawModule` for the given module.
+    fn module(&mut self, module: &MinidumpModule, name_rva: u32) {
+        let offset = self.data.len();
+        self.data.resize(offset + MODULE_NAME_END, 0);
+        self.data.extend_from_slice(&module.raw);
+
+        self.set_u64(offset, module.base_address);
+        self.set_u32(offset + 8, module.size);
+        self.set_u32(offset + 12, module.checksum);
+        self.set_u32(offset + 16, module.timestamp);
+        self.set_u32(offset + 20, name_rva);
+    }
+}
+
+/// A minidump, inspected without processing.
+///
+/// Use [`streams`](Self::streams) to list the contents of the minidump, and the typed readers such
+/// as [`system_info`](Self::system_info) to read individual streams. See the [module
+/// documentation](self) for an example.
+#[derive(Clone, Copy)]
+pub struct Minidump<'d> {
+    reader: Reader<'d>,
+    version: u32,
+    stream_count: usize,
+    directory_offset: usize,
+    timestamp: u32,
+    flags: u64,
+}
+
+impl fmt::Debug for Minidump<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Minidump")
+            .field("version", &self.version)
+            .field("timestamp", &self.timestamp)
+            .field("flags", &self.flags)
+            .field("big_endian", &self.reader.big_endian)
+            .field("streams", &self.stream_count)
+            .finish()
+    }
+}
+
+impl<'d> Minidump<'d> {
+    /// Tests whether the buffer could contain a minidump.
+    pub fn test(data: &[u8]) -> bool {
+        match data.get(..4) {
+            Some(magic) => {
+                magic == MINIDUMP_SIGNATURE.to_le_bytes()
+                    || magic == MINIDUMP_SIGNATURE.to_be_bytes()
+            }
+            None => false,
+        }
+    }
+
+    /// Parses the header and validates the stream directory of a minidump.
+    pub fn parse(data: &'d [u8]) -> Result<Self, MinidumpError> {
+        if data.len() < HEADER_SIZE || !Self::test(data) {
+            return Err(MinidumpErrorKind::InvalidHeader.into());
+        }
+
+        let big_endian = data[..4] == MINIDUMP_SIGNATURE.to_be_bytes();
+        let reader = Reader { data, big_endian };
+        let header = |offset| reader.u32(offset).unwrap_or_default();
+
+        let stream_count = header(8) as usize;
+        let directory_offset = header(12) as usize;
+        let directory_size = stream_count
+            .checked_mul(DIRECTORY_SIZE)
+            .and_then(|size| size.checked_add(directory_offset));
+
+        match directory_size {
+            Some(end) if end <= data.len() => (),
+            _ => return Err(MinidumpErrorKind::InvalidDirectory.into()),
+        }
+
+        Ok(Minidump {
+            reader,
+            // The upper 16 bits of the version are implementation specific.
+            version: header(4) & 0xffff,
+            stream_count,
+            directory_offset,
+            timestamp: header(20),
+            flags: reader.u64(24).unwrap_or_default(),
+        })
+    }
+
+    /// Returns the minidump format version, usually `0xa793`.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Returns the time at which the minidump was written as UNIX timestamp.
+    pub fn timestamp(&self) -> u32 {
+        self.timestamp
+    }
+
+    /// Returns the raw `MINIDUMP_TYPE` flags describing the contents of the minidump.
+    pub fn flags(&self) -> u64 {
+        self.flags
+    }
+
+    /// Returns `true` if the minidump was written in big-endian byte order.
+    pub fn is_big_endian(&self) -> bool {
+        self.reader.big_endian
+    }
+
+    /// Returns an iterator over all streams in the minidump's directory.
+    pub fn streams(&self) -> MinidumpStreams<'d> {
+        MinidumpStreams {
+            reader: self.reader,
+            offset: self.directory_offset,
+            remaining: self.stream_count,
+        }
+    }
+
+    /// Returns the first stream of the given type, if any.
+    pub fn stream(&self, stream_type: MinidumpStreamType) -> Option<MinidumpStream> {
+        self.streams()
+            .find(|stream| stream.stream_type == stream_type)
+    }
+
+    /// Returns the contents of the first stream of the given type, if any.
+    ///
+    /// Returns an error if the stream points outside of the minidump.
+    pub fn stream_data(
+        &self,
+        stream_type: MinidumpStreamType,
+    ) -> Result<Option<&'d [u8]>, MinidumpError> {
+        let stream = match self.stream(stream_type) {
+            Some(stream) => stream,
+            None => return Ok(None),
+        };
+
+        let start = stream.offset as usize;
+        let end = start + stream.size as usize;
+        match self.reader.data.get(start..end) {
+            Some(data) => Ok(Some(data)),
+            None => Err(MinidumpErrorKind::InvalidDirectory.into()),
+        }
+    }
+
+    fn stream_reader(
+        &self,
+        stream_type: MinidumpStreamType,
+        min_size: usize,
+    ) -> Result<Option<Reader<'d>>, MinidumpError> {
+        match self.stream_data(stream_type)? {
+            Some(data) if data.len() < min_size => Err(MinidumpErrorKind::InvalidStream.into()),
+            Some(data) => Ok(Some(Reader {
+                data,
+                big_endian: self.reader.big_endian,
+            })),
+            None => Ok(None),
+        }
+    }
+
+    /// Reads information on the operating system and CPU of the crashed system.
+    ///
+    /// Returns `None` if the minidump does not contain a system info stream.
+    pub fn system_info(&self) -> Result<Option<MinidumpSystemInfo>, MinidumpError> {
+        let stream = match self.stream_reader(MinidumpStreamType::SystemInfo, SYSTEM_INFO_SIZE)? {
+            Some(stream) => stream,
+            None => return Ok(None),
+        };
+
+        // The stream is at least `SYSTEM_INFO_SIZE` bytes, so all reads below succeed.
+        let processor_architecture = stream.u16(0).unwrap_or_default();
+        let csd_version = match stream.u32(24).unwrap_or_default() {
+            0 => None,
+            rva => Some(self.reader.string(rva as usize)?),
+        };
+
+        // On x86 and AMD64, the CPU information starts with the 12-byte vendor identifier.
+        let cpu_vendor = match processor_architecture {
+            0 | 9 => {
+                let vendor = stream.data[32..44]
+                    .iter()
+                    .take_while(|&&byte| byte != 0)
+                    .map(|&byte| byte as char)
+                    .collect::<String>();
+                Some(vendor).filter(|vendor| !vendor.is_empty())
+            }
+            _ => None,
+        };
+
+        Ok(Some(MinidumpSystemInfo {
+            processor_architecture,
+            processor_level: stream.u16(2).unwrap_or_default(),
+            processor_revision: stream.u16(4).unwrap_or_default(),
+            cpu_count: stream.u8(6).unwrap_or_default(),
+            product_type: stream.u8(7).unwrap_or_default(),
+            major_version: stream.u32(8).unwrap_or_default(),
+            minor_version: stream.u32(12).unwrap_or_default(),
+            build_number: stream.u32(16).unwrap_or_default(),
+            platform_id: stream.u32(20).unwrap_or_default(),
+            csd_version,
+            cpu_vendor,
+        }))
+    }
+
+    /// Reads information on the crashed process.
+    ///
+    /// Returns `None` if the minidump does not contain a misc info stream.
+    pub fn misc_info(&self) -> Result<Option<MinidumpMiscInfo>, MinidumpError> {
+        let stream = match self.stream_reader(MinidumpStreamType::MiscInfo, 24)? {
+            Some(stream) => stream,
+            None => return Ok(None),
+        };
+
+        let flags = stream.u32(4).unwrap_or_default();
+        let field = |flag: u32, offset: usize| {
+            if flags & flag != 0 {
+                stream.u32(offset)
+            } else {
+                None
+            }
+        };
+
+        Ok(Some(MinidumpMiscInfo {
+            process_id: field(MISC_INFO_PROCESS_ID, 8),
+            process_create_time: field(MISC_INFO_PROCESS_TIMES, 12),
+            process_user_time: field(MISC_INFO_PROCESS_TIMES, 16),
+            process_kernel_time: field(MISC_INFO_PROCESS_TIMES, 20),
+            processor_max_mhz: field(MISC_INFO_PROCESSOR_POWER_INFO, 24),
+            processor_current_mhz: field(MISC_INFO_PROCESSOR_POWER_INFO, 28),
+        }))
+    }
+
+    /// Reads information on the exception or signal that caused the crash.
+    ///
+    /// Returns `None` if the minidump does not contain an exception stream.
+    pub fn exception(&self) -> Result<Option<MinidumpException>, MinidumpError> {
+        let stream = match self.stream_reader(MinidumpStreamType::Exception, EXCEPTION_SIZE)? {
+            Some(stream) => stream,
+            None => return Ok(None),
+        };
+
+        Ok(Some(MinidumpException {
+            thread_id: stream.u32(0).unwrap_or_default(),
+            code: stream.u32(8).unwrap_or_default(),
+            flags: stream.u32(12).unwrap_or_default(),
+            address: stream.u64(24).unwrap_or_default(),
+        }))
+    }
+
+    /// Returns an iterator over all memory regions captured in the minidump.
+    ///
+    /// This yields regions of the [`MemoryList`](MinidumpStreamType::MemoryList) stream first,
+    /// which usually contains thread stacks, followed by regions of the
+    /// [`Memory64List`](MinidumpStreamType::Memory64List) stream written for full-memory dumps.
+    /// The contents of the regions are borrowed from the minidump, see [`MinidumpMemoryRegions`].
+    pub fn memory_regions(&self) -> Result<MinidumpMemoryRegions<'d>, MinidumpError> {
+        let list = self.stream_reader(MinidumpStreamType::MemoryList, 4)?;
+        let list64 = self.stream_reader(MinidumpStreamType::Memory64List, 16)?;
+
+        // Limit the lists to the number of declared descriptors.
+        let list = list.map(|list| {
+            let count = list.u32(0).unwrap_or_default() as usize;
+            list.truncate(
+                count
+                    .saturating_mul(MEMORY_DESCRIPTOR_SIZE)
+                    .saturating_add(4),
+            )
+        });
+        let list64 = list64.map(|list| {
+            let count = list.u64(0).unwrap_or_default() as usize;
+            list.truncate(
+                count
+                    .saturating_mul(MEMORY_DESCRIPTOR64_SIZE)
+                    .saturating_add(16),
+            )
+        });
+
+        Ok(MinidumpMemoryRegions {
+            reader: self.reader,
+            list,
+            list64,
+            index: 0,
+            index64: 0,
+            rva64: list64.and_then(|list| list.u64(8)).unwrap_or_default(),
+        })
+    }
+
+    /// Reads `size` bytes of memory of the crashed process starting at `address`.
+    ///
+    /// The memory is borrowed from the minidump without copying. Returns `None` if the range is
+    /// not fully contained in a single memory region.
+    pub fn read_memory(
+        &self,
+        address: u64,
+        size: usize,
+    ) -> Result<Option<&'d [u8]>, MinidumpError> {
+        Ok(self
+            .memory_regions()?
+            .find(|region| region.contains(address))
+            .and_then(|region| region.read(address, size)))
+    }
+
+    /// Reads the list of modules loaded into the crashed process.
+    ///
+    /// Returns an empty list if the minidump does not contain a module list stream.
+    pub fn modules(&self) -> Result<Vec<MinidumpModule>, MinidumpError> {
+        let stream = match self.stream_reader(MinidumpStreamType::ModuleList, 4)? {
+            Some(stream) => stream,
+            None => return Ok(Vec::new()),
+        };
+
+        let count = stream.u32(0).unwrap_or_default() as usize;
+        let size = count
+            .checked_mul(MODULE_SIZE)
+            .ok_or(MinidumpErrorKind::InvalidStream)?;
+
+        // Some writers pad the count to eight bytes, which is detected by the stream size.
+        let start = if stream.data.len() == size + 8 { 8 } else { 4 };
+
+        (0..count)
+            .map(|index| {
+                let offset = start + index * MODULE_SIZE;
+                let raw = stream
+                    .data
+                    .get(offset + MODULE_NAME_END..offset + MODULE_SIZE)
+                    .ok_or(MinidumpErrorKind::InvalidStream)?;
+
+                Ok(MinidumpModule {
+                    base_address: stream.u64(offset).unwrap_or_default(),
+                    size: stream.u32(offset + 8).unwrap_or_default(),
+                    checksum: stream.u32(offset + 12).unwrap_or_default(),
+                    timestamp: stream.u32(offset + 16).unwrap_or_default(),
+                    name: self
+                        .reader
+                        .string(stream.u32(offset + 20).unwrap_or_default() as usize)?,
+                    raw: raw.try_into().unwrap(),
+                })
+            })
+            .collect()
+    }
+
+    /// Writes a copy of this minidump with its module list replaced by the given modules.
+    ///
+    /// Custom loaders, such as those of game engines or packers, can leave the module list of a
+    /// minidump incomplete or wrong. Processing such a minidump attributes frames to the wrong
+    /// modules, and stack walking cannot apply CFI of the affected modules. Read the list with
+    /// [`modules`](Self::modules), correct names, base addresses or sizes, or add missing modules,
+    /// and pass the result to this function. The returned minidump can be processed again with
+    /// `ProcessState::from_minidump`.
+    ///
+    /// The new module list and all module names are appended to the end of the minidump, followed
+    /// by a new stream directory. The original data is not modified.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use symbolic_minidump::minidump::Minidump;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let data = std::fs::read("crash.dmp")?;
+    /// let minidump = Minidump::parse(&data)?;
+    ///
+    /// let mut modules = minidump.modules()?;
+    /// for module in &mut modules {
+    ///     if module.name.ends_with("game.bi") {
+    ///         module.name.push('n');
+    ///     }
+    /// }
+    ///
+    /// let patched = minidump.with_modules(&modules)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(modules = modules.len()))
+    )]
+    pub fn with_modules(&self, modules: &[MinidumpModule]) -> Result<Vec<u8>, MinidumpError> {
+        let mut writer = Writer {
+            data: self.reader.data.to_vec(),
+            big_endian: self.reader.big_endian,
+        };
+
+        let name_rvas = modules
+            .iter()
+            .map(|module| writer.string(&module.name))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        writer.align();
+        let list_rva = writer.rva()?;
+        writer.u32(modules.len() as u32);
+        for (module, name_rva) in modules.iter().zip(name_rvas) {
+            writer.module(module, name_rva);
+        }
+        let list_size = writer.rva()? - list_rva;
+
+        // Write a new directory that points to the new module list, or adds it if missing.
+        let mut streams: Vec<_> = self.streams().collect();
+        let list = MinidumpStream {
+            stream_type: MinidumpStreamType::ModuleList,
+            size: list_size,
+            offset: list_rva,
+        };
+        match streams
+            .iter_mut()
+            .find(|stream| stream.stream_type == MinidumpStreamType::ModuleList)
+        {
+            Some(stream) => *stream = list,
+            None => streams.push(list),
+        }
+
+        writer.align();
+        let directory_rva = writer.rva()?;
+        for stream in &streams {
+            writer.u32(stream.stream_type.to_u32());
+            writer.u32(stream.size);
+            writer.u32(stream.offset);
+        }
+
+        writer.set_u32(8, streams.len() as u32);
+        writer.set_u32(12, directory_rva);
+        Ok(writer.data)
+    }
+}
@@ -9,13 +9,15 @@
 //! [official docs]: https://chromium.googlesource.com/breakpad/breakpad/+/master/docs/processor_design.md
 //! [`ProcessState`]: struct.ProcessState.html
 
+use std::cell::RefCell;
 use std::cmp::Ordering;
 use std::collections::{BTreeMap, BTreeSet};
 use std::ffi::{CStr, CString};
 use std::hash::{Hash, Hasher};
+use std::io::Write;
 use std::marker::PhantomData;
 use std::ops::{Bound, Range, RangeBounds};
-use std::os::raw::{c_char, c_void};
+use std::os::raw::{c_char, c_int, c_void};
 use std::str::FromStr;
 use std::{fmt, ptr, slice, str};
 
@@ -23,8 +25,12 @@ use lazy_static::lazy_static;
 use regex::Regex;
 
 use symbolic_common::{Arch, ByteView, CpuFamily, DebugId, ParseDebugIdError, Uuid};
+use symbolic_debuginfo::breakpad::{BreakpadCfiEntry, BreakpadUnwindRules};
+#[cfg(feature = "symcache")]
+use symbolic_symcache::{LineInfo, SymCache, SymCacheError};
 
-use crate::cfi::CfiCache;
+use crate::cfi::{self, CfiCache};
+use crate::minidump::{Minidump, MinidumpError, MinidumpMemoryRegion};
 use crate::utils;
 
 lazy_static! {
@@ -632,6 +638,21 @@ impl FromStr for FrameTrust {
 
 impl std::error::Error for ParseFrameTrustError {}
 
+impl FrameTrust {
+    /// Returns `true` if the frame was found by scanning the stack.
+    ///
+    /// Scanned frames may be spurious, as the stackwalker only guesses that a value on the stack
+    /// is a return address. Consumers should down-rank such frames, for instance when grouping.
+    pub fn is_scanned(self) -> bool {
+        matches!(self, FrameTrust::Scan | FrameTrust::CFIScan)
+    }
+
+    /// Returns `true` if the frame was recovered using call frame information.
+    pub fn uses_cfi(self) -> bool {
+        matches!(self, FrameTrust::CFI | FrameTrust::CFIScan)
+    }
+}
+
 impl Default for FrameTrust {
     fn default() -> FrameTrust {
         FrameTrust::None
@@ -694,6 +715,327 @@ impl fmt::Display for RegVal {
     }
 }
 
+impl RegVal {
+    /// Returns the register value zero-extended to 64 bits.
+    pub fn value(self) -> u64 {
+        match self {
+            RegVal::U32(u) => u.into(),
+            RegVal::U64(u) => u,
+        }
+    }
+}
+
+/// Declares a typed CPU context with one optional field per register.
+macro_rules! cpu_context {
+    (
+        $(#[$attr:meta])*
+        $name:ident: $ty:ty {
+            $($field:ident => $register:literal,)*
+        }
+    ) => {
+        $(#[$attr])*
+        ///
+        /// Each field is `None` if the stackwalker could not recover the register for this frame.
+        #[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+        pub struct $name {
+            $(
+                #[doc = concat!("The `", $register, "` register.")]
+                pub $field: Option<$ty>,
+            )*
+        }
+
+        impl $name {
+            fn from_registers(registers: &BTreeMap<&'static str, RegVal>) -> Self {
+                Self {
+                    $($field: registers.get($register).map(|v| v.value() as $ty),)*
+                }
+            }
+
+            /// Returns a mapping of register names to the values of all recovered registers.
+            pub fn registers(&self) -> BTreeMap<&'static str, $ty> {
+                let mut registers = BTreeMap::new();
+                $(
+                    if let Some(value) = self.$field {
+                        registers.insert($register, value);
+                    }
+                )*
+                registers
+            }
+        }
+    };
+}
+
+cpu_context! {
+    /// Registers of a 32-bit x86 stack frame.
+    X86CpuContext: u32 {
+        eip => "eip",
+        esp => "esp",
+        ebp => "ebp",
+        ebx => "ebx",
+        esi => "esi",
+        edi => "edi",
+        eax => "eax",
+        ecx => "ecx",
+        edx => "edx",
+        eflags => "eflags",
+    }
+}
+
+cpu_context! {
+    /// Registers of an x86_64 stack frame.
+    Amd64CpuContext: u64 {
+        rax => "rax",
+        rdx => "rdx",
+        rcx => "rcx",
+        rbx => "rbx",
+        rsi => "rsi",
+        rdi => "rdi",
+        rbp => "rbp",
+        rsp => "rsp",
+        r8 => "r8",
+        r9 => "r9",
+        r10 => "r10",
+        r11 => "r11",
+        r12 => "r12",
+        r13 => "r13",
+        r14 => "r14",
+        r15 => "r15",
+        rip => "rip",
+    }
+}
+
+cpu_context! {
+    /// Registers of a 32-bit ARM stack frame.
+    ArmCpuContext: u32 {
+        r0 => "r0",
+        r1 => "r1",
+        r2 => "r2",
+        r3 => "r3",
+        r4 => "r4",
+        r5 => "r5",
+        r6 => "r6",
+        r7 => "r7",
+        r8 => "r8",
+        r9 => "r9",
+        r10 => "r10",
+        fp => "fp",
+        r12 => "r12",
+        sp => "sp",
+        lr => "lr",
+        pc => "pc",
+    }
+}
+
+cpu_context! {
+    /// Registers of an ARM64 stack frame.
+    Arm64CpuContext: u64 {
+        x0 => "x0",
+        x1 => "x1",
+        x2 => "x2",
+        x3 => "x3",
+        x4 => "x4",
+        x5 => "x5",
+        x6 => "x6",
+        x7 => "x7",
+        x8 => "x8",
+        x9 => "x9",
+        x10 => "x10",
+        x11 => "x11",
+        x12 => "x12",
+        x13 => "x13",
+        x14 => "x14",
+        x15 => "x15",
+        x16 => "x16",
+        x17 => "x17",
+        x18 => "x18",
+        x19 => "x19",
+        x20 => "x20",
+        x21 => "x21",
+        x22 => "x22",
+        x23 => "x23",
+        x24 => "x24",
+        x25 => "x25",
+        x26 => "x26",
+        x27 => "x27",
+        x28 => "x28",
+        fp => "x29",
+        lr => "x30",
+        sp => "sp",
+        pc => "pc",
+    }
+}
+
+cpu_context! {
+    /// Registers of a 32-bit PowerPC stack frame.
+    PpcCpuContext: u32 {
+        srr0 => "srr0",
+        r1 => "r1",
+    }
+}
+
+cpu_context! {
+    /// Registers of a 64-bit PowerPC stack frame.
+    Ppc64CpuContext: u64 {
+        srr0 => "srr0",
+        r1 => "r1",
+    }
+}
+
+cpu_context! {
+    /// Registers of a 32-bit or 64-bit MIPS stack frame.
+    ///
+    /// Values of 32-bit frames are zero-extended.
+    MipsCpuContext: u64 {
+        gp => "gp",
+        sp => "sp",
+        fp => "fp",
+        ra => "ra",
+        pc => "pc",
+        s0 => "s0",
+        s1 => "s1",
+        s2 => "s2",
+        s3 => "s3",
+        s4 => "s4",
+        s5 => "s5",
+        s6 => "s6",
+        s7 => "s7",
+    }
+}
+
+cpu_context! {
+    /// Registers of a 32-bit or 64-bit RISC-V stack frame.
+    ///
+    /// Values of 32-bit frames are zero-extended. The frame pointer is stored in `s0`.
+    RiscvCpuContext: u64 {
+        pc => "pc",
+        ra => "ra",
+        sp => "sp",
+        gp => "gp",
+        tp => "tp",
+        t0 => "t0",
+        t1 => "t1",
+        t2 => "t2",
+        s0 => "s0",
+        s1 => "s1",
+        a0 => "a0",
+        a1 => "a1",
+        a2 => "a2",
+        a3 => "a3",
+        a4 => "a4",
+        a5 => "a5",
+        a6 => "a6",
+        a7 => "a7",
+        s2 => "s2",
+        s3 => "s3",
+        s4 => "s4",
+        s5 => "s5",
+        s6 => "s6",
+        s7 => "s7",
+        s8 => "s8",
+        s9 => "s9",
+        s10 => "s10",
+        s11 => "s11",
+        t3 => "t3",
+        t4 => "t4",
+        t5 => "t5",
+        t6 => "t6",
+    }
+}
+
+/// The registers of a stack frame, typed by CPU architecture.
+///
+/// This is returned by [`StackFrame::context`] and [`CallStack::context`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CpuContext {
+    /// Registers of a 32-bit x86 frame.
+    X86(X86CpuContext),
+    /// Registers of an x86_64 frame.
+    Amd64(Amd64CpuContext),
+    /// Registers of a 32-bit ARM frame.
+    Arm(ArmCpuContext),
+    /// Registers of an ARM64 frame.
+    Arm64(Arm64CpuContext),
+    /// Registers of a 32-bit PowerPC frame.
+    Ppc(PpcCpuContext),
+    /// Registers of a 64-bit PowerPC frame.
+    Ppc64(Ppc64CpuContext),
+    /// Registers of a 32-bit or 64-bit MIPS frame.
+    Mips(MipsCpuContext),
+    /// Registers of a 32-bit or 64-bit RISC-V frame.
+    ///
+    /// Breakpad only walks RISC-V stacks in revisions that include its RISC-V stack walkers.
+    Riscv(RiscvCpuContext),
+    /// The CPU architecture is not known, so no registers are available.
+    Unknown,
+}
+
+impl CpuContext {
+    /// Converts a mapping of register names to values into a typed context.
+    fn from_registers(arch: Arch, registers: &BTreeMap<&'static str, RegVal>) -> Self {
+        match arch.cpu_family() {
+            CpuFamily::Intel32 => CpuContext::X86(X86CpuContext::from_registers(registers)),
+            CpuFamily::Amd64 => CpuContext::Amd64(Amd64CpuContext::from_registers(registers)),
+            CpuFamily::Arm32 => CpuContext::Arm(ArmCpuContext::from_registers(registers)),
+            CpuFamily::Arm64 => CpuContext::Arm64(Arm64CpuContext::from_registers(registers)),
+            CpuFamily::Ppc32 => CpuContext::Ppc(PpcCpuContext::from_registers(registers)),
+            CpuFamily::Ppc64 => CpuContext::Ppc64(Ppc64CpuContext::from_registers(registers)),
+            CpuFamily::Mips32 | CpuFamily::Mips64 => {
+                CpuContext::Mips(MipsCpuContext::from_registers(registers))
+            }
+            CpuFamily::Riscv32 | CpuFamily::Riscv64 => {
+                CpuContext::Riscv(RiscvCpuContext::from_registers(registers))
+            }
+            _ => CpuContext::Unknown,
+        }
+    }
+
+    /// Returns the value of the instruction pointer, if recovered.
+    pub fn instruction_pointer(&self) -> Option<u64> {
+        match *self {
+            CpuContext::X86(ref c) => c.eip.map(u64::from),
+            CpuContext::Amd64(ref c) => c.rip,
+            CpuContext::Arm(ref c) => c.pc.map(u64::from),
+            CpuContext::Arm64(ref c) => c.pc,
+            CpuContext::Ppc(ref c) => c.srr0.map(u64::from),
+            CpuContext::Ppc64(ref c) => c.srr0,
+            CpuContext::Mips(ref c) => c.pc,
+            CpuContext::Riscv(ref c) => c.pc,
+            CpuContext::Unknown => None,
+        }
+    }
+
+    /// Returns the value of the stack pointer, if recovered.
+    pub fn stack_pointer(&self) -> Option<u64> {
+        match *self {
+            CpuContext::X86(ref c) => c.esp.map(u64::from),
+            CpuContext::Amd64(ref c) => c.rsp,
+            CpuContext::Arm(ref c) => c.sp.map(u64::from),
+            CpuContext::Arm64(ref c) => c.sp,
+            CpuContext::Ppc(ref c) => c.r1.map(u64::from),
+            CpuContext::Ppc64(ref c) => c.r1,
+            CpuContext::Mips(ref c) => c.sp,
+            CpuContext::Riscv(ref c) => c.sp,
+            CpuContext::Unknown => None,
+        }
+    }
+
+    /// Returns the value of the frame pointer, if recovered.
+    ///
+    /// PowerPC does not have a dedicated frame pointer register.
+    pub fn frame_pointer(&self) -> Option<u64> {
+        match *self {
+            CpuContext::X86(ref c) => c.ebp.map(u64::from),
+            CpuContext::Amd64(ref c) => c.rbp,
+            CpuContext::Arm(ref c) => c.fp.map(u64::from),
+            CpuContext::Arm64(ref c) => c.fp,
+            CpuContext::Mips(ref c) => c.fp,
+            CpuContext::Riscv(ref c) => c.s0,
+            CpuContext::Ppc(_) | CpuContext::Ppc64(_) | CpuContext::Unknown => None,
+        }
+    }
+}
+
 /// Contains information from the memorydump, especially the frame's instruction
 /// pointer. Also references an optional `CodeModule` that contains the
 /// instruction of this stack frame.
@@ -758,6 +1100,33 @@ impl StackFrame {
         unsafe { stack_frame_trust(self) }
     }
 
+    /// Resolves the source locations of this frame's instruction, including inlined functions.
+    ///
+    /// The instruction is looked up in the SymCache of the frame's module. The returned chain
+    /// starts with the innermost inlined function and ends with the function that contains the
+    /// instruction in the module. Since [`instruction`](Self::instruction) points into the call
+    /// instruction for all but the innermost frame, this resolves the location of the call.
+    ///
+    /// Returns an empty list if the frame has no module, or if there is no SymCache for it.
+    #[cfg(feature = "symcache")]
+    pub fn inline_frames<'d>(
+        &self,
+        symcaches: &SymCacheMap<'d>,
+    ) -> Result<Vec<LineInfo<'d>>, SymCacheError> {
+        let module = match self.module() {
+            Some(module) => module,
+            None => return Ok(Vec::new()),
+        };
+
+        let symcache = match module.id().and_then(|id| symcaches.get(&id)) {
+            Some(symcache) => symcache,
+            None => return Ok(Vec::new()),
+        };
+
+        let offset = self.instruction().wrapping_sub(module.base_address());
+        symcache.lookup(offset)?.collect()
+    }
+
     /// Returns a mapping of registers to their known values, if any.
     pub fn registers(&self, arch: Arch) -> BTreeMap<&'static str, RegVal> {
         unsafe {
@@ -781,6 +1150,14 @@ impl StackFrame {
             map
         }
     }
+
+    /// Returns the registers of this frame typed by CPU architecture.
+    ///
+    /// This contains the same values as [`registers`](Self::registers). Registers that were not
+    /// recovered by the stackwalker are `None`.
+    pub fn context(&self, arch: Arch) -> CpuContext {
+        CpuContext::from_registers(arch, &self.registers(arch))
+    }
 }
 
 impl fmt::Debug for StackFrame {
@@ -794,6 +1171,33 @@ impl fmt::Debug for StackFrame {
     }
 }
 
+/// Describes how the stackwalker recovered a [`StackFrame`].
+///
+/// Apart from the innermost frame, each frame is recovered by unwinding its callee, which is the
+/// previous frame in the call stack. The provenance refers to the module and unwind information
+/// of that callee.
+///
+/// This is returned by [`CallStack::frame_provenance`].
+#[derive(Clone, Debug)]
+pub struct FrameProvenance<'a> {
+    /// How well the frame's instruction pointer is trusted.
+    pub trust: FrameTrust,
+
+    /// The module of the callee frame whose unwind information produced this frame.
+    ///
+    /// This is `None` for the innermost frame and if the callee is not covered by a module.
+    pub module: Option<&'a CodeModule>,
+
+    /// The address relative to `module` at which unwind information was looked up.
+    pub module_offset: Option<u64>,
+
+    /// The CFI rules that produced this frame.
+    ///
+    /// This is only available for frames recovered from CFI, and only if the frame information
+    /// of the module is a CFI cache in the binary format (see [`CfiCache::lookup`]).
+    pub cfi: Option<BreakpadCfiEntry<'a>>,
+}
+
 /// Represents a thread of the `ProcessState` which holds a list of [`StackFrame`]s.
 ///
 /// [`StackFrame`]: struct.StackFrame.html
@@ -814,6 +1218,94 @@ impl CallStack {
             slice::from_raw_parts(data as *const &StackFrame, size)
         }
     }
+
+    /// Returns the CPU context of this thread, which are the registers of its innermost frame.
+    ///
+    /// Returns `None` if the thread has no frames.
+    pub fn context(&self, arch: Arch) -> Option<CpuContext> {
+        self.frames().first().map(|frame| frame.context(arch))
+    }
+
+    /// Returns the memory region containing the stack pointer of this thread.
+    ///
+    /// Breakpad does not retain stack memory in the process state. Instead, the region is looked
+    /// up in the [`Minidump`] that was processed and borrowed from its buffer without copying,
+    /// see [`Minidump::memory_regions`]. Returns `None` if the stack pointer was not recovered or
+    /// is not covered by any memory region.
+    pub fn stack_memory<'d>(
+        &self,
+        minidump: &Minidump<'d>,
+        arch: Arch,
+    ) -> Result<Option<MinidumpMemoryRegion<'d>>, MinidumpError> {
+        let stack_pointer = match self.context(arch).and_then(|c| c.stack_pointer()) {
+            Some(stack_pointer) => stack_pointer,
+            None => return Ok(None),
+        };
+
+        Ok(minidump
+            .memory_regions()?
+            .find(|region| region.contains(stack_pointer)))
+    }
+
+    /// Returns how the frame at the given index was recovered.
+    ///
+    /// Pass the same frame information that was used to process the minidump in `frame_infos` to
+    /// resolve the CFI rules that produced the frame. Returns `None` if the index is out of
+    /// bounds.
+    pub fn frame_provenance<'a>(
+        &'a self,
+        index: usize,
+        frame_infos: Option<&'a FrameInfoMap<'_>>,
+    ) -> Option<FrameProvenance<'a>> {
+        let frames = self.frames();
+        let trust = frames.get(index)?.trust();
+
+        let callee = match index.checked_sub(1) {
+            Some(callee_index) => frames[callee_index],
+            None => {
+                return Some(FrameProvenance {
+                    trust,
+                    module: None,
+                    module_offset: None,
+                    cfi: None,
+                })
+            }
+        };
+
+        let module = callee.module();
+        let module_offset =
+            module.map(|module| callee.instruction().wrapping_sub(module.base_address()));
+
+        let cfi = match (module, module_offset, frame_infos) {
+            (Some(module), Some(offset), Some(frame_infos)) if trust.uses_cfi() => module
+                .id()
+                .and_then(|id| frame_infos.get(&id))
+                .and_then(|cache| cache.lookup(offset)),
+            _ => None,
+        };
+
+        Some(FrameProvenance {
+            trust,
+            module,
+            module_offset,
+            cfi,
+        })
+    }
+
+    /// Resolves the inline frame chains of all frames in the call stack.
+    ///
+    /// Returns one chain per [`StackFrame`], in the same order as [`frames`](Self::frames). See
+    /// [`StackFrame::inline_frames`] for the contents of each chain.
+    #[cfg(feature = "symcache")]
+    pub fn inline_frames<'d>(
+        &self,
+        symcaches: &SymCacheMap<'d>,
+    ) -> Result<Vec<Vec<LineInfo<'d>>>, SymCacheError> {
+        self.frames()
+            .iter()
+            .map(|frame| frame.inline_frames(symcaches))
+            .collect()
+    }
 }
 
 impl fmt::Debug for CallStack {
@@ -1016,23 +1508,88 @@ impl fmt::Display for ProcessMinidumpError {
 
 impl std::error::Error for ProcessMinidumpError {}
 
+/// Callback used by Breakpad to look up unwind rules in a binary CFI cache.
+type CfiLookup = unsafe extern "C" fn(
+    cfi_cache: *const c_void,
+    address: u64,
+    kind_out: *mut c_int,
+    size_out: *mut usize,
+) -> *const c_char;
+
+/// No unwind rules cover the address.
+const CFI_LOOKUP_NONE: c_int = 0;
+/// The lookup returned the rules of a `STACK CFI` record.
+const CFI_LOOKUP_CFI: c_int = 1;
+/// The lookup returned the fields of a `STACK WIN` record.
+const CFI_LOOKUP_WIN: c_int = 2;
+
 /// Internal type used to transfer Breakpad symbols over FFI.
 #[repr(C)]
 struct SymbolEntry {
     debug_identifier: *const c_char,
     symbol_size: usize,
     symbol_data: *const u8,
+    cfi_cache: *const c_void,
+    cfi_lookup: Option<CfiLookup>,
+}
+
+/// A binary CFI cache passed to Breakpad, along with a buffer for the rules of the last lookup.
+struct CfiLookupContext<'a> {
+    cache: &'a CfiCache<'a>,
+    buffer: RefCell<Vec<u8>>,
+}
+
+/// Looks up the unwind rules covering a module-relative address in a [`CfiLookupContext`].
+///
+/// The returned buffer contains the rules of a `STACK CFI` record or the fields of a `STACK WIN`
+/// record, depending on the kind. It is valid until the next lookup in the same context.
+unsafe extern "C" fn cfi_cache_lookup(
+    cfi_cache: *const c_void,
+    address: u64,
+    kind_out: *mut c_int,
+    size_out: *mut usize,
+) -> *const c_char {
+    let context = &*(cfi_cache as *const CfiLookupContext<'_>);
+    let mut buffer = context.buffer.borrow_mut();
+    buffer.clear();
+
+    // Writing into a vector cannot fail.
+    *kind_out = match context.cache.lookup(address).map(|entry| entry.rules) {
+        Some(BreakpadUnwindRules::Cfi(rules)) => {
+            for (index, rule) in rules.iter().enumerate() {
+                let separator = if index > 0 { " " } else { "" };
+                write!(buffer, "{}{}", separator, rule).ok();
+            }
+            CFI_LOOKUP_CFI
+        }
+        Some(BreakpadUnwindRules::Win(record)) => {
+            cfi::write_win_fields(&mut *buffer, &record).ok();
+            CFI_LOOKUP_WIN
+        }
+        None => CFI_LOOKUP_NONE,
+    };
+
+    *size_out = buffer.len();
+    buffer.as_ptr() as *const c_char
 }
 
 /// Container for call frame information (CFI) of [`CodeModule`]s.
 ///
 /// This information is required by the stackwalker in case framepointers are
-/// missing in the raw stacktraces. Frame information is given as plain ASCII
-/// text as specified in the Breakpad symbol file specification.
+/// missing in the raw stacktraces. Unwind rules of binary CFI caches are looked
+/// up directly by the stackwalker. Older caches are passed as plain ASCII text
+/// as specified in the Breakpad symbol file specification.
 ///
 /// [`CodeModule`]: struct.CodeModule.html
 pub type FrameInfoMap<'a> = BTreeMap<CodeModuleId, CfiCache<'a>>;
 
+/// Container for SymCaches of [`CodeModule`]s.
+///
+/// SymCaches are used to resolve the inline frame chains of [`StackFrame`]s after processing.
+/// See [`StackFrame::inline_frames`] and [`CallStack::inline_frames`].
+#[cfg(feature = "symcache")]
+pub type SymCacheMap<'a> = BTreeMap<CodeModuleId, SymCache<'a>>;
+
 type IProcessState = c_void;
 
 /// Snapshot of the state of a processes during its crash. The object can be
@@ -1049,6 +1606,17 @@ impl<'a> ProcessState<'a> {
     /// process. The parameter `frame_infos` expects a map of Breakpad symbols
     /// containing STACK CFI and STACK WIN records to allow stackwalking with
     /// omitted frame pointers.
+    ///
+    /// If the module list of the minidump is incomplete or wrong, correct it with
+    /// [`Minidump::with_modules`](crate::minidump::Minidump::with_modules) and process the
+    /// returned minidump instead.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(size = buffer.len(), cfi_count = frame_infos.map_or(0, BTreeMap::len))
+        )
+    )]
     pub fn from_minidump(
         buffer: &ByteView<'a>,
         frame_infos: Option<&FrameInfoMap<'_>>,
@@ -1056,26 +1624,38 @@ impl<'a> ProcessState<'a> {
         let cfi_count = frame_infos.map_or(0, BTreeMap::len);
         let mut result: ProcessResult = ProcessResult::Ok;
 
-        // Keep a reference to all CStrings to extend their lifetime.
+        // Keep a reference to all CStrings and lookup contexts to extend their lifetime.
         let cfi_vec: Vec<_> = frame_infos.map_or(Vec::new(), |s| {
             s.iter()
                 .map(|(k, v)| {
-                    (
-                        CString::new(k.to_string()),
-                        v.as_slice().len(),
-                        v.as_slice().as_ptr(),
-                    )
+                    let context = CfiLookupContext {
+                        cache: v,
+                        buffer: RefCell::default(),
+                    };
+                    (CString::new(k.to_string()), context)
                 })
                 .collect()
         });
 
-        // Keep a reference to all symbol entries to extend their lifetime.
+        // Keep a reference to all symbol entries to extend their lifetime. Binary caches are not
+        // converted to ASCII. Instead, Breakpad looks up their rules through `cfi_cache_lookup`.
         let cfi_entries: Vec<_> = cfi_vec
             .iter()
-            .map(|&(ref id, size, data)| SymbolEntry {
-                debug_identifier: id.as_ref().map(|i| i.as_ptr()).unwrap_or(ptr::null()),
-                symbol_size: size,
-                symbol_data: data,
+            .map(|(id, context)| {
+                let (data, cfi_cache, cfi_lookup) = if context.cache.is_binary() {
+                    let cfi_cache = context as *const CfiLookupContext<'_> as *const c_void;
+                    (&[][..], cfi_cache, Some(cfi_cache_lookup as CfiLookup))
+                } else {
+                    (context.cache.as_slice(), ptr::null(), None)
+                };
+
+                SymbolEntry {
+                    debug_identifier: id.as_ref().map(|i| i.as_ptr()).unwrap_or(ptr::null()),
+                    symbol_size: data.len(),
+                    symbol_data: data.as_ptr(),
+                    cfi_cache,
+                    cfi_lookup,
+                }
             })
             .collect();
 
@@ -1,6 +1,9 @@
+#[cfg(feature = "processor")]
 use std::ffi::CStr;
+#[cfg(feature = "processor")]
 use std::os::raw::c_char;
 
+#[cfg(feature = "processor")]
 extern "C" {
     fn string_delete(string: *mut c_char);
 }
@@ -8,6 +11,7 @@ extern "C" {
 /// Converts an owned raw pointer to characters to an owned `String`.
 ///
 /// If the pointer is NULL, an empty string `""` is returned.
+#[cfg(feature = "processor")]
 pub fn ptr_to_string(ptr: *mut c_char) -> String {
     if ptr.is_null() {
         return String::new();
@@ -20,3 +24,15 @@ pub fn ptr_to_string(ptr: *mut c_char) -> String {
     unsafe { string_delete(ptr) };
     string
 }
+
+/// Parses a hexadecimal number with an optional `0x` prefix.
+#[cfg(any(feature = "applecrashreport", feature = "hserrlog"))]
+pub fn parse_hex(string: &str) -> Option<u64> {
+    u64::from_str_radix(string.trim_start_matches("0x"), 16).ok()
+}
+
+/// Returns the last component of a path with either forward or backward slashes.
+#[cfg(any(feature = "applecrashreport", feature = "hserrlog"))]
+pub fn file_name(path: &str) -> &str {
+    path.rsplit(['/', '\\']).next().unwrap_or(path)
+}
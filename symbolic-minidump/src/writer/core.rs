@@ -0,0 +1,347 @@
+//! Reading of ELF core files.
+
+use std::convert::TryInto;
+
+use goblin::container::Ctx;
+use goblin::elf::header::{EM_AARCH64, EM_X86_64, ET_CORE};
+use goblin::elf::program_header::{ProgramHeader, PF_R, PF_W, PF_X, PT_LOAD, PT_NOTE};
+use goblin::elf::Elf;
+
+use symbolic_common::{Arch, CpuFamily};
+use symbolic_debuginfo::elf::{CoreFileMapping, CoreThread};
+
+use super::{
+    modules_from_mappings, CpuContext, ExceptionInfo, LinuxStream, Mapping, MinidumpWriter,
+    ReadMemory, SystemInfo, ThreadInfo, WriterError, WriterErrorKind,
+};
+
+pub const NT_PRSTATUS: u32 = 1;
+pub const NT_FPREGSET: u32 = 2;
+pub const NT_GNU_BUILD_ID: u32 = 3;
+pub const NT_AUXV: u32 = 6;
+pub const NT_SIGINFO: u32 = 0x5349_4749;
+pub const NT_FILE: u32 = 0x4649_4c45;
+
+/// A note in an ELF note segment.
+pub struct Note<'a> {
+    pub kind: u32,
+    pub name: &'a [u8],
+    pub desc: &'a [u8],
+}
+
+/// Iterates notes in the contents of a `PT_NOTE` segment.
+///
+/// Note names are returned without their trailing NUL terminator. Iteration stops at the first
+/// malformed note.
+pub fn iter_notes(data: &[u8], little_endian: bool) -> impl Iterator<Item = Note<'_>> {
+    let read_u32 = move |bytes: &[u8]| {
+        let bytes = bytes.try_into().unwrap();
+        if little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        }
+    };
+
+    let align = |value: usize| (value + 3) & !3;
+    let mut offset = 0;
+
+    std::iter::from_fn(move || {
+        let header = data.get(offset..offset + 12)?;
+        let name_size = read_u32(&header[0..4]) as usize;
+        let desc_size = read_u32(&header[4..8]) as usize;
+        let kind = read_u32(&header[8..12]);
+
+        let name_start = offset + 12;
+        let desc_start = name_start.checked_add(align(name_size))?;
+        let end = desc_start.checked_add(align(desc_size))?;
+
+        let name = data.get(name_start..name_start + name_size)?;
+        let name = name.strip_suffix(&[0]).unwrap_or(name);
+        let desc = data.get(desc_start..desc_start + desc_size)?;
+
+        offset = end;
+        Some(Note { kind, name, desc })
+    })
+}
+
+/// Memory contents of a core file, backed by its `PT_LOAD` segments.
+struct CoreMemory<'a> {
+    data: &'a [u8],
+    segments: Vec<ProgramHeader>,
+}
+
+impl CoreMemory<'_> {
+    /// Returns the segment containing the given address.
+    fn find_segment(&self, address: u64) -> Option<&ProgramHeader> {
+        self.segments.iter().find(|segment| {
+            matches!(address.checked_sub(segment.p_vaddr), Some(offset) if offset < segment.p_memsz)
+        })
+    }
+}
+
+impl ReadMemory for CoreMemory<'_> {
+    fn read_memory(&self, address: u64, len: usize) -> Option<Vec<u8>> {
+        let segment = self.find_segment(address)?;
+
+        let start = address - segment.p_vaddr;
+        let end = start.checked_add(len as u64)?;
+        if end > segment.p_memsz {
+            return None;
+        }
+
+        // Segments may be partially dumped. Memory beyond the file size reads as zeros.
+        let mut buffer = vec![0; len];
+        if start < segment.p_filesz {
+            let available = (segment.p_filesz.min(end) - start) as usize;
+            let file_start = segment.p_offset.checked_add(start)? as usize;
+            let bytes = self
+                .data
+                .get(file_start..file_start.checked_add(available)?)?;
+            buffer[..available].copy_from_slice(bytes);
+        } else if segment.p_filesz == 0 {
+            return None;
+        }
+
+        Some(buffer)
+    }
+
+    fn mapping_end(&self, address: u64) -> Option<u64> {
+        let segment = self.find_segment(address)?;
+        segment.p_vaddr.checked_add(segment.p_memsz)
+    }
+}
+
+/// Formats file mappings and segment permissions like `/proc/<pid>/maps`.
+fn format_maps(mappings: &[Mapping], segments: &[ProgramHeader]) -> String {
+    let mut maps = String::new();
+
+    for mapping in mappings {
+        let flags = segments
+            .iter()
+            .find(|segment| segment.p_vaddr == mapping.start)
+            .map_or(PF_R, |segment| segment.p_flags);
+
+        maps.push_str(&format!(
+            "{:x}-{:x} {}{}{}p {:08x} 00:00 0 {}\n",
+            mapping.start,
+            mapping.end,
+            if flags & PF_R != 0 { 'r' } else { '-' },
+            if flags & PF_W != 0 { 'w' } else { '-' },
+            if flags & PF_X != 0 { 'x' } else { '-' },
+            mapping.offset,
+            mapping.path,
+        ));
+    }
+
+    maps
+}
+
+pub fn read_core(data: &[u8]) -> Result<MinidumpWriter, WriterError> {
+    let header =
+        Elf::parse_header(data).map_err(|e| WriterError::new(WriterErrorKind::InvalidCore, e))?;
+
+    if header.e_type != ET_CORE {
+        return Err(WriterError::new(
+            WriterErrorKind::InvalidCore,
+            "not an ELF core file",
+        ));
+    }
+
+    let (arch, cpu_family) = match header.e_machine {
+        EM_X86_64 => (Arch::Amd64, CpuFamily::Amd64),
+        EM_AARCH64 => (Arch::Arm64, CpuFamily::Arm64),
+        _ => return Err(WriterErrorKind::UnsupportedArch.into()),
+    };
+
+    let ctx = header
+        .container()
+        .and_then(|container| Ok(Ctx::new(container, header.endianness()?)))
+        .map_err(|e| WriterError::new(WriterErrorKind::InvalidCore, e))?;
+
+    let program_headers =
+        ProgramHeader::parse(data, header.e_phoff as usize, header.e_phnum as usize, ctx)
+            .map_err(|e| WriterError::new(WriterErrorKind::InvalidCore, e))?;
+
+    let memory = CoreMemory {
+        data,
+        segments: program_headers
+            .iter()
+            .filter(|phdr| phdr.p_type == PT_LOAD)
+            .cloned()
+            .collect(),
+    };
+
+    let mut writer = MinidumpWriter::new(SystemInfo {
+        cpu_family,
+        ..Default::default()
+    });
+
+    let mut mappings = Vec::new();
+    let mut signal = None;
+
+    for phdr in program_headers.iter().filter(|phdr| phdr.p_type == PT_NOTE) {
+        let range = phdr.file_range();
+        let notes = data
+            .get(range)
+            .ok_or_else(|| WriterError::new(WriterErrorKind::InvalidCore, "truncated notes"))?;
+
+        for note in iter_notes(notes, ctx.le.is_little()) {
+            match (note.name, note.kind) {
+                (b"CORE", NT_PRSTATUS) => {
+                    let invalid = || WriterError::new(WriterErrorKind::InvalidCore, "bad prstatus");
+                    let thread = CoreThread::from_prstatus(note.desc, arch).ok_or_else(invalid)?;
+                    let values: Vec<_> = thread.registers.iter().map(|&(_, v)| v).collect();
+                    let context = CpuContext::from_register_values(cpu_family, &values)
+                        .ok_or_else(invalid)?;
+
+                    let thread_id = thread.thread_id;
+                    if signal.is_none() && thread.signal != 0 {
+                        signal = Some(ExceptionInfo {
+                            thread_id,
+                            signal: thread.signal,
+                            ..Default::default()
+                        });
+                    }
+
+                    let stack = memory.read_stack(context.stack_pointer());
+                    writer.add_thread(ThreadInfo {
+                        thread_id,
+                        context,
+                        stack,
+                    });
+                }
+                (b"CORE", NT_FPREGSET) => {
+                    if let Some(thread) = writer.threads.last_mut() {
+                        thread.context.set_float_regs(note.desc);
+                    }
+                }
+                (b"CORE", NT_AUXV) => {
+                    writer.add_linux_stream(LinuxStream::Auxv, note.desc.to_vec());
+                }
+                (b"CORE", NT_SIGINFO) => {
+                    // si_signo, si_errno, si_code, padding, then si_addr for faults.
+                    let read_u32 = |offset: usize| {
+                        note.desc
+                            .get(offset..offset + 4)
+                            .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+                    };
+
+                    if let (Some(signo), Some(code)) = (read_u32(0), read_u32(8)) {
+                        let address = note
+                            .desc
+                            .get(16..24)
+                            .map_or(0, |b| u64::from_le_bytes(b.try_into().unwrap()));
+
+                        let thread_id = writer.threads.last().map_or(0, |t| t.thread_id);
+                        signal = Some(ExceptionInfo {
+                            thread_id,
+                            signal: signo,
+                            code,
+                            address,
+                        });
+                    }
+                }
+                (b"CORE", NT_FILE) => {
+                    let file_mappings =
+                        CoreFileMapping::parse_note(note.desc).ok_or_else(|| {
+                            WriterError::new(WriterErrorKind::InvalidCore, "bad file note")
+                        })?;
+
+                    mappings = file_mappings
+                        .into_iter()
+                        .map(|mapping| Mapping {
+                            start: mapping.start,
+                            end: mapping.end,
+                            offset: mapping.offset,
+                            path: mapping.path.into_owned(),
+                        })
+                        .collect();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    if writer.threads.is_empty() {
+        return Err(WriterError::new(
+            WriterErrorKind::InvalidCore,
+            "core file contains no threads",
+        ));
+    }
+
+    if let Some(exception) = signal {
+        writer.set_exception(exception);
+    }
+
+    if !mappings.is_empty() {
+        let maps = format_maps(&mappings, &memory.segments);
+        writer.add_linux_stream(LinuxStream::Maps, maps.into_bytes());
+    }
+
+    for mut module in modules_from_mappings(&mappings) {
+        module.build_id = memory.read_build_id(module.base);
+        writer.add_module(module);
+    }
+
+    Ok(writer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_iter_notes() {
+        let data = [
+            4, 0, 0, 0, 3, 0, 0, 0, 3, 0, 0, 0, b'G', b'N', b'U', 0, 1, 2, 3, 0, // build id
+            5, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, b'C', b'O', b'R', b'E', 0, 0, 0,
+            0, // prstatus
+        ];
+
+        let notes: Vec<_> = iter_notes(&data, true).collect();
+        assert_eq!(notes.len(), 2);
+        assert_eq!(notes[0].name, b"GNU");
+        assert_eq!(notes[0].kind, NT_GNU_BUILD_ID);
+        assert_eq!(notes[0].desc, [1, 2, 3]);
+        assert_eq!(notes[1].name, b"CORE");
+        assert_eq!(notes[1].kind, NT_PRSTATUS);
+        assert!(notes[1].desc.is_empty());
+    }
+
+    #[test]
+    fn test_read_memory_overflow() {
+        let data = [0xab; 16];
+        let memory = CoreMemory {
+            data: &data,
+            segments: vec![
+                ProgramHeader {
+                    p_type: PT_LOAD,
+                    p_vaddr: u64::MAX - 0xf,
+                    p_memsz: 0x100,
+                    p_filesz: 0x10,
+                    ..Default::default()
+                },
+                ProgramHeader {
+                    p_type: PT_LOAD,
+                    p_vaddr: 0x1000,
+                    p_memsz: 0x100,
+                    p_filesz: 0x100,
+                    p_offset: u64::MAX,
+                    ..Default::default()
+                },
+            ],
+        };
+
+        assert_eq!(memory.read_memory(u64::MAX - 1, 1), Some(vec![0xab]));
+        assert_eq!(memory.mapping_end(u64::MAX - 1), None);
+        assert_eq!(memory.read_memory(0x1000, 4), None);
+        assert_eq!(memory.mapping_end(0x1000), Some(0x1100));
+    }
+
+    #[test]
+    fn test_not_a_core() {
+        let error = read_core(b"\x7fELF").unwrap_err();
+        assert_eq!(error.kind(), WriterErrorKind::InvalidCore);
+    }
+}
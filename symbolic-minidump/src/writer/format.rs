@@ -0,0 +1,185 @@
+//! Raw minidump structures and a little-endian serializer.
+//!
+//! The layouts in this module mirror the definitions in Breakpad's `minidump_format.h`. All values
+//! are written in little-endian byte order, which is the only byte order the Breakpad processor
+//! supports.
+
+/// The minidump signature, `MDMP` in little-endian.
+pub const MINIDUMP_SIGNATURE: u32 = 0x504d_444d;
+
+/// The minidump format version.
+pub const MINIDUMP_VERSION: u32 = 0xa793;
+
+/// Size of `MDRawHeader`.
+pub const HEADER_SIZE: usize = 32;
+
+/// Size of `MDRawDirectory`.
+pub const DIRECTORY_SIZE: usize = 12;
+
+/// Size of `MDRawThread`.
+pub const THREAD_SIZE: usize = 48;
+
+/// Size of `MDRawModule`.
+pub const MODULE_SIZE: usize = 108;
+
+/// Size of `MDRawContextAMD64`.
+pub const CONTEXT_AMD64_SIZE: usize = 1232;
+
+/// Size of `MDRawContextARM64`.
+pub const CONTEXT_ARM64_SIZE: usize = 912;
+
+pub const STREAM_THREAD_LIST: u32 = 3;
+pub const STREAM_MODULE_LIST: u32 = 4;
+pub const STREAM_MEMORY_LIST: u32 = 5;
+pub const STREAM_EXCEPTION: u32 = 6;
+pub const STREAM_SYSTEM_INFO: u32 = 7;
+
+pub const CPU_ARCHITECTURE_AMD64: u16 = 9;
+pub const CPU_ARCHITECTURE_ARM64: u16 = 12;
+
+pub const PLATFORM_LINUX: u32 = 0x8201;
+
+pub const CONTEXT_AMD64: u32 = 0x0010_0000;
+pub const CONTEXT_AMD64_FULL: u32 = CONTEXT_AMD64 | 0x1 | 0x2 | 0x8;
+pub const CONTEXT_AMD64_SEGMENTS: u32 = CONTEXT_AMD64 | 0x4;
+
+pub const CONTEXT_ARM64: u32 = 0x0040_0000;
+pub const CONTEXT_ARM64_FULL: u32 = CONTEXT_ARM64 | 0x1 | 0x2 | 0x4;
+
+/// CodeView signature for ELF build identifiers, `BpEL` in little-endian.
+pub const CV_SIGNATURE_ELF: u32 = 0x4270_454c;
+
+/// A location descriptor (`MDLocationDescriptor`) pointing into the minidump.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Location {
+    pub size: u32,
+    pub rva: u32,
+}
+
+/// An append-only buffer that keeps track of relative virtual addresses (RVAs).
+#[derive(Debug, Default)]
+pub struct Buffer {
+    data: Vec<u8>,
+}
+
+impl Buffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The RVA of the next byte written to this buffer.
+    pub fn rva(&self) -> u32 {
+        self.data.len() as u32
+    }
+
+    pub fn u8(&mut self, value: u8) {
+        self.data.push(value);
+    }
+
+    pub fn u16(&mut self, value: u16) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn u32(&mut self, value: u32) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn u64(&mut self, value: u64) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn u128(&mut self, value: u128) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn bytes(&mut self, bytes: &[u8]) {
+        self.data.extend_from_slice(bytes);
+    }
+
+    pub fn zeros(&mut self, count: usize) {
+        self.data.resize(self.data.len() + count, 0);
+    }
+
+    pub fn location(&mut self, location: Location) {
+        self.u32(location.size);
+        self.u32(location.rva);
+    }
+
+    /// Pads the buffer with zeros to the given alignment.
+    pub fn align(&mut self, alignment: usize) {
+        let padding = (alignment - self.data.len() % alignment) % alignment;
+        self.zeros(padding);
+    }
+
+    /// Reserves `size` zeroed bytes and returns their RVA to be patched later.
+    pub fn reserve(&mut self, size: usize) -> u32 {
+        let rva = self.rva();
+        self.zeros(size);
+        rva
+    }
+
+    /// Overwrites a previously reserved `u32` at the given RVA.
+    pub fn patch_u32(&mut self, rva: u32, value: u32) {
+        let offset = rva as usize;
+        self.data[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    /// Overwrites a previously reserved location descriptor at the given RVA.
+    pub fn patch_location(&mut self, rva: u32, location: Location) {
+        self.patch_u32(rva, location.size);
+        self.patch_u32(rva + 4, location.rva);
+    }
+
+    /// Appends a blob of data and returns its location.
+    pub fn blob(&mut self, bytes: &[u8]) -> Location {
+        self.align(8);
+        let rva = self.rva();
+        self.bytes(bytes);
+        Location {
+            size: bytes.len() as u32,
+            rva,
+        }
+    }
+
+    /// Appends a `MDString` (length-prefixed UTF-16LE) and returns its RVA.
+    pub fn string(&mut self, string: &str) -> u32 {
+        self.align(4);
+        let rva = self.rva();
+        let units: Vec<u16> = string.encode_utf16().collect();
+        self.u32((units.len() * 2) as u32);
+        for unit in units {
+            self.u16(unit);
+        }
+        self.u16(0);
+        rva
+    }
+
+    pub fn into_inner(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_string() {
+        let mut buffer = Buffer::new();
+        buffer.u8(1);
+        let rva = buffer.string("ab");
+        assert_eq!(rva, 4);
+        assert_eq!(
+            buffer.into_inner(),
+            [1, 0, 0, 0, 4, 0, 0, 0, b'a', 0, b'b', 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn test_patch_location() {
+        let mut buffer = Buffer::new();
+        let rva = buffer.reserve(8);
+        buffer.patch_location(rva, Location { size: 2, rva: 3 });
+        assert_eq!(buffer.into_inner(), [2, 0, 0, 0, 3, 0, 0, 0]);
+    }
+}
@@ -0,0 +1,224 @@
+//! Capturing of live processes on Linux using `ptrace`.
+
+use std::fs::File;
+use std::io;
+use std::os::unix::fs::FileExt;
+use std::path::PathBuf;
+
+use symbolic_common::CpuFamily;
+use symbolic_debuginfo::elf::ElfObject;
+
+use super::core::{NT_FPREGSET, NT_PRSTATUS};
+use super::{
+    modules_from_mappings, parse_maps, CpuContext, Mapping, MinidumpWriter, ReadMemory, SystemInfo,
+    ThreadInfo, WriterError, WriterErrorKind,
+};
+
+/// Size of the buffer used to retrieve register sets.
+const REGSET_BUFFER_SIZE: usize = 1024;
+
+/// A thread stopped with `PTRACE_ATTACH`, which is detached again on drop.
+struct AttachedThread {
+    tid: libc::pid_t,
+}
+
+impl AttachedThread {
+    fn attach(tid: libc::pid_t) -> io::Result<Self> {
+        let null = std::ptr::null_mut::<libc::c_void>();
+        if unsafe { libc::ptrace(libc::PTRACE_ATTACH, tid, null, null) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+
+        // Construct the guard before waiting, so that the thread is detached on errors.
+        let thread = AttachedThread { tid };
+
+        loop {
+            let mut status = 0;
+            if unsafe { libc::waitpid(tid, &mut status, libc::__WALL) } == -1 {
+                let error = io::Error::last_os_error();
+                if error.kind() == io::ErrorKind::Interrupted {
+                    continue;
+                }
+                return Err(error);
+            }
+
+            if libc::WIFSTOPPED(status) {
+                return Ok(thread);
+            }
+        }
+    }
+
+    fn regset(&self, kind: u32) -> io::Result<Vec<u8>> {
+        let mut buffer = vec![0u8; REGSET_BUFFER_SIZE];
+        let mut iov = libc::iovec {
+            iov_base: buffer.as_mut_ptr() as *mut libc::c_void,
+            iov_len: buffer.len(),
+        };
+
+        let result = unsafe {
+            libc::ptrace(
+                libc::PTRACE_GETREGSET,
+                self.tid,
+                kind as usize as *mut libc::c_void,
+                &mut iov as *mut libc::iovec as *mut libc::c_void,
+            )
+        };
+
+        if result == -1 {
+            return Err(io::Error::last_os_error());
+        }
+
+        buffer.truncate(iov.iov_len);
+        Ok(buffer)
+    }
+}
+
+impl Drop for AttachedThread {
+    fn drop(&mut self) {
+        let null = std::ptr::null_mut::<libc::c_void>();
+        unsafe { libc::ptrace(libc::PTRACE_DETACH, self.tid, null, null) };
+    }
+}
+
+/// Memory of a live process, read through `/proc/<pid>/mem`.
+struct ProcessMemory {
+    mem: File,
+    mappings: Vec<Mapping>,
+}
+
+impl ReadMemory for ProcessMemory {
+    fn read_memory(&self, address: u64, len: usize) -> Option<Vec<u8>> {
+        let mut buffer = vec![0; len];
+        self.mem.read_exact_at(&mut buffer, address).ok()?;
+        Some(buffer)
+    }
+
+    fn mapping_end(&self, address: u64) -> Option<u64> {
+        self.mappings
+            .iter()
+            .find(|mapping| address >= mapping.start && address < mapping.end)
+            .map(|mapping| mapping.end)
+    }
+}
+
+/// Returns the CPU family of the current process, which must match the target process.
+fn host_cpu_family() -> Option<CpuFamily> {
+    if cfg!(target_arch = "x86_64") {
+        Some(CpuFamily::Amd64)
+    } else if cfg!(target_arch = "aarch64") {
+        Some(CpuFamily::Arm64)
+    } else {
+        None
+    }
+}
+
+/// Returns the OS version string in the format written by Breakpad.
+fn os_version() -> String {
+    let mut uts = unsafe { std::mem::zeroed::<libc::utsname>() };
+    if unsafe { libc::uname(&mut uts) } != 0 {
+        return String::new();
+    }
+
+    let field = |chars: &[libc::c_char]| {
+        let bytes: Vec<u8> = chars
+            .iter()
+            .take_while(|c| **c != 0)
+            .map(|c| *c as u8)
+            .collect();
+        String::from_utf8_lossy(&bytes).into_owned()
+    };
+
+    format!(
+        "{} {} {} {}",
+        field(&uts.sysname),
+        field(&uts.release),
+        field(&uts.version),
+        field(&uts.machine)
+    )
+}
+
+fn cpu_count() -> u8 {
+    let count = unsafe { libc::sysconf(libc::_SC_NPROCESSORS_CONF) };
+    count.clamp(1, u8::MAX as libc::c_long) as u8
+}
+
+pub fn read_process(pid: u32) -> Result<MinidumpWriter, WriterError> {
+    let cpu_family = host_cpu_family().ok_or(WriterErrorKind::UnsupportedArch)?;
+    let proc_dir = PathBuf::from(format!("/proc/{}", pid));
+    let access_error = |e| WriterError::new(WriterErrorKind::ProcessAccess, e);
+
+    let mut tids = std::fs::read_dir(proc_dir.join("task"))
+        .map_err(access_error)?
+        .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+        .collect::<Vec<libc::pid_t>>();
+    tids.sort_unstable();
+
+    // Stop all threads before reading anything, so that memory and registers are consistent.
+    // Threads that exit in the meantime are skipped.
+    let attached: Vec<_> = tids
+        .into_iter()
+        .filter_map(|tid| AttachedThread::attach(tid).ok())
+        .collect();
+
+    if attached.is_empty() {
+        return Err(WriterError::new(
+            WriterErrorKind::ProcessAccess,
+            "failed to attach to any thread",
+        ));
+    }
+
+    let maps = std::fs::read_to_string(proc_dir.join("maps")).map_err(access_error)?;
+    let memory = ProcessMemory {
+        mem: File::open(proc_dir.join("mem")).map_err(access_error)?,
+        mappings: parse_maps(&maps),
+    };
+
+    let mut writer = MinidumpWriter::new(SystemInfo {
+        cpu_family,
+        cpu_count: cpu_count(),
+        os_version: os_version(),
+    });
+
+    for thread in &attached {
+        let regs = thread.regset(NT_PRSTATUS).map_err(access_error)?;
+        let mut context = CpuContext::from_user_regs(cpu_family, &regs)
+            .ok_or_else(|| WriterError::new(WriterErrorKind::ProcessAccess, "bad registers"))?;
+
+        if let Ok(fpregs) = thread.regset(NT_FPREGSET) {
+            context.set_float_regs(&fpregs);
+        }
+
+        let stack = memory.read_stack(context.stack_pointer());
+        writer.add_thread(ThreadInfo {
+            thread_id: thread.tid as u32,
+            context,
+            stack,
+        });
+    }
+
+    for mut module in modules_from_mappings(&memory.mappings) {
+        module.build_id = memory.read_build_id(module.base).or_else(|| {
+            // Fall back to the file on disk if the headers are not mapped.
+            let data = std::fs::read(&module.name).ok()?;
+            let code_id = ElfObject::parse(&data).ok()?.code_id()?;
+            (0..code_id.as_str().len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(code_id.as_str().get(i..i + 2)?, 16).ok())
+                .collect()
+        });
+        writer.add_module(module);
+    }
+
+    // Resume the process before reading the remaining metadata.
+    drop(attached);
+
+    writer.add_procfs(&proc_dir)?;
+    if let Ok(cpuinfo) = std::fs::read("/proc/cpuinfo") {
+        writer.add_linux_stream(super::LinuxStream::CpuInfo, cpuinfo);
+    }
+    if let Ok(lsb_release) = std::fs::read("/etc/lsb-release") {
+        writer.add_linux_stream(super::LinuxStream::LsbRelease, lsb_release);
+    }
+
+    Ok(writer)
+}
@@ -0,0 +1,950 @@
+//! Creation of minidumps from crashed processes and core files.
+//!
+//! The root type is [`MinidumpWriter`], which collects threads, loaded modules, memory and system
+//! information and serializes them into a minidump that can be processed by the Breakpad processor
+//! (see [`ProcessState`]) or any other minidump consumer.
+//!
+//! A writer can be populated in three ways:
+//!
+//!  - From an ELF core file with [`MinidumpWriter::from_core`]. Thread registers are read from
+//!    `NT_PRSTATUS` notes, loaded modules from the `NT_FILE` note and stack memory from the dumped
+//!    `PT_LOAD` segments.
+//!  - From a live process on Linux with [`MinidumpWriter::from_process`]. This attaches to all
+//!    threads of the process using `ptrace` and reads their registers and memory.
+//!  - Manually, by adding threads, modules and memory regions.
+//!
+//! Additional Linux metadata, such as the contents of `/proc/<pid>/maps` or the command line, can
+//! be attached with [`MinidumpWriter::add_procfs`] or [`MinidumpWriter::add_linux_stream`]. This is
+//! particularly useful for core files, which do not carry this information themselves.
+//!
+//! Currently, only x86_64 and ARM64 processes are supported.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use symbolic_minidump::writer::MinidumpWriter;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let core = std::fs::read("core.1234")?;
+//! let mut writer = MinidumpWriter::from_core(&core)?;
+//! writer.add_procfs("/var/lib/crashes/1234/proc")?;
+//! std::fs::write("crash.dmp", writer.to_vec())?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`ProcessState`]: ../processor/struct.ProcessState.html
+
+use std::convert::TryInto;
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Write};
+use std::path::Path;
+
+use thiserror::Error;
+
+use symbolic_common::CpuFamily;
+
+mod core;
+mod format;
+#[cfg(target_os = "linux")]
+mod linux;
+
+use self::format::{Buffer, Location};
+
+/// The maximum number of bytes captured from a thread's stack.
+pub const MAX_STACK_SIZE: u64 = 256 * 1024;
+
+/// Bytes below the stack pointer that are captured in addition to the stack (the red zone).
+const STACK_RED_ZONE: u64 = 128;
+
+/// The kind of a [`WriterError`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WriterErrorKind {
+    /// The core file is not a valid ELF core dump.
+    InvalidCore,
+
+    /// The architecture of the process is not supported.
+    UnsupportedArch,
+
+    /// Attaching to or reading from the process failed.
+    ProcessAccess,
+
+    /// Reading process metadata failed.
+    ReadFailed,
+
+    /// Writing the minidump failed, likely IO.
+    WriteFailed,
+}
+
+impl fmt::Display for WriterErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidCore => write!(f, "invalid core file"),
+            Self::UnsupportedArch => write!(f, "unsupported architecture"),
+            Self::ProcessAccess => write!(f, "failed to access process"),
+            Self::ReadFailed => write!(f, "failed to read process metadata"),
+            Self::WriteFailed => write!(f, "failed to write minidump"),
+        }
+    }
+}
+
+/// An error returned when creating or writing minidumps.
+#[derive(Debug, Error)]
+#[error("{kind}")]
+pub struct WriterError {
+    kind: WriterErrorKind,
+    #[source]
+    source: Option<Box<dyn Error + Send + Sync + 'static>>,
+}
+
+impl WriterError {
+    /// Creates a new writer error from a known kind of error as well as an arbitrary error
+    /// payload.
+    fn new<E>(kind: WriterErrorKind, source: E) -> Self
+    where
+        E: Into<Box<dyn Error + Send + Sync>>,
+    {
+        let source = Some(source.into());
+        Self { kind, source }
+    }
+
+    /// Returns the corresponding [`WriterErrorKind`] for this error.
+    pub fn kind(&self) -> WriterErrorKind {
+        self.kind
+    }
+}
+
+impl From<WriterErrorKind> for WriterError {
+    fn from(kind: WriterErrorKind) -> Self {
+        Self { kind, source: None }
+    }
+}
+
+/// Register values of an x86_64 thread.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Amd64Context {
+    /// General purpose registers in the order `rax`, `rcx`, `rdx`, `rbx`, `rsp`, `rbp`, `rsi`,
+    /// `rdi`, `r8` to `r15`.
+    pub gpr: [u64; 16],
+    /// The instruction pointer.
+    pub rip: u64,
+    /// The flags register.
+    pub eflags: u32,
+    /// Segment registers in the order `cs`, `ds`, `es`, `fs`, `gs`, `ss`.
+    pub segments: [u16; 6],
+    /// The legacy `FXSAVE` area containing x87, MMX and SSE state, if available.
+    ///
+    /// This is either empty or exactly 512 bytes long.
+    pub fxsave: Vec<u8>,
+}
+
+impl Amd64Context {
+    /// Index of the stack pointer in [`gpr`](Self::gpr).
+    pub const RSP: usize = 4;
+
+    /// Reads registers from a Linux `user_regs_struct`.
+    fn from_user_regs(regs: &[u64]) -> Option<Self> {
+        if regs.len() < 27 {
+            return None;
+        }
+
+        // r15, r14, r13, r12, rbp, rbx, r11, r10, r9, r8, rax, rcx, rdx, rsi, rdi, orig_rax, rip,
+        // cs, eflags, rsp, ss, fs_base, gs_base, ds, es, fs, gs
+        let gpr = [
+            regs[10], regs[11], regs[12], regs[5], regs[19], regs[4], regs[13], regs[14], regs[9],
+            regs[8], regs[7], regs[6], regs[3], regs[2], regs[1], regs[0],
+        ];
+
+        Some(Amd64Context {
+            gpr,
+            rip: regs[16],
+            eflags: regs[18] as u32,
+            segments: [
+                regs[17] as u16,
+                regs[23] as u16,
+                regs[24] as u16,
+                regs[25] as u16,
+                regs[26] as u16,
+                regs[20] as u16,
+            ],
+            fxsave: Vec::new(),
+        })
+    }
+}
+
+/// Register values of an ARM64 thread.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Arm64Context {
+    /// General purpose registers `x0` to `x30`, including the frame pointer and link register.
+    pub x: [u64; 31],
+    /// The stack pointer.
+    pub sp: u64,
+    /// The program counter.
+    pub pc: u64,
+    /// The processor state (`cpsr`).
+    pub pstate: u32,
+    /// The floating point and SIMD registers `v0` to `v31`.
+    pub v: Vec<u128>,
+    /// The floating point status register.
+    pub fpsr: u32,
+    /// The floating point control register.
+    pub fpcr: u32,
+}
+
+impl Arm64Context {
+    /// Reads registers from a Linux `user_pt_regs`.
+    fn from_user_regs(regs: &[u64]) -> Option<Self> {
+        if regs.len() < 34 {
+            return None;
+        }
+
+        let mut x = [0; 31];
+        x.copy_from_slice(&regs[..31]);
+
+        Some(Arm64Context {
+            x,
+            sp: regs[31],
+            pc: regs[32],
+            pstate: regs[33] as u32,
+            v: Vec::new(),
+            fpsr: 0,
+            fpcr: 0,
+        })
+    }
+}
+
+/// The CPU context of a thread.
+#[non_exhaustive]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CpuContext {
+    /// Registers of an x86_64 thread.
+    Amd64(Amd64Context),
+    /// Registers of an ARM64 thread.
+    Arm64(Arm64Context),
+}
+
+impl CpuContext {
+    /// Reads a CPU context from the raw Linux register set of the given CPU family.
+    ///
+    /// This is the layout of `pr_reg` in `NT_PRSTATUS` notes, as well as the register set returned
+    /// by `PTRACE_GETREGSET`.
+    fn from_user_regs(cpu_family: CpuFamily, data: &[u8]) -> Option<Self> {
+        let regs: Vec<u64> = data
+            .chunks_exact(8)
+            .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()))
+            .collect();
+
+        Self::from_register_values(cpu_family, &regs)
+    }
+
+    /// Creates a CPU context from the values of the Linux register set, in the order of `pr_reg`.
+    fn from_register_values(cpu_family: CpuFamily, regs: &[u64]) -> Option<Self> {
+        match cpu_family {
+            CpuFamily::Amd64 => Amd64Context::from_user_regs(regs).map(CpuContext::Amd64),
+            CpuFamily::Arm64 => Arm64Context::from_user_regs(regs).map(CpuContext::Arm64),
+            _ => None,
+        }
+    }
+
+    /// Applies the raw Linux floating point register set (`NT_FPREGSET`).
+    fn set_float_regs(&mut self, data: &[u8]) {
+        match self {
+            CpuContext::Amd64(context) => {
+                if data.len() >= 512 {
+                    context.fxsave = data[..512].to_vec();
+                }
+            }
+            CpuContext::Arm64(context) => {
+                if data.len() >= 32 * 16 + 8 {
+                    context.v = data[..32 * 16]
+                        .chunks_exact(16)
+                        .map(|chunk| u128::from_le_bytes(chunk.try_into().unwrap()))
+                        .collect();
+                    context.fpsr = u32::from_le_bytes(data[512..516].try_into().unwrap());
+                    context.fpcr = u32::from_le_bytes(data[516..520].try_into().unwrap());
+                }
+            }
+        }
+    }
+
+    /// Returns the CPU family of this context.
+    pub fn cpu_family(&self) -> CpuFamily {
+        match self {
+            CpuContext::Amd64(_) => CpuFamily::Amd64,
+            CpuContext::Arm64(_) => CpuFamily::Arm64,
+        }
+    }
+
+    /// Returns the value of the instruction pointer.
+    pub fn instruction_pointer(&self) -> u64 {
+        match self {
+            CpuContext::Amd64(context) => context.rip,
+            CpuContext::Arm64(context) => context.pc,
+        }
+    }
+
+    /// Returns the value of the stack pointer.
+    pub fn stack_pointer(&self) -> u64 {
+        match self {
+            CpuContext::Amd64(context) => context.gpr[Amd64Context::RSP],
+            CpuContext::Arm64(context) => context.sp,
+        }
+    }
+
+    fn write(&self, buffer: &mut Buffer) -> Location {
+        buffer.align(16);
+        let rva = buffer.rva();
+
+        match self {
+            CpuContext::Amd64(context) => {
+                let flags = if context.fxsave.is_empty() {
+                    format::CONTEXT_AMD64_FULL & !0x8
+                } else {
+                    format::CONTEXT_AMD64_FULL
+                };
+
+                buffer.zeros(6 * 8); // p1_home to p6_home
+                buffer.u32(flags | format::CONTEXT_AMD64_SEGMENTS);
+                buffer.u32(match context.fxsave.get(24..28) {
+                    Some(mxcsr) => u32::from_le_bytes(mxcsr.try_into().unwrap()),
+                    None => 0,
+                });
+                for segment in context.segments {
+                    buffer.u16(segment);
+                }
+                buffer.u32(context.eflags);
+                buffer.zeros(6 * 8); // dr0 to dr7
+                for register in context.gpr {
+                    buffer.u64(register);
+                }
+                buffer.u64(context.rip);
+                if context.fxsave.is_empty() {
+                    buffer.zeros(512);
+                } else {
+                    buffer.bytes(&context.fxsave);
+                }
+                buffer.zeros(26 * 16 + 6 * 8); // vector registers and debug control
+            }
+            CpuContext::Arm64(context) => {
+                let flags = if context.v.is_empty() {
+                    format::CONTEXT_ARM64_FULL & !0x4
+                } else {
+                    format::CONTEXT_ARM64_FULL
+                };
+
+                buffer.u32(flags);
+                buffer.u32(context.pstate);
+                for register in context.x {
+                    buffer.u64(register);
+                }
+                buffer.u64(context.sp);
+                buffer.u64(context.pc);
+                for index in 0..32 {
+                    buffer.u128(context.v.get(index).copied().unwrap_or_default());
+                }
+                buffer.u32(context.fpcr);
+                buffer.u32(context.fpsr);
+                buffer.zeros(8 * 4 + 8 * 8 + 2 * 4 + 2 * 8); // debug registers
+            }
+        }
+
+        debug_assert_eq!(
+            (buffer.rva() - rva) as usize,
+            match self {
+                CpuContext::Amd64(_) => format::CONTEXT_AMD64_SIZE,
+                CpuContext::Arm64(_) => format::CONTEXT_ARM64_SIZE,
+            }
+        );
+
+        Location {
+            size: buffer.rva() - rva,
+            rva,
+        }
+    }
+}
+
+/// A region of captured process memory.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MemoryRegion {
+    /// The address of the first byte in the process' address space.
+    pub base: u64,
+    /// The captured memory contents.
+    pub data: Vec<u8>,
+}
+
+/// A thread of the process.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ThreadInfo {
+    /// The thread identifier (`tid`).
+    pub thread_id: u32,
+    /// The register values at the time of the crash.
+    pub context: CpuContext,
+    /// Memory of the thread's stack, starting slightly below the stack pointer.
+    pub stack: Option<MemoryRegion>,
+}
+
+/// A module loaded into the process.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ModuleInfo {
+    /// The address at which the module is loaded.
+    pub base: u64,
+    /// The size of the module's mapping in memory.
+    pub size: u64,
+    /// The path of the module on the file system.
+    pub name: String,
+    /// The GNU build identifier of the module, if it could be determined.
+    pub build_id: Option<Vec<u8>>,
+}
+
+/// Information on the signal that caused the crash.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ExceptionInfo {
+    /// The thread that received the signal.
+    pub thread_id: u32,
+    /// The signal number, for example `SIGSEGV`.
+    pub signal: u32,
+    /// The signal code (`si_code`).
+    pub code: u32,
+    /// The faulting address for memory access violations.
+    pub address: u64,
+}
+
+/// Information on the operating system and CPU.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SystemInfo {
+    /// The CPU family of the process.
+    pub cpu_family: CpuFamily,
+    /// The number of CPUs.
+    pub cpu_count: u8,
+    /// The operating system version in the form `Linux <release> <version> <machine>`.
+    pub os_version: String,
+}
+
+impl Default for SystemInfo {
+    fn default() -> Self {
+        SystemInfo {
+            cpu_family: CpuFamily::Unknown,
+            cpu_count: 1,
+            os_version: String::new(),
+        }
+    }
+}
+
+/// Linux-specific minidump streams, as written by Breakpad's Linux client.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LinuxStream {
+    /// Contents of `/proc/cpuinfo`.
+    CpuInfo,
+    /// Contents of `/proc/<pid>/status`.
+    ProcStatus,
+    /// Contents of `/etc/lsb-release`.
+    LsbRelease,
+    /// Contents of `/proc/<pid>/cmdline`.
+    CmdLine,
+    /// Contents of `/proc/<pid>/environ`.
+    Environ,
+    /// Contents of `/proc/<pid>/auxv`.
+    Auxv,
+    /// Contents of `/proc/<pid>/maps`.
+    Maps,
+}
+
+impl LinuxStream {
+    fn stream_type(self) -> u32 {
+        match self {
+            LinuxStream::CpuInfo => 0x4767_0003,
+            LinuxStream::ProcStatus => 0x4767_0004,
+            LinuxStream::LsbRelease => 0x4767_0005,
+            LinuxStream::CmdLine => 0x4767_0006,
+            LinuxStream::Environ => 0x4767_0007,
+            LinuxStream::Auxv => 0x4767_0008,
+            LinuxStream::Maps => 0x4767_0009,
+        }
+    }
+}
+
+/// A mapping of a file into the process' address space.
+#[derive(Clone, Debug)]
+struct Mapping {
+    start: u64,
+    end: u64,
+    offset: u64,
+    path: String,
+}
+
+/// Groups contiguous file mappings into modules.
+///
+/// Every module starts at a mapping with file offset zero. Subsequent mappings of the same file
+/// extend the module, even if there are anonymous gaps in between (e.g. `.bss`).
+fn modules_from_mappings(mappings: &[Mapping]) -> Vec<ModuleInfo> {
+    let mut modules: Vec<ModuleInfo> = Vec::new();
+
+    for mapping in mappings {
+        if mapping.path.is_empty() || mapping.path.starts_with('[') {
+            continue;
+        }
+
+        match modules.last_mut() {
+            Some(module) if module.name == mapping.path && mapping.offset != 0 => {
+                module.size = mapping.end - module.base;
+            }
+            _ if mapping.offset == 0 => modules.push(ModuleInfo {
+                base: mapping.start,
+                size: mapping.end - mapping.start,
+                name: mapping.path.clone(),
+                build_id: None,
+            }),
+            _ => {}
+        }
+    }
+
+    modules
+}
+
+/// Access to the memory of a process.
+trait ReadMemory {
+    /// Reads `len` bytes at the given address, or returns `None` if it is not available.
+    fn read_memory(&self, address: u64, len: usize) -> Option<Vec<u8>>;
+
+    /// Returns the end address of the mapping containing `address`.
+    fn mapping_end(&self, address: u64) -> Option<u64>;
+
+    /// Captures the stack of a thread given its stack pointer.
+    fn read_stack(&self, stack_pointer: u64) -> Option<MemoryRegion> {
+        let start = stack_pointer.saturating_sub(STACK_RED_ZONE);
+        let end = self
+            .mapping_end(stack_pointer)?
+            .min(stack_pointer.saturating_add(MAX_STACK_SIZE));
+
+        // The red zone might not be mapped if the stack is exhausted.
+        let base = if self.mapping_end(start) == self.mapping_end(stack_pointer) {
+            start
+        } else {
+            stack_pointer
+        };
+
+        let data = self.read_memory(base, (end - base) as usize)?;
+        Some(MemoryRegion { base, data })
+    }
+
+    /// Reads the GNU build identifier from the ELF headers mapped at `base`.
+    fn read_build_id(&self, base: u64) -> Option<Vec<u8>> {
+        use goblin::container::Ctx;
+        use goblin::elf::program_header::{ProgramHeader, PT_LOAD, PT_NOTE};
+        use goblin::elf::Elf;
+
+        let header_bytes = self.read_memory(base, 64)?;
+        let header = Elf::parse_header(&header_bytes).ok()?;
+        let container = header.container().ok()?;
+        let ctx = Ctx::new(container, header.endianness().ok()?);
+
+        let phdr_size = header.e_phnum as usize * header.e_phentsize as usize;
+        let phdr_bytes = self.read_memory(base + header.e_phoff, phdr_size)?;
+        let phdrs = ProgramHeader::parse(&phdr_bytes, 0, header.e_phnum as usize, ctx).ok()?;
+
+        let first_load = phdrs.iter().find(|phdr| phdr.p_type == PT_LOAD)?;
+        let bias = base.wrapping_sub(first_load.p_vaddr & !0xfff);
+
+        for phdr in phdrs.iter().filter(|phdr| phdr.p_type == PT_NOTE) {
+            let notes =
+                self.read_memory(bias.wrapping_add(phdr.p_vaddr), phdr.p_filesz as usize)?;
+            let little_endian = ctx.le.is_little();
+            let build_id = core::iter_notes(&notes, little_endian)
+                .find(|note| note.name == b"GNU" && note.kind == core::NT_GNU_BUILD_ID);
+
+            if let Some(note) = build_id {
+                return Some(note.desc.to_vec());
+            }
+        }
+
+        None
+    }
+}
+
+/// A writer for minidump files.
+///
+/// See the [module documentation](index.html) for more information.
+#[derive(Clone, Debug, Default)]
+pub struct MinidumpWriter {
+    system_info: SystemInfo,
+    threads: Vec<ThreadInfo>,
+    modules: Vec<ModuleInfo>,
+    memory: Vec<MemoryRegion>,
+    exception: Option<ExceptionInfo>,
+    linux_streams: Vec<(LinuxStream, Vec<u8>)>,
+    timestamp: u32,
+}
+
+impl MinidumpWriter {
+    /// Creates an empty minidump writer.
+    pub fn new(system_info: SystemInfo) -> Self {
+        MinidumpWriter {
+            system_info,
+            ..Default::default()
+        }
+    }
+
+    /// Creates a minidump writer from an ELF core file.
+    ///
+    /// The core file must contain `NT_PRSTATUS` notes for its threads. If it also contains an
+    /// `NT_FILE` note, loaded modules are reconstructed from it, including their build ids if the
+    /// ELF headers were dumped. The first thread is assumed to be the crashing thread.
+    pub fn from_core(data: &[u8]) -> Result<Self, WriterError> {
+        core::read_core(data)
+    }
+
+    /// Creates a minidump writer by attaching to a live process.
+    ///
+    /// All threads of the process are stopped using `ptrace` while their registers and stacks are
+    /// captured, and are resumed before this function returns. The caller needs permission to
+    /// trace the process, which usually requires being its parent or having `CAP_SYS_PTRACE`.
+    ///
+    /// Process metadata from `/proc/<pid>` is attached automatically.
+    #[cfg(target_os = "linux")]
+    pub fn from_process(pid: u32) -> Result<Self, WriterError> {
+        linux::read_process(pid)
+    }
+
+    /// Returns the system information.
+    pub fn system_info(&self) -> &SystemInfo {
+        &self.system_info
+    }
+
+    /// Returns the threads added to this writer.
+    pub fn threads(&self) -> &[ThreadInfo] {
+        &self.threads
+    }
+
+    /// Returns the modules added to this writer.
+    pub fn modules(&self) -> &[ModuleInfo] {
+        &self.modules
+    }
+
+    /// Returns the exception information, if any.
+    pub fn exception(&self) -> Option<&ExceptionInfo> {
+        self.exception.as_ref()
+    }
+
+    /// Adds a thread.
+    pub fn add_thread(&mut self, thread: ThreadInfo) {
+        self.threads.push(thread);
+    }
+
+    /// Adds a loaded module.
+    pub fn add_module(&mut self, module: ModuleInfo) {
+        self.modules.push(module);
+    }
+
+    /// Adds a region of memory in addition to the thread stacks.
+    pub fn add_memory(&mut self, region: MemoryRegion) {
+        self.memory.push(region);
+    }
+
+    /// Sets information on the signal that caused the crash.
+    pub fn set_exception(&mut self, exception: ExceptionInfo) {
+        self.exception = Some(exception);
+    }
+
+    /// Sets the time at which the crash occurred as UNIX timestamp.
+    pub fn set_timestamp(&mut self, timestamp: u32) {
+        self.timestamp = timestamp;
+    }
+
+    /// Adds a Linux-specific stream with raw contents.
+    ///
+    /// If a stream of the same kind has been added before, it is replaced.
+    pub fn add_linux_stream(&mut self, stream: LinuxStream, data: Vec<u8>) {
+        self.linux_streams
+            .retain(|(existing, _)| *existing != stream);
+        self.linux_streams.push((stream, data));
+    }
+
+    /// Attaches process metadata from a directory laid out like `/proc/<pid>`.
+    ///
+    /// The files `maps`, `cmdline`, `environ`, `auxv` and `status` are read if they exist. Missing
+    /// files are skipped. If there is a `maps` file and no modules have been added yet, modules are
+    /// reconstructed from the file mappings.
+    pub fn add_procfs<P: AsRef<Path>>(&mut self, path: P) -> Result<(), WriterError> {
+        let path = path.as_ref();
+        let files = [
+            ("maps", LinuxStream::Maps),
+            ("cmdline", LinuxStream::CmdLine),
+            ("environ", LinuxStream::Environ),
+            ("auxv", LinuxStream::Auxv),
+            ("status", LinuxStream::ProcStatus),
+        ];
+
+        for (name, stream) in files {
+            match std::fs::read(path.join(name)) {
+                Ok(data) => self.add_linux_stream(stream, data),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(WriterError::new(WriterErrorKind::ReadFailed, e)),
+            }
+        }
+
+        if self.modules.is_empty() {
+            if let Some((_, maps)) = self
+                .linux_streams
+                .iter()
+                .find(|(s, _)| *s == LinuxStream::Maps)
+            {
+                let mappings = parse_maps(&String::from_utf8_lossy(maps));
+                self.modules = modules_from_mappings(&mappings);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Serializes the minidump into the given writer.
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<(), WriterError> {
+        writer
+            .write_all(&self.to_vec())
+            .map_err(|e| WriterError::new(WriterErrorKind::WriteFailed, e))
+    }
+
+    /// Serializes the minidump into a buffer.
+    pub fn to_vec(&self) -> Vec<u8> {
+        let stream_count = 4 + self.exception.is_some() as usize + self.linux_streams.len();
+
+        let mut buffer = Buffer::new();
+        buffer.u32(format::MINIDUMP_SIGNATURE);
+        buffer.u32(format::MINIDUMP_VERSION);
+        buffer.u32(stream_count as u32);
+        buffer.u32(format::HEADER_SIZE as u32);
+        buffer.u32(0); // checksum
+        buffer.u32(self.timestamp);
+        buffer.u64(0); // flags
+
+        let directory = buffer.reserve(stream_count * format::DIRECTORY_SIZE);
+        let mut streams = Vec::with_capacity(stream_count);
+
+        // Memory contents are shared between the thread list and the memory list.
+        let mut memory = Vec::new();
+        let mut thread_memory = Vec::with_capacity(self.threads.len());
+        for thread in &self.threads {
+            let location = thread.stack.as_ref().map(|stack| {
+                let location = buffer.blob(&stack.data);
+                memory.push((stack.base, location));
+                (stack.base, location)
+            });
+            thread_memory.push(location);
+        }
+        for region in &self.memory {
+            let location = buffer.blob(&region.data);
+            memory.push((region.base, location));
+        }
+
+        let contexts: Vec<_> = self
+            .threads
+            .iter()
+            .map(|thread| thread.context.write(&mut buffer))
+            .collect();
+
+        // Thread list
+        buffer.align(8);
+        let rva = buffer.rva();
+        buffer.u32(self.threads.len() as u32);
+        for ((thread, stack), context) in self.threads.iter().zip(&thread_memory).zip(&contexts) {
+            let (stack_base, stack_location) = stack.unwrap_or_default();
+            buffer.u32(thread.thread_id);
+            buffer.u32(0); // suspend_count
+            buffer.u32(0); // priority_class
+            buffer.u32(0); // priority
+            buffer.u64(0); // teb
+            buffer.u64(stack_base);
+            buffer.location(stack_location);
+            buffer.location(*context);
+        }
+        debug_assert_eq!(
+            (buffer.rva() - rva) as usize,
+            4 + self.threads.len() * format::THREAD_SIZE
+        );
+        streams.push((format::STREAM_THREAD_LIST, rva, buffer.rva()));
+
+        // Module list
+        let mut module_data = Vec::with_capacity(self.modules.len());
+        for module in &self.modules {
+            let name_rva = buffer.string(&module.name);
+            let cv_record = module.build_id.as_ref().map(|build_id| {
+                let mut record = format::CV_SIGNATURE_ELF.to_le_bytes().to_vec();
+                record.extend_from_slice(build_id);
+                buffer.blob(&record)
+            });
+            module_data.push((name_rva, cv_record.unwrap_or_default()));
+        }
+
+        buffer.align(8);
+        let rva = buffer.rva();
+        buffer.u32(self.modules.len() as u32);
+        for (module, (name_rva, cv_record)) in self.modules.iter().zip(module_data) {
+            buffer.u64(module.base);
+            buffer.u32(module.size as u32);
+            buffer.u32(0); // checksum
+            buffer.u32(0); // time_date_stamp
+            buffer.u32(name_rva);
+            buffer.zeros(13 * 4); // version_info
+            buffer.location(cv_record);
+            buffer.location(Location::default()); // misc_record
+            buffer.u64(0); // reserved0
+            buffer.u64(0); // reserved1
+        }
+        debug_assert_eq!(
+            (buffer.rva() - rva) as usize,
+            4 + self.modules.len() * format::MODULE_SIZE
+        );
+        streams.push((format::STREAM_MODULE_LIST, rva, buffer.rva()));
+
+        // Memory list
+        buffer.align(8);
+        let rva = buffer.rva();
+        buffer.u32(memory.len() as u32);
+        for (base, location) in &memory {
+            buffer.u64(*base);
+            buffer.location(*location);
+        }
+        streams.push((format::STREAM_MEMORY_LIST, rva, buffer.rva()));
+
+        // System info
+        let csd_version_rva = buffer.string(&self.system_info.os_version);
+        buffer.align(8);
+        let rva = buffer.rva();
+        buffer.u16(match self.system_info.cpu_family {
+            CpuFamily::Arm64 => format::CPU_ARCHITECTURE_ARM64,
+            _ => format::CPU_ARCHITECTURE_AMD64,
+        });
+        buffer.u16(0); // processor_level
+        buffer.u16(0); // processor_revision
+        buffer.u8(self.system_info.cpu_count);
+        buffer.u8(0); // product_type
+        let (major, minor, build) = parse_kernel_version(&self.system_info.os_version);
+        buffer.u32(major);
+        buffer.u32(minor);
+        buffer.u32(build);
+        buffer.u32(format::PLATFORM_LINUX);
+        buffer.u32(csd_version_rva);
+        buffer.u16(0); // suite_mask
+        buffer.u16(0); // reserved2
+        buffer.zeros(24); // cpu
+        streams.push((format::STREAM_SYSTEM_INFO, rva, buffer.rva()));
+
+        // Exception
+        if let Some(exception) = self.exception {
+            let context = self
+                .threads
+                .iter()
+                .position(|thread| thread.thread_id == exception.thread_id)
+                .map(|index| contexts[index])
+                .unwrap_or_default();
+
+            buffer.align(8);
+            let rva = buffer.rva();
+            buffer.u32(exception.thread_id);
+            buffer.u32(0); // alignment
+            buffer.u32(exception.signal);
+            buffer.u32(exception.code);
+            buffer.u64(0); // exception_record
+            buffer.u64(exception.address);
+            buffer.u32(0); // number_parameters
+            buffer.u32(0); // alignment
+            buffer.zeros(15 * 8); // exception_information
+            buffer.location(context);
+            streams.push((format::STREAM_EXCEPTION, rva, buffer.rva()));
+        }
+
+        for (stream, data) in &self.linux_streams {
+            let location = buffer.blob(data);
+            streams.push((
+                stream.stream_type(),
+                location.rva,
+                location.rva + location.size,
+            ));
+        }
+
+        for (index, (stream_type, start, end)) in streams.into_iter().enumerate() {
+            let entry = directory + (index * format::DIRECTORY_SIZE) as u32;
+            buffer.patch_u32(entry, stream_type);
+            buffer.patch_location(
+                entry + 4,
+                Location {
+                    size: end - start,
+                    rva: start,
+                },
+            );
+        }
+
+        buffer.into_inner()
+    }
+}
+
+/// Parses the contents of a `/proc/<pid>/maps` file into file mappings.
+fn parse_maps(maps: &str) -> Vec<Mapping> {
+    maps.lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(6, ' ');
+            let (start, end) = parts.next()?.split_once('-')?;
+            let _permissions = parts.next()?;
+            let offset = parts.next()?;
+            let _device = parts.next()?;
+            let _inode = parts.next()?;
+            let path = parts.next().unwrap_or_default().trim();
+
+            Some(Mapping {
+                start: u64::from_str_radix(start, 16).ok()?,
+                end: u64::from_str_radix(end, 16).ok()?,
+                offset: u64::from_str_radix(offset, 16).ok()?,
+                path: path.to_owned(),
+            })
+        })
+        .collect()
+}
+
+/// Extracts the numeric kernel version from an OS version string like `Linux 5.4.0-42 ...`.
+fn parse_kernel_version(os_version: &str) -> (u32, u32, u32) {
+    let release = os_version.split(' ').nth(1).unwrap_or_default();
+    let mut numbers = release
+        .split(|c: char| !c.is_ascii_digit())
+        .map(|part| part.parse().unwrap_or(0));
+
+    (
+        numbers.next().unwrap_or(0),
+        numbers.next().unwrap_or(0),
+        numbers.next().unwrap_or(0),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_maps() {
+        let maps = "\
+55d0c0a00000-55d0c0a02000 r--p 00000000 fd:01 1234 /usr/bin/cat
+55d0c0a02000-55d0c0a07000 r-xp 00002000 fd:01 1234 /usr/bin/cat
+55d0c0a0a000-55d0c0a0b000 rw-p 00000000 00:00 0
+7ffd5a4c1000-7ffd5a4e2000 rw-p 00000000 00:00 0                          [stack]
+7f0000000000-7f0000001000 r--p 00000000 fd:01 99 /lib/with space.so
+";
+        let mappings = parse_maps(maps);
+        assert_eq!(mappings.len(), 5);
+        assert_eq!(mappings[1].offset, 0x2000);
+        assert_eq!(mappings[4].path, "/lib/with space.so");
+
+        let modules = modules_from_mappings(&mappings);
+        assert_eq!(modules.len(), 2);
+        assert_eq!(modules[0].name, "/usr/bin/cat");
+        assert_eq!(modules[0].base, 0x55d0_c0a0_0000);
+        assert_eq!(modules[0].size, 0x7000);
+    }
+
+    #[test]
+    fn test_parse_kernel_version() {
+        let version = "Linux 5.15.0-91-generic #101-Ubuntu SMP x86_64";
+        assert_eq!(parse_kernel_version(version), (5, 15, 0));
+        assert_eq!(parse_kernel_version(""), (0, 0, 0));
+    }
+}
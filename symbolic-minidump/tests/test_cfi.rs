@@ -1,8 +1,12 @@
 use std::str;
 
 use symbolic_common::ByteView;
+use symbolic_debuginfo::breakpad::{BreakpadCfiTable, BreakpadStackRecords};
 use symbolic_debuginfo::Object;
-use symbolic_minidump::cfi::{AsciiCfiWriter, CfiCache};
+use symbolic_minidump::cfi::{
+    AsciiCfiWriter, CfiCache, CfiErrorKind, UnwindChecker, UnwindIssue, UnwindIssueKind,
+    CFICACHE_LATEST_VERSION, CFICACHE_MAGIC,
+};
 use symbolic_testutils::fixture;
 
 use similar_asserts::assert_eq;
@@ -106,3 +110,190 @@ fn cfi_from_pe_windows() -> Result<(), Error> {
 
     Ok(())
 }
+
+#[test]
+fn cfi_cache_binary_roundtrip() -> Result<(), Error> {
+    let buffer = ByteView::open(fixture("linux/crash"))?;
+    let object = Object::parse(&buffer)?;
+
+    let mut written = Vec::new();
+    CfiCache::from_object(&object)?.write_to(&mut written)?;
+
+    let cache = CfiCache::from_bytes(ByteView::from_vec(written))?;
+    assert_eq!(cache.version(), CFICACHE_LATEST_VERSION);
+    assert!(cache.is_latest());
+
+    let ascii: Vec<u8> = AsciiCfiWriter::transform(&object)?;
+    let table = BreakpadCfiTable::from_records(BreakpadStackRecords::new(&ascii))?;
+    assert!(!table.is_empty());
+
+    for entry in &table {
+        assert_eq!(cache.lookup(entry.range.start).as_ref(), Some(entry));
+        assert_eq!(
+            cache.lookup(entry.range.end - 1).as_ref(),
+            table.lookup(entry.range.end - 1)
+        );
+    }
+    assert_eq!(cache.lookup(0), None);
+
+    // The converted ASCII must describe the same unwind rules.
+    let converted = cache.to_breakpad_cfi();
+    let converted_table = BreakpadCfiTable::from_records(BreakpadStackRecords::new(&converted))?;
+    assert!(converted_table.iter().eq(table.iter()));
+
+    Ok(())
+}
+
+#[test]
+fn cfi_cache_binary_windows() -> Result<(), Error> {
+    let buffer = ByteView::open(fixture("windows/crash.pdb"))?;
+    let object = Object::parse(&buffer)?;
+
+    let ascii: Vec<u8> = AsciiCfiWriter::transform(&object)?;
+    let table = BreakpadCfiTable::from_records(BreakpadStackRecords::new(&ascii))?;
+    let cache = CfiCache::from_object(&object)?;
+    assert!(!table.is_empty());
+
+    for entry in &table {
+        assert_eq!(
+            cache.lookup(entry.range.start).as_ref(),
+            table.lookup(entry.range.start)
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn cfi_cache_text_versions() -> Result<(), Error> {
+    let mut buffer = Vec::new();
+    buffer.extend_from_slice(&CFICACHE_MAGIC.to_ne_bytes());
+    buffer.extend_from_slice(&2u32.to_ne_bytes());
+    buffer.extend_from_slice(b"STACK CFI INIT 1000 10 .cfa: $rsp 8 + .ra: .cfa -8 + ^\n");
+
+    let cache = CfiCache::from_bytes(ByteView::from_vec(buffer))?;
+    assert_eq!(cache.version(), 2);
+    assert!(!cache.is_latest());
+    assert_eq!(cache.lookup(0x1000), None);
+    assert!(cache.to_breakpad_cfi().starts_with(b"STACK CFI INIT 1000"));
+
+    Ok(())
+}
+
+#[test]
+fn cfi_cache_bad_format() {
+    let mut buffer = Vec::new();
+    buffer.extend_from_slice(&CFICACHE_MAGIC.to_ne_bytes());
+    buffer.extend_from_slice(&CFICACHE_LATEST_VERSION.to_ne_bytes());
+    buffer.extend_from_slice(&[0xff; 12]);
+
+    let result = CfiCache::from_bytes(ByteView::from_vec(buffer));
+    assert_eq!(
+        result.err().map(|e| e.kind()),
+        Some(CfiErrorKind::BadFormat)
+    );
+}
+
+#[test]
+fn cfi_cache_bad_rule_count() -> Result<(), Error> {
+    let mut buffer = Vec::new();
+    buffer.extend_from_slice(&CFICACHE_MAGIC.to_ne_bytes());
+    buffer.extend_from_slice(&CFICACHE_LATEST_VERSION.to_ne_bytes());
+
+    // Header: a single CFI entry without any rules or strings.
+    for field in &[1u32, 0, 0, 0, 0, 0] {
+        buffer.extend_from_slice(&field.to_ne_bytes());
+    }
+
+    // The entry claims a huge number of rule references.
+    buffer.extend_from_slice(&0x1000u64.to_ne_bytes());
+    buffer.extend_from_slice(&0x10u64.to_ne_bytes());
+    buffer.extend_from_slice(&[0, 0, 0, 0]);
+    buffer.extend_from_slice(&0u32.to_ne_bytes());
+    buffer.extend_from_slice(&u32::MAX.to_ne_bytes());
+    buffer.extend_from_slice(&0u32.to_ne_bytes());
+
+    let cache = CfiCache::from_bytes(ByteView::from_vec(buffer))?;
+    assert_eq!(cache.lookup(0x1000), None);
+
+    Ok(())
+}
+
+#[test]
+fn unwind_checker() {
+    let mut checker = UnwindChecker::new();
+    checker.add_function(Some("a"), 0x10..0x30);
+    checker.add_function(Some("b"), 0x30..0x50);
+    checker.add_function(None, 0x60..0x70);
+    checker.add_function(Some("empty"), 0x70..0x70);
+
+    checker.add_unwind_range(0x30..0x48);
+    checker.add_unwind_range(0x10..0x20);
+    checker.add_unwind_range(0x18..0x28);
+    checker.add_unwind_range(0x80..0x90);
+
+    let issue = |kind, range, function: Option<&str>| UnwindIssue {
+        kind,
+        range,
+        function: function.map(str::to_owned),
+    };
+
+    assert_eq!(checker.function_count(), 3);
+    assert_eq!(checker.unwind_range_count(), 4);
+    assert_eq!(
+        checker.check(),
+        [
+            issue(UnwindIssueKind::Overlapping, 0x18..0x20, Some("a")),
+            issue(UnwindIssueKind::Missing, 0x28..0x30, Some("a")),
+            issue(UnwindIssueKind::Missing, 0x48..0x50, Some("b")),
+            issue(UnwindIssueKind::Missing, 0x60..0x70, None),
+        ]
+    );
+}
+
+#[test]
+fn unwind_checker_elf() -> Result<(), Error> {
+    let buffer = ByteView::open(fixture("linux/crash"))?;
+    let object = Object::parse(&buffer)?;
+    let issues = UnwindChecker::from_object(&object)?.check();
+
+    // The CRT startup code is written in assembly without CFI directives.
+    let missing: Vec<_> = issues
+        .iter()
+        .map(|issue| (issue.kind, issue.function.as_deref()))
+        .collect();
+    assert_eq!(
+        missing,
+        [
+            (UnwindIssueKind::Missing, Some("_init")),
+            (UnwindIssueKind::Missing, Some("deregister_tm_clones")),
+            (UnwindIssueKind::Missing, Some("register_tm_clones")),
+            (UnwindIssueKind::Missing, Some("__do_global_dtors_aux")),
+            (UnwindIssueKind::Missing, Some("frame_dummy")),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn unwind_checker_pdb() -> Result<(), Error> {
+    let buffer = ByteView::open(fixture("windows/crash.pdb"))?;
+    let object = Object::parse(&buffer)?;
+    let issues = UnwindChecker::from_object(&object)?.check();
+
+    // Nested `STACK WIN` records are not reported as overlapping.
+    assert!(issues
+        .iter()
+        .all(|issue| issue.kind == UnwindIssueKind::Missing));
+
+    let seh_prolog = issues
+        .iter()
+        .find(|issue| issue.function.as_deref() == Some("__SEH_prolog4"));
+    assert_eq!(
+        seh_prolog.map(|issue| issue.range.clone()),
+        Some(0x34e0..0x3526)
+    );
+
+    Ok(())
+}
@@ -0,0 +1,273 @@
+use symbolic_common::CpuFamily;
+use symbolic_minidump::minidump::{
+    Minidump, MinidumpErrorKind, MinidumpException, MinidumpModule, MinidumpStream,
+    MinidumpStreamType,
+};
+use symbolic_testutils::fixture;
+
+type Error = Box<dyn std::error::Error>;
+
+#[test]
+fn test_streams_linux() -> Result<(), Error> {
+    let data = std::fs::read(fixture("linux/mini.dmp"))?;
+    let minidump = Minidump::parse(&data)?;
+
+    assert_eq!(minidump.version(), 0xa793);
+    assert_eq!(minidump.timestamp(), 1522061032);
+    assert!(!minidump.is_big_endian());
+
+    let streams: Vec<_> = minidump.streams().collect();
+    assert_eq!(streams.len(), 13);
+    assert_eq!(
+        streams[0],
+        MinidumpStream {
+            stream_type: MinidumpStreamType::ThreadList,
+            size: 52,
+            offset: 192,
+        }
+    );
+    assert_eq!(streams[12].stream_type, MinidumpStreamType::LinuxDsoDebug);
+
+    let maps = minidump
+        .stream_data(MinidumpStreamType::LinuxMaps)?
+        .unwrap();
+    assert_eq!(maps.len(), 3382);
+    assert!(minidump
+        .stream_data(MinidumpStreamType::CrashpadInfo)?
+        .is_none());
+
+    Ok(())
+}
+
+#[test]
+fn test_system_info_linux() -> Result<(), Error> {
+    let data = std::fs::read(fixture("linux/mini.dmp"))?;
+    let minidump = Minidump::parse(&data)?;
+
+    let info = minidump.system_info()?.unwrap();
+    assert_eq!(info.os_name(), "Linux");
+    assert_eq!(info.cpu_family(), CpuFamily::Amd64);
+    assert_eq!(info.cpu_count, 4);
+    assert_eq!(info.cpu_vendor.as_deref(), Some("GenuineIntel"));
+    assert_eq!(
+        info.csd_version.as_deref(),
+        Some("Linux 4.9.60-linuxkit-aufs #1 SMP Mon Nov 6 16:00:12 UTC 2017 x86_64")
+    );
+
+    assert_eq!(minidump.misc_info()?, None);
+    assert_eq!(
+        minidump.exception()?,
+        Some(MinidumpException {
+            thread_id: 1304,
+            code: 11, // SIGSEGV
+            flags: 0,
+            address: 69,
+        })
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_system_info_macos() -> Result<(), Error> {
+    let data = std::fs::read(fixture("macos/mini.dmp"))?;
+    let minidump = Minidump::parse(&data)?;
+
+    let info = minidump.system_info()?.unwrap();
+    assert_eq!(info.os_name(), "Mac OS X");
+    assert_eq!(info.os_version(), "10.12.6 16G29");
+    assert_eq!(info.cpu_family(), CpuFamily::Amd64);
+    assert_eq!(info.cpu_count, 2);
+
+    let misc = minidump.misc_info()?.unwrap();
+    assert_eq!(misc.process_id, Some(2984));
+    assert_eq!(misc.process_create_time, Some(1521713398));
+    assert_eq!(misc.processor_max_mhz, Some(2574));
+
+    Ok(())
+}
+
+#[test]
+fn test_system_info_windows() -> Result<(), Error> {
+    let data = std::fs::read(fixture("windows/mini.dmp"))?;
+    let minidump = Minidump::parse(&data)?;
+
+    let info = minidump.system_info()?.unwrap();
+    assert_eq!(info.os_name(), "Windows NT");
+    assert_eq!(info.os_version(), "10.0.14393");
+    assert_eq!(info.cpu_family(), CpuFamily::Intel32);
+
+    let misc = minidump.misc_info()?.unwrap();
+    assert_eq!(misc.process_id, Some(1928));
+
+    let exception = minidump.exception()?.unwrap();
+    assert_eq!(exception.code, 0xc000_0005); // EXCEPTION_ACCESS_VIOLATION
+
+    Ok(())
+}
+
+#[test]
+fn test_system_info_riscv64() -> Result<(), Error> {
+    let data = std::fs::read(fixture("linux/riscv64/mini.dmp"))?;
+    let minidump = Minidump::parse(&data)?;
+
+    let info = minidump.system_info()?.unwrap();
+    assert_eq!(info.os_name(), "Linux");
+    assert_eq!(info.cpu_family(), CpuFamily::Riscv64);
+    assert_eq!(
+        info.csd_version.as_deref(),
+        Some("Linux 6.1.0 #1 SMP riscv64")
+    );
+
+    assert_eq!(minidump.exception()?, None);
+    assert_eq!(
+        minidump.read_memory(0x3f_ffff_f070, 16)?,
+        Some(&[0, 0, 0, 0, 0, 0, 0, 0, 0, 0x11, 0, 0, 1, 0, 0, 0][..])
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_invalid_minidump() {
+    let result = Minidump::parse(b"MDMP");
+    assert_eq!(
+        result.err().map(|e| e.kind()),
+        Some(MinidumpErrorKind::InvalidHeader)
+    );
+
+    let mut data = std::fs::read(fixture("linux/mini.dmp")).unwrap();
+    data.truncate(64);
+    let result = Minidump::parse(&data);
+    assert_eq!(
+        result.err().map(|e| e.kind()),
+        Some(MinidumpErrorKind::InvalidDirectory)
+    );
+}
+
+#[test]
+fn test_memory_regions_linux() -> Result<(), Error> {
+    let data = std::fs::read(fixture("linux/mini.dmp"))?;
+    let minidump = Minidump::parse(&data)?;
+
+    let regions: Vec<_> = minidump.memory_regions()?.collect();
+    assert_eq!(regions.len(), 2);
+    assert_eq!(regions[0].base_address, 0x7fff_5ae4_a000);
+    assert_eq!(regions[0].size(), 12288);
+
+    let memory = minidump.read_memory(0x7fff_5ae4_a008, 8)?.unwrap();
+    assert_eq!(memory, &[0x20, 0, 0, 0, 0, 0, 0, 0]);
+
+    // The contents are borrowed from the minidump rather than copied.
+    let range = data.as_ptr_range();
+    assert!(range.contains(&memory.as_ptr()));
+
+    assert!(minidump.read_memory(0x7fff_5ae4_cffc, 8)?.is_none());
+    assert!(minidump.read_memory(0x1000, 1)?.is_none());
+
+    Ok(())
+}
+
+#[test]
+fn test_memory_regions_64() -> Result<(), Error> {
+    let mut data = Vec::new();
+
+    // Header with a single stream, followed by the stream directory.
+    for value in [0x504d_444d, 0xa793, 1, 32, 0, 0, 0, 0] {
+        data.extend_from_slice(&u32::to_le_bytes(value));
+    }
+    for value in [9, 48, 44] {
+        data.extend_from_slice(&u32::to_le_bytes(value));
+    }
+
+    // Memory64List with two regions stored back to back at offset 92.
+    for value in [2, 92, 0x1000, 4, 0x2000, 2] {
+        data.extend_from_slice(&u64::to_le_bytes(value));
+    }
+    data.extend_from_slice(b"abcdef");
+
+    let minidump = Minidump::parse(&data)?;
+    let regions: Vec<_> = minidump.memory_regions()?.collect();
+    assert_eq!(regions.len(), 2);
+    assert_eq!(regions[0].base_address, 0x1000);
+    assert_eq!(regions[0].data(), b"abcd");
+    assert_eq!(regions[1].base_address, 0x2000);
+    assert_eq!(regions[1].data(), b"ef");
+
+    assert_eq!(minidump.read_memory(0x1002, 2)?, Some(&b"cd"[..]));
+    assert_eq!(minidump.read_memory(0x1002, 3)?, None);
+    assert_eq!(minidump.read_memory(0x2001, 1)?, Some(&b"f"[..]));
+
+    // Regions pointing outside of the minidump end the iteration.
+    data.truncate(data.len() - 1);
+    let minidump = Minidump::parse(&data)?;
+    assert_eq!(minidump.memory_regions()?.count(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_modules_linux() -> Result<(), Error> {
+    let data = std::fs::read(fixture("linux/mini.dmp"))?;
+    let minidump = Minidump::parse(&data)?;
+
+    let modules = minidump.modules()?;
+    assert!(!modules.is_empty());
+    assert_eq!(modules[0].name, "/work/linux/build/crash");
+    assert_eq!(modules[0].base_address, 0x40_0000);
+    assert_eq!(modules[0].size, 106496);
+    assert!(modules[0].contains(0x40_1000));
+    assert!(!modules[0].contains(modules[0].end_address()));
+
+    Ok(())
+}
+
+#[test]
+fn test_with_modules() -> Result<(), Error> {
+    let data = std::fs::read(fixture("linux/mini.dmp"))?;
+    let minidump = Minidump::parse(&data)?;
+
+    let mut modules = minidump.modules()?;
+    modules[0].name = "/opt/game/crash".into();
+    modules[0].base_address = 0x50_0000;
+    modules.push(MinidumpModule::new("packed.so", 0x10_0000, 0x2000));
+
+    let patched = minidump.with_modules(&modules)?;
+    // Only the stream count and directory offset of the header are changed.
+    assert_eq!(patched[16..data.len()], data[16..]);
+
+    let patched = Minidump::parse(&patched)?;
+    assert_eq!(patched.streams().len(), minidump.streams().len());
+    assert_eq!(patched.modules()?, modules);
+
+    // Streams other than the module list are unchanged.
+    assert_eq!(patched.system_info()?, minidump.system_info()?);
+    assert_eq!(
+        patched.stream_data(MinidumpStreamType::LinuxMaps)?,
+        minidump.stream_data(MinidumpStreamType::LinuxMaps)?
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_with_modules_missing_list() -> Result<(), Error> {
+    let mut data = Vec::new();
+
+    // Header without any streams.
+    for value in [0x504d_444d, 0xa793, 0, 32, 0, 0, 0, 0] {
+        data.extend_from_slice(&u32::to_le_bytes(value));
+    }
+
+    let minidump = Minidump::parse(&data)?;
+    assert!(minidump.modules()?.is_empty());
+
+    let modules = vec![MinidumpModule::new("game.exe", 0x40_0000, 0x1000)];
+    let patched = minidump.with_modules(&modules)?;
+
+    let patched = Minidump::parse(&patched)?;
+    assert_eq!(patched.streams().len(), 1);
+    assert_eq!(patched.modules()?, modules);
+
+    Ok(())
+}
@@ -3,9 +3,11 @@
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
-use symbolic_common::ByteView;
+use symbolic_common::{ByteView, CpuFamily};
+use symbolic_debuginfo::Object;
 use symbolic_minidump::cfi::CfiCache;
-use symbolic_minidump::processor::{FrameInfoMap, ProcessState};
+use symbolic_minidump::minidump::Minidump;
+use symbolic_minidump::processor::{CpuContext, FrameInfoMap, FrameTrust, ProcessState};
 use symbolic_testutils::fixture;
 
 type Error = Box<dyn std::error::Error>;
@@ -43,6 +45,196 @@ fn process_minidump_linux_cfi() -> Result<(), Error> {
     Ok(())
 }
 
+#[test]
+fn process_minidump_linux_cfi_binary() -> Result<(), Error> {
+    let buffer = ByteView::open(fixture("linux/mini.dmp"))?;
+    let symbols = ByteView::open(fixture("linux/crash.sym"))?;
+    let object = Object::parse(&symbols)?;
+
+    // Binary caches are looked up directly by the stackwalker and must yield the same stack
+    // traces as the ASCII records.
+    let mut frame_info = FrameInfoMap::new();
+    let cache = CfiCache::from_object(&object)?;
+    assert!(cache.is_latest());
+    frame_info.insert("C0BCC3F19827FE653058404B2831D9E60".parse().unwrap(), cache);
+
+    let state = ProcessState::from_minidump(&buffer, Some(&frame_info))?;
+    insta::assert_debug_snapshot!("process_state_linux_cfi", &state);
+    Ok(())
+}
+
+#[test]
+fn frame_provenance_linux_cfi() -> Result<(), Error> {
+    let buffer = ByteView::open(fixture("linux/mini.dmp"))?;
+    let symbols = ByteView::open(fixture("linux/crash.sym"))?;
+    let object = Object::parse(&symbols)?;
+
+    let mut frame_info = FrameInfoMap::new();
+    frame_info.insert(
+        "C0BCC3F19827FE653058404B2831D9E60".parse().unwrap(),
+        CfiCache::from_object(&object)?,
+    );
+    let state = ProcessState::from_minidump(&buffer, Some(&frame_info))?;
+
+    let thread = &state.threads()[state.requesting_thread() as usize];
+    let first = thread.frame_provenance(0, Some(&frame_info)).unwrap();
+    assert_eq!(first.trust, FrameTrust::Context);
+    assert!(first.module.is_none());
+
+    let mut cfi_frames = 0;
+    for index in 1..thread.frames().len() {
+        let provenance = thread.frame_provenance(index, Some(&frame_info)).unwrap();
+        assert_eq!(provenance.trust, thread.frames()[index].trust());
+
+        if provenance.trust == FrameTrust::CFI {
+            cfi_frames += 1;
+            let cfi = provenance.cfi.expect("missing cfi rules");
+            assert!(cfi.range.contains(&provenance.module_offset.unwrap()));
+        }
+    }
+
+    assert!(cfi_frames > 0);
+    assert!(thread
+        .frame_provenance(thread.frames().len(), None)
+        .is_none());
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "symcache")]
+fn inline_frames_linux() -> Result<(), Error> {
+    use std::io::Cursor;
+
+    use symbolic_minidump::processor::SymCacheMap;
+    use symbolic_symcache::{SymCache, SymCacheWriter};
+
+    let buffer = ByteView::open(fixture("linux/mini.dmp"))?;
+    let debug = ByteView::open(fixture("linux/crash.debug"))?;
+    let object = Object::parse(&debug)?;
+
+    let mut symcache_buf = Vec::new();
+    SymCacheWriter::write_object(&object, Cursor::new(&mut symcache_buf))?;
+
+    let mut symcaches = SymCacheMap::new();
+    symcaches.insert(
+        "C0BCC3F19827FE653058404B2831D9E60".parse().unwrap(),
+        SymCache::parse(&symcache_buf)?,
+    );
+
+    let state = ProcessState::from_minidump(&buffer, None)?;
+    let thread = &state.threads()[state.requesting_thread() as usize];
+    let inline_frames = thread.inline_frames(&symcaches)?;
+    assert_eq!(inline_frames.len(), thread.frames().len());
+
+    // `crash` and `start` are inlined into `main`.
+    let crash_frame: Vec<_> = inline_frames[0]
+        .iter()
+        .map(|line| (line.function_name().as_str().to_owned(), line.line()))
+        .collect();
+    assert_eq!(
+        crash_frame,
+        [
+            ("crash".to_owned(), 23),
+            ("start".to_owned(), 27),
+            ("main".to_owned(), 35)
+        ]
+    );
+
+    for (frame, lines) in thread.frames().iter().zip(&inline_frames) {
+        let covered = frame
+            .module()
+            .and_then(|module| module.id())
+            .filter(|id| symcaches.contains_key(id))
+            .is_some();
+        if !covered {
+            assert!(lines.is_empty());
+        }
+        assert_eq!(lines, &frame.inline_frames(&symcaches)?);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn thread_context_linux() -> Result<(), Error> {
+    let buffer = ByteView::open(fixture("linux/mini.dmp"))?;
+    let state = ProcessState::from_minidump(&buffer, None)?;
+    let arch = state.system_info().cpu_arch();
+
+    let thread = &state.threads()[state.requesting_thread() as usize];
+    let context = match thread.context(arch) {
+        Some(CpuContext::Amd64(context)) => context,
+        other => panic!("unexpected context {:?}", other),
+    };
+
+    let frame = thread.frames()[0];
+    assert_eq!(context.rip, Some(frame.instruction()));
+    assert!(context.rsp.is_some());
+    assert_eq!(context.registers().len(), frame.registers(arch).len());
+
+    let caller = thread.frames()[1].context(arch);
+    assert!(caller.instruction_pointer().is_some());
+
+    Ok(())
+}
+
+#[test]
+fn stack_memory_linux() -> Result<(), Error> {
+    let buffer = ByteView::open(fixture("linux/mini.dmp"))?;
+    let state = ProcessState::from_minidump(&buffer, None)?;
+    let minidump = Minidump::parse(&buffer)?;
+    let arch = state.system_info().cpu_arch();
+
+    let thread = &state.threads()[state.requesting_thread() as usize];
+    let stack = thread
+        .stack_memory(&minidump, arch)?
+        .expect("missing stack memory");
+    let stack_pointer = thread.context(arch).and_then(|c| c.stack_pointer());
+    assert!(stack.contains(stack_pointer.unwrap()));
+    assert_eq!(stack.data().len() as u64, stack.size());
+
+    // The stack memory points into the minidump buffer instead of a copy.
+    let buffer_range = buffer.as_ptr_range();
+    let stack_range = stack.data().as_ptr_range();
+    assert!(buffer_range.start <= stack_range.start && stack_range.end <= buffer_range.end);
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(not(breakpad_riscv), ignore = "Breakpad does not support RISC-V")]
+fn thread_context_riscv64() -> Result<(), Error> {
+    let buffer = ByteView::open(fixture("linux/riscv64/mini.dmp"))?;
+    let state = ProcessState::from_minidump(&buffer, None)?;
+    let arch = state.system_info().cpu_arch();
+    assert_eq!(arch.cpu_family(), CpuFamily::Riscv64);
+
+    let thread = &state.threads()[0];
+    let context = match thread.context(arch) {
+        Some(CpuContext::Riscv(context)) => context,
+        other => panic!("unexpected context {:?}", other),
+    };
+
+    assert_eq!(context.pc, Some(0x1_0000_1234));
+    assert_eq!(context.sp, Some(0x3f_ffff_f040));
+    assert_eq!(context.s0, Some(0x3f_ffff_f080));
+    assert_eq!(context.ra, Some(0x1_0000_1100));
+
+    let frame = thread.frames()[0];
+    assert_eq!(frame.trust(), FrameTrust::Context);
+    assert_eq!(frame.instruction(), 0x1_0000_1234);
+    assert_eq!(context.registers().len(), frame.registers(arch).len());
+
+    let minidump = Minidump::parse(&buffer)?;
+    let stack = thread
+        .stack_memory(&minidump, arch)?
+        .expect("missing stack memory");
+    assert_eq!(stack.base_address, 0x3f_ffff_f000);
+    assert!(stack.contains(context.sp.unwrap()));
+
+    Ok(())
+}
+
 #[test]
 fn process_minidump_macos() -> Result<(), Error> {
     let buffer = ByteView::open(fixture("macos/mini.dmp"))?;
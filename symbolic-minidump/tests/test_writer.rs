@@ -0,0 +1,152 @@
+#![cfg(feature = "writer")]
+
+use std::convert::TryInto;
+
+use symbolic_common::{ByteView, CpuFamily};
+use symbolic_minidump::writer::{
+    Amd64Context, CpuContext, ExceptionInfo, LinuxStream, MemoryRegion, MinidumpWriter, ModuleInfo,
+    SystemInfo, ThreadInfo,
+};
+use symbolic_testutils::fixture;
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+/// Returns `(stream_type, size, rva)` for every stream in the directory.
+fn read_directory(data: &[u8]) -> Vec<(u32, u32, u32)> {
+    let count = read_u32(data, 8) as usize;
+    let directory = read_u32(data, 12) as usize;
+    (0..count)
+        .map(|index| {
+            let entry = directory + index * 12;
+            (
+                read_u32(data, entry),
+                read_u32(data, entry + 4),
+                read_u32(data, entry + 8),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_write_manual() {
+    let mut writer = MinidumpWriter::new(SystemInfo {
+        cpu_family: CpuFamily::Amd64,
+        cpu_count: 4,
+        os_version: "Linux 5.15.0 #1 SMP x86_64".into(),
+    });
+
+    let mut context = Amd64Context {
+        rip: 0x1000,
+        ..Default::default()
+    };
+    context.gpr[Amd64Context::RSP] = 0x7fff_0000;
+
+    writer.add_thread(ThreadInfo {
+        thread_id: 42,
+        context: CpuContext::Amd64(context),
+        stack: Some(MemoryRegion {
+            base: 0x7fff_0000,
+            data: vec![0xab; 64],
+        }),
+    });
+    writer.add_module(ModuleInfo {
+        base: 0x1000,
+        size: 0x2000,
+        name: "/usr/bin/crash".into(),
+        build_id: Some(vec![1, 2, 3, 4]),
+    });
+    writer.set_exception(ExceptionInfo {
+        thread_id: 42,
+        signal: 11,
+        code: 1,
+        address: 0xdead,
+    });
+    writer.add_linux_stream(LinuxStream::CmdLine, b"crash\0".to_vec());
+
+    let data = writer.to_vec();
+    assert_eq!(&data[..4], b"MDMP");
+
+    let streams = read_directory(&data);
+    let types: Vec<_> = streams.iter().map(|s| s.0).collect();
+    assert_eq!(types, [3, 4, 5, 7, 6, 0x4767_0006]);
+
+    // thread list: one thread with id 42
+    let (_, size, rva) = streams[0];
+    assert_eq!(size, 4 + 48);
+    assert_eq!(read_u32(&data, rva as usize), 1);
+    assert_eq!(read_u32(&data, rva as usize + 4), 42);
+
+    // command line stream contents
+    let (_, size, rva) = streams[5];
+    assert_eq!(&data[rva as usize..(rva + size) as usize], b"crash\0");
+}
+
+#[test]
+fn test_write_core() {
+    let core = ByteView::open(fixture("linux/core/crash.core")).unwrap();
+    let writer = MinidumpWriter::from_core(&core).unwrap();
+    assert_eq!(writer.system_info().cpu_family, CpuFamily::Amd64);
+
+    let thread = &writer.threads()[0];
+    assert!(thread.stack.is_some());
+
+    let exception = writer.exception().unwrap();
+    assert_eq!(exception.thread_id, thread.thread_id);
+    assert_eq!(exception.signal, 11); // SIGSEGV
+
+    let module = writer
+        .modules()
+        .iter()
+        .find(|module| module.name.ends_with("/crash"))
+        .expect("missing crash module");
+    assert_eq!(
+        module.build_id.as_deref(),
+        Some(
+            &[
+                0x6e, 0xa1, 0x8a, 0xcb, 0xd5, 0x10, 0x42, 0x2d, 0xdc, 0x7b, 0x54, 0xf2, 0x56, 0xb0,
+                0x41, 0x18, 0xc0, 0xe5, 0x91, 0xbe
+            ][..]
+        )
+    );
+
+    let data = writer.to_vec();
+    assert_eq!(&data[..4], b"MDMP");
+
+    let types: Vec<_> = read_directory(&data).iter().map(|s| s.0).collect();
+    for stream_type in [3, 4, 7, 6] {
+        assert!(
+            types.contains(&stream_type),
+            "missing stream {}",
+            stream_type
+        );
+    }
+}
+
+#[test]
+#[cfg(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+))]
+fn test_write_process() {
+    let mut child = std::process::Command::new("sleep")
+        .arg("10")
+        .spawn()
+        .expect("failed to spawn child");
+
+    let writer = MinidumpWriter::from_process(child.id());
+    child.kill().ok();
+    child.wait().ok();
+
+    let writer = writer.expect("failed to capture process");
+    assert_eq!(writer.threads().len(), 1);
+    assert!(writer.threads()[0].stack.is_some());
+    assert!(writer
+        .modules()
+        .iter()
+        .any(|module| module.name.ends_with("sleep") && module.build_id.is_some()));
+
+    let data = writer.to_vec();
+    assert_eq!(&data[..4], b"MDMP");
+}
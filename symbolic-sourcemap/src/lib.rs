@@ -6,6 +6,8 @@ use std::borrow::Cow;
 use std::fmt;
 use std::ops::Deref;
 
+use symbolic_common::{source_context, SourceContext, SourceContextOptions};
+
 #[cfg(test)]
 use similar_asserts::assert_eq;
 
@@ -125,6 +127,18 @@ impl<'a> SourceView<'a> {
     pub fn line_count(&self) -> usize {
         self.sv.line_count()
     }
+
+    /// Returns a specific line along with its surrounding lines.
+    ///
+    /// Like [`get_line`](Self::get_line), the `idx` is 0-based. See [`source_context`] for more
+    /// information.
+    pub fn get_source_context(
+        &self,
+        idx: u32,
+        options: SourceContextOptions,
+    ) -> Option<SourceContext<'_>> {
+        source_context(self.as_str().as_bytes(), idx.checked_add(1)?, options)
+    }
 }
 
 impl SourceMapView {
@@ -233,6 +247,18 @@ impl SourceMapView {
     }
 }
 
+#[test]
+fn test_source_context() {
+    let view = SourceView::new("var a = 1;\nvar b = 2;\nthrow new Error();\n");
+    let options = SourceContextOptions::new().context_lines(1);
+    let context = view.get_source_context(2, options).unwrap();
+
+    assert_eq!(context.pre_context, ["var b = 2;"]);
+    assert_eq!(context.context_line, "throw new Error();");
+    assert!(context.post_context.is_empty());
+    assert_eq!(view.get_source_context(3, options), None);
+}
+
 #[test]
 fn test_react_native_hermes() {
     let bytes = include_bytes!("../tests/fixtures/react-native-hermes.map");
@@ -1,6 +1,8 @@
 use std::fmt;
+use std::ops::Range;
 
 use symbolic_common::{Arch, AsSelf, DebugId, Language, Name, NameMangling};
+use symbolic_debuginfo::DebugEntity;
 
 use crate::{new, old, preamble, SymCacheError};
 
@@ -13,6 +15,7 @@ impl From<new::Error> for SymCacheError {
         let kind = match new_error {
             new::Error::BufferNotAligned
             | new::Error::BadFormatLength
+            | new::Error::BadSourceLocation
             | new::Error::WrongEndianness => old::SymCacheErrorKind::BadCacheFile,
             new::Error::HeaderTooSmall => old::SymCacheErrorKind::BadFileHeader,
             new::Error::WrongFormat => old::SymCacheErrorKind::BadFileMagic,
@@ -24,6 +27,7 @@ impl From<new::Error> for SymCacheError {
 }
 
 #[derive(Debug)]
+#[allow(clippy::large_enum_variant)]
 enum SymCacheInner<'data> {
     Old(old::SymCache<'data>),
     New(new::SymCache<'data>),
@@ -74,6 +78,18 @@ impl<'data> SymCache<'data> {
         }
     }
 
+    /// The checksum of the cache file's contents.
+    ///
+    /// Byte-identical inputs always produce byte-identical SymCaches with the same checksum. This
+    /// is `None` for legacy SymCaches, and `Some(0)` for SymCaches written before checksums were
+    /// introduced.
+    pub fn checksum(&self) -> Option<u32> {
+        match &self.0 {
+            SymCacheInner::New(symc) => Some(symc.checksum()),
+            SymCacheInner::Old(_) => None,
+        }
+    }
+
     /// Returns true if line information is included.
     #[deprecated(since = "8.6.0", note = "this will be removed in a future version")]
     pub fn has_line_info(&self) -> bool {
@@ -94,6 +110,89 @@ impl<'data> SymCache<'data> {
         }
     }
 
+    /// Returns true if this cache was built from the symbol table only.
+    ///
+    /// See [`SymCacheWriter::set_symbols_only`](crate::SymCacheWriter::set_symbols_only).
+    pub fn is_symbols_only(&self) -> bool {
+        match &self.0 {
+            SymCacheInner::New(symc) => symc.is_symbols_only(),
+            SymCacheInner::Old(_) => false,
+        }
+    }
+
+    /// Returns true if the debug information entity of every line is included.
+    ///
+    /// See [`SymCacheWriter::set_record_debug_entities`](crate::SymCacheWriter::set_record_debug_entities).
+    pub fn has_debug_entities(&self) -> bool {
+        match &self.0 {
+            SymCacheInner::New(symc) => symc.has_debug_entities(),
+            SymCacheInner::Old(_) => false,
+        }
+    }
+
+    /// Returns all address ranges that originate from the given debug information entity.
+    ///
+    /// This includes ranges where a function created from the entity was inlined into another
+    /// function. The result is empty if the SymCache does not include debug entities.
+    pub fn ranges_for_debug_entity(&self, entity: DebugEntity) -> Vec<Range<u64>> {
+        match &self.0 {
+            SymCacheInner::New(symc) => symc.ranges_for_debug_entity(entity),
+            SymCacheInner::Old(_) => Vec::new(),
+        }
+    }
+
+    /// Returns true if this cache contains an index to look up functions by name.
+    ///
+    /// See [`SymCacheWriter::set_function_name_index`](crate::SymCacheWriter::set_function_name_index).
+    pub fn has_function_name_index(&self) -> bool {
+        match &self.0 {
+            SymCacheInner::New(symc) => symc.has_function_name_index(),
+            SymCacheInner::Old(_) => false,
+        }
+    }
+
+    /// Returns all address ranges covered by functions with the given name.
+    ///
+    /// This includes ranges where the function was inlined into another function. The result is
+    /// empty if the SymCache does not include a function name index.
+    pub fn find_function(&self, name: &str) -> Vec<Range<u64>> {
+        match &self.0 {
+            SymCacheInner::New(symc) => symc.find_function(name),
+            SymCacheInner::Old(_) => Vec::new(),
+        }
+    }
+
+    /// Returns true if this cache contains an index to look up addresses by file and line.
+    ///
+    /// See [`SymCacheWriter::set_line_index`](crate::SymCacheWriter::set_line_index).
+    pub fn has_line_index(&self) -> bool {
+        match &self.0 {
+            SymCacheInner::New(symc) => symc.has_line_index(),
+            SymCacheInner::Old(_) => false,
+        }
+    }
+
+    /// Returns all address ranges that correspond to the given line in a source file.
+    ///
+    /// This includes ranges of functions inlined at that line. The result is empty if the SymCache
+    /// does not include a line index.
+    pub fn find_line(&self, path: &str, line: u32) -> Vec<Range<u64>> {
+        match &self.0 {
+            SymCacheInner::New(symc) => symc.find_line(path, line),
+            SymCacheInner::Old(_) => Vec::new(),
+        }
+    }
+
+    /// Returns true if this cache records the locations of source files in a source bundle.
+    ///
+    /// See [`LineInfo::source_bundle_range`](crate::LineInfo::source_bundle_range).
+    pub fn has_file_sources(&self) -> bool {
+        match &self.0 {
+            SymCacheInner::New(symc) => symc.has_file_sources(),
+            SymCacheInner::Old(_) => false,
+        }
+    }
+
     /// Returns an iterator over all functions.
     #[deprecated(since = "8.6.0", note = "this will be removed in a future version")]
     #[allow(deprecated)]
@@ -124,6 +223,22 @@ impl<'data> SymCache<'data> {
             }
         }
     }
+
+    /// Looks up an address, falling back to the closest preceding function if there is none.
+    ///
+    /// If `addr` is not covered by any function, such as past the end of the last function, this
+    /// returns the lines of the preceding function instead of an empty iterator. Such lines are marked with
+    /// [`LineInfo::is_fallback`](crate::LineInfo::is_fallback). Legacy SymCaches do not support
+    /// fallbacks and behave like [`lookup`](Self::lookup).
+    pub fn lookup_with_fallback(&self, addr: u64) -> Result<Lookup<'data, '_>, SymCacheError> {
+        match &self.0 {
+            SymCacheInner::New(symc) => Ok(Lookup(LookupInner::New {
+                iter: symc.lookup_with_fallback(addr),
+                lookup_addr: addr,
+            })),
+            SymCacheInner::Old(_) => self.lookup(addr),
+        }
+    }
 }
 
 impl<'data> fmt::Debug for SymCache<'data> {
@@ -240,6 +355,7 @@ impl<'data> fmt::Debug for Function<'data> {
 }
 
 #[derive(Clone, Debug)]
+#[allow(clippy::large_enum_variant)]
 enum FunctionsInner<'data> {
     Old(old::Functions<'data>),
     New(std::iter::Enumerate<new::Functions<'data>>),
@@ -325,6 +441,10 @@ impl<'data, 'cache> Iterator for Lookup<'data, 'cache> {
                     filename: sl.file().map(|f| f.path_name()).unwrap_or_default(),
                     base_dir: sl.file().and_then(|f| f.directory()).unwrap_or_default(),
                     comp_dir: sl.file().and_then(|f| f.comp_dir()).unwrap_or_default(),
+                    debug_entity: sl.debug_entity(),
+                    provenance: sl.provenance(),
+                    fallback_distance: sl.fallback_distance(),
+                    source_bundle_range: sl.source_bundle_range(),
                 }))
             }
         }
@@ -108,12 +108,13 @@ pub(crate) mod preamble;
 pub use compat::*;
 pub use new::transform;
 pub use new::SymCacheWriter;
+pub use new::{CancellationToken, Progress, ProgressObserver, ProgressStage, Provenance};
 #[allow(deprecated)]
 pub use old::format;
 pub use old::{Line, LineInfo, SymCacheError, SymCacheErrorKind, ValueKind};
 
 /// The latest version of the file format.
-pub const SYMCACHE_VERSION: u32 = 7;
+pub const SYMCACHE_VERSION: u32 = 8;
 
 // Version history:
 //
@@ -124,3 +125,4 @@ pub const SYMCACHE_VERSION: u32 = 7;
 // 5: PR #221: Invalid inlinee nesting leading to wrong stack traces
 // 6: PR #319: Correct line offsets and spacer line records
 // 7: PR #459: A new binary format fundamentally based on addr ranges
+// 8: Range provenances and line entries in the header
@@ -3,6 +3,7 @@
 use std::io::{Seek, Write};
 
 use symbolic_common::{Arch, DebugId};
+use symbolic_debuginfo::sourcebundle::SourceBundle;
 use symbolic_debuginfo::{Function as SymbolicFunction, ObjectLike, Symbol};
 
 #[cfg(feature = "il2cpp")]
@@ -120,6 +121,28 @@ where
         Ok(())
     }
 
+    /// Processes the [`ObjectLike`] like [`process_object`](Self::process_object), but parses its
+    /// debug information on a separate thread.
+    ///
+    /// At most `capacity` parsed functions are buffered between the two threads. See
+    /// [`SymCacheConverter::process_object_pipelined`] for details.
+    pub fn process_object_pipelined<'d, 'o, O>(
+        &mut self,
+        object: &'o O,
+        capacity: usize,
+    ) -> Result<(), SymCacheError>
+    where
+        O: ObjectLike<'d, 'o> + Sync,
+        O::Error: std::error::Error + Send + Sync + 'static,
+    {
+        self.converter.set_arch(object.arch());
+        self.converter.set_debug_id(object.debug_id());
+
+        self.converter.process_object_pipelined(object, capacity)?;
+
+        Ok(())
+    }
+
     #[cfg(feature = "il2cpp")]
     /// Processes a set of [`UsymSymbols`], passing all mapped symbols into the converter.
     pub fn process_usym(&mut self, usym: &UsymSymbols) -> Result<(), SymCacheError> {
@@ -141,11 +164,95 @@ where
         self.converter.set_arch(arch)
     }
 
+    /// Sets whether the debug information entity of each line should be recorded.
+    ///
+    /// Lookups on the resulting SymCache yield the DWARF DIE, PDB symbol or Breakpad `FUNC`
+    /// record that each line originates from. Defaults to `false`.
+    pub fn set_record_debug_entities(&mut self, record: bool) {
+        self.converter.set_record_debug_entities(record)
+    }
+
+    /// Sets whether the SymCache should include an index to look up functions by name.
+    ///
+    /// This allows mapping function names back to their address ranges with
+    /// [`SymCache::find_function`](crate::SymCache::find_function), at the cost of a larger
+    /// SymCache. Defaults to `false`.
+    pub fn set_function_name_index(&mut self, index: bool) {
+        self.converter.set_function_name_index(index)
+    }
+
+    /// Sets whether the SymCache should include an index to look up addresses by file and line.
+    ///
+    /// This allows mapping source lines back to their address ranges with
+    /// [`SymCache::find_line`](crate::SymCache::find_line), at the cost of a larger SymCache.
+    /// Defaults to `false`.
+    pub fn set_line_index(&mut self, index: bool) {
+        self.converter.set_line_index(index)
+    }
+
+    /// Records the locations of source files within a source bundle.
+    ///
+    /// This allows reading the source of a looked up location directly from the bundle with
+    /// [`LineInfo::source_bundle_range`](crate::LineInfo::source_bundle_range), without
+    /// matching paths between the SymCache and the bundle.
+    pub fn set_source_bundle(&mut self, bundle: &SourceBundle<'_>) -> Result<(), SymCacheError> {
+        self.converter.set_source_bundle(bundle)
+    }
+
+    /// Sets whether the SymCache should be built from the symbol table only.
+    ///
+    /// This skips debug information in [`process_object`](Self::process_object), which is
+    /// considerably faster but omits inlinees, file names and line numbers. Defaults to `false`.
+    pub fn set_symbols_only(&mut self, symbols_only: bool) {
+        self.converter.set_symbols_only(symbols_only)
+    }
+
     /// Sets the debug identifier of this SymCache.
     pub fn set_debug_id(&mut self, debug_id: DebugId) {
         self.converter.set_debug_id(debug_id)
     }
 
+    /// Limits the nesting depth of inlinees written to this SymCache.
+    ///
+    /// Deeper inlinees are collapsed into a synthetic `… N frames inlined` record. There is no
+    /// limit by default.
+    pub fn set_max_inline_depth(&mut self, max_depth: Option<usize>) {
+        self.converter.set_max_inline_depth(max_depth)
+    }
+
+    /// Sets whether functions created by the machine outliner should be attributed to their
+    /// caller.
+    ///
+    /// Outlined functions named `OUTLINED_FUNCTION_N` that are called from a single location are
+    /// looked up as that call site in the caller. Outlined functions with multiple callers are
+    /// looked up without file and line information. Both have [`Provenance::Outlined`]. This
+    /// requires call site information from DWARF. Defaults to `false`.
+    ///
+    /// [`Provenance::Outlined`]: crate::Provenance::Outlined
+    pub fn set_collapse_outlined_functions(&mut self, collapse: bool) {
+        self.converter.set_collapse_outlined_functions(collapse)
+    }
+
+    /// Sets a [`ProgressObserver`] that is notified while processing objects and writing.
+    ///
+    /// The observer is called after every top-level function and symbol processed by
+    /// [`process_object`](Self::process_object), and after every section written by
+    /// [`finish`](Self::finish).
+    pub fn set_progress_observer<O>(&mut self, observer: O)
+    where
+        O: ProgressObserver + 'static,
+    {
+        self.converter.set_progress_observer(observer)
+    }
+
+    /// Sets a [`CancellationToken`] to abort [`process_object`](Self::process_object).
+    ///
+    /// The token is checked between top-level functions and symbols. Once it is cancelled,
+    /// processing fails with [`SymCacheErrorKind::Cancelled`] and the writer must be discarded.
+    pub fn set_cancellation_token(&mut self, token: Option<CancellationToken>) {
+        self.converter.set_cancellation_token(token)
+    }
+
     /// Adds a new symbol to this SymCache.
     ///
     /// Symbols **must** be added in ascending order using this method. This will emit a function
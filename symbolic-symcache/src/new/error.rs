@@ -15,6 +15,8 @@ pub enum Error {
     #[error("header is too small")]
     HeaderTooSmall,
     /// The file was generated by a system with different endianness.
+    ///
+    /// This is no longer returned, as such files are converted while parsing.
     #[error("endianness mismatch")]
     WrongEndianness,
     /// The file magic does not match.
@@ -26,4 +28,7 @@ pub enum Error {
     /// The self-advertised size of the buffer is not correct.
     #[error("incorrect buffer length")]
     BadFormatLength,
+    /// A source location refers to an invalid caller source location.
+    #[error("invalid inlined source location")]
+    BadSourceLocation,
 }
@@ -1,4 +1,7 @@
+use std::ops::Range;
+
 use symbolic_common::Language;
+use symbolic_debuginfo::DebugEntity;
 
 use super::{raw, SymCache};
 
@@ -15,15 +18,21 @@ impl<'data> SymCache<'data> {
                 return SourceLocationIter {
                     cache: self,
                     source_location_idx: u32::MAX,
+                    range_idx: u32::MAX,
+                    fallback_distance: None,
                 }
             }
         };
 
         let source_location_start = (self.source_locations.len() - self.ranges.len()) as u32;
-        let mut source_location_idx = match self.ranges.binary_search_by_key(&addr, |r| r.0) {
-            Ok(idx) => source_location_start + idx as u32,
+        let range_idx = match self.ranges.binary_search_by_key(&addr, |r| r.0) {
+            Ok(idx) => idx as u32,
             Err(idx) if idx == 0 => u32::MAX,
-            Err(idx) => source_location_start + idx as u32 - 1,
+            Err(idx) => idx as u32 - 1,
+        };
+        let mut source_location_idx = match range_idx {
+            u32::MAX => u32::MAX,
+            idx => source_location_start + idx,
         };
 
         if let Some(source_location) = self.source_locations.get(source_location_idx as usize) {
@@ -35,7 +44,224 @@ impl<'data> SymCache<'data> {
         SourceLocationIter {
             cache: self,
             source_location_idx,
+            range_idx,
+            fallback_distance: None,
+        }
+    }
+
+    /// Looks up an instruction address, falling back to the preceding function if there is none.
+    ///
+    /// This behaves like [`lookup`](Self::lookup), except when `addr` is not covered by any range,
+    /// such as past the end of the last function. In that case, the returned iterator yields the
+    /// source locations of the closest preceding range, marked as fallback with the distance of
+    /// `addr` from the end of that range, see [`SourceLocation::fallback_distance`]. The iterator
+    /// is only empty if `addr` lies before the first function.
+    pub fn lookup_with_fallback(&self, addr: u64) -> SourceLocationIter<'data, '_> {
+        let mut iter = self.lookup(addr);
+        if iter.source_location_idx != u32::MAX || iter.range_idx == u32::MAX {
+            return iter;
+        }
+
+        let source_location_start = self.source_locations.len() - self.ranges.len();
+        let mut range_idx = iter.range_idx as usize;
+        while range_idx > 0 {
+            range_idx -= 1;
+
+            let source_location_idx = source_location_start + range_idx;
+            if self.source_locations[source_location_idx] != raw::NO_SOURCE_LOCATION {
+                let end = self.ranges[range_idx + 1].0 as u64;
+                iter.source_location_idx = source_location_idx as u32;
+                iter.range_idx = range_idx as u32;
+                iter.fallback_distance = Some(addr.saturating_sub(end));
+                break;
+            }
+        }
+
+        iter
+    }
+
+    /// Returns `true` if this SymCache was built from the symbol table only.
+    ///
+    /// Such SymCaches do not contain inlinees, file names or line numbers.
+    pub fn is_symbols_only(&self) -> bool {
+        self.header.flags & raw::HEADER_FLAG_SYMBOLS_ONLY != 0
+    }
+
+    /// Returns `true` if this SymCache records the debug information entity of every source
+    /// location.
+    pub fn has_debug_entities(&self) -> bool {
+        !self.debug_entities.is_empty()
+    }
+
+    /// Returns all address ranges that originate from the given debug information entity.
+    ///
+    /// A range matches if the entity produced its source location, or any of the source locations
+    /// it has been inlined into. Adjacent ranges are merged. This requires a linear scan over all
+    /// ranges and is intended for diagnostics only.
+    pub fn ranges_for_debug_entity(&self, entity: DebugEntity) -> Vec<Range<u64>> {
+        let mut result: Vec<Range<u64>> = Vec::new();
+        if !self.has_debug_entities() {
+            return result;
+        }
+
+        let wanted = raw::DebugEntity::from(Some(entity));
+        let source_location_start = self.source_locations.len() - self.ranges.len();
+
+        for (idx, range) in self.ranges.iter().enumerate() {
+            let mut source_location_idx = (source_location_start + idx) as u32;
+            let mut found = false;
+            while let Some(source_location) =
+                self.source_locations.get(source_location_idx as usize)
+            {
+                if self.debug_entities.get(source_location_idx as usize) == Some(&wanted) {
+                    found = true;
+                    break;
+                }
+                source_location_idx = source_location.inlined_into_idx;
+            }
+
+            if !found {
+                continue;
+            }
+
+            let start = range.0 as u64;
+            let end = self
+                .ranges
+                .get(idx + 1)
+                .map_or(u64::MAX, |next| next.0 as u64);
+
+            match result.last_mut() {
+                Some(last) if last.end == start => last.end = end,
+                _ => result.push(start..end),
+            }
+        }
+
+        result
+    }
+
+    /// Returns `true` if this SymCache contains an index to look up functions by name.
+    pub fn has_function_name_index(&self) -> bool {
+        !self.function_ranges.is_empty()
+    }
+
+    /// Returns all address ranges covered by functions with the given name.
+    ///
+    /// The name must match the function name exactly as it is stored in the SymCache, which is
+    /// usually the mangled name. Ranges where the function has been inlined into other functions
+    /// are included. The ranges are sorted by address, and adjacent ranges are merged.
+    ///
+    /// This performs a binary search on the function name index and returns an empty list if the
+    /// SymCache was written without it, see [`has_function_name_index`](Self::has_function_name_index).
+    pub fn find_function(&self, name: &str) -> Vec<Range<u64>> {
+        let function_name = |range: &raw::FunctionRange| {
+            self.functions
+                .get(range.function_idx as usize)
+                .and_then(|function| self.get_string(function.name_offset))
+                .unwrap_or_default()
+        };
+
+        let start = self
+            .function_ranges
+            .partition_point(|range| function_name(range) < name);
+
+        let mut ranges: Vec<Range<u64>> = self.function_ranges[start..]
+            .iter()
+            .take_while(|range| function_name(range) == name)
+            .map(|range| {
+                let end = match range.end {
+                    u32::MAX => u64::MAX,
+                    end => end as u64,
+                };
+                range.start as u64..end
+            })
+            .collect();
+
+        // Multiple functions can share a name, for instance if they were compiled in different
+        // compilation directories.
+        ranges.sort_by_key(|range| range.start);
+
+        let mut result: Vec<Range<u64>> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            match result.last_mut() {
+                Some(last) if last.end >= range.start => last.end = last.end.max(range.end),
+                _ => result.push(range),
+            }
+        }
+
+        result
+    }
+
+    /// Returns `true` if this SymCache contains an index to look up addresses by file and line.
+    pub fn has_line_index(&self) -> bool {
+        !self.line_entries.is_empty()
+    }
+
+    /// Returns all address ranges that correspond to the given line in a source file.
+    ///
+    /// The path must match the [full path](File::full_path) of a file in the SymCache exactly.
+    /// Ranges of functions that have been inlined at the given line are included, which allows
+    /// to resolve breakpoint locations such as `foo.c:42`. The ranges are sorted by address, and
+    /// adjacent ranges are merged.
+    ///
+    /// This performs a binary search on the line index and returns an empty list if the SymCache
+    /// was written without it, see [`has_line_index`](Self::has_line_index).
+    pub fn find_line(&self, path: &str, line: u32) -> Vec<Range<u64>> {
+        let mut ranges: Vec<Range<u64>> = Vec::new();
+        if !self.has_line_index() {
+            return ranges;
         }
+
+        let source_location_start = self.source_locations.len() - self.ranges.len();
+
+        // Files are deduplicated by their fragments, so multiple files can share a full path.
+        for file_idx in 0..self.files.len() as u32 {
+            match self.get_file(file_idx) {
+                Some(file) if file.full_path() == path => (),
+                _ => continue,
+            }
+
+            let start = self
+                .line_entries
+                .partition_point(|entry| (entry.file_idx, entry.line) < (file_idx, line));
+
+            for entry in self.line_entries[start..]
+                .iter()
+                .take_while(|entry| (entry.file_idx, entry.line) == (file_idx, line))
+            {
+                let range_idx =
+                    match (entry.source_location_idx as usize).checked_sub(source_location_start) {
+                        Some(range_idx) => range_idx,
+                        None => continue,
+                    };
+
+                if let Some(range) = self.ranges.get(range_idx) {
+                    let end = self
+                        .ranges
+                        .get(range_idx + 1)
+                        .map_or(u64::MAX, |next| next.0 as u64);
+                    ranges.push(range.0 as u64..end);
+                }
+            }
+        }
+
+        ranges.sort_by_key(|range| range.start);
+
+        let mut result: Vec<Range<u64>> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            match result.last_mut() {
+                Some(last) if last.end >= range.start => last.end = last.end.max(range.end),
+                _ => result.push(range),
+            }
+        }
+
+        result
+    }
+
+    /// Returns `true` if this SymCache records the locations of source files in a source bundle.
+    ///
+    /// See [`SourceLocation::source_bundle_range`].
+    pub fn has_file_sources(&self) -> bool {
+        !self.file_sources.is_empty()
     }
 
     pub(crate) fn get_file(&self, file_idx: u32) -> Option<File<'data>> {
@@ -151,6 +377,34 @@ impl<'data> Function<'data> {
     }
 }
 
+/// The origin of the address range a [`SourceLocation`] was looked up in.
+///
+/// This indicates how precise a source location is, for instance to display the confidence of a
+/// symbolicated frame.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Provenance {
+    /// The origin of the range is not known.
+    #[default]
+    Unknown,
+    /// The range was created from a line record of the debug information, such as a DWARF line
+    /// program, PDB line information or Breakpad `LINE` records.
+    LineInfo,
+    /// The range starts at the entry of a function that has no line records.
+    ///
+    /// The function is known from the debug information, but its file and line are not.
+    Padding,
+    /// The range was created from the symbol table and has no file or line information.
+    Symbol,
+    /// The range covers a function created by the machine outliner.
+    ///
+    /// If the outlined function has a single caller, the range is attributed to the call site in
+    /// that caller. Otherwise, it is the outlined function without file and line information.
+    ///
+    /// See [`SymCacheWriter::set_collapse_outlined_functions`](crate::SymCacheWriter::set_collapse_outlined_functions).
+    Outlined,
+}
+
 /// A Source Location as included in the SymCache.
 ///
 /// The source location represents a `(function, file, line, inlined_into)` tuple corresponding to
@@ -158,7 +412,10 @@ impl<'data> Function<'data> {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SourceLocation<'data, 'cache> {
     pub(crate) cache: &'cache SymCache<'data>,
-    pub(crate) source_location: &'data raw::SourceLocation,
+    pub(crate) source_location_idx: u32,
+    pub(crate) source_location: &'cache raw::SourceLocation,
+    pub(crate) range_idx: u32,
+    pub(crate) fallback_distance: Option<u64>,
 }
 
 impl<'data, 'cache> SourceLocation<'data, 'cache> {
@@ -179,15 +436,72 @@ impl<'data, 'cache> SourceLocation<'data, 'cache> {
         self.cache.get_function(self.source_location.function_idx)
     }
 
+    /// The debug information entity this source location was created from.
+    ///
+    /// This is only available if the SymCache was written with debug entities enabled, see
+    /// [`SymCache::has_debug_entities`].
+    pub fn debug_entity(&self) -> Option<DebugEntity> {
+        self.cache
+            .debug_entities
+            .get(self.source_location_idx as usize)
+            .and_then(|entity| entity.to_entity())
+    }
+
+    /// The byte range of this location's source file within the associated source bundle.
+    ///
+    /// Pass the range to [`SourceBundle::source_by_range`] to read the file's contents without
+    /// matching its path. This is only available if the SymCache was written with a source bundle
+    /// that contains the file, see [`SymCache::has_file_sources`].
+    ///
+    /// [`SourceBundle::source_by_range`]: symbolic_debuginfo::sourcebundle::SourceBundle::source_by_range
+    pub fn source_bundle_range(&self) -> Option<Range<u64>> {
+        let source = self
+            .cache
+            .file_sources
+            .get(self.source_location.file_idx as usize)?;
+
+        if *source == raw::NO_FILE_SOURCE {
+            return None;
+        }
+
+        let start = source.offset as u64;
+        Some(start..start + source.len as u64)
+    }
+
+    /// The origin of the address range this source location was looked up in.
+    ///
+    /// All source locations of an inlining hierarchy share the provenance of their range. SymCaches
+    /// written before provenances were recorded return [`Provenance::Unknown`].
+    pub fn provenance(&self) -> Provenance {
+        self.cache
+            .range_provenances
+            .get(self.range_idx as usize)
+            .map_or(Provenance::Unknown, |provenance| provenance.to_provenance())
+    }
+
+    /// The distance of the looked up address from the end of the range of this source location.
+    ///
+    /// This is `None` unless the source location does not cover the looked up address, which only
+    /// happens for fallbacks of [`SymCache::lookup_with_fallback`].
+    pub fn fallback_distance(&self) -> Option<u64> {
+        self.fallback_distance
+    }
+
     // TODO: maybe forward some of the `File` and `Function` accessors, such as:
     // `function_name` or `full_path` for convenience.
 }
 
 /// An Iterator that yields [`SourceLocation`]s, representing an inlining hierarchy.
+///
+/// The first item is the innermost inlined function containing the address, followed by the
+/// functions it has been inlined into. Since [`SymCache::parse`] validates the inlining hierarchy,
+/// this iterator is guaranteed to terminate and can be used with all iterator adapters.
 #[derive(Debug, Clone)]
 pub struct SourceLocationIter<'data, 'cache> {
     pub(crate) cache: &'cache SymCache<'data>,
     pub(crate) source_location_idx: u32,
+    pub(crate) range_idx: u32,
+    pub(crate) fallback_distance: Option<u64>,
 }
 
 impl<'data, 'cache> Iterator for SourceLocationIter<'data, 'cache> {
@@ -197,15 +511,21 @@ impl<'data, 'cache> Iterator for SourceLocationIter<'data, 'cache> {
         if self.source_location_idx == u32::MAX {
             return None;
         }
-        self.cache
+        let source_location_idx = self.source_location_idx;
+        let source_location = self
+            .cache
             .source_locations
-            .get(self.source_location_idx as usize)
-            .map(|source_location| {
-                self.source_location_idx = source_location.inlined_into_idx;
-                SourceLocation {
-                    cache: self.cache,
-                    source_location,
-                }
-            })
+            .get(source_location_idx as usize);
+        self.source_location_idx = source_location.map_or(u32::MAX, |sl| sl.inlined_into_idx);
+
+        source_location.map(|source_location| SourceLocation {
+            cache: self.cache,
+            source_location_idx,
+            range_idx: self.range_idx,
+            fallback_distance: self.fallback_distance,
+            source_location,
+        })
     }
 }
+
+impl std::iter::FusedIterator for SourceLocationIter<'_, '_> {}
@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::convert::TryInto;
 use std::{mem, ptr};
 
@@ -6,6 +7,7 @@ use symbolic_common::{Arch, DebugId};
 mod compat;
 mod error;
 mod lookup;
+mod progress;
 pub(crate) mod raw;
 pub mod transform;
 mod writer;
@@ -13,8 +15,9 @@ mod writer;
 pub use compat::*;
 pub use error::Error;
 pub use lookup::*;
+pub use progress::*;
 
-use raw::align_to_eight;
+use raw::{align_to_eight, SwapBytes};
 
 type Result<T, E = Error> = std::result::Result<T, E>;
 
@@ -22,14 +25,24 @@ type Result<T, E = Error> = std::result::Result<T, E>;
 ///
 /// This can be parsed from a binary buffer via [`SymCache::parse`], and lookups on it can be performed
 /// via the [`SymCache::lookup`] method.
+///
+/// SymCaches written on a system with the opposite endianness are converted to the native byte
+/// order while parsing. This requires a copy of all data except for strings, whereas native
+/// SymCaches are read without copying.
 #[derive(Clone, PartialEq, Eq)]
 pub struct SymCache<'data> {
-    header: &'data raw::Header,
-    files: &'data [raw::File],
-    functions: &'data [raw::Function],
-    source_locations: &'data [raw::SourceLocation],
-    ranges: &'data [raw::Range],
+    header: Cow<'data, raw::Header>,
+    files: Cow<'data, [raw::File]>,
+    functions: Cow<'data, [raw::Function]>,
+    source_locations: Cow<'data, [raw::SourceLocation]>,
+    ranges: Cow<'data, [raw::Range]>,
     string_bytes: &'data [u8],
+    debug_entities: Cow<'data, [raw::DebugEntity]>,
+    function_ranges: Cow<'data, [raw::FunctionRange]>,
+    line_entries: Cow<'data, [raw::LineEntry]>,
+    file_sources: Cow<'data, [raw::FileSource]>,
+    range_provenances: Cow<'data, [raw::RangeProvenance]>,
+    swapped: bool,
 }
 
 impl<'data> std::fmt::Debug for SymCache<'data> {
@@ -57,23 +70,39 @@ impl<'data> SymCache<'data> {
             return Err(Error::BufferNotAligned);
         }
 
-        let mut header_size = mem::size_of::<raw::Header>();
+        if buf.len() < 8 {
+            return Err(Error::HeaderTooSmall);
+        }
+
+        let swapped = match u32::from_ne_bytes(buf[..4].try_into().unwrap()) {
+            raw::SYMCACHE_MAGIC => false,
+            raw::SYMCACHE_MAGIC_FLIPPED => true,
+            _ => return Err(Error::WrongFormat),
+        };
+
+        let mut version = u32::from_ne_bytes(buf[4..8].try_into().unwrap());
+        if swapped {
+            version = version.swap_bytes();
+        }
+
+        let mut header_size = match version {
+            raw::SYMCACHE_VERSION => mem::size_of::<raw::Header>(),
+            7 => raw::HEADER_SIZE_V7,
+            _ => return Err(Error::WrongVersion),
+        };
         header_size += align_to_eight(header_size);
 
         if buf.len() < header_size {
             return Err(Error::HeaderTooSmall);
         }
-        // SAFETY: we checked that the buffer is well aligned and large enough to fit a `raw::Header`.
-        let header = unsafe { &*(buf.as_ptr() as *const raw::Header) };
-        if header.magic == raw::SYMCACHE_MAGIC_FLIPPED {
-            return Err(Error::WrongEndianness);
-        }
-        if header.magic != raw::SYMCACHE_MAGIC {
-            return Err(Error::WrongFormat);
-        }
-        if header.version != raw::SYMCACHE_VERSION {
-            return Err(Error::WrongVersion);
-        }
+
+        let header = if swapped || version != raw::SYMCACHE_VERSION {
+            Cow::Owned(raw::Header::read(buf, swapped))
+        } else {
+            // SAFETY: we checked that the buffer is well aligned and large enough to fit a
+            // `raw::Header`.
+            Cow::Borrowed(unsafe { &*(buf.as_ptr() as *const raw::Header) })
+        };
 
         let mut files_size = mem::size_of::<raw::File>() * header.num_files as usize;
         files_size += align_to_eight(files_size);
@@ -88,17 +117,76 @@ impl<'data> SymCache<'data> {
         let mut ranges_size = mem::size_of::<raw::Range>() * header.num_ranges as usize;
         ranges_size += align_to_eight(ranges_size);
 
+        let has_file_sources = header.flags & raw::HEADER_FLAG_FILE_SOURCES != 0;
+        let num_file_sources = if has_file_sources {
+            header.num_files
+        } else {
+            0
+        };
+
+        let mut string_bytes_size = header.string_bytes as usize;
+        if header.num_debug_entities > 0
+            || header.num_function_ranges > 0
+            || header.num_line_entries > 0
+            || has_file_sources
+        {
+            string_bytes_size += align_to_eight(string_bytes_size);
+        }
+
+        let mut debug_entities_size =
+            mem::size_of::<raw::DebugEntity>() * header.num_debug_entities as usize;
+        if header.num_function_ranges > 0 || header.num_line_entries > 0 || has_file_sources {
+            debug_entities_size += align_to_eight(debug_entities_size);
+        }
+
+        let mut function_ranges_size =
+            mem::size_of::<raw::FunctionRange>() * header.num_function_ranges as usize;
+        if header.num_line_entries > 0 || has_file_sources {
+            function_ranges_size += align_to_eight(function_ranges_size);
+        }
+
+        let mut line_entries_size =
+            mem::size_of::<raw::LineEntry>() * header.num_line_entries as usize;
+        if has_file_sources {
+            line_entries_size += align_to_eight(line_entries_size);
+        }
+
+        let file_sources_size = mem::size_of::<raw::FileSource>() * num_file_sources as usize;
+
+        let range_provenances_size =
+            mem::size_of::<raw::RangeProvenance>() * header.num_range_provenances as usize;
+
         let expected_buf_size = header_size
             + files_size
             + functions_size
             + source_locations_size
             + ranges_size
-            + header.string_bytes as usize;
+            + string_bytes_size
+            + debug_entities_size
+            + function_ranges_size
+            + line_entries_size
+            + file_sources_size
+            + range_provenances_size;
 
         if buf.len() < expected_buf_size || source_locations_size < ranges_size {
             return Err(Error::BadFormatLength);
         }
 
+        if header.num_debug_entities != 0
+            && header.num_debug_entities != header.num_source_locations
+        {
+            return Err(Error::BadFormatLength);
+        }
+
+        if header.num_range_provenances != 0 && header.num_range_provenances != header.num_ranges {
+            return Err(Error::BadFormatLength);
+        }
+
+        // Every range has a corresponding source location at the end of the source locations.
+        if header.num_ranges > header.num_source_locations {
+            return Err(Error::BadFormatLength);
+        }
+
         // SAFETY: we just made sure that all the pointers we are constructing via pointer
         // arithmetic are within `buf`
         let files_start = unsafe { buf.as_ptr().add(header_size) };
@@ -106,31 +194,67 @@ impl<'data> SymCache<'data> {
         let source_locations_start = unsafe { functions_start.add(functions_size) };
         let ranges_start = unsafe { source_locations_start.add(source_locations_size) };
         let string_bytes_start = unsafe { ranges_start.add(ranges_size) };
+        let debug_entities_start = unsafe { string_bytes_start.add(string_bytes_size) };
+        let function_ranges_start = unsafe { debug_entities_start.add(debug_entities_size) };
+        let line_entries_start = unsafe { function_ranges_start.add(function_ranges_size) };
+        let file_sources_start = unsafe { line_entries_start.add(line_entries_size) };
+        let range_provenances_start = unsafe { file_sources_start.add(file_sources_size) };
 
         // SAFETY: the above buffer size check also made sure we are not going out of bounds
         // here
-        let files = unsafe {
-            &*(ptr::slice_from_raw_parts(files_start, header.num_files as usize)
-                as *const [raw::File])
-        };
+        let files = unsafe { read_section::<raw::File>(files_start, header.num_files, swapped) };
         let functions = unsafe {
-            &*(ptr::slice_from_raw_parts(functions_start, header.num_functions as usize)
-                as *const [raw::Function])
+            read_section::<raw::Function>(functions_start, header.num_functions, swapped)
         };
         let source_locations = unsafe {
-            &*(ptr::slice_from_raw_parts(
+            read_section::<raw::SourceLocation>(
                 source_locations_start,
-                header.num_source_locations as usize,
-            ) as *const [raw::SourceLocation])
-        };
-        let ranges = unsafe {
-            &*(ptr::slice_from_raw_parts(ranges_start, header.num_ranges as usize)
-                as *const [raw::Range])
+                header.num_source_locations,
+                swapped,
+            )
         };
+        let ranges =
+            unsafe { read_section::<raw::Range>(ranges_start, header.num_ranges, swapped) };
         let string_bytes = unsafe {
             &*(ptr::slice_from_raw_parts(string_bytes_start, header.string_bytes as usize)
                 as *const [u8])
         };
+        let debug_entities = unsafe {
+            read_section::<raw::DebugEntity>(
+                debug_entities_start,
+                header.num_debug_entities,
+                swapped,
+            )
+        };
+        let function_ranges = unsafe {
+            read_section::<raw::FunctionRange>(
+                function_ranges_start,
+                header.num_function_ranges,
+                swapped,
+            )
+        };
+        let line_entries = unsafe {
+            read_section::<raw::LineEntry>(line_entries_start, header.num_line_entries, swapped)
+        };
+        let file_sources = unsafe {
+            read_section::<raw::FileSource>(file_sources_start, num_file_sources, swapped)
+        };
+        let range_provenances = unsafe {
+            read_section::<raw::RangeProvenance>(
+                range_provenances_start,
+                header.num_range_provenances,
+                swapped,
+            )
+        };
+
+        // The writer only ever refers to caller source locations that have been written before.
+        // Validating this once guarantees that lookups terminate and never read out of bounds.
+        for (idx, source_location) in source_locations.iter().enumerate() {
+            let inlined_into_idx = source_location.inlined_into_idx;
+            if inlined_into_idx != u32::MAX && inlined_into_idx as usize >= idx {
+                return Err(Error::BadSourceLocation);
+            }
+        }
 
         Ok(SymCache {
             header,
@@ -139,6 +263,12 @@ impl<'data> SymCache<'data> {
             source_locations,
             ranges,
             string_bytes,
+            debug_entities,
+            function_ranges,
+            line_entries,
+            file_sources,
+            range_provenances,
+            swapped,
         })
     }
 
@@ -149,12 +279,16 @@ impl<'data> SymCache<'data> {
         }
         let len_offset = offset as usize;
         let len_size = std::mem::size_of::<u32>();
-        let len = u32::from_ne_bytes(
+        let mut len = u32::from_ne_bytes(
             self.string_bytes
                 .get(len_offset..len_offset + len_size)?
                 .try_into()
                 .unwrap(),
-        ) as usize;
+        );
+        if self.swapped {
+            len = len.swap_bytes();
+        }
+        let len = len as usize;
 
         let start_offset = len_offset + len_size;
         let end_offset = start_offset + len;
@@ -177,4 +311,267 @@ impl<'data> SymCache<'data> {
     pub fn debug_id(&self) -> DebugId {
         self.header.debug_id
     }
+
+    /// The CRC32 checksum of the data following the header.
+    ///
+    /// The writer produces byte-identical SymCaches for identical inputs, so this checksum can be
+    /// used to deduplicate caches without comparing their contents. It is `0` for SymCaches that
+    /// were written before checksums were introduced.
+    pub fn checksum(&self) -> u32 {
+        self.header.checksum
+    }
+}
+
+/// Creates a slice of `len` raw items at `start`, converting them to the native byte order if the
+/// SymCache was written with the opposite endianness.
+///
+/// # Safety
+///
+/// `start` must be aligned for `T` and valid for reads of `len` items during `'data`.
+unsafe fn read_section<'data, T>(start: *const u8, len: u32, swapped: bool) -> Cow<'data, [T]>
+where
+    T: Clone + SwapBytes,
+{
+    let items = &*(ptr::slice_from_raw_parts(start, len as usize) as *const [T]);
+    if swapped {
+        Cow::Owned(items.iter().map(T::swap_bytes).collect())
+    } else {
+        Cow::Borrowed(items)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use symbolic_common::ByteView;
+    use symbolic_debuginfo::sourcebundle::{SourceBundle, SourceBundleWriter, SourceFileInfo};
+    use symbolic_debuginfo::Object;
+    use symbolic_testutils::fixture;
+
+    use super::*;
+    use crate::SymCacheWriter;
+
+    /// Writes a SymCache and returns it along with the offset of its source locations.
+    fn write_symcache() -> (Vec<u8>, usize) {
+        let data = ByteView::open(fixture("linux/crash.debug")).unwrap();
+        let object = Object::parse(&data).unwrap();
+
+        let mut buffer = Vec::new();
+        SymCacheWriter::write_object(&object, Cursor::new(&mut buffer)).unwrap();
+
+        let symcache = SymCache::parse(&buffer).unwrap();
+        assert!(symcache.header.num_source_locations > 1);
+
+        let files_size = mem::size_of::<raw::File>() * symcache.files.len();
+        let functions_size = mem::size_of::<raw::Function>() * symcache.functions.len();
+        let header_size = mem::size_of::<raw::Header>();
+        let offset = header_size
+            + align_to_eight(header_size)
+            + files_size
+            + align_to_eight(files_size)
+            + functions_size
+            + align_to_eight(functions_size);
+
+        (buffer, offset)
+    }
+
+    /// Writes a source bundle containing the first file of the object.
+    fn write_bundle(object: &Object<'_>) -> Vec<u8> {
+        let session = object.debug_session().unwrap();
+        let path = session.files().next().unwrap().unwrap().abs_path_str();
+
+        let mut writer = Cursor::new(Vec::new());
+        let mut bundle = SourceBundleWriter::start(&mut writer).unwrap();
+        let mut info = SourceFileInfo::new();
+        info.set_path(path);
+        bundle.add_file("source", &b"source"[..], info).unwrap();
+        bundle.finish().unwrap();
+
+        writer.into_inner()
+    }
+
+    /// Overwrites the `inlined_into_idx` of the source location at the given index.
+    fn set_inlined_into(buffer: &mut [u8], offset: usize, idx: usize, inlined_into_idx: u32) {
+        let offset =
+            offset + idx * mem::size_of::<raw::SourceLocation>() + mem::size_of::<u32>() * 3;
+        buffer[offset..offset + 4].copy_from_slice(&inlined_into_idx.to_ne_bytes());
+    }
+
+    /// Converts a native SymCache into the opposite byte order.
+    fn swap_symcache(buffer: &[u8]) -> Vec<u8> {
+        let symcache = SymCache::parse(buffer).unwrap();
+        let mut swapped = buffer.to_vec();
+
+        let swap_words = |bytes: &mut [u8]| {
+            for word in bytes.chunks_exact_mut(4) {
+                word.reverse();
+            }
+        };
+
+        // The header consists of 4-byte words, except for the UUID and the padding of the debug id.
+        let header_size = mem::size_of::<raw::Header>();
+        swap_words(&mut swapped[..8]);
+        swap_words(&mut swapped[24..28]);
+        swap_words(&mut swapped[40..header_size]);
+
+        let string_start = header_size
+            + align_to_eight(header_size)
+            + [
+                mem::size_of_val(&*symcache.files),
+                mem::size_of_val(&*symcache.functions),
+                mem::size_of_val(&*symcache.source_locations),
+                mem::size_of_val(&*symcache.ranges),
+            ]
+            .iter()
+            .map(|size| size + align_to_eight(*size))
+            .sum::<usize>();
+        let string_end = string_start + symcache.string_bytes.len();
+
+        swap_words(&mut swapped[header_size..string_start]);
+        // Range provenances are single bytes at the very end and do not need to be swapped.
+        let provenances_start = buffer.len() - symcache.range_provenances.len();
+        swap_words(&mut swapped[string_end + align_to_eight(string_end)..provenances_start]);
+
+        // Strings are prefixed with their length.
+        let mut offset = string_start;
+        while offset < string_end {
+            let len = u32::from_ne_bytes(buffer[offset..offset + 4].try_into().unwrap());
+            swapped[offset..offset + 4].reverse();
+            offset += 4 + len as usize;
+        }
+
+        swapped
+    }
+
+    #[test]
+    fn test_foreign_endianness() {
+        let data =
+            ByteView::open(fixture("macos/crash.dSYM/Contents/Resources/DWARF/crash")).unwrap();
+        let object = Object::parse(&data).unwrap();
+        let bundle_data = write_bundle(&object);
+        let bundle = SourceBundle::parse(&bundle_data).unwrap();
+
+        let mut buffer = Vec::new();
+        let mut writer = SymCacheWriter::new(Cursor::new(&mut buffer)).unwrap();
+        writer.set_record_debug_entities(true);
+        writer.set_function_name_index(true);
+        writer.set_line_index(true);
+        writer.process_object(&object).unwrap();
+        writer.set_source_bundle(&bundle).unwrap();
+        writer.finish().unwrap();
+
+        let native = SymCache::parse(&buffer).unwrap();
+        let swapped_buffer = swap_symcache(&buffer);
+        assert_ne!(buffer, swapped_buffer);
+
+        let swapped = SymCache::parse(&swapped_buffer).unwrap();
+        assert!(swapped.swapped);
+        assert!(crate::SymCache::parse(&swapped_buffer).is_ok());
+        assert_eq!(native.header, swapped.header);
+        assert_eq!(native.files, swapped.files);
+        assert_eq!(native.functions, swapped.functions);
+        assert_eq!(native.source_locations, swapped.source_locations);
+        assert_eq!(native.ranges, swapped.ranges);
+        assert_eq!(native.debug_entities, swapped.debug_entities);
+        assert_eq!(native.function_ranges, swapped.function_ranges);
+        assert_eq!(native.line_entries, swapped.line_entries);
+        assert!(native.has_file_sources());
+        assert_eq!(native.file_sources, swapped.file_sources);
+        assert_eq!(native.range_provenances, swapped.range_provenances);
+
+        for range in native.ranges.iter() {
+            let expected: Vec<_> = native
+                .lookup(range.0 as u64)
+                .map(|sl| {
+                    (
+                        sl.function().and_then(|f| f.name()),
+                        sl.file().map(|f| f.full_path()),
+                        sl.line(),
+                    )
+                })
+                .collect();
+            let actual: Vec<_> = swapped
+                .lookup(range.0 as u64)
+                .map(|sl| {
+                    (
+                        sl.function().and_then(|f| f.name()),
+                        sl.file().map(|f| f.full_path()),
+                        sl.line(),
+                    )
+                })
+                .collect();
+            assert_eq!(expected, actual);
+        }
+    }
+
+    #[test]
+    fn test_read_version_7() {
+        let (buffer, _) = write_symcache();
+        let native = SymCache::parse(&buffer).unwrap();
+        assert_eq!(native.version(), raw::SYMCACHE_VERSION);
+        assert!(native.line_entries.is_empty());
+
+        // Version 7 headers lack the trailing fields, and the SymCache ends before the range
+        // provenances.
+        let header_size = mem::size_of::<raw::Header>();
+        let payload_end = buffer.len() - native.range_provenances.len();
+        let mut old = buffer[..raw::HEADER_SIZE_V7].to_vec();
+        old[4..8].copy_from_slice(&7u32.to_ne_bytes());
+        old.extend_from_slice(&buffer[header_size + align_to_eight(header_size)..payload_end]);
+
+        let symcache = SymCache::parse(&old).unwrap();
+        assert_eq!(symcache.version(), 7);
+        assert!(symcache.range_provenances.is_empty());
+        assert_eq!(native.files, symcache.files);
+        assert_eq!(native.functions, symcache.functions);
+        assert_eq!(native.source_locations, symcache.source_locations);
+        assert_eq!(native.ranges, symcache.ranges);
+
+        for range in native.ranges.iter() {
+            let expected: Vec<_> = native
+                .lookup(range.0 as u64)
+                .map(|sl| (sl.function().and_then(|f| f.name()), sl.line()))
+                .collect();
+            let actual: Vec<_> = symcache
+                .lookup(range.0 as u64)
+                .map(|sl| (sl.function().and_then(|f| f.name()), sl.line()))
+                .collect();
+            assert_eq!(expected, actual);
+        }
+
+        let mut newer = buffer;
+        newer[4..8].copy_from_slice(&(raw::SYMCACHE_VERSION + 1).to_ne_bytes());
+        assert!(matches!(SymCache::parse(&newer), Err(Error::WrongVersion)));
+    }
+
+    #[test]
+    fn test_valid_inlined_into() {
+        let (mut buffer, offset) = write_symcache();
+
+        set_inlined_into(&mut buffer, offset, 1, 0);
+        assert!(SymCache::parse(&buffer).is_ok());
+    }
+
+    #[test]
+    fn test_cyclic_inlined_into() {
+        let (mut buffer, offset) = write_symcache();
+
+        set_inlined_into(&mut buffer, offset, 1, 1);
+        assert!(matches!(
+            SymCache::parse(&buffer),
+            Err(Error::BadSourceLocation)
+        ));
+    }
+
+    #[test]
+    fn test_out_of_bounds_inlined_into() {
+        let (mut buffer, offset) = write_symcache();
+
+        set_inlined_into(&mut buffer, offset, 0, u32::MAX - 1);
+        assert!(matches!(
+            SymCache::parse(&buffer),
+            Err(Error::BadSourceLocation)
+        ));
+    }
 }
@@ -0,0 +1,110 @@
+//! Progress reporting and cancellation for the [SymCache Converter](`super::writer::SymCacheConverter`).
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// The stage of a SymCache conversion, reported as part of [`Progress`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ProgressStage {
+    /// Functions are converted from debug information.
+    ///
+    /// Debug information is parsed lazily, so the total number of functions is not known in
+    /// advance.
+    Functions,
+    /// Symbols are converted from the symbol table.
+    Symbols,
+    /// The SymCache is serialized. Progress is measured in bytes written.
+    Serialize,
+}
+
+/// The progress of a SymCache conversion, reported to a [`ProgressObserver`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Progress {
+    /// The current stage of the conversion.
+    pub stage: ProgressStage,
+    /// The number of items processed in the current stage.
+    ///
+    /// This counts top-level functions, symbols, or bytes written depending on the stage.
+    pub processed: u64,
+    /// The total number of items in the current stage, if known in advance.
+    pub total: Option<u64>,
+}
+
+/// Receives [`Progress`] updates while converting a SymCache.
+///
+/// This is implemented for all closures taking a [`Progress`] argument.
+pub trait ProgressObserver {
+    /// Called when the conversion makes progress.
+    fn on_progress(&mut self, progress: Progress);
+}
+
+impl<F> ProgressObserver for F
+where
+    F: FnMut(Progress),
+{
+    fn on_progress(&mut self, progress: Progress) {
+        self(progress)
+    }
+}
+
+// This is essentially just a newtype in order to implement `Debug`.
+#[derive(Default)]
+pub(crate) struct Observer(pub Option<Box<dyn ProgressObserver>>);
+
+impl Observer {
+    /// Reports progress to the observer, if any.
+    pub fn report(&mut self, stage: ProgressStage, processed: u64, total: Option<u64>) {
+        if let Some(ref mut observer) = self.0 {
+            observer.on_progress(Progress {
+                stage,
+                processed,
+                total,
+            });
+        }
+    }
+}
+
+impl std::fmt::Debug for Observer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Observer").field(&self.0.is_some()).finish()
+    }
+}
+
+/// A token to cancel a running SymCache conversion from another thread.
+///
+/// Clones of a token share their state, so cancelling one clone cancels all of them. The
+/// converter checks the token between functions and symbols and fails with
+/// [`SymCacheErrorKind::Cancelled`](crate::SymCacheErrorKind::Cancelled) once it is cancelled.
+///
+/// # Examples
+///
+/// ```
+/// use symbolic_symcache::CancellationToken;
+///
+/// let token = CancellationToken::new();
+/// let handle = token.clone();
+/// assert!(!token.is_cancelled());
+///
+/// handle.cancel();
+/// assert!(token.is_cancelled());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Creates a new token that is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests cancellation of all conversions using this token.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns `true` if cancellation has been requested.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
@@ -1,6 +1,12 @@
 //! The raw SymCache binary file format internals.
 //!
-use symbolic_common::{Arch, DebugId};
+use std::convert::TryInto;
+use std::mem;
+
+use symbolic_common::{Arch, DebugId, Uuid};
+use symbolic_debuginfo::DebugEntity as SymbolicDebugEntity;
+
+use super::Provenance;
 
 pub use crate::SYMCACHE_VERSION;
 
@@ -47,10 +53,94 @@ pub struct Header {
     pub num_ranges: u32,
     /// Total number of bytes used for string data.
     pub string_bytes: u32,
+    /// Number of included [`DebugEntity`]s.
+    ///
+    /// This is either `0` or equal to `num_source_locations`.
+    pub num_debug_entities: u32,
+    /// Flags describing the contents of the SymCache, see [`HEADER_FLAG_SYMBOLS_ONLY`].
+    pub flags: u32,
+    /// Number of included [`FunctionRange`]s.
+    pub num_function_ranges: u32,
+    /// CRC32 checksum of all data following the header.
+    ///
+    /// Since the writer is deterministic, identical inputs produce identical checksums. This is
+    /// `0` for SymCaches written before checksums were introduced.
+    pub checksum: u32,
+    /// Number of included [`RangeProvenance`]s.
+    ///
+    /// This is either `0` or equal to `num_ranges`.
+    pub num_range_provenances: u32,
+    /// Number of included [`LineEntry`]s.
+    pub num_line_entries: u32,
+}
+
+/// The size of the [`Header`] in version 7 of the format.
+///
+/// Version 7 headers end before [`num_range_provenances`](Header::num_range_provenances), and the
+/// SymCache contains neither range provenances nor line entries.
+pub const HEADER_SIZE_V7: usize = mem::size_of::<Header>() - 2 * mem::size_of::<u32>();
+
+impl Header {
+    /// Reads a header that cannot be borrowed from the buffer.
+    ///
+    /// This is the case if the header was written on a system with the opposite endianness, or
+    /// with version 7 of the format, which lacks the trailing fields. The buffer must be at least
+    /// as large as the header of the respective version.
+    pub(crate) fn read(bytes: &[u8], swapped: bool) -> Self {
+        let u32_at = |offset: usize| {
+            let value = u32::from_ne_bytes(bytes[offset..offset + 4].try_into().unwrap());
+            if swapped {
+                value.swap_bytes()
+            } else {
+                value
+            }
+        };
+
+        // The debug identifier starts with the UUID bytes, followed by the appendix. Following
+        // fields start after the entire identifier, including its padding.
+        let uuid = Uuid::from_bytes(bytes[8..24].try_into().unwrap());
+        let debug_id = DebugId::from_parts(uuid, u32_at(24));
+        let arch_offset = 8 + mem::size_of::<DebugId>();
+
+        let version = u32_at(4);
+        let u32_since_v8 = |offset: usize| {
+            if version > 7 {
+                u32_at(offset)
+            } else {
+                0
+            }
+        };
+
+        Header {
+            magic: u32_at(0),
+            version,
+            debug_id,
+            arch: Arch::from_u32(u32_at(arch_offset)),
+            num_files: u32_at(arch_offset + 4),
+            num_functions: u32_at(arch_offset + 8),
+            num_source_locations: u32_at(arch_offset + 12),
+            num_ranges: u32_at(arch_offset + 16),
+            string_bytes: u32_at(arch_offset + 20),
+            num_debug_entities: u32_at(arch_offset + 24),
+            flags: u32_at(arch_offset + 28),
+            num_function_ranges: u32_at(arch_offset + 32),
+            checksum: u32_at(arch_offset + 36),
+            num_range_provenances: u32_since_v8(arch_offset + 40),
+            num_line_entries: u32_since_v8(arch_offset + 44),
+        }
+    }
+}
 
-    /// Some reserved space in the header for future extensions that would not require a
-    /// completely new parsing method.
-    pub _reserved: [u8; 16],
+/// The SymCache was built from the symbol table only and does not contain line information.
+pub const HEADER_FLAG_SYMBOLS_ONLY: u32 = 1;
+
+/// The SymCache contains a [`FileSource`] for every [`File`].
+pub const HEADER_FLAG_FILE_SOURCES: u32 = 2;
+
+/// Converts raw data that was written on a system with the opposite endianness.
+pub(crate) trait SwapBytes {
+    /// Returns a copy of this value with the byte order of all fields reversed.
+    fn swap_bytes(&self) -> Self;
 }
 
 /// Serialized Function metadata in the SymCache.
@@ -67,6 +157,17 @@ pub struct Function {
     pub lang: u32,
 }
 
+impl SwapBytes for Function {
+    fn swap_bytes(&self) -> Self {
+        Function {
+            name_offset: self.name_offset.swap_bytes(),
+            comp_dir_offset: self.comp_dir_offset.swap_bytes(),
+            entry_pc: self.entry_pc.swap_bytes(),
+            lang: self.lang.swap_bytes(),
+        }
+    }
+}
+
 /// Serialized File in the SymCache.
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 #[repr(C)]
@@ -79,6 +180,16 @@ pub struct File {
     pub path_name_offset: u32,
 }
 
+impl SwapBytes for File {
+    fn swap_bytes(&self) -> Self {
+        File {
+            comp_dir_offset: self.comp_dir_offset.swap_bytes(),
+            directory_offset: self.directory_offset.swap_bytes(),
+            path_name_offset: self.path_name_offset.swap_bytes(),
+        }
+    }
+}
+
 /// A location in a source file, comprising a file, a line, a function, and
 /// the index of the source location this was inlined into, if any.
 ///
@@ -100,6 +211,17 @@ pub struct SourceLocation {
     pub inlined_into_idx: u32,
 }
 
+impl SwapBytes for SourceLocation {
+    fn swap_bytes(&self) -> Self {
+        SourceLocation {
+            file_idx: self.file_idx.swap_bytes(),
+            line: self.line.swap_bytes(),
+            function_idx: self.function_idx.swap_bytes(),
+            inlined_into_idx: self.inlined_into_idx.swap_bytes(),
+        }
+    }
+}
+
 /// A representation of a code range in the SymCache.
 ///
 /// We only save the start address, the end is implicitly given
@@ -108,6 +230,220 @@ pub struct SourceLocation {
 #[repr(C)]
 pub struct Range(pub u32);
 
+impl SwapBytes for Range {
+    fn swap_bytes(&self) -> Self {
+        Range(self.0.swap_bytes())
+    }
+}
+
+/// A reference to the debug information entity a [`SourceLocation`] was created from.
+///
+/// Debug entities are stored in a separate section after the string bytes, with one entry per
+/// [`SourceLocation`]. The meaning of `high` and `low` depends on the `kind`:
+///
+/// | `kind`                      | `high`             | `low`               |
+/// |-----------------------------|--------------------|---------------------|
+/// | [`DEBUG_ENTITY_NONE`]       | unused             | unused              |
+/// | [`DEBUG_ENTITY_DWARF_DIE`]  | upper offset bits  | lower offset bits   |
+/// | [`DEBUG_ENTITY_PDB_SYMBOL`] | module index       | symbol offset       |
+/// | [`DEBUG_ENTITY_BREAKPAD`]   | upper offset bits  | lower offset bits   |
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[repr(C)]
+pub struct DebugEntity {
+    /// The kind of debug entity.
+    pub kind: u32,
+    /// The first value of the entity reference.
+    pub high: u32,
+    /// The second value of the entity reference.
+    pub low: u32,
+}
+
+impl SwapBytes for DebugEntity {
+    fn swap_bytes(&self) -> Self {
+        DebugEntity {
+            kind: self.kind.swap_bytes(),
+            high: self.high.swap_bytes(),
+            low: self.low.swap_bytes(),
+        }
+    }
+}
+
+/// An address range covered by a [`Function`], used to look up functions by name.
+///
+/// Function ranges are stored in an optional section after the debug entities. They are sorted by
+/// the name of their function and then by start address, so that all ranges of a function name
+/// can be found with a binary search. A range covers all addresses where the function is executed,
+/// including addresses where it has been inlined into other functions.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[repr(C)]
+pub struct FunctionRange {
+    /// The function covering this range (reference to a [`Function`]).
+    pub function_idx: u32,
+    /// The first address of the range.
+    pub start: u32,
+    /// The first address after the range, or `u32::MAX` if the range extends to infinity.
+    pub end: u32,
+}
+
+impl SwapBytes for FunctionRange {
+    fn swap_bytes(&self) -> Self {
+        FunctionRange {
+            function_idx: self.function_idx.swap_bytes(),
+            start: self.start.swap_bytes(),
+            end: self.end.swap_bytes(),
+        }
+    }
+}
+
+/// A line in a source file covered by a [`Range`], used to look up addresses by file and line.
+///
+/// Line entries are stored in an optional section after the function ranges. They are sorted by
+/// file, line and source location, so that all ranges of a line can be found with a binary search.
+/// A line is covered by a range if its source location, or any of the source locations it has been
+/// inlined into, points to the line.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(C)]
+pub struct LineEntry {
+    /// The source file (reference to a [`File`]).
+    pub file_idx: u32,
+    /// The line number.
+    pub line: u32,
+    /// The source location of the covering range (reference to a [`SourceLocation`]).
+    ///
+    /// This always refers to one of the trailing source locations that correspond to [`Range`]s.
+    pub source_location_idx: u32,
+}
+
+impl SwapBytes for LineEntry {
+    fn swap_bytes(&self) -> Self {
+        LineEntry {
+            file_idx: self.file_idx.swap_bytes(),
+            line: self.line.swap_bytes(),
+            source_location_idx: self.source_location_idx.swap_bytes(),
+        }
+    }
+}
+
+/// The location of a [`File`]'s contents within an associated source bundle.
+///
+/// File sources are stored in an optional section after the line entries, with one entry per
+/// [`File`]. The section is present if the header contains [`HEADER_FLAG_FILE_SOURCES`]. The range covers the file's entry in the source bundle archive, including its header.
+/// Files that are not contained in the bundle are marked with [`NO_FILE_SOURCE`].
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[repr(C)]
+pub struct FileSource {
+    /// The byte offset of the file's entry in the source bundle.
+    pub offset: u32,
+    /// The length of the file's entry in bytes.
+    pub len: u32,
+}
+
+impl SwapBytes for FileSource {
+    fn swap_bytes(&self) -> Self {
+        FileSource {
+            offset: self.offset.swap_bytes(),
+            len: self.len.swap_bytes(),
+        }
+    }
+}
+
+/// The sentinel [`FileSource`] for files that are not contained in the source bundle.
+pub const NO_FILE_SOURCE: FileSource = FileSource {
+    offset: u32::MAX,
+    len: 0,
+};
+
+/// The origin of a [`Range`] in the source data.
+///
+/// Range provenances are stored in an optional section after the file sources, with one entry
+/// per [`Range`]. Since they are single bytes, the section is not aligned and its byte order does
+/// not depend on the endianness of the writer. See the `PROVENANCE_*` constants for valid values.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[repr(C)]
+pub struct RangeProvenance(pub u8);
+
+impl SwapBytes for RangeProvenance {
+    fn swap_bytes(&self) -> Self {
+        *self
+    }
+}
+
+impl RangeProvenance {
+    /// Converts this raw provenance into its public representation.
+    pub fn to_provenance(self) -> Provenance {
+        match self.0 {
+            PROVENANCE_LINE_INFO => Provenance::LineInfo,
+            PROVENANCE_PADDING => Provenance::Padding,
+            PROVENANCE_SYMBOL => Provenance::Symbol,
+            PROVENANCE_OUTLINED => Provenance::Outlined,
+            _ => Provenance::Unknown,
+        }
+    }
+}
+
+/// The origin of the range is not known.
+pub const PROVENANCE_UNKNOWN: u8 = 0;
+/// The range starts at a line record of the debug information.
+pub const PROVENANCE_LINE_INFO: u8 = 1;
+/// The range starts at the entry of a function without line records in the debug information.
+pub const PROVENANCE_PADDING: u8 = 2;
+/// The range starts at a symbol from the symbol table.
+pub const PROVENANCE_SYMBOL: u8 = 3;
+/// The range covers an outlined function and starts at the call site in its only caller, or at the
+/// outlined function itself if it has multiple callers.
+pub const PROVENANCE_OUTLINED: u8 = 4;
+
+/// No debug entity is known for this source location.
+pub const DEBUG_ENTITY_NONE: u32 = 0;
+/// The offset of a DIE in the `.debug_info` section.
+pub const DEBUG_ENTITY_DWARF_DIE: u32 = 1;
+/// A symbol record in a PDB module.
+pub const DEBUG_ENTITY_PDB_SYMBOL: u32 = 2;
+/// The offset of a `FUNC` record in a Breakpad symbol file.
+pub const DEBUG_ENTITY_BREAKPAD: u32 = 3;
+
+/// The sentinel [`DebugEntity`] for source locations without a known entity.
+pub const NO_DEBUG_ENTITY: DebugEntity = DebugEntity {
+    kind: DEBUG_ENTITY_NONE,
+    high: 0,
+    low: 0,
+};
+
+impl DebugEntity {
+    /// Converts this raw entity into its public representation.
+    pub fn to_entity(self) -> Option<SymbolicDebugEntity> {
+        let wide = (self.high as u64) << 32 | self.low as u64;
+        match self.kind {
+            DEBUG_ENTITY_DWARF_DIE => Some(SymbolicDebugEntity::DwarfDie(wide)),
+            DEBUG_ENTITY_PDB_SYMBOL => Some(SymbolicDebugEntity::PdbSymbol {
+                module: self.high,
+                symbol: self.low,
+            }),
+            DEBUG_ENTITY_BREAKPAD => Some(SymbolicDebugEntity::BreakpadFunc(wide)),
+            _ => None,
+        }
+    }
+}
+
+impl From<Option<SymbolicDebugEntity>> for DebugEntity {
+    fn from(entity: Option<SymbolicDebugEntity>) -> Self {
+        let (kind, high, low) = match entity {
+            Some(SymbolicDebugEntity::DwarfDie(offset)) => {
+                (DEBUG_ENTITY_DWARF_DIE, (offset >> 32) as u32, offset as u32)
+            }
+            Some(SymbolicDebugEntity::PdbSymbol { module, symbol }) => {
+                (DEBUG_ENTITY_PDB_SYMBOL, module, symbol)
+            }
+            Some(SymbolicDebugEntity::BreakpadFunc(offset)) => {
+                (DEBUG_ENTITY_BREAKPAD, (offset >> 32) as u32, offset as u32)
+            }
+            _ => return NO_DEBUG_ENTITY,
+        };
+
+        DebugEntity { kind, high, low }
+    }
+}
+
 /// Returns the amount left to add to the remainder to get 8 if
 /// `to_align` isn't a multiple of 8.
 pub fn align_to_eight(to_align: usize) -> usize {
@@ -127,7 +463,7 @@ mod tests {
 
     #[test]
     fn test_sizeof() {
-        assert_eq!(mem::size_of::<Header>(), 80);
+        assert_eq!(mem::size_of::<Header>(), 88);
         assert_eq!(mem::align_of::<Header>(), 4);
 
         assert_eq!(mem::size_of::<Function>(), 16);
@@ -141,5 +477,17 @@ mod tests {
 
         assert_eq!(mem::size_of::<Range>(), 4);
         assert_eq!(mem::align_of::<Range>(), 4);
+
+        assert_eq!(mem::size_of::<DebugEntity>(), 12);
+        assert_eq!(mem::align_of::<DebugEntity>(), 4);
+
+        assert_eq!(mem::size_of::<FunctionRange>(), 12);
+        assert_eq!(mem::align_of::<FunctionRange>(), 4);
+
+        assert_eq!(mem::size_of::<LineEntry>(), 12);
+        assert_eq!(mem::align_of::<LineEntry>(), 4);
+
+        assert_eq!(mem::size_of::<FileSource>(), 8);
+        assert_eq!(mem::align_of::<FileSource>(), 4);
     }
 }
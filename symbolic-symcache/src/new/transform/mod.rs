@@ -55,13 +55,66 @@ pub trait Transformer {
     }
 }
 
-// This is essentially just a newtype in order to implement `Debug`.
+/// A sequence of [`Transformer`]s that are applied in order.
+///
+/// Each transformer receives the output of the previous one, so that multiple deobfuscation steps
+/// can be combined, for example resolving hidden symbols with a
+/// [`BcSymbolMap`](symbolic_debuginfo::macho::BcSymbolMap) before applying an il2cpp line mapping.
+/// Since the pipeline is a [`Transformer`] itself, pipelines can be nested.
 #[derive(Default)]
-pub(crate) struct Transformers(pub Vec<Box<dyn Transformer>>);
+pub struct TransformerPipeline(Vec<Box<dyn Transformer>>);
 
-impl std::fmt::Debug for Transformers {
+impl TransformerPipeline {
+    /// Creates an empty pipeline, which passes all records through unchanged.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a [`Transformer`] to the end of this pipeline.
+    pub fn push<T>(&mut self, transformer: T)
+    where
+        T: Transformer + 'static,
+    {
+        self.0.push(Box::new(transformer));
+    }
+
+    /// Appends a [`Transformer`] to the end of this pipeline and returns the pipeline.
+    pub fn with<T>(mut self, transformer: T) -> Self
+    where
+        T: Transformer + 'static,
+    {
+        self.push(transformer);
+        self
+    }
+
+    /// Returns the number of transformers in this pipeline.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if this pipeline does not contain any transformers.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl Transformer for TransformerPipeline {
+    fn transform_function<'f>(&'f self, f: Function<'f>) -> Function<'f> {
+        self.0
+            .iter()
+            .fold(f, |f, transformer| transformer.transform_function(f))
+    }
+
+    fn transform_source_location<'f>(&'f self, sl: SourceLocation<'f>) -> SourceLocation<'f> {
+        self.0.iter().fold(sl, |sl, transformer| {
+            transformer.transform_source_location(sl)
+        })
+    }
+}
+
+impl std::fmt::Debug for TransformerPipeline {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let len = self.0.len();
-        f.debug_tuple("Transformers").field(&len).finish()
+        f.debug_tuple("TransformerPipeline").field(&len).finish()
     }
 }
@@ -4,10 +4,14 @@
 use std::borrow::Cow;
 use std::collections::btree_map;
 use std::collections::{BTreeMap, HashMap};
+use std::convert::TryInto;
 use std::io::Write;
+use std::sync::mpsc;
+use std::thread;
 
 use indexmap::IndexSet;
 use symbolic_common::{Arch, DebugId};
+use symbolic_debuginfo::sourcebundle::SourceBundle;
 use symbolic_debuginfo::{DebugSession, Function, ObjectLike, Symbol};
 
 #[cfg(feature = "il2cpp")]
@@ -15,7 +19,9 @@ use symbolic_common::Language;
 #[cfg(feature = "il2cpp")]
 use symbolic_il2cpp::usym::{UsymSourceRecord, UsymSymbols};
 
-use super::{raw, transform};
+use super::progress::Observer;
+use super::transform::{self, Transformer};
+use super::{raw, CancellationToken, ProgressObserver, ProgressStage};
 use crate::{SymCacheError, SymCacheErrorKind};
 
 /// The SymCache Converter.
@@ -30,7 +36,7 @@ pub struct SymCacheConverter {
     arch: Arch,
 
     /// A list of transformers that are used to transform each function / source location.
-    transformers: transform::Transformers,
+    transformers: transform::TransformerPipeline,
 
     /// The concatenation of all strings that have been added to this `Converter`.
     string_bytes: Vec<u8>,
@@ -49,6 +55,43 @@ pub struct SymCacheConverter {
     /// by the start address of the next range.
     ranges: BTreeMap<u32, raw::SourceLocation>,
 
+    /// Whether [`raw::DebugEntity`]s should be recorded and serialized.
+    record_debug_entities: bool,
+    /// The [`raw::DebugEntity`]s of the entries in `source_locations`, in the same order.
+    source_location_entities: Vec<raw::DebugEntity>,
+    /// A map from code ranges to the [`raw::DebugEntity`]s of their source locations.
+    range_entities: BTreeMap<u32, raw::DebugEntity>,
+    /// A map from code ranges to the [`raw::RangeProvenance`] describing where they came from.
+    range_provenances: BTreeMap<u32, raw::RangeProvenance>,
+
+    /// Whether only the symbol table should be processed, skipping debug information.
+    symbols_only: bool,
+
+    /// Whether [`raw::FunctionRange`]s should be serialized to look up functions by name.
+    function_name_index: bool,
+
+    /// Whether [`raw::LineEntry`]s should be serialized to look up addresses by file and line.
+    line_index: bool,
+
+    /// The [`raw::FileSource`]s of files in an associated source bundle, by their full path.
+    file_sources: HashMap<String, raw::FileSource>,
+
+    /// The maximum nesting depth of inlinees, deeper inlinees are replaced by synthetic records.
+    max_inline_depth: Option<usize>,
+
+    /// Whether outlined functions should be attributed to their callers.
+    collapse_outlined_functions: bool,
+    /// The end addresses and [`raw::Function`] indices of outlined functions, by their start
+    /// address.
+    outlined_functions: BTreeMap<u32, (u32, u32)>,
+    /// The return addresses of calls, by the address of the called function.
+    call_sites: HashMap<u32, Vec<u32>>,
+
+    /// Receives progress updates while processing objects and serializing.
+    observer: Observer,
+    /// Cancels processing of objects when triggered.
+    cancellation_token: Option<CancellationToken>,
+
     /// This is highest addr that we know is outside of a valid function.
     /// Functions have an explicit end, while Symbols implicitly extend to infinity.
     /// In case the highest addr belongs to a Symbol, this will be `None` and the SymCache
@@ -70,7 +113,7 @@ impl SymCacheConverter {
     where
         T: transform::Transformer + 'static,
     {
-        self.transformers.0.push(Box::new(t));
+        self.transformers.push(t);
     }
 
     /// Sets the CPU architecture of this SymCache.
@@ -83,6 +126,140 @@ impl SymCacheConverter {
         self.debug_id = debug_id;
     }
 
+    /// Sets whether the debug information entity of each source location should be recorded.
+    ///
+    /// When enabled, the SymCache contains a reference to the DWARF DIE, PDB symbol or Breakpad
+    /// `FUNC` record that produced each source location. This allows tracing back lookup results
+    /// to the original debug information. Defaults to `false`.
+    pub fn set_record_debug_entities(&mut self, record: bool) {
+        self.record_debug_entities = record;
+    }
+
+    /// Sets whether the SymCache should be built from the symbol table only.
+    ///
+    /// When enabled, [`process_object`](Self::process_object) skips debug information entirely,
+    /// which is considerably faster but yields a SymCache without inlinees, file names and line
+    /// numbers. Such SymCaches are marked in their header, see [`SymCache::is_symbols_only`].
+    /// Defaults to `false`.
+    ///
+    /// [`SymCache::is_symbols_only`]: crate::SymCache::is_symbols_only
+    pub fn set_symbols_only(&mut self, symbols_only: bool) {
+        self.symbols_only = symbols_only;
+    }
+
+    /// Sets whether an index to look up functions by name should be serialized.
+    ///
+    /// When enabled, the SymCache contains the address ranges of every function sorted by name,
+    /// which allows to resolve function names to addresses via [`SymCache::find_function`].
+    /// Defaults to `false`.
+    ///
+    /// [`SymCache::find_function`]: crate::SymCache::find_function
+    pub fn set_function_name_index(&mut self, index: bool) {
+        self.function_name_index = index;
+    }
+
+    /// Sets whether an index to look up addresses by file and line should be serialized.
+    ///
+    /// When enabled, the SymCache contains the source locations of every line sorted by file and
+    /// line number, which allows to resolve source lines to addresses via
+    /// [`SymCache::find_line`]. Defaults to `false`.
+    ///
+    /// [`SymCache::find_line`]: crate::SymCache::find_line
+    pub fn set_line_index(&mut self, index: bool) {
+        self.line_index = index;
+    }
+
+    /// Records the locations of source files within a source bundle.
+    ///
+    /// When set, the SymCache contains the byte range of every file's contents in the bundle, so
+    /// that sources can be read with [`SourceBundle::source_by_range`] without matching paths,
+    /// see [`LineInfo::source_bundle_range`]. Files are matched by their full path when
+    /// serializing. Bundles larger than 4 GiB are not supported, files beyond that are skipped.
+    ///
+    /// [`LineInfo::source_bundle_range`]: crate::LineInfo::source_bundle_range
+    pub fn set_source_bundle(&mut self, bundle: &SourceBundle<'_>) -> Result<(), SymCacheError> {
+        let session = bundle
+            .debug_session()
+            .map_err(|e| SymCacheError::new(SymCacheErrorKind::BadDebugFile, e))?;
+
+        self.file_sources.clear();
+        for file in session.files() {
+            let path = file
+                .map_err(|e| SymCacheError::new(SymCacheErrorKind::BadDebugFile, e))?
+                .abs_path_str();
+            let range = session
+                .source_range_by_path(&path)
+                .map_err(|e| SymCacheError::new(SymCacheErrorKind::BadDebugFile, e))?;
+
+            let source = range.and_then(|range| {
+                Some(raw::FileSource {
+                    offset: range.start.try_into().ok()?,
+                    len: (range.end - range.start).try_into().ok()?,
+                })
+            });
+
+            if let Some(source) = source {
+                self.file_sources.insert(path, source);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Limits the nesting depth of inlinees written to this SymCache.
+    ///
+    /// Inlinees nested deeper than `max_depth` are collapsed into a single synthetic inlinee named
+    /// `… N frames inlined`, which tells the number of frames it replaces. Lookups of addresses
+    /// within such inlinees yield this record as their innermost frame. See
+    /// [`Function::limit_inline_depth`] for details. There is no limit by default.
+    pub fn set_max_inline_depth(&mut self, max_depth: Option<usize>) {
+        self.max_inline_depth = max_depth;
+    }
+
+    /// Sets whether functions created by the machine outliner should be attributed to their
+    /// caller.
+    ///
+    /// The LLVM machine outliner moves instruction sequences shared by multiple functions into
+    /// functions named `OUTLINED_FUNCTION_N`, which clutter stack traces. When enabled, the code
+    /// of an outlined function with a single caller is looked up as the call site in that caller,
+    /// using call site information from DWARF. Outlined functions shared by multiple callers
+    /// cannot be attributed to one of them, and are looked up as the outlined function without
+    /// file, line or inlinees instead. Both have [`Provenance::Outlined`]. Outlined functions
+    /// without call site information are not changed. Defaults to `false`.
+    ///
+    /// [`Provenance::Outlined`]: crate::Provenance::Outlined
+    pub fn set_collapse_outlined_functions(&mut self, collapse: bool) {
+        self.collapse_outlined_functions = collapse;
+    }
+
+    /// Sets a [`ProgressObserver`] that is notified while processing objects and serializing.
+    ///
+    /// The observer is called after every top-level function and symbol processed by
+    /// [`process_object`](Self::process_object), and after every section written by
+    /// [`serialize`](Self::serialize).
+    pub fn set_progress_observer<O>(&mut self, observer: O)
+    where
+        O: ProgressObserver + 'static,
+    {
+        self.observer.0 = Some(Box::new(observer));
+    }
+
+    /// Sets a [`CancellationToken`] to abort [`process_object`](Self::process_object).
+    ///
+    /// The token is checked between top-level functions and symbols. Once it is cancelled,
+    /// processing stops with [`SymCacheErrorKind::Cancelled`] and the converter must be discarded.
+    pub fn set_cancellation_token(&mut self, token: Option<CancellationToken>) {
+        self.cancellation_token = token;
+    }
+
+    /// Returns an error if the conversion has been cancelled.
+    fn check_cancelled(&self) -> Result<(), SymCacheError> {
+        match self.cancellation_token {
+            Some(ref token) if token.is_cancelled() => Err(SymCacheErrorKind::Cancelled.into()),
+            _ => Ok(()),
+        }
+    }
+
     /// Insert a string into this converter.
     ///
     /// If the string was already present, it is not added again. A newly added string
@@ -117,36 +294,141 @@ impl SymCacheConverter {
 
     /// This processes the given [`ObjectLike`] object, collecting all its functions and line
     /// information into the converter.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub fn process_object<'d, 'o, O>(&mut self, object: &'o O) -> Result<(), SymCacheError>
     where
         O: ObjectLike<'d, 'o>,
         O::Error: std::error::Error + Send + Sync + 'static,
     {
-        let session = object
-            .debug_session()
-            .map_err(|e| SymCacheError::new(SymCacheErrorKind::BadDebugFile, e))?;
+        if !self.symbols_only {
+            let session = object
+                .debug_session()
+                .map_err(|e| SymCacheError::new(SymCacheErrorKind::BadDebugFile, e))?;
+
+            for (index, function) in session.functions().enumerate() {
+                self.check_cancelled()?;
+                let function =
+                    function.map_err(|e| SymCacheError::new(SymCacheErrorKind::BadDebugFile, e))?;
+
+                self.process_symbolic_function(&function);
+                self.observer
+                    .report(ProgressStage::Functions, index as u64 + 1, None);
+            }
+        }
+
+        self.process_object_symbols(object)
+    }
+
+    /// Processes the given [`ObjectLike`] object like [`process_object`](Self::process_object),
+    /// but parses its debug information on a separate thread.
+    ///
+    /// Parsing and conversion run as a two-stage pipeline: a background thread reads functions
+    /// from the object's debug session while the calling thread adds them to the converter. The
+    /// stages are connected by a bounded channel that holds at most `capacity` parsed functions,
+    /// so memory usage stays predictable even if conversion falls behind. A `capacity` of `0`
+    /// hands over every function directly.
+    ///
+    /// The resulting SymCache is identical to the one produced by `process_object`. Progress is
+    /// reported and cancellation is checked on the calling thread.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, object)))]
+    pub fn process_object_pipelined<'d, 'o, O>(
+        &mut self,
+        object: &'o O,
+        capacity: usize,
+    ) -> Result<(), SymCacheError>
+    where
+        O: ObjectLike<'d, 'o> + Sync,
+        O::Error: std::error::Error + Send + Sync + 'static,
+    {
+        if !self.symbols_only {
+            let token = self.cancellation_token.clone();
+
+            thread::scope(|scope| {
+                let (sender, receiver) = mpsc::sync_channel(capacity);
+
+                scope.spawn(move || {
+                    let session = match object.debug_session() {
+                        Ok(session) => session,
+                        Err(e) => {
+                            let error = SymCacheError::new(SymCacheErrorKind::BadDebugFile, e);
+                            sender.send(Err(error)).ok();
+                            return;
+                        }
+                    };
 
-        for function in session.functions() {
-            let function =
-                function.map_err(|e| SymCacheError::new(SymCacheErrorKind::BadDebugFile, e))?;
+                    for function in session.functions() {
+                        if matches!(token, Some(ref token) if token.is_cancelled()) {
+                            break;
+                        }
 
-            self.process_symbolic_function(&function);
+                        let function = function
+                            .map(Function::into_owned)
+                            .map_err(|e| SymCacheError::new(SymCacheErrorKind::BadDebugFile, e));
+
+                        // Stop parsing after an error or once the receiving side has hung up.
+                        let failed = function.is_err();
+                        if sender.send(function).is_err() || failed {
+                            break;
+                        }
+                    }
+                });
+
+                // Returning early drops the receiver, which in turn stops the parser thread.
+                for (index, function) in receiver.into_iter().enumerate() {
+                    self.check_cancelled()?;
+                    self.process_symbolic_function(&function?);
+                    self.observer
+                        .report(ProgressStage::Functions, index as u64 + 1, None);
+                }
+
+                Ok::<_, SymCacheError>(())
+            })?;
+
+            // The parser thread stops silently when cancelled.
+            self.check_cancelled()?;
         }
 
-        for symbol in object.symbols() {
+        self.process_object_symbols(object)
+    }
+
+    /// Processes the symbol table of the given [`ObjectLike`] object.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    fn process_object_symbols<'d, 'o, O>(&mut self, object: &'o O) -> Result<(), SymCacheError>
+    where
+        O: ObjectLike<'d, 'o>,
+    {
+        let symbols = object.symbols();
+        let total = symbols.size_hint().1.map(|total| total as u64);
+        for (index, symbol) in symbols.enumerate() {
+            self.check_cancelled()?;
             self.process_symbolic_symbol(&symbol);
+            self.observer
+                .report(ProgressStage::Symbols, index as u64 + 1, total);
         }
 
         Ok(())
     }
 
     pub fn process_symbolic_function(&mut self, function: &Function<'_>) {
+        match self.max_inline_depth {
+            Some(max_depth) if function.inline_depth() > max_depth => {
+                let mut function = function.clone();
+                function.limit_inline_depth(max_depth);
+                self.process_function_tree(&function);
+            }
+            _ => self.process_function_tree(function),
+        }
+    }
+
+    /// Processes a function and its inlinees after the inline depth has been limited.
+    fn process_function_tree(&mut self, function: &Function<'_>) {
         // skip over empty functions or functions whose address is too large to fit in a u32
         if function.size == 0 || function.address > u32::MAX as u64 {
             return;
         }
 
-        let comp_dir = std::str::from_utf8(function.compilation_dir).ok();
+        let comp_dir = std::str::from_utf8(&function.compilation_dir).ok();
+        let entity = raw::DebugEntity::from(function.entity);
 
         let entry_pc = if function.inline {
             u32::MAX
@@ -156,13 +438,11 @@ impl SymCacheConverter {
 
         let function_idx = {
             let language = function.name.language();
-            let mut function = transform::Function {
+            let function = transform::Function {
                 name: function.name.as_str().into(),
                 comp_dir: comp_dir.map(Into::into),
             };
-            for transformer in &self.transformers.0 {
-                function = transformer.transform_function(function);
-            }
+            let function = self.transformers.transform_function(function);
 
             let string_bytes = &mut self.string_bytes;
             let strings = &mut self.strings;
@@ -181,8 +461,12 @@ impl SymCacheConverter {
             fun_idx as u32
         };
 
+        if self.collapse_outlined_functions {
+            self.record_outlining(function, function_idx);
+        }
+
         for line in &function.lines {
-            let mut location = transform::SourceLocation {
+            let location = transform::SourceLocation {
                 file: transform::File {
                     name: line.file.name_str(),
                     directory: Some(line.file.dir_str()),
@@ -190,9 +474,7 @@ impl SymCacheConverter {
                 },
                 line: line.line as u32,
             };
-            for transformer in &self.transformers.0 {
-                location = transformer.transform_source_location(location);
-            }
+            let location = self.transformers.transform_source_location(location);
 
             let string_bytes = &mut self.string_bytes;
             let strings = &mut self.strings;
@@ -218,7 +500,8 @@ impl SymCacheConverter {
                 inlined_into_idx: u32::MAX,
             };
 
-            match self.ranges.entry(line.address as u32) {
+            let address = line.address as u32;
+            match self.ranges.entry(address) {
                 btree_map::Entry::Vacant(entry) => {
                     if function.inline {
                         // BUG:
@@ -232,9 +515,15 @@ impl SymCacheConverter {
                         let caller_source_location = entry.get().clone();
 
                         let mut callee_source_location = source_location;
-                        let (inlined_into_idx, _) =
+                        let (inlined_into_idx, is_new) =
                             self.source_locations.insert_full(caller_source_location);
 
+                        if self.record_debug_entities && is_new {
+                            let caller_entity = self.range_entities.get(&address).copied();
+                            self.source_location_entities
+                                .push(caller_entity.unwrap_or(raw::NO_DEBUG_ENTITY));
+                        }
+
                         callee_source_location.inlined_into_idx = inlined_into_idx as u32;
                         entry.insert(callee_source_location);
                     } else {
@@ -245,39 +534,141 @@ impl SymCacheConverter {
                     }
                 }
             }
+
+            if self.record_debug_entities {
+                self.range_entities.insert(address, entity);
+            }
+            self.range_provenances
+                .insert(address, raw::RangeProvenance(raw::PROVENANCE_LINE_INFO));
         }
 
-        // add the bare minimum of information for the function if there isn't any.
-        self.ranges.entry(entry_pc).or_insert(raw::SourceLocation {
-            file_idx: u32::MAX,
-            line: 0,
-            function_idx,
-            inlined_into_idx: u32::MAX,
-        });
+        // add the bare minimum of information for the function if there isn't any. Functions
+        // split into multiple parts need this at the start of every part.
+        let part_starts = function
+            .ranges
+            .iter()
+            .map(|range| range.start as u32)
+            .filter(|_| !function.inline);
+
+        for address in std::iter::once(entry_pc).chain(part_starts) {
+            if let btree_map::Entry::Vacant(entry) = self.ranges.entry(address) {
+                entry.insert(raw::SourceLocation {
+                    file_idx: u32::MAX,
+                    line: 0,
+                    function_idx,
+                    inlined_into_idx: u32::MAX,
+                });
+
+                if self.record_debug_entities {
+                    self.range_entities.insert(address, entity);
+                }
+                self.range_provenances
+                    .insert(address, raw::RangeProvenance(raw::PROVENANCE_PADDING));
+            }
+        }
 
         for inlinee in &function.inlinees {
-            self.process_symbolic_function(inlinee);
+            self.process_function_tree(inlinee);
         }
 
-        let function_end = function.end_address() as u32;
+        let function_end = function.ranges_end() as u32;
         let last_addr = self.last_addr.get_or_insert(0);
         if function_end > *last_addr {
             *last_addr = function_end;
         }
     }
 
+    /// Records call sites and the ranges of outlined functions for
+    /// [`collapse_outlined`](Self::collapse_outlined).
+    fn record_outlining(&mut self, function: &Function<'_>, function_idx: u32) {
+        if !function.inline && function.is_outlined() {
+            let end = function.ranges_end() as u32;
+            self.outlined_functions
+                .insert(function.address as u32, (end, function_idx));
+        }
+
+        for call_site in &function.call_sites {
+            if let Some(target) = call_site.target {
+                let return_address = call_site.return_address as u32;
+                self.call_sites
+                    .entry(target as u32)
+                    .or_default()
+                    .push(return_address);
+            }
+        }
+    }
+
+    /// Replaces the ranges of outlined functions with the source location of their call site.
+    ///
+    /// The call site is only known if the outlined function is called from a single location.
+    /// Outlined functions shared by multiple call sites are not attributed to any caller, and
+    /// their ranges are replaced by the outlined function without file and line information.
+    fn collapse_outlined(&mut self) {
+        for (&start, &(end, function_idx)) in &self.outlined_functions {
+            let mut return_addresses = match self.call_sites.get(&start) {
+                Some(return_addresses) => return_addresses.clone(),
+                None => continue,
+            };
+
+            return_addresses.sort_unstable();
+            return_addresses.dedup();
+            let (location, entity) = match return_addresses[..] {
+                [return_address] => {
+                    // The call instruction precedes the return address.
+                    let call_address = return_address.saturating_sub(1);
+                    if (start..end).contains(&call_address) {
+                        continue;
+                    }
+
+                    match self.ranges.range(..=call_address).next_back() {
+                        Some((&address, location)) => {
+                            (location.clone(), self.range_entities.get(&address).copied())
+                        }
+                        None => continue,
+                    }
+                }
+                _ => {
+                    let location = raw::SourceLocation {
+                        file_idx: u32::MAX,
+                        line: 0,
+                        function_idx,
+                        inlined_into_idx: u32::MAX,
+                    };
+                    (location, self.range_entities.get(&start).copied())
+                }
+            };
+
+            let covered = self
+                .ranges
+                .range(start..end)
+                .map(|(&address, _)| address)
+                .collect::<Vec<_>>();
+
+            for address in covered {
+                self.ranges.remove(&address);
+                self.range_entities.remove(&address);
+                self.range_provenances.remove(&address);
+            }
+
+            self.ranges.insert(start, location);
+            if let Some(entity) = entity {
+                self.range_entities.insert(start, entity);
+            }
+            self.range_provenances
+                .insert(start, raw::RangeProvenance(raw::PROVENANCE_OUTLINED));
+        }
+    }
+
     pub fn process_symbolic_symbol(&mut self, symbol: &Symbol<'_>) {
         let name_idx = {
-            let mut function = transform::Function {
+            let function = transform::Function {
                 name: match symbol.name {
                     Some(ref name) => name.clone(),
                     None => return,
                 },
                 comp_dir: None,
             };
-            for transformer in &self.transformers.0 {
-                function = transformer.transform_function(function);
-            }
+            let function = self.transformers.transform_function(function);
 
             Self::insert_string(&mut self.string_bytes, &mut self.strings, &function.name)
         };
@@ -298,6 +689,10 @@ impl SymCacheConverter {
                     function_idx,
                     inlined_into_idx: u32::MAX,
                 });
+                self.range_provenances.insert(
+                    symbol.address as u32,
+                    raw::RangeProvenance(raw::PROVENANCE_SYMBOL),
+                );
             }
             btree_map::Entry::Occupied(entry) => {
                 // ASSUMPTION:
@@ -341,13 +736,11 @@ impl SymCacheConverter {
             let identifier = Some((record.native_file, record.native_symbol));
             if identifier != curr_id {
                 function_idx = {
-                    let mut function = transform::Function {
+                    let function = transform::Function {
                         name: record.managed_symbol.clone(),
                         comp_dir: None,
                     };
-                    for transformer in &self.transformers.0 {
-                        function = transformer.transform_function(function);
-                    }
+                    let function = self.transformers.transform_function(function);
 
                     let string_bytes = &mut self.string_bytes;
                     let strings = &mut self.strings;
@@ -364,7 +757,7 @@ impl SymCacheConverter {
             }
 
             let managed_dir = Some(record.managed_file_info.dir_str()).filter(|d| !d.is_empty());
-            let mut location = transform::SourceLocation {
+            let location = transform::SourceLocation {
                 file: transform::File {
                     name: record.managed_file_info.name_str(),
                     directory: managed_dir,
@@ -372,9 +765,7 @@ impl SymCacheConverter {
                 },
                 line: record.managed_line,
             };
-            for transformer in &self.transformers.0 {
-                location = transformer.transform_source_location(location);
-            }
+            let location = self.transformers.transform_source_location(location);
 
             let string_bytes = &mut self.string_bytes;
             let strings = &mut self.strings;
@@ -413,6 +804,8 @@ impl SymCacheConverter {
                     entry.insert(source_location);
                 }
             }
+            self.range_provenances
+                .insert(address, raw::RangeProvenance(raw::PROVENANCE_LINE_INFO));
             curr_id = identifier;
         }
 
@@ -425,9 +818,14 @@ impl SymCacheConverter {
     /// Serialize the converted data.
     ///
     /// This writes the SymCache binary format into the given [`Write`].
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub fn serialize<W: Write>(mut self, writer: &mut W) -> std::io::Result<()> {
         let mut writer = WriteWrapper::new(writer);
 
+        if self.collapse_outlined_functions {
+            self.collapse_outlined();
+        }
+
         // Insert a trailing sentinel source location in case we have a definite end addr
         if let Some(last_addr) = self.last_addr {
             // TODO: to be extra safe, we might check that `last_addr` is indeed larger than
@@ -447,9 +845,55 @@ impl SymCacheConverter {
         let num_functions = self.functions.len() as u32;
         let num_source_locations = (self.source_locations.len() + self.ranges.len()) as u32;
         let num_ranges = self.ranges.len() as u32;
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            files = num_files,
+            functions = num_functions,
+            source_locations = num_source_locations,
+            ranges = num_ranges,
+            "writing symcache"
+        );
         let string_bytes = self.string_bytes.len() as u32;
+        let num_debug_entities = if self.record_debug_entities {
+            num_source_locations
+        } else {
+            0
+        };
+
+        let function_ranges = if self.function_name_index {
+            self.function_ranges()
+        } else {
+            Vec::new()
+        };
+        let num_function_ranges = function_ranges.len() as u32;
+        let line_entries = if self.line_index {
+            self.line_entries()
+        } else {
+            Vec::new()
+        };
+        let num_line_entries = line_entries.len() as u32;
+        let file_sources = if self.file_sources.is_empty() {
+            Vec::new()
+        } else {
+            self.file_sources()
+        };
+        let num_range_provenances = if self.range_provenances.is_empty() {
+            0
+        } else {
+            num_ranges
+        };
+
+        let mut flags = if self.symbols_only {
+            raw::HEADER_FLAG_SYMBOLS_ONLY
+        } else {
+            0
+        };
+        if !file_sources.is_empty() {
+            flags |= raw::HEADER_FLAG_FILE_SOURCES;
+        }
 
-        let header = raw::Header {
+        let mut header = raw::Header {
             magic: raw::SYMCACHE_MAGIC,
             version: raw::SYMCACHE_VERSION,
 
@@ -461,37 +905,274 @@ impl SymCacheConverter {
             num_source_locations,
             num_ranges,
             string_bytes,
-            _reserved: [0; 16],
+            num_debug_entities,
+            flags,
+            num_function_ranges,
+            checksum: 0,
+            num_range_provenances,
+            num_line_entries,
         };
 
+        // The checksum covers everything after the header, so the payload is written twice: once
+        // to compute the checksum and once into the actual writer. Both passes start at the same
+        // position, so that they produce identical padding.
+        let mut header_size = std::mem::size_of::<raw::Header>();
+        header_size += raw::align_to_eight(header_size);
+
+        let mut checksum = WriteWrapper {
+            writer: ChecksumWriter::default(),
+            position: header_size,
+        };
+        self.write_payload(
+            &mut checksum,
+            &function_ranges,
+            &line_entries,
+            &file_sources,
+            |_| (),
+        )?;
+        header.checksum = checksum.writer.0.finalize();
+
+        // The checksum pass has already computed the final size of the SymCache.
+        let total = Some(checksum.position as u64);
+        let mut observer = std::mem::take(&mut self.observer);
+
         writer.write(&[header])?;
         writer.align()?;
+        observer.report(ProgressStage::Serialize, writer.position as u64, total);
+        self.write_payload(
+            &mut writer,
+            &function_ranges,
+            &line_entries,
+            &file_sources,
+            |position| observer.report(ProgressStage::Serialize, position as u64, total),
+        )?;
 
-        for f in self.files {
-            writer.write(&[f])?;
+        Ok(())
+    }
+
+    /// Writes all sections following the header.
+    ///
+    /// `on_section` is called with the current position after every section.
+    fn write_payload<W: Write, F: FnMut(usize)>(
+        &self,
+        writer: &mut WriteWrapper<W>,
+        function_ranges: &[raw::FunctionRange],
+        line_entries: &[raw::LineEntry],
+        file_sources: &[raw::FileSource],
+        mut on_section: F,
+    ) -> std::io::Result<()> {
+        for f in &self.files {
+            writer.write(std::slice::from_ref(f))?;
         }
         writer.align()?;
+        on_section(writer.position);
 
-        for f in self.functions {
-            writer.write(&[f])?;
+        for f in &self.functions {
+            writer.write(std::slice::from_ref(f))?;
         }
         writer.align()?;
+        on_section(writer.position);
 
-        for s in self.source_locations {
-            writer.write(&[s])?;
+        for s in &self.source_locations {
+            writer.write(std::slice::from_ref(s))?;
         }
         for s in self.ranges.values() {
             writer.write(std::slice::from_ref(s))?;
         }
         writer.align()?;
+        on_section(writer.position);
 
         for r in self.ranges.keys() {
             writer.write(&[raw::Range(*r)])?;
         }
         writer.align()?;
+        on_section(writer.position);
 
         writer.write(&self.string_bytes)?;
+        on_section(writer.position);
+
+        if self.record_debug_entities {
+            writer.align()?;
+
+            writer.write(&self.source_location_entities)?;
+            for r in self.ranges.keys() {
+                let entity = self.range_entities.get(r).copied();
+                writer.write(&[entity.unwrap_or(raw::NO_DEBUG_ENTITY)])?;
+            }
+            on_section(writer.position);
+        }
+
+        if !function_ranges.is_empty() {
+            writer.align()?;
+
+            writer.write(function_ranges)?;
+            on_section(writer.position);
+        }
+
+        if !line_entries.is_empty() {
+            writer.align()?;
+
+            writer.write(line_entries)?;
+            on_section(writer.position);
+        }
+
+        if !file_sources.is_empty() {
+            writer.align()?;
+
+            writer.write(file_sources)?;
+            on_section(writer.position);
+        }
+
+        // Provenances are single bytes and need no alignment.
+        if !self.range_provenances.is_empty() {
+            for r in self.ranges.keys() {
+                let provenance = self.range_provenances.get(r).copied();
+                writer.write(&[
+                    provenance.unwrap_or(raw::RangeProvenance(raw::PROVENANCE_UNKNOWN))
+                ])?;
+            }
+            on_section(writer.position);
+        }
+
+        Ok(())
+    }
+
+    /// Resolves a string offset to the string previously inserted into `string_bytes`.
+    fn get_string(&self, offset: u32) -> &str {
+        let start = offset as usize + std::mem::size_of::<u32>();
+        let len = match self.string_bytes.get(offset as usize..start) {
+            Some(bytes) => u32::from_ne_bytes(bytes.try_into().unwrap()) as usize,
+            None => return "",
+        };
+
+        self.string_bytes
+            .get(start..start + len)
+            .and_then(|bytes| std::str::from_utf8(bytes).ok())
+            .unwrap_or_default()
+    }
+
+    /// Computes the address ranges of all functions, sorted by function name and start address.
+    ///
+    /// A range is attributed to its function and to all functions it has been inlined into.
+    fn function_ranges(&self) -> Vec<raw::FunctionRange> {
+        let mut function_ranges: Vec<raw::FunctionRange> = Vec::new();
+        let mut last_ranges = HashMap::<u32, usize>::new();
+
+        let mut ranges = self.ranges.iter().peekable();
+        while let Some((&start, source_location)) = ranges.next() {
+            let end = ranges.peek().map_or(u32::MAX, |(&next, _)| next);
+
+            let mut next_location = Some(source_location);
+            while let Some(location) = next_location {
+                if location.function_idx == u32::MAX {
+                    break;
+                }
+
+                // Extend the previous range of this function if it ends right here. This also
+                // deduplicates functions that occur multiple times in one inline chain.
+                match last_ranges.get(&location.function_idx) {
+                    Some(&idx) if function_ranges[idx].end >= start => {
+                        let range = &mut function_ranges[idx];
+                        range.end = range.end.max(end);
+                    }
+                    _ => {
+                        last_ranges.insert(location.function_idx, function_ranges.len());
+                        function_ranges.push(raw::FunctionRange {
+                            function_idx: location.function_idx,
+                            start,
+                            end,
+                        });
+                    }
+                }
+
+                next_location = self
+                    .source_locations
+                    .get_index(location.inlined_into_idx as usize);
+            }
+        }
+
+        function_ranges.retain(|range| {
+            let function = &self.functions[range.function_idx as usize];
+            function.name_offset != u32::MAX
+        });
+
+        function_ranges.sort_by(|a, b| {
+            let name_a = self.get_string(self.functions[a.function_idx as usize].name_offset);
+            let name_b = self.get_string(self.functions[b.function_idx as usize].name_offset);
+            // Break ties on the function index to keep the output deterministic.
+            (name_a, a.start, a.function_idx).cmp(&(name_b, b.start, b.function_idx))
+        });
+
+        function_ranges
+    }
+
+    /// Computes the location of every file in the source bundle, in the order of `files`.
+    fn file_sources(&self) -> Vec<raw::FileSource> {
+        self.files
+            .iter()
+            .map(|file| {
+                let comp_dir = self.get_string(file.comp_dir_offset);
+                let directory = self.get_string(file.directory_offset);
+                let path_name = self.get_string(file.path_name_offset);
+
+                // This must match `File::full_path` of the lookup.
+                let prefix = symbolic_common::join_path(comp_dir, directory);
+                let full_path = symbolic_common::join_path(&prefix, path_name);
+                let full_path = symbolic_common::clean_path(&full_path);
+
+                self.file_sources
+                    .get(full_path.as_ref())
+                    .copied()
+                    .unwrap_or(raw::NO_FILE_SOURCE)
+            })
+            .collect()
+    }
+
+    /// Computes the lines covered by all ranges, sorted by file, line and source location.
+    ///
+    /// A range covers the line of its own source location and the lines of all source locations
+    /// it has been inlined into.
+    fn line_entries(&self) -> Vec<raw::LineEntry> {
+        let mut line_entries = Vec::new();
+        let source_location_start = self.source_locations.len() as u32;
+
+        for (idx, source_location) in self.ranges.values().enumerate() {
+            let source_location_idx = source_location_start + idx as u32;
+
+            let mut next_location = Some(source_location);
+            while let Some(location) = next_location {
+                if location.file_idx != u32::MAX && location.line != 0 {
+                    line_entries.push(raw::LineEntry {
+                        file_idx: location.file_idx,
+                        line: location.line,
+                        source_location_idx,
+                    });
+                }
+
+                next_location = self
+                    .source_locations
+                    .get_index(location.inlined_into_idx as usize);
+            }
+        }
+
+        // Recursive inlinees may refer to the same line more than once.
+        line_entries.sort();
+        line_entries.dedup();
+        line_entries
+    }
+}
+
+/// A [`Write`] implementation that computes the CRC32 checksum of all written data.
+#[derive(Default)]
+struct ChecksumWriter(crc32fast::Hasher);
+
+impl Write for ChecksumWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.update(buf);
+        Ok(buf.len())
+    }
 
+    fn flush(&mut self) -> std::io::Result<()> {
         Ok(())
     }
 }
@@ -1,10 +1,13 @@
 #![allow(deprecated)]
 
 use std::fmt;
+use std::ops::Range;
 
 use symbolic_common::{Arch, AsSelf, DebugId, Language, Name, NameMangling};
+use symbolic_debuginfo::DebugEntity;
 
 use crate::format;
+use crate::new::Provenance;
 use crate::SymCacheError;
 
 /// A platform independent symbolication cache.
@@ -307,6 +310,10 @@ impl<'a> SymCache<'a> {
             filename,
             base_dir,
             comp_dir: fun.comp_dir.read_str(self.data)?,
+            debug_entity: None,
+            provenance: Provenance::Unknown,
+            fallback_distance: None,
+            source_bundle_range: None,
         })
     }
 }
@@ -407,6 +414,10 @@ pub struct LineInfo<'a> {
     pub(crate) filename: &'a str,
     pub(crate) base_dir: &'a str,
     pub(crate) comp_dir: &'a str,
+    pub(crate) debug_entity: Option<DebugEntity>,
+    pub(crate) provenance: Provenance,
+    pub(crate) fallback_distance: Option<u64>,
+    pub(crate) source_bundle_range: Option<Range<u64>>,
 }
 
 impl<'a> LineInfo<'a> {
@@ -488,6 +499,48 @@ impl<'a> LineInfo<'a> {
     pub fn function_name(&self) -> Name<'a> {
         Name::new(self.symbol(), NameMangling::Unknown, self.language())
     }
+
+    /// The debug information entity this line was created from.
+    ///
+    /// This is only available in SymCaches that were written with debug entities enabled, see
+    /// [`SymCacheWriter::set_record_debug_entities`](crate::SymCacheWriter::set_record_debug_entities).
+    pub fn debug_entity(&self) -> Option<DebugEntity> {
+        self.debug_entity
+    }
+
+    /// The origin of the address range this line was looked up in.
+    ///
+    /// This distinguishes line information from ranges that only have a function or symbol name.
+    pub fn provenance(&self) -> Provenance {
+        self.provenance
+    }
+
+    /// Returns `true` if the looked up address lies outside of the function of this line.
+    ///
+    /// Such lines are returned by [`SymCache::lookup_with_fallback`](crate::SymCache::lookup_with_fallback)
+    /// for addresses that are not covered by any function.
+    pub fn is_fallback(&self) -> bool {
+        self.fallback_distance.is_some()
+    }
+
+    /// The distance of the looked up address from the end of the preceding function.
+    ///
+    /// This is only set for fallback lines, see [`is_fallback`](Self::is_fallback).
+    pub fn fallback_distance(&self) -> Option<u64> {
+        self.fallback_distance
+    }
+
+    /// The byte range of this line's source file within the associated source bundle.
+    ///
+    /// Pass the range to [`SourceBundle::source_by_range`] to read the file's contents without
+    /// matching its path. This is only available in SymCaches that were written with a source
+    /// bundle containing the file, see
+    /// [`SymCacheWriter::set_source_bundle`](crate::SymCacheWriter::set_source_bundle).
+    ///
+    /// [`SourceBundle::source_by_range`]: symbolic_debuginfo::sourcebundle::SourceBundle::source_by_range
+    pub fn source_bundle_range(&self) -> Option<Range<u64>> {
+        self.source_bundle_range.clone()
+    }
 }
 
 impl fmt::Display for LineInfo<'_> {
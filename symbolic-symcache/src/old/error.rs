@@ -1,8 +1,10 @@
 use std::error::Error;
-use std::fmt;
+use std::{fmt, io};
 
 use thiserror::Error;
 
+use symbolic_debuginfo::ObjectError;
+
 #[doc(hidden)]
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum ValueKind {
@@ -66,6 +68,9 @@ pub enum SymCacheErrorKind {
 
     /// Generic error when writing a symcache, most likely IO.
     WriteFailed,
+
+    /// The conversion was cancelled via a `CancellationToken`.
+    Cancelled,
 }
 
 impl fmt::Display for SymCacheErrorKind {
@@ -83,6 +88,7 @@ impl fmt::Display for SymCacheErrorKind {
             Self::ValueTooLarge(kind) => write!(f, "{} too large for symcache file format", kind),
             Self::TooManyValues(kind) => write!(f, "too many {}s for symcache", kind),
             Self::WriteFailed => write!(f, "failed to write symcache"),
+            Self::Cancelled => write!(f, "symcache conversion was cancelled"),
         }
     }
 }
@@ -118,3 +124,15 @@ impl From<SymCacheErrorKind> for SymCacheError {
         Self { kind, source: None }
     }
 }
+
+impl From<ObjectError> for SymCacheError {
+    fn from(error: ObjectError) -> Self {
+        Self::new(SymCacheErrorKind::BadDebugFile, error)
+    }
+}
+
+impl From<io::Error> for SymCacheError {
+    fn from(error: io::Error) -> Self {
+        Self::new(SymCacheErrorKind::WriteFailed, error)
+    }
+}
@@ -20,7 +20,7 @@ pub use crate::preamble::*;
     since = "8.6.0",
     note = "Use symbolic_symcache::SYMCACHE_VERSION instead"
 )]
-pub const SYMCACHE_VERSION: u32 = 7;
+pub const SYMCACHE_VERSION: u32 = 8;
 
 // Version history:
 //
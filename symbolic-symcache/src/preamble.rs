@@ -23,12 +23,20 @@ impl Preamble {
             return Err(SymCacheErrorKind::BadFileHeader.into());
         }
         // SAFETY: we checked that the buffer is large enough to fit a `Preamble`.
-        let preamble = unsafe { &*(buf.as_ptr() as *const Self) };
-        if preamble.magic != SYMCACHE_MAGIC {
+        let mut preamble = unsafe { *(buf.as_ptr() as *const Self) };
+
+        // Files written on a system with the opposite endianness start with the reversed magic.
+        let mut flipped = SYMCACHE_MAGIC;
+        flipped.reverse();
+
+        if preamble.magic == flipped {
+            preamble.magic = SYMCACHE_MAGIC;
+            preamble.version = preamble.version.swap_bytes();
+        } else if preamble.magic != SYMCACHE_MAGIC {
             return Err(SymCacheErrorKind::BadFileMagic.into());
         }
 
-        Ok(*preamble)
+        Ok(preamble)
     }
 }
 
@@ -57,6 +65,22 @@ mod tests {
         assert_eq!(Preamble::parse(&buf).unwrap(), preamble);
     }
 
+    #[test]
+    fn flipped_preamble() {
+        let mut magic = SYMCACHE_MAGIC;
+        magic.reverse();
+
+        let preamble = Preamble {
+            magic,
+            version: 1729u32.swap_bytes(),
+        };
+        let buf = write_preamble(&[preamble]);
+
+        let parsed = Preamble::parse(&buf).unwrap();
+        assert_eq!(parsed.magic, SYMCACHE_MAGIC);
+        assert_eq!({ parsed.version }, 1729);
+    }
+
     #[test]
     fn invalid_magic() {
         let preamble = Preamble {
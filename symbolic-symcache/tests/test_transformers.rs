@@ -4,7 +4,7 @@ use std::io::Cursor;
 use symbolic_common::{ByteView, SelfCell};
 use symbolic_debuginfo::macho::BcSymbolMap;
 use symbolic_debuginfo::Object;
-use symbolic_symcache::transform::{self, Transformer};
+use symbolic_symcache::transform::{self, Transformer, TransformerPipeline};
 use symbolic_symcache::{SymCache, SymCacheWriter};
 
 type Error = Box<dyn std::error::Error>;
@@ -89,3 +89,54 @@ fn test_transformer_symbolmap() -> Result<(), Error> {
 
     Ok(())
 }
+
+/// Prefixes function names, to verify the order in which transformers are applied.
+struct PrefixFunctions(&'static str);
+
+impl Transformer for PrefixFunctions {
+    fn transform_function<'f>(&'f self, mut f: transform::Function<'f>) -> transform::Function<'f> {
+        f.name = format!("{}{}", self.0, f.name).into();
+        f
+    }
+}
+
+#[test]
+fn test_transformer_pipeline() -> Result<(), Error> {
+    let buffer = ByteView::open(
+        "../symbolic-debuginfo/tests/fixtures/2d10c42f-591d-3265-b147-78ba0868073f.dwarf-hidden",
+    )?;
+    let object = Object::parse(&buffer)?;
+
+    let map_buffer = ByteView::open(
+        "../symbolic-debuginfo/tests/fixtures/c8374b6d-6e96-34d8-ae38-efaa5fec424f.bcsymbolmap",
+    )?;
+    let bc_symbol_map = OwnedBcSymbolMap(SelfCell::try_new(map_buffer, |s| unsafe {
+        BcSymbolMap::parse(&*s)
+    })?);
+
+    // The symbol map only resolves hidden names, so it has to run before the prefix is added.
+    let pipeline = TransformerPipeline::new()
+        .with(bc_symbol_map)
+        .with(TransformerPipeline::new().with(PrefixFunctions("app::")));
+    assert_eq!(pipeline.len(), 2);
+
+    let mut buffer = Vec::new();
+    let mut writer = SymCacheWriter::new(Cursor::new(&mut buffer))?;
+    writer.add_transformer(pipeline);
+    writer.process_object(&object)?;
+    let _ = writer.finish()?;
+
+    let cache = SymCache::parse(&buffer)?;
+    let sl = cache.lookup(0x5a74)?.next().unwrap()?;
+
+    assert_eq!(
+        sl.function_name(),
+        "app::-[SentryMessage initWithFormatted:]"
+    );
+    assert_eq!(
+        sl.abs_path(),
+        "/Users/philipphofmann/git-repos/sentry-cocoa/Sources/Sentry/SentryMessage.m"
+    );
+
+    Ok(())
+}
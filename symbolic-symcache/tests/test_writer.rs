@@ -1,9 +1,17 @@
+use std::cell::RefCell;
 use std::fmt;
 use std::io::Cursor;
-
-use symbolic_common::ByteView;
-use symbolic_debuginfo::Object;
-use symbolic_symcache::{SymCache, SymCacheWriter};
+use std::rc::Rc;
+
+use symbolic_common::{ByteView, Language, Name, NameMangling};
+use symbolic_debuginfo::sourcebundle::{SourceBundle, SourceBundleWriter, SourceFileInfo};
+use symbolic_debuginfo::{
+    CallSite, DebugEntity, FileInfo, Function, LineInfo, LineRecords, Object,
+};
+use symbolic_symcache::{
+    CancellationToken, Progress, ProgressStage, Provenance, SymCache, SymCacheErrorKind,
+    SymCacheWriter,
+};
 use symbolic_testutils::fixture;
 
 #[cfg(feature = "il2cpp")]
@@ -57,7 +65,7 @@ fn test_write_header_linux() -> Result<(), Error> {
     let symcache = SymCache::parse(&buffer)?;
     insta::assert_debug_snapshot!(symcache, @r###"
     SymCache {
-        version: 7,
+        version: 8,
         debug_id: DebugId {
             uuid: "c0bcc3f1-9827-fe65-3058-404b2831d9e6",
             appendix: 0,
@@ -97,7 +105,7 @@ fn test_write_header_macos() -> Result<(), Error> {
     let symcache = SymCache::parse(&buffer)?;
     insta::assert_debug_snapshot!(symcache, @r###"
     SymCache {
-        version: 7,
+        version: 8,
         debug_id: DebugId {
             uuid: "67e9247c-814e-392b-a027-dbde6748fcbf",
             appendix: 0,
@@ -223,6 +231,632 @@ fn test_lookup_modulo_u16() -> Result<(), Error> {
     Ok(())
 }
 
+#[test]
+fn test_debug_entities_breakpad() -> Result<(), Error> {
+    let data = ByteView::open(fixture("xul.sym"))?;
+    let object = Object::parse(&data)?;
+
+    let mut buffer = Vec::new();
+    let mut writer = SymCacheWriter::new(Cursor::new(&mut buffer))?;
+    writer.set_record_debug_entities(true);
+    writer.process_object(&object)?;
+    writer.finish()?;
+
+    let symcache = SymCache::parse(&buffer)?;
+    assert!(symcache.has_debug_entities());
+
+    let symbols = symcache.lookup(0xc6dd98)?.collect::<Vec<_>>()?;
+    let entity = symbols[0].debug_entity().expect("debug entity");
+    let offset = match entity {
+        DebugEntity::BreakpadFunc(offset) => offset as usize,
+        other => panic!("unexpected debug entity {:?}", other),
+    };
+    assert!(data[offset..].starts_with(b"FUNC "));
+
+    let ranges = symcache.ranges_for_debug_entity(entity);
+    assert!(ranges.iter().any(|range| range.contains(&0xc6dd98)));
+
+    Ok(())
+}
+
+#[test]
+fn test_debug_entities_dwarf() -> Result<(), Error> {
+    let data = ByteView::open(fixture("macos/crash.dSYM/Contents/Resources/DWARF/crash"))?;
+    let object = Object::parse(&data)?;
+
+    let mut buffer = Vec::new();
+    let mut writer = SymCacheWriter::new(Cursor::new(&mut buffer))?;
+    writer.set_record_debug_entities(true);
+    writer.process_object(&object)?;
+    writer.finish()?;
+
+    let symcache = SymCache::parse(&buffer)?;
+    let symbols = symcache.lookup(0x1ba0)?.collect::<Vec<_>>()?;
+    assert!(!symbols.is_empty());
+
+    for symbol in &symbols {
+        let entity = symbol.debug_entity().expect("debug entity");
+        assert!(matches!(entity, DebugEntity::DwarfDie(_)));
+
+        let ranges = symcache.ranges_for_debug_entity(entity);
+        assert!(ranges.iter().any(|range| range.contains(&0x1ba0)));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_debug_entities_disabled() -> Result<(), Error> {
+    let data = ByteView::open(fixture("xul.sym"))?;
+    let object = Object::parse(&data)?;
+
+    let mut buffer = Vec::new();
+    SymCacheWriter::write_object(&object, Cursor::new(&mut buffer))?;
+
+    let symcache = SymCache::parse(&buffer)?;
+    assert!(!symcache.has_debug_entities());
+
+    let symbols = symcache.lookup(0xc6dd98)?.collect::<Vec<_>>()?;
+    assert_eq!(symbols[0].debug_entity(), None);
+
+    Ok(())
+}
+
+#[test]
+fn test_symbols_only() -> Result<(), Error> {
+    let data = ByteView::open(fixture("macos/crash.dSYM/Contents/Resources/DWARF/crash"))?;
+    let object = Object::parse(&data)?;
+
+    let mut buffer = Vec::new();
+    SymCacheWriter::write_object(&object, Cursor::new(&mut buffer))?;
+    let symcache = SymCache::parse(&buffer)?;
+    assert!(!symcache.is_symbols_only());
+
+    let full = symcache.lookup(0x1ba0)?.collect::<Vec<_>>()?;
+    assert!(full[0].line() > 0);
+
+    let mut buffer = Vec::new();
+    let mut writer = SymCacheWriter::new(Cursor::new(&mut buffer))?;
+    writer.set_symbols_only(true);
+    writer.process_object(&object)?;
+    writer.finish()?;
+
+    let symcache = SymCache::parse(&buffer)?;
+    assert!(symcache.is_symbols_only());
+
+    let symbols = symcache.lookup(0x1ba0)?.collect::<Vec<_>>()?;
+    assert_eq!(symbols.len(), 1);
+    assert_eq!(symbols[0].line(), 0);
+    assert_eq!(symbols[0].symbol(), full.last().unwrap().symbol());
+
+    Ok(())
+}
+
+#[test]
+fn test_progress_observer() -> Result<(), Error> {
+    let data = ByteView::open(fixture("macos/crash.dSYM/Contents/Resources/DWARF/crash"))?;
+    let object = Object::parse(&data)?;
+
+    let updates = Rc::new(RefCell::new(Vec::<Progress>::new()));
+    let observed = updates.clone();
+
+    let mut buffer = Vec::new();
+    let mut writer = SymCacheWriter::new(Cursor::new(&mut buffer))?;
+    writer.set_progress_observer(move |progress| observed.borrow_mut().push(progress));
+    writer.process_object(&object)?;
+    writer.finish()?;
+
+    let updates = updates.borrow();
+    let functions: Vec<_> = updates
+        .iter()
+        .filter(|p| p.stage == ProgressStage::Functions)
+        .collect();
+    assert!(!functions.is_empty());
+    assert!(functions.iter().all(|p| p.total.is_none()));
+    assert!(functions
+        .windows(2)
+        .all(|w| w[0].processed < w[1].processed));
+
+    let symbols = updates
+        .iter()
+        .rfind(|p| p.stage == ProgressStage::Symbols)
+        .unwrap();
+    assert_eq!(symbols.processed, object.symbols().count() as u64);
+
+    let serialize = updates.last().unwrap();
+    assert_eq!(serialize.stage, ProgressStage::Serialize);
+    assert_eq!(serialize.processed, buffer.len() as u64);
+    assert_eq!(serialize.total, Some(buffer.len() as u64));
+
+    Ok(())
+}
+
+#[test]
+fn test_cancellation() -> Result<(), Error> {
+    let data = ByteView::open(fixture("macos/crash.dSYM/Contents/Resources/DWARF/crash"))?;
+    let object = Object::parse(&data)?;
+
+    let token = CancellationToken::new();
+    let handle = token.clone();
+    let processed = Rc::new(RefCell::new(0));
+    let observed = processed.clone();
+
+    let mut buffer = Vec::new();
+    let mut writer = SymCacheWriter::new(Cursor::new(&mut buffer))?;
+    writer.set_cancellation_token(Some(token));
+    writer.set_progress_observer(move |progress: Progress| {
+        *observed.borrow_mut() = progress.processed;
+        if progress.processed == 10 {
+            handle.cancel();
+        }
+    });
+
+    let error = writer.process_object(&object).unwrap_err();
+    assert_eq!(error.kind(), SymCacheErrorKind::Cancelled);
+    assert_eq!(*processed.borrow(), 10);
+
+    Ok(())
+}
+
+#[test]
+fn test_function_name_index() -> Result<(), Error> {
+    let data = ByteView::open(fixture("macos/crash.dSYM/Contents/Resources/DWARF/crash"))?;
+    let object = Object::parse(&data)?;
+
+    let mut buffer = Vec::new();
+    let mut writer = SymCacheWriter::new(Cursor::new(&mut buffer))?;
+    writer.set_record_debug_entities(true);
+    writer.set_function_name_index(true);
+    writer.process_object(&object)?;
+    writer.finish()?;
+
+    let symcache = SymCache::parse(&buffer)?;
+    assert!(symcache.has_function_name_index());
+    assert!(symcache.has_debug_entities());
+
+    // The address resolves to an inlined function, which must be found along with its caller.
+    let symbols = symcache.lookup(0x1060)?.collect::<Vec<_>>()?;
+    assert_eq!(symbols.len(), 3);
+
+    for symbol in &symbols {
+        let ranges = symcache.find_function(symbol.symbol());
+        assert!(ranges.iter().any(|range| range.contains(&0x1060)));
+    }
+
+    // The inlined function is also found at other call sites.
+    let ranges = symcache.find_function("_ZN15google_breakpad18MinidumpFileWriter4CopyEjPKvl");
+    assert!(ranges.len() > 1);
+
+    assert!(symcache.find_function("does_not_exist").is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_function_name_index_disabled() -> Result<(), Error> {
+    let data = ByteView::open(fixture("macos/crash.dSYM/Contents/Resources/DWARF/crash"))?;
+    let object = Object::parse(&data)?;
+
+    let mut buffer = Vec::new();
+    SymCacheWriter::write_object(&object, Cursor::new(&mut buffer))?;
+
+    let symcache = SymCache::parse(&buffer)?;
+    assert!(!symcache.has_function_name_index());
+
+    let symbols = symcache.lookup(0x1ba0)?.collect::<Vec<_>>()?;
+    assert!(symcache.find_function(symbols[0].symbol()).is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_line_index() -> Result<(), Error> {
+    let data = ByteView::open(fixture("macos/crash.dSYM/Contents/Resources/DWARF/crash"))?;
+    let object = Object::parse(&data)?;
+
+    let mut buffer = Vec::new();
+    let mut writer = SymCacheWriter::new(Cursor::new(&mut buffer))?;
+    writer.set_line_index(true);
+    writer.process_object(&object)?;
+    writer.finish()?;
+
+    let symcache = SymCache::parse(&buffer)?;
+    assert!(symcache.has_line_index());
+
+    // The address resolves to an inlined function, so it must be found via the call site lines of
+    // all its callers as well.
+    let symbols = symcache.lookup(0x1060)?.collect::<Vec<_>>()?;
+    assert_eq!(symbols.len(), 3);
+
+    for symbol in &symbols {
+        let ranges = symcache.find_line(&symbol.abs_path(), symbol.line());
+        assert!(ranges.iter().any(|range| range.contains(&0x1060)));
+    }
+
+    let path = symbols[0].abs_path();
+    assert!(symcache.find_line(&path, 0).is_empty());
+    assert!(symcache
+        .find_line("does_not_exist.cpp", symbols[0].line())
+        .is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_line_index_disabled() -> Result<(), Error> {
+    let data = ByteView::open(fixture("macos/crash.dSYM/Contents/Resources/DWARF/crash"))?;
+    let object = Object::parse(&data)?;
+
+    let mut buffer = Vec::new();
+    SymCacheWriter::write_object(&object, Cursor::new(&mut buffer))?;
+
+    let symcache = SymCache::parse(&buffer)?;
+    assert!(!symcache.has_line_index());
+
+    let symbols = symcache.lookup(0x1ba0)?.collect::<Vec<_>>()?;
+    assert!(symcache
+        .find_line(&symbols[0].abs_path(), symbols[0].line())
+        .is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_source_bundle_ranges() -> Result<(), Error> {
+    let data = ByteView::open(fixture("macos/crash.dSYM/Contents/Resources/DWARF/crash"))?;
+    let object = Object::parse(&data)?;
+
+    let mut buffer = Vec::new();
+    SymCacheWriter::write_object(&object, Cursor::new(&mut buffer))?;
+    let symcache = SymCache::parse(&buffer)?;
+    assert!(!symcache.has_file_sources());
+
+    // Only bundle the header containing the inlined function.
+    let symbols = symcache.lookup(0x1060)?.collect::<Vec<_>>()?;
+    assert_eq!(symbols.len(), 3);
+    assert_eq!(symbols[0].source_bundle_range(), None);
+    let path = symbols[1].abs_path();
+    assert!(path.ends_with("minidump_file_writer-inl.h"));
+
+    let mut bundle_buffer = Cursor::new(Vec::new());
+    let mut bundle_writer = SourceBundleWriter::start(&mut bundle_buffer)?;
+    let mut info = SourceFileInfo::new();
+    info.set_path(path.clone());
+    bundle_writer.add_file("minidump_file_writer-inl.h", &b"// inline"[..], info)?;
+    bundle_writer.finish()?;
+    let bundle_data = bundle_buffer.into_inner();
+    let bundle = SourceBundle::parse(&bundle_data)?;
+
+    let mut buffer = Vec::new();
+    let mut writer = SymCacheWriter::new(Cursor::new(&mut buffer))?;
+    writer.set_source_bundle(&bundle)?;
+    writer.process_object(&object)?;
+    writer.finish()?;
+
+    let symcache = SymCache::parse(&buffer)?;
+    assert!(symcache.has_file_sources());
+
+    let symbols = symcache.lookup(0x1060)?.collect::<Vec<_>>()?;
+    let ranges: Vec<_> = symbols
+        .iter()
+        .map(|symbol| symbol.source_bundle_range())
+        .collect();
+    assert!(ranges[0].is_none());
+    assert!(ranges[2].is_none());
+
+    let source = bundle.source_by_range(ranges[1].clone().unwrap())?;
+    assert_eq!(source, "// inline");
+
+    Ok(())
+}
+
+fn write_symcache(object: &Object<'_>) -> Result<Vec<u8>, Error> {
+    let mut buffer = Vec::new();
+    let mut writer = SymCacheWriter::new(Cursor::new(&mut buffer))?;
+    writer.set_record_debug_entities(true);
+    writer.set_function_name_index(true);
+    writer.process_object(object)?;
+    writer.finish()?;
+    Ok(buffer)
+}
+
+#[test]
+fn test_deterministic_output() -> Result<(), Error> {
+    let data = ByteView::open(fixture("macos/crash.dSYM/Contents/Resources/DWARF/crash"))?;
+    let object = Object::parse(&data)?;
+
+    let first = write_symcache(&object)?;
+    let second = write_symcache(&Object::parse(&data)?)?;
+    assert!(first == second, "SymCache output is not deterministic");
+
+    let symcache = SymCache::parse(&first)?;
+    assert!(matches!(symcache.checksum(), Some(checksum) if checksum != 0));
+
+    Ok(())
+}
+
+#[test]
+fn test_checksum_differs() -> Result<(), Error> {
+    let macos = ByteView::open(fixture("macos/crash.dSYM/Contents/Resources/DWARF/crash"))?;
+    let linux = ByteView::open(fixture("linux/crash.debug"))?;
+
+    let macos_buffer = write_symcache(&Object::parse(&macos)?)?;
+    let linux_buffer = write_symcache(&Object::parse(&linux)?)?;
+
+    let macos_checksum = SymCache::parse(&macos_buffer)?.checksum();
+    let linux_checksum = SymCache::parse(&linux_buffer)?.checksum();
+    assert_ne!(macos_checksum, linux_checksum);
+
+    Ok(())
+}
+
+#[test]
+fn test_provenance() -> Result<(), Error> {
+    let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash\n\
+        FILE 0 /src/main.c\n\
+        FUNC 1000 20 0 with_lines\n\
+        1000 20 7 0\n\
+        FUNC 1020 10 0 without_lines\n\
+        PUBLIC 1040 0 public_only\n";
+    let object = Object::parse(data)?;
+
+    let mut buffer = Vec::new();
+    SymCacheWriter::write_object(&object, Cursor::new(&mut buffer))?;
+    let symcache = SymCache::parse(&buffer)?;
+
+    let provenance = |addr| -> Result<_, Error> {
+        let lines = symcache.lookup(addr)?.collect::<Vec<_>>()?;
+        Ok(lines
+            .iter()
+            .map(|line| line.provenance())
+            .collect::<Vec<_>>())
+    };
+
+    assert_eq!(provenance(0x1010)?, [Provenance::LineInfo]);
+    assert_eq!(provenance(0x1028)?, [Provenance::Padding]);
+    assert_eq!(provenance(0x1048)?, [Provenance::Symbol]);
+
+    Ok(())
+}
+
+#[test]
+fn test_lookup_with_fallback() -> Result<(), Error> {
+    let data = b"MODULE Linux x86_64 492E2DD23CC306CA9C494EEF1533A3810 crash\n\
+        FILE 0 /src/main.c\n\
+        FUNC 1000 10 0 first\n\
+        1000 10 7 0\n\
+        FUNC 1040 10 0 second\n\
+        1040 10 12 0\n";
+    let object = Object::parse(data)?;
+
+    let mut buffer = Vec::new();
+    SymCacheWriter::write_object(&object, Cursor::new(&mut buffer))?;
+    let symcache = SymCache::parse(&buffer)?;
+
+    let lines = symcache.lookup_with_fallback(0x1028)?.collect::<Vec<_>>()?;
+    assert_eq!(lines[0].symbol(), "first");
+    assert!(!lines[0].is_fallback());
+
+    assert!(symcache.lookup(0x1058)?.collect::<Vec<_>>()?.is_empty());
+
+    let lines = symcache.lookup_with_fallback(0x1058)?.collect::<Vec<_>>()?;
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0].symbol(), "second");
+    assert_eq!(lines[0].line(), 12);
+    assert!(lines[0].is_fallback());
+    assert_eq!(lines[0].fallback_distance(), Some(0x8));
+
+    assert!(symcache
+        .lookup_with_fallback(0x800)?
+        .collect::<Vec<_>>()?
+        .is_empty());
+
+    Ok(())
+}
+
+/// Creates a top-level function without line records.
+fn function(name: &'static str, address: u64, size: u64) -> Function<'static> {
+    Function {
+        address,
+        size,
+        ranges: Vec::new(),
+        name: Name::new(name, NameMangling::Unmangled, Language::C),
+        compilation_dir: b"/src"[..].into(),
+        lines: LineRecords::new(),
+        inlinees: Vec::new(),
+        inline: false,
+        artificial: false,
+        noreturn: false,
+        calling_convention: None,
+        call_sites: Vec::new(),
+        entity: None,
+    }
+}
+
+#[test]
+fn test_split_function() -> Result<(), Error> {
+    let mut function = function("main", 0x1000, 0x18);
+    function.ranges = vec![0x1000..0x1010, 0x3000..0x3008];
+
+    let mut writer = SymCacheWriter::new(Cursor::new(Vec::new()))?;
+    writer.add_function(function)?;
+    let buffer = writer.finish()?.into_inner();
+    let symcache = SymCache::parse(&buffer)?;
+
+    for address in [0x1000, 0x3000, 0x3007] {
+        let lines = symcache.lookup(address)?.collect::<Vec<_>>()?;
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].symbol(), "main");
+    }
+
+    // The function ends with its cold part rather than after the sum of all sizes.
+    assert!(symcache.lookup(0x3008)?.collect::<Vec<_>>()?.is_empty());
+
+    Ok(())
+}
+
+fn outlining_symcache(collapse: bool) -> Result<Vec<u8>, Error> {
+    let call = |return_address, target| CallSite {
+        return_address,
+        target: Some(target),
+        tail_call: false,
+    };
+
+    let line = |address, size, line| LineInfo {
+        address,
+        size: Some(size),
+        file: FileInfo {
+            name: b"main.c"[..].into(),
+            ..Default::default()
+        },
+        line,
+    };
+
+    let mut main = function("main", 0x1000, 0x20);
+    for (address, number) in [(0x1000, 10), (0x1008, 11), (0x1010, 12)] {
+        main.lines.push(line(address, 0x8, number));
+    }
+    main.call_sites = vec![
+        call(0x1004, 0x2010),
+        call(0x100c, 0x2000),
+        call(0x1018, 0x2010),
+    ];
+
+    let mut writer = SymCacheWriter::new(Cursor::new(Vec::new()))?;
+    writer.set_collapse_outlined_functions(collapse);
+    writer.add_function(main)?;
+    writer.add_function(function("OUTLINED_FUNCTION_0", 0x2000, 0x8))?;
+    let mut outlined = function("OUTLINED_FUNCTION_1", 0x2010, 0x8);
+    outlined.lines.push(line(0x2010, 0x4, 10));
+    outlined.lines.push(line(0x2014, 0x4, 12));
+    writer.add_function(outlined)?;
+    Ok(writer.finish()?.into_inner())
+}
+
+#[test]
+fn test_collapse_outlined_functions() -> Result<(), Error> {
+    let buffer = outlining_symcache(true)?;
+    let symcache = SymCache::parse(&buffer)?;
+
+    // Called from a single location, which is looked up instead.
+    let lines = symcache.lookup(0x2004)?.collect::<Vec<_>>()?;
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0].symbol(), "main");
+    assert_eq!(lines[0].line(), 11);
+    assert_eq!(lines[0].provenance(), Provenance::Outlined);
+
+    // Called from multiple locations, which cannot be told apart. The line records of the
+    // outlined function belong to any of the callers and are dropped.
+    for address in [0x2010, 0x2014] {
+        let lines = symcache.lookup(address)?.collect::<Vec<_>>()?;
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].symbol(), "OUTLINED_FUNCTION_1");
+        assert_eq!(lines[0].line(), 0);
+        assert_eq!(lines[0].filename(), "");
+        assert_eq!(lines[0].provenance(), Provenance::Outlined);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_collapse_outlined_functions_object() -> Result<(), Error> {
+    let data = ByteView::open(fixture("macos/outlined/outlined.o"))?;
+    let object = Object::parse(&data)?;
+
+    let mut buffer = Vec::new();
+    let mut writer = SymCacheWriter::new(Cursor::new(&mut buffer))?;
+    writer.set_collapse_outlined_functions(true);
+    writer.process_object(&object)?;
+    writer.finish()?;
+    let symcache = SymCache::parse(&buffer)?;
+
+    // Addresses in object files are file offsets, and `__text` starts at 0x3b8. The outlined
+    // function at 0x3e8 is called by `first` and `second`.
+    for address in [0x3e8, 0x414] {
+        let lines = symcache.lookup(address)?.collect::<Vec<_>>()?;
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].symbol(), "_OUTLINED_FUNCTION_0");
+        assert_eq!(lines[0].line(), 0);
+        assert_eq!(lines[0].provenance(), Provenance::Outlined);
+    }
+
+    let lines = symcache.lookup(0x3d8)?.collect::<Vec<_>>()?;
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0].symbol(), "second");
+    assert_eq!(lines[0].provenance(), Provenance::LineInfo);
+
+    Ok(())
+}
+
+#[test]
+fn test_collapse_outlined_functions_disabled() -> Result<(), Error> {
+    let buffer = outlining_symcache(false)?;
+    let symcache = SymCache::parse(&buffer)?;
+
+    let lines = symcache.lookup(0x2004)?.collect::<Vec<_>>()?;
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0].symbol(), "OUTLINED_FUNCTION_0");
+
+    let lines = symcache.lookup(0x2014)?.collect::<Vec<_>>()?;
+    assert_eq!(lines[0].symbol(), "OUTLINED_FUNCTION_1");
+    assert_eq!(lines[0].line(), 12);
+
+    Ok(())
+}
+
+#[test]
+fn test_pipelined_output() -> Result<(), Error> {
+    let data = ByteView::open(fixture("linux/crash.debug"))?;
+    let object = Object::parse(&data)?;
+
+    let expected = write_symcache(&object)?;
+
+    for capacity in [0, 1, 64] {
+        let mut buffer = Vec::new();
+        let mut writer = SymCacheWriter::new(Cursor::new(&mut buffer))?;
+        writer.set_record_debug_entities(true);
+        writer.set_function_name_index(true);
+        writer.process_object_pipelined(&object, capacity)?;
+        writer.finish()?;
+
+        assert!(
+            buffer == expected,
+            "pipelined output differs at capacity {}",
+            capacity
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_pipelined_cancellation() -> Result<(), Error> {
+    let data = ByteView::open(fixture("macos/crash.dSYM/Contents/Resources/DWARF/crash"))?;
+    let object = Object::parse(&data)?;
+
+    let token = CancellationToken::new();
+    let handle = token.clone();
+    let processed = Rc::new(RefCell::new(0));
+    let observed = processed.clone();
+
+    let mut buffer = Vec::new();
+    let mut writer = SymCacheWriter::new(Cursor::new(&mut buffer))?;
+    writer.set_cancellation_token(Some(token));
+    writer.set_progress_observer(move |progress: Progress| {
+        *observed.borrow_mut() = progress.processed;
+        if progress.processed == 10 {
+            handle.cancel();
+        }
+    });
+
+    let error = writer.process_object_pipelined(&object, 4).unwrap_err();
+    assert_eq!(error.kind(), SymCacheErrorKind::Cancelled);
+    assert_eq!(*processed.borrow(), 10);
+
+    Ok(())
+}
+
 /// Tests that the cache is lenient toward adding additional flags at the end.
 #[test]
 fn test_trailing_marker() -> Result<(), Error> {
@@ -254,7 +888,7 @@ fn test_mapless_usym() -> Result<(), Error> {
 
     insta::assert_debug_snapshot!(cache, @r###"
     SymCache {
-        version: 7,
+        version: 8,
         debug_id: DebugId {
             uuid: "153d10d1-0db0-33d6-aacd-a4e1948da97b",
             appendix: 0,
@@ -287,7 +921,7 @@ fn test_usym() -> Result<(), Error> {
 
     insta::assert_debug_snapshot!(cache, @r###"
     SymCache {
-        version: 7,
+        version: 8,
         debug_id: DebugId {
             uuid: "153d10d1-0db0-33d6-aacd-a4e1948da97b",
             appendix: 0,
@@ -321,3 +955,40 @@ fn test_write_functions_usym() -> Result<(), Error> {
 
     Ok(())
 }
+
+#[test]
+fn test_max_inline_depth() -> Result<(), Error> {
+    let data = ByteView::open(fixture("linux/crash.debug"))?;
+    let object = Object::parse(&data)?;
+
+    let mut buffer = Vec::new();
+    let mut writer = SymCacheWriter::new(Cursor::new(&mut buffer))?;
+    writer.set_max_inline_depth(Some(2));
+    writer.process_object(&object)?;
+    writer.finish()?;
+
+    let symcache = SymCache::parse(&buffer)?;
+    let symbols = symcache.lookup(0x1cc3)?.collect::<Vec<_>>()?;
+    let names: Vec<_> = symbols
+        .iter()
+        .map(|s| s.function_name().to_string())
+        .collect();
+
+    assert_eq!(names.len(), 4);
+    assert_eq!(names[3], "main");
+    assert_eq!(
+        names[1],
+        "_ZNSt7__cxx1112basic_stringIcSt11char_traitsIcESaIcEEC4ERKS4_"
+    );
+    assert!(names[0].starts_with("… ") && names[0].ends_with(" frames inlined"));
+
+    // Lookups outside of deeply inlined code are not affected.
+    let full_buffer = write_symcache(&object)?;
+    let full = SymCache::parse(&full_buffer)?
+        .lookup(0x1a6f)?
+        .collect::<Vec<_>>()?;
+    let limited = symcache.lookup(0x1a6f)?.collect::<Vec<_>>()?;
+    assert_eq!(full.len(), limited.len());
+
+    Ok(())
+}
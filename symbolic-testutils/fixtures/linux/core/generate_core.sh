@@ -0,0 +1,33 @@
+#!/bin/bash
+
+# This script was used to generate the fixtures in this directory, used to test
+# `MinidumpWriter::from_core`.
+
+# Pre-requisites:
+#
+# - gcc (x86_64)
+# - a kernel `core_pattern` that writes a plain `core` file to the working directory
+
+set -e
+cd "$(dirname "$0")"
+
+# 1. compile a program that crashes with SIGSEGV in `main`.
+gcc -x c -O0 -o crash - << EOF
+#include <signal.h>
+
+int main(void) {
+    raise(SIGSEGV);
+    return 0;
+}
+EOF
+
+# 2. run it with core dumps enabled. To keep the core small, only dump anonymous private memory
+# (including the stack) and the ELF headers of mapped files, which contain the build ids.
+rm -f core crash.core
+(
+    ulimit -c unlimited
+    echo 0x11 > /proc/self/coredump_filter
+    ./crash
+) || true
+
+mv core crash.core
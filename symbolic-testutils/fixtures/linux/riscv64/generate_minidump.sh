@@ -0,0 +1,97 @@
+#!/bin/bash
+
+# This script was used to generate the minidump in this directory, used to test processing of
+# RISC-V contexts in `ProcessState::from_minidump`.
+
+# Pre-requisites:
+#
+# - python3
+#
+# There is no RISC-V crash reporter to produce this minidump, so it is written from scratch. The
+# layouts follow Breakpad's `minidump_format.h` and `minidump_cpu_riscv64.h`. The minidump
+# contains a system info stream, a single thread with a RISC-V 64 context and its stack memory.
+# The stack contains a frame record at `s0`, whose return address points back into the code at
+# `pc`.
+
+set -e
+cd "$(dirname "$0")"
+
+python3 - << EOF
+import struct
+
+STACK_BASE = 0x3f_ffff_f000
+STACK_SIZE = 0x100
+PC = 0x1_0000_1234
+SP = STACK_BASE + 0x40
+S0 = STACK_BASE + 0x80
+RA = 0x1_0000_1100
+
+out = bytearray()
+
+def rva():
+    return len(out)
+
+def align(n):
+    out.extend(b"\0" * (-len(out) % n))
+
+# MDRawHeader with three streams, followed by the stream directory.
+out += struct.pack("<IIIIIIQ", 0x504D444D, 0xA793, 3, 32, 0, 1700000000, 0)
+directory = rva()
+out += b"\0" * 12 * 3
+
+# MDString with the CSD version.
+csd = "Linux 6.1.0 #1 SMP riscv64".encode("utf-16-le")
+csd_rva = rva()
+out += struct.pack("<I", len(csd)) + csd + b"\0\0"
+align(8)
+
+# MDRawSystemInfo: MD_CPU_ARCHITECTURE_RISCV64 (0x8006) on Linux (0x8201).
+system_info = rva()
+out += struct.pack("<HHHBBIIIIIHH", 0x8006, 0, 0, 1, 0, 6, 1, 0, 0x8201, csd_rva, 0, 0)
+out += b"\0" * 24
+system_info_size = rva() - system_info
+
+# Stack memory with a frame record below the frame pointer: the saved return address at s0 - 8
+# and the caller's frame pointer at s0 - 16.
+align(16)
+stack = bytearray(STACK_SIZE)
+struct.pack_into("<QQ", stack, S0 - 16 - STACK_BASE, 0, RA)
+stack_rva = rva()
+out += stack
+
+# MDRawContextRISCV64 with MD_CONTEXT_RISCV64_INTEGER | MD_CONTEXT_RISCV64_FLOATING_POINT. The
+# 31 general purpose registers x1-x31 follow the pc, then 32 floating point registers and fcsr.
+context = rva()
+gpr = [0] * 31
+gpr[0] = RA  # ra
+gpr[1] = SP  # sp
+gpr[7] = S0  # s0
+gpr[9] = 0x2a  # a0
+out += struct.pack("<IIQ", 0x08000001 | 0x08000004, 1, PC)
+out += struct.pack("<31Q", *gpr)
+out += struct.pack("<32QI", *([0] * 32), 0)
+align(8)
+context_size = rva() - context
+
+# MDRawThreadList with a single thread.
+thread_list = rva()
+out += struct.pack("<I", 1)
+out += struct.pack("<IIIIQQIIII", 4242, 0, 0, 0, 0, STACK_BASE, STACK_SIZE, stack_rva, context_size, context)
+thread_list_size = rva() - thread_list
+
+# MDRawMemoryList with the stack.
+memory_list = rva()
+out += struct.pack("<IQII", 1, STACK_BASE, STACK_SIZE, stack_rva)
+memory_list_size = rva() - memory_list
+
+streams = [
+    (3, thread_list_size, thread_list),
+    (5, memory_list_size, memory_list),
+    (7, system_info_size, system_info),
+]
+for index, stream in enumerate(streams):
+    struct.pack_into("<III", out, directory + index * 12, *stream)
+
+with open("mini.dmp", "wb") as f:
+    f.write(out)
+EOF
@@ -0,0 +1,30 @@
+#!/bin/bash
+
+# This script was used to generate `outlined.o` in this directory, used to test attributing
+# functions created by the machine outliner to their callers.
+
+# Pre-requisites:
+#
+# - llc (LLVM 14) with the AArch64 target
+#
+# `outlined.ll` is written by hand and corresponds to the following C code, compiled for arm64
+# Apple platforms at `-Oz`, where clang enables the machine outliner with `-moutline`:
+#
+#     int sink(int);
+#
+#     int first(int x) {
+#         return sink(((((x * 13) ^ 85) * 7 + 1234) ^ 4321) * 11) + 1;
+#     }
+#
+#     int second(int x) {
+#         return sink(((((x * 13) ^ 85) * 7 + 1234) ^ 4321) * 11) + 2;
+#     }
+#
+# The outliner moves the shared computation and the tail call to `sink` into
+# `OUTLINED_FUNCTION_0`, which is called from both functions. Both calls are described by
+# `DW_TAG_call_site` entries. The output is an object file, since there is no Mach-O linker.
+
+set -e
+cd "$(dirname "$0")"
+
+llc -O2 -enable-machine-outliner -filetype=obj -o outlined.o outlined.ll
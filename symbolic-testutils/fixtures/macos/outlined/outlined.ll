@@ -0,0 +1,47 @@
+target datalayout = "e-m:o-i64:64-i128:128-n32:64-S128"
+target triple = "arm64-apple-macosx12.0.0"
+
+declare i32 @sink(i32)
+
+define i32 @first(i32 %x) #0 !dbg !10 {
+  %a = mul i32 %x, 13, !dbg !20
+  %b = xor i32 %a, 85, !dbg !20
+  %c = mul i32 %b, 7, !dbg !20
+  %d = add i32 %c, 1234, !dbg !20
+  %e = xor i32 %d, 4321, !dbg !20
+  %f = mul i32 %e, 11, !dbg !20
+  %r = call i32 @sink(i32 %f), !dbg !21
+  %s = add i32 %r, 1, !dbg !22
+  ret i32 %s, !dbg !22
+}
+
+define i32 @second(i32 %x) #0 !dbg !11 {
+  %a = mul i32 %x, 13, !dbg !30
+  %b = xor i32 %a, 85, !dbg !30
+  %c = mul i32 %b, 7, !dbg !30
+  %d = add i32 %c, 1234, !dbg !30
+  %e = xor i32 %d, 4321, !dbg !30
+  %f = mul i32 %e, 11, !dbg !30
+  %r = call i32 @sink(i32 %f), !dbg !31
+  %s = add i32 %r, 2, !dbg !32
+  ret i32 %s, !dbg !32
+}
+
+attributes #0 = { minsize nounwind optsize "frame-pointer"="non-leaf" }
+
+!llvm.dbg.cu = !{!0}
+!llvm.module.flags = !{!2, !3}
+
+!0 = distinct !DICompileUnit(language: DW_LANG_C99, file: !1, producer: "handwritten", isOptimized: true, emissionKind: FullDebug)
+!1 = !DIFile(filename: "outlined.c", directory: "/tmp")
+!2 = !{i32 7, !"Dwarf Version", i32 4}
+!3 = !{i32 2, !"Debug Info Version", i32 3}
+!4 = !DISubroutineType(types: !{})
+!10 = distinct !DISubprogram(name: "first", scope: !1, file: !1, line: 3, type: !4, scopeLine: 3, flags: DIFlagPrototyped | DIFlagAllCallsDescribed, spFlags: DISPFlagDefinition | DISPFlagOptimized, unit: !0)
+!11 = distinct !DISubprogram(name: "second", scope: !1, file: !1, line: 8, type: !4, scopeLine: 8, flags: DIFlagPrototyped | DIFlagAllCallsDescribed, spFlags: DISPFlagDefinition | DISPFlagOptimized, unit: !0)
+!20 = !DILocation(line: 4, column: 5, scope: !10)
+!21 = !DILocation(line: 5, column: 5, scope: !10)
+!22 = !DILocation(line: 6, column: 5, scope: !10)
+!30 = !DILocation(line: 9, column: 5, scope: !11)
+!31 = !DILocation(line: 10, column: 5, scope: !11)
+!32 = !DILocation(line: 11, column: 5, scope: !11)
@@ -0,0 +1,49 @@
+#!/bin/bash
+
+# This script was used to generate `mingw.exe` in this directory, used to test DWARF debug
+# information in PE files.
+
+# Pre-requisites:
+#
+# - gcc (x86_64)
+# - objcopy with PE support (binutils)
+#
+# GCC-based Windows toolchains such as MinGW keep DWARF in `.debug_*` sections of the PE file.
+# The fixture emulates this by converting an ELF executable with objcopy, which preserves the
+# debug sections. With a MinGW toolchain, an equivalent file can be built directly with:
+#
+#     x86_64-w64-mingw32-gcc -g -O0 -nostdlib -fno-asynchronous-unwind-tables -o mingw.exe main.c
+#
+# The checked-in fixture was built with GCC 12.2.0 and binutils 2.40.
+
+set -e
+cd "$(dirname "$0")"
+
+# The compilation directory is recorded in the DWARF, build in the same place for identical output.
+tmp=/tmp/mingw
+rm -rf "$tmp"
+mkdir -p "$tmp"
+trap 'rm -rf "$tmp"' EXIT
+
+# 1. write the source file. `add` is at the start of the text section (address 0x1000 in the PE)
+# and starts on line 1 of `main.c`.
+cat > "$tmp/main.c" << EOF
+static int add(int a, int b) {
+    return a + b;
+}
+
+int _start(void) {
+    return add(1, 2);
+}
+EOF
+
+# 2. compile a freestanding ELF executable with DWARF but without a build id or unwind tables.
+(
+    cd "$tmp"
+    gcc -g -O0 -nostdlib -static -fno-asynchronous-unwind-tables -Wl,--build-id=none \
+        -o main.elf main.c
+)
+
+# 3. convert it to a PE executable, keeping the DWARF sections. objcopy warns that the
+# non-loaded debug sections are below the image base, which is expected.
+objcopy -O pei-x86-64 --image-base 0x400000 --subsystem console "$tmp/main.elf" mingw.exe
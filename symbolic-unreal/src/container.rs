@@ -1,10 +1,13 @@
 //! API to process Unreal Engine 4 crashes.
 #![warn(missing_docs)]
 
+use std::convert::TryFrom;
 use std::fmt;
-use std::io::Read;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Read, Write};
 use std::iter::FusedIterator;
 use std::ops::Deref;
+use std::path::{Path, PathBuf};
 
 use bytes::Bytes;
 use flate2::bufread::ZlibDecoder;
@@ -127,6 +130,159 @@ fn gread_files(
     Ok(files)
 }
 
+/// The maximum length of directory and file names when extracting a crash with
+/// [`Unreal4Crash::extract_to`].
+const MAX_NAME_LEN: usize = 1024;
+
+/// The maximum encoded size of a [`Unreal4Header`] with names up to [`MAX_NAME_LEN`].
+const MAX_HEADER_SIZE: usize = 2 * (4 + MAX_NAME_LEN) + 8;
+
+/// The number of bytes decompressed at once when extracting a crash.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Size limits for [`Unreal4Crash::extract_to`].
+///
+/// By default, no limits are applied.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Unreal4Limits {
+    /// The maximum size of a single file in bytes.
+    pub max_file_size: usize,
+    /// The maximum size of all files in bytes.
+    pub max_total_size: usize,
+    /// The maximum number of files.
+    pub max_file_count: usize,
+}
+
+impl Default for Unreal4Limits {
+    fn default() -> Self {
+        Self {
+            max_file_size: usize::MAX,
+            max_total_size: usize::MAX,
+            max_file_count: usize::MAX,
+        }
+    }
+}
+
+/// Reads a UE4 crash file progressively from a decompressing reader.
+///
+/// Only a small window of the decompressed data is kept in memory at any time.
+struct Unreal4StreamReader<R> {
+    inner: R,
+    buf: Vec<u8>,
+    pos: usize,
+    eof: bool,
+}
+
+impl<R: Read> Unreal4StreamReader<R> {
+    fn new(inner: R) -> Self {
+        Self {
+            inner,
+            buf: Vec::new(),
+            pos: 0,
+            eof: false,
+        }
+    }
+
+    /// Returns the buffered data that has not been consumed yet.
+    fn buffered(&self) -> &[u8] {
+        &self.buf[self.pos..]
+    }
+
+    /// Buffers at least `len` bytes, unless the end of the data is reached first.
+    fn fill(&mut self, len: usize) -> Result<(), Unreal4Error> {
+        if self.buffered().len() >= len || self.eof {
+            return Ok(());
+        }
+
+        self.buf.drain(..self.pos);
+        self.pos = 0;
+
+        while self.buf.len() < len && !self.eof {
+            let start = self.buf.len();
+            self.buf.resize(start + CHUNK_SIZE, 0);
+
+            match self.inner.read(&mut self.buf[start..]) {
+                Ok(read) => {
+                    self.buf.truncate(start + read);
+                    self.eof = read == 0;
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => self.buf.truncate(start),
+                Err(e) => return Err(Unreal4Error::new(Unreal4ErrorKind::BadCompression, e)),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads a value that occupies at most `max_size` bytes.
+    fn read<'a, T>(&'a mut self, max_size: usize) -> Result<T, Unreal4Error>
+    where
+        T: TryFromCtx<'a, Endian, Error = scroll::Error>,
+    {
+        self.fill(max_size)?;
+        let mut offset = 0;
+        let value = self.buf[self.pos..].gread_with(&mut offset, scroll::LE)?;
+        self.pos += offset;
+        Ok(value)
+    }
+
+    /// Copies the next `len` bytes into the given writer.
+    fn copy_to<W: Write>(&mut self, mut len: usize, writer: &mut W) -> Result<(), Unreal4Error> {
+        while len > 0 {
+            self.fill(1)?;
+
+            let chunk = &self.buf[self.pos..];
+            if chunk.is_empty() {
+                return Err(Unreal4ErrorKind::BadData.into());
+            }
+
+            let chunk = &chunk[..chunk.len().min(len)];
+            writer
+                .write_all(chunk)
+                .map_err(|e| Unreal4Error::new(Unreal4ErrorKind::Io, e))?;
+
+            self.pos += chunk.len();
+            len -= chunk.len();
+        }
+
+        Ok(())
+    }
+
+    /// Checks whether the remaining data is exactly the trailing header of the legacy format.
+    ///
+    /// The legacy format does not declare the number of files up front, so the only way to detect
+    /// the end of the file list is to look for a final header with a matching file count.
+    fn at_trailer(&mut self, file_count: usize) -> Result<bool, Unreal4Error> {
+        self.fill(MAX_HEADER_SIZE + 1)?;
+
+        let buffered = self.buffered();
+        if !self.eof || buffered.len() > MAX_HEADER_SIZE {
+            return Ok(false);
+        }
+
+        let mut offset = 0;
+        Ok(
+            match buffered.gread_with::<Unreal4Header>(&mut offset, scroll::LE) {
+                Ok(header) => offset == buffered.len() && header.file_count as usize == file_count,
+                Err(_) => false,
+            },
+        )
+    }
+}
+
+/// Checks that a file name from a crash can be safely used as a file name on disk.
+fn validate_file_name(name: &str) -> Result<&str, Unreal4Error> {
+    let invalid =
+        name.is_empty() || name == "." || name == ".." || name.contains(['/', '\\', ':', '\0']);
+
+    if invalid {
+        let message = format!("invalid file name {:?}", name);
+        return Err(Unreal4Error::new(Unreal4ErrorKind::BadData, message));
+    }
+
+    Ok(name)
+}
+
 /// Unreal Engine 4 crash file.
 #[derive(Debug)]
 pub struct Unreal4Crash {
@@ -210,6 +366,95 @@ impl Unreal4Crash {
         Self::from_bytes(decompressed.into())
     }
 
+    /// Extracts the files of a UE4 crash dump to the given directory, streaming them from the
+    /// original, compressed data.
+    ///
+    /// Unlike [`parse`](Self::parse), this never holds the decompressed crash in memory. Files are
+    /// written to disk while decompressing, after their sizes have been checked against the given
+    /// `limits`. If a file or the crash as a whole exceeds the limits, this function returns `Err`
+    /// with [`Unreal4ErrorKind::TooLarge`] before writing the offending file.
+    ///
+    /// The directory is created if it does not exist. Existing files are never overwritten, and
+    /// file names containing path separators are rejected with [`Unreal4ErrorKind::BadData`]. On
+    /// error, files that have been extracted before remain in the directory.
+    ///
+    /// Returns the paths of all extracted files in the order of the crash file.
+    pub fn extract_to<P>(
+        slice: &[u8],
+        path: P,
+        limits: &Unreal4Limits,
+    ) -> Result<Vec<PathBuf>, Unreal4Error>
+    where
+        P: AsRef<Path>,
+    {
+        if slice.is_empty() {
+            return Err(Unreal4ErrorKind::Empty.into());
+        }
+
+        let path = path.as_ref();
+        let mut reader = Unreal4StreamReader::new(ZlibDecoder::new(slice));
+
+        // See `from_bytes` for the differences between the two formats. In the legacy format, the
+        // file count is only known once the trailing header has been reached.
+        reader.fill(3)?;
+        let file_count = if reader.buffered().starts_with(b"CR1") {
+            reader.pos += 3;
+            let header = reader.read::<Unreal4Header>(MAX_HEADER_SIZE)?;
+            Some(header.file_count as usize)
+        } else {
+            reader.read::<Unreal4Header>(MAX_HEADER_SIZE)?;
+            None
+        };
+
+        fs::create_dir_all(path).map_err(|e| Unreal4Error::new(Unreal4ErrorKind::Io, e))?;
+
+        let mut extracted = Vec::new();
+        let mut total_size = 0usize;
+
+        loop {
+            match file_count {
+                Some(count) if extracted.len() == count => break,
+                None if reader.at_trailer(extracted.len())? => break,
+                _ => (),
+            }
+
+            if extracted.len() >= limits.max_file_count {
+                return Err(Unreal4ErrorKind::TooLarge.into());
+            }
+
+            let _index = reader.read::<i32>(4)?;
+            let file_name = reader.read::<AnsiString>(4 + MAX_NAME_LEN)?;
+            let len = reader.read::<i32>(4)?;
+            let len = usize::try_from(len)
+                .map_err(|e| Unreal4Error::new(Unreal4ErrorKind::BadData, e))?;
+
+            total_size = total_size.saturating_add(len);
+            if len > limits.max_file_size || total_size > limits.max_total_size {
+                return Err(Unreal4ErrorKind::TooLarge.into());
+            }
+
+            let file_path = path.join(validate_file_name(&file_name)?);
+            let mut file = OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&file_path)
+                .map_err(|e| Unreal4Error::new(Unreal4ErrorKind::Io, e))?;
+
+            reader.copy_to(len, &mut file)?;
+            extracted.push(file_path);
+        }
+
+        // The legacy format ends with its trailing header, which has been verified above.
+        if file_count.is_some() {
+            reader.fill(1)?;
+            if !reader.buffered().is_empty() {
+                return Err(Unreal4ErrorKind::TrailingData.into());
+            }
+        }
+
+        Ok(extracted)
+    }
+
     /// Returns the file name of this UE4 crash.
     pub fn name(&self) -> &str {
         &self.header.file_name
@@ -263,6 +508,26 @@ impl Unreal4Crash {
         }
     }
 
+    /// Returns the GPU crash dumps referenced by the console properties of the context.
+    ///
+    /// References to files that are not contained in the crash are skipped.
+    pub fn gpu_crash_dumps(&self) -> Result<Vec<Unreal4File>, Unreal4Error> {
+        let context = match self.context()? {
+            Some(context) => context,
+            None => return Ok(Vec::new()),
+        };
+
+        let dumps = context
+            .console_properties
+            .map(|console| console.gpu_crash_dumps)
+            .unwrap_or_default();
+
+        Ok(dumps
+            .iter()
+            .filter_map(|dump| self.files().find(|file| file.name() == dump.file_name))
+            .collect())
+    }
+
     /// Get up to `limit` log entries of this crash.
     pub fn logs(&self, limit: usize) -> Result<Vec<Unreal4LogEntry>, Unreal4Error> {
         match self.file_by_type(Unreal4FileType::Log) {
@@ -303,6 +303,145 @@ impl Unreal4ContextPlatformProperties {
     }
 }
 
+/// Memory statistics reported by console platforms.
+///
+/// All values are in bytes.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde_::Serialize))]
+#[cfg_attr(feature = "serde", serde(crate = "serde_"))]
+pub struct Unreal4ContextConsoleMemoryStats {
+    /// MemoryStats.AvailablePhysical
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub available_physical: Option<u64>,
+    /// MemoryStats.UsedPhysical
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub used_physical: Option<u64>,
+    /// MemoryStats.PeakUsedPhysical
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub peak_used_physical: Option<u64>,
+    /// MemoryStats.UsedVirtual
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub used_virtual: Option<u64>,
+    /// MemoryStats.PeakUsedVirtual
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub peak_used_virtual: Option<u64>,
+    /// MemoryStats.TotalGPU
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub total_gpu: Option<u64>,
+    /// MemoryStats.UsedGPU
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub used_gpu: Option<u64>,
+}
+
+/// A reference to a GPU crash dump attached to the crash report.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde_::Serialize))]
+#[cfg_attr(feature = "serde", serde(crate = "serde_"))]
+pub struct Unreal4ContextGpuCrashDump {
+    /// The name of the dump file within the crash report.
+    pub file_name: String,
+    /// The GPU crash dump format, if specified.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub format: Option<String>,
+}
+
+/// Console specific properties emitted on PlayStation 5 and Xbox.
+///
+/// Console platforms write these into the `PlatformSpecificProperties` element of the context.
+/// Properties that are not known to this parser are collected in `custom`.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde_::Serialize))]
+#[cfg_attr(feature = "serde", serde(crate = "serde_"))]
+pub struct Unreal4ContextConsoleProperties {
+    /// Platform, for instance `PS5` or `XSX`.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub platform: Option<String>,
+    /// SystemSoftwareVersion
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub system_software_version: Option<String>,
+    /// SDKVersion
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub sdk_version: Option<String>,
+    /// IsDevKit
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub is_dev_kit: Option<bool>,
+    /// Memory statistics at the time of the crash.
+    pub memory_stats: Unreal4ContextConsoleMemoryStats,
+    /// GPUCrashDump references.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub gpu_crash_dumps: Vec<Unreal4ContextGpuCrashDump>,
+    /// Custom attributes
+    pub custom: BTreeMap<String, String>,
+}
+
+impl Unreal4ContextConsoleProperties {
+    fn from_xml(root: &Element) -> Option<Self> {
+        let list = root.find("PlatformSpecificProperties")?;
+
+        let mut rv = Unreal4ContextConsoleProperties::default();
+
+        fn get_text_or_none(elm: &Element) -> Option<String> {
+            let text = elm.text();
+            if !text.is_empty() {
+                Some(text.to_string())
+            } else {
+                None
+            }
+        }
+
+        for child in list.children() {
+            let tag = child.tag();
+
+            // We don't expect an XML with namespace here
+            if tag.ns().is_some() {
+                continue;
+            }
+
+            let stats = &mut rv.memory_stats;
+            match tag.name() {
+                "Platform" => rv.platform = get_text_or_none(child),
+                "SystemSoftwareVersion" => rv.system_software_version = get_text_or_none(child),
+                "SDKVersion" => rv.sdk_version = get_text_or_none(child),
+                "IsDevKit" => rv.is_dev_kit = child.text().parse::<bool>().ok(),
+                "MemoryStats.AvailablePhysical" => {
+                    stats.available_physical = child.text().parse::<u64>().ok()
+                }
+                "MemoryStats.UsedPhysical" => {
+                    stats.used_physical = child.text().parse::<u64>().ok()
+                }
+                "MemoryStats.PeakUsedPhysical" => {
+                    stats.peak_used_physical = child.text().parse::<u64>().ok()
+                }
+                "MemoryStats.UsedVirtual" => stats.used_virtual = child.text().parse::<u64>().ok(),
+                "MemoryStats.PeakUsedVirtual" => {
+                    stats.peak_used_virtual = child.text().parse::<u64>().ok()
+                }
+                "MemoryStats.TotalGPU" => stats.total_gpu = child.text().parse::<u64>().ok(),
+                "MemoryStats.UsedGPU" => stats.used_gpu = child.text().parse::<u64>().ok(),
+                "GPUCrashDump" => {
+                    if let Some(file_name) = get_text_or_none(child) {
+                        rv.gpu_crash_dumps.push(Unreal4ContextGpuCrashDump {
+                            file_name,
+                            format: child.get_attr("Format").map(str::to_owned),
+                        });
+                    }
+                }
+                _ => {
+                    rv.custom.insert(
+                        tag.name().to_string(),
+                        get_text_or_none(child).unwrap_or_default(),
+                    );
+                }
+            }
+        }
+
+        Some(rv)
+    }
+}
+
 /// The context data found in the context xml file.
 ///
 /// [Source](https://github.com/EpicGames/UnrealEngine/blob/b70f31f6645d764bcb55829228918a6e3b571e0b/Engine/Source/Runtime/Core/Private/GenericPlatform/GenericPlatformCrashContext.cpp)
@@ -318,6 +457,10 @@ pub struct Unreal4Context {
     #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub platform_properties: Option<Unreal4ContextPlatformProperties>,
 
+    /// Console specific properties.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub console_properties: Option<Unreal4ContextConsoleProperties>,
+
     /// Engine data.
     #[cfg_attr(
         feature = "serde",
@@ -348,6 +491,7 @@ impl Unreal4Context {
         Ok(Unreal4Context {
             runtime_properties: Unreal4ContextRuntimeProperties::from_xml(&root),
             platform_properties: Unreal4ContextPlatformProperties::from_xml(&root),
+            console_properties: Unreal4ContextConsoleProperties::from_xml(&root),
             engine_data: root
                 .find("EngineData")
                 .map_or_else(Default::default, load_data_bag),
@@ -390,6 +534,67 @@ const ROOT_WITH_GAME_AND_ENGINE_DATA: &str = r#"<?xml version="1.0" encoding="UT
 </FGenericCrashContext>
 "#;
 
+#[allow(dead_code)]
+const CONSOLE_PROPERTIES: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<FGenericCrashContext>
+    <PlatformSpecificProperties>
+        <Platform>PS5</Platform>
+        <SystemSoftwareVersion>04.50.00.05</SystemSoftwareVersion>
+        <IsDevKit>true</IsDevKit>
+        <MemoryStats.UsedPhysical>13958643712</MemoryStats.UsedPhysical>
+        <MemoryStats.PeakUsedPhysical>14495514624</MemoryStats.PeakUsedPhysical>
+        <GPUCrashDump Format="aftermath">gpu_crash_0.dmp</GPUCrashDump>
+        <GPUCrashDump>gpu_crash_1.dmp</GPUCrashDump>
+        <TitleId>PPSA01234</TitleId>
+    </PlatformSpecificProperties>
+</FGenericCrashContext>
+"#;
+
+#[test]
+fn test_get_console_properties() {
+    let context = Unreal4Context::parse(CONSOLE_PROPERTIES.as_bytes()).unwrap();
+    let console = context.console_properties.expect("console properties");
+
+    assert_eq!(console.platform.as_deref(), Some("PS5"));
+    assert_eq!(
+        console.system_software_version.as_deref(),
+        Some("04.50.00.05")
+    );
+    assert_eq!(console.sdk_version, None);
+    assert_eq!(console.is_dev_kit, Some(true));
+    assert_eq!(
+        console.memory_stats,
+        Unreal4ContextConsoleMemoryStats {
+            used_physical: Some(13_958_643_712),
+            peak_used_physical: Some(14_495_514_624),
+            ..Default::default()
+        }
+    );
+    assert_eq!(
+        console.gpu_crash_dumps,
+        vec![
+            Unreal4ContextGpuCrashDump {
+                file_name: "gpu_crash_0.dmp".into(),
+                format: Some("aftermath".into()),
+            },
+            Unreal4ContextGpuCrashDump {
+                file_name: "gpu_crash_1.dmp".into(),
+                format: None,
+            },
+        ]
+    );
+    assert_eq!(
+        console.custom.get("TitleId").map(String::as_str),
+        Some("PPSA01234")
+    );
+}
+
+#[test]
+fn test_get_console_properties_missing_element() {
+    let root = Element::from_reader(ONLY_ROOT_AND_CHILD_NODES.as_bytes()).unwrap();
+    assert!(Unreal4ContextConsoleProperties::from_xml(&root).is_none());
+}
+
 #[test]
 fn test_get_runtime_properties_missing_element() {
     let root = Element::from_reader(ONLY_ROOT_NODE.as_bytes()).unwrap();
@@ -27,6 +27,9 @@ pub enum Unreal4ErrorKind {
 
     /// Invalid XML.
     InvalidXml,
+
+    /// Failed to write extracted files to disk.
+    Io,
 }
 
 impl fmt::Display for Unreal4ErrorKind {
@@ -39,6 +42,7 @@ impl fmt::Display for Unreal4ErrorKind {
             Self::TooLarge => write!(f, "crash file contents are too large"),
             Self::InvalidLogEntry => write!(f, "invalid log entry"),
             Self::InvalidXml => write!(f, "invalid xml"),
+            Self::Io => write!(f, "failed to write extracted files"),
         }
     }
 }
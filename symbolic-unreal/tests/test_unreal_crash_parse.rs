@@ -2,12 +2,21 @@ use std::fs::File;
 use std::io::Read;
 
 use symbolic_testutils::fixture;
-use symbolic_unreal::{Unreal4Crash, Unreal4Error, Unreal4FileType};
+use symbolic_unreal::{
+    Unreal4Crash, Unreal4Error, Unreal4ErrorKind, Unreal4FileType, Unreal4Limits,
+};
 use time::format_description::well_known::Rfc3339;
 
 #[cfg(test)]
 use similar_asserts::assert_eq;
 
+fn get_unreal_crash_data() -> Vec<u8> {
+    let mut file = File::open(fixture("unreal/unreal_crash")).expect("example file opens");
+    let mut file_content = Vec::new();
+    file.read_to_end(&mut file_content).expect("fixture file");
+    file_content
+}
+
 fn get_unreal_crash() -> Result<Unreal4Crash, Unreal4Error> {
     let mut file = File::open(fixture("unreal/unreal_crash")).expect("example file opens");
     let mut file_content = Vec::new();
@@ -206,3 +215,102 @@ fn test_invalid_file_count() {
     let compressed = enc.finish().unwrap();
     assert!(Unreal4Crash::parse_with_limit(&compressed, 1024 * 1024).is_err());
 }
+
+/// Creates a compressed crash file in the `CR1` format with the given files.
+fn create_cr1_crash(files: &[(&str, &[u8])]) -> Vec<u8> {
+    use std::io::Write;
+
+    fn write_string(bytes: &mut Vec<u8>, s: &str) {
+        bytes.extend_from_slice(&(s.len() as u32 + 1).to_le_bytes());
+        bytes.extend_from_slice(s.as_bytes());
+        bytes.push(0);
+    }
+
+    let mut bytes = b"CR1".to_vec();
+    write_string(&mut bytes, "CrashReport");
+    write_string(&mut bytes, "CrashReport");
+    bytes.extend_from_slice(&0i32.to_le_bytes());
+    bytes.extend_from_slice(&(files.len() as i32).to_le_bytes());
+
+    for (index, (name, data)) in files.iter().enumerate() {
+        bytes.extend_from_slice(&(index as i32).to_le_bytes());
+        write_string(&mut bytes, name);
+        bytes.extend_from_slice(&(data.len() as i32).to_le_bytes());
+        bytes.extend_from_slice(data);
+    }
+
+    let mut enc = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+    enc.write_all(&bytes).unwrap();
+    enc.finish().unwrap()
+}
+
+#[test]
+fn test_extract_to() {
+    let ue4_crash = get_unreal_crash().expect("test crash file loads");
+    let dir = tempfile::tempdir().unwrap();
+
+    let limits = Unreal4Limits::default();
+    let paths = Unreal4Crash::extract_to(&get_unreal_crash_data(), dir.path(), &limits)
+        .expect("crash extracted");
+
+    assert_eq!(paths.len(), ue4_crash.file_count());
+    for (path, file) in paths.iter().zip(ue4_crash.files()) {
+        assert_eq!(path, &dir.path().join(file.name()));
+        assert_eq!(std::fs::read(path).unwrap(), file.data());
+    }
+}
+
+#[test]
+fn test_extract_to_cr1() {
+    let data = create_cr1_crash(&[("a.log", b"hello"), ("empty.txt", b"")]);
+    let dir = tempfile::tempdir().unwrap();
+
+    let paths = Unreal4Crash::extract_to(&data, dir.path(), &Unreal4Limits::default())
+        .expect("crash extracted");
+
+    assert_eq!(paths.len(), 2);
+    assert_eq!(std::fs::read(&paths[0]).unwrap(), b"hello");
+    assert_eq!(std::fs::read(&paths[1]).unwrap(), b"");
+}
+
+#[test]
+fn test_extract_to_limits() {
+    let data = get_unreal_crash_data();
+
+    let limits = Unreal4Limits {
+        max_file_size: 410_699,
+        ..Default::default()
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let error = Unreal4Crash::extract_to(&data, dir.path(), &limits).expect_err("too large");
+    assert_eq!(error.kind(), Unreal4ErrorKind::TooLarge);
+    assert!(!dir.path().join("UE4Minidump.dmp").exists());
+
+    let limits = Unreal4Limits {
+        max_total_size: 1024,
+        ..Default::default()
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let error = Unreal4Crash::extract_to(&data, dir.path(), &limits).expect_err("too large");
+    assert_eq!(error.kind(), Unreal4ErrorKind::TooLarge);
+
+    let limits = Unreal4Limits {
+        max_file_count: 3,
+        ..Default::default()
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let error = Unreal4Crash::extract_to(&data, dir.path(), &limits).expect_err("too many files");
+    assert_eq!(error.kind(), Unreal4ErrorKind::TooLarge);
+}
+
+#[test]
+fn test_extract_to_invalid_name() {
+    let data = create_cr1_crash(&[("../evil.txt", b"evil")]);
+    let dir = tempfile::tempdir().unwrap();
+    let target = dir.path().join("crash");
+
+    let error = Unreal4Crash::extract_to(&data, &target, &Unreal4Limits::default())
+        .expect_err("invalid file name");
+    assert_eq!(error.kind(), Unreal4ErrorKind::BadData);
+    assert!(!dir.path().join("evil.txt").exists());
+}
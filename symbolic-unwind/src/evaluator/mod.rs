@@ -32,7 +32,8 @@
 //! assignments described above. They can be be parsed with the
 //! [assignment](parsing::assignment), [assignment_complete](parsing::assignment_complete),
 //! [assignments](parsing::assignments),
-//! and [assignments_complete](parsing::assignments_complete) parsers.
+//! and [assignments_complete](parsing::assignments_complete) parsers, and evaluated with
+//! [`Evaluator::evaluate_program_string`].
 //!
 //! By contrast, Breakpad `STACK CFI` records (see [here](https://github.com/google/breakpad/blob/main/docs/symbol_files.md#stack-cfi-records)
 //! contain sequences of rules for essentially the same purpose. They can be parsed with the
@@ -211,6 +212,36 @@ impl<'memory, A: RegisterValue, E: Endianness> Evaluator<'memory, A, E> {
         Ok(computed_registers)
     }
 
+    /// Evaluates an assignment and stores the result in its variable.
+    ///
+    /// Subsequent expressions that refer to the variable evaluate to the assigned value.
+    pub fn evaluate_assignment(
+        &mut self,
+        assignment: &Assignment<A>,
+    ) -> Result<A, EvaluationError<A>> {
+        let Assignment(ref var, ref expr) = *assignment;
+        let value = self.evaluate(expr)?;
+        self.variables.insert(var.clone(), value);
+        Ok(value)
+    }
+
+    /// Parses a program string of a `STACK WIN` record and evaluates its assignments in order.
+    ///
+    /// Returns the values of all variables assigned by the program. Variables that were set
+    /// before but are not assigned by the program are not included.
+    pub fn evaluate_program_string(
+        &mut self,
+        program_string: &str,
+    ) -> Result<BTreeMap<Variable, A>, ExpressionError<A>> {
+        let mut assigned = BTreeMap::new();
+        for assignment in parsing::assignments_complete(program_string.trim())? {
+            let value = self.evaluate_assignment(&assignment)?;
+            assigned.insert(assignment.0, value);
+        }
+
+        Ok(assigned)
+    }
+
     /// Reads a string of CFI rules and adds them to the evaluator.
     pub fn add_cfi_rules_string(&mut self, rules_string: &str) -> Result<(), ParseExprError> {
         for Rule(lhs, rhs) in parsing::rules_complete(rules_string.trim())?.into_iter() {
@@ -534,4 +565,28 @@ mod test {
         let eval = Evaluator::new(LittleEndian);
         assert!(eval.evaluate(&expr).is_err());
     }
+
+    #[test]
+    fn program_string() {
+        let memory = MemoryRegion {
+            base_addr: 0x100,
+            contents: &[0x20, 0, 0, 0, 0x30, 0, 0, 0],
+        };
+
+        let mut variables = BTreeMap::new();
+        variables.insert("$ebp".parse().unwrap(), 0x100u32);
+
+        let mut eval = Evaluator::new(LittleEndian)
+            .memory(memory)
+            .variables(variables);
+        let assigned = eval
+            .evaluate_program_string("$T0 $ebp = $eip $T0 4 + ^ = $ebp $T0 ^ =")
+            .unwrap();
+
+        let get = |name: &str| assigned.get(&name.parse().unwrap()).copied();
+        assert_eq!(get("$T0"), Some(0x100));
+        assert_eq!(get("$eip"), Some(0x30));
+        assert_eq!(get("$ebp"), Some(0x20));
+        assert_eq!(get("$esp"), None);
+    }
 }
@@ -4,3 +4,4 @@ pub use base::*;
 
 mod base;
 pub mod evaluator;
+pub mod stackwalker;
@@ -0,0 +1,788 @@
+//! Stack walking on raw stack memory.
+//!
+//! The [`Stackwalker`] unwinds a thread from a [`CpuContext`] and a snapshot of its stack memory,
+//! without requiring a full minidump. This is useful for agents that only capture the registers
+//! and the stack of a crashing thread.
+//!
+//! Frames are recovered using the following strategies, in order of preference:
+//!
+//! 1. Breakpad `STACK WIN` records provided by a [`SymbolProvider`], used by 32-bit Windows
+//!    modules.
+//! 2. Breakpad `STACK CFI` rules provided by a [`SymbolProvider`].
+//! 3. The frame pointer chain, if the context declares a frame pointer register.
+//! 4. Scanning the stack for values that point into one of the loaded [`Module`]s.
+//!
+//! # Example
+//!
+//! ```
+//! use symbolic_unwind::stackwalker::{CpuContext, Module, Stackwalker, SymbolProvider};
+//! use symbolic_unwind::LittleEndian;
+//!
+//! struct NoCfi;
+//!
+//! impl SymbolProvider for NoCfi {
+//!     fn cfi_rules(&self, _module: &Module, _address: u64) -> Option<String> {
+//!         None
+//!     }
+//! }
+//!
+//! let modules = [Module::new(0x1000, 0x1000, "main")];
+//! let mut context = CpuContext::amd64();
+//! context.set_register("$rip", 0x1010u64);
+//! context.set_register("$rsp", 0x8000);
+//!
+//! // The return address into `main` is found by scanning the stack.
+//! let stack = 0x1234u64.to_le_bytes();
+//! let frames = Stackwalker::new(LittleEndian).walk(context, &stack, &modules, &NoCfi);
+//! assert_eq!(frames.len(), 2);
+//! assert_eq!(frames[1].instruction(), 0x1234);
+//! ```
+use std::collections::BTreeMap;
+use std::fmt;
+
+use num_traits::FromPrimitive;
+
+use super::base::{Endianness, MemoryRegion, RegisterValue};
+use super::evaluator::parsing::identifier_complete;
+use super::evaluator::{Constant, Evaluator, Identifier};
+
+/// The default maximum number of frames returned by [`Stackwalker::walk`].
+pub const DEFAULT_MAX_FRAMES: usize = 1024;
+
+/// The maximum number of pointer-sized words scanned when searching for a return address.
+const MAX_SCAN_WORDS: usize = 40;
+
+/// The register state of a thread or a recovered frame.
+///
+/// Registers are identified by their names in Breakpad `STACK CFI` records, such as `$rsp` on
+/// x86_64 or `sp` on ARM64. The context declares which of its registers hold the instruction
+/// pointer, stack pointer and optionally the frame pointer.
+#[derive(Clone, PartialEq, Eq)]
+pub struct CpuContext<A> {
+    instruction_pointer: String,
+    stack_pointer: String,
+    frame_pointer: Option<String>,
+    registers: BTreeMap<String, A>,
+}
+
+impl<A> CpuContext<A> {
+    /// Creates an empty context with the given instruction and stack pointer register names.
+    pub fn new(instruction_pointer: &str, stack_pointer: &str) -> Self {
+        Self {
+            instruction_pointer: instruction_pointer.to_owned(),
+            stack_pointer: stack_pointer.to_owned(),
+            frame_pointer: None,
+            registers: BTreeMap::new(),
+        }
+    }
+
+    /// Creates an empty context for x86 using the register names of Breakpad.
+    pub fn x86() -> Self {
+        Self::new("$eip", "$esp").with_frame_pointer("$ebp")
+    }
+
+    /// Creates an empty context for x86_64 using the register names of Breakpad.
+    pub fn amd64() -> Self {
+        Self::new("$rip", "$rsp").with_frame_pointer("$rbp")
+    }
+
+    /// Creates an empty context for ARM64 using the register names of Breakpad.
+    pub fn arm64() -> Self {
+        Self::new("pc", "sp").with_frame_pointer("x29")
+    }
+
+    /// Declares the frame pointer register of this context.
+    #[must_use]
+    pub fn with_frame_pointer(mut self, frame_pointer: &str) -> Self {
+        self.frame_pointer = Some(frame_pointer.to_owned());
+        self
+    }
+
+    /// Sets the value of a register.
+    pub fn set_register(&mut self, name: &str, value: A) {
+        self.registers.insert(name.to_owned(), value);
+    }
+
+    /// Iterates over all registers with known values.
+    pub fn registers(&self) -> impl Iterator<Item = (&str, &A)> {
+        self.registers
+            .iter()
+            .map(|(name, value)| (name.as_str(), value))
+    }
+
+    /// Returns an empty context with the same register names.
+    fn empty(&self) -> Self {
+        Self {
+            instruction_pointer: self.instruction_pointer.clone(),
+            stack_pointer: self.stack_pointer.clone(),
+            frame_pointer: self.frame_pointer.clone(),
+            registers: BTreeMap::new(),
+        }
+    }
+}
+
+impl<A: Copy> CpuContext<A> {
+    /// Returns the value of a register, if known.
+    pub fn register(&self, name: &str) -> Option<A> {
+        self.registers.get(name).copied()
+    }
+
+    /// Returns the value of the instruction pointer, if known.
+    pub fn instruction_pointer(&self) -> Option<A> {
+        self.register(&self.instruction_pointer)
+    }
+
+    /// Returns the value of the stack pointer, if known.
+    pub fn stack_pointer(&self) -> Option<A> {
+        self.register(&self.stack_pointer)
+    }
+
+    /// Returns the value of the frame pointer, if known.
+    pub fn frame_pointer(&self) -> Option<A> {
+        self.register(self.frame_pointer.as_deref()?)
+    }
+}
+
+impl<A: fmt::Debug> fmt::Debug for CpuContext<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.registers.iter()).finish()
+    }
+}
+
+/// A module loaded into the address space of the unwound process.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Module {
+    /// The address at which the module is loaded.
+    pub base: u64,
+    /// The size of the module in memory.
+    pub size: u64,
+    /// The name or path of the module.
+    pub name: String,
+}
+
+impl Module {
+    /// Creates a new module.
+    pub fn new(base: u64, size: u64, name: &str) -> Self {
+        Self {
+            base,
+            size,
+            name: name.to_owned(),
+        }
+    }
+
+    /// Returns true if the given address lies within this module.
+    pub fn contains(&self, address: u64) -> bool {
+        address >= self.base && address - self.base < self.size
+    }
+}
+
+/// Provides unwind information for loaded modules.
+pub trait SymbolProvider {
+    /// Returns the Breakpad `STACK CFI` rules for the given address.
+    ///
+    /// The `address` is relative to the module's base address. The returned string must contain
+    /// the rules of the `STACK CFI INIT` record covering the address, followed by the rules of
+    /// all `STACK CFI` delta records at or before the address, for instance:
+    /// `.cfa: $rsp 8 + .ra: .cfa -8 + ^ .cfa: $rsp 16 + $rbp: .cfa -16 + ^`.
+    fn cfi_rules(&self, module: &Module, address: u64) -> Option<String>;
+
+    /// Returns the Breakpad `STACK WIN` record covering the given address.
+    ///
+    /// The `address` is relative to the module's base address. These records are only used for
+    /// 32-bit Windows modules. The default implementation returns `None`.
+    fn stack_win_record(&self, module: &Module, address: u64) -> Option<StackWinRecord> {
+        let _ = (module, address);
+        None
+    }
+}
+
+/// The unwind information of a Breakpad `STACK WIN` record.
+///
+/// See the [Breakpad documentation] for a description of the fields.
+///
+/// [Breakpad documentation]: https://github.com/google/breakpad/blob/main/docs/symbol_files.md#stack-win-records
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StackWinRecord {
+    /// The number of bytes the function expects to be passed as arguments.
+    pub parameter_size: u32,
+    /// The number of bytes used by the function to save callee-saves registers.
+    pub saved_register_size: u32,
+    /// The number of bytes used to save the function's local variables.
+    pub local_size: u32,
+    /// Whether the function uses the base pointer register as a general-purpose register.
+    ///
+    /// This is only relevant for records without a program string.
+    pub allocates_base_pointer: bool,
+    /// A program for recovering the caller's register values, such as
+    /// `$T0 $ebp = $eip $T0 4 + ^ = $ebp $T0 ^ = $esp $T0 8 + =`.
+    pub program_string: Option<String>,
+}
+
+/// How a frame was recovered, ordered from least to most trustworthy.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FrameTrust {
+    /// The frame was recovered by scanning the stack for return addresses.
+    Scan,
+    /// The frame was recovered by following the frame pointer chain.
+    FramePointer,
+    /// The frame was recovered using call frame information or a `STACK WIN` record.
+    Cfi,
+    /// The frame is the initial context that was passed to the stack walker.
+    Context,
+}
+
+/// A stack frame recovered by the [`Stackwalker`].
+#[derive(Clone, Debug)]
+pub struct Frame<A> {
+    context: CpuContext<A>,
+    module: Option<usize>,
+    trust: FrameTrust,
+}
+
+impl<A: RegisterValue> Frame<A> {
+    /// The instruction address of this frame.
+    ///
+    /// For all frames except the first, this is the return address into the frame.
+    pub fn instruction(&self) -> u64 {
+        self.context.instruction_pointer().map_or(0, Into::into)
+    }
+
+    /// The registers that could be recovered for this frame.
+    pub fn context(&self) -> &CpuContext<A> {
+        &self.context
+    }
+
+    /// The index of the module containing the instruction of this frame.
+    pub fn module(&self) -> Option<usize> {
+        self.module
+    }
+
+    /// How this frame was recovered.
+    pub fn trust(&self) -> FrameTrust {
+        self.trust
+    }
+}
+
+/// Unwinds a thread from a register context and its raw stack memory.
+///
+/// See the [module level documentation](self) for more information.
+#[derive(Clone, Copy, Debug)]
+pub struct Stackwalker<E> {
+    endian: E,
+    max_frames: usize,
+}
+
+impl<E: Endianness> Stackwalker<E> {
+    /// Creates a stack walker reading stack memory with the given endianness.
+    pub fn new(endian: E) -> Self {
+        Self {
+            endian,
+            max_frames: DEFAULT_MAX_FRAMES,
+        }
+    }
+
+    /// Sets the maximum number of frames returned by [`walk`](Self::walk).
+    #[must_use]
+    pub fn max_frames(mut self, max_frames: usize) -> Self {
+        self.max_frames = max_frames;
+        self
+    }
+
+    /// Unwinds the stack starting at `context`.
+    ///
+    /// The `stack` must contain the thread's stack memory starting at the stack pointer of
+    /// `context`. The first frame is always the context itself. Unwinding stops once no strategy
+    /// yields a plausible caller frame, which requires the return address to point into one of
+    /// the `modules` and the stack pointer to grow.
+    pub fn walk<A, P>(
+        &self,
+        context: CpuContext<A>,
+        stack: &[u8],
+        modules: &[Module],
+        provider: &P,
+    ) -> Vec<Frame<A>>
+    where
+        A: RegisterValue + FromPrimitive,
+        P: SymbolProvider + ?Sized,
+    {
+        let memory = MemoryRegion {
+            base_addr: context.stack_pointer().map_or(0, Into::into),
+            contents: stack,
+        };
+
+        let mut frames = Vec::new();
+        if self.max_frames == 0 || context.instruction_pointer().is_none() {
+            return frames;
+        }
+
+        let ip = context.instruction_pointer().map_or(0, Into::into);
+        frames.push(Frame {
+            module: find_module(modules, ip),
+            context,
+            trust: FrameTrust::Context,
+        });
+
+        // The parameter size of the frame called by the callee, used by `STACK WIN` programs.
+        let mut callee_parameter_size = 0;
+
+        while frames.len() < self.max_frames {
+            let callee = &frames[frames.len() - 1];
+            let win_record = callee.module.and_then(|index| {
+                let module = &modules[index];
+                provider.stack_win_record(module, lookup_address(callee) - module.base)
+            });
+
+            let caller = win_record
+                .as_ref()
+                .and_then(|record| self.unwind_win(callee, record, callee_parameter_size, memory))
+                .or_else(|| self.unwind_cfi(callee, memory, modules, provider))
+                .or_else(|| self.unwind_frame_pointer(callee, memory))
+                .or_else(|| self.unwind_scan(callee, memory, modules));
+
+            let mut caller = match caller {
+                Some(caller) => caller,
+                None => break,
+            };
+
+            let ip = caller.instruction();
+            let sp: Option<u64> = caller.context.stack_pointer().map(Into::into);
+            let callee_sp: Option<u64> = callee.context.stack_pointer().map(Into::into);
+            if ip == 0 || sp <= callee_sp {
+                break;
+            }
+
+            caller.module = find_module(modules, ip);
+            if caller.module.is_none() {
+                break;
+            }
+
+            callee_parameter_size = win_record.map_or(0, |record| record.parameter_size);
+            frames.push(caller);
+        }
+
+        frames
+    }
+
+    /// Recovers the caller frame by evaluating the program of a `STACK WIN` record.
+    ///
+    /// Records without a program string describe frames with omitted frame pointers (FPO). For
+    /// these, the return address is expected right above the locals and saved registers.
+    fn unwind_win<A>(
+        &self,
+        callee: &Frame<A>,
+        record: &StackWinRecord,
+        callee_parameter_size: u32,
+        memory: MemoryRegion<'_>,
+    ) -> Option<Frame<A>>
+    where
+        A: RegisterValue + FromPrimitive,
+    {
+        let sp = callee.context.stack_pointer()?;
+        let ra_search_start = sp
+            .checked_add(&A::from_u32(callee_parameter_size)?)?
+            .checked_add(&A::from_u32(record.local_size)?)?
+            .checked_add(&A::from_u32(record.saved_register_size)?)?;
+
+        let mut constants = BTreeMap::new();
+        let mut constant = |name: &str, value: A| {
+            constants.insert(name.parse::<Constant>().ok()?, value);
+            Some(())
+        };
+        constant(".cbCalleeParams", A::from_u32(callee_parameter_size)?)?;
+        constant(".cbSavedRegs", A::from_u32(record.saved_register_size)?)?;
+        constant(".cbLocals", A::from_u32(record.local_size)?)?;
+        constant(".cbParams", A::from_u32(record.parameter_size)?)?;
+        constant(".raSearchStart", ra_search_start)?;
+        constant(".raSearch", ra_search_start)?;
+
+        let mut variables = BTreeMap::new();
+        for (name, value) in callee.context.registers() {
+            if let Ok(Identifier::Var(var)) = identifier_complete(name) {
+                variables.insert(var, *value);
+            }
+        }
+
+        let program = match record.program_string {
+            Some(ref program) => program.as_str(),
+            // The caller's base pointer was saved by the callee among its saved registers.
+            None if record.allocates_base_pointer => {
+                "$eip .raSearchStart ^ = $esp .raSearchStart 4 + = \
+                 $ebp $esp .cbCalleeParams + .cbSavedRegs + 8 - ^ ="
+            }
+            None => "$eip .raSearchStart ^ = $esp .raSearchStart 4 + =",
+        };
+
+        let mut evaluator = Evaluator::new(self.endian)
+            .memory(memory)
+            .constants(constants)
+            .variables(variables);
+        let assigned = evaluator.evaluate_program_string(program).ok()?;
+
+        // Registers not assigned by the program, such as the base pointer in FPO frames, are
+        // preserved across the call. Temporary variables like `$T0` are discarded.
+        let mut context = callee.context.empty();
+        for (name, value) in callee.context.registers() {
+            if name != context.instruction_pointer && name != context.stack_pointer {
+                context.set_register(name, *value);
+            }
+        }
+        for (var, value) in assigned {
+            let name = var.to_string();
+            if !name.starts_with("$T") {
+                context.set_register(&name, value);
+            }
+        }
+
+        context.instruction_pointer()?;
+        context.stack_pointer()?;
+
+        Some(Frame {
+            context,
+            module: None,
+            trust: FrameTrust::Cfi,
+        })
+    }
+
+    /// Recovers the caller frame by evaluating `STACK CFI` rules.
+    fn unwind_cfi<A, P>(
+        &self,
+        callee: &Frame<A>,
+        memory: MemoryRegion<'_>,
+        modules: &[Module],
+        provider: &P,
+    ) -> Option<Frame<A>>
+    where
+        A: RegisterValue,
+        P: SymbolProvider + ?Sized,
+    {
+        let module = &modules[callee.module?];
+        let rules = provider.cfi_rules(module, lookup_address(callee) - module.base)?;
+
+        let mut constants = BTreeMap::new();
+        let mut variables = BTreeMap::new();
+        for (name, value) in callee.context.registers() {
+            match identifier_complete(name) {
+                Ok(Identifier::Var(var)) => variables.insert(var, *value),
+                Ok(Identifier::Const(con)) => constants.insert(con, *value),
+                Err(_) => None,
+            };
+        }
+
+        let mut evaluator = Evaluator::new(self.endian)
+            .memory(memory)
+            .constants(constants)
+            .variables(variables);
+        evaluator.add_cfi_rules_string(&rules).ok()?;
+        let registers = evaluator.evaluate_cfi_rules().ok()?;
+
+        let mut context = callee.context.empty();
+        for (ident, value) in registers {
+            let name = match ident {
+                Identifier::Const(ref con) if con.is_cfa() => context.stack_pointer.clone(),
+                Identifier::Const(ref con) if con.is_ra() => context.instruction_pointer.clone(),
+                ident => ident.to_string(),
+            };
+            context.registers.insert(name, value);
+        }
+
+        Some(Frame {
+            context,
+            module: None,
+            trust: FrameTrust::Cfi,
+        })
+    }
+
+    /// Recovers the caller frame by following the frame pointer.
+    ///
+    /// This assumes the standard frame layout where the frame pointer points to the saved frame
+    /// pointer of the caller, immediately followed by the return address.
+    fn unwind_frame_pointer<A>(
+        &self,
+        callee: &Frame<A>,
+        memory: MemoryRegion<'_>,
+    ) -> Option<Frame<A>>
+    where
+        A: RegisterValue + FromPrimitive,
+    {
+        let fp = callee.context.frame_pointer()?;
+        let width = A::from_u8(A::WIDTH)?;
+
+        let caller_fp = memory.get(fp, self.endian)?;
+        let caller_ip = memory.get(fp.checked_add(&width)?, self.endian)?;
+        let caller_sp = fp.checked_add(&width)?.checked_add(&width)?;
+
+        let mut context = callee.context.empty();
+        context.set_register(&callee.context.instruction_pointer, caller_ip);
+        context.set_register(&callee.context.stack_pointer, caller_sp);
+        if let Some(ref frame_pointer) = callee.context.frame_pointer {
+            context.set_register(frame_pointer, caller_fp);
+        }
+
+        Some(Frame {
+            context,
+            module: None,
+            trust: FrameTrust::FramePointer,
+        })
+    }
+
+    /// Recovers the caller frame by scanning the stack for a plausible return address.
+    fn unwind_scan<A>(
+        &self,
+        callee: &Frame<A>,
+        memory: MemoryRegion<'_>,
+        modules: &[Module],
+    ) -> Option<Frame<A>>
+    where
+        A: RegisterValue + FromPrimitive,
+    {
+        let width = A::from_u8(A::WIDTH)?;
+
+        // The first frame's return address may be at the very top of the stack. For all other
+        // frames, the stack pointer already points past the return address into the callee.
+        let mut address = callee.context.stack_pointer()?;
+        if callee.trust != FrameTrust::Context {
+            address = address.checked_add(&width)?;
+        }
+
+        for _ in 0..MAX_SCAN_WORDS {
+            let value = memory.get(address, self.endian)?;
+            if find_module(modules, value.into()).is_some() {
+                let mut context = callee.context.empty();
+                context.set_register(&callee.context.instruction_pointer, value);
+                context.set_register(&callee.context.stack_pointer, address.checked_add(&width)?);
+
+                return Some(Frame {
+                    context,
+                    module: None,
+                    trust: FrameTrust::Scan,
+                });
+            }
+
+            address = address.checked_add(&width)?;
+        }
+
+        None
+    }
+}
+
+/// Returns the address used to look up unwind information for a frame.
+///
+/// The instruction of caller frames is a return address, which points to the instruction after
+/// the call. This might already belong to a different function, so look up the call instead.
+fn lookup_address<A: RegisterValue>(frame: &Frame<A>) -> u64 {
+    let ip = frame.instruction();
+    match frame.trust {
+        FrameTrust::Context => ip,
+        _ => ip.saturating_sub(1),
+    }
+}
+
+fn find_module(modules: &[Module], address: u64) -> Option<usize> {
+    modules.iter().position(|module| module.contains(address))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::base::LittleEndian;
+
+    /// Provides the same CFI rules for every address.
+    struct StaticCfi(&'static str);
+
+    impl SymbolProvider for StaticCfi {
+        fn cfi_rules(&self, _module: &Module, _address: u64) -> Option<String> {
+            Some(self.0.to_owned())
+        }
+    }
+
+    struct NoCfi;
+
+    impl SymbolProvider for NoCfi {
+        fn cfi_rules(&self, _module: &Module, _address: u64) -> Option<String> {
+            None
+        }
+    }
+
+    /// Provides the same `STACK WIN` record for every address.
+    struct StaticWin(StackWinRecord);
+
+    impl SymbolProvider for StaticWin {
+        fn cfi_rules(&self, _module: &Module, _address: u64) -> Option<String> {
+            None
+        }
+
+        fn stack_win_record(&self, _module: &Module, _address: u64) -> Option<StackWinRecord> {
+            Some(self.0.clone())
+        }
+    }
+
+    fn stack32(words: &[u32]) -> Vec<u8> {
+        words.iter().flat_map(|word| word.to_le_bytes()).collect()
+    }
+
+    fn stack(words: &[u64]) -> Vec<u8> {
+        words.iter().flat_map(|word| word.to_le_bytes()).collect()
+    }
+
+    fn modules() -> Vec<Module> {
+        vec![
+            Module::new(0x1000, 0x1000, "main"),
+            Module::new(0x4000, 0x1000, "lib"),
+        ]
+    }
+
+    #[test]
+    fn test_walk_cfi() {
+        let mut context = CpuContext::amd64();
+        context.set_register("$rip", 0x4010u64);
+        context.set_register("$rsp", 0x8000);
+
+        let stack = stack(&[0x1020, 0, 0x1030, 0]);
+        let provider = StaticCfi(".cfa: $rsp 16 + .ra: .cfa -16 + ^");
+        let frames = Stackwalker::new(LittleEndian).walk(context, &stack, &modules(), &provider);
+
+        let summary: Vec<_> = frames
+            .iter()
+            .map(|f| (f.instruction(), f.module(), f.trust()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (0x4010, Some(1), FrameTrust::Context),
+                (0x1020, Some(0), FrameTrust::Cfi),
+                (0x1030, Some(0), FrameTrust::Cfi),
+            ]
+        );
+        assert_eq!(frames[2].context().stack_pointer(), Some(0x8020));
+    }
+
+    #[test]
+    fn test_walk_frame_pointer() {
+        let mut context = CpuContext::amd64();
+        context.set_register("$rip", 0x4010u64);
+        context.set_register("$rsp", 0x8000);
+        context.set_register("$rbp", 0x8010);
+
+        // [rbp] = caller rbp, [rbp + 8] = return address
+        let stack = stack(&[0, 0, 0x8030, 0x1020, 0, 0, 0, 0x1030]);
+        let frames = Stackwalker::new(LittleEndian).walk(context, &stack, &modules(), &NoCfi);
+
+        let summary: Vec<_> = frames
+            .iter()
+            .map(|f| (f.instruction(), f.trust()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (0x4010, FrameTrust::Context),
+                (0x1020, FrameTrust::FramePointer),
+                (0x1030, FrameTrust::FramePointer),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_walk_scan() {
+        let mut context = CpuContext::<u32>::x86();
+        context.set_register("$eip", 0x4010);
+        context.set_register("$esp", 0x8000);
+
+        let stack: Vec<u8> = [7u32, 0x1020, 9, 0x4020]
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect();
+        let frames = Stackwalker::new(LittleEndian).walk(context, &stack, &modules(), &NoCfi);
+
+        let summary: Vec<_> = frames
+            .iter()
+            .map(|f| (f.instruction(), f.trust()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (0x4010, FrameTrust::Context),
+                (0x1020, FrameTrust::Scan),
+                (0x4020, FrameTrust::Scan),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_walk_stack_win_program() {
+        let mut context = CpuContext::<u32>::x86();
+        context.set_register("$eip", 0x4010);
+        context.set_register("$esp", 0x8000);
+        context.set_register("$ebp", 0x8008);
+
+        // [ebp] = caller ebp, [ebp + 4] = return address
+        let stack = stack32(&[0, 0, 0x8018, 0x1020, 0, 0, 0, 0x1030]);
+        let provider = StaticWin(StackWinRecord {
+            program_string: Some("$T0 $ebp = $eip $T0 4 + ^ = $ebp $T0 ^ = $esp $T0 8 + =".into()),
+            ..Default::default()
+        });
+        let frames = Stackwalker::new(LittleEndian).max_frames(3).walk(
+            context,
+            &stack,
+            &modules(),
+            &provider,
+        );
+
+        let summary: Vec<_> = frames
+            .iter()
+            .map(|f| (f.instruction(), f.trust()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (0x4010, FrameTrust::Context),
+                (0x1020, FrameTrust::Cfi),
+                (0x1030, FrameTrust::Cfi),
+            ]
+        );
+        assert_eq!(frames[1].context().stack_pointer(), Some(0x8010));
+        assert_eq!(frames[1].context().register("$ebp"), Some(0x8018));
+        assert_eq!(frames[1].context().register("$T0"), None);
+    }
+
+    #[test]
+    fn test_walk_stack_win_fpo() {
+        let mut context = CpuContext::<u32>::x86();
+        context.set_register("$eip", 0x4010);
+        context.set_register("$esp", 0x8000);
+        context.set_register("$ebp", 0x1234);
+
+        // The return address is located after 4 bytes of locals and 4 bytes of saved registers.
+        let stack = stack32(&[0, 0, 0x1020]);
+        let provider = StaticWin(StackWinRecord {
+            local_size: 4,
+            saved_register_size: 4,
+            ..Default::default()
+        });
+        let frames = Stackwalker::new(LittleEndian).max_frames(2).walk(
+            context,
+            &stack,
+            &modules(),
+            &provider,
+        );
+
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[1].instruction(), 0x1020);
+        assert_eq!(frames[1].trust(), FrameTrust::Cfi);
+        assert_eq!(frames[1].context().stack_pointer(), Some(0x800c));
+        assert_eq!(frames[1].context().register("$ebp"), Some(0x1234));
+    }
+
+    #[test]
+    fn test_walk_max_frames() {
+        let mut context = CpuContext::amd64();
+        context.set_register("$rip", 0x4010u64);
+        context.set_register("$rsp", 0x8000);
+
+        let stack = stack(&[0x1020, 0x1030]);
+        let frames =
+            Stackwalker::new(LittleEndian)
+                .max_frames(1)
+                .walk(context, &stack, &modules(), &NoCfi);
+        assert_eq!(frames.len(), 1);
+    }
+}
@@ -0,0 +1,49 @@
+//! A unified error type for all symbolic crates.
+
+use thiserror::Error;
+
+/// An error returned by any of the enabled symbolic crates.
+///
+/// This wraps the top-level error types of the individual crates, so that they can be propagated
+/// with the `?` operator from a single function. Use pattern matching or [`std::error::Error::source`]
+/// to inspect the original error.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    /// An I/O error, for instance when opening a file.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    /// An error when parsing an object file or its debug information.
+    #[cfg(feature = "debuginfo")]
+    #[error(transparent)]
+    Object(#[from] crate::debuginfo::ObjectError),
+
+    /// An error when writing or reading a SymCache.
+    #[cfg(feature = "symcache")]
+    #[error(transparent)]
+    SymCache(#[from] crate::symcache::SymCacheError),
+
+    /// An error when parsing a source map.
+    #[cfg(feature = "sourcemap")]
+    #[error(transparent)]
+    SourceMap(#[from] crate::sourcemap::ParseSourceMapError),
+
+    /// An error when processing an Unreal Engine 4 crash report.
+    #[cfg(feature = "unreal")]
+    #[error(transparent)]
+    Unreal(#[from] crate::unreal::Unreal4Error),
+
+    /// An error when extracting call frame information from an object file.
+    #[cfg(feature = "minidump")]
+    #[error(transparent)]
+    Cfi(#[from] crate::minidump::cfi::CfiError),
+
+    /// An error when processing a minidump.
+    #[cfg(feature = "minidump")]
+    #[error(transparent)]
+    ProcessMinidump(#[from] crate::minidump::processor::ProcessMinidumpError),
+}
+
+/// A result with the unified symbolic [`Error`].
+pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -59,12 +59,52 @@
 //! - **`minidump-serde`**
 //! - **`unreal-serde`**
 //!
+//! To profile conversions, the following features emit [`tracing`](https://docs.rs/tracing) spans
+//! for parsing DWARF units, writing SymCaches and processing minidumps:
+//!
+//! - **`debuginfo-tracing`**
+//! - **`minidump-tracing`**
+//! - **`symcache-tracing`**
+//!
+//! The **`minidump-writer`** feature additionally enables creation of minidumps from ELF core files
+//! and live Linux processes.
+//!
+//! The **`common-compression`** feature enables `ByteView::open_auto`, which transparently
+//! decompresses gzip-compressed files.
+//!
+//! ## Errors and Prelude
+//!
+//! Each crate defines its own error type. To propagate errors from several crates in a single
+//! function, use the unified [`Error`] type, which implements `From` for the top-level errors of all
+//! enabled crates:
+//!
+//! ```
+//! use symbolic::common::ByteView;
+//! use symbolic::debuginfo::Object;
+//!
+//! fn object_kind(path: &str) -> symbolic::Result<String> {
+//!     let view = ByteView::open(path)?;
+//!     let object = Object::parse(&view)?;
+//!     Ok(object.kind().to_string())
+//! }
+//!
+//! assert!(object_kind("does/not/exist").is_err());
+//! ```
+//!
+//! The most commonly used types and traits of all enabled crates can be imported at once from the
+//! [`prelude`].
+//!
 //! ## Minimal Rust Version
 //!
 //! This crate is known to require at least Rust 1.41.
 
 #![warn(missing_docs)]
 
+mod error;
+pub mod prelude;
+
+pub use crate::error::{Error, Result};
+
 #[doc(inline)]
 pub use symbolic_common as common;
 #[doc(inline)]
@@ -0,0 +1,28 @@
+//! Commonly used types and traits of all enabled symbolic crates.
+//!
+//! This module can be glob-imported to bring the most frequently needed items into scope:
+//!
+//! ```
+//! use symbolic::prelude::*;
+//! ```
+
+pub use crate::common::{Arch, ByteView, CodeId, DebugId, Language, Name};
+pub use crate::error::{Error, Result};
+
+#[cfg(feature = "debuginfo")]
+pub use crate::debuginfo::{Archive, DebugSession, Object, ObjectLike};
+
+#[cfg(feature = "demangle")]
+pub use crate::demangle::{Demangle, DemangleOptions};
+
+#[cfg(feature = "minidump")]
+pub use crate::minidump::{cfi::AsciiCfiWriter, processor::ProcessState};
+
+#[cfg(feature = "sourcemap")]
+pub use crate::sourcemap::{SourceMapView, SourceView};
+
+#[cfg(feature = "symcache")]
+pub use crate::symcache::{SymCache, SymCacheWriter};
+
+#[cfg(feature = "unreal")]
+pub use crate::unreal::Unreal4Crash;